@@ -102,12 +102,12 @@ class FileSelector(tk.Frame):
                 ftypes = [('All files', '*.*')]
                 if 'RasterAndVector' in self.file_type:
                     ftypes = [("Shapefiles", "*.shp"), ('Raster files', ('*.dep', '*.tif',
-                                                '*.tiff', '*.bil', '*.flt',
+                                                '*.tiff', '*.bil', '*.img', '*.flt', '*.hgt', '*.xyz', '*.nc',
                                                 '*.sdat', '*.rdc',
                                                 '*.asc', '*grd'))]
                 elif 'Raster' in self.file_type:
                     ftypes = [('Raster files', ('*.dep', '*.tif',
-                                                '*.tiff', '*.bil', '*.flt',
+                                                '*.tiff', '*.bil', '*.img', '*.flt', '*.hgt', '*.xyz', '*.nc',
                                                 '*.sdat', '*.rdc',
                                                 '*.asc', '*.grd'))]
                 elif 'Lidar' in self.file_type:
@@ -258,12 +258,12 @@ class FileOrFloat(tk.Frame):
             ftypes = [('All files', '*.*')]
             if 'RasterAndVector' in self.file_type:
                     ftypes = [("Shapefiles", "*.shp"), ('Raster files', ('*.dep', '*.tif',
-                                                '*.tiff', '*.bil', '*.flt',
+                                                '*.tiff', '*.bil', '*.img', '*.flt', '*.hgt', '*.xyz', '*.nc',
                                                 '*.sdat', '*.rdc',
                                                 '*.asc'))]
             elif 'Raster' in self.file_type:
                 ftypes = [('Raster files', ('*.dep', '*.tif',
-                                            '*.tiff', '*.bil', '*.flt',
+                                            '*.tiff', '*.bil', '*.img', '*.flt', '*.hgt', '*.xyz', '*.nc',
                                             '*.sdat', '*.rdc',
                                             '*.asc'))]
             elif 'Lidar' in self.file_type:
@@ -413,12 +413,12 @@ class MultifileSelector(tk.Frame):
             ftypes = [('All files', '*.*')]
             if 'RasterAndVector' in self.file_type:
                     ftypes = [("Shapefiles", "*.shp"), ('Raster files', ('*.dep', '*.tif',
-                                                '*.tiff', '*.bil', '*.flt',
+                                                '*.tiff', '*.bil', '*.img', '*.flt', '*.hgt', '*.xyz', '*.nc',
                                                 '*.sdat', '*.rdc',
                                                 '*.asc'))]
             elif 'Raster' in self.file_type:
                 ftypes = [('Raster files', ('*.dep', '*.tif',
-                                            '*.tiff', '*.bil', '*.flt',
+                                            '*.tiff', '*.bil', '*.img', '*.flt', '*.hgt', '*.xyz', '*.nc',
                                             '*.sdat', '*.rdc',
                                             '*.asc'))]
             elif 'Lidar' in self.file_type:
@@ -0,0 +1,375 @@
+/*
+This code is part of the WhiteboxTools geospatial analysis library.
+License: MIT
+
+Notes: Buffers are built as the union of simple pieces, rather than by tracing and then
+cleaning the self-intersecting offset curves of a line. Each segment contributes the rectangle
+that lies within the buffer distance of it; each vertex where two segments meet contributes
+the wedge that fills the gap between their rectangles on the outside of the turn, shaped by
+the join style; and the ends of an open line contribute their caps. The vertices of adjacent
+pieces coincide exactly, so the union, performed with polygon_union_all, has no slivers along
+the seams. A polygon is grown by adding the buffer of its rings to it, and shrunk by taking the
+buffer of its rings away from it. Arcs are approximated with 8 vertices per quarter circle.
+*/
+
+use super::{polygon_boolean, polygon_union_all, BooleanOperation};
+use crate::structures::Point2D;
+use std::f64::consts::PI;
+
+/// The number of segments used to approximate a quarter of a circle.
+const SEGMENTS_PER_QUADRANT: f64 = 8.0;
+
+/// The greatest ratio of the length of a mitre to the buffer distance. Sharper corners are
+/// bevelled instead, so that their mitres do not extend far beyond the buffer.
+const MITRE_LIMIT: f64 = 5.0;
+
+/// The shape of the ends of a buffered line.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LineCap {
+    /// A semicircle centred on the end.
+    Round,
+    /// A straight edge through the end, perpendicular to the line.
+    Flat,
+    /// A straight edge perpendicular to the line, the buffer distance beyond the end.
+    Square,
+}
+
+/// The shape of the corners of a buffered line or polygon, on the outside of each turn.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LineJoin {
+    /// An arc centred on the vertex.
+    Round,
+    /// A sharp corner where the offset edges meet, which is bevelled if it would extend more
+    /// than five times the buffer distance from the vertex.
+    Mitre,
+}
+
+/// Returns the area within `distance` of a set of points, as a set of polygons in the form
+/// returned by `polygon_boolean`. Points are buffered as circles, or as squares aligned with
+/// the axes with `LineCap::Square`; with `LineCap::Flat`, and for distances that are not
+/// positive, the buffer is empty.
+pub fn buffer_points(
+    points: &[Point2D],
+    distance: f64,
+    cap: LineCap,
+    snap_tolerance: f64,
+) -> Vec<Vec<Vec<Point2D>>> {
+    if !(distance > 0f64) || cap == LineCap::Flat {
+        return vec![];
+    }
+    let mut pieces = vec![];
+    for p in points {
+        point_piece(*p, distance, cap, &mut pieces);
+    }
+    polygon_union_all(&pieces, snap_tolerance)
+}
+
+/// Returns the area within `distance` of a set of lines, e.g. the parts of a Shapefile polyline
+/// record, as a set of polygons in the form returned by `polygon_boolean`. For distances that
+/// are not positive, the buffer is empty.
+pub fn buffer_polylines(
+    lines: &[Vec<Point2D>],
+    distance: f64,
+    cap: LineCap,
+    join: LineJoin,
+    snap_tolerance: f64,
+) -> Vec<Vec<Vec<Point2D>>> {
+    if !(distance > 0f64) {
+        return vec![];
+    }
+    let mut pieces = vec![];
+    for line in lines {
+        line_pieces(line, false, distance, cap, join, &mut pieces);
+    }
+    polygon_union_all(&pieces, snap_tolerance)
+}
+
+/// Returns a polygon, given as a set of rings combined with the even-odd rule, grown by
+/// `distance`, or shrunk by it if the distance is negative. The result is a set of polygons in
+/// the form returned by `polygon_boolean`, which is empty if the polygon is shrunk away.
+pub fn buffer_polygon(
+    rings: &[Vec<Point2D>],
+    distance: f64,
+    join: LineJoin,
+    snap_tolerance: f64,
+) -> Vec<Vec<Vec<Point2D>>> {
+    if distance == 0f64 || !distance.is_finite() {
+        return polygon_union_all(&[rings.to_vec()], snap_tolerance);
+    }
+    // The band within the distance of the boundary.
+    let mut pieces = vec![];
+    for ring in rings {
+        line_pieces(ring, true, distance.abs(), LineCap::Flat, join, &mut pieces);
+    }
+    if distance > 0f64 {
+        pieces.push(rings.to_vec());
+        polygon_union_all(&pieces, snap_tolerance)
+    } else {
+        let band = polygon_union_all(&pieces, snap_tolerance)
+            .into_iter()
+            .flatten()
+            .collect::<Vec<_>>();
+        polygon_boolean(rings, &band, BooleanOperation::Difference, snap_tolerance)
+    }
+}
+
+/// Adds the buffer of a single point.
+fn point_piece(p: Point2D, distance: f64, cap: LineCap, pieces: &mut Vec<Vec<Vec<Point2D>>>) {
+    let ring = if cap == LineCap::Square {
+        vec![
+            p.translate(-distance, -distance),
+            p.translate(-distance, distance),
+            p.translate(distance, distance),
+            p.translate(distance, -distance),
+        ]
+    } else {
+        let mut ring = vec![p.translate(distance, 0f64)];
+        add_arc(p, 1f64, 0f64, 2f64 * PI, distance, &mut ring);
+        ring
+    };
+    pieces.push(vec![close(ring)]);
+}
+
+/// Adds the pieces of the buffer of a line, or of a ring if `closed` is true, in which case
+/// there are joins at every vertex and no caps.
+fn line_pieces(
+    line: &[Point2D],
+    closed: bool,
+    distance: f64,
+    cap: LineCap,
+    join: LineJoin,
+    pieces: &mut Vec<Vec<Vec<Point2D>>>,
+) {
+    let mut points: Vec<Point2D> = Vec::with_capacity(line.len());
+    for p in line {
+        if p.x.is_finite() && p.y.is_finite() && points.last() != Some(p) {
+            points.push(*p);
+        }
+    }
+    if closed {
+        while points.len() > 1 && points.first() == points.last() {
+            points.pop();
+        }
+    }
+    let closed = closed && points.len() > 2;
+    let n = points.len();
+    if n == 0 {
+        return;
+    }
+    if n == 1 {
+        if cap != LineCap::Flat {
+            point_piece(points[0], distance, cap, pieces);
+        }
+        return;
+    }
+
+    // The unit direction of each segment.
+    let num_segments = if closed { n } else { n - 1 };
+    let directions = (0..num_segments)
+        .map(|i| {
+            let (a, b) = (points[i], points[(i + 1) % n]);
+            let length = a.distance(&b);
+            ((b.x - a.x) / length, (b.y - a.y) / length)
+        })
+        .collect::<Vec<_>>();
+
+    for i in 0..num_segments {
+        let (ux, uy) = directions[i];
+        let (nx, ny) = (-uy * distance, ux * distance);
+        let mut a = points[i];
+        let mut b = points[(i + 1) % n];
+        if !closed && cap == LineCap::Square {
+            if i == 0 {
+                a = a.translate(-ux * distance, -uy * distance);
+            }
+            if i == num_segments - 1 {
+                b = b.translate(ux * distance, uy * distance);
+            }
+        }
+        pieces.push(vec![close(vec![
+            a.translate(nx, ny),
+            b.translate(nx, ny),
+            b.translate(-nx, -ny),
+            a.translate(-nx, -ny),
+        ])]);
+    }
+
+    if !closed && cap == LineCap::Round {
+        // Semicircles around the back of the start and the front of the end.
+        let (ux, uy) = directions[0];
+        let p = points[0];
+        let mut ring = vec![p.translate(-uy * distance, ux * distance)];
+        add_arc(p, -uy, ux, PI, distance, &mut ring);
+        ring.push(p.translate(uy * distance, -ux * distance));
+        ring.push(p);
+        pieces.push(vec![close(ring)]);
+
+        let (ux, uy) = directions[num_segments - 1];
+        let p = points[n - 1];
+        let mut ring = vec![p.translate(uy * distance, -ux * distance)];
+        add_arc(p, uy, -ux, PI, distance, &mut ring);
+        ring.push(p.translate(-uy * distance, ux * distance));
+        ring.push(p);
+        pieces.push(vec![close(ring)]);
+    }
+
+    let joins = if closed { 0..n } else { 1..n - 1 };
+    for i in joins {
+        let (u1x, u1y) = directions[(i + num_segments - 1) % num_segments];
+        let (u2x, u2y) = directions[i % num_segments];
+        let cross = u1x * u2y - u1y * u2x;
+        let dot = u1x * u2x + u1y * u2y;
+        if cross == 0f64 && dot > 0f64 {
+            continue; // the segments are collinear, and their rectangles meet exactly.
+        }
+        // The outside of the turn is to the right of a left turn and to the left of a right
+        // turn; the gap there is swept from the offset of one segment to that of the other.
+        let side = if cross > 0f64 { -1f64 } else { 1f64 };
+        let (n1x, n1y) = (-u1y * side, u1x * side);
+        let (n2x, n2y) = (-u2y * side, u2x * side);
+        let v = points[i];
+        let p1 = v.translate(n1x * distance, n1y * distance);
+        let p2 = v.translate(n2x * distance, n2y * distance);
+        let mut ring = vec![v, p1];
+        match join {
+            LineJoin::Round => {
+                // A hairpin turn is swept around the front of the vertex.
+                let sweep = -side * cross.abs().atan2(dot);
+                add_arc(v, n1x, n1y, sweep, distance, &mut ring);
+            }
+            LineJoin::Mitre => {
+                // The mitre lies along the bisector of the offsets, at the distance divided
+                // by the cosine of half the angle between them.
+                if 1f64 + dot > 2f64 / (MITRE_LIMIT * MITRE_LIMIT) {
+                    let scale = distance / (1f64 + dot);
+                    ring.push(v.translate((n1x + n2x) * scale, (n1y + n2y) * scale));
+                }
+            }
+        }
+        ring.push(p2);
+        pieces.push(vec![close(ring)]);
+    }
+}
+
+/// Adds the interior vertices of an arc of radius `distance` about `centre`, starting in the
+/// unit direction (`x`, `y`) and sweeping anticlockwise through `sweep` radians, or clockwise
+/// if it is negative. The ends are left to the caller, so that they match the other pieces.
+fn add_arc(centre: Point2D, x: f64, y: f64, sweep: f64, distance: f64, ring: &mut Vec<Point2D>) {
+    let start = y.atan2(x);
+    let num_segments = (sweep.abs() / (PI / 2f64) * SEGMENTS_PER_QUADRANT).ceil() as usize;
+    for k in 1..num_segments {
+        let angle = start + sweep * k as f64 / num_segments as f64;
+        ring.push(centre.translate(distance * angle.cos(), distance * angle.sin()));
+    }
+}
+
+/// Closes a ring by repeating its first vertex.
+fn close(mut ring: Vec<Point2D>) -> Vec<Point2D> {
+    ring.push(ring[0]);
+    ring
+}
+
+#[cfg(test)]
+mod test {
+    use super::{buffer_points, buffer_polygon, buffer_polylines, LineCap, LineJoin};
+    use crate::structures::Point2D;
+    use std::f64::consts::PI;
+
+    /// The area of a result, with holes subtracted.
+    fn area(polygons: &[Vec<Vec<Point2D>>]) -> f64 {
+        let mut ret = 0f64;
+        for ring in polygons.iter().flatten() {
+            for i in 0..ring.len() - 1 {
+                ret -= ring[i].x * ring[i + 1].y - ring[i + 1].x * ring[i].y;
+            }
+        }
+        ret / 2f64
+    }
+
+    /// The area of a circle approximated with 32 segments.
+    fn circle_area(radius: f64) -> f64 {
+        16f64 * radius * radius * (PI / 16f64).sin()
+    }
+
+    fn square(x: f64, y: f64, size: f64) -> Vec<Point2D> {
+        vec![
+            Point2D::new(x, y),
+            Point2D::new(x, y + size),
+            Point2D::new(x + size, y + size),
+            Point2D::new(x + size, y),
+            Point2D::new(x, y),
+        ]
+    }
+
+    #[test]
+    fn test_points() {
+        let points = vec![Point2D::new(0.0, 0.0), Point2D::new(10.0, 0.0)];
+        let circles = buffer_points(&points, 2.0, LineCap::Round, 0.0);
+        assert_eq!(circles.len(), 2);
+        assert!((area(&circles) - 2.0 * circle_area(2.0)).abs() < 1e-9);
+        // Overlapping buffers are merged.
+        let squares = buffer_points(&points, 6.0, LineCap::Square, 0.0);
+        assert_eq!(squares.len(), 1);
+        assert!((area(&squares) - 22.0 * 12.0).abs() < 1e-9);
+        assert!(buffer_points(&points, 2.0, LineCap::Flat, 0.0).is_empty());
+        assert!(buffer_points(&points, -2.0, LineCap::Round, 0.0).is_empty());
+    }
+
+    #[test]
+    fn test_line_caps() {
+        let line = vec![vec![Point2D::new(0.0, 0.0), Point2D::new(10.0, 0.0)]];
+        let flat = buffer_polylines(&line, 1.0, LineCap::Flat, LineJoin::Round, 0.0);
+        assert!((area(&flat) - 20.0).abs() < 1e-9);
+        let square = buffer_polylines(&line, 1.0, LineCap::Square, LineJoin::Round, 0.0);
+        assert!((area(&square) - 24.0).abs() < 1e-9);
+        let round = buffer_polylines(&line, 1.0, LineCap::Round, LineJoin::Round, 0.0);
+        assert_eq!(round.len(), 1);
+        assert_eq!(round[0].len(), 1);
+        assert!((area(&round) - 20.0 - circle_area(1.0)).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_line_joins() {
+        // A right angle, whose outer corner is a quarter circle or a square.
+        let line = vec![vec![
+            Point2D::new(0.0, 0.0),
+            Point2D::new(10.0, 0.0),
+            Point2D::new(10.0, 10.0),
+        ]];
+        let mitre = buffer_polylines(&line, 1.0, LineCap::Flat, LineJoin::Mitre, 0.0);
+        assert_eq!(mitre.len(), 1);
+        assert!((area(&mitre) - 40.0).abs() < 1e-9);
+        let round = buffer_polylines(&line, 1.0, LineCap::Flat, LineJoin::Round, 0.0);
+        assert!((area(&round) - 39.0 - circle_area(1.0) / 4.0).abs() < 1e-9);
+        // A hairpin turn is bevelled rather than mitred.
+        let line = vec![vec![
+            Point2D::new(0.0, 0.0),
+            Point2D::new(10.0, 0.0),
+            Point2D::new(0.0, 0.1),
+        ]];
+        let mitre = buffer_polylines(&line, 1.0, LineCap::Flat, LineJoin::Mitre, 0.0);
+        assert!(mitre.iter().flatten().flatten().all(|p| p.x < 12.0));
+    }
+
+    #[test]
+    fn test_polygons() {
+        let polygon = vec![square(0.0, 0.0, 10.0)];
+        let grown = buffer_polygon(&polygon, 1.0, LineJoin::Mitre, 0.0);
+        assert_eq!(grown.len(), 1);
+        assert!((area(&grown) - 144.0).abs() < 1e-9);
+        let grown = buffer_polygon(&polygon, 1.0, LineJoin::Round, 0.0);
+        assert!((area(&grown) - 140.0 - circle_area(1.0)).abs() < 1e-9);
+        let shrunk = buffer_polygon(&polygon, -1.0, LineJoin::Round, 0.0);
+        assert!((area(&shrunk) - 64.0).abs() < 1e-9);
+        assert!(buffer_polygon(&polygon, -6.0, LineJoin::Round, 0.0).is_empty());
+
+        // Growing a polygon fills its hole, and shrinking it widens the hole.
+        let polygon = vec![square(0.0, 0.0, 10.0), square(4.0, 4.0, 2.0)];
+        let grown = buffer_polygon(&polygon, 1.5, LineJoin::Mitre, 0.0);
+        assert_eq!(grown[0].len(), 1);
+        assert!((area(&grown) - 169.0).abs() < 1e-9);
+        let shrunk = buffer_polygon(&polygon, -1.0, LineJoin::Mitre, 0.0);
+        assert_eq!(shrunk[0].len(), 2);
+        assert!((area(&shrunk) - 64.0 + 16.0).abs() < 1e-9);
+    }
+}
@@ -6,6 +6,7 @@ Last Modified: 25/09/2018
 License: MIT
 */
 // private sub-module defined in other files
+mod buffer;
 mod convex_hull;
 mod delaunay_triangulation;
 mod is_clockwise_order;
@@ -13,11 +14,13 @@ mod line_ops;
 // mod lzw;
 mod minimum_bounding_box;
 mod poly_area;
+mod poly_boolean;
 mod poly_ops;
 mod poly_perimeter;
 mod smallest_enclosing_circle;
 
 // exports identifiers from private sub-modules in the current module namespace
+pub use self::buffer::{buffer_points, buffer_polygon, buffer_polylines, LineCap, LineJoin};
 pub use self::convex_hull::convex_hull;
 pub use self::delaunay_triangulation::{triangulate, Triangulation};
 pub use self::delaunay_triangulation::EMPTY;
@@ -29,6 +32,7 @@ pub use self::line_ops::{
 // pub use self::lzw::{lzw_decode, lzw_encode};
 pub use self::minimum_bounding_box::{minimum_bounding_box, MinimizationCriterion};
 pub use self::poly_area::polygon_area;
+pub use self::poly_boolean::{polygon_boolean, polygon_union_all, BooleanOperation};
 pub use self::poly_ops::{
     interior_point, point_in_poly, poly_in_poly, poly_is_convex, poly_overlaps_poly, winding_number,
 };
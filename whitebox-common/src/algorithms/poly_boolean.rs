@@ -0,0 +1,1234 @@
+/*
+This code is part of the WhiteboxTools geospatial analysis library.
+License: MIT
+
+Notes: Boolean operations on polygons (intersection, union, difference and symmetrical
+difference), using the sweep-line algorithm of Martinez, Rueda and Feito (2009). The
+edges of both polygons are swept from left to right; where they cross or overlap they are
+divided, and each edge is labelled as in or out of the result according to whether it lies
+inside the other polygon. The edges in the result are then connected into rings, which are
+nested into polygons with holes.
+
+A polygon is given as a set of rings, i.e. the parts of a Shapefile record, and the rings
+are combined with the even-odd rule, so that their orientation does not matter. Vertices of
+either polygon that lie within the snapping tolerance of one another are snapped together
+before the sweep, and vertices within the tolerance of an edge are inserted into it, so that
+shared boundaries that are slightly misaligned are treated as shared. Crossing points within
+the tolerance of an edge's end are snapped to the end. The tolerance is never less than the
+rounding errors in coordinates of the polygons' magnitude, so that the sweep stays consistent
+with projected coordinates in the millions.
+
+Martinez, F., Rueda, A. J., & Feito, F. R. (2009). A new algorithm for computing Boolean
+operations on polygons. Computers & Geosciences, 35(6), 1177-1185.
+*/
+
+use super::point_in_poly;
+use crate::rstar::{RTree, AABB};
+use crate::structures::{BoundingBox, Point2D, RectangleWithData};
+use std::cmp::Ordering;
+use std::collections::HashMap;
+
+/// A Boolean operation on two polygons.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BooleanOperation {
+    /// The area within both polygons.
+    Intersection,
+    /// The area within either polygon.
+    Union,
+    /// The area within the first polygon but not the second.
+    Difference,
+    /// The area within one of the polygons but not both.
+    SymmetricalDifference,
+}
+
+/// Performs a Boolean operation on two polygons, each of which is a set of rings combined with
+/// the even-odd rule, e.g. the parts of a Shapefile polygon record. The result is a set of
+/// polygons, each of which is a list of closed rings: a clockwise exterior ring followed by
+/// counter-clockwise holes, as in the Shapefile format. Vertices within `snap_tolerance` of
+/// one another are snapped together.
+pub fn polygon_boolean(
+    subject: &[Vec<Point2D>],
+    clipping: &[Vec<Point2D>],
+    operation: BooleanOperation,
+    snap_tolerance: f64,
+) -> Vec<Vec<Vec<Point2D>>> {
+    let tolerance = working_tolerance(subject.iter().chain(clipping), snap_tolerance);
+    let mut snapper = VertexSnapper::new(tolerance);
+    let mut polygons = vec![snapper.snap_rings(subject), snapper.snap_rings(clipping)];
+    insert_nodes(&mut polygons, tolerance);
+    boolean_op(&polygons[0], &polygons[1], operation, tolerance)
+}
+
+/// Returns the union of a set of polygons, each of which is a set of rings combined with the
+/// even-odd rule, in the form returned by `polygon_boolean`. The polygons are merged in pairs,
+/// then the pairs in pairs, and so on, which is much faster than adding them one at a time.
+pub fn polygon_union_all(
+    polygons: &[Vec<Vec<Point2D>>],
+    snap_tolerance: f64,
+) -> Vec<Vec<Vec<Point2D>>> {
+    // The vertices of all of the polygons are snapped together before any are merged.
+    let tolerance = working_tolerance(polygons.iter().flatten(), snap_tolerance);
+    let mut snapper = VertexSnapper::new(tolerance);
+    let mut polygons = polygons
+        .iter()
+        .map(|rings| snapper.snap_rings(rings))
+        .filter(|rings| !rings.is_empty())
+        .collect::<Vec<_>>();
+    if polygons.is_empty() {
+        return vec![];
+    }
+    insert_nodes(&mut polygons, tolerance);
+    cascaded_union(&polygons, tolerance)
+}
+
+fn cascaded_union(polygons: &[Vec<Vec<Point2D>>], tolerance: f64) -> Vec<Vec<Vec<Point2D>>> {
+    if polygons.len() == 1 {
+        return boolean_op(&polygons[0], &[], BooleanOperation::Union, tolerance);
+    }
+    let mid = polygons.len() / 2;
+    let flatten = |result: Vec<Vec<Vec<Point2D>>>| {
+        result
+            .into_iter()
+            .flatten()
+            .map(|mut ring| {
+                ring.pop(); // rings are open during the sweep
+                ring
+            })
+            .collect::<Vec<Vec<Point2D>>>()
+    };
+    let a = flatten(cascaded_union(&polygons[..mid], tolerance));
+    let b = flatten(cascaded_union(&polygons[mid..], tolerance));
+    boolean_op(&a, &b, BooleanOperation::Union, tolerance)
+}
+
+/// Returns the tolerance used with a set of rings, which is the snapping tolerance, but no
+/// less than the rounding errors in points computed from coordinates of their magnitude.
+fn working_tolerance<'a>(
+    rings: impl Iterator<Item = &'a Vec<Point2D>>,
+    snap_tolerance: f64,
+) -> f64 {
+    let scale = rings
+        .flatten()
+        .filter(|p| p.x.is_finite() && p.y.is_finite())
+        .fold(0f64, |m, p| m.max(p.x.abs()).max(p.y.abs()));
+    if snap_tolerance.is_finite() {
+        snap_tolerance.max(scale * 1e-12)
+    } else {
+        scale * 1e-12
+    }
+}
+
+/// Snaps vertices to the first vertex seen within the tolerance of them, using a grid of
+/// cells the size of the tolerance to find the nearby vertices. The coordinates of other
+/// vertices are snapped to the first coordinates seen within the tolerance of them, so that
+/// edges that are nearly vertical or horizontal become exactly so; the sweep cannot divide an
+/// edge that is nearly vertical, but whose ends differ in x by a rounding error.
+struct VertexSnapper {
+    tolerance: f64,
+    cells: HashMap<(i64, i64), Vec<Point2D>>,
+    xs: HashMap<i64, Vec<f64>>,
+    ys: HashMap<i64, Vec<f64>>,
+}
+
+impl VertexSnapper {
+    fn new(tolerance: f64) -> VertexSnapper {
+        VertexSnapper {
+            tolerance: if tolerance.is_finite() && tolerance > 0f64 {
+                tolerance
+            } else {
+                0f64
+            },
+            cells: HashMap::new(),
+            xs: HashMap::new(),
+            ys: HashMap::new(),
+        }
+    }
+
+    fn snap_coordinate(values: &mut HashMap<i64, Vec<f64>>, v: f64, tolerance: f64) -> f64 {
+        let cell = (v / tolerance).floor() as i64;
+        let mut nearest: Option<(f64, f64)> = None;
+        for c in cell - 1..=cell + 1 {
+            if let Some(vs) = values.get(&c) {
+                for w in vs {
+                    let d = (v - w).abs();
+                    if d <= tolerance && nearest.map_or(true, |(dn, _)| d < dn) {
+                        nearest = Some((d, *w));
+                    }
+                }
+            }
+        }
+        match nearest {
+            Some((_, w)) => w,
+            None => {
+                values.entry(cell).or_insert(vec![]).push(v);
+                v
+            }
+        }
+    }
+
+    fn snap(&mut self, p: Point2D) -> Point2D {
+        if self.tolerance == 0f64 {
+            return p;
+        }
+        let col = (p.x / self.tolerance).floor() as i64;
+        let row = (p.y / self.tolerance).floor() as i64;
+        let mut nearest: Option<(f64, Point2D)> = None;
+        for r in row - 1..=row + 1 {
+            for c in col - 1..=col + 1 {
+                if let Some(points) = self.cells.get(&(c, r)) {
+                    for q in points {
+                        let d = p.distance(q);
+                        if d <= self.tolerance && nearest.map_or(true, |(dn, _)| d < dn) {
+                            nearest = Some((d, *q));
+                        }
+                    }
+                }
+            }
+        }
+        match nearest {
+            Some((_, q)) => q,
+            None => {
+                let q = Point2D::new(
+                    VertexSnapper::snap_coordinate(&mut self.xs, p.x, self.tolerance),
+                    VertexSnapper::snap_coordinate(&mut self.ys, p.y, self.tolerance),
+                );
+                let col = (q.x / self.tolerance).floor() as i64;
+                let row = (q.y / self.tolerance).floor() as i64;
+                self.cells.entry((col, row)).or_insert(vec![]).push(q);
+                q
+            }
+        }
+    }
+
+    /// Snaps the vertices of a set of rings and returns the rings, open (i.e. without a repeated
+    /// last vertex) and without repeated vertices, leaving out those that have collapsed.
+    fn snap_rings(&mut self, rings: &[Vec<Point2D>]) -> Vec<Vec<Point2D>> {
+        let mut ret = vec![];
+        for ring in rings {
+            let mut snapped: Vec<Point2D> = Vec::with_capacity(ring.len());
+            for p in ring {
+                if !p.x.is_finite() || !p.y.is_finite() {
+                    continue;
+                }
+                let p = self.snap(*p);
+                if snapped.last() != Some(&p) {
+                    snapped.push(p);
+                }
+            }
+            while snapped.len() > 1 && snapped.first() == snapped.last() {
+                snapped.pop();
+            }
+            if snapped.len() >= 3 && signed_ring_area(&snapped) != 0f64 {
+                ret.push(snapped);
+            }
+        }
+        ret
+    }
+}
+
+/// Inserts into each edge the vertices, of any of the polygons, that lie within the tolerance
+/// of its interior, so that an edge that runs along another polygon's boundary shares that
+/// boundary's vertices, rather than passing slightly to one side of them.
+fn insert_nodes(polygons: &mut [Vec<Vec<Point2D>>], tolerance: f64) {
+    if tolerance == 0f64 {
+        return;
+    }
+    let vertices = polygons
+        .iter()
+        .flatten()
+        .flatten()
+        .map(|p| RectangleWithData::new(*p, [p.x, p.y], [p.x, p.y]))
+        .collect::<Vec<_>>();
+    let tree = RTree::bulk_load(vertices);
+    for rings in polygons.iter_mut() {
+        for ring in rings.iter_mut() {
+            let n = ring.len();
+            let mut noded = Vec::with_capacity(n);
+            for i in 0..n {
+                let (a, b) = (ring[i], ring[(i + 1) % n]);
+                noded.push(a);
+                let envelope = AABB::from_corners(
+                    [a.x.min(b.x) - tolerance, a.y.min(b.y) - tolerance],
+                    [a.x.max(b.x) + tolerance, a.y.max(b.y) + tolerance],
+                );
+                let (dx, dy) = (b.x - a.x, b.y - a.y);
+                let len2 = dx * dx + dy * dy;
+                let mut nodes = tree
+                    .locate_in_envelope_intersecting(&envelope)
+                    .map(|v| v.data)
+                    .filter(|q| *q != a && *q != b && distance_to_segment(*q, a, b) <= tolerance)
+                    .map(|q| (((q.x - a.x) * dx + (q.y - a.y) * dy) / len2, q))
+                    .filter(|(t, _)| *t > 0f64 && *t < 1f64)
+                    .collect::<Vec<_>>();
+                nodes.sort_by(|n1, n2| n1.0.partial_cmp(&n2.0).unwrap_or(Ordering::Equal));
+                for (_, q) in nodes {
+                    if noded.last() != Some(&q) {
+                        noded.push(q);
+                    }
+                }
+            }
+            *ring = noded;
+        }
+    }
+}
+
+/// An end of an edge. The left event of an edge is the end that the sweep line reaches first.
+#[derive(Clone, Debug)]
+struct SweepEvent {
+    point: Point2D,
+    left: bool,
+    /// The event at the other end of the edge.
+    other: usize,
+    is_subject: bool,
+    contour_id: usize,
+    /// False if the edge overlaps an edge below it, which stands in for both in the result.
+    contributing: bool,
+    /// The overlapping edges just above and just below this one, if there are any.
+    overlap: Option<usize>,
+    under: Option<usize>,
+    /// Whether the edge is an inside-outside transition of its own polygon, going upwards.
+    in_out: bool,
+    /// Whether the nearest edge of the other polygon below this one is an inside-outside
+    /// transition, i.e. whether the edge is outside the other polygon.
+    other_in_out: bool,
+    /// 1 if the result is entered upwards across the edge, -1 if it is left, or 0 if the edge
+    /// is not in the result.
+    result_transition: i8,
+}
+
+/// Returns twice the signed area of the triangle p0, p1, p2, which is positive if the points
+/// are in counter-clockwise order.
+fn signed_area(p0: Point2D, p1: Point2D, p2: Point2D) -> f64 {
+    (p0.x - p2.x) * (p1.y - p2.y) - (p1.x - p2.x) * (p0.y - p2.y)
+}
+
+/// Returns the distance from a point to the segment from a to b.
+fn distance_to_segment(p: Point2D, a: Point2D, b: Point2D) -> f64 {
+    let (dx, dy) = (b.x - a.x, b.y - a.y);
+    let len2 = dx * dx + dy * dy;
+    if len2 == 0f64 {
+        return p.distance(&a);
+    }
+    let t = (((p.x - a.x) * dx + (p.y - a.y) * dy) / len2)
+        .max(0f64)
+        .min(1f64);
+    p.distance(&Point2D::new(a.x + t * dx, a.y + t * dy))
+}
+
+/// Returns twice the signed area of a ring, which is positive if it is counter-clockwise. The
+/// coordinates are taken relative to the first vertex, so that the area of a small ring is not
+/// lost to rounding when its coordinates are large.
+fn signed_ring_area(ring: &[Point2D]) -> f64 {
+    let n = ring.len();
+    let mut area = 0f64;
+    for i in 1..n.saturating_sub(1) {
+        area += signed_area(ring[0], ring[i], ring[i + 1]);
+    }
+    area
+}
+
+struct Sweep {
+    events: Vec<SweepEvent>,
+    operation: BooleanOperation,
+    tolerance: f64,
+}
+
+impl Sweep {
+    fn new_event(
+        &mut self,
+        point: Point2D,
+        left: bool,
+        other: usize,
+        is_subject: bool,
+        contour_id: usize,
+    ) -> usize {
+        self.events.push(SweepEvent {
+            point,
+            left,
+            other,
+            is_subject,
+            contour_id,
+            contributing: true,
+            overlap: None,
+            under: None,
+            in_out: false,
+            other_in_out: false,
+            result_transition: 0,
+        });
+        self.events.len() - 1
+    }
+
+    fn other_point(&self, e: usize) -> Point2D {
+        self.events[self.events[e].other].point
+    }
+
+    /// Returns true if the edge of an event is below a point.
+    fn is_below(&self, e: usize, p: Point2D) -> bool {
+        let ev = &self.events[e];
+        if ev.left {
+            signed_area(ev.point, self.other_point(e), p) > 0f64
+        } else {
+            signed_area(self.other_point(e), ev.point, p) > 0f64
+        }
+    }
+
+    fn is_above(&self, e: usize, p: Point2D) -> bool {
+        !self.is_below(e, p)
+    }
+
+    /// Returns whether the edge of a left event passes through a point, to within the
+    /// tolerance: Some(true) if the point is within the edge, Some(false) if it is the left end
+    /// of the edge, or None if the edge does not pass through it.
+    fn touches(&self, e: usize, p: Point2D) -> Option<bool> {
+        let (a, b) = (self.events[e].point, self.other_point(e));
+        if a == p {
+            return Some(false);
+        }
+        if p.distance(&b) <= self.tolerance || distance_to_segment(p, a, b) > self.tolerance {
+            return None;
+        }
+        Some(true)
+    }
+
+    /// The order in which events are processed: from left to right, then bottom to top, with
+    /// right events before left events at the same point, and lower edges first.
+    fn compare_events(&self, e1: usize, e2: usize) -> Ordering {
+        let (p1, p2) = (self.events[e1].point, self.events[e2].point);
+        if p1.x > p2.x {
+            return Ordering::Greater;
+        }
+        if p1.x < p2.x {
+            return Ordering::Less;
+        }
+        if p1.y != p2.y {
+            return if p1.y > p2.y {
+                Ordering::Greater
+            } else {
+                Ordering::Less
+            };
+        }
+        if self.events[e1].left != self.events[e2].left {
+            return if self.events[e1].left {
+                Ordering::Greater
+            } else {
+                Ordering::Less
+            };
+        }
+        // Same point, same kind of event: the lower edge is processed first.
+        if signed_area(p1, self.other_point(e1), self.other_point(e2)) != 0f64 {
+            return if !self.is_below(e1, self.other_point(e2)) {
+                Ordering::Greater
+            } else {
+                Ordering::Less
+            };
+        }
+        if !self.events[e1].is_subject && self.events[e2].is_subject {
+            Ordering::Greater
+        } else {
+            Ordering::Less
+        }
+    }
+
+    /// The order of edges in the sweep line, from bottom to top.
+    fn compare_segments(&self, le1: usize, le2: usize) -> Ordering {
+        if le1 == le2 {
+            return Ordering::Equal;
+        }
+        let (p1, q1) = (self.events[le1].point, self.other_point(le1));
+        let (p2, q2) = (self.events[le2].point, self.other_point(le2));
+        if signed_area(p1, q1, p2) != 0f64 || signed_area(p1, q1, q2) != 0f64 {
+            // The edges are not collinear.
+            if p1 == p2 {
+                return if self.is_below(le1, q2) {
+                    Ordering::Less
+                } else {
+                    Ordering::Greater
+                };
+            }
+            if p1.x == p2.x {
+                return if p1.y < p2.y {
+                    Ordering::Less
+                } else {
+                    Ordering::Greater
+                };
+            }
+            if self.compare_events(le1, le2) == Ordering::Greater {
+                return if self.is_above(le2, p1) {
+                    Ordering::Less
+                } else {
+                    Ordering::Greater
+                };
+            }
+            return if self.is_below(le1, p2) {
+                Ordering::Less
+            } else {
+                Ordering::Greater
+            };
+        }
+        if self.events[le1].is_subject == self.events[le2].is_subject {
+            // Collinear edges of the same polygon.
+            if p1 == p2 {
+                if q1 == q2 {
+                    return Ordering::Equal;
+                }
+                return if self.events[le1].contour_id > self.events[le2].contour_id {
+                    Ordering::Greater
+                } else {
+                    Ordering::Less
+                };
+            }
+        } else {
+            // Collinear edges of different polygons.
+            return if self.events[le1].is_subject {
+                Ordering::Less
+            } else {
+                Ordering::Greater
+            };
+        }
+        if self.compare_events(le1, le2) == Ordering::Greater {
+            Ordering::Greater
+        } else {
+            Ordering::Less
+        }
+    }
+
+    /// Returns whether a point is in the result, given whether it is in the subject and in
+    /// the clipping polygon.
+    fn is_in_result(&self, in_subject: bool, in_clipping: bool) -> bool {
+        match self.operation {
+            BooleanOperation::Intersection => in_subject && in_clipping,
+            BooleanOperation::Union => in_subject || in_clipping,
+            BooleanOperation::Difference => in_subject && !in_clipping,
+            BooleanOperation::SymmetricalDifference => in_subject ^ in_clipping,
+        }
+    }
+
+    /// Returns whether the area just below (or just above) an edge is in the subject and in
+    /// the clipping polygon.
+    fn state(&self, e: usize, above: bool) -> (bool, bool) {
+        let ev = &self.events[e];
+        let this_in = ev.in_out != above;
+        let that_in = !ev.other_in_out;
+        if ev.is_subject {
+            (this_in, that_in)
+        } else {
+            (that_in, this_in)
+        }
+    }
+
+    /// Sets the result transition of an edge, by comparing whether the areas just below and
+    /// just above it are in the result. An edge that stands in for the edges that overlap it
+    /// is compared with the area above the topmost of them.
+    fn update_transition(&mut self, e: usize) {
+        if !self.events[e].contributing {
+            self.events[e].result_transition = 0;
+            return;
+        }
+        let mut top = e;
+        while let Some(o) = self.events[top].overlap {
+            top = o;
+        }
+        let (subject_below, clipping_below) = self.state(e, false);
+        let (subject_above, clipping_above) = self.state(top, true);
+        let below = self.is_in_result(subject_below, clipping_below);
+        let above = self.is_in_result(subject_above, clipping_above);
+        self.events[e].result_transition = match (below, above) {
+            (false, true) => 1,
+            (true, false) => -1,
+            _ => 0,
+        };
+    }
+
+    /// Labels an edge from the nearest edge below it in the sweep line.
+    fn compute_fields(&mut self, e: usize, prev: Option<usize>) {
+        match prev {
+            None => {
+                self.events[e].in_out = false;
+                self.events[e].other_in_out = true;
+            }
+            Some(prev) => {
+                if self.events[e].is_subject == self.events[prev].is_subject {
+                    self.events[e].in_out = !self.events[prev].in_out;
+                    self.events[e].other_in_out = self.events[prev].other_in_out;
+                } else {
+                    self.events[e].in_out = !self.events[prev].other_in_out;
+                    self.events[e].other_in_out = self.events[prev].in_out;
+                }
+            }
+        }
+        self.update_transition(e);
+    }
+
+    /// Labels a stack of overlapping edges, each from the one below it, and then the edge at
+    /// the bottom, which stands in for them all.
+    fn relabel_overlaps(&mut self, e: usize) {
+        let mut bottom = e;
+        while let Some(u) = self.events[bottom].under {
+            bottom = u;
+        }
+        let mut below = bottom;
+        while let Some(o) = self.events[below].overlap {
+            self.compute_fields(o, Some(below));
+            below = o;
+        }
+        self.update_transition(bottom);
+    }
+
+    /// Divides an edge at a point, adding the new events to the queue. Returns false if the
+    /// point is not strictly between the ends of the edge, in which case it is not divided.
+    fn divide_segment(&mut self, se: usize, p: Point2D, queue: &mut EventQueue) -> bool {
+        let other = self.events[se].other;
+        // A point that is not strictly between the ends in the sweep order, which may happen
+        // with a nearly vertical edge, would put one of the pieces behind the sweep line.
+        let precedes = |a: Point2D, b: Point2D| a.x < b.x || (a.x == b.x && a.y < b.y);
+        if !precedes(self.events[se].point, p) || !precedes(p, self.events[other].point) {
+            return false;
+        }
+        let is_subject = self.events[se].is_subject;
+        let contour_id = self.events[se].contour_id;
+        let r = self.new_event(p, false, se, is_subject, contour_id);
+        let l = self.new_event(p, true, other, is_subject, contour_id);
+        // Guard against rounding errors that would put the left event after the right one.
+        if self.compare_events(l, other) == Ordering::Greater {
+            self.events[other].left = true;
+            self.events[l].left = false;
+        }
+        self.events[other].other = l;
+        self.events[se].other = r;
+        queue.push(self, l);
+        queue.push(self, r);
+        true
+    }
+
+    /// Returns the points where two edges cross, or the ends of their overlap, snapping
+    /// points within the tolerance of an end of either edge to that end.
+    fn intersection(&self, se1: usize, se2: usize) -> Vec<Point2D> {
+        let (a1, a2) = (self.events[se1].point, self.other_point(se1));
+        let (b1, b2) = (self.events[se2].point, self.other_point(se2));
+        let ends = [a1, a2, b1, b2];
+        let snap = |p: Point2D| -> Point2D {
+            for q in &ends {
+                if p.distance(q) <= self.tolerance {
+                    return *q;
+                }
+            }
+            p
+        };
+        let va = Point2D::new(a2.x - a1.x, a2.y - a1.y);
+        let vb = Point2D::new(b2.x - b1.x, b2.y - b1.y);
+        let e = Point2D::new(b1.x - a1.x, b1.y - a1.y);
+        let cross = |u: Point2D, v: Point2D| u.x * v.y - u.y * v.x;
+        let dot = |u: Point2D, v: Point2D| u.x * v.x + u.y * v.y;
+        let to_point = |p: Point2D, s: f64, d: Point2D| Point2D::new(p.x + s * d.x, p.y + s * d.y);
+
+        let kross = cross(va, vb);
+        if kross != 0f64 {
+            // An end of one edge on the other is taken as it is, rather than computed, which
+            // would put it slightly off the edge. Edges with two ends on one another are
+            // nearly collinear, and are treated as overlapping between those ends.
+            let mut on_other: Vec<Point2D> = vec![];
+            for (p, q1, q2) in [(a1, b1, b2), (a2, b1, b2), (b1, a1, a2), (b2, a1, a2)] {
+                if distance_to_segment(p, q1, q2) <= self.tolerance && !on_other.contains(&p) {
+                    on_other.push(p);
+                }
+            }
+            if on_other.len() > 1 {
+                on_other.sort_by(|p, q| {
+                    (p.x, p.y)
+                        .partial_cmp(&(q.x, q.y))
+                        .unwrap_or(Ordering::Equal)
+                });
+                return vec![on_other[0], on_other[on_other.len() - 1]];
+            }
+            if on_other.len() == 1 {
+                return on_other;
+            }
+            let s = cross(e, vb) / kross;
+            if s < 0f64 || s > 1f64 {
+                return vec![];
+            }
+            let t = cross(e, va) / kross;
+            if t < 0f64 || t > 1f64 {
+                return vec![];
+            }
+            if s == 0f64 || s == 1f64 {
+                return vec![snap(to_point(a1, s, va))];
+            }
+            if t == 0f64 || t == 1f64 {
+                return vec![snap(to_point(b1, t, vb))];
+            }
+            return vec![snap(to_point(a1, s, va))];
+        }
+        if cross(e, va) != 0f64 {
+            return vec![]; // parallel
+        }
+        let sqr_len_a = dot(va, va);
+        let sa = dot(va, e) / sqr_len_a;
+        let sb = sa + dot(va, vb) / sqr_len_a;
+        let smin = sa.min(sb);
+        let smax = sa.max(sb);
+        if smin <= 1f64 && smax >= 0f64 {
+            if smin == 1f64 {
+                return vec![snap(to_point(a1, smin.max(0f64), va))];
+            }
+            if smax == 0f64 {
+                return vec![snap(to_point(a1, smax.min(1f64), va))];
+            }
+            return vec![
+                snap(to_point(a1, smin.max(0f64), va)),
+                snap(to_point(a1, smax.min(1f64), va)),
+            ];
+        }
+        vec![]
+    }
+
+    /// Divides two edges that are adjacent in the sweep line where they cross or overlap.
+    /// Returns 0 if they do not, 1 if they cross, 2 if they overlap from a shared left end,
+    /// and 3 if they otherwise overlap.
+    fn possible_intersection(&mut self, se1: usize, se2: usize, queue: &mut EventQueue) -> u8 {
+        let inter = self.intersection(se1, se2);
+        if inter.is_empty() {
+            return 0;
+        }
+        let (p1, p2) = (self.events[se1].point, self.events[se2].point);
+        let (q1, q2) = (self.other_point(se1), self.other_point(se2));
+        if inter.len() == 1 && (p1 == p2 || q1 == q2) {
+            return 0; // the edges meet at an end of both
+        }
+        if inter.len() == 1 || inter[0] == inter[1] {
+            let p = inter[0];
+            if p != p1 && p != q1 {
+                self.divide_segment(se1, p, queue);
+            }
+            if p != p2 && p != q2 {
+                self.divide_segment(se2, p, queue);
+            }
+            return 1;
+        }
+
+        // The edges overlap.
+        let mut events: Vec<usize> = vec![];
+        let left_coincide = p1 == p2;
+        let right_coincide = q1 == q2;
+        if !left_coincide {
+            if self.compare_events(se1, se2) == Ordering::Greater {
+                events.push(se2);
+                events.push(se1);
+            } else {
+                events.push(se1);
+                events.push(se2);
+            }
+        }
+        if !right_coincide {
+            let (o1, o2) = (self.events[se1].other, self.events[se2].other);
+            if self.compare_events(o1, o2) == Ordering::Greater {
+                events.push(o2);
+                events.push(o1);
+            } else {
+                events.push(o1);
+                events.push(o2);
+            }
+        }
+
+        if left_coincide {
+            // The edges are equal or share their left end.
+            self.events[se2].contributing = false;
+            self.events[se1].overlap = Some(se2);
+            self.events[se2].under = Some(se1);
+            if !right_coincide {
+                let longer = self.events[events[1]].other;
+                let p = self.events[events[0]].point;
+                self.divide_segment(longer, p, queue);
+            }
+            return 2;
+        }
+        if right_coincide {
+            // The edges share their right end.
+            let p = self.events[events[1]].point;
+            self.divide_segment(events[0], p, queue);
+            return 3;
+        }
+        if events[0] != self.events[events[3]].other {
+            // Neither edge contains the other.
+            let p = self.events[events[1]].point;
+            self.divide_segment(events[0], p, queue);
+            let p = self.events[events[2]].point;
+            self.divide_segment(events[1], p, queue);
+            return 3;
+        }
+        // One edge contains the other.
+        let p = self.events[events[1]].point;
+        self.divide_segment(events[0], p, queue);
+        let p = self.events[events[2]].point;
+        let e = self.events[events[3]].other;
+        self.divide_segment(e, p, queue);
+        3
+    }
+
+    /// Sweeps the edges, dividing and labelling them, and returns the processed events in order.
+    fn subdivide(
+        &mut self,
+        queue: &mut EventQueue,
+        subject_max_x: f64,
+        clipping_max_x: f64,
+    ) -> Vec<usize> {
+        let mut sorted_events = vec![];
+        // The edges in the sweep line, from bottom to top.
+        let mut status: Vec<usize> = vec![];
+        let right_bound = subject_max_x.min(clipping_max_x);
+        while let Some(e) = queue.pop(self) {
+            sorted_events.push(e);
+            // Nothing to the right of either polygon can be in an intersection, nor anything
+            // to the right of the subject in a difference.
+            let x = self.events[e].point.x;
+            if (self.operation == BooleanOperation::Intersection && x > right_bound)
+                || (self.operation == BooleanOperation::Difference && x > subject_max_x)
+            {
+                break;
+            }
+            if self.events[e].left {
+                let pos =
+                    status.partition_point(|&s| self.compare_segments(s, e) == Ordering::Less);
+                // Edges that pass through the point are divided there first, so that the edges
+                // that start at the point are labelled against the pieces of those edges, and
+                // the event is put back until their right events have been processed.
+                let p = self.events[e].point;
+                let mut through = vec![];
+                for &s in status[..pos].iter().rev() {
+                    match self.touches(s, p) {
+                        Some(true) => through.push(s),
+                        Some(false) => {}
+                        None => break,
+                    }
+                }
+                for &s in &status[pos..] {
+                    match self.touches(s, p) {
+                        Some(true) => through.push(s),
+                        Some(false) => {}
+                        None => break,
+                    }
+                }
+                let mut divided = false;
+                for s in through {
+                    divided |= self.divide_segment(s, p, queue);
+                }
+                if divided {
+                    sorted_events.pop();
+                    queue.push(self, e);
+                    continue;
+                }
+                status.insert(pos, e);
+                let prev = if pos > 0 { Some(status[pos - 1]) } else { None };
+                let next = status.get(pos + 1).copied();
+                self.compute_fields(e, prev);
+                if let Some(next) = next {
+                    if self.possible_intersection(e, next, queue) == 2 {
+                        self.compute_fields(e, prev);
+                        self.relabel_overlaps(e);
+                    }
+                }
+                if let Some(prev) = prev {
+                    if self.possible_intersection(prev, e, queue) == 2 {
+                        let prev_prev = if pos > 1 { Some(status[pos - 2]) } else { None };
+                        self.compute_fields(prev, prev_prev);
+                        self.relabel_overlaps(prev);
+                    }
+                }
+            } else {
+                let le = self.events[e].other;
+                if let Some(pos) = status.iter().position(|&s| s == le) {
+                    let prev = if pos > 0 { Some(status[pos - 1]) } else { None };
+                    let next = status.get(pos + 1).copied();
+                    status.remove(pos);
+                    if let (Some(prev), Some(next)) = (prev, next) {
+                        self.possible_intersection(prev, next, queue);
+                    }
+                }
+            }
+        }
+        sorted_events
+    }
+
+    /// Connects the edges in the result into rings, and nests the rings into polygons.
+    fn connect_edges(&self, sorted_events: &[usize]) -> Vec<Vec<Vec<Point2D>>> {
+        // Direct each edge in the result so that the result lies to its left. A positive
+        // transition means that the result lies above the edge, or, for a vertical edge, to
+        // the left of it, as the sweep treats vertical edges as leaning slightly to the right.
+        let mut edges: Vec<(Point2D, Point2D)> = vec![];
+        let mut outgoing: HashMap<(u64, u64), Vec<usize>> = HashMap::new();
+        for &e in sorted_events {
+            let ev = &self.events[e];
+            if !ev.left || ev.result_transition == 0 {
+                continue;
+            }
+            let edge = if ev.result_transition > 0 {
+                (ev.point, self.other_point(e))
+            } else {
+                (self.other_point(e), ev.point)
+            };
+            outgoing
+                .entry(point_key(edge.0))
+                .or_insert(vec![])
+                .push(edges.len());
+            edges.push(edge);
+        }
+
+        // Walk the edges into rings, splitting them where they pass through a vertex more than
+        // once, which leaves simple rings that are counter-clockwise if they are exterior rings
+        // and clockwise if they are holes.
+        let mut used = vec![false; edges.len()];
+        let mut rings: Vec<Vec<Point2D>> = vec![];
+        for i in 0..edges.len() {
+            if used[i] {
+                continue;
+            }
+            let mut tracer = RingTracer::new(edges[i].0);
+            let mut edge = i;
+            loop {
+                used[edge] = true;
+                let p = edges[edge].1;
+                tracer.add_vertex(p, &mut rings);
+                let next = outgoing
+                    .get(&point_key(p))
+                    .and_then(|candidates| candidates.iter().copied().find(|&j| !used[j]));
+                match next {
+                    Some(j) => edge = j,
+                    None => break,
+                }
+            }
+            tracer.finish(&mut rings);
+        }
+        build_polygons(rings)
+    }
+}
+
+/// Returns a key for a point, which is the same for points that are equal, including zero and
+/// negative zero.
+fn point_key(p: Point2D) -> (u64, u64) {
+    ((p.x + 0f64).to_bits(), (p.y + 0f64).to_bits())
+}
+
+/// Follows the edges of a ring, splitting off a simple ring each time that a vertex is
+/// revisited.
+struct RingTracer {
+    points: Vec<Point2D>,
+    positions: HashMap<(u64, u64), usize>,
+}
+
+impl RingTracer {
+    fn new(start: Point2D) -> RingTracer {
+        let mut positions = HashMap::new();
+        positions.insert(point_key(start), 0);
+        RingTracer {
+            points: vec![start],
+            positions,
+        }
+    }
+
+    fn add_vertex(&mut self, p: Point2D, rings: &mut Vec<Vec<Point2D>>) {
+        match self.positions.get(&point_key(p)) {
+            Some(&m) => {
+                for q in &self.points[m + 1..] {
+                    self.positions.remove(&point_key(*q));
+                }
+                rings.push(self.points.split_off(m));
+                self.points.push(p);
+            }
+            None => {
+                self.positions.insert(point_key(p), self.points.len());
+                self.points.push(p);
+            }
+        }
+    }
+
+    fn finish(self, rings: &mut Vec<Vec<Point2D>>) {
+        // A ring that does not close, which rounding can cause, is closed by a final edge.
+        if self.points.len() >= 3 {
+            rings.push(self.points);
+        }
+    }
+}
+
+/// Sorts simple rings into exterior rings and holes, orients them, and assigns each hole to
+/// the smallest exterior ring that contains it.
+fn build_polygons(rings: Vec<Vec<Point2D>>) -> Vec<Vec<Vec<Point2D>>> {
+    let mut exteriors: Vec<(Vec<Point2D>, f64, BoundingBox)> = vec![];
+    let mut holes: Vec<Vec<Point2D>> = vec![];
+    for ring in rings {
+        // Drop the vertices within straight runs, which are left where edges were divided.
+        let n = ring.len();
+        let mut points: Vec<Point2D> = (0..n)
+            .filter(|&i| signed_area(ring[(i + n - 1) % n], ring[i], ring[(i + 1) % n]) != 0f64)
+            .map(|i| ring[i])
+            .collect();
+        if points.len() < 3 {
+            continue;
+        }
+        let area = signed_ring_area(&points);
+        if area == 0f64 {
+            continue;
+        }
+        // Exterior rings are written clockwise and holes counter-clockwise.
+        points.reverse();
+        points.push(points[0]);
+        if area > 0f64 {
+            let bb = BoundingBox::from_points(&points);
+            exteriors.push((points, area, bb));
+        } else {
+            holes.push(points);
+        }
+    }
+
+    let mut polygons: Vec<Vec<Vec<Point2D>>> = exteriors
+        .iter()
+        .map(|(ring, _, _)| vec![ring.clone()])
+        .collect();
+    for hole in holes {
+        // The midpoint of an edge of the hole is within its exterior ring and on no other ring.
+        let p = Point2D::new(
+            (hole[0].x + hole[1].x) / 2f64,
+            (hole[0].y + hole[1].y) / 2f64,
+        );
+        let mut parent: Option<(usize, f64)> = None;
+        for (j, (ring, area, bb)) in exteriors.iter().enumerate() {
+            if parent.map_or(true, |(_, a)| *area < a)
+                && bb.is_point_in_box(p.x, p.y)
+                && point_in_poly(&p, ring)
+            {
+                parent = Some((j, *area));
+            }
+        }
+        if let Some((j, _)) = parent {
+            polygons[j].push(hole);
+        }
+    }
+    polygons
+}
+
+/// A priority queue of events, in the order in which they are processed.
+struct EventQueue {
+    heap: Vec<usize>,
+}
+
+impl EventQueue {
+    fn push(&mut self, sweep: &Sweep, e: usize) {
+        self.heap.push(e);
+        let mut i = self.heap.len() - 1;
+        while i > 0 {
+            let parent = (i - 1) / 2;
+            if sweep.compare_events(self.heap[i], self.heap[parent]) == Ordering::Less {
+                self.heap.swap(i, parent);
+                i = parent;
+            } else {
+                break;
+            }
+        }
+    }
+
+    fn pop(&mut self, sweep: &Sweep) -> Option<usize> {
+        if self.heap.is_empty() {
+            return None;
+        }
+        let top = self.heap.swap_remove(0);
+        let n = self.heap.len();
+        let mut i = 0;
+        loop {
+            let (l, r) = (2 * i + 1, 2 * i + 2);
+            let mut smallest = i;
+            if l < n && sweep.compare_events(self.heap[l], self.heap[smallest]) == Ordering::Less {
+                smallest = l;
+            }
+            if r < n && sweep.compare_events(self.heap[r], self.heap[smallest]) == Ordering::Less {
+                smallest = r;
+            }
+            if smallest == i {
+                break;
+            }
+            self.heap.swap(i, smallest);
+            i = smallest;
+        }
+        Some(top)
+    }
+}
+
+/// Performs a Boolean operation on two sets of open rings, which have already been snapped.
+fn boolean_op(
+    subject: &[Vec<Point2D>],
+    clipping: &[Vec<Point2D>],
+    operation: BooleanOperation,
+    tolerance: f64,
+) -> Vec<Vec<Vec<Point2D>>> {
+    if operation == BooleanOperation::Intersection && (subject.is_empty() || clipping.is_empty()) {
+        return vec![];
+    }
+    let mut sweep = Sweep {
+        events: vec![],
+        operation,
+        tolerance,
+    };
+    let mut queue = EventQueue { heap: vec![] };
+    let mut max_x = [f64::NEG_INFINITY; 2];
+    let mut contour_id = 0;
+    for (is_subject, rings) in [(true, subject), (false, clipping)] {
+        for ring in rings {
+            contour_id += 1;
+            for i in 0..ring.len() {
+                let (p1, p2) = (ring[i], ring[(i + 1) % ring.len()]);
+                if p1 == p2 {
+                    continue;
+                }
+                let m = if is_subject { 0 } else { 1 };
+                max_x[m] = max_x[m].max(p1.x);
+                let e1 = sweep.new_event(p1, false, 0, is_subject, contour_id);
+                let e2 = sweep.new_event(p2, false, e1, is_subject, contour_id);
+                sweep.events[e1].other = e2;
+                if sweep.compare_events(e1, e2) == Ordering::Greater {
+                    sweep.events[e2].left = true;
+                } else {
+                    sweep.events[e1].left = true;
+                }
+                queue.push(&sweep, e1);
+                queue.push(&sweep, e2);
+            }
+        }
+    }
+    let sorted_events = sweep.subdivide(&mut queue, max_x[0], max_x[1]);
+    sweep.connect_edges(&sorted_events)
+}
+
+#[cfg(test)]
+mod test {
+    use super::{polygon_boolean, polygon_union_all, signed_ring_area, BooleanOperation};
+    use crate::structures::Point2D;
+
+    fn square(x: f64, y: f64, size: f64) -> Vec<Point2D> {
+        vec![
+            Point2D::new(x, y),
+            Point2D::new(x, y + size),
+            Point2D::new(x + size, y + size),
+            Point2D::new(x + size, y),
+            Point2D::new(x, y),
+        ]
+    }
+
+    /// The area of a result, with holes subtracted.
+    fn area(polygons: &[Vec<Vec<Point2D>>]) -> f64 {
+        polygons
+            .iter()
+            .flatten()
+            .map(|ring| -signed_ring_area(&ring[..ring.len() - 1]) / 2f64)
+            .sum()
+    }
+
+    #[test]
+    fn test_overlapping_squares() {
+        let a = vec![square(0.0, 0.0, 2.0)];
+        let b = vec![square(1.0, 1.0, 2.0)];
+        let intersection = polygon_boolean(&a, &b, BooleanOperation::Intersection, 0.0);
+        assert_eq!(intersection.len(), 1);
+        assert!((area(&intersection) - 1.0).abs() < 1e-12);
+        let union = polygon_boolean(&a, &b, BooleanOperation::Union, 0.0);
+        assert_eq!(union.len(), 1);
+        assert!((area(&union) - 7.0).abs() < 1e-12);
+        let difference = polygon_boolean(&a, &b, BooleanOperation::Difference, 0.0);
+        assert!((area(&difference) - 3.0).abs() < 1e-12);
+        let xor = polygon_boolean(&a, &b, BooleanOperation::SymmetricalDifference, 0.0);
+        assert!((area(&xor) - 6.0).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_holes() {
+        // A square with a hole, minus a smaller square within the hole, is unchanged, and
+        // minus a square across the hole's edge gets a larger hole.
+        let a = vec![square(0.0, 0.0, 10.0), square(3.0, 3.0, 4.0)];
+        let inner = vec![square(4.0, 4.0, 1.0)];
+        let difference = polygon_boolean(&a, &inner, BooleanOperation::Difference, 0.0);
+        assert_eq!(difference.len(), 1);
+        assert_eq!(difference[0].len(), 2);
+        assert!((area(&difference) - 84.0).abs() < 1e-12);
+        let across = vec![square(6.0, 6.0, 2.0)];
+        let difference = polygon_boolean(&a, &across, BooleanOperation::Difference, 0.0);
+        assert!((area(&difference) - 81.0).abs() < 1e-12);
+        // The union with a square that fills the hole has no hole.
+        let fill = vec![square(2.0, 2.0, 6.0)];
+        let union = polygon_boolean(&a, &fill, BooleanOperation::Union, 0.0);
+        assert_eq!(union.len(), 1);
+        assert_eq!(union[0].len(), 1);
+        assert!((area(&union) - 100.0).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_shared_edges() {
+        // Adjacent squares merge into one polygon, and have no area in common.
+        let polygons = vec![
+            vec![square(0.0, 0.0, 1.0)],
+            vec![square(1.0, 0.0, 1.0)],
+            vec![square(0.0, 1.0, 1.0)],
+            vec![square(1.0, 1.0, 1.0)],
+        ];
+        let union = polygon_union_all(&polygons, 0.0);
+        assert_eq!(union.len(), 1);
+        assert_eq!(union[0].len(), 1);
+        assert!((area(&union) - 4.0).abs() < 1e-12);
+        let intersection = polygon_boolean(
+            &polygons[0],
+            &polygons[1],
+            BooleanOperation::Intersection,
+            0.0,
+        );
+        assert!(area(&intersection).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_snapping() {
+        // Squares that nearly share an edge merge into one polygon when the gap is snapped.
+        let a = vec![square(0.0, 0.0, 1.0)];
+        let b = vec![square(1.0 + 1e-9, 0.0, 1.0)];
+        let union = polygon_boolean(&a, &b, BooleanOperation::Union, 0.0);
+        assert_eq!(union.len(), 2);
+        let union = polygon_boolean(&a, &b, BooleanOperation::Union, 1e-6);
+        assert_eq!(union.len(), 1);
+        assert!((area(&union) - 2.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_misaligned_boundaries() {
+        // A shared boundary with a vertex on only one side, and coordinates that differ by
+        // rounding errors, at the magnitude of projected coordinates.
+        let (x, y) = (500000.0, 4000000.0);
+        let a = vec![vec![
+            Point2D::new(x, y),
+            Point2D::new(x, y + 2.0),
+            Point2D::new(x + 1.0, y + 2.0),
+            Point2D::new(x + 1.0 + 3e-9, y + 1.0),
+            Point2D::new(x + 1.0, y),
+        ]];
+        let b = vec![vec![
+            Point2D::new(x + 1.0 - 2e-9, y - 1e-9),
+            Point2D::new(x + 1.0 + 1e-9, y + 2.0),
+            Point2D::new(x + 2.0, y + 2.0),
+            Point2D::new(x + 2.0, y),
+        ]];
+        let union = polygon_boolean(&a, &b, BooleanOperation::Union, 1e-6);
+        assert_eq!(union.len(), 1);
+        assert_eq!(union[0].len(), 1);
+        assert!((area(&union) - 4.0).abs() < 1e-6);
+        let intersection = polygon_boolean(&a, &b, BooleanOperation::Intersection, 1e-6);
+        assert!(area(&intersection).abs() < 1e-6);
+        let xor = polygon_boolean(&a, &b, BooleanOperation::SymmetricalDifference, 1e-6);
+        assert!((area(&xor) - 4.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_orientation() {
+        let a = vec![square(0.0, 0.0, 10.0), square(3.0, 3.0, 4.0)];
+        let union = polygon_boolean(&a, &[], BooleanOperation::Union, 0.0);
+        assert_eq!(union.len(), 1);
+        // Exterior rings are clockwise and holes are counter-clockwise.
+        let exterior = &union[0][0];
+        let hole = &union[0][1];
+        assert!(signed_ring_area(&exterior[..exterior.len() - 1]) < 0.0);
+        assert!(signed_ring_area(&hole[..hole.len() - 1]) > 0.0);
+        assert_eq!(exterior[0], exterior[exterior.len() - 1]);
+    }
+}
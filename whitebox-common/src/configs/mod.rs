@@ -13,6 +13,63 @@ pub struct Configs {
     pub working_directory: String,
     pub compress_rasters: bool,
     pub max_procs: isize,
+    /// The memory budget of a single raster, in megabytes. Rasters larger than this
+    /// are read from disk one block at a time. -1 = no limit.
+    #[serde(default = "default_max_memory")]
+    pub max_memory: isize,
+    /// The method used to compress GeoTIFF outputs when compress_rasters is true:
+    /// 'deflate', 'lzw', 'packbits', or 'zstd'.
+    #[serde(default = "default_compression")]
+    pub compression: String,
+    /// The level of DEFLATE (1-9) or ZSTD (1-22) compression. -1 = the method's default.
+    #[serde(default = "default_compression_level")]
+    pub compression_level: isize,
+    /// The predictor applied to compressed GeoTIFF outputs: 1 = none, 2 = horizontal
+    /// differencing, 3 = floating-point (integer rasters use 2 instead).
+    #[serde(default = "default_predictor")]
+    pub predictor: usize,
+    /// Write GeoTIFF outputs as Cloud-Optimized GeoTIFFs, i.e. tiled and with
+    /// internal overviews.
+    #[serde(default)]
+    pub cog: bool,
+    /// The width and height, in pixels, of the tiles of Cloud-Optimized GeoTIFFs.
+    #[serde(default = "default_cog_tile_size")]
+    pub cog_tile_size: usize,
+    /// The number of overview levels of Cloud-Optimized GeoTIFFs, each half the
+    /// size of the last. -1 = as many as are needed to fit the image within a tile.
+    #[serde(default = "default_cog_overview_levels")]
+    pub cog_overview_levels: isize,
+    /// The resampling method used to build overviews: 'nearest', 'average', or 'mode'.
+    #[serde(default = "default_cog_resampling")]
+    pub cog_resampling: String,
+}
+
+fn default_max_memory() -> isize {
+    -1
+}
+
+fn default_compression() -> String {
+    "deflate".to_string()
+}
+
+fn default_compression_level() -> isize {
+    -1
+}
+
+fn default_predictor() -> usize {
+    1
+}
+
+fn default_cog_tile_size() -> usize {
+    256
+}
+
+fn default_cog_overview_levels() -> isize {
+    -1
+}
+
+fn default_cog_resampling() -> String {
+    "nearest".to_string()
 }
 
 impl Configs {
@@ -21,7 +78,15 @@ impl Configs {
             verbose_mode: true,
             working_directory: String::new(),
             compress_rasters: true,
-            max_procs: -1
+            max_procs: -1,
+            max_memory: -1,
+            compression: default_compression(),
+            compression_level: default_compression_level(),
+            predictor: default_predictor(),
+            cog: false,
+            cog_tile_size: default_cog_tile_size(),
+            cog_overview_levels: default_cog_overview_levels(),
+            cog_resampling: default_cog_resampling(),
         }
     }
 }
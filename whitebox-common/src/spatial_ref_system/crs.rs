@@ -0,0 +1,799 @@
+/*
+This code is part of the WhiteboxTools geospatial analysis library.
+License: MIT
+*/
+use super::datum::{Datum, HelmertParameters};
+use super::ellipsoid::{ellipsoid_from_name, Ellipsoid};
+use super::epsg_to_wkt::{epsg_codes, esri_wkt_from_epsg};
+use super::projections::{Projection, ProjectionMethod, ProjectionParameters};
+use super::wkt::{WktNode, WktValue};
+use std::io::{Error, ErrorKind};
+use std::sync::OnceLock;
+
+/// The kind of coordinate reference system, and for projected systems, the projection.
+#[derive(Clone, Debug, PartialEq)]
+pub enum CrsKind {
+    /// Longitude and latitude, in the angular unit.
+    Geographic,
+    /// Easting and northing, in the linear unit.
+    Projected(Projection),
+    /// Earth-centred, earth-fixed X, Y, Z coordinates, in the linear unit.
+    Geocentric,
+}
+
+/// A coordinate reference system, with everything that is needed to transform
+/// coordinates to and from it.
+#[derive(Clone, Debug, PartialEq)]
+pub struct CoordinateReferenceSystem {
+    pub name: String,
+    pub kind: CrsKind,
+    pub datum: Datum,
+    /// The longitude of the prime meridian, in degrees east of Greenwich.
+    pub prime_meridian: f64,
+    /// The size of the angular unit of geographic coordinates, in radians.
+    pub angular_unit: f64,
+    /// The size of the linear unit of projected and geocentric coordinates, in metres.
+    pub linear_unit: f64,
+    /// The EPSG code of the system, if it is known.
+    pub epsg: Option<u16>,
+}
+
+impl CoordinateReferenceSystem {
+    /// Geographic coordinates on WGS84, in degrees (EPSG:4326).
+    pub fn wgs84() -> CoordinateReferenceSystem {
+        CoordinateReferenceSystem {
+            name: "GCS_WGS_1984".to_string(),
+            kind: CrsKind::Geographic,
+            datum: Datum::wgs84(),
+            prime_meridian: 0f64,
+            angular_unit: 1f64.to_radians(),
+            linear_unit: 1f64,
+            epsg: Some(4326),
+        }
+    }
+
+    /// Creates the coordinate reference system with an EPSG code.
+    pub fn from_epsg(code: u16) -> Result<CoordinateReferenceSystem, Error> {
+        let wkt = esri_wkt_from_epsg(code);
+        if wkt == "Unknown EPSG Code" {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                format!("The EPSG code {} is not recognized.", code),
+            ));
+        }
+        let mut crs = CoordinateReferenceSystem::from_wkt(&wkt)?;
+        crs.epsg = Some(code);
+        Ok(crs)
+    }
+
+    /// Creates a coordinate reference system from its well-known text (WKT)
+    /// description, as found in a .prj file or a raster's metadata. Both the OGC/Esri WKT1
+    /// and the ISO 19162 WKT2 forms are read. For compound systems, the horizontal system
+    /// is used, and for WKT2 bound systems, the base system, with its transformation to
+    /// WGS84 when that is the target.
+    pub fn from_wkt(wkt: &str) -> Result<CoordinateReferenceSystem, Error> {
+        let root = WktNode::parse(wkt.trim())?;
+        crs_from_node(&root)
+    }
+
+    /// Creates the coordinate reference system of a layer from its WKT or, if that is
+    /// missing or cannot be read, its EPSG code. Layers that have neither, for which the
+    /// WKT is often 'not specified' and the EPSG code zero, have no known system.
+    pub fn from_wkt_or_epsg(wkt: &str, epsg: u16) -> Option<CoordinateReferenceSystem> {
+        let wkt = wkt.trim();
+        if !wkt.is_empty()
+            && !wkt.eq_ignore_ascii_case("not specified")
+            && wkt != "Unknown EPSG Code"
+        {
+            if let Ok(mut crs) = CoordinateReferenceSystem::from_wkt(wkt) {
+                if crs.epsg.is_none() && epsg != 0 {
+                    crs.epsg = Some(epsg);
+                }
+                return Some(crs);
+            }
+        }
+        if epsg != 0 {
+            return CoordinateReferenceSystem::from_epsg(epsg).ok();
+        }
+        None
+    }
+
+    pub fn is_geographic(&self) -> bool {
+        self.kind == CrsKind::Geographic
+    }
+
+    pub fn is_projected(&self) -> bool {
+        match self.kind {
+            CrsKind::Projected(_) => true,
+            _ => false,
+        }
+    }
+
+    /// Returns true if coordinates in the two systems are the same, i.e. they are of the
+    /// same kind and units, on the same datum and prime meridian, and, if projected, their
+    /// projections agree to within a millimetre. Names and EPSG codes are not compared,
+    /// so that the WKT of a system written by different software is still recognized.
+    pub fn is_equivalent(&self, other: &CoordinateReferenceSystem) -> bool {
+        if !self.datum.is_equivalent(&other.datum)
+            || (self.prime_meridian - other.prime_meridian).abs() > 1e-9
+        {
+            return false;
+        }
+        match (&self.kind, &other.kind) {
+            (CrsKind::Geographic, CrsKind::Geographic) => {
+                same_factor(self.angular_unit, other.angular_unit)
+            }
+            (CrsKind::Geocentric, CrsKind::Geocentric) => {
+                same_factor(self.linear_unit, other.linear_unit)
+            }
+            (CrsKind::Projected(a), CrsKind::Projected(b)) => {
+                same_factor(self.linear_unit, other.linear_unit)
+                    && same_projection(a, b, &self.datum.ellipsoid)
+            }
+            _ => false,
+        }
+    }
+
+    /// Returns the EPSG code of the system: that given in its WKT or, failing that, the
+    /// code of an equivalent system, preferring one with the same name. None is returned
+    /// if the system does not match any that has an EPSG code.
+    pub fn identify_epsg(&self) -> Option<u16> {
+        if self.epsg.is_some() {
+            return self.epsg;
+        }
+        let name = normalize_name(&self.name);
+        let mut found = None;
+        for (code, crs) in epsg_systems() {
+            if !might_be_equivalent(self, crs) || !self.is_equivalent(crs) {
+                continue;
+            }
+            if normalize_name(&crs.name) == name {
+                return Some(*code);
+            }
+            if found.is_none() {
+                found = Some(*code);
+            }
+        }
+        found
+    }
+}
+
+/// Compares the coordinate reference systems of a tool's input layers as they are read,
+/// so that the tool can warn the user when layers that it combines are in different
+/// systems. Layers with an unknown system are not compared.
+#[derive(Default)]
+pub struct CrsCheck {
+    first: Option<(String, CoordinateReferenceSystem)>,
+    warned: bool,
+}
+
+impl CrsCheck {
+    pub fn new() -> CrsCheck {
+        CrsCheck::default()
+    }
+
+    /// Adds a layer, by its file name and system, returning a warning message the first
+    /// time that a layer's system differs from that of the first layer with a known system.
+    pub fn check(
+        &mut self,
+        layer_name: &str,
+        crs: Option<CoordinateReferenceSystem>,
+    ) -> Option<String> {
+        let crs = crs?;
+        match self.first {
+            None => {
+                self.first = Some((layer_name.to_string(), crs));
+                None
+            }
+            Some((ref first_name, ref first_crs)) => {
+                if self.warned || first_crs.is_equivalent(&crs) {
+                    return None;
+                }
+                self.warned = true;
+                Some(format!(
+                    "The coordinate reference system of {} ({}) differs from that of {} ({}). The input layers may not align.",
+                    layer_name,
+                    describe(&crs),
+                    first_name,
+                    describe(first_crs)
+                ))
+            }
+        }
+    }
+}
+
+/// Describes a system by its name and, where it can be identified, EPSG code.
+fn describe(crs: &CoordinateReferenceSystem) -> String {
+    match crs.identify_epsg() {
+        Some(code) => format!("{}, EPSG:{}", crs.name, code),
+        None => crs.name.clone(),
+    }
+}
+
+/// Returns the systems with EPSG codes, in order of code, which are parsed on first use.
+fn epsg_systems() -> &'static Vec<(u16, CoordinateReferenceSystem)> {
+    static SYSTEMS: OnceLock<Vec<(u16, CoordinateReferenceSystem)>> = OnceLock::new();
+    SYSTEMS.get_or_init(|| {
+        epsg_codes()
+            .into_iter()
+            .filter_map(|code| match CoordinateReferenceSystem::from_epsg(code) {
+                Ok(crs) => Some((code, crs)),
+                Err(_) => None,
+            })
+            .collect()
+    })
+}
+
+/// A quick test that rules out most systems before the full comparison.
+fn might_be_equivalent(a: &CoordinateReferenceSystem, b: &CoordinateReferenceSystem) -> bool {
+    match (&a.kind, &b.kind) {
+        (CrsKind::Geographic, CrsKind::Geographic) | (CrsKind::Geocentric, CrsKind::Geocentric) => {
+            a.datum.ellipsoid.is_equivalent(&b.datum.ellipsoid)
+        }
+        (CrsKind::Projected(pa), CrsKind::Projected(pb)) => {
+            pa.method == pb.method
+                && (pa.parameters.false_easting - pb.parameters.false_easting).abs() < 0.01
+                && (pa.parameters.false_northing - pb.parameters.false_northing).abs() < 0.01
+                && a.datum.ellipsoid.is_equivalent(&b.datum.ellipsoid)
+        }
+        _ => false,
+    }
+}
+
+/// Compares unit conversion factors, which are often written to differing precision.
+fn same_factor(a: f64, b: f64) -> bool {
+    (a - b).abs() <= 1e-10 * a.abs().max(b.abs())
+}
+
+/// Compares two projections on the ellipsoid by projecting a small set of points around
+/// the origin of the first. Projections that cannot be applied are compared by their
+/// parameters instead.
+fn same_projection(a: &Projection, b: &Projection, ellipsoid: &Ellipsoid) -> bool {
+    let (pa, pb) = match (a.projector(ellipsoid), b.projector(ellipsoid)) {
+        (Ok(pa), Ok(pb)) => (pa, pb),
+        _ => return same_parameters(a, b),
+    };
+    let lon0 = a.parameters.central_meridian;
+    let lat0 = a.parameters.latitude_of_origin.max(-80f64).min(80f64);
+    for (dlon, dlat) in [
+        (0.0, 0.0),
+        (-1.0, -1.0),
+        (1.5, 0.5),
+        (2.5, 1.2),
+        (-0.7, 2.0),
+    ] {
+        let lon = (lon0 + dlon).to_radians();
+        let lat = (lat0 + dlat).max(-89f64).min(89f64).to_radians();
+        match (pa.forward(lon, lat), pb.forward(lon, lat)) {
+            (Some((xa, ya)), Some((xb, yb))) => {
+                if (xa - xb).abs() > 0.001 || (ya - yb).abs() > 0.001 {
+                    return false;
+                }
+            }
+            (None, None) => {}
+            _ => return false,
+        }
+    }
+    true
+}
+
+fn unsupported(what: &str) -> Error {
+    Error::new(
+        ErrorKind::InvalidInput,
+        format!(
+            "The coordinate reference system is not supported: {}.",
+            what
+        ),
+    )
+}
+
+fn same_parameters(a: &Projection, b: &Projection) -> bool {
+    let close = |x: f64, y: f64| (x - y).abs() < 1e-9;
+    let close_option = |x: Option<f64>, y: Option<f64>| match (x, y) {
+        (Some(x), Some(y)) => close(x, y),
+        (None, None) => true,
+        _ => false,
+    };
+    let same_method = match (&a.method, &b.method) {
+        (ProjectionMethod::Other(m), ProjectionMethod::Other(n)) => {
+            normalize_name(m) == normalize_name(n)
+        }
+        (m, n) => m == n,
+    };
+    let (p, q) = (&a.parameters, &b.parameters);
+    let mut other_p = p.other.clone();
+    let mut other_q = q.other.clone();
+    other_p.sort_by(|x, y| normalize_name(&x.0).cmp(&normalize_name(&y.0)));
+    other_q.sort_by(|x, y| normalize_name(&x.0).cmp(&normalize_name(&y.0)));
+    same_method
+        && close(p.central_meridian, q.central_meridian)
+        && close(p.latitude_of_origin, q.latitude_of_origin)
+        && close_option(p.standard_parallel_1, q.standard_parallel_1)
+        && close_option(p.standard_parallel_2, q.standard_parallel_2)
+        && close(p.scale_factor, q.scale_factor)
+        && close(p.false_easting, q.false_easting)
+        && close(p.false_northing, q.false_northing)
+        && other_p.len() == other_q.len()
+        && other_p
+            .iter()
+            .zip(other_q.iter())
+            .all(|(x, y)| normalize_name(&x.0) == normalize_name(&y.0) && close(x.1, y.1))
+}
+
+/// Reduces a projection or parameter name to lower-case letters and digits.
+fn normalize_name(name: &str) -> String {
+    name.chars()
+        .filter(|c| c.is_alphanumeric())
+        .collect::<String>()
+        .to_lowercase()
+}
+
+const GEOGRAPHIC_KEYWORDS: [&str; 5] = [
+    "GEOGCS",
+    "GEOGCRS",
+    "GEOGRAPHICCRS",
+    "BASEGEOGCRS",
+    "BASEGEODCRS",
+];
+const GEODETIC_KEYWORDS: [&str; 3] = ["GEOCCS", "GEODCRS", "GEODETICCRS"];
+const PROJECTED_KEYWORDS: [&str; 3] = ["PROJCS", "PROJCRS", "PROJECTEDCRS"];
+
+fn crs_from_node(node: &WktNode) -> Result<CoordinateReferenceSystem, Error> {
+    let keyword = node.keyword.to_uppercase();
+    let mut crs = if GEOGRAPHIC_KEYWORDS.contains(&keyword.as_str())
+        || GEODETIC_KEYWORDS.contains(&keyword.as_str())
+    {
+        geodetic_from_node(node)?
+    } else if PROJECTED_KEYWORDS.contains(&keyword.as_str()) {
+        projected_from_node(node)?
+    } else if keyword == "COMPD_CS" || keyword == "COMPOUNDCRS" {
+        let mut keywords = GEOGRAPHIC_KEYWORDS.to_vec();
+        keywords.extend_from_slice(&GEODETIC_KEYWORDS);
+        keywords.extend_from_slice(&PROJECTED_KEYWORDS);
+        let mut crs = match node.child(&keywords) {
+            Some(horizontal) => crs_from_node(horizontal)?,
+            None => return Err(unsupported("the compound system has no horizontal part")),
+        };
+        crs.epsg = crs.epsg.or(authority_code(node));
+        return Ok(crs);
+    } else if keyword == "BOUNDCRS" {
+        return bound_from_node(node);
+    } else {
+        return Err(unsupported(&format!("{} systems", node.keyword)));
+    };
+    crs.epsg = authority_code(node);
+    Ok(crs)
+}
+
+/// Reads a WKT2 bound system, i.e. a system with a transformation to another, which is
+/// how WKT2 gives the equivalent of the WKT1 TOWGS84 element.
+fn bound_from_node(node: &WktNode) -> Result<CoordinateReferenceSystem, Error> {
+    let source = node
+        .child(&["SOURCECRS"])
+        .and_then(|source| source.children().next())
+        .ok_or_else(|| unsupported("the bound system has no SOURCECRS"))?;
+    let mut crs = crs_from_node(source)?;
+    let target = node
+        .child(&["TARGETCRS"])
+        .and_then(|target| target.children().next())
+        .map(crs_from_node);
+    let transformation = node.child(&["ABRIDGEDTRANSFORMATION"]);
+    if let (Some(Ok(target)), Some(transformation)) = (target, transformation) {
+        if target.datum.is_equivalent(&Datum::wgs84()) {
+            if let Some(params) = helmert_from_node(transformation) {
+                crs.datum.to_wgs84 = Some(params);
+            }
+        }
+    }
+    Ok(crs)
+}
+
+/// Reads the parameters of a geocentric translation or Helmert transformation from a
+/// WKT2 ABRIDGEDTRANSFORMATION element.
+fn helmert_from_node(node: &WktNode) -> Option<HelmertParameters> {
+    let method = normalize_name(node.child(&["METHOD"])?.name()?);
+    if !method.contains("geocentrictranslation")
+        && !method.contains("positionvector")
+        && !method.contains("coordinateframe")
+    {
+        return None;
+    }
+    let mut values = [0f64; 7];
+    for parameter in node.children_named(&["PARAMETER"]) {
+        let value = match parameter.number(1) {
+            Some(value) => value,
+            None => continue,
+        };
+        let index = match normalize_name(parameter.name().unwrap_or("")).as_str() {
+            "xaxistranslation" => 0,
+            "yaxistranslation" => 1,
+            "zaxistranslation" => 2,
+            "xaxisrotation" => 3,
+            "yaxisrotation" => 4,
+            "zaxisrotation" => 5,
+            "scaledifference" => 6,
+            _ => continue,
+        };
+        // Translations are converted to metres, rotations to arc-seconds, and the scale
+        // difference to parts per million, which are also the units assumed when a
+        // parameter has none.
+        let default_factor = match index {
+            0..=2 => 1f64,
+            3..=5 => (1f64 / 3600f64).to_radians(),
+            _ => 1e-6,
+        };
+        let factor = unit_factor(parameter).unwrap_or(default_factor);
+        values[index] = match index {
+            0..=2 => value * factor,
+            3..=5 => (value * factor).to_degrees() * 3600f64,
+            // PROJ writes the scale difference as a unitless factor, i.e. 1 + ds.
+            _ if unit_factor(parameter).is_none() && (value - 1f64).abs() < 1e-4 => {
+                (value - 1f64) * 1e6
+            }
+            _ => value * factor * 1e6,
+        };
+    }
+    let params = HelmertParameters::from_slice(&values)?;
+    if method.contains("coordinateframe") {
+        Some(HelmertParameters::from_coordinate_frame(
+            params.dx, params.dy, params.dz, params.rx, params.ry, params.rz, params.ds,
+        ))
+    } else {
+        Some(params)
+    }
+}
+
+/// Returns the EPSG code of an AUTHORITY (WKT1) or ID (WKT2) element, if there is one.
+fn authority_code(node: &WktNode) -> Option<u16> {
+    let authority = node.child(&["AUTHORITY", "ID"])?;
+    if !authority.name()?.eq_ignore_ascii_case("EPSG") {
+        return None;
+    }
+    match authority.values.get(1)? {
+        WktValue::Number(n) => Some(*n as u16),
+        WktValue::Text(text) => text.trim().parse::<u16>().ok(),
+        _ => None,
+    }
+}
+
+/// Returns the conversion factor of an element's own unit, i.e. the size of the unit in
+/// metres or radians, or of a scale unit, in units of one.
+fn unit_factor(node: &WktNode) -> Option<f64> {
+    node.child(&["UNIT", "ANGLEUNIT", "LENGTHUNIT", "SCALEUNIT"])
+        .and_then(|unit| unit.number(1))
+        .filter(|factor| *factor > 0f64)
+}
+
+/// Returns the conversion factor of a system's coordinate unit, which WKT1 gives as a
+/// UNIT of the system and WKT2 gives either for the system or for each AXIS.
+fn coordinate_unit(node: &WktNode, keywords: &[&str]) -> Option<f64> {
+    let factor = |unit: &WktNode| unit.number(1).filter(|factor| *factor > 0f64);
+    if let Some(unit) = node.child(keywords) {
+        return factor(unit);
+    }
+    node.children_named(&["AXIS"])
+        .find_map(|axis| axis.child(keywords))
+        .and_then(factor)
+}
+
+/// Converts a factor in radians per unit into degrees per unit. The degree is commonly
+/// given to fewer digits than a double holds, and the difference could otherwise move
+/// a latitude of 90 past the pole.
+fn degrees_per_unit(angular_unit: f64) -> f64 {
+    let to_degrees = angular_unit.to_degrees();
+    if (to_degrees - 1f64).abs() < 1e-10 {
+        1f64
+    } else {
+        to_degrees
+    }
+}
+
+/// Reads the datum (with its ellipsoid and TOWGS84) and prime meridian of a geographic
+/// or geocentric system. WKT2 may give a datum ensemble, a terrestrial reference frame,
+/// or a dynamic datum in place of the DATUM.
+fn datum_from_node(node: &WktNode) -> Result<(Datum, f64), Error> {
+    let datum_node = node
+        .child(&["DATUM", "GEODETICDATUM", "TRF", "ENSEMBLE"])
+        .ok_or_else(|| unsupported("it has no DATUM"))?;
+    let spheroid = datum_node
+        .child(&["SPHEROID", "ELLIPSOID"])
+        .ok_or_else(|| unsupported("its datum has no SPHEROID"))?;
+    let ellipsoid_name = spheroid.name().unwrap_or("");
+    let length_unit = unit_factor(spheroid).unwrap_or(1f64);
+    let ellipsoid = match (spheroid.number(1), spheroid.number(2)) {
+        (Some(a), Some(inv_f)) if a > 0f64 => {
+            Ellipsoid::new(ellipsoid_name, a * length_unit, inv_f)
+        }
+        _ => match ellipsoid_from_name(ellipsoid_name) {
+            Some(ellipsoid) => ellipsoid,
+            None => return Err(unsupported("the size of its SPHEROID is not given")),
+        },
+    };
+    let mut datum = Datum::new(datum_node.name().unwrap_or(""), ellipsoid);
+    if let Some(towgs84) = datum_node.child(&["TOWGS84"]) {
+        if let Some(params) = HelmertParameters::from_slice(&towgs84.numbers()) {
+            datum.to_wgs84 = Some(params);
+        }
+    }
+    // WKT1 gives the prime meridian in degrees; WKT2 may give it in another unit.
+    let prime_meridian = match node.child(&["PRIMEM", "PRIMEMERIDIAN"]) {
+        Some(pm) => {
+            let value = pm.number(1).unwrap_or(0f64);
+            match pm
+                .child(&["ANGLEUNIT", "UNIT"])
+                .and_then(|unit| unit.number(1))
+            {
+                Some(factor) if factor > 0f64 => value * degrees_per_unit(factor),
+                _ => value,
+            }
+        }
+        None => 0f64,
+    };
+    Ok((datum, prime_meridian))
+}
+
+/// Reads a geographic system or, if its coordinate system is Cartesian, a geocentric one.
+fn geodetic_from_node(node: &WktNode) -> Result<CoordinateReferenceSystem, Error> {
+    let (datum, prime_meridian) = datum_from_node(node)?;
+    let is_geocentric = node.is("GEOCCS")
+        || ((node.is("GEODCRS") || node.is("GEODETICCRS"))
+            && node
+                .child(&["CS"])
+                .map_or(false, |cs| match cs.values.first() {
+                    Some(WktValue::Keyword(kind)) => kind.eq_ignore_ascii_case("Cartesian"),
+                    _ => false,
+                }));
+    let (kind, angular_unit, linear_unit) = if is_geocentric {
+        (
+            CrsKind::Geocentric,
+            1f64.to_radians(),
+            coordinate_unit(node, &["UNIT", "LENGTHUNIT"]).unwrap_or(1f64),
+        )
+    } else {
+        (
+            CrsKind::Geographic,
+            coordinate_unit(node, &["UNIT", "ANGLEUNIT"]).unwrap_or(1f64.to_radians()),
+            1f64,
+        )
+    };
+    Ok(CoordinateReferenceSystem {
+        name: node.name().unwrap_or("").to_string(),
+        kind: kind,
+        datum: datum,
+        prime_meridian: prime_meridian,
+        angular_unit: angular_unit,
+        linear_unit: linear_unit,
+        epsg: None,
+    })
+}
+
+fn projected_from_node(node: &WktNode) -> Result<CoordinateReferenceSystem, Error> {
+    let base_node = node
+        .child(&GEOGRAPHIC_KEYWORDS)
+        .or_else(|| node.child(&GEODETIC_KEYWORDS))
+        .ok_or_else(|| unsupported("the projected system has no GEOGCS"))?;
+    let base = geodetic_from_node(base_node)?;
+    let linear_unit = coordinate_unit(node, &["UNIT", "LENGTHUNIT"]).unwrap_or(1f64);
+    // WKT1 gives the PROJECTION and its PARAMETERs within the system; WKT2 gives them
+    // as the METHOD and PARAMETERs of a CONVERSION.
+    let conversion = node
+        .child(&["CONVERSION", "DERIVINGCONVERSION"])
+        .unwrap_or(node);
+    let projection_name = conversion
+        .child(&["PROJECTION", "METHOD"])
+        .and_then(|p| p.name())
+        .ok_or_else(|| unsupported("the projected system has no PROJECTION"))?;
+
+    let mut method = match normalize_name(projection_name).as_str() {
+        "transversemercator" | "gausskruger" => ProjectionMethod::TransverseMercator,
+        "lambertconformalconic"
+        | "lambertconformalconic1sp"
+        | "lambertconformalconic2sp"
+        | "lambertconicconformal1sp"
+        | "lambertconicconformal2sp" => ProjectionMethod::LambertConformalConic,
+        "albers" | "albersconicequalarea" | "albersequalarea" => ProjectionMethod::AlbersEqualArea,
+        "polarstereographic"
+        | "stereographicnorthpole"
+        | "stereographicsouthpole"
+        | "polarstereographicvarianta"
+        | "polarstereographicvariantb"
+        | "stereographic" => ProjectionMethod::PolarStereographic,
+        "mercator" | "mercator1sp" | "mercator2sp" | "mercatorvarianta" | "mercatorvariantb" => {
+            ProjectionMethod::Mercator
+        }
+        "mercatorauxiliarysphere" | "popularvisualisationpseudomercator" | "googlemercator" => {
+            ProjectionMethod::WebMercator
+        }
+        _ => ProjectionMethod::Other(projection_name.to_string()),
+    };
+
+    // Angular parameters are in the geographic system's unit, and the false easting and
+    // northing in the projected system's unit, unless WKT2 gives a parameter its own.
+    let to_degrees = degrees_per_unit(base.angular_unit);
+    let mut params = ProjectionParameters::default();
+    for parameter in conversion.children_named(&["PARAMETER"]) {
+        let value = match parameter.number(1) {
+            Some(value) => value,
+            None => continue,
+        };
+        let own_unit = unit_factor(parameter);
+        let angle = value * own_unit.map_or(to_degrees, degrees_per_unit);
+        let length = value * own_unit.unwrap_or(linear_unit);
+        let parameter_name = parameter.name().unwrap_or("");
+        match normalize_name(parameter_name).as_str() {
+            "falseeasting" | "eastingatfalseorigin" => params.false_easting = length,
+            "falsenorthing" | "northingatfalseorigin" => params.false_northing = length,
+            "centralmeridian"
+            | "longitudeofcenter"
+            | "longitudeoforigin"
+            | "longitudeofnaturalorigin"
+            | "longitudeoffalseorigin"
+            | "straightverticallongitudefrompole" => params.central_meridian = angle,
+            "latitudeoforigin"
+            | "latitudeofcenter"
+            | "latitudeofnaturalorigin"
+            | "latitudeoffalseorigin" => params.latitude_of_origin = angle,
+            "standardparallel1"
+            | "latitudeof1ststandardparallel"
+            | "latitudeofstandardparallel" => params.standard_parallel_1 = Some(angle),
+            "standardparallel2" | "latitudeof2ndstandardparallel" => {
+                params.standard_parallel_2 = Some(angle)
+            }
+            "scalefactor" | "scalefactoratnaturalorigin" => {
+                params.scale_factor = value * own_unit.unwrap_or(1f64)
+            }
+            _ => params.other.push((parameter_name.to_string(), value)),
+        }
+    }
+
+    match normalize_name(projection_name).as_str() {
+        "stereographicnorthpole" => {
+            params.latitude_of_origin = 90f64;
+            params.standard_parallel_1 = params.standard_parallel_1.map(|sp| sp.abs());
+        }
+        "stereographicsouthpole" => {
+            params.latitude_of_origin = -90f64;
+            params.standard_parallel_1 = params.standard_parallel_1.map(|sp| -sp.abs());
+        }
+        "polarstereographic" | "polarstereographicvariantb"
+            if params.standard_parallel_1.is_none()
+                && (params.latitude_of_origin.abs() - 90f64).abs() > 1e-10 =>
+        {
+            // GDAL gives the standard parallel of variant B as the latitude of origin.
+            params.standard_parallel_1 = Some(params.latitude_of_origin);
+        }
+        "stereographic" if (params.latitude_of_origin.abs() - 90f64).abs() > 1e-10 => {
+            // an oblique stereographic projection
+            method = ProjectionMethod::Other(projection_name.to_string());
+        }
+        _ => {}
+    }
+
+    // OGC WKT describes the web Mercator projection as Mercator on a sphere, which can
+    // be recognized by its name or its PROJ extension.
+    if method == ProjectionMethod::Mercator {
+        let name = normalize_name(node.name().unwrap_or(""));
+        let proj4 = node
+            .child(&["EXTENSION"])
+            .and_then(|extension| match extension.values.get(1) {
+                Some(WktValue::Text(text)) => Some(text.clone()),
+                _ => None,
+            })
+            .unwrap_or_default();
+        if name.contains("pseudomercator")
+            || name.contains("webmercator")
+            || (proj4.contains("+a=6378137") && proj4.contains("+b=6378137"))
+        {
+            method = ProjectionMethod::WebMercator;
+        }
+    }
+
+    Ok(CoordinateReferenceSystem {
+        name: node.name().unwrap_or("").to_string(),
+        kind: CrsKind::Projected(Projection::new(method, params)),
+        datum: base.datum,
+        prime_meridian: base.prime_meridian,
+        angular_unit: base.angular_unit,
+        linear_unit: linear_unit,
+        epsg: None,
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::super::transformation::CoordinateTransformation;
+    use super::*;
+
+    const UTM17N_WKT2: &str = "PROJCRS[\"WGS 84 / UTM zone 17N\",BASEGEOGCRS[\"WGS 84\",ENSEMBLE[\"World Geodetic System 1984 ensemble\",MEMBER[\"World Geodetic System 1984 (Transit)\"],MEMBER[\"World Geodetic System 1984 (G730)\"],ELLIPSOID[\"WGS 84\",6378137,298.257223563,LENGTHUNIT[\"metre\",1]],ENSEMBLEACCURACY[2.0]],PRIMEM[\"Greenwich\",0,ANGLEUNIT[\"degree\",0.0174532925199433]],ID[\"EPSG\",4326]],CONVERSION[\"UTM zone 17N\",METHOD[\"Transverse Mercator\",ID[\"EPSG\",9807]],PARAMETER[\"Latitude of natural origin\",0,ANGLEUNIT[\"degree\",0.0174532925199433],ID[\"EPSG\",8801]],PARAMETER[\"Longitude of natural origin\",-81,ANGLEUNIT[\"degree\",0.0174532925199433],ID[\"EPSG\",8802]],PARAMETER[\"Scale factor at natural origin\",0.9996,SCALEUNIT[\"unity\",1],ID[\"EPSG\",8805]],PARAMETER[\"False easting\",500000,LENGTHUNIT[\"metre\",1],ID[\"EPSG\",8806]],PARAMETER[\"False northing\",0,LENGTHUNIT[\"metre\",1],ID[\"EPSG\",8807]]],CS[Cartesian,2],AXIS[\"(E)\",east,ORDER[1],LENGTHUNIT[\"metre\",1]],AXIS[\"(N)\",north,ORDER[2],LENGTHUNIT[\"metre\",1]],USAGE[SCOPE[\"Navigation and medium accuracy spatial referencing.\"],AREA[\"Between 84°W and 78°W, northern hemisphere between equator and 84°N, onshore and offshore.\"],BBOX[0,-84,84,-78]]";
+
+    #[test]
+    fn test_wkt2_projected() {
+        let wkt = format!("{},ID[\"EPSG\",32617]]", UTM17N_WKT2);
+        let crs = CoordinateReferenceSystem::from_wkt(&wkt).unwrap();
+        assert_eq!(crs.epsg, Some(32617));
+        assert_eq!(crs.linear_unit, 1f64);
+        match crs.kind {
+            CrsKind::Projected(ref projection) => {
+                assert_eq!(projection.method, ProjectionMethod::TransverseMercator);
+                assert_eq!(projection.parameters.central_meridian, -81f64);
+                assert_eq!(projection.parameters.scale_factor, 0.9996);
+                assert_eq!(projection.parameters.false_easting, 500000f64);
+            }
+            _ => panic!("expected a projected system"),
+        }
+        let esri = CoordinateReferenceSystem::from_epsg(32617).unwrap();
+        assert!(crs.is_equivalent(&esri));
+    }
+
+    #[test]
+    fn test_wkt2_geocentric_and_bound() {
+        let geocentric = CoordinateReferenceSystem::from_wkt("GEODCRS[\"WGS 84\",DATUM[\"World Geodetic System 1984\",ELLIPSOID[\"WGS 84\",6378137,298.257223563,LENGTHUNIT[\"metre\",1]]],PRIMEM[\"Greenwich\",0,ANGLEUNIT[\"degree\",0.0174532925199433]],CS[Cartesian,3],AXIS[\"(X)\",geocentricX,ORDER[1],LENGTHUNIT[\"metre\",1]],AXIS[\"(Y)\",geocentricY,ORDER[2],LENGTHUNIT[\"metre\",1]],AXIS[\"(Z)\",geocentricZ,ORDER[3],LENGTHUNIT[\"metre\",1]],ID[\"EPSG\",4978]]").unwrap();
+        assert_eq!(geocentric.kind, CrsKind::Geocentric);
+        assert_eq!(geocentric.epsg, Some(4978));
+
+        let bound = CoordinateReferenceSystem::from_wkt("BOUNDCRS[SOURCECRS[GEOGCRS[\"unknown\",DATUM[\"Unknown based on Airy 1830 ellipsoid\",ELLIPSOID[\"Airy 1830\",6377563.396,299.3249646,LENGTHUNIT[\"metre\",1]]],PRIMEM[\"Greenwich\",0,ANGLEUNIT[\"degree\",0.0174532925199433]],CS[ellipsoidal,2],AXIS[\"longitude\",east,ORDER[1],ANGLEUNIT[\"degree\",0.0174532925199433]],AXIS[\"latitude\",north,ORDER[2],ANGLEUNIT[\"degree\",0.0174532925199433]]]],TARGETCRS[GEOGCRS[\"WGS 84\",DATUM[\"World Geodetic System 1984\",ELLIPSOID[\"WGS 84\",6378137,298.257223563,LENGTHUNIT[\"metre\",1]]],PRIMEM[\"Greenwich\",0,ANGLEUNIT[\"degree\",0.0174532925199433]],CS[ellipsoidal,2],AXIS[\"latitude\",north,ORDER[1],ANGLEUNIT[\"degree\",0.0174532925199433]],AXIS[\"longitude\",east,ORDER[2],ANGLEUNIT[\"degree\",0.0174532925199433]],ID[\"EPSG\",4326]]],ABRIDGEDTRANSFORMATION[\"Transformation from unknown to WGS84\",METHOD[\"Position Vector transformation (geog2D domain)\",ID[\"EPSG\",9606]],PARAMETER[\"X-axis translation\",446.448,ID[\"EPSG\",8605]],PARAMETER[\"Y-axis translation\",-125.157,ID[\"EPSG\",8606]],PARAMETER[\"Z-axis translation\",542.06,ID[\"EPSG\",8607]],PARAMETER[\"X-axis rotation\",0.15,ID[\"EPSG\",8608]],PARAMETER[\"Y-axis rotation\",0.247,ID[\"EPSG\",8609]],PARAMETER[\"Z-axis rotation\",0.842,ID[\"EPSG\",8610]],PARAMETER[\"Scale difference\",0.999979511,ID[\"EPSG\",8611]]]]").unwrap();
+        assert!(bound.is_geographic());
+        let params = bound.datum.to_wgs84.unwrap();
+        assert!((params.dx - 446.448).abs() < 1e-9);
+        assert!((params.rz - 0.842).abs() < 1e-9);
+        assert!((params.ds + 20.489).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_equivalence_across_wkt_flavours() {
+        let ogc = CoordinateReferenceSystem::from_wkt("PROJCS[\"NAD83 / UTM zone 17N\",GEOGCS[\"NAD83\",DATUM[\"North_American_Datum_1983\",SPHEROID[\"GRS 1980\",6378137,298.257222101,AUTHORITY[\"EPSG\",\"7019\"]],TOWGS84[0,0,0,0,0,0,0],AUTHORITY[\"EPSG\",\"6269\"]],PRIMEM[\"Greenwich\",0,AUTHORITY[\"EPSG\",\"8901\"]],UNIT[\"degree\",0.0174532925199433,AUTHORITY[\"EPSG\",\"9122\"]],AUTHORITY[\"EPSG\",\"4269\"]],PROJECTION[\"Transverse_Mercator\"],PARAMETER[\"latitude_of_origin\",0],PARAMETER[\"central_meridian\",-81],PARAMETER[\"scale_factor\",0.9996],PARAMETER[\"false_easting\",500000],PARAMETER[\"false_northing\",0],UNIT[\"metre\",1,AUTHORITY[\"EPSG\",\"9001\"]],AXIS[\"Easting\",EAST],AXIS[\"Northing\",NORTH]]").unwrap();
+        let esri = CoordinateReferenceSystem::from_epsg(26917).unwrap();
+        assert!(ogc.is_equivalent(&esri));
+        // NAD83 coincides with WGS84 but is not the same system
+        let wgs84 = CoordinateReferenceSystem::from_epsg(32617).unwrap();
+        assert!(!ogc.is_equivalent(&wgs84));
+        // a different zone
+        let zone18 = CoordinateReferenceSystem::from_epsg(26918).unwrap();
+        assert!(!ogc.is_equivalent(&zone18));
+        assert_eq!(ogc.identify_epsg(), Some(26917));
+    }
+
+    #[test]
+    fn test_identify_epsg() {
+        let wkt2 = format!("{}]", UTM17N_WKT2);
+        let crs = CoordinateReferenceSystem::from_wkt(&wkt2).unwrap();
+        assert_eq!(crs.epsg, None);
+        assert_eq!(crs.identify_epsg(), Some(32617));
+
+        let mut geographic = CoordinateReferenceSystem::wgs84();
+        geographic.epsg = None;
+        assert_eq!(geographic.identify_epsg(), Some(4326));
+    }
+
+    #[test]
+    fn test_crs_check() {
+        let mut check = CrsCheck::new();
+        assert!(check
+            .check("a.tif", CoordinateReferenceSystem::from_epsg(26917).ok())
+            .is_none());
+        assert!(check.check("b.tif", None).is_none());
+        assert!(check
+            .check("c.tif", CoordinateReferenceSystem::from_epsg(26917).ok())
+            .is_none());
+        let warning = check.check("d.tif", CoordinateReferenceSystem::from_epsg(32617).ok());
+        assert!(warning.unwrap().contains("EPSG:32617"));
+        // only the first difference is reported
+        assert!(check
+            .check("e.tif", CoordinateReferenceSystem::from_epsg(4326).ok())
+            .is_none());
+    }
+
+    #[test]
+    fn test_unsupported_projection_is_kept() {
+        let crs = CoordinateReferenceSystem::from_epsg(28992).unwrap();
+        match crs.kind {
+            CrsKind::Projected(ref projection) => match projection.method {
+                ProjectionMethod::Other(ref name) => assert_eq!(name, "Double_Stereographic"),
+                _ => panic!("expected an unsupported projection"),
+            },
+            _ => panic!("expected a projected system"),
+        }
+        assert!(crs.is_equivalent(&CoordinateReferenceSystem::from_epsg(28992).unwrap()));
+        assert!(CoordinateTransformation::new(&crs, &CoordinateReferenceSystem::wgs84()).is_err());
+    }
+}
@@ -0,0 +1,355 @@
+/*
+This code is part of the WhiteboxTools geospatial analysis library.
+License: MIT
+*/
+use super::ellipsoid::Ellipsoid;
+use crate::na::{Matrix3, Vector3};
+
+/// The seven parameters of a Helmert transformation from a datum to WGS84, using the
+/// position vector convention of the WKT TOWGS84 element, i.e. translations in metres,
+/// rotations in arc-seconds, and the scale difference in parts per million.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct HelmertParameters {
+    pub dx: f64,
+    pub dy: f64,
+    pub dz: f64,
+    pub rx: f64,
+    pub ry: f64,
+    pub rz: f64,
+    pub ds: f64,
+}
+
+impl HelmertParameters {
+    pub fn new(dx: f64, dy: f64, dz: f64, rx: f64, ry: f64, rz: f64, ds: f64) -> Self {
+        HelmertParameters {
+            dx: dx,
+            dy: dy,
+            dz: dz,
+            rx: rx,
+            ry: ry,
+            rz: rz,
+            ds: ds,
+        }
+    }
+
+    /// A three-parameter (geocentric translation) transformation.
+    pub fn translation(dx: f64, dy: f64, dz: f64) -> Self {
+        HelmertParameters::new(dx, dy, dz, 0f64, 0f64, 0f64, 0f64)
+    }
+
+    /// Creates the parameters from the three or seven values of a TOWGS84 element.
+    pub fn from_slice(values: &[f64]) -> Option<Self> {
+        match values.len() {
+            3 => Some(HelmertParameters::translation(
+                values[0], values[1], values[2],
+            )),
+            7 => Some(HelmertParameters::new(
+                values[0], values[1], values[2], values[3], values[4], values[5], values[6],
+            )),
+            _ => None,
+        }
+    }
+
+    /// Creates the parameters from values given in the coordinate frame rotation
+    /// convention (e.g. EPSG method 9607), which differs only in the sign of the rotations.
+    pub fn from_coordinate_frame(
+        dx: f64,
+        dy: f64,
+        dz: f64,
+        rx: f64,
+        ry: f64,
+        rz: f64,
+        ds: f64,
+    ) -> Self {
+        HelmertParameters::new(dx, dy, dz, -rx, -ry, -rz, ds)
+    }
+
+    pub fn is_identity(&self) -> bool {
+        *self == HelmertParameters::default()
+    }
+
+    pub fn to_vec(&self) -> Vec<f64> {
+        vec![
+            self.dx, self.dy, self.dz, self.rx, self.ry, self.rz, self.ds,
+        ]
+    }
+
+    fn rotation_matrix(&self) -> Matrix3<f64> {
+        let sec_to_rad = std::f64::consts::PI / (180f64 * 3600f64);
+        let (rx, ry, rz) = (
+            self.rx * sec_to_rad,
+            self.ry * sec_to_rad,
+            self.rz * sec_to_rad,
+        );
+        let scale = 1f64 + self.ds * 1e-6;
+        Matrix3::new(1f64, -rz, ry, rz, 1f64, -rx, -ry, rx, 1f64) * scale
+    }
+}
+
+/// A Helmert transformation between geocentric coordinates, precomputed for repeated use.
+#[derive(Clone, Debug)]
+pub(crate) struct Helmert {
+    translation: Vector3<f64>,
+    matrix: Matrix3<f64>,
+    inverse_matrix: Matrix3<f64>,
+}
+
+impl Helmert {
+    pub(crate) fn new(params: &HelmertParameters) -> Helmert {
+        let matrix = params.rotation_matrix();
+        Helmert {
+            translation: Vector3::new(params.dx, params.dy, params.dz),
+            matrix: matrix,
+            inverse_matrix: matrix.try_inverse().unwrap_or_else(Matrix3::identity),
+        }
+    }
+
+    /// Transforms geocentric coordinates on the datum to WGS84.
+    pub(crate) fn to_wgs84(&self, x: f64, y: f64, z: f64) -> (f64, f64, f64) {
+        let v = self.translation + self.matrix * Vector3::new(x, y, z);
+        (v.x, v.y, v.z)
+    }
+
+    /// Transforms geocentric WGS84 coordinates to the datum; the exact inverse of `to_wgs84`.
+    pub(crate) fn from_wgs84(&self, x: f64, y: f64, z: f64) -> (f64, f64, f64) {
+        let v = self.inverse_matrix * (Vector3::new(x, y, z) - self.translation);
+        (v.x, v.y, v.z)
+    }
+}
+
+/// A geodetic datum: an ellipsoid and, where known, the Helmert transformation that
+/// relates it to WGS84.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Datum {
+    pub name: String,
+    pub ellipsoid: Ellipsoid,
+    pub to_wgs84: Option<HelmertParameters>,
+}
+
+impl Datum {
+    /// Creates a datum, looking up its transformation to WGS84 by name if it is one of
+    /// the commonly used datums.
+    pub fn new(name: &str, ellipsoid: Ellipsoid) -> Datum {
+        Datum {
+            name: name.to_string(),
+            ellipsoid: ellipsoid,
+            to_wgs84: datum_shift_from_name(name),
+        }
+    }
+
+    pub fn wgs84() -> Datum {
+        Datum::new("WGS_1984", Ellipsoid::wgs84())
+    }
+
+    /// Returns true if the two are the same datum, i.e. they share an ellipsoid and
+    /// either their names, allowing for common abbreviations (e.g. 'D_WGS_1984' and
+    /// 'World Geodetic System 1984'), or their transformations to WGS84 match. Datums
+    /// that merely coincide with WGS84, such as NAD83, are not the same as WGS84.
+    pub fn is_equivalent(&self, other: &Datum) -> bool {
+        if !self.ellipsoid.is_equivalent(&other.ellipsoid) {
+            return false;
+        }
+        let name = canonical_datum_name(&self.name);
+        let other_name = canonical_datum_name(&other.name);
+        if !name.is_empty() && name == other_name {
+            return true;
+        }
+        match (&self.to_wgs84, &other.to_wgs84) {
+            (Some(a), Some(b)) => a == b && !a.is_identity(),
+            _ => false,
+        }
+    }
+
+    /// Returns true if coordinates on the two datums can be used interchangeably when
+    /// transforming between them, i.e. they share an ellipsoid and there is no known
+    /// difference between their transformations to WGS84.
+    pub(crate) fn is_interchangeable(&self, other: &Datum) -> bool {
+        if !self.ellipsoid.is_equivalent(&other.ellipsoid) {
+            return false;
+        }
+        match (&self.to_wgs84, &other.to_wgs84) {
+            (Some(a), Some(b)) => a == b,
+            _ => true,
+        }
+    }
+}
+
+/// Reduces a datum name to a canonical form for comparison, dropping the 'D_' prefix of
+/// Esri names, case, and punctuation, e.g. 'D_North_American_1983' and
+/// 'North American Datum 1983' both become 'northamerican1983'.
+pub(crate) fn normalize_datum_name(name: &str) -> String {
+    let name = name.trim();
+    let name = if name.starts_with("D_") || name.starts_with("d_") {
+        &name[2..]
+    } else {
+        name
+    };
+    name.to_lowercase()
+        .replace("datum", "")
+        .chars()
+        .filter(|c| c.is_alphanumeric())
+        .collect()
+}
+
+/// Reduces a datum name to the form used to compare datums, which also expands the
+/// common abbreviations of names, e.g. 'NAD83' and 'North American Datum 1983' both
+/// become 'nad1983'.
+pub(crate) fn canonical_datum_name(name: &str) -> String {
+    let mut name = normalize_datum_name(name)
+        .replace("worldgeodeticsystem", "wgs")
+        .replace("northamerican", "nad")
+        .replace("europeanterrestrialreferencesystem", "etrs")
+        .replace("geocentricofaustralia", "gda")
+        .replace("ordnancesurveyofgreatbritain", "osgb")
+        .replace("ensemble", "");
+    for (short, long) in [
+        ("wgs84", "wgs1984"),
+        ("wgs72", "wgs1972"),
+        ("nad83", "nad1983"),
+        ("nad27", "nad1927"),
+        ("etrs89", "etrs1989"),
+        ("gda94", "gda1994"),
+        ("ed50", "european1950"),
+    ] {
+        if name.starts_with(short) {
+            name = format!("{}{}", long, &name[short.len()..]);
+        }
+    }
+    name
+}
+
+/// Returns the transformation to WGS84 of commonly used datums, by name. Datums realized
+/// from, or practically coincident with, WGS84 (e.g. NAD83, ETRS89, GDA94) have a zero
+/// transformation. Where several transformations exist for a datum, that used by
+/// default in common GIS software is chosen; these are generally accurate to a few metres.
+pub fn datum_shift_from_name(name: &str) -> Option<HelmertParameters> {
+    shift_from_key(&normalize_datum_name(name))
+        .or_else(|| shift_from_key(&canonical_datum_name(name)))
+}
+
+fn shift_from_key(key: &str) -> Option<HelmertParameters> {
+    let zero = HelmertParameters::default();
+    let t = HelmertParameters::translation;
+    let pv = HelmertParameters::new;
+    let params = match key {
+        "wgs1984"
+        | "wgs84"
+        | "worldgeodeticsystem1984"
+        | "northamerican1983"
+        | "nad83"
+        | "northamerican1983harn"
+        | "northamerican1983csrs"
+        | "nad1983harn"
+        | "nad1983csrs"
+        | "nad19832011"
+        | "nad83harn"
+        | "nad83csrs"
+        | "nad832011"
+        | "etrs1989"
+        | "etrs89"
+        | "europeanterrestrialreferencesystem1989"
+        | "gda1994"
+        | "gda94"
+        | "gda2020"
+        | "geocentricofaustralia1994"
+        | "geocentricofaustralia2020"
+        | "nzgd2000"
+        | "newzealandgeodetic2000"
+        | "sirgas2000"
+        | "sistemadereferenciageocentricoparalasamericas2000"
+        | "jgd2000"
+        | "jgd2011"
+        | "japanesegeodetic2000"
+        | "japanesegeodetic2011"
+        | "korea2000"
+        | "china2000"
+        | "chinageodeticcoordinatesystem2000"
+        | "hartebeesthoek1994"
+        | "hartebeesthoek94"
+        | "chtrf95"
+        | "swissterrestrialreferenceframe1995"
+        | "rgf1993"
+        | "rgf93"
+        | "reseaugeodesiquefrancais1993"
+        | "itrf2000"
+        | "itrf2008"
+        | "itrf2014"
+        | "nad1983nsrs2007"
+        | "nad1983cors96"
+        | "nad1983pa11"
+        | "nad1983ma11"
+        | "sweref99"
+        | "sirgas"
+        | "greenland1996"
+        | "turkishnationalreferenceframe"
+        | "posgar1994"
+        | "posgar1998"
+        | "posgar2007"
+        | "retedinamicanazionale2008"
+        | "mexicoitrf92"
+        | "mexicoitrf2008"
+        | "geodesinasional1995"
+        | "libyangeodetic2006"
+        | "reseaugeodesiquedelardc2005" => zero,
+        "wgs1972" | "wgs72" | "worldgeodeticsystem1972" => {
+            pv(0.0, 0.0, 4.5, 0.0, 0.0, 0.554, 0.2263)
+        }
+        "northamerican1927" | "nad27" => t(-8.0, 160.0, 176.0),
+        "osgb1936" | "ordnancesurveyofgreatbritain1936" => {
+            pv(446.448, -125.157, 542.06, 0.15, 0.247, 0.842, -20.489)
+        }
+        "european1950" | "ed50" => t(-87.0, -98.0, -121.0),
+        "european1979" | "ed79" => t(-86.0, -98.0, -119.0),
+        "deutscheshauptdreiecksnetz" | "dhdn" => pv(598.1, 73.7, 418.2, 0.202, 0.045, -2.455, 6.7),
+        "militargeographischeinstitut" | "mgi" => {
+            pv(577.326, 90.129, 463.919, 5.137, 1.474, 5.297, 2.4232)
+        }
+        "amersfoort" => pv(
+            565.2369, 50.0087, 465.658, -0.406857, 0.350733, -1.87035, 4.0812,
+        ),
+        "belge1972" | "reseaunationalbelge1972" => pv(
+            -106.8686, 52.2978, -103.7239, 0.3366, -0.457, 1.8422, -1.2747,
+        ),
+        "ch1903" | "ch1903plus" => t(674.374, 15.056, 405.346),
+        "nouvelletriangulationfrancaise" | "nouvelletriangulationfrancaiseparis" | "ntf" => {
+            t(-168.0, -60.0, 320.0)
+        }
+        "montemario" | "rome1940" => pv(-104.1, -49.1, -9.9, 0.971, -2.917, 0.714, -11.68),
+        "ireland1965" | "tm65" => pv(482.5, -130.6, 564.6, -1.042, -0.214, -0.631, 8.15),
+        "hungarian1972" => t(52.17, -71.82, -14.9),
+        "sjtsk" | "systemjednotnetrigonometrickesitekatastralni" => t(589.0, 76.0, 480.0),
+        "pulkovo1942" => t(23.92, -141.27, -80.9),
+        "australiangeodetic1966" | "australian1966" | "agd1966" | "agd66" => {
+            pv(-117.808, -51.681, 137.784, 0.303, 0.446, 0.234, -0.29)
+        }
+        "australiangeodetic1984" | "australian1984" | "agd1984" | "agd84" => {
+            pv(-117.763, -51.51, 139.061, 0.292, 0.443, 0.277, -0.191)
+        }
+        "newzealand1949" | "nzgd1949" => pv(59.47, -5.04, 187.44, 0.47, -0.1, 1.024, -4.5993),
+        "pulkovo1995" => pv(24.47, -130.89, -81.56, 0.0, 0.0, 0.13, -0.22),
+        "tokyo" => t(-146.414, 507.337, 680.507),
+        "beijing1954" => t(15.8, -154.4, -82.3),
+        "southamerican1969" | "sad69" => t(-57.0, 1.0, -41.0),
+        "provisionalsamerican1956" | "provisionalsouthamerican1956" | "psad56" => {
+            t(-288.0, 175.0, -376.0)
+        }
+        "bogota" | "bogota1975" => t(307.0, 304.0, -318.0),
+        "campoinchauspe" => t(-148.0, 136.0, 90.0),
+        "indonesian1974" => t(-24.0, -15.0, 5.0),
+        "nordsahara1959" => t(-186.0, -93.0, 310.0),
+        "ainelabd1970" => t(-143.0, -236.0, 7.0),
+        "batavia" => t(-377.0, 681.0, -50.0),
+        "kalianpur1975" => t(295.0, 736.0, 257.0),
+        "hongkong1980" => pv(
+            -162.619, -276.959, -161.764, 0.067753, -2.243649, -1.158827, -1.094246,
+        ),
+        "indian1975" => t(210.0, 814.0, 289.0),
+        "kertau" => t(-11.0, 851.0, 5.0),
+        "arc1960" => t(-160.0, -6.0, -302.0),
+        "cape" => t(-136.0, -108.0, -292.0),
+        "oldhawaiian" => t(61.0, -285.0, -181.0),
+        "puertorico" => t(11.0, 72.0, -101.0),
+        _ => return None,
+    };
+    Some(params)
+}
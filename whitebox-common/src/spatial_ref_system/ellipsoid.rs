@@ -0,0 +1,162 @@
+/*
+This code is part of the WhiteboxTools geospatial analysis library.
+License: MIT
+*/
+
+/// An ellipsoid of revolution, defined by its semi-major axis (in metres) and its
+/// inverse flattening. A sphere has an inverse flattening of zero.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Ellipsoid {
+    pub name: String,
+    pub semi_major_axis: f64,
+    pub inverse_flattening: f64,
+}
+
+impl Ellipsoid {
+    pub fn new(name: &str, semi_major_axis: f64, inverse_flattening: f64) -> Ellipsoid {
+        Ellipsoid {
+            name: name.to_string(),
+            semi_major_axis: semi_major_axis,
+            inverse_flattening: inverse_flattening,
+        }
+    }
+
+    pub fn wgs84() -> Ellipsoid {
+        Ellipsoid::new("WGS_1984", 6378137.0, 298.257223563)
+    }
+
+    pub fn grs80() -> Ellipsoid {
+        Ellipsoid::new("GRS_1980", 6378137.0, 298.257222101)
+    }
+
+    /// Returns the flattening, (a - b) / a.
+    pub fn flattening(&self) -> f64 {
+        if self.inverse_flattening == 0f64 {
+            0f64
+        } else {
+            1f64 / self.inverse_flattening
+        }
+    }
+
+    /// Returns the semi-minor axis, in metres.
+    pub fn semi_minor_axis(&self) -> f64 {
+        self.semi_major_axis * (1f64 - self.flattening())
+    }
+
+    /// Returns the square of the first eccentricity.
+    pub fn eccentricity_squared(&self) -> f64 {
+        let f = self.flattening();
+        f * (2f64 - f)
+    }
+
+    /// Returns the first eccentricity.
+    pub fn eccentricity(&self) -> f64 {
+        self.eccentricity_squared().sqrt()
+    }
+
+    pub fn is_sphere(&self) -> bool {
+        self.inverse_flattening == 0f64
+    }
+
+    /// Returns true if the two ellipsoids have the same shape and size, to within a
+    /// millimetre, regardless of their names.
+    pub fn is_equivalent(&self, other: &Ellipsoid) -> bool {
+        (self.semi_major_axis - other.semi_major_axis).abs() < 0.001
+            && (self.semi_minor_axis() - other.semi_minor_axis()).abs() < 0.001
+    }
+
+    /// Converts geodetic coordinates (longitude and latitude in radians, and the height
+    /// above the ellipsoid in metres) to geocentric (earth-centred, earth-fixed) X, Y, Z
+    /// coordinates, in metres.
+    pub fn geodetic_to_geocentric(&self, lon: f64, lat: f64, h: f64) -> (f64, f64, f64) {
+        let a = self.semi_major_axis;
+        let e2 = self.eccentricity_squared();
+        let (sin_lat, cos_lat) = lat.sin_cos();
+        // the radius of curvature in the prime vertical
+        let nu = a / (1f64 - e2 * sin_lat * sin_lat).sqrt();
+        (
+            (nu + h) * cos_lat * lon.cos(),
+            (nu + h) * cos_lat * lon.sin(),
+            ((1f64 - e2) * nu + h) * sin_lat,
+        )
+    }
+
+    /// Converts geocentric X, Y, Z coordinates (in metres) to geodetic longitude and
+    /// latitude, in radians, and height above the ellipsoid, in metres.
+    pub fn geocentric_to_geodetic(&self, x: f64, y: f64, z: f64) -> (f64, f64, f64) {
+        let a = self.semi_major_axis;
+        let b = self.semi_minor_axis();
+        let e2 = self.eccentricity_squared();
+        let p = (x * x + y * y).sqrt();
+        let lon = y.atan2(x);
+        if p < 1e-9 {
+            // on the polar axis
+            let lat = if z >= 0f64 {
+                std::f64::consts::FRAC_PI_2
+            } else {
+                -std::f64::consts::FRAC_PI_2
+            };
+            return (lon, lat, z.abs() - b);
+        }
+
+        // Bowring's formula gives a good first estimate, which is then refined by
+        // iteration to well below a millimetre.
+        let ep2 = (a * a - b * b) / (b * b);
+        let theta = (z * a).atan2(p * b);
+        let (sin_t, cos_t) = theta.sin_cos();
+        let mut lat = (z + ep2 * b * sin_t.powi(3)).atan2(p - e2 * a * cos_t.powi(3));
+        for _ in 0..5 {
+            let sin_lat = lat.sin();
+            let nu = a / (1f64 - e2 * sin_lat * sin_lat).sqrt();
+            let h = p / lat.cos() - nu;
+            let new_lat = z.atan2(p * (1f64 - e2 * nu / (nu + h)));
+            if (new_lat - lat).abs() < 1e-14 {
+                lat = new_lat;
+                break;
+            }
+            lat = new_lat;
+        }
+        let sin_lat = lat.sin();
+        let nu = a / (1f64 - e2 * sin_lat * sin_lat).sqrt();
+        // the height is computed from whichever of p and z is better conditioned
+        let h = if lat.abs() < 1f64 {
+            p / lat.cos() - nu
+        } else {
+            z / sin_lat - (1f64 - e2) * nu
+        };
+        (lon, lat, h)
+    }
+}
+
+/// Returns the ellipsoid with the given name, if it is one of the commonly used
+/// ellipsoids. Names are matched ignoring case, spaces, and underscores.
+pub fn ellipsoid_from_name(name: &str) -> Option<Ellipsoid> {
+    let key: String = name
+        .chars()
+        .filter(|c| c.is_alphanumeric())
+        .collect::<String>()
+        .to_lowercase();
+    let (a, inv_f) = match key.as_str() {
+        "wgs1984" | "wgs84" => (6378137.0, 298.257223563),
+        "grs1980" | "grs80" => (6378137.0, 298.257222101),
+        "wgs1972" | "wgs72" => (6378135.0, 298.26),
+        "clarke1866" => (6378206.4, 294.9786982),
+        "clarke1880" | "clarke1880rgs" => (6378249.145, 293.465),
+        "clarke1880ign" => (6378249.2, 293.4660212936269),
+        "bessel1841" => (6377397.155, 299.1528128),
+        "airy1830" => (6377563.396, 299.3249646),
+        "airymodified" | "airymodified1849" => (6377340.189, 299.3249646),
+        "international1924" | "hayford" | "international1909" => (6378388.0, 297.0),
+        "krasovsky1940" | "krassowsky1940" => (6378245.0, 298.3),
+        "grs1967" => (6378160.0, 298.247167427),
+        "grs1967truncated" | "australian" | "australiannationalspheroid" => (6378160.0, 298.25),
+        "everest1830" => (6377276.345, 300.8017),
+        "everestadj1937" | "everest18301937adjustment" => (6377276.345, 300.8017255),
+        "everest1830modified" | "everestmodified" => (6377304.063, 300.8017),
+        "helmert1906" => (6378200.0, 298.3),
+        "sphere" | "sphereradius6378137" | "wgs84majorauxiliarysphere" => (6378137.0, 0.0),
+        "sphereradius6371000" | "sphereauthalic" => (6371000.0, 0.0),
+        _ => return None,
+    };
+    Some(Ellipsoid::new(name, a, inv_f))
+}
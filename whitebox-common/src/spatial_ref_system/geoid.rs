@@ -0,0 +1,354 @@
+/*
+This code is part of the WhiteboxTools geospatial analysis library.
+License: MIT
+*/
+use super::crs::{CoordinateReferenceSystem, CrsKind};
+use super::transformation::CoordinateTransformation;
+use crate::utils::{ByteOrderReader, Endianness};
+use std::fs;
+use std::io::{Cursor, Error, ErrorKind};
+use std::path::Path;
+
+/// The value that marks missing heights in GTX files.
+const GTX_NODATA: f32 = -88.8888;
+
+/// A grid of geoid heights (undulations), i.e. the heights of a geoid model above the
+/// ellipsoid, in metres, at the nodes of a regular grid of longitude and latitude.
+///
+/// The grids are read from local files in the binary formats in which geoid models are
+/// commonly distributed: the GTX format of NOAA's VDatum and PROJ (.gtx), and the NGS
+/// format of the GEOID12B and GEOID18 models (.bin), in either byte order.
+#[derive(Clone, Debug)]
+pub struct GeoidGrid {
+    /// The latitude of the southernmost row of nodes, in degrees.
+    pub south: f64,
+    /// The longitude of the westernmost column of nodes, in degrees east.
+    pub west: f64,
+    /// The spacing of the rows, in degrees.
+    pub delta_lat: f64,
+    /// The spacing of the columns, in degrees.
+    pub delta_lon: f64,
+    pub rows: usize,
+    pub columns: usize,
+    /// The heights, in rows from south to north, with None for missing values.
+    values: Vec<Option<f32>>,
+}
+
+impl GeoidGrid {
+    /// Reads a geoid grid file, whose format is given by its extension: '.gtx' for GTX
+    /// files and '.bin' for NGS files.
+    pub fn read(file_name: &str) -> Result<GeoidGrid, Error> {
+        let extension = Path::new(file_name)
+            .extension()
+            .and_then(|e| e.to_str())
+            .unwrap_or("")
+            .to_lowercase();
+        let bytes = fs::read(file_name)?;
+        match extension.as_str() {
+            "gtx" => GeoidGrid::from_gtx_bytes(bytes),
+            "bin" => GeoidGrid::from_ngs_bytes(bytes),
+            _ => Err(Error::new(
+                ErrorKind::InvalidInput,
+                format!(
+                    "The geoid grid file {} is not in a supported format (.gtx or .bin).",
+                    file_name
+                ),
+            )),
+        }
+    }
+
+    /// Reads the big-endian GTX format: the latitude and longitude of the south-west node,
+    /// the row and column spacings, the numbers of rows and columns, and the heights.
+    fn from_gtx_bytes(bytes: Vec<u8>) -> Result<GeoidGrid, Error> {
+        let mut reader = ByteOrderReader::new(Cursor::new(bytes), Endianness::BigEndian);
+        let south = reader.read_f64()?;
+        let west = reader.read_f64()?;
+        let delta_lat = reader.read_f64()?;
+        let delta_lon = reader.read_f64()?;
+        let rows = reader.read_i32()?;
+        let columns = reader.read_i32()?;
+        GeoidGrid::from_reader(reader, south, west, delta_lat, delta_lon, rows, columns)
+    }
+
+    /// Reads the NGS format, whose header has the same fields as that of GTX files
+    /// followed by a data kind, which is 1 for 4-byte floating-point heights. The byte
+    /// order is whichever gives the expected data kind.
+    fn from_ngs_bytes(bytes: Vec<u8>) -> Result<GeoidGrid, Error> {
+        let mut reader = ByteOrderReader::new(Cursor::new(bytes), Endianness::LittleEndian);
+        reader.seek(40);
+        if reader.read_i32()? != 1 {
+            reader.set_byte_order(Endianness::BigEndian);
+            reader.seek(40);
+            if reader.read_i32()? != 1 {
+                return Err(Error::new(
+                    ErrorKind::InvalidData,
+                    "The NGS geoid grid does not contain 4-byte floating-point heights.",
+                ));
+            }
+        }
+        reader.seek(0);
+        let south = reader.read_f64()?;
+        let west = reader.read_f64()?;
+        let delta_lat = reader.read_f64()?;
+        let delta_lon = reader.read_f64()?;
+        let rows = reader.read_i32()?;
+        let columns = reader.read_i32()?;
+        reader.seek(44);
+        GeoidGrid::from_reader(reader, south, west, delta_lat, delta_lon, rows, columns)
+    }
+
+    fn from_reader(
+        mut reader: ByteOrderReader<Cursor<Vec<u8>>>,
+        south: f64,
+        west: f64,
+        delta_lat: f64,
+        delta_lon: f64,
+        rows: i32,
+        columns: i32,
+    ) -> Result<GeoidGrid, Error> {
+        if rows < 2 || columns < 2 || !(delta_lat > 0f64) || !(delta_lon > 0f64) {
+            return Err(Error::new(
+                ErrorKind::InvalidData,
+                "The header of the geoid grid is not valid.",
+            ));
+        }
+        let (rows, columns) = (rows as usize, columns as usize);
+        if reader.len() < reader.pos() + 4 * rows * columns {
+            return Err(Error::new(
+                ErrorKind::InvalidData,
+                "The geoid grid file is shorter than its header indicates.",
+            ));
+        }
+        let mut values = Vec::with_capacity(rows * columns);
+        for _ in 0..rows * columns {
+            let value = reader.read_f32()?;
+            // Both formats mark missing values with -88.8888, or with very large values.
+            values.push(
+                if (value - GTX_NODATA).abs() < 0.0001 || !value.is_finite() || value.abs() > 1e4 {
+                    None
+                } else {
+                    Some(value)
+                },
+            );
+        }
+        Ok(GeoidGrid {
+            south: south,
+            west: west,
+            delta_lat: delta_lat,
+            delta_lon: delta_lon,
+            rows: rows,
+            columns: columns,
+            values: values,
+        })
+    }
+
+    /// Returns true if the columns of the grid go all the way around the globe, so that
+    /// the last column is followed by the first.
+    fn is_global(&self) -> bool {
+        self.columns as f64 * self.delta_lon >= 360f64 - 1e-9
+    }
+
+    fn value(&self, row: usize, column: usize) -> Option<f64> {
+        let column = if column >= self.columns && self.is_global() {
+            column - self.columns
+        } else {
+            column
+        };
+        if row >= self.rows || column >= self.columns {
+            return None;
+        }
+        self.values[row * self.columns + column].map(|v| v as f64)
+    }
+
+    /// Returns the geoid height at a longitude and latitude, in degrees, interpolated
+    /// bilinearly from the four surrounding nodes. Longitudes may be given in either the
+    /// -180 to 180 or the 0 to 360 convention. None is returned outside of the grid and
+    /// where any of the surrounding nodes that are used is missing.
+    pub fn get_height(&self, lon: f64, lat: f64) -> Option<f64> {
+        // Bring the longitude into the 360 degrees that start at the western edge.
+        let lon = self.west + (lon - self.west).rem_euclid(360f64);
+        let row = (lat - self.south) / self.delta_lat;
+        let column = (lon - self.west) / self.delta_lon;
+        let last_row = (self.rows - 1) as f64;
+        let last_column = if self.is_global() {
+            self.columns as f64
+        } else {
+            (self.columns - 1) as f64
+        };
+        // allow for rounding at the edges
+        let tolerance = 1e-9;
+        if row < -tolerance
+            || row > last_row + tolerance
+            || column < -tolerance
+            || column > last_column + tolerance
+        {
+            return None;
+        }
+        let row = row.max(0f64).min(last_row);
+        let column = column.max(0f64).min(last_column);
+        let r0 = (row.floor() as usize).min(self.rows - 2);
+        let c0 = (column.floor() as usize).min(last_column as usize - 1);
+        let (dr, dc) = (row - r0 as f64, column - c0 as f64);
+        let nodes = [
+            (r0, c0, (1f64 - dr) * (1f64 - dc)),
+            (r0, c0 + 1, (1f64 - dr) * dc),
+            (r0 + 1, c0, dr * (1f64 - dc)),
+            (r0 + 1, c0 + 1, dr * dc),
+        ];
+        let mut height = 0f64;
+        for (r, c, weight) in nodes.iter() {
+            // nodes that carry no weight, e.g. beyond a location on an edge, may be missing
+            if *weight > 0f64 {
+                height += weight * self.value(*r, *c)?;
+            }
+        }
+        Some(height)
+    }
+}
+
+/// Converts heights between ellipsoidal heights and orthometric heights, i.e. heights
+/// above the geoid, at locations given in a layer's coordinate reference system. The
+/// orthometric height is the ellipsoidal height less the geoid height.
+///
+/// The locations are converted to longitude and latitude on the layer's datum, which is
+/// taken to be that of the geoid model.
+pub struct GeoidTransformation {
+    grid: GeoidGrid,
+    to_geographic: CoordinateTransformation,
+    to_orthometric: bool,
+    metres_per_unit: f64,
+}
+
+impl GeoidTransformation {
+    /// Creates a transformation with a geoid grid for layers in a coordinate reference
+    /// system, converting ellipsoidal heights to orthometric heights if `to_orthometric`
+    /// is true, and the reverse otherwise. Heights are in units of `metres_per_unit`
+    /// metres.
+    pub fn new(
+        grid: GeoidGrid,
+        crs: &CoordinateReferenceSystem,
+        to_orthometric: bool,
+        metres_per_unit: f64,
+    ) -> Result<GeoidTransformation, Error> {
+        if crs.kind == CrsKind::Geocentric {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                "Heights cannot be transformed in a geocentric coordinate reference system.",
+            ));
+        }
+        let geographic = CoordinateReferenceSystem {
+            name: format!("Geographic coordinates on {}", crs.datum.name),
+            kind: CrsKind::Geographic,
+            datum: crs.datum.clone(),
+            prime_meridian: 0f64,
+            angular_unit: 1f64.to_radians(),
+            linear_unit: 1f64,
+            epsg: None,
+        };
+        Ok(GeoidTransformation {
+            grid: grid,
+            to_geographic: CoordinateTransformation::new(crs, &geographic)?,
+            to_orthometric: to_orthometric,
+            metres_per_unit: metres_per_unit,
+        })
+    }
+
+    /// Returns the geoid height, in metres, at a location.
+    pub fn get_geoid_height(&self, x: f64, y: f64) -> Option<f64> {
+        let (lon, lat) = self.to_geographic.transform(x, y)?;
+        self.grid.get_height(lon, lat)
+    }
+
+    /// Transforms the height at a location. Returns None outside of the geoid grid.
+    pub fn transform(&self, x: f64, y: f64, z: f64) -> Option<f64> {
+        let offset = self.get_geoid_height(x, y)? / self.metres_per_unit;
+        if self.to_orthometric {
+            Some(z - offset)
+        } else {
+            Some(z + offset)
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn gtx(south: f64, west: f64, delta: f64, rows: i32, columns: i32, values: &[f32]) -> Vec<u8> {
+        let mut bytes = vec![];
+        for v in &[south, west, delta, delta] {
+            bytes.extend_from_slice(&v.to_be_bytes());
+        }
+        bytes.extend_from_slice(&rows.to_be_bytes());
+        bytes.extend_from_slice(&columns.to_be_bytes());
+        for v in values {
+            bytes.extend_from_slice(&v.to_be_bytes());
+        }
+        bytes
+    }
+
+    #[test]
+    fn test_bilinear_interpolation() {
+        // rows from south to north
+        let values = [0f32, 1.0, 2.0, 10.0, 11.0, 12.0, 20.0, 21.0, GTX_NODATA];
+        let grid = GeoidGrid::from_gtx_bytes(gtx(40.0, 279.0, 1.0, 3, 3, &values)).unwrap();
+        assert_eq!(grid.get_height(-81.0, 40.0), Some(0.0));
+        assert!((grid.get_height(-80.5, 40.5).unwrap() - 5.5).abs() < 1e-9);
+        assert!((grid.get_height(279.25, 41.0).unwrap() - 10.25).abs() < 1e-9);
+        // the edges of the grid, and nodes that are missing
+        assert!((grid.get_height(-79.0, 42.0 - 1e-12).is_none()));
+        assert!((grid.get_height(-80.0, 42.0).unwrap() - 21.0).abs() < 1e-9);
+        assert!(grid.get_height(-82.0, 41.0).is_none());
+        assert!(grid.get_height(-80.5, 42.5).is_none());
+    }
+
+    #[test]
+    fn test_global_grid_wraps() {
+        // 2 rows of 4 columns every 90 degrees, which go all the way around
+        let values = [0f32, 1.0, 2.0, 3.0, 0.0, 1.0, 2.0, 3.0];
+        let grid = GeoidGrid::from_gtx_bytes(gtx(0.0, 0.0, 90.0, 2, 4, &values)).unwrap();
+        assert!((grid.get_height(315.0, 45.0).unwrap() - 1.5).abs() < 1e-9);
+        assert!((grid.get_height(-45.0, 45.0).unwrap() - 1.5).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_ngs_byte_orders() {
+        let mut little = vec![];
+        for v in &[40f64, 279.0, 1.0, 1.0] {
+            little.extend_from_slice(&v.to_le_bytes());
+        }
+        for v in &[2i32, 2, 1] {
+            little.extend_from_slice(&v.to_le_bytes());
+        }
+        for v in &[-30f32, -32.0, -34.0, -36.0] {
+            little.extend_from_slice(&v.to_le_bytes());
+        }
+        let grid = GeoidGrid::from_ngs_bytes(little).unwrap();
+        assert!((grid.get_height(-80.5, 40.5).unwrap() + 33.0).abs() < 1e-6);
+
+        let mut big = gtx(40.0, 279.0, 1.0, 2, 2, &[]);
+        big.extend_from_slice(&1i32.to_be_bytes());
+        for v in &[-30f32, -32.0, -34.0, -36.0] {
+            big.extend_from_slice(&v.to_be_bytes());
+        }
+        let grid = GeoidGrid::from_ngs_bytes(big).unwrap();
+        assert!((grid.get_height(-81.0, 41.0).unwrap() + 34.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_geoid_transformation() {
+        let values = [-35f32, -35.0, -35.0, -35.0];
+        let grid = GeoidGrid::from_gtx_bytes(gtx(43.0, -82.0, 1.0, 2, 2, &values)).unwrap();
+        let utm = CoordinateReferenceSystem::from_epsg(32617).unwrap();
+        let transformation = GeoidTransformation::new(grid.clone(), &utm, true, 1.0).unwrap();
+        // (500000, 4800000) is at about 81 W, 43.35 N
+        let h = transformation
+            .transform(500000.0, 4800000.0, 200.0)
+            .unwrap();
+        assert!((h - 235.0).abs() < 1e-6);
+        let feet = GeoidTransformation::new(grid, &utm, false, 0.3048).unwrap();
+        let h = feet.transform(500000.0, 4800000.0, 0.0).unwrap();
+        assert!((h + 35.0 / 0.3048).abs() < 1e-6);
+        assert!(transformation.transform(500000.0, 6000000.0, 0.0).is_none());
+    }
+}
@@ -1,3 +1,17 @@
+mod crs;
+mod datum;
+mod ellipsoid;
 mod epsg_to_wkt;
+mod geoid;
+mod projections;
+mod transformation;
+mod wkt;
 
+pub use self::crs::{CoordinateReferenceSystem, CrsCheck, CrsKind};
+pub use self::datum::{datum_shift_from_name, Datum, HelmertParameters};
+pub use self::ellipsoid::{ellipsoid_from_name, Ellipsoid};
 pub use self::epsg_to_wkt::esri_wkt_from_epsg;
+pub use self::geoid::{GeoidGrid, GeoidTransformation};
+pub use self::projections::{Projection, ProjectionMethod, ProjectionParameters};
+pub use self::transformation::CoordinateTransformation;
+pub use self::wkt::{WktNode, WktValue};
@@ -0,0 +1,660 @@
+/*
+This code is part of the WhiteboxTools geospatial analysis library.
+License: MIT
+*/
+use super::ellipsoid::Ellipsoid;
+use std::f64::consts::{FRAC_PI_2, FRAC_PI_4, PI};
+use std::io::{Error, ErrorKind};
+
+/// The map projection methods that coordinates can be transformed with.
+#[derive(Clone, Debug, PartialEq)]
+pub enum ProjectionMethod {
+    /// Transverse Mercator, including UTM and Gauss-Kruger.
+    TransverseMercator,
+    /// Lambert Conformal Conic, with one or two standard parallels.
+    LambertConformalConic,
+    AlbersEqualArea,
+    /// Polar Stereographic, with either a scale factor at the pole (variant A) or a
+    /// standard parallel (variant B).
+    PolarStereographic,
+    /// Mercator on the ellipsoid, with either a scale factor or a standard parallel.
+    Mercator,
+    /// Spherical Mercator applied to geographic coordinates on the ellipsoid, as used by
+    /// web mapping services (EPSG:3857).
+    WebMercator,
+    /// A projection that is recognized, but that coordinates cannot be transformed with,
+    /// by the name that it is given in the WKT.
+    Other(String),
+}
+
+/// The parameters of a map projection. Angles are in degrees, and the false easting and
+/// northing are in metres.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ProjectionParameters {
+    pub central_meridian: f64,
+    pub latitude_of_origin: f64,
+    pub standard_parallel_1: Option<f64>,
+    pub standard_parallel_2: Option<f64>,
+    pub scale_factor: f64,
+    pub false_easting: f64,
+    pub false_northing: f64,
+    /// Any other parameters, by the names given in the WKT, e.g. the azimuth of an
+    /// oblique projection.
+    pub other: Vec<(String, f64)>,
+}
+
+impl Default for ProjectionParameters {
+    fn default() -> ProjectionParameters {
+        ProjectionParameters {
+            central_meridian: 0f64,
+            latitude_of_origin: 0f64,
+            standard_parallel_1: None,
+            standard_parallel_2: None,
+            scale_factor: 1f64,
+            false_easting: 0f64,
+            false_northing: 0f64,
+            other: vec![],
+        }
+    }
+}
+
+/// A map projection: a method and its parameters.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Projection {
+    pub method: ProjectionMethod,
+    pub parameters: ProjectionParameters,
+}
+
+/// Converts between geodetic coordinates (longitude and latitude, in radians) and
+/// projected coordinates (in metres). Either direction returns `None` for coordinates
+/// that cannot be projected, e.g. the pole on a Mercator projection.
+pub(crate) trait Projector: Send + Sync {
+    fn forward(&self, lon: f64, lat: f64) -> Option<(f64, f64)>;
+    fn inverse(&self, x: f64, y: f64) -> Option<(f64, f64)>;
+}
+
+impl Projection {
+    pub fn new(method: ProjectionMethod, parameters: ProjectionParameters) -> Projection {
+        Projection {
+            method: method,
+            parameters: parameters,
+        }
+    }
+
+    /// Prepares the projection for converting coordinates on the given ellipsoid.
+    pub(crate) fn projector(&self, ellipsoid: &Ellipsoid) -> Result<Box<dyn Projector>, Error> {
+        let p = &self.parameters;
+        let projector: Box<dyn Projector> = match self.method {
+            ProjectionMethod::TransverseMercator => Box::new(TransverseMercator::new(ellipsoid, p)),
+            ProjectionMethod::LambertConformalConic => {
+                Box::new(LambertConformalConic::new(ellipsoid, p)?)
+            }
+            ProjectionMethod::AlbersEqualArea => Box::new(AlbersEqualArea::new(ellipsoid, p)?),
+            ProjectionMethod::PolarStereographic => {
+                Box::new(PolarStereographic::new(ellipsoid, p)?)
+            }
+            ProjectionMethod::Mercator => Box::new(Mercator::new(ellipsoid, p)),
+            ProjectionMethod::WebMercator => Box::new(WebMercator::new(ellipsoid, p)),
+            ProjectionMethod::Other(ref name) => {
+                return Err(Error::new(
+                    ErrorKind::InvalidInput,
+                    format!("Coordinates cannot be transformed with the {} projection.", name),
+                ))
+            }
+        };
+        Ok(projector)
+    }
+}
+
+/// Returns the longitude difference from the central meridian, wrapped to [-PI, PI].
+fn wrap_longitude(lon: f64) -> f64 {
+    if lon < -PI || lon > PI {
+        lon - 2f64 * PI * ((lon + PI) / (2f64 * PI)).floor()
+    } else {
+        lon
+    }
+}
+
+/// The isometric colatitude function t of the conformal projections (EPSG Guidance
+/// Note 7-2), tan(PI/4 - lat/2) / ((1 - e sin lat) / (1 + e sin lat))^(e/2).
+fn conformal_t(lat: f64, e: f64) -> f64 {
+    let lat = lat.max(-FRAC_PI_2).min(FRAC_PI_2);
+    let e_sin = e * lat.sin();
+    (FRAC_PI_4 - lat / 2f64).tan() / ((1f64 - e_sin) / (1f64 + e_sin)).powf(e / 2f64)
+}
+
+/// Inverts `conformal_t` by iteration, returning the latitude.
+fn latitude_from_t(t: f64, e: f64) -> f64 {
+    let mut lat = FRAC_PI_2 - 2f64 * t.atan();
+    for _ in 0..15 {
+        let e_sin = e * lat.sin();
+        let new_lat =
+            FRAC_PI_2 - 2f64 * (t * ((1f64 - e_sin) / (1f64 + e_sin)).powf(e / 2f64)).atan();
+        if (new_lat - lat).abs() < 1e-14 {
+            return new_lat;
+        }
+        lat = new_lat;
+    }
+    lat
+}
+
+/// The m function of the conic projections, cos lat / sqrt(1 - e^2 sin^2 lat).
+fn conic_m(lat: f64, e2: f64) -> f64 {
+    let sin_lat = lat.sin();
+    lat.cos() / (1f64 - e2 * sin_lat * sin_lat).sqrt()
+}
+
+/// Transverse Mercator, using the Krüger series to the sixth order in n (Karney, 2011,
+/// Transverse Mercator with an accuracy of a few nanometers, J. Geodesy 85), which is
+/// accurate to well under a millimetre within several thousand kilometres of the
+/// central meridian.
+struct TransverseMercator {
+    e: f64,
+    e2: f64,
+    k0_a: f64,
+    alpha: [f64; 6],
+    beta: [f64; 6],
+    lon0: f64,
+    false_easting: f64,
+    false_northing: f64,
+    northing_of_origin: f64,
+}
+
+impl TransverseMercator {
+    fn new(ellipsoid: &Ellipsoid, p: &ProjectionParameters) -> TransverseMercator {
+        let f = ellipsoid.flattening();
+        let n = f / (2f64 - f);
+        let (n2, n3) = (n * n, n * n * n);
+        let (n4, n5, n6) = (n3 * n, n3 * n2, n3 * n3);
+        let a =
+            ellipsoid.semi_major_axis / (1f64 + n) * (1f64 + n2 / 4f64 + n4 / 64f64 + n6 / 256f64);
+        let alpha = [
+            n / 2f64 - 2f64 * n2 / 3f64 + 5f64 * n3 / 16f64 + 41f64 * n4 / 180f64
+                - 127f64 * n5 / 288f64
+                + 7891f64 * n6 / 37800f64,
+            13f64 * n2 / 48f64 - 3f64 * n3 / 5f64 + 557f64 * n4 / 1440f64 + 281f64 * n5 / 630f64
+                - 1983433f64 * n6 / 1935360f64,
+            61f64 * n3 / 240f64 - 103f64 * n4 / 140f64
+                + 15061f64 * n5 / 26880f64
+                + 167603f64 * n6 / 181440f64,
+            49561f64 * n4 / 161280f64 - 179f64 * n5 / 168f64 + 6601661f64 * n6 / 7257600f64,
+            34729f64 * n5 / 80640f64 - 3418889f64 * n6 / 1995840f64,
+            212378941f64 * n6 / 319334400f64,
+        ];
+        let beta = [
+            n / 2f64 - 2f64 * n2 / 3f64 + 37f64 * n3 / 96f64 - n4 / 360f64 - 81f64 * n5 / 512f64
+                + 96199f64 * n6 / 604800f64,
+            n2 / 48f64 + n3 / 15f64 - 437f64 * n4 / 1440f64 + 46f64 * n5 / 105f64
+                - 1118711f64 * n6 / 3870720f64,
+            17f64 * n3 / 480f64 - 37f64 * n4 / 840f64 - 209f64 * n5 / 4480f64
+                + 5569f64 * n6 / 90720f64,
+            4397f64 * n4 / 161280f64 - 11f64 * n5 / 504f64 - 830251f64 * n6 / 7257600f64,
+            4583f64 * n5 / 161280f64 - 108847f64 * n6 / 3991680f64,
+            20648693f64 * n6 / 638668800f64,
+        ];
+        let mut tm = TransverseMercator {
+            e: ellipsoid.eccentricity(),
+            e2: ellipsoid.eccentricity_squared(),
+            k0_a: p.scale_factor * a,
+            alpha: alpha,
+            beta: beta,
+            lon0: p.central_meridian.to_radians(),
+            false_easting: p.false_easting,
+            false_northing: p.false_northing,
+            northing_of_origin: 0f64,
+        };
+        if p.latitude_of_origin != 0f64 {
+            tm.northing_of_origin = tm.project(0f64, p.latitude_of_origin.to_radians()).1;
+        }
+        tm
+    }
+
+    /// Projects a point relative to the central meridian, without the false origin.
+    fn project(&self, dlon: f64, lat: f64) -> (f64, f64) {
+        let e = self.e;
+        let tau = lat.tan();
+        let sigma = (e * (e * tau / (1f64 + tau * tau).sqrt()).atanh()).sinh();
+        // the tangent of the conformal latitude
+        let tau_c = tau * (1f64 + sigma * sigma).sqrt() - sigma * (1f64 + tau * tau).sqrt();
+        let xi_c = tau_c.atan2(dlon.cos());
+        let eta_c = (dlon.sin() / (tau_c * tau_c + dlon.cos().powi(2)).sqrt()).asinh();
+        let mut xi = xi_c;
+        let mut eta = eta_c;
+        for (j, alpha) in self.alpha.iter().enumerate() {
+            let k = 2f64 * (j + 1) as f64;
+            xi += alpha * (k * xi_c).sin() * (k * eta_c).cosh();
+            eta += alpha * (k * xi_c).cos() * (k * eta_c).sinh();
+        }
+        (self.k0_a * eta, self.k0_a * xi)
+    }
+}
+
+impl Projector for TransverseMercator {
+    fn forward(&self, lon: f64, lat: f64) -> Option<(f64, f64)> {
+        let dlon = wrap_longitude(lon - self.lon0);
+        if dlon.abs() > FRAC_PI_2 {
+            // the far hemisphere cannot be represented
+            return None;
+        }
+        let (x, y) = self.project(dlon, lat);
+        Some((
+            x + self.false_easting,
+            y - self.northing_of_origin + self.false_northing,
+        ))
+    }
+
+    fn inverse(&self, x: f64, y: f64) -> Option<(f64, f64)> {
+        let xi = (y - self.false_northing + self.northing_of_origin) / self.k0_a;
+        let eta = (x - self.false_easting) / self.k0_a;
+        let mut xi_c = xi;
+        let mut eta_c = eta;
+        for (j, beta) in self.beta.iter().enumerate() {
+            let k = 2f64 * (j + 1) as f64;
+            xi_c -= beta * (k * xi).sin() * (k * eta).cosh();
+            eta_c -= beta * (k * xi).cos() * (k * eta).sinh();
+        }
+        let sinh_eta = eta_c.sinh();
+        let tau_c = xi_c.sin() / (sinh_eta * sinh_eta + xi_c.cos().powi(2)).sqrt();
+        let dlon = sinh_eta.atan2(xi_c.cos());
+
+        // Solve for the tangent of the geodetic latitude by Newton's method.
+        let e = self.e;
+        let mut tau = tau_c;
+        for _ in 0..10 {
+            let sigma = (e * (e * tau / (1f64 + tau * tau).sqrt()).atanh()).sinh();
+            let tau_i = tau * (1f64 + sigma * sigma).sqrt() - sigma * (1f64 + tau * tau).sqrt();
+            let delta = (tau_c - tau_i) / (1f64 + tau_i * tau_i).sqrt()
+                * (1f64 + (1f64 - self.e2) * tau * tau)
+                / ((1f64 - self.e2) * (1f64 + tau * tau).sqrt());
+            tau += delta;
+            if delta.abs() < 1e-14 * tau.abs().max(1f64) {
+                break;
+            }
+        }
+        if !tau.is_finite() || !dlon.is_finite() {
+            return None;
+        }
+        Some((wrap_longitude(dlon + self.lon0), tau.atan()))
+    }
+}
+
+/// Lambert Conformal Conic (EPSG methods 9801 and 9802). A single standard parallel, or
+/// two equal ones, gives the one standard parallel variant with a scale factor.
+struct LambertConformalConic {
+    e: f64,
+    n: f64,
+    a_f: f64,
+    r_origin: f64,
+    lon0: f64,
+    false_easting: f64,
+    false_northing: f64,
+}
+
+impl LambertConformalConic {
+    fn new(ellipsoid: &Ellipsoid, p: &ProjectionParameters) -> Result<Self, Error> {
+        let e = ellipsoid.eccentricity();
+        let e2 = ellipsoid.eccentricity_squared();
+        let a = ellipsoid.semi_major_axis;
+        let lat0 = p.latitude_of_origin.to_radians();
+        let sp1 = p
+            .standard_parallel_1
+            .unwrap_or(p.latitude_of_origin)
+            .to_radians();
+        let sp2 = p
+            .standard_parallel_2
+            .unwrap_or(sp1.to_degrees())
+            .to_radians();
+        let (n, f) = if (sp1 - sp2).abs() < 1e-10 {
+            let n = sp1.sin();
+            (
+                n,
+                conic_m(sp1, e2) / (n * conformal_t(sp1, e).powf(n)) * p.scale_factor,
+            )
+        } else {
+            let (m1, m2) = (conic_m(sp1, e2), conic_m(sp2, e2));
+            let (t1, t2) = (conformal_t(sp1, e), conformal_t(sp2, e));
+            let n = (m1.ln() - m2.ln()) / (t1.ln() - t2.ln());
+            (n, m1 / (n * t1.powf(n)))
+        };
+        if n.abs() < 1e-10 || !n.is_finite() {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                "The standard parallels of the Lambert Conformal Conic projection are invalid.",
+            ));
+        }
+        let a_f = a * f;
+        Ok(LambertConformalConic {
+            e: e,
+            n: n,
+            a_f: a_f,
+            r_origin: a_f * conformal_t(lat0, e).powf(n),
+            lon0: p.central_meridian.to_radians(),
+            false_easting: p.false_easting,
+            false_northing: p.false_northing,
+        })
+    }
+}
+
+impl Projector for LambertConformalConic {
+    fn forward(&self, lon: f64, lat: f64) -> Option<(f64, f64)> {
+        // the pole opposite the cone's apex is at infinity
+        if (lat + self.n.signum() * FRAC_PI_2).abs() < 1e-10 {
+            return None;
+        }
+        let r = self.a_f * conformal_t(lat, self.e).powf(self.n);
+        let theta = self.n * wrap_longitude(lon - self.lon0);
+        Some((
+            self.false_easting + r * theta.sin(),
+            self.false_northing + self.r_origin - r * theta.cos(),
+        ))
+    }
+
+    fn inverse(&self, x: f64, y: f64) -> Option<(f64, f64)> {
+        let dx = x - self.false_easting;
+        let dy = self.r_origin - (y - self.false_northing);
+        let sign = self.n.signum();
+        let r = sign * (dx * dx + dy * dy).sqrt();
+        let theta = (sign * dx).atan2(sign * dy);
+        let t = (r / self.a_f).powf(1f64 / self.n);
+        let lat = latitude_from_t(t, self.e);
+        if !lat.is_finite() {
+            return None;
+        }
+        Some((wrap_longitude(theta / self.n + self.lon0), lat))
+    }
+}
+
+/// Albers Equal Area Conic (EPSG method 9822).
+struct AlbersEqualArea {
+    e: f64,
+    e2: f64,
+    a: f64,
+    n: f64,
+    c: f64,
+    rho0: f64,
+    q_pole: f64,
+    lon0: f64,
+    false_easting: f64,
+    false_northing: f64,
+}
+
+impl AlbersEqualArea {
+    fn new(ellipsoid: &Ellipsoid, p: &ProjectionParameters) -> Result<Self, Error> {
+        let e = ellipsoid.eccentricity();
+        let e2 = ellipsoid.eccentricity_squared();
+        let a = ellipsoid.semi_major_axis;
+        let lat0 = p.latitude_of_origin.to_radians();
+        let sp1 = p
+            .standard_parallel_1
+            .unwrap_or(p.latitude_of_origin)
+            .to_radians();
+        let sp2 = p
+            .standard_parallel_2
+            .unwrap_or(sp1.to_degrees())
+            .to_radians();
+        let (m1, m2) = (conic_m(sp1, e2), conic_m(sp2, e2));
+        let (q1, q2) = (albers_q(sp1, e, e2), albers_q(sp2, e, e2));
+        let n = if (sp1 - sp2).abs() < 1e-10 {
+            sp1.sin()
+        } else {
+            (m1 * m1 - m2 * m2) / (q2 - q1)
+        };
+        if n.abs() < 1e-10 || !n.is_finite() {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                "The standard parallels of the Albers Equal Area projection are invalid.",
+            ));
+        }
+        let c = m1 * m1 + n * q1;
+        let rho0 = a * (c - n * albers_q(lat0, e, e2)).sqrt() / n;
+        Ok(AlbersEqualArea {
+            e: e,
+            e2: e2,
+            a: a,
+            n: n,
+            c: c,
+            rho0: rho0,
+            q_pole: albers_q(FRAC_PI_2, e, e2),
+            lon0: p.central_meridian.to_radians(),
+            false_easting: p.false_easting,
+            false_northing: p.false_northing,
+        })
+    }
+}
+
+/// The q (or alpha) function of the equal area projections.
+fn albers_q(lat: f64, e: f64, e2: f64) -> f64 {
+    let sin_lat = lat.sin();
+    if e < 1e-12 {
+        return 2f64 * sin_lat;
+    }
+    let e_sin = e * sin_lat;
+    (1f64 - e2)
+        * (sin_lat / (1f64 - e_sin * e_sin)
+            - 1f64 / (2f64 * e) * ((1f64 - e_sin) / (1f64 + e_sin)).ln())
+}
+
+impl Projector for AlbersEqualArea {
+    fn forward(&self, lon: f64, lat: f64) -> Option<(f64, f64)> {
+        let rho = self.a * (self.c - self.n * albers_q(lat, self.e, self.e2)).sqrt() / self.n;
+        let theta = self.n * wrap_longitude(lon - self.lon0);
+        if !rho.is_finite() {
+            return None;
+        }
+        Some((
+            self.false_easting + rho * theta.sin(),
+            self.false_northing + self.rho0 - rho * theta.cos(),
+        ))
+    }
+
+    fn inverse(&self, x: f64, y: f64) -> Option<(f64, f64)> {
+        let dx = x - self.false_easting;
+        let dy = self.rho0 - (y - self.false_northing);
+        let sign = self.n.signum();
+        let rho = (dx * dx + dy * dy).sqrt();
+        let theta = (sign * dx).atan2(sign * dy);
+        let q = (self.c - rho * rho * self.n * self.n / (self.a * self.a)) / self.n;
+        if q.abs() > self.q_pole.abs() + 1e-10 {
+            return None;
+        }
+        let q = q.max(-self.q_pole).min(self.q_pole);
+
+        // Iterate for the latitude whose q is that of the point (Snyder, 1987, eq. 3-16).
+        let e = self.e;
+        let e2 = self.e2;
+        let mut lat = (q / 2f64).max(-1f64).min(1f64).asin();
+        for _ in 0..15 {
+            let sin_lat = lat.sin();
+            let cos_lat = lat.cos();
+            if cos_lat.abs() < 1e-12 {
+                break;
+            }
+            let e_sin = e * sin_lat;
+            let one_minus = 1f64 - e_sin * e_sin;
+            let correction = if e < 1e-12 {
+                (q - 2f64 * sin_lat) / (2f64 * cos_lat)
+            } else {
+                one_minus * one_minus / (2f64 * cos_lat)
+                    * (q / (1f64 - e2) - sin_lat / one_minus
+                        + 1f64 / (2f64 * e) * ((1f64 - e_sin) / (1f64 + e_sin)).ln())
+            };
+            lat += correction;
+            if correction.abs() < 1e-14 {
+                break;
+            }
+        }
+        if !lat.is_finite() {
+            return None;
+        }
+        Some((wrap_longitude(theta / self.n + self.lon0), lat))
+    }
+}
+
+/// Polar Stereographic (EPSG methods 9810 and 9829). The pole is that of the latitude of
+/// origin, or of the standard parallel if there is one.
+struct PolarStereographic {
+    e: f64,
+    south: bool,
+    // the factor relating the t function to the distance from the pole
+    rho_factor: f64,
+    lon0: f64,
+    false_easting: f64,
+    false_northing: f64,
+}
+
+impl PolarStereographic {
+    fn new(ellipsoid: &Ellipsoid, p: &ProjectionParameters) -> Result<Self, Error> {
+        let e = ellipsoid.eccentricity();
+        let e2 = ellipsoid.eccentricity_squared();
+        let a = ellipsoid.semi_major_axis;
+        let rho_factor = match p.standard_parallel_1 {
+            Some(sp) if (sp.abs() - 90f64).abs() > 1e-10 => {
+                // variant B: true scale at the standard parallel
+                let lat_f = sp.abs().to_radians();
+                a * conic_m(lat_f, e2) / conformal_t(lat_f, e)
+            }
+            _ => {
+                // variant A: a scale factor at the pole
+                if (p.latitude_of_origin.abs() - 90f64).abs() > 1e-10 {
+                    return Err(Error::new(
+                        ErrorKind::InvalidInput,
+                        "Only the polar aspect of the Stereographic projection is supported.",
+                    ));
+                }
+                2f64 * a * p.scale_factor
+                    / ((1f64 + e).powf(1f64 + e) * (1f64 - e).powf(1f64 - e)).sqrt()
+            }
+        };
+        let south = match p.standard_parallel_1 {
+            Some(sp) if (sp.abs() - 90f64).abs() > 1e-10 => sp < 0f64,
+            _ => p.latitude_of_origin < 0f64,
+        };
+        Ok(PolarStereographic {
+            e: e,
+            south: south,
+            rho_factor: rho_factor,
+            lon0: p.central_meridian.to_radians(),
+            false_easting: p.false_easting,
+            false_northing: p.false_northing,
+        })
+    }
+}
+
+impl Projector for PolarStereographic {
+    fn forward(&self, lon: f64, lat: f64) -> Option<(f64, f64)> {
+        let lat = if self.south { -lat } else { lat };
+        if (lat + FRAC_PI_2).abs() < 1e-10 {
+            // the opposite pole
+            return None;
+        }
+        let rho = self.rho_factor * conformal_t(lat, self.e);
+        let dlon = lon - self.lon0;
+        let dy = rho * dlon.cos();
+        Some((
+            self.false_easting + rho * dlon.sin(),
+            if self.south {
+                self.false_northing + dy
+            } else {
+                self.false_northing - dy
+            },
+        ))
+    }
+
+    fn inverse(&self, x: f64, y: f64) -> Option<(f64, f64)> {
+        let dx = x - self.false_easting;
+        let dy = y - self.false_northing;
+        let rho = (dx * dx + dy * dy).sqrt();
+        let t = rho / self.rho_factor;
+        let lat = latitude_from_t(t, self.e);
+        if self.south {
+            Some((wrap_longitude(self.lon0 + dx.atan2(dy)), -lat))
+        } else {
+            Some((wrap_longitude(self.lon0 + dx.atan2(-dy)), lat))
+        }
+    }
+}
+
+/// Mercator on the ellipsoid (EPSG methods 9804 and 9805).
+struct Mercator {
+    e: f64,
+    a_k0: f64,
+    lon0: f64,
+    false_easting: f64,
+    false_northing: f64,
+}
+
+impl Mercator {
+    fn new(ellipsoid: &Ellipsoid, p: &ProjectionParameters) -> Mercator {
+        let k0 = match p.standard_parallel_1 {
+            Some(sp) => conic_m(sp.to_radians(), ellipsoid.eccentricity_squared()),
+            None => p.scale_factor,
+        };
+        Mercator {
+            e: ellipsoid.eccentricity(),
+            a_k0: ellipsoid.semi_major_axis * k0,
+            lon0: p.central_meridian.to_radians(),
+            false_easting: p.false_easting,
+            false_northing: p.false_northing,
+        }
+    }
+}
+
+impl Projector for Mercator {
+    fn forward(&self, lon: f64, lat: f64) -> Option<(f64, f64)> {
+        if lat.abs() >= FRAC_PI_2 - 1e-10 {
+            return None;
+        }
+        Some((
+            self.false_easting + self.a_k0 * wrap_longitude(lon - self.lon0),
+            self.false_northing - self.a_k0 * conformal_t(lat, self.e).ln(),
+        ))
+    }
+
+    fn inverse(&self, x: f64, y: f64) -> Option<(f64, f64)> {
+        let t = (-(y - self.false_northing) / self.a_k0).exp();
+        Some((
+            wrap_longitude((x - self.false_easting) / self.a_k0 + self.lon0),
+            latitude_from_t(t, self.e),
+        ))
+    }
+}
+
+/// Popular Visualisation Pseudo Mercator (EPSG method 1024), which applies the
+/// spherical Mercator formulas, with a radius equal to the semi-major axis, to
+/// geographic coordinates on the ellipsoid.
+struct WebMercator {
+    a: f64,
+    lon0: f64,
+    false_easting: f64,
+    false_northing: f64,
+}
+
+impl WebMercator {
+    fn new(ellipsoid: &Ellipsoid, p: &ProjectionParameters) -> WebMercator {
+        WebMercator {
+            a: ellipsoid.semi_major_axis,
+            lon0: p.central_meridian.to_radians(),
+            false_easting: p.false_easting,
+            false_northing: p.false_northing,
+        }
+    }
+}
+
+impl Projector for WebMercator {
+    fn forward(&self, lon: f64, lat: f64) -> Option<(f64, f64)> {
+        if lat.abs() >= FRAC_PI_2 - 1e-10 {
+            return None;
+        }
+        Some((
+            self.false_easting + self.a * wrap_longitude(lon - self.lon0),
+            self.false_northing + self.a * (FRAC_PI_4 + lat / 2f64).tan().ln(),
+        ))
+    }
+
+    fn inverse(&self, x: f64, y: f64) -> Option<(f64, f64)> {
+        Some((
+            wrap_longitude((x - self.false_easting) / self.a + self.lon0),
+            FRAC_PI_2 - 2f64 * (-(y - self.false_northing) / self.a).exp().atan(),
+        ))
+    }
+}
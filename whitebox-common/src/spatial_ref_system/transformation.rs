@@ -0,0 +1,420 @@
+/*
+This code is part of the WhiteboxTools geospatial analysis library.
+License: MIT
+*/
+use super::crs::{CoordinateReferenceSystem, CrsKind};
+use super::datum::Helmert;
+use super::projections::Projector;
+use std::io::Error;
+use std::sync::Arc;
+
+/// Transforms coordinates from one coordinate reference system to another: from the
+/// source system to geodetic coordinates, through a Helmert datum shift if the datums
+/// differ, and then to the target system.
+///
+/// A datum shift is only applied when the transformations of both datums to WGS84 are
+/// known, either from the TOWGS84 element of their WKT or, for commonly used datums, by
+/// name. Otherwise, geodetic coordinates are carried across unchanged, which for
+/// datums on different ellipsoids may be in error by up to several hundred metres.
+///
+/// ```
+/// use whitebox_common::spatial_ref_system::CoordinateTransformation;
+///
+/// // WGS84 geographic coordinates to UTM zone 17N
+/// let transformation = CoordinateTransformation::from_epsg(4326, 32617).unwrap();
+/// let (easting, northing) = transformation.transform(-81.0, 43.0).unwrap();
+/// assert!((easting - 500000.0).abs() < 0.001);
+/// ```
+#[derive(Clone)]
+pub struct CoordinateTransformation {
+    pub source: CoordinateReferenceSystem,
+    pub target: CoordinateReferenceSystem,
+    source_projector: Option<Arc<dyn Projector>>,
+    target_projector: Option<Arc<dyn Projector>>,
+    datum_shift: Option<(Helmert, Helmert)>,
+    is_identity: bool,
+}
+
+impl CoordinateTransformation {
+    pub fn new(
+        source: &CoordinateReferenceSystem,
+        target: &CoordinateReferenceSystem,
+    ) -> Result<CoordinateTransformation, Error> {
+        let projector =
+            |crs: &CoordinateReferenceSystem| -> Result<Option<Arc<dyn Projector>>, Error> {
+                match &crs.kind {
+                    CrsKind::Projected(projection) => {
+                        Ok(Some(Arc::from(projection.projector(&crs.datum.ellipsoid)?)))
+                    }
+                    _ => Ok(None),
+                }
+            };
+        let same_datum = source.datum.is_interchangeable(&target.datum);
+        let datum_shift = match (&source.datum.to_wgs84, &target.datum.to_wgs84) {
+            (Some(src), Some(dst)) if !same_datum => Some((Helmert::new(src), Helmert::new(dst))),
+            _ => None,
+        };
+        Ok(CoordinateTransformation {
+            source: source.clone(),
+            target: target.clone(),
+            source_projector: projector(source)?,
+            target_projector: projector(target)?,
+            datum_shift: datum_shift,
+            is_identity: same_datum
+                && source.kind == target.kind
+                && source.prime_meridian == target.prime_meridian
+                && source.angular_unit == target.angular_unit
+                && source.linear_unit == target.linear_unit,
+        })
+    }
+
+    /// Creates the transformation between two EPSG codes.
+    pub fn from_epsg(source: u16, target: u16) -> Result<CoordinateTransformation, Error> {
+        CoordinateTransformation::new(
+            &CoordinateReferenceSystem::from_epsg(source)?,
+            &CoordinateReferenceSystem::from_epsg(target)?,
+        )
+    }
+
+    /// Creates the transformation between two systems described by well-known text.
+    pub fn from_wkt(source: &str, target: &str) -> Result<CoordinateTransformation, Error> {
+        CoordinateTransformation::new(
+            &CoordinateReferenceSystem::from_wkt(source)?,
+            &CoordinateReferenceSystem::from_wkt(target)?,
+        )
+    }
+
+    /// Returns the transformation in the opposite direction.
+    pub fn inverse(&self) -> CoordinateTransformation {
+        CoordinateTransformation {
+            source: self.target.clone(),
+            target: self.source.clone(),
+            source_projector: self.target_projector.clone(),
+            target_projector: self.source_projector.clone(),
+            datum_shift: self
+                .datum_shift
+                .as_ref()
+                .map(|(src, dst)| (dst.clone(), src.clone())),
+            is_identity: self.is_identity,
+        }
+    }
+
+    /// Returns true if the two systems are the same, so that coordinates are unchanged.
+    pub fn is_identity(&self) -> bool {
+        self.is_identity
+    }
+
+    /// Transforms a point's x and y coordinates, i.e. its easting and northing or its
+    /// longitude and latitude. Returns `None` if the point cannot be represented in the
+    /// target system, e.g. a pole in Mercator coordinates.
+    pub fn transform(&self, x: f64, y: f64) -> Option<(f64, f64)> {
+        self.transform_3d(x, y, 0f64).map(|(x, y, _)| (x, y))
+    }
+
+    /// Transforms a point's x, y, and z coordinates. The z coordinate is a height above
+    /// the ellipsoid, in metres, which changes with a datum shift, except for geocentric
+    /// systems, where it is the Z axis.
+    pub fn transform_3d(&self, x: f64, y: f64, z: f64) -> Option<(f64, f64, f64)> {
+        if self.is_identity {
+            return Some((x, y, z));
+        }
+        let (lon, lat, h) = self.to_geodetic(x, y, z)?;
+        let (lon, lat, h) = match &self.datum_shift {
+            Some((src, dst)) => {
+                let (gx, gy, gz) = self
+                    .source
+                    .datum
+                    .ellipsoid
+                    .geodetic_to_geocentric(lon, lat, h);
+                let (gx, gy, gz) = src.to_wgs84(gx, gy, gz);
+                let (gx, gy, gz) = dst.from_wgs84(gx, gy, gz);
+                self.target
+                    .datum
+                    .ellipsoid
+                    .geocentric_to_geodetic(gx, gy, gz)
+            }
+            None => (lon, lat, h),
+        };
+        self.from_geodetic(lon, lat, h)
+    }
+
+    /// Converts source coordinates to longitude and latitude (in radians east of
+    /// Greenwich) and ellipsoidal height.
+    fn to_geodetic(&self, x: f64, y: f64, z: f64) -> Option<(f64, f64, f64)> {
+        let crs = &self.source;
+        match &crs.kind {
+            CrsKind::Geographic => Some((
+                x * crs.angular_unit + crs.prime_meridian.to_radians(),
+                y * crs.angular_unit,
+                z,
+            )),
+            CrsKind::Projected(_) => {
+                let projector = self.source_projector.as_ref()?;
+                let (lon, lat) = projector.inverse(x * crs.linear_unit, y * crs.linear_unit)?;
+                Some((lon + crs.prime_meridian.to_radians(), lat, z))
+            }
+            CrsKind::Geocentric => Some(crs.datum.ellipsoid.geocentric_to_geodetic(
+                x * crs.linear_unit,
+                y * crs.linear_unit,
+                z * crs.linear_unit,
+            )),
+        }
+    }
+
+    fn from_geodetic(&self, lon: f64, lat: f64, h: f64) -> Option<(f64, f64, f64)> {
+        let crs = &self.target;
+        let result = match &crs.kind {
+            CrsKind::Geographic => {
+                let mut lon = lon - crs.prime_meridian.to_radians();
+                if lon > std::f64::consts::PI {
+                    lon -= 2f64 * std::f64::consts::PI;
+                } else if lon < -std::f64::consts::PI {
+                    lon += 2f64 * std::f64::consts::PI;
+                }
+                (lon / crs.angular_unit, lat / crs.angular_unit, h)
+            }
+            CrsKind::Projected(_) => {
+                let projector = self.target_projector.as_ref()?;
+                let (x, y) = projector.forward(lon - crs.prime_meridian.to_radians(), lat)?;
+                (x / crs.linear_unit, y / crs.linear_unit, h)
+            }
+            CrsKind::Geocentric => {
+                let (x, y, z) = crs.datum.ellipsoid.geodetic_to_geocentric(lon, lat, h);
+                (
+                    x / crs.linear_unit,
+                    y / crs.linear_unit,
+                    z / crs.linear_unit,
+                )
+            }
+        };
+        if result.0.is_finite() && result.1.is_finite() && result.2.is_finite() {
+            Some(result)
+        } else {
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::super::datum::{Helmert, HelmertParameters};
+    use super::super::ellipsoid::Ellipsoid;
+    use super::super::projections::{Projection, ProjectionMethod, ProjectionParameters};
+    use super::*;
+
+    fn dms(d: f64, m: f64, s: f64) -> f64 {
+        d.signum() * (d.abs() + m / 60f64 + s / 3600f64)
+    }
+
+    fn projected(
+        ellipsoid: Ellipsoid,
+        method: ProjectionMethod,
+        params: ProjectionParameters,
+        linear_unit: f64,
+    ) -> CoordinateReferenceSystem {
+        let mut crs = CoordinateReferenceSystem::wgs84();
+        crs.datum.ellipsoid = ellipsoid;
+        crs.kind = CrsKind::Projected(Projection::new(method, params));
+        crs.linear_unit = linear_unit;
+        crs
+    }
+
+    /// Checks a projection against a worked example and that the inverse recovers the
+    /// geographic coordinates.
+    fn check(crs: &CoordinateReferenceSystem, lon: f64, lat: f64, x: f64, y: f64, tolerance: f64) {
+        let mut geographic = CoordinateReferenceSystem::wgs84();
+        geographic.datum = crs.datum.clone();
+        let transformation = CoordinateTransformation::new(&geographic, crs).unwrap();
+        let (px, py) = transformation.transform(lon, lat).unwrap();
+        assert!(
+            (px - x).abs() < tolerance && (py - y).abs() < tolerance,
+            "expected ({}, {}), found ({}, {})",
+            x,
+            y,
+            px,
+            py
+        );
+        let (plon, plat) = transformation.inverse().transform(px, py).unwrap();
+        assert!((plon - lon).abs() < 1e-9 && (plat - lat).abs() < 1e-9);
+    }
+
+    // The worked examples are from EPSG Guidance Note 7-2.
+
+    #[test]
+    fn test_transverse_mercator() {
+        let params = ProjectionParameters {
+            latitude_of_origin: 49f64,
+            central_meridian: -2f64,
+            scale_factor: 0.9996012717,
+            false_easting: 400000f64,
+            false_northing: -100000f64,
+            ..Default::default()
+        };
+        let airy = Ellipsoid::new("Airy_1830", 6377563.396, 299.3249646);
+        let crs = projected(airy, ProjectionMethod::TransverseMercator, params, 1f64);
+        check(
+            &crs,
+            dms(0.0, 30.0, 0.0),
+            dms(50.0, 30.0, 0.0),
+            577274.99,
+            69740.50,
+            0.01,
+        );
+    }
+
+    #[test]
+    fn test_utm_from_epsg() {
+        let transformation = CoordinateTransformation::from_epsg(4326, 32618).unwrap();
+        let (x, y) = transformation.transform(-75f64, 40f64).unwrap();
+        assert!((x - 500000f64).abs() < 0.001);
+        assert!((y - 4427757.22).abs() < 0.01);
+        let (x, y) = transformation.transform(-73.5, 45.5).unwrap();
+        let (lon, lat) = transformation.inverse().transform(x, y).unwrap();
+        assert!((lon + 73.5).abs() < 1e-9 && (lat - 45.5).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_lambert_conformal_conic() {
+        let us_foot = 0.3048006096012192;
+        let params = ProjectionParameters {
+            latitude_of_origin: dms(27.0, 50.0, 0.0),
+            central_meridian: -99f64,
+            standard_parallel_1: Some(dms(28.0, 23.0, 0.0)),
+            standard_parallel_2: Some(dms(30.0, 17.0, 0.0)),
+            false_easting: 2000000f64 * us_foot,
+            ..Default::default()
+        };
+        let clarke = Ellipsoid::new("Clarke_1866", 6378206.4, 294.9786982);
+        let crs = projected(
+            clarke,
+            ProjectionMethod::LambertConformalConic,
+            params,
+            us_foot,
+        );
+        check(
+            &crs,
+            -96f64,
+            dms(28.0, 30.0, 0.0),
+            2963503.91,
+            254759.80,
+            0.01,
+        );
+    }
+
+    #[test]
+    fn test_albers_round_trip() {
+        // NAD83 / Conus Albers (EPSG:5070)
+        let transformation = CoordinateTransformation::from_epsg(4269, 5070).unwrap();
+        let (x, y) = transformation.transform(-96f64, 23f64).unwrap();
+        assert!(x.abs() < 0.001 && y.abs() < 0.001);
+        let (x, y) = transformation.transform(-120f64, 45f64).unwrap();
+        let (lon, lat) = transformation.inverse().transform(x, y).unwrap();
+        assert!((lon + 120f64).abs() < 1e-9 && (lat - 45f64).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_polar_stereographic() {
+        let params = ProjectionParameters {
+            latitude_of_origin: 90f64,
+            scale_factor: 0.994,
+            false_easting: 2000000f64,
+            false_northing: 2000000f64,
+            ..Default::default()
+        };
+        let crs = projected(
+            Ellipsoid::wgs84(),
+            ProjectionMethod::PolarStereographic,
+            params,
+            1f64,
+        );
+        check(&crs, 44f64, 73f64, 3320416.75, 632668.43, 0.01);
+
+        let params = ProjectionParameters {
+            central_meridian: 70f64,
+            standard_parallel_1: Some(-71f64),
+            false_easting: 6000000f64,
+            false_northing: 6000000f64,
+            ..Default::default()
+        };
+        let crs = projected(
+            Ellipsoid::wgs84(),
+            ProjectionMethod::PolarStereographic,
+            params,
+            1f64,
+        );
+        check(&crs, 120f64, -75f64, 7255380.79, 7053389.56, 0.01);
+    }
+
+    #[test]
+    fn test_mercator() {
+        let params = ProjectionParameters {
+            central_meridian: 110f64,
+            scale_factor: 0.997,
+            false_easting: 3900000f64,
+            false_northing: 900000f64,
+            ..Default::default()
+        };
+        let bessel = Ellipsoid::new("Bessel_1841", 6377397.155, 299.1528128);
+        let crs = projected(bessel, ProjectionMethod::Mercator, params, 1f64);
+        check(&crs, 120f64, -3f64, 5009726.58, 569150.82, 0.01);
+    }
+
+    #[test]
+    fn test_web_mercator() {
+        let transformation = CoordinateTransformation::from_epsg(4326, 3857).unwrap();
+        let (x, y) = transformation
+            .transform(dms(-100.0, 20.0, 0.0), dms(24.0, 22.0, 54.433))
+            .unwrap();
+        assert!((x + 11169055.58).abs() < 0.01 && (y - 2800000.00).abs() < 0.01);
+        assert!(transformation.transform(0f64, 90f64).is_none());
+    }
+
+    #[test]
+    fn test_geocentric() {
+        let (x, y, z) = Ellipsoid::wgs84().geodetic_to_geocentric(
+            dms(2.0, 7.0, 46.38).to_radians(),
+            dms(53.0, 48.0, 33.82).to_radians(),
+            73f64,
+        );
+        assert!((x - 3771793.968).abs() < 0.001);
+        assert!((y - 140253.342).abs() < 0.001);
+        assert!((z - 5124304.349).abs() < 0.001);
+        let (lon, lat, h) = Ellipsoid::wgs84().geocentric_to_geodetic(x, y, z);
+        assert!((lon.to_degrees() - dms(2.0, 7.0, 46.38)).abs() < 1e-9);
+        assert!((lat.to_degrees() - dms(53.0, 48.0, 33.82)).abs() < 1e-9);
+        assert!((h - 73f64).abs() < 0.001);
+    }
+
+    #[test]
+    fn test_helmert() {
+        // WGS72 to WGS84, position vector transformation
+        let params = HelmertParameters::new(0.0, 0.0, 4.5, 0.0, 0.0, 0.554, 0.219);
+        let helmert = Helmert::new(&params);
+        let (x, y, z) = helmert.to_wgs84(3657660.66, 255768.55, 5201382.11);
+        assert!((x - 3657660.78).abs() < 0.01);
+        assert!((y - 255778.43).abs() < 0.01);
+        assert!((z - 5201387.75).abs() < 0.01);
+        let (x, y, z) = helmert.from_wgs84(x, y, z);
+        assert!((x - 3657660.66).abs() < 1e-6);
+        assert!((y - 255768.55).abs() < 1e-6);
+        assert!((z - 5201382.11).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_datum_shift() {
+        // OSGB 1936 / British National Grid to WGS84; the shift is about 100 m.
+        let transformation = CoordinateTransformation::from_epsg(27700, 4326).unwrap();
+        let (lon, lat) = transformation.transform(530000f64, 180000f64).unwrap();
+        let (lon_ballpark, lat_ballpark) = {
+            let mut osgb = CoordinateReferenceSystem::from_epsg(27700).unwrap();
+            osgb.datum.to_wgs84 = None;
+            CoordinateTransformation::new(&osgb, &CoordinateReferenceSystem::wgs84())
+                .unwrap()
+                .transform(530000f64, 180000f64)
+                .unwrap()
+        };
+        assert!((lon - lon_ballpark).abs() > 0.001);
+        assert!((lon + 0.12835).abs() < 0.00001 && (lat - 51.50399).abs() < 0.00001);
+        assert!((lat - lat_ballpark).abs() < 0.01);
+    }
+}
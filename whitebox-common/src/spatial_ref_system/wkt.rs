@@ -0,0 +1,184 @@
+/*
+This code is part of the WhiteboxTools geospatial analysis library.
+License: MIT
+*/
+use std::io::{Error, ErrorKind};
+
+/// A value within a well-known text (WKT) element.
+#[derive(Clone, Debug, PartialEq)]
+pub enum WktValue {
+    Text(String),
+    Number(f64),
+    /// An unquoted enumeration value, e.g. the EAST of AXIS["Easting",EAST].
+    Keyword(String),
+    Node(WktNode),
+}
+
+/// An element of well-known text, e.g. SPHEROID["GRS_1980",6378137.0,298.257222101],
+/// with its keyword and the values within its brackets.
+#[derive(Clone, Debug, PartialEq)]
+pub struct WktNode {
+    pub keyword: String,
+    pub values: Vec<WktValue>,
+}
+
+impl WktNode {
+    /// Parses a WKT string into a tree of elements.
+    pub fn parse(wkt: &str) -> Result<WktNode, Error> {
+        let chars: Vec<char> = wkt.chars().collect();
+        let mut pos = 0usize;
+        let node = match parse_value(&chars, &mut pos)? {
+            WktValue::Node(node) => node,
+            _ => return Err(wkt_error("it does not begin with a keyword and bracket")),
+        };
+        skip_whitespace(&chars, &mut pos);
+        if pos < chars.len() {
+            return Err(wkt_error("there is text following the closing bracket"));
+        }
+        Ok(node)
+    }
+
+    /// Returns true if the element's keyword matches, ignoring case.
+    pub fn is(&self, keyword: &str) -> bool {
+        self.keyword.eq_ignore_ascii_case(keyword)
+    }
+
+    /// Returns the first child element with one of the keywords, ignoring case.
+    pub fn child(&self, keywords: &[&str]) -> Option<&WktNode> {
+        self.children()
+            .find(|node| keywords.iter().any(|keyword| node.is(keyword)))
+    }
+
+    /// Returns all of the child elements with one of the keywords, ignoring case.
+    pub fn children_named<'a>(
+        &'a self,
+        keywords: &'a [&'a str],
+    ) -> impl Iterator<Item = &'a WktNode> + 'a {
+        self.children()
+            .filter(move |node| keywords.iter().any(|keyword| node.is(keyword)))
+    }
+
+    pub fn children(&self) -> impl Iterator<Item = &WktNode> {
+        self.values.iter().filter_map(|value| match value {
+            WktValue::Node(node) => Some(node),
+            _ => None,
+        })
+    }
+
+    /// Returns the element's name, i.e. its first value, if it is quoted text.
+    pub fn name(&self) -> Option<&str> {
+        match self.values.first() {
+            Some(WktValue::Text(text)) => Some(text),
+            _ => None,
+        }
+    }
+
+    /// Returns the numeric values of the element, in order.
+    pub fn numbers(&self) -> Vec<f64> {
+        self.values
+            .iter()
+            .filter_map(|value| match value {
+                WktValue::Number(n) => Some(*n),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// Returns the numeric value at the index, counting all values.
+    pub fn number(&self, index: usize) -> Option<f64> {
+        match self.values.get(index) {
+            Some(WktValue::Number(n)) => Some(*n),
+            Some(WktValue::Text(text)) => text.trim().parse::<f64>().ok(),
+            _ => None,
+        }
+    }
+}
+
+fn wkt_error(reason: &str) -> Error {
+    Error::new(
+        ErrorKind::InvalidData,
+        format!("The well-known text (WKT) could not be parsed: {}.", reason),
+    )
+}
+
+fn skip_whitespace(chars: &[char], pos: &mut usize) {
+    while *pos < chars.len() && chars[*pos].is_whitespace() {
+        *pos += 1;
+    }
+}
+
+fn parse_value(chars: &[char], pos: &mut usize) -> Result<WktValue, Error> {
+    skip_whitespace(chars, pos);
+    if *pos >= chars.len() {
+        return Err(wkt_error("it ends unexpectedly"));
+    }
+    let c = chars[*pos];
+    if c == '"' {
+        // A quoted string, within which a doubled quote is a literal quote.
+        let mut text = String::new();
+        *pos += 1;
+        loop {
+            if *pos >= chars.len() {
+                return Err(wkt_error("a quoted string is not closed"));
+            }
+            if chars[*pos] == '"' {
+                if *pos + 1 < chars.len() && chars[*pos + 1] == '"' {
+                    text.push('"');
+                    *pos += 2;
+                    continue;
+                }
+                *pos += 1;
+                break;
+            }
+            text.push(chars[*pos]);
+            *pos += 1;
+        }
+        return Ok(WktValue::Text(text));
+    }
+
+    let start = *pos;
+    while *pos < chars.len() && !chars[*pos].is_whitespace() && !"[](),\"".contains(chars[*pos]) {
+        *pos += 1;
+    }
+    let token: String = chars[start..*pos].iter().collect();
+    if token.is_empty() {
+        return Err(wkt_error(&format!("an unexpected '{}' was found", c)));
+    }
+    skip_whitespace(chars, pos);
+    if *pos < chars.len() && (chars[*pos] == '[' || chars[*pos] == '(') {
+        let close = if chars[*pos] == '[' { ']' } else { ')' };
+        *pos += 1;
+        let mut values = vec![];
+        skip_whitespace(chars, pos);
+        if *pos < chars.len() && chars[*pos] == close {
+            *pos += 1;
+        } else {
+            loop {
+                values.push(parse_value(chars, pos)?);
+                skip_whitespace(chars, pos);
+                if *pos >= chars.len() {
+                    return Err(wkt_error(&format!("the {} element is not closed", token)));
+                }
+                if chars[*pos] == ',' {
+                    *pos += 1;
+                } else if chars[*pos] == close {
+                    *pos += 1;
+                    break;
+                } else {
+                    return Err(wkt_error(&format!(
+                        "an unexpected '{}' was found in the {} element",
+                        chars[*pos], token
+                    )));
+                }
+            }
+        }
+        return Ok(WktValue::Node(WktNode {
+            keyword: token,
+            values: values,
+        }));
+    }
+    match token.parse::<f64>() {
+        Ok(n) => Ok(WktValue::Number(n)),
+        Err(_) => Ok(WktValue::Keyword(token)),
+    }
+}
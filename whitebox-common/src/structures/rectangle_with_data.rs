@@ -3,7 +3,7 @@ use crate::rstar::{Envelope, Point, PointDistance, RTreeObject, AABB};
 
 type RectangleF64 = Rectangle<[f64; 2]>;
 
-#[derive(Debug)]
+#[derive(Clone, Debug)]
 pub struct RectangleWithData<T> {
     pub data: T,
     pub rectangle: RectangleF64,
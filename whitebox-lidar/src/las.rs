@@ -15,6 +15,7 @@ use super::vlr::Vlr;
 use super::zlidar_compression::{ZlidarCompression};
 use whitebox_raster::geotiff::geokeys::GeoKeys;
 use whitebox_common::spatial_ref_system::esri_wkt_from_epsg;
+use whitebox_common::spatial_ref_system;
 use whitebox_common::structures::{ BoundingBox, Point3D };
 use whitebox_common::utils::{ ByteOrderReader, Endianness };
 use byteorder::{ LittleEndian, WriteBytesExt };
@@ -520,6 +521,64 @@ impl LasFile {
         self.geokeys.find_epsg_code()
     }
 
+    /// Returns the coordinate reference system of the point cloud, read from its WKT
+    /// record or, failing that, the EPSG code of its GeoKeys.
+    pub fn get_coordinate_reference_system(
+        &self,
+    ) -> Option<spatial_ref_system::CoordinateReferenceSystem> {
+        spatial_ref_system::CoordinateReferenceSystem::from_wkt_or_epsg(
+            &self.wkt,
+            self.get_epsg_code(),
+        )
+    }
+
+    /// Sets the coordinate reference system of the point cloud, replacing its projection
+    /// VLRs with an OGC WKT record and, if the EPSG code of the system is known (i.e. is
+    /// not zero), a GeoKey directory that refers to it.
+    pub fn set_coordinate_reference_system(&mut self, wkt: &str, epsg_code: u16) {
+        self.vlr_data
+            .retain(|vlr| !vlr.user_id.starts_with("LASF_Projection"));
+
+        let mut vlr: Vlr = Default::default();
+        vlr.user_id = String::from("LASF_Projection");
+        vlr.record_id = 2112u16;
+        vlr.description = String::from("OGC WKT Coordinate System");
+        vlr.binary_data = format!("{}\0", wkt).as_bytes().to_vec();
+        vlr.record_length_after_header = vlr.binary_data.len() as u16;
+        self.vlr_data.push(vlr);
+        self.wkt = wkt.to_string();
+
+        self.geokeys = GeoKeys::default();
+        if epsg_code != 0 {
+            let is_geographic = spatial_ref_system::CoordinateReferenceSystem::from_epsg(epsg_code)
+                .map(|crs| crs.is_geographic())
+                .unwrap_or(false);
+            // GTModelTypeGeoKey, followed by GeographicTypeGeoKey or ProjectedCSTypeGeoKey
+            let keys: Vec<u16> = if is_geographic {
+                vec![1, 1, 0, 2, 1024, 0, 1, 2, 2048, 0, 1, epsg_code]
+            } else {
+                vec![1, 1, 0, 2, 1024, 0, 1, 1, 3072, 0, 1, epsg_code]
+            };
+            let mut vlr: Vlr = Default::default();
+            vlr.user_id = String::from("LASF_Projection");
+            vlr.record_id = 34735u16;
+            vlr.description = String::from("GeoTiff Projection Keys");
+            for key in keys {
+                vlr.binary_data.write_u16::<LittleEndian>(key).unwrap();
+            }
+            vlr.record_length_after_header = vlr.binary_data.len() as u16;
+            self.geokeys
+                .add_key_directory(&vlr.binary_data, Endianness::LittleEndian);
+            self.vlr_data.push(vlr);
+        }
+        self.header.number_of_vlrs = self.vlr_data.len() as u32;
+
+        if self.header.point_format >= 6 {
+            // the WKT bit of the global encoding, which is required for these point formats
+            self.header.global_encoding.value |= 16u16;
+        }
+    }
+
     pub fn read(&mut self) -> Result<(), Error> {
         if self.file_name.to_lowercase().ends_with(".zlidar") {
             return self.read_zlidar_data();
@@ -6806,6 +6865,23 @@ impl LidarPointRecord {
             LidarPointRecord::PointRecord10 { point_data, .. } => point_data.clone(),
         };
     }
+
+    /// Replaces the point data of the record, e.g. once its coordinates are transformed.
+    pub fn set_point_data(&mut self, data: PointData) {
+        match self {
+            LidarPointRecord::PointRecord0 { point_data } => *point_data = data,
+            LidarPointRecord::PointRecord1 { point_data, .. } => *point_data = data,
+            LidarPointRecord::PointRecord2 { point_data, .. } => *point_data = data,
+            LidarPointRecord::PointRecord3 { point_data, .. } => *point_data = data,
+            LidarPointRecord::PointRecord4 { point_data, .. } => *point_data = data,
+            LidarPointRecord::PointRecord5 { point_data, .. } => *point_data = data,
+            LidarPointRecord::PointRecord6 { point_data, .. } => *point_data = data,
+            LidarPointRecord::PointRecord7 { point_data, .. } => *point_data = data,
+            LidarPointRecord::PointRecord8 { point_data, .. } => *point_data = data,
+            LidarPointRecord::PointRecord9 { point_data, .. } => *point_data = data,
+            LidarPointRecord::PointRecord10 { point_data, .. } => *point_data = data,
+        }
+    }
 }
 
 #[derive(Default, Copy, Clone, Debug)]
@@ -520,7 +520,7 @@ fn run(args: &Vec<String>) -> Result<(), std::io::Error> {
                 println!("Output file written")
             }
         }
-        Err(e) => return Err(e),
+        Err(e) => return Err(e.into()),
     };
 
     let elapsed_time = get_formatted_elapsed_time(start);
@@ -624,7 +624,7 @@ fn run(args: &Vec<String>) -> Result<(), std::io::Error> {
                 println!("Output file written")
             }
         }
-        Err(e) => return Err(e),
+        Err(e) => return Err(e.into()),
     };
 
     if configurations.verbose_mode {
@@ -941,7 +941,7 @@ fn run(args: &Vec<String>) -> Result<(), std::io::Error> {
     };
     let _ = match lsp_raster.write() {
         Ok(_) => (),
-        Err(e) => return Err(e),
+        Err(e) => return Err(e.into()),
     };
     drop(lsp_raster);
 
@@ -963,7 +963,7 @@ fn run(args: &Vec<String>) -> Result<(), std::io::Error> {
     };
     let _ = match scl_raster.write() {
         Ok(_) => (),
-        Err(e) => return Err(e),
+        Err(e) => return Err(e.into()),
     };
     drop(scl_raster);
 
@@ -984,7 +984,7 @@ fn run(args: &Vec<String>) -> Result<(), std::io::Error> {
     };
     let _ = match zsc_raster.write() {
         Ok(_) => (),
-        Err(e) => return Err(e),
+        Err(e) => return Err(e.into()),
     };
 
     if configurations.verbose_mode {
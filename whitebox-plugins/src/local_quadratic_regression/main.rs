@@ -270,7 +270,7 @@ fn run(args: &Vec<String>) -> Result<(), std::io::Error> {
 
                 for col in 0..columns {
 
-                    z = input[(row, col)];
+                    z = input.get_value(row, col);
 
                     if z != nodata {
 
@@ -286,7 +286,7 @@ fn run(args: &Vec<String>) -> Result<(), std::io::Error> {
                         let mut zs = vec![];
 
                         for c in 0..num_cells {
-                            zi = input[((row + dy[c] as isize), (col + dx[c] as isize))];
+                            zi = input.get_value(row + dy[c] as isize, col + dx[c] as isize);
                             if zi != nodata {
                                 xs.push(dx[c] as f64 * resolution);
                                 ys.push(dy[c] as f64 * resolution);
@@ -412,7 +412,7 @@ fn run(args: &Vec<String>) -> Result<(), std::io::Error> {
                     println!("Output file {:?} written", o+1);
                 }
             }
-            Err(e) => return Err(e),
+            Err(e) => return Err(e.into()),
         };
     }
 
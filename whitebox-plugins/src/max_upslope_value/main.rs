@@ -228,7 +228,7 @@ fn run(args: &Vec<String>) -> Result<(), std::io::Error> {
                         max_slope = f64::MIN;
                         neighbouring_nodata = false;
                         for i in 0..8 {
-                            z_n = input[(row + dy[i], col + dx[i])];
+                            z_n = input.get_value(row + dy[i], col + dx[i]);
                             if z_n != nodata {
                                 slope = (z - z_n) / grid_lengths[i];
                                 if slope > max_slope && slope > 0f64 {
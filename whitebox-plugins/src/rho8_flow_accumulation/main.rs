@@ -272,7 +272,7 @@ fn run(args: &Vec<String>) -> Result<(), std::io::Error> {
                             let mut dir = 0;
                             let mut max_slope = f64::MIN;
                             for i in 0..8 {
-                                z_n = input[(row + d_y[i], col + d_x[i])];
+                                z_n = input.get_value(row + d_y[i], col + d_x[i]);
                                 if z_n != nodata {
                                     slope = match i {
                                         1 | 3 | 5 | 7 => (z - z_n),
@@ -471,7 +471,7 @@ fn run(args: &Vec<String>) -> Result<(), std::io::Error> {
         let cell = stack.pop().expect("Error during pop operation.");
         row = cell.0;
         col = cell.1;
-        fa = output[(row, col)];
+        fa = output.get_value(row, col);
         num_inflowing.decrement(row, col, 1i8);
         dir = flow_dir[(row, col)];
         if dir >= 0 {
@@ -522,11 +522,17 @@ fn run(args: &Vec<String>) -> Result<(), std::io::Error> {
                 } else {
                     let dir = flow_dir[(row, col)];
                     if dir >= 0 {
-                        output[(row, col)] =
-                            (output[(row, col)] * cell_area / flow_widths[dir as usize]).ln();
+                        output.set_value(
+                            row,
+                            col,
+                            (output.get_value(row, col) * cell_area / flow_widths[dir as usize]).ln(),
+                        );
                     } else {
-                        output[(row, col)] =
-                            (output[(row, col)] * cell_area / flow_widths[3]).ln();
+                        output.set_value(
+                            row,
+                            col,
+                            (output.get_value(row, col) * cell_area / flow_widths[3]).ln(),
+                        );
                     }
                 }
             }
@@ -594,7 +600,7 @@ fn run(args: &Vec<String>) -> Result<(), std::io::Error> {
                 println!("Output file written")
             }
         }
-        Err(e) => return Err(e),
+        Err(e) => return Err(e.into()),
     };
     if configurations.verbose_mode {
         println!(
@@ -21,12 +21,10 @@ pub fn read_arcascii(
     let mut yllcorner: f64 = f64::NEG_INFINITY;
     //let mut likely_float = false;
     for line in f.lines() {
-        let line_unwrapped = line.unwrap();
-        let mut line_split = line_unwrapped.split(" ");
-        let mut vec = line_split.collect::<Vec<&str>>();
-        if vec.len() == 1 {
-            line_split = line_unwrapped.split("\t");
-            vec = line_split.collect::<Vec<&str>>();
+        let line_unwrapped = line?;
+        let vec = line_unwrapped.split_whitespace().collect::<Vec<&str>>();
+        if vec.is_empty() {
+            continue;
         }
         if vec[0].to_lowercase().contains("nrows") {
             configs.rows = vec[vec.len() - 1].trim().parse::<f32>().unwrap() as usize;
@@ -147,9 +145,9 @@ pub fn write_arcascii<'a>(r: &'a mut Raster) -> Result<(), Error> {
     let mut col = 0;
     for i in 0..num_cells {
         if col < r.configs.columns - 1 {
-            s2 += &format!("{:.*} ", 2, r.data[i]);
+            s2 += &format!("{:.*} ", 2, r.get_cell(i));
         } else {
-            s2 += &format!("{:.*}\n", 2, r.data[i]);
+            s2 += &format!("{:.*}\n", 2, r.get_cell(i));
         }
         col += 1;
         if col == r.configs.columns {
@@ -159,7 +157,7 @@ pub fn write_arcascii<'a>(r: &'a mut Raster) -> Result<(), Error> {
         }
     }
 
-    let _ = writer.flush();
+    writer.flush()?;
 
     Ok(())
 }
@@ -30,7 +30,7 @@ pub fn read_arcbinary(
     let mut yllcorner: f64 = f64::NEG_INFINITY;
 
     for line in f.lines() {
-        let line_unwrapped = line.unwrap();
+        let line_unwrapped = line?;
         // println!("{}", line_unwrapped);
         let line_split = line_unwrapped.split(" ");
         let vec = line_split.collect::<Vec<&str>>();
@@ -110,11 +110,7 @@ pub fn read_arcbinary(
 
     // read the data file
     // let data_file = file_name.replace(".hdr", ".flt");
-    let data_file = Path::new(&file_name)
-        .with_extension("flt")
-        .into_os_string()
-        .into_string()
-        .unwrap();
+    let data_file = get_data_file_name(&file_name, "hdr", "flt");
     let mut f = File::open(data_file.clone())?;
 
     configs.minimum = f64::INFINITY;
@@ -203,15 +199,11 @@ pub fn write_arcbinary<'a>(r: &'a mut Raster) -> Result<(), Error> {
         writer.write_all("BYTEORDER MSBFIRST\n".as_bytes())?;
     }
 
-    let _ = writer.flush();
+    writer.flush()?;
 
     // read the data file
     // let data_file = r.file_name.replace(".hdr", ".flt");
-    let data_file = Path::new(&r.file_name)
-        .with_extension("flt")
-        .into_os_string()
-        .into_string()
-        .unwrap();
+    let data_file = get_data_file_name(&r.file_name, "hdr", "flt");
     let f = File::create(&data_file)?;
     let mut writer = BufWriter::new(f);
 
@@ -219,11 +211,11 @@ pub fn write_arcbinary<'a>(r: &'a mut Raster) -> Result<(), Error> {
 
     let num_cells: usize = r.configs.rows * r.configs.columns;
     for i in 0..num_cells {
-        u32_bytes = unsafe { mem::transmute(r.data[i] as f32) };
+        u32_bytes = unsafe { mem::transmute(r.get_cell(i) as f32) };
         writer.write(&u32_bytes)?;
     }
 
-    let _ = writer.flush();
+    writer.flush()?;
 
     Ok(())
 }
@@ -0,0 +1,612 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::fs::File;
+use std::io::prelude::*;
+use std::io::{BufWriter, Error};
+use std::path::Path;
+
+/// A colour, given as its (red, green, blue, alpha) components.
+pub type Colour = (u8, u8, u8, u8);
+
+/// A colour table, which assigns a colour to each of the values (classes) of a
+/// categorical raster, e.g. the ColorMap of a paletted GeoTIFF.
+#[derive(Default, Clone, Debug, PartialEq)]
+pub struct ColourTable {
+    colours: BTreeMap<i64, Colour>,
+}
+
+impl ColourTable {
+    pub fn new() -> ColourTable {
+        ColourTable::default()
+    }
+
+    /// Creates a colour table that assigns distinct colours to a set of class values,
+    /// cycling through a qualitative palette if there are many classes.
+    pub fn qualitative(values: &[i64]) -> ColourTable {
+        const PALETTE: [Colour; 12] = [
+            (166, 206, 227, 255),
+            (31, 120, 180, 255),
+            (178, 223, 138, 255),
+            (51, 160, 44, 255),
+            (251, 154, 153, 255),
+            (227, 26, 28, 255),
+            (253, 191, 111, 255),
+            (255, 127, 0, 255),
+            (202, 178, 214, 255),
+            (106, 61, 154, 255),
+            (255, 255, 153, 255),
+            (177, 89, 40, 255),
+        ];
+        let mut table = ColourTable::new();
+        for (i, &value) in values.iter().enumerate() {
+            table.set_colour(value, PALETTE[i % PALETTE.len()]);
+        }
+        table
+    }
+
+    pub fn set_colour(&mut self, value: i64, colour: Colour) {
+        self.colours.insert(value, colour);
+    }
+
+    pub fn get_colour(&self, value: i64) -> Option<Colour> {
+        self.colours.get(&value).copied()
+    }
+
+    pub fn remove_colour(&mut self, value: i64) {
+        self.colours.remove(&value);
+    }
+
+    /// Returns the values and colours of the table, in order of increasing value.
+    pub fn iter(&self) -> impl Iterator<Item = (i64, Colour)> + '_ {
+        self.colours.iter().map(|(&value, &colour)| (value, colour))
+    }
+
+    /// Returns the smallest and largest values that have a colour, if any.
+    pub fn value_range(&self) -> Option<(i64, i64)> {
+        match (self.colours.keys().next(), self.colours.keys().next_back()) {
+            (Some(&min), Some(&max)) => Some((min, max)),
+            _ => None,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.colours.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.colours.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        self.colours.clear();
+    }
+}
+
+/// A row of a raster attribute table, describing one of the classes of a categorical raster.
+#[derive(Default, Clone, Debug, PartialEq)]
+pub struct AttributeTableEntry {
+    /// The value of the grid cells that belong to the class.
+    pub value: i64,
+    /// The number of grid cells that belong to the class.
+    pub count: usize,
+    /// The name, or label, of the class.
+    pub name: String,
+    /// The colour used to display the class.
+    pub colour: Option<Colour>,
+}
+
+/// A raster attribute table, which describes the classes of a categorical raster, e.g.
+/// the output of a classification. Entries are kept in order of increasing value.
+#[derive(Default, Clone, Debug, PartialEq)]
+pub struct AttributeTable {
+    entries: Vec<AttributeTableEntry>,
+}
+
+impl AttributeTable {
+    pub fn new() -> AttributeTable {
+        AttributeTable::default()
+    }
+
+    /// Adds an entry for a class value, or renames the class if it already has one,
+    /// returning the entry.
+    pub fn add_entry(&mut self, value: i64, name: &str) -> &mut AttributeTableEntry {
+        let i = match self.entries.binary_search_by_key(&value, |e| e.value) {
+            Ok(i) => {
+                self.entries[i].name = name.to_string();
+                i
+            }
+            Err(i) => {
+                self.entries.insert(
+                    i,
+                    AttributeTableEntry {
+                        value: value,
+                        name: name.to_string(),
+                        ..Default::default()
+                    },
+                );
+                i
+            }
+        };
+        &mut self.entries[i]
+    }
+
+    pub fn get_entry(&self, value: i64) -> Option<&AttributeTableEntry> {
+        self.entries
+            .binary_search_by_key(&value, |e| e.value)
+            .ok()
+            .map(|i| &self.entries[i])
+    }
+
+    pub fn get_entry_mut(&mut self, value: i64) -> Option<&mut AttributeTableEntry> {
+        match self.entries.binary_search_by_key(&value, |e| e.value) {
+            Ok(i) => Some(&mut self.entries[i]),
+            Err(_) => None,
+        }
+    }
+
+    /// Returns the name of the class with a value, if it has an entry.
+    pub fn get_name(&self, value: i64) -> Option<&str> {
+        self.get_entry(value).map(|e| e.name.as_str())
+    }
+
+    pub fn remove_entry(&mut self, value: i64) {
+        if let Ok(i) = self.entries.binary_search_by_key(&value, |e| e.value) {
+            self.entries.remove(i);
+        }
+    }
+
+    pub fn entries(&self) -> &[AttributeTableEntry] {
+        &self.entries
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        self.entries.clear();
+    }
+}
+
+/// Returns the name of the auxiliary (.aux.xml) file that accompanies a raster file. These
+/// files, which are also used by GDAL, store the colour table and attribute table of
+/// rasters whose formats have no place for them.
+pub(crate) fn get_aux_file_name(file_name: &str) -> String {
+    format!("{}.aux.xml", file_name)
+}
+
+// The GDAL field types and usages of attribute table columns.
+const GFT_INTEGER: u8 = 0;
+const GFT_STRING: u8 = 2;
+const GFU_GENERIC: u8 = 0;
+const GFU_PIXEL_COUNT: u8 = 1;
+const GFU_NAME: u8 = 2;
+const GFU_MIN: u8 = 3;
+const GFU_MIN_MAX: u8 = 5;
+const GFU_RED: u8 = 6;
+const GFU_GREEN: u8 = 7;
+const GFU_BLUE: u8 = 8;
+const GFU_ALPHA: u8 = 9;
+
+/// Reads the colour table and attribute table of the first band of a raster from its
+/// auxiliary (.aux.xml) file, if it has one. A colour table is only read if the raster
+/// doesn't already have one.
+pub(crate) fn read_aux_file(
+    file_name: &str,
+    colour_table: &mut ColourTable,
+    attribute_table: &mut AttributeTable,
+) -> Result<(), Error> {
+    let aux_file = get_aux_file_name(file_name);
+    if !Path::new(&aux_file).is_file() {
+        return Ok(());
+    }
+    let xml = fs::read_to_string(&aux_file)?;
+    // Only the first band is considered.
+    let band = match elements(&xml, "PAMRasterBand").into_iter().next() {
+        Some((_, content)) => content,
+        None => return Ok(()),
+    };
+
+    if colour_table.is_empty() {
+        if let Some((_, content)) = elements(band, "ColorTable").into_iter().next() {
+            for (i, (tag, _)) in elements(content, "Entry").into_iter().enumerate() {
+                let component = |name: &str, default: u8| {
+                    xml_attribute(tag, name)
+                        .and_then(|c| c.parse::<u8>().ok())
+                        .unwrap_or(default)
+                };
+                colour_table.set_colour(
+                    i as i64,
+                    (
+                        component("c1", 0),
+                        component("c2", 0),
+                        component("c3", 0),
+                        component("c4", 255),
+                    ),
+                );
+            }
+        }
+    }
+
+    if let Some((tag, content)) = elements(band, "GDALRasterAttributeTable").into_iter().next() {
+        // Tables with a linear binning give the value of each row by its position.
+        let row0_min = xml_attribute(tag, "Row0Min").and_then(|v| v.parse::<f64>().ok());
+        let bin_size = xml_attribute(tag, "BinSize").and_then(|v| v.parse::<f64>().ok());
+
+        let mut usages = vec![];
+        for (_, field) in elements(content, "FieldDefn") {
+            let name = element_text(field, "Name").unwrap_or_default();
+            let usage = element_text(field, "Usage")
+                .and_then(|u| u.trim().parse::<u8>().ok())
+                .unwrap_or(GFU_GENERIC);
+            usages.push(match usage {
+                GFU_GENERIC if name.eq_ignore_ascii_case("value") => GFU_MIN_MAX,
+                GFU_GENERIC if name.eq_ignore_ascii_case("count") => GFU_PIXEL_COUNT,
+                usage => usage,
+            });
+        }
+
+        let mut table = AttributeTable::new();
+        for (i, (_, row)) in elements(content, "Row").into_iter().enumerate() {
+            let mut value = match (row0_min, bin_size) {
+                (Some(min), Some(size)) => Some((min + i as f64 * size).round() as i64),
+                _ => None,
+            };
+            let mut count = 0usize;
+            let mut name = String::new();
+            let mut colour = [None; 4];
+            for (field, (_, text)) in elements(row, "F").into_iter().enumerate() {
+                let text = xml_unescape(text.trim());
+                match usages.get(field) {
+                    Some(&GFU_MIN_MAX) | Some(&GFU_MIN) => {
+                        value = text.parse::<f64>().ok().map(|v| v.round() as i64).or(value)
+                    }
+                    Some(&GFU_PIXEL_COUNT) => {
+                        count = text.parse::<f64>().map(|c| c as usize).unwrap_or(0)
+                    }
+                    Some(&GFU_NAME) => name = text,
+                    Some(&usage) if usage >= GFU_RED && usage <= GFU_ALPHA => {
+                        colour[(usage - GFU_RED) as usize] =
+                            text.parse::<f64>().ok().map(|c| c.max(0f64).min(255f64) as u8)
+                    }
+                    _ => {}
+                }
+            }
+            if let Some(value) = value {
+                let entry = table.add_entry(value, &name);
+                entry.count = count;
+                // Transparent black is written for classes that have no colour.
+                match colour {
+                    [Some(0), Some(0), Some(0), Some(0)] => {}
+                    [Some(r), Some(g), Some(b), a] => entry.colour = Some((r, g, b, a.unwrap_or(255))),
+                    _ => {}
+                }
+            }
+        }
+        *attribute_table = table;
+    }
+
+    Ok(())
+}
+
+/// Writes the colour table and attribute table of a raster to its auxiliary (.aux.xml)
+/// file, in the form used by GDAL.
+pub(crate) fn write_aux_file(
+    file_name: &str,
+    colour_table: &ColourTable,
+    attribute_table: &AttributeTable,
+) -> Result<(), Error> {
+    let f = File::create(get_aux_file_name(file_name))?;
+    let mut writer = BufWriter::new(f);
+    writer.write_all("<PAMDataset>\n  <PAMRasterBand band=\"1\">\n".as_bytes())?;
+
+    // GDAL colour tables list the colours of every value from zero upwards.
+    match colour_table.value_range() {
+        Some((min, max)) if min >= 0 && max < 65536 => {
+            writer.write_all("    <ColorInterp>Palette</ColorInterp>\n".as_bytes())?;
+            writer.write_all("    <ColorTable>\n".as_bytes())?;
+            for value in 0..=max {
+                let (r, g, b, a) = colour_table.get_colour(value).unwrap_or((0, 0, 0, 0));
+                let s = format!(
+                    "      <Entry c1=\"{}\" c2=\"{}\" c3=\"{}\" c4=\"{}\" />\n",
+                    r, g, b, a
+                );
+                writer.write_all(s.as_bytes())?;
+            }
+            writer.write_all("    </ColorTable>\n".as_bytes())?;
+        }
+        _ => {}
+    }
+
+    if !attribute_table.is_empty() {
+        let has_colours = attribute_table.entries().iter().any(|e| e.colour.is_some());
+        let mut fields = vec![
+            ("Value", GFT_INTEGER, GFU_MIN_MAX),
+            ("Count", GFT_INTEGER, GFU_PIXEL_COUNT),
+            ("Class_Name", GFT_STRING, GFU_NAME),
+        ];
+        if has_colours {
+            fields.push(("Red", GFT_INTEGER, GFU_RED));
+            fields.push(("Green", GFT_INTEGER, GFU_GREEN));
+            fields.push(("Blue", GFT_INTEGER, GFU_BLUE));
+            fields.push(("Alpha", GFT_INTEGER, GFU_ALPHA));
+        }
+        writer.write_all(
+            "    <GDALRasterAttributeTable tableType=\"thematic\">\n".as_bytes(),
+        )?;
+        for (i, (name, field_type, usage)) in fields.iter().enumerate() {
+            let s = format!(
+                "      <FieldDefn index=\"{}\">\n        <Name>{}</Name>\n        <Type>{}</Type>\n        <Usage>{}</Usage>\n      </FieldDefn>\n",
+                i, name, field_type, usage
+            );
+            writer.write_all(s.as_bytes())?;
+        }
+        for (i, entry) in attribute_table.entries().iter().enumerate() {
+            let mut s = format!(
+                "      <Row index=\"{}\">\n        <F>{}</F>\n        <F>{}</F>\n        <F>{}</F>\n",
+                i,
+                entry.value,
+                entry.count,
+                xml_escape(&entry.name)
+            );
+            if has_colours {
+                let (r, g, b, a) = entry.colour.unwrap_or((0, 0, 0, 0));
+                s.push_str(&format!(
+                    "        <F>{}</F>\n        <F>{}</F>\n        <F>{}</F>\n        <F>{}</F>\n",
+                    r, g, b, a
+                ));
+            }
+            s.push_str("      </Row>\n");
+            writer.write_all(s.as_bytes())?;
+        }
+        writer.write_all("    </GDALRasterAttributeTable>\n".as_bytes())?;
+    }
+
+    writer.write_all("  </PAMRasterBand>\n</PAMDataset>\n".as_bytes())?;
+    writer.flush()?;
+
+    Ok(())
+}
+
+/// Returns the opening tag and content of each of the (non-nested) elements with a given
+/// name. Empty elements, e.g. `<F />`, have no content.
+fn elements<'a>(xml: &'a str, name: &str) -> Vec<(&'a str, &'a str)> {
+    let mut found = vec![];
+    let open = format!("<{}", name);
+    let close = format!("</{}>", name);
+    let mut rest = xml;
+    while let Some(start) = rest.find(&open) {
+        let after_name = &rest[start + open.len()..];
+        // The name must not merely be the start of a longer one, e.g. <Row and <RowCount.
+        if !after_name.starts_with(|c: char| c == '>' || c == '/' || c.is_whitespace()) {
+            rest = after_name;
+            continue;
+        }
+        let tag_end = match after_name.find('>') {
+            Some(i) => i,
+            None => break,
+        };
+        let tag = &rest[start..start + open.len() + tag_end + 1];
+        let after_tag = &after_name[tag_end + 1..];
+        if tag.ends_with("/>") {
+            found.push((tag, ""));
+            rest = after_tag;
+        } else {
+            match after_tag.find(&close) {
+                Some(end) => {
+                    found.push((tag, &after_tag[..end]));
+                    rest = &after_tag[end + close.len()..];
+                }
+                None => break,
+            }
+        }
+    }
+    found
+}
+
+fn element_text(xml: &str, name: &str) -> Option<String> {
+    elements(xml, name)
+        .into_iter()
+        .next()
+        .map(|(_, content)| xml_unescape(content.trim()))
+}
+
+/// Returns the value of an attribute of an XML tag, e.g. c1 in `<Entry c1="255" />`.
+fn xml_attribute(tag: &str, name: &str) -> Option<String> {
+    let pattern = format!(" {}=\"", name);
+    let start = tag.find(&pattern)? + pattern.len();
+    let end = tag[start..].find('"')?;
+    Some(xml_unescape(&tag[start..start + end]))
+}
+
+fn xml_escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+fn xml_unescape(text: &str) -> String {
+    text.replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&apos;", "'")
+        .replace("&amp;", "&")
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::test_utils::*;
+    use crate::*;
+
+    #[test]
+    fn tables_are_kept_in_value_order() {
+        let mut table = AttributeTable::new();
+        table.add_entry(3, "forest");
+        table.add_entry(1, "water").colour = Some((0, 0, 255, 255));
+        table.add_entry(2, "wetland");
+        table.add_entry(3, "woodland").count = 12;
+        table.remove_entry(2);
+        let values: Vec<i64> = table.entries().iter().map(|e| e.value).collect();
+        assert_eq!(values, vec![1, 3]);
+        assert_eq!(table.get_name(3), Some("woodland"));
+        assert_eq!(table.get_entry(3).unwrap().count, 12);
+        assert_eq!(table.get_entry(1).unwrap().colour, Some((0, 0, 255, 255)));
+        assert!(table.get_entry(2).is_none());
+
+        let values: Vec<i64> = (0..14).map(|i| 20 - i).collect();
+        let colours = ColourTable::qualitative(&values);
+        assert_eq!(colours.len(), 14);
+        assert_eq!(colours.value_range(), Some((7, 20)));
+        // The palette is reused once its twelve colours are exhausted.
+        assert_eq!(colours.get_colour(20), colours.get_colour(8));
+        assert_ne!(colours.get_colour(20), colours.get_colour(19));
+        let values: Vec<i64> = colours.iter().map(|(value, _)| value).collect();
+        assert_eq!(values, (7..=20).collect::<Vec<i64>>());
+    }
+
+    #[test]
+    fn aux_file_round_trip() {
+        let file_name = temp_file("classes.img");
+        let mut colours = ColourTable::new();
+        colours.set_colour(1, (10, 20, 30, 255));
+        colours.set_colour(3, (40, 50, 60, 128));
+        let mut table = AttributeTable::new();
+        table.add_entry(1, "Sand & <gravel>").count = 100;
+        let entry = table.add_entry(3, "Rock \"outcrop\"");
+        entry.count = 7;
+        entry.colour = Some((1, 2, 3, 4));
+        table.add_entry(250, "Unclassified");
+        write_aux_file(&file_name, &colours, &table).unwrap();
+
+        let mut colours_read = ColourTable::new();
+        let mut table_read = AttributeTable::new();
+        read_aux_file(&file_name, &mut colours_read, &mut table_read).unwrap();
+        assert_eq!(table_read, table);
+        // Every value from zero has a colour; those without one are transparent black.
+        assert_eq!(colours_read.value_range(), Some((0, 3)));
+        assert_eq!(colours_read.get_colour(0), Some((0, 0, 0, 0)));
+        assert_eq!(colours_read.get_colour(1), Some((10, 20, 30, 255)));
+        assert_eq!(colours_read.get_colour(2), Some((0, 0, 0, 0)));
+        assert_eq!(colours_read.get_colour(3), Some((40, 50, 60, 128)));
+
+        // A raster's own colour table is kept.
+        let mut colours_read = ColourTable::qualitative(&[5]);
+        read_aux_file(&file_name, &mut colours_read, &mut table_read).unwrap();
+        assert_eq!(colours_read, ColourTable::qualitative(&[5]));
+        std::fs::remove_file(get_aux_file_name(&file_name)).unwrap();
+
+        // Rasters without an auxiliary file have no tables.
+        let mut table_read = AttributeTable::new();
+        read_aux_file(&file_name, &mut ColourTable::new(), &mut table_read).unwrap();
+        assert!(table_read.is_empty());
+    }
+
+    #[test]
+    fn read_binned_attribute_table() {
+        // The values of the rows of a table with a linear binning are given by their
+        // positions, and the usages of generic fields are inferred from their names.
+        let file_name = temp_file("binned.tif");
+        let xml = r#"<PAMDataset>
+  <PAMRasterBand band="1">
+    <GDALRasterAttributeTable Row0Min="10" BinSize="5" tableType="thematic">
+      <FieldDefn index="0">
+        <Name>COUNT</Name>
+        <Type>0</Type>
+        <Usage>0</Usage>
+      </FieldDefn>
+      <FieldDefn index="1">
+        <Name>Label</Name>
+        <Type>2</Type>
+        <Usage>2</Usage>
+      </FieldDefn>
+      <FieldDefn index="2">
+        <Name>R</Name>
+        <Type>0</Type>
+        <Usage>6</Usage>
+      </FieldDefn>
+      <FieldDefn index="3">
+        <Name>G</Name>
+        <Type>0</Type>
+        <Usage>7</Usage>
+      </FieldDefn>
+      <FieldDefn index="4">
+        <Name>B</Name>
+        <Type>0</Type>
+        <Usage>8</Usage>
+      </FieldDefn>
+      <Row index="0"><F>4</F><F>Low</F><F>0</F><F>128</F><F>300</F></Row>
+      <Row index="1"><F>6</F><F>High &amp; dry</F><F /><F /><F /></Row>
+    </GDALRasterAttributeTable>
+  </PAMRasterBand>
+  <PAMRasterBand band="2">
+    <GDALRasterAttributeTable />
+  </PAMRasterBand>
+</PAMDataset>
+"#;
+        std::fs::write(get_aux_file_name(&file_name), xml).unwrap();
+        let mut table = AttributeTable::new();
+        read_aux_file(&file_name, &mut ColourTable::new(), &mut table).unwrap();
+        assert_eq!(table.len(), 2);
+        let low = table.get_entry(10).unwrap();
+        assert_eq!((low.name.as_str(), low.count), ("Low", 4));
+        assert_eq!(low.colour, Some((0, 128, 255, 255)));
+        let high = table.get_entry(15).unwrap();
+        assert_eq!((high.name.as_str(), high.count), ("High & dry", 6));
+        assert_eq!(high.colour, None);
+        std::fs::remove_file(get_aux_file_name(&file_name)).unwrap();
+    }
+
+    #[test]
+    fn rasters_keep_their_tables() {
+        // GeoTIFFs store the colours in their ColorMaps and the attribute tables in
+        // auxiliary files; Whitebox rasters store both in their headers.
+        for (name, other_files) in [
+            ("classes.tif", vec!["classes.tif.aux.xml"]),
+            ("classes.dep", vec!["classes.tas"]),
+            ("classes.bil", vec!["classes.hdr", "classes.prj", "classes.bil.aux.xml"]),
+        ] {
+            let file_name = temp_file(name);
+            let mut output = sample_raster(&file_name, 20, 10, DataType::U8);
+            output.configs.photometric_interp = PhotometricInterpretation::Categorical;
+            output.configs.colour_table = ColourTable::qualitative(&[17, 34, 48]);
+            let table = &mut output.configs.attribute_table;
+            table.add_entry(17, "seventeen");
+            table.add_entry(34, "thirty-four").colour = Some((9, 8, 7, 255));
+            table.add_entry(48, "forty-eight");
+            output.update_attribute_counts();
+            let count = |value: f64| {
+                (0..20)
+                    .flat_map(|row| (0..10).map(move |col| (row, col)))
+                    .filter(|&(row, col)| output.get_value(row, col) == value)
+                    .count()
+            };
+            let counts: Vec<usize> = output
+                .configs
+                .attribute_table
+                .entries()
+                .iter()
+                .map(|e| e.count)
+                .collect();
+            assert_eq!(counts, vec![count(17.0), count(34.0), count(48.0)]);
+            assert!(counts.iter().all(|&c| c > 0));
+            output.write().unwrap();
+
+            let input = Raster::new(&file_name, "r").unwrap();
+            assert_eq!(input.configs.attribute_table, output.configs.attribute_table);
+            for (value, colour) in output.configs.colour_table.iter() {
+                assert_eq!(input.configs.colour_table.get_colour(value), Some(colour));
+            }
+            assert_same_cells(&output, &input);
+            std::fs::remove_file(&file_name).unwrap();
+            for other_file in other_files {
+                std::fs::remove_file(temp_file(other_file)).unwrap();
+            }
+        }
+    }
+}
@@ -0,0 +1,666 @@
+use super::*;
+use memmap2::MmapRaw;
+use std::fs::{File, OpenOptions};
+use std::io::{Error, ErrorKind, SeekFrom};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering};
+use std::sync::Mutex;
+
+/// The smallest amount of cell data, in bytes, that is read from a file at once.
+const MIN_BLOCK_BYTES: usize = 1 << 20;
+
+/// Memory is only released in whole multiples of this size, which is itself
+/// a multiple of the page size on all supported platforms.
+const RELEASE_ALIGNMENT: usize = 1 << 16;
+
+static SCRATCH_FILE_COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+/// A source of raster cell values that are read, and possibly written, a block of
+/// rows at a time. Rows are numbered consecutively across bands, i.e. row `r` of
+/// band `b` is row `b * rows + r`.
+pub(crate) trait BlockSource: Send {
+    /// Reads the rows beginning with `first_row` into `data`, which holds a whole number of rows.
+    fn read_rows(&mut self, first_row: usize, data: &mut [f64]) -> Result<(), Error>;
+
+    /// Writes the rows beginning with `first_row` back to the file that they were read from.
+    fn write_rows(&mut self, _first_row: usize, _data: &[f64]) -> Result<(), Error> {
+        Err(Error::new(
+            ErrorKind::Other,
+            "The raster file cannot be modified in place.",
+        ))
+    }
+
+    /// Returns true if modified rows can be written back to the file, given the
+    /// raster's current configuration.
+    fn is_writable(&self, _configs: &RasterConfigs) -> bool {
+        false
+    }
+
+    /// The number of rows that are most efficiently read at once, e.g. the height of a strip or tile.
+    fn preferred_rows(&self) -> usize {
+        1
+    }
+}
+
+/// The source of a newly created raster, for which every cell initially holds the same value.
+pub(crate) struct FillSource {
+    value: f64,
+}
+
+impl FillSource {
+    pub fn new(value: f64) -> FillSource {
+        FillSource { value: value }
+    }
+}
+
+impl BlockSource for FillSource {
+    fn read_rows(&mut self, _first_row: usize, data: &mut [f64]) -> Result<(), Error> {
+        for z in data.iter_mut() {
+            *z = self.value;
+        }
+        Ok(())
+    }
+}
+
+/// The arrangement of uncompressed cell values within a data file, following the
+/// conventions of Esri BIL header (HDR) files.
+#[derive(Clone, Copy, Debug, Default)]
+pub(crate) struct RawLayout {
+    /// The number of bytes preceding the first row.
+    pub skip_bytes: usize,
+    /// The number of bytes in a row of a single band.
+    pub band_row_bytes: usize,
+    /// The number of bytes in a row of all bands, for band-interleaved-by-line
+    /// and band-interleaved-by-pixel files.
+    pub total_row_bytes: usize,
+    /// The number of bytes between bands, for band-sequential files.
+    pub band_gap_bytes: usize,
+}
+
+/// Uncompressed cell values stored row by row, as in Whitebox data (.tas) files and
+/// Esri BIL, BIP, and BSQ files.
+pub(crate) struct RawBlockSource {
+    file: File,
+    writable: bool,
+    layout: RawLayout,
+    rows: usize,
+    columns: usize,
+    bands: usize,
+    data_type: DataType,
+    endian: Endianness,
+    interleave: BandInterleave,
+    data_size: usize,
+    buffer: Vec<u8>,
+}
+
+impl RawBlockSource {
+    /// Opens the data file `file_name`, which holds the cells described by `configs`
+    /// arranged as described by `layout`.
+    pub fn new(
+        file_name: &str,
+        configs: &RasterConfigs,
+        layout: RawLayout,
+        writable: bool,
+    ) -> Result<RawBlockSource, Error> {
+        let data_size = match configs.data_type {
+            DataType::U8 | DataType::I8 => 1,
+            DataType::U16 | DataType::I16 => 2,
+            DataType::U32 | DataType::I32 | DataType::F32 => 4,
+            DataType::U64 | DataType::I64 | DataType::F64 => 8,
+            _ => {
+                return Err(Error::new(
+                    ErrorKind::InvalidData,
+                    format!(
+                        "Raster data type {:?} cannot be read one block at a time.",
+                        configs.data_type
+                    ),
+                ))
+            }
+        };
+        let file = OpenOptions::new()
+            .read(true)
+            .write(writable)
+            .open(file_name)?;
+        Ok(RawBlockSource {
+            file: file,
+            writable: writable,
+            layout: layout,
+            rows: configs.rows,
+            columns: configs.columns,
+            bands: configs.bands.max(1),
+            data_type: configs.data_type,
+            endian: configs.endian,
+            interleave: configs.interleave,
+            data_size: data_size,
+            buffer: vec![],
+        })
+    }
+
+    /// Returns the file position of a row of a band and the number of bytes that it spans.
+    /// Pixel-interleaved rows span the samples of every band.
+    fn row_position(&self, band: usize, row: usize) -> (u64, usize) {
+        let l = &self.layout;
+        match self.interleave {
+            BandInterleave::BSQ => (
+                (l.skip_bytes
+                    + band * (self.rows * l.band_row_bytes + l.band_gap_bytes)
+                    + row * l.band_row_bytes) as u64,
+                self.columns * self.data_size,
+            ),
+            BandInterleave::BIL => (
+                (l.skip_bytes + row * l.total_row_bytes + band * l.band_row_bytes) as u64,
+                self.columns * self.data_size,
+            ),
+            BandInterleave::BIP => (
+                (l.skip_bytes + row * l.total_row_bytes) as u64,
+                self.bands * self.columns * self.data_size,
+            ),
+        }
+    }
+
+    /// Returns the position of a cell's value within the bytes of its row.
+    fn cell_offset(&self, band: usize, column: usize) -> usize {
+        match self.interleave {
+            BandInterleave::BIP => (column * self.bands + band) * self.data_size,
+            _ => column * self.data_size,
+        }
+    }
+}
+
+impl BlockSource for RawBlockSource {
+    fn read_rows(&mut self, first_row: usize, data: &mut [f64]) -> Result<(), Error> {
+        let num_rows = data.len() / self.columns;
+        let mut offset: usize;
+        for r in 0..num_rows {
+            let (band, row) = ((first_row + r) / self.rows, (first_row + r) % self.rows);
+            let (position, num_bytes) = self.row_position(band, row);
+            self.buffer.resize(num_bytes, 0u8);
+            self.file.seek(SeekFrom::Start(position))?;
+            self.file.read_exact(&mut self.buffer)?;
+            for col in 0..self.columns {
+                offset = self.cell_offset(band, col);
+                data[r * self.columns + col] = decode_value(
+                    &self.buffer[offset..offset + self.data_size],
+                    self.data_type,
+                    self.endian,
+                );
+            }
+        }
+        Ok(())
+    }
+
+    fn write_rows(&mut self, first_row: usize, data: &[f64]) -> Result<(), Error> {
+        if !self.writable {
+            return Err(Error::new(
+                ErrorKind::PermissionDenied,
+                "The raster file was not opened for writing.",
+            ));
+        }
+        let num_rows = data.len() / self.columns;
+        let mut offset: usize;
+        let mut bytes = Vec::with_capacity(self.data_size);
+        for r in 0..num_rows {
+            let (band, row) = ((first_row + r) / self.rows, (first_row + r) % self.rows);
+            let (position, num_bytes) = self.row_position(band, row);
+            self.buffer.resize(num_bytes, 0u8);
+            if self.interleave == BandInterleave::BIP && self.bands > 1 {
+                // the row holds the values of the other bands too
+                self.file.seek(SeekFrom::Start(position))?;
+                self.file.read_exact(&mut self.buffer)?;
+            }
+            for col in 0..self.columns {
+                offset = self.cell_offset(band, col);
+                bytes.clear();
+                encode_value(&mut bytes, self.data_type, self.endian, data[r * self.columns + col])?;
+                self.buffer[offset..offset + self.data_size].copy_from_slice(&bytes);
+            }
+            self.file.seek(SeekFrom::Start(position))?;
+            self.file.write_all(&self.buffer)?;
+        }
+        self.file.flush()
+    }
+
+    fn is_writable(&self, configs: &RasterConfigs) -> bool {
+        self.writable
+            && configs.rows == self.rows
+            && configs.columns == self.columns
+            && configs.bands.max(1) == self.bands
+            && configs.data_type == self.data_type
+            && configs.endian == self.endian
+            && configs.interleave == self.interleave
+    }
+}
+
+/// A cache that holds the cells of a raster that is too large to read into memory.
+///
+/// Blocks of rows are read from their source the first time that they are accessed
+/// and are decoded into a scratch file, which is mapped into memory. The number of
+/// blocks held in memory at once is limited by a memory budget; when the limit is
+/// reached, the least recently used block is released and its values are read back
+/// from the scratch file, by the operating system, the next time it is accessed.
+/// Modified blocks are written back to the source with `write_back`.
+///
+/// Cell values are returned by copy, so nothing refers into a block once it has been
+/// released. Errors in reading a block from its source are returned by the accessor
+/// that first touched the block, which leaves the block unread so that a later access
+/// may try again.
+pub(crate) struct BlockCache {
+    state: Mutex<CacheState>,
+    map: Option<MmapRaw>,
+    ptr: *mut f64,
+    scratch_file: Option<File>,
+    scratch_file_name: PathBuf,
+    file_name: String,
+    num_rows: usize,
+    columns: usize,
+    block_rows: usize,
+    max_resident: usize,
+    max_memory: usize,
+    loaded: Vec<AtomicBool>,
+    resident: Vec<AtomicBool>,
+    dirty: Vec<AtomicBool>,
+    last_used: Vec<AtomicU64>,
+    clock: AtomicU64,
+    all_loaded: AtomicBool,
+}
+
+struct CacheState {
+    source: Box<dyn BlockSource>,
+    resident: Vec<usize>,
+}
+
+// The raw pointer into the memory map is only written through while the state
+// lock is held, for blocks that have not yet been handed out, or through `&mut self`.
+unsafe impl Send for BlockCache {}
+unsafe impl Sync for BlockCache {}
+
+impl BlockCache {
+    /// Creates a cache for `num_rows` rows (summed across all bands) of `columns` cells
+    /// of the raster `file_name`, keeping at most roughly `max_memory` bytes of cell
+    /// values in memory.
+    pub fn new(
+        source: Box<dyn BlockSource>,
+        file_name: &str,
+        num_rows: usize,
+        columns: usize,
+        max_memory: usize,
+    ) -> Result<BlockCache, Error> {
+        let row_bytes = columns * mem::size_of::<f64>();
+        if num_rows == 0 || row_bytes == 0 {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                "A block cache cannot be created for an empty raster.",
+            ));
+        }
+        // Blocks are a whole number of the source's preferred rows and at least MIN_BLOCK_BYTES.
+        let preferred_rows = source.preferred_rows().max(1);
+        let multiple = (MIN_BLOCK_BYTES + preferred_rows * row_bytes - 1) / (preferred_rows * row_bytes);
+        let block_rows = (preferred_rows * multiple.max(1)).min(num_rows);
+        let num_blocks = (num_rows + block_rows - 1) / block_rows;
+        let max_resident = (max_memory / (block_rows * row_bytes)).max(2);
+
+        let scratch_file_name = get_scratch_directory(file_name).join(format!(
+            ".wb_scratch_{}_{}.tmp",
+            std::process::id(),
+            SCRATCH_FILE_COUNTER.fetch_add(1, Ordering::Relaxed)
+        ));
+        let scratch_file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create_new(true)
+            .open(&scratch_file_name)?;
+        scratch_file.set_len((num_rows * row_bytes) as u64)?;
+        let map = MmapRaw::map_raw(&scratch_file)?;
+        let ptr = map.as_mut_ptr() as *mut f64;
+
+        // On Unix-like systems, the file can be unlinked while it is mapped, which
+        // ensures it is cleaned up even if the process is killed.
+        #[cfg(unix)]
+        let _ = std::fs::remove_file(&scratch_file_name);
+
+        Ok(BlockCache {
+            state: Mutex::new(CacheState {
+                source: source,
+                resident: vec![],
+            }),
+            map: Some(map),
+            ptr: ptr,
+            scratch_file: Some(scratch_file),
+            scratch_file_name: scratch_file_name,
+            file_name: file_name.to_string(),
+            num_rows: num_rows,
+            columns: columns,
+            block_rows: block_rows,
+            max_resident: max_resident,
+            max_memory: max_memory,
+            loaded: (0..num_blocks).map(|_| AtomicBool::new(false)).collect(),
+            resident: (0..num_blocks).map(|_| AtomicBool::new(false)).collect(),
+            dirty: (0..num_blocks).map(|_| AtomicBool::new(false)).collect(),
+            last_used: (0..num_blocks).map(|_| AtomicU64::new(0)).collect(),
+            clock: AtomicU64::new(0),
+            all_loaded: AtomicBool::new(false),
+        })
+    }
+
+    /// The number of cells held by the cache.
+    pub fn len(&self) -> usize {
+        self.num_rows * self.columns
+    }
+
+    /// The maximum number of bytes of cell values held in memory at once.
+    pub fn max_memory(&self) -> usize {
+        self.max_memory
+    }
+
+    /// The name of the raster file that the cells are read from.
+    pub fn file_name(&self) -> &str {
+        &self.file_name
+    }
+
+    /// Returns true if modified cells can be written back to the source file.
+    pub fn is_writable(&self, configs: &RasterConfigs) -> bool {
+        self.state
+            .lock()
+            .expect("Error locking the raster block cache.")
+            .source
+            .is_writable(configs)
+    }
+
+    #[inline]
+    pub fn get(&self, idx: usize) -> Result<f64, Error> {
+        self.touch(idx / (self.block_rows * self.columns))?;
+        Ok(unsafe { *self.ptr.add(idx) })
+    }
+
+    #[inline]
+    pub fn set(&mut self, idx: usize, value: f64) -> Result<(), Error> {
+        let block = idx / (self.block_rows * self.columns);
+        self.touch(block)?;
+        *self.dirty[block].get_mut() = true;
+        unsafe {
+            *self.ptr.add(idx) = value;
+        }
+        Ok(())
+    }
+
+    /// Assigns a value to every cell.
+    pub fn fill(&mut self, value: f64) {
+        let block_cells = self.block_rows * self.columns;
+        for block in 0..self.loaded.len() {
+            // the block's existing values are overwritten, so they needn't be read, and
+            // touching a block that has been loaded can't fail
+            *self.loaded[block].get_mut() = true;
+            let _ = self.touch(block);
+            let end = ((block + 1) * block_cells).min(self.len());
+            for idx in block * block_cells..end {
+                unsafe {
+                    *self.ptr.add(idx) = value;
+                }
+            }
+            *self.dirty[block].get_mut() = true;
+        }
+        *self.all_loaded.get_mut() = true;
+    }
+
+    /// Reads every block that has not yet been accessed. Afterwards, the source
+    /// file is no longer needed to retrieve cell values.
+    pub fn load_all(&self) -> Result<(), Error> {
+        if self.all_loaded.load(Ordering::Acquire) {
+            return Ok(());
+        }
+        for block in 0..self.loaded.len() {
+            if !self.loaded[block].load(Ordering::Acquire) {
+                self.make_resident(block)?;
+            }
+        }
+        self.all_loaded.store(true, Ordering::Release);
+        Ok(())
+    }
+
+    /// Reads every block and closes the source file. Afterwards, the file may be
+    /// overwritten without affecting the cell values.
+    pub fn detach(&mut self) -> Result<(), Error> {
+        self.load_all()?;
+        let state = self.state.get_mut().expect("Error locking the raster block cache.");
+        state.source = Box::new(FillSource::new(0f64));
+        for d in self.dirty.iter_mut() {
+            *d.get_mut() = false;
+        }
+        Ok(())
+    }
+
+    /// Writes the modified blocks back to the source file.
+    pub fn write_back(&mut self) -> Result<(), Error> {
+        let block_cells = self.block_rows * self.columns;
+        let len = self.len();
+        let ptr = self.ptr;
+        let state = self.state.get_mut().expect("Error locking the raster block cache.");
+        for block in 0..self.dirty.len() {
+            if *self.dirty[block].get_mut() {
+                let start = block * block_cells;
+                let end = (start + block_cells).min(len);
+                let data = unsafe { std::slice::from_raw_parts(ptr.add(start), end - start) };
+                state.source.write_rows(block * self.block_rows, data)?;
+                *self.dirty[block].get_mut() = false;
+            }
+        }
+        Ok(())
+    }
+
+    #[inline]
+    fn touch(&self, block: usize) -> Result<(), Error> {
+        if !self.resident[block].load(Ordering::Acquire) {
+            self.make_resident(block)?;
+        }
+        let now = self.clock.load(Ordering::Relaxed);
+        if self.last_used[block].load(Ordering::Relaxed) != now {
+            self.last_used[block].store(now, Ordering::Relaxed);
+        }
+        Ok(())
+    }
+
+    /// Brings a block into memory, reading it from the source if it has never been
+    /// accessed, and releases the least recently used block if the budget is exceeded.
+    fn make_resident(&self, block: usize) -> Result<(), Error> {
+        let mut state = self.state.lock().expect("Error locking the raster block cache.");
+        if self.resident[block].load(Ordering::Acquire) {
+            return Ok(()); // another thread got here first
+        }
+        if !self.loaded[block].load(Ordering::Acquire) {
+            let block_cells = self.block_rows * self.columns;
+            let start = block * block_cells;
+            let end = (start + block_cells).min(self.len());
+            // No references into this block have been handed out yet.
+            let data = unsafe { std::slice::from_raw_parts_mut(self.ptr.add(start), end - start) };
+            state.source.read_rows(block * self.block_rows, data)?;
+            self.loaded[block].store(true, Ordering::Release);
+        }
+        let now = self.clock.fetch_add(1, Ordering::Relaxed) + 1;
+        self.last_used[block].store(now, Ordering::Relaxed);
+        self.resident[block].store(true, Ordering::Release);
+        state.resident.push(block);
+
+        if state.resident.len() > self.max_resident {
+            let mut lru = 0usize;
+            let mut oldest = u64::MAX;
+            for i in 0..state.resident.len() {
+                let t = self.last_used[state.resident[i]].load(Ordering::Relaxed);
+                if state.resident[i] != block && t < oldest {
+                    oldest = t;
+                    lru = i;
+                }
+            }
+            let evicted = state.resident.swap_remove(lru);
+            self.resident[evicted].store(false, Ordering::Release);
+            self.release(evicted);
+        }
+        Ok(())
+    }
+
+    /// Releases the memory used by a block. Its values remain in the scratch file.
+    fn release(&self, block: usize) {
+        #[cfg(unix)]
+        {
+            let block_bytes = self.block_rows * self.columns * mem::size_of::<f64>();
+            let map_len = self.len() * mem::size_of::<f64>();
+            let start = (block * block_bytes + RELEASE_ALIGNMENT - 1) / RELEASE_ALIGNMENT * RELEASE_ALIGNMENT;
+            let end = ((block + 1) * block_bytes).min(map_len) / RELEASE_ALIGNMENT * RELEASE_ALIGNMENT;
+            if let Some(map) = &self.map {
+                if end > start {
+                    let _ = unsafe {
+                        map.unchecked_advise_range(memmap2::UncheckedAdvice::DontNeed, start, end - start)
+                    };
+                }
+            }
+        }
+        #[cfg(not(unix))]
+        let _ = block;
+    }
+
+    /// Copies the cache. The copy holds the current cell values in a scratch file of its
+    /// own, so that the two rasters can be modified independently. It cannot be written back.
+    pub fn try_clone(&self) -> Result<BlockCache, Error> {
+        self.load_all()?;
+        let mut other = BlockCache::new(
+            Box::new(FillSource::new(0f64)),
+            &self.file_name,
+            self.num_rows,
+            self.columns,
+            self.max_memory,
+        )?;
+        let block_cells = self.block_rows * self.columns;
+        for block in 0..self.loaded.len() {
+            let start = block * block_cells;
+            let end = (start + block_cells).min(self.len());
+            unsafe {
+                std::ptr::copy_nonoverlapping(self.ptr.add(start), other.ptr.add(start), end - start);
+            }
+            *other.loaded[block].get_mut() = true;
+            other.release(block);
+            if !self.resident[block].load(Ordering::Acquire) {
+                self.release(block);
+            }
+        }
+        *other.all_loaded.get_mut() = true;
+        Ok(other)
+    }
+}
+
+impl Drop for BlockCache {
+    fn drop(&mut self) {
+        self.map = None;
+        self.scratch_file = None;
+        let _ = std::fs::remove_file(&self.scratch_file_name);
+    }
+}
+
+/// Returns the directory in which the scratch file of a raster is created. Scratch
+/// files are the size of the raster, so they are kept beside the raster file
+/// itself rather than in the system's temporary directory, which may be memory-backed.
+fn get_scratch_directory(file_name: &str) -> PathBuf {
+    match Path::new(file_name).parent() {
+        Some(p) if !p.as_os_str().is_empty() && p.is_dir() => p.to_path_buf(),
+        _ => std::env::current_dir().unwrap_or(std::env::temp_dir()),
+    }
+}
+
+/// Returns the memory budget, in bytes, of a single raster, as set by the `max_memory`
+/// setting (in megabytes). Zero indicates that rasters are always read into memory.
+pub(crate) fn get_max_memory() -> usize {
+    match whitebox_common::configs::get_configs() {
+        Ok(configs) if configs.max_memory > 0 => configs.max_memory as usize * 1_048_576,
+        _ => 0,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::test_utils::*;
+    use crate::*;
+
+    // Large enough for four blocks of rows, of which at most two are held in memory.
+    const ROWS: usize = 1000;
+    const COLUMNS: usize = 500;
+    const MAX_MEMORY: usize = 100_000;
+
+    #[test]
+    fn out_of_core_reads_match_in_memory_reads() {
+        for name in ["blocks.tif", "blocks.dep"] {
+            let file_name = temp_file(name);
+            sample_raster(&file_name, ROWS, COLUMNS, DataType::F32).write().unwrap();
+
+            let in_memory = Raster::new_with_max_memory(&file_name, "r", 0).unwrap();
+            let out_of_core = Raster::new_with_max_memory(&file_name, "r", MAX_MEMORY).unwrap();
+            assert!(!in_memory.is_out_of_core());
+            assert!(out_of_core.is_out_of_core());
+            assert_same_cells(&sample_raster("", ROWS, COLUMNS, DataType::F32), &in_memory);
+            // Reading column by column releases and re-reads blocks repeatedly.
+            for col in 0..COLUMNS as isize {
+                for row in 0..ROWS as isize {
+                    assert_eq!(out_of_core.get_value(row, col), in_memory.get_value(row, col));
+                }
+            }
+            assert_eq!(out_of_core.configs.maximum, in_memory.configs.maximum);
+            drop(out_of_core);
+            std::fs::remove_file(&file_name).unwrap();
+            let _ = std::fs::remove_file(file_name.replace(".dep", ".tas"));
+        }
+    }
+
+    #[test]
+    fn modified_blocks_are_written_back() {
+        let file_name = temp_file("write_back.dep");
+        sample_raster(&file_name, ROWS, COLUMNS, DataType::F32).write().unwrap();
+
+        let mut r = Raster::new_with_max_memory(&file_name, "rw", MAX_MEMORY).unwrap();
+        assert!(r.is_out_of_core());
+        let copy = r.clone();
+        for row in (0..ROWS as isize).step_by(97) {
+            r.set_value(row, 3, -1.5);
+        }
+        r.write().unwrap();
+        drop(r);
+
+        // The copy is independent of the raster that was modified.
+        let input = Raster::new_with_max_memory(&file_name, "r", 0).unwrap();
+        for row in 0..ROWS as isize {
+            let expected = if row % 97 == 0 {
+                -1.5
+            } else {
+                sample_value(row, 3, DataType::F32)
+            };
+            assert_eq!(input.get_value(row, 3), expected);
+            assert_eq!(copy.get_value(row, 3), sample_value(row, 3, DataType::F32));
+            assert_eq!(input.get_value(row, 4), sample_value(row, 4, DataType::F32));
+        }
+        std::fs::remove_file(&file_name).unwrap();
+        std::fs::remove_file(file_name.replace(".dep", ".tas")).unwrap();
+    }
+
+    #[test]
+    fn read_errors_are_returned() {
+        let file_name = temp_file("truncated.dep");
+        let data_file = file_name.replace(".dep", ".tas");
+        sample_raster(&file_name, ROWS, COLUMNS, DataType::F32).write().unwrap();
+
+        let mut r = Raster::new_with_max_memory(&file_name, "rw", MAX_MEMORY).unwrap();
+        assert!(r.is_out_of_core());
+        // The last rows are cut off the data file after it has been opened.
+        let data_len = std::fs::metadata(&data_file).unwrap().len();
+        std::fs::OpenOptions::new()
+            .write(true)
+            .open(&data_file)
+            .unwrap()
+            .set_len(data_len / 2)
+            .unwrap();
+        let last_row = ROWS as isize - 1;
+        assert_eq!(r.try_get_value(0, 0).unwrap(), r.get_value(0, 0));
+        assert!(r.try_get_value(last_row, 0).is_err());
+        assert!(r.try_set_value(last_row, 0, 1.0).is_err());
+        // The failed block is left unread, so it fails again rather than returning garbage.
+        assert!(r.try_get_value(last_row, 1).is_err());
+        drop(r);
+        std::fs::remove_file(&file_name).unwrap();
+        std::fs::remove_file(&data_file).unwrap();
+    }
+}
@@ -0,0 +1,582 @@
+use super::*;
+use whitebox_common::spatial_ref_system::esri_wkt_from_epsg;
+use whitebox_common::utils::Endianness;
+use std::collections::HashMap;
+use std::f64;
+use std::fs;
+use std::fs::File;
+use std::io::prelude::*;
+use std::io::{BufWriter, Error, ErrorKind};
+
+/// Returns the name of the header file of an ENVI raster. This is the data file's name
+/// with a .hdr extension, either replacing (e.g. image.hdr) or appended to (e.g.
+/// image.img.hdr) its extension.
+pub(crate) fn get_envi_header_file_name(file_name: &str) -> String {
+    let path = Path::new(file_name);
+    if has_extension(path, "hdr") {
+        return file_name.to_string();
+    }
+    let appended = format!("{}.hdr", file_name);
+    if Path::new(&appended).is_file() {
+        return appended;
+    }
+    path.with_extension("hdr")
+        .into_os_string()
+        .into_string()
+        .expect("Error creating header file name string for ENVI file.")
+}
+
+/// Returns the name of the data file of an ENVI raster. This is the file itself, unless
+/// it is the header (.hdr) file, in which case it is the data file that accompanies it.
+pub(crate) fn get_envi_data_file_name(file_name: &str) -> String {
+    let path = Path::new(file_name);
+    if !has_extension(path, "hdr") {
+        return file_name.to_string();
+    }
+    // The header's name may have been formed by appending .hdr to that of the data file.
+    let stem = path.with_extension("");
+    if stem.extension().is_some() && stem.is_file() {
+        return stem.to_string_lossy().to_string();
+    }
+    let data_file = ["img", "dat", "bin", "raw", "bsq", "bil", "bip"]
+        .iter()
+        .map(|extension| path.with_extension(extension))
+        .find(|data_file| data_file.is_file())
+        .unwrap_or_else(|| {
+            if stem.is_file() {
+                stem.clone()
+            } else {
+                path.with_extension("img")
+            }
+        });
+    data_file
+        .into_os_string()
+        .into_string()
+        .expect("Error creating data file name string for ENVI file.")
+}
+
+fn has_extension(path: &Path, extension: &str) -> bool {
+    path.extension()
+        .and_then(|e| e.to_str())
+        .map(|e| e.eq_ignore_ascii_case(extension))
+        .unwrap_or(false)
+}
+
+/// Parses the fields of an ENVI header. Keys are lower case. The braces that enclose
+/// values, which may span several lines, are removed.
+fn parse_envi_header(text: &str) -> Result<HashMap<String, String>, Error> {
+    let mut lines = text.lines();
+    if !lines
+        .next()
+        .map(|line| line.trim().eq_ignore_ascii_case("envi"))
+        .unwrap_or(false)
+    {
+        return Err(Error::new(
+            ErrorKind::InvalidData,
+            "The ENVI header file does not begin with 'ENVI'.",
+        ));
+    }
+    let mut fields = HashMap::new();
+    while let Some(line) = lines.next() {
+        let (key, value) = match line.find('=') {
+            Some(i) => (line[..i].trim().to_lowercase(), line[i + 1..].trim().to_string()),
+            None => continue,
+        };
+        let value = if value.starts_with('{') {
+            let mut value = value[1..].to_string();
+            while !value.contains('}') {
+                match lines.next() {
+                    Some(line) => {
+                        value.push('\n');
+                        value.push_str(line);
+                    }
+                    None => {
+                        return Err(Error::new(
+                            ErrorKind::InvalidData,
+                            format!("The value of the ENVI header field '{}' is not closed.", key),
+                        ))
+                    }
+                }
+            }
+            let end = value.rfind('}').unwrap();
+            value[..end].trim().to_string()
+        } else {
+            value
+        };
+        fields.insert(key, value);
+    }
+    Ok(fields)
+}
+
+/// Splits a list value of an ENVI header, e.g. the band names, into its items.
+fn split_list(value: &str) -> Vec<String> {
+    value
+        .split(',')
+        .map(|item| item.trim().to_string())
+        .filter(|item| !item.is_empty())
+        .collect()
+}
+
+fn parse_number<T: std::str::FromStr>(key: &str, value: &str) -> Result<T, Error> {
+    value.trim().parse::<T>().map_err(|_| {
+        Error::new(
+            ErrorKind::InvalidData,
+            format!("Invalid value '{}' for the ENVI header field '{}'.", value, key),
+        )
+    })
+}
+
+/// Reads the header (HDR) file of an ENVI raster, returning the arrangement of the
+/// values within the data file.
+pub(crate) fn read_envi_header(
+    file_name: &String,
+    configs: &mut RasterConfigs,
+) -> Result<RawLayout, Error> {
+    let header_file = get_envi_header_file_name(file_name);
+    let text = fs::read_to_string(&header_file).map_err(|e| {
+        Error::new(
+            e.kind(),
+            format!("Unable to read the ENVI header file '{}': {}", header_file, e),
+        )
+    })?;
+    let fields = parse_envi_header(&text)?;
+    let field = |key: &str| fields.get(key).map(|v| v.as_str());
+    let required = |key: &str| {
+        field(key).ok_or_else(|| {
+            Error::new(
+                ErrorKind::InvalidData,
+                format!("The ENVI header file is missing the required field '{}'.", key),
+            )
+        })
+    };
+
+    configs.columns = parse_number("samples", required("samples")?)?;
+    configs.rows = parse_number("lines", required("lines")?)?;
+    configs.bands = parse_number::<usize>("bands", required("bands")?)?.max(1);
+
+    configs.data_type = match parse_number::<u16>("data type", required("data type")?)? {
+        1 => DataType::U8,
+        2 => DataType::I16,
+        3 => DataType::I32,
+        4 => DataType::F32,
+        5 => DataType::F64,
+        12 => DataType::U16,
+        13 => DataType::U32,
+        14 => DataType::I64,
+        15 => DataType::U64,
+        6 | 9 => {
+            return Err(Error::new(
+                ErrorKind::InvalidData,
+                "Complex-valued ENVI rasters are not supported.",
+            ))
+        }
+        code => {
+            return Err(Error::new(
+                ErrorKind::InvalidData,
+                format!("Unrecognized ENVI data type code {}.", code),
+            ))
+        }
+    };
+
+    configs.interleave = match field("interleave").unwrap_or("bsq").to_lowercase().as_str() {
+        "bsq" => BandInterleave::BSQ,
+        "bil" => BandInterleave::BIL,
+        "bip" => BandInterleave::BIP,
+        interleave => {
+            return Err(Error::new(
+                ErrorKind::InvalidData,
+                format!("Unsupported ENVI interleave '{}'.", interleave),
+            ))
+        }
+    };
+
+    configs.endian = match field("byte order").unwrap_or("0") {
+        "1" => Endianness::BigEndian,
+        _ => Endianness::LittleEndian,
+    };
+
+    configs.nodata = match field("data ignore value") {
+        Some(value) => parse_number("data ignore value", value)?,
+        None => -32768f64, // default in event that it is not in header file
+    };
+
+    if let Some(description) = field("description") {
+        configs.title = description.split_whitespace().collect::<Vec<&str>>().join(" ");
+    }
+    if let Some(names) = field("band names") {
+        configs.band_names = split_list(names);
+    }
+    if let Some(wavelengths) = field("wavelength") {
+        configs.wavelengths = split_list(wavelengths)
+            .iter()
+            .map(|w| parse_number("wavelength", w))
+            .collect::<Result<Vec<f64>, Error>>()?;
+    }
+    if let Some(units) = field("wavelength units") {
+        configs.wavelength_units = units.to_string();
+    }
+
+    match field("map info") {
+        Some(map_info) => read_map_info(map_info, configs)?,
+        None => {
+            // Without map information, the raster is located in pixel coordinates.
+            configs.resolution_x = 1f64;
+            configs.resolution_y = 1f64;
+            configs.west = 0f64;
+            configs.north = configs.rows as f64;
+        }
+    }
+    configs.south = configs.north - configs.resolution_y * configs.rows as f64;
+    configs.east = configs.west + configs.resolution_x * configs.columns as f64;
+
+    if let Some(wkt) = field("coordinate system string") {
+        configs.projection = wkt.to_string();
+        configs.coordinate_ref_system_wkt = wkt.to_string();
+    } else if configs.epsg_code != 0 {
+        configs.coordinate_ref_system_wkt = esri_wkt_from_epsg(configs.epsg_code);
+        configs.projection = configs.coordinate_ref_system_wkt.clone();
+    }
+
+    configs.photometric_interp = PhotometricInterpretation::Continuous;
+
+    let band_row_bytes = configs.columns * configs.data_type.get_data_size();
+    Ok(RawLayout {
+        skip_bytes: match field("header offset") {
+            Some(offset) => parse_number("header offset", offset)?,
+            None => 0,
+        },
+        band_row_bytes: band_row_bytes,
+        total_row_bytes: match configs.interleave {
+            BandInterleave::BSQ => band_row_bytes,
+            _ => band_row_bytes * configs.bands,
+        },
+        band_gap_bytes: 0,
+    })
+}
+
+/// Reads the map info field of an ENVI header, e.g.
+/// `{UTM, 1.0, 1.0, 492088.0, 4737708.0, 30.0, 30.0, 17, North, WGS-84, units=Meters}`,
+/// which gives the location of a reference pixel, the grid resolution, and the projection.
+fn read_map_info(map_info: &str, configs: &mut RasterConfigs) -> Result<(), Error> {
+    let items = split_list(map_info);
+    let (values, options): (Vec<&String>, Vec<&String>) =
+        items.iter().partition(|item| !item.contains('='));
+    if values.len() < 7 {
+        return Err(Error::new(
+            ErrorKind::InvalidData,
+            "The ENVI map info field has too few values.",
+        ));
+    }
+    let projection = values[0].to_lowercase();
+    // The reference pixel is one-based; (1, 1) is the upper-left corner of the upper-left pixel.
+    let ref_x: f64 = parse_number("map info", values[1])?;
+    let ref_y: f64 = parse_number("map info", values[2])?;
+    let easting: f64 = parse_number("map info", values[3])?;
+    let northing: f64 = parse_number("map info", values[4])?;
+    configs.resolution_x = parse_number("map info", values[5])?;
+    configs.resolution_y = parse_number("map info", values[6])?;
+    configs.west = easting - (ref_x - 1f64) * configs.resolution_x;
+    configs.north = northing + (ref_y - 1f64) * configs.resolution_y;
+
+    for option in options {
+        let mut pair = option.splitn(2, '=');
+        let key = pair.next().unwrap_or("").trim().to_lowercase();
+        let value = pair.next().unwrap_or("").trim();
+        if key == "units" {
+            configs.xy_units = value.to_lowercase();
+        } else if key == "rotation" && parse_number::<f64>("map info", value)? != 0f64 {
+            println!("Warning, non-zero rotation values are not currently supported.");
+        }
+    }
+
+    if projection == "utm" && values.len() >= 10 {
+        let zone: u16 = parse_number("map info", values[7])?;
+        let north = values[8].to_lowercase().starts_with('n');
+        let datum = values[9].to_lowercase();
+        configs.epsg_code = if datum.contains("wgs-84") || datum.contains("wgs84") {
+            if north {
+                32600 + zone
+            } else {
+                32700 + zone
+            }
+        } else if (datum.contains("nad 83") || datum.contains("nad83")) && north {
+            26900 + zone
+        } else {
+            0
+        };
+    } else if projection == "geographic lat/lon" {
+        let datum = values.get(7).map(|d| d.to_lowercase()).unwrap_or_default();
+        if datum.contains("wgs-84") || datum.contains("wgs84") {
+            configs.epsg_code = 4326;
+        }
+        if configs.xy_units == "not specified" {
+            configs.xy_units = "degrees".to_string();
+        }
+    }
+    Ok(())
+}
+
+pub fn write_envi<'a>(r: &'a mut Raster) -> Result<(), Error> {
+    write_envi_header(r)?;
+
+    // write the data file
+    let data_file = get_envi_data_file_name(&r.file_name);
+    write_band_data(r, &data_file)
+}
+
+/// Writes the header (HDR) file of an ENVI raster.
+pub(crate) fn write_envi_header(r: &mut Raster) -> Result<(), Error> {
+    if r.configs.photometric_interp == PhotometricInterpretation::RGB {
+        return Err(Error::new(
+            ErrorKind::InvalidInput,
+            "ENVI files are not suitable for storing packed RGB data. Use a GeoTiff format instead.",
+        ));
+    }
+
+    /*
+        The following is an example of the header file (HDR):
+
+        ENVI
+        description = {Whitebox raster}
+        samples = 8500
+        lines = 5016
+        bands = 1
+        header offset = 0
+        file type = ENVI Standard
+        data type = 4
+        interleave = bsq
+        byte order = 0
+        map info = {UTM, 1, 1, 492088.67, 4737708.10, 0.5, 0.5, 17, North, WGS-84, units=Meters}
+        data ignore value = -32768
+        band names = {Band 1}
+    */
+    let data_type = match r.configs.data_type {
+        DataType::U8 => 1,
+        DataType::I16 => 2,
+        DataType::I32 => 3,
+        DataType::F32 => 4,
+        DataType::F64 => 5,
+        DataType::U16 => 12,
+        DataType::U32 => 13,
+        DataType::I64 => 14,
+        DataType::U64 => 15,
+        _ => {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                format!(
+                    "The raster is of a data type ({:?}) that is not supported by the ENVI raster format.",
+                    r.configs.data_type
+                ),
+            ))
+        }
+    };
+
+    let header_file = get_envi_header_file_name(&r.file_name);
+    let f = File::create(header_file)?;
+    let mut writer = BufWriter::new(f);
+
+    writer.write_all("ENVI\n".as_bytes())?;
+
+    let description = if r.configs.title.is_empty() {
+        "Whitebox raster"
+    } else {
+        &r.configs.title
+    };
+    writer.write_all(format!("description = {{{}}}\n", description).as_bytes())?;
+    writer.write_all(format!("samples = {}\n", r.configs.columns).as_bytes())?;
+    writer.write_all(format!("lines = {}\n", r.configs.rows).as_bytes())?;
+    let nbands = r.configs.bands.max(1);
+    writer.write_all(format!("bands = {}\n", nbands).as_bytes())?;
+    writer.write_all("header offset = 0\n".as_bytes())?;
+    writer.write_all("file type = ENVI Standard\n".as_bytes())?;
+    writer.write_all(format!("data type = {}\n", data_type).as_bytes())?;
+    let interleave = match r.configs.interleave {
+        BandInterleave::BSQ => "bsq",
+        BandInterleave::BIL => "bil",
+        BandInterleave::BIP => "bip",
+    };
+    writer.write_all(format!("interleave = {}\n", interleave).as_bytes())?;
+    if r.configs.endian == Endianness::LittleEndian {
+        writer.write_all("byte order = 0\n".as_bytes())?;
+    } else {
+        writer.write_all("byte order = 1\n".as_bytes())?;
+    }
+
+    let units = if r.configs.xy_units.is_empty() || r.configs.xy_units == "not specified" {
+        String::new()
+    } else {
+        format!(", units={}", envi_units(&r.configs.xy_units))
+    };
+    // The upper-left corner of the upper-left pixel is used as the reference pixel.
+    let location = format!(
+        "1, 1, {}, {}, {}, {}",
+        r.configs.west, r.configs.north, r.configs.resolution_x, r.configs.resolution_y
+    );
+    let epsg = r.configs.epsg_code;
+    let projection = if epsg > 32600 && epsg <= 32660 {
+        format!("UTM, {}, {}, North, WGS-84", location, epsg - 32600)
+    } else if epsg > 32700 && epsg <= 32760 {
+        format!("UTM, {}, {}, South, WGS-84", location, epsg - 32700)
+    } else if epsg > 26900 && epsg <= 26923 {
+        format!("UTM, {}, {}, North, North America 1983", location, epsg - 26900)
+    } else if epsg == 4326 {
+        format!("Geographic Lat/Lon, {}, WGS-84", location)
+    } else {
+        format!("Arbitrary, {}", location)
+    };
+    writer.write_all(format!("map info = {{{}{}}}\n", projection, units).as_bytes())?;
+
+    // The coordinate system is written as WKT, if it is known.
+    let wkt = if is_wkt(&r.configs.projection) {
+        r.configs.projection.clone()
+    } else if is_wkt(&r.configs.coordinate_ref_system_wkt) {
+        r.configs.coordinate_ref_system_wkt.clone()
+    } else if epsg != 0 {
+        esri_wkt_from_epsg(epsg)
+    } else {
+        String::new()
+    };
+    if is_wkt(&wkt) {
+        let wkt = wkt.lines().map(|l| l.trim()).collect::<Vec<&str>>().join("");
+        writer.write_all(format!("coordinate system string = {{{}}}\n", wkt).as_bytes())?;
+    }
+
+    writer.write_all(format!("data ignore value = {}\n", r.configs.nodata).as_bytes())?;
+
+    let band_names = if r.configs.band_names.len() == nbands {
+        r.configs.band_names.clone()
+    } else {
+        (1..=nbands).map(|b| format!("Band {}", b)).collect()
+    };
+    writer.write_all(format!("band names = {{{}}}\n", band_names.join(", ")).as_bytes())?;
+
+    if r.configs.wavelengths.len() == nbands {
+        if !r.configs.wavelength_units.is_empty() {
+            let s = format!("wavelength units = {}\n", r.configs.wavelength_units);
+            writer.write_all(s.as_bytes())?;
+        }
+        let wavelengths = r
+            .configs
+            .wavelengths
+            .iter()
+            .map(|w| w.to_string())
+            .collect::<Vec<String>>();
+        writer.write_all(format!("wavelength = {{{}}}\n", wavelengths.join(", ")).as_bytes())?;
+    }
+
+    writer.flush()?;
+
+    Ok(())
+}
+
+/// Returns true if the projection is given as WKT.
+pub(crate) fn is_wkt(projection: &str) -> bool {
+    let projection = projection.trim_start();
+    ["PROJCS[", "GEOGCS[", "COMPD_CS[", "GEOCCS[", "LOCAL_CS[", "PROJCRS[", "GEOGCRS["]
+        .iter()
+        .any(|keyword| projection.starts_with(keyword))
+}
+
+/// Returns the ENVI name of a linear or angular unit, e.g. Meters rather than metres.
+fn envi_units(units: &str) -> String {
+    match units.to_lowercase().as_str() {
+        "m" | "metre" | "metres" | "meter" | "meters" => "Meters".to_string(),
+        "ft" | "foot" | "feet" => "Feet".to_string(),
+        "degree" | "degrees" => "Degrees".to_string(),
+        _ => {
+            let mut chars = units.chars();
+            match chars.next() {
+                Some(c) => c.to_uppercase().chain(chars).collect(),
+                None => String::new(),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::test_utils::*;
+    use crate::*;
+    use whitebox_common::utils::Endianness;
+
+    #[test]
+    fn envi_round_trip() {
+        let file_name = temp_file("envi.img");
+        for interleave in [BandInterleave::BSQ, BandInterleave::BIL, BandInterleave::BIP] {
+            for endian in [Endianness::LittleEndian, Endianness::BigEndian] {
+                for data_type in [DataType::I16, DataType::F32, DataType::U8] {
+                    let mut output = sample_raster(&file_name, 6, 5, data_type);
+                    output.configs.interleave = interleave;
+                    output.configs.endian = endian;
+                    output.configs.epsg_code = 32617;
+                    output.configs.xy_units = "metres".to_string();
+                    output.set_num_bands(3);
+                    for band in 1..3 {
+                        for row in 0..6 {
+                            for col in 0..5 {
+                                let value = (band * 10) as f64 + (row * col) as f64;
+                                output.set_band_value(band, row, col, value);
+                            }
+                        }
+                    }
+                    output.configs.band_names =
+                        vec!["red".to_string(), "nir".to_string(), "swir".to_string()];
+                    output.configs.wavelengths = vec![0.65, 0.86, 1.6];
+                    output.write().unwrap();
+
+                    let input = Raster::new(&file_name, "r").unwrap();
+                    assert_eq!(input.configs.data_type, data_type);
+                    assert_eq!(input.configs.interleave, interleave);
+                    assert_eq!(input.configs.endian, endian);
+                    assert_eq!(input.configs.epsg_code, 32617);
+                    assert_eq!(input.configs.band_names, output.configs.band_names);
+                    assert_eq!(input.configs.wavelengths, output.configs.wavelengths);
+                    assert_same_extent(&output, &input);
+                    assert_same_cells(&output, &input);
+                }
+            }
+        }
+        std::fs::remove_file(&file_name).unwrap();
+        std::fs::remove_file(temp_file("envi.hdr")).unwrap();
+    }
+
+    #[test]
+    fn read_envi_header_with_offset() {
+        // A header named by appending .hdr to the data file's name, a big-endian data file
+        // with a header offset, and a reference pixel at the centre of a cell.
+        let file_name = temp_file("offset.dat");
+        let header = "ENVI\n\
+            description = {A test\n  image}\n\
+            samples = 3\n\
+            lines = 2\n\
+            bands = 1\n\
+            header offset = 16\n\
+            data type = 12\n\
+            interleave = bsq\n\
+            byte order = 1\n\
+            map info = {Geographic Lat/Lon, 1.5, 1.5, -80.5, 44.5, 1.0, 1.0, WGS-84}\n\
+            data ignore value = 0\n\
+            band names = {elevation}\n";
+        std::fs::write(format!("{}.hdr", file_name), header).unwrap();
+        let mut data = vec![0xFFu8; 16];
+        for v in [0u16, 1, 2, 300, 400, 500] {
+            data.extend_from_slice(&v.to_be_bytes());
+        }
+        std::fs::write(&file_name, data).unwrap();
+
+        let input = Raster::new(&file_name, "r").unwrap();
+        assert_eq!(input.configs.title, "A test image");
+        assert_eq!((input.configs.rows, input.configs.columns), (2, 3));
+        assert_eq!(input.configs.data_type, DataType::U16);
+        assert_eq!((input.configs.west, input.configs.north), (-81.0, 45.0));
+        assert_eq!((input.configs.east, input.configs.south), (-78.0, 43.0));
+        assert_eq!(input.configs.epsg_code, 4326);
+        assert_eq!(input.configs.xy_units, "degrees");
+        assert_eq!(input.configs.nodata, 0.0);
+        assert_eq!(input.get_value(0, 0), 0.0);
+        assert_eq!(input.get_value(0, 2), 2.0);
+        assert_eq!(input.get_value(1, 1), 400.0);
+        std::fs::remove_file(&file_name).unwrap();
+        std::fs::remove_file(format!("{}.hdr", file_name)).unwrap();
+    }
+}
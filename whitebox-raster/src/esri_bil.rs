@@ -4,33 +4,39 @@ use std::convert::TryInto;
 use std::f64;
 use std::fs::File;
 use std::io::prelude::*;
-use std::io::{BufReader, BufWriter, Error, SeekFrom};
+use std::io::{BufReader, BufWriter, Error, ErrorKind};
 
-pub fn read_esri_bil(
+/// Reads the header (HDR) and projection (PRJ) files of a BIL raster, returning the
+/// arrangement of the values within the data file.
+pub(crate) fn read_esri_bil_header(
     file_name: &String,
     configs: &mut RasterConfigs,
-    data: &mut Vec<f64>,
-) -> Result<(), Error> {
+) -> Result<RawLayout, Error> {
     // read the header file
     let header_file = Path::new(&file_name)
         .with_extension("hdr")
         .into_os_string()
         .into_string()
         .expect("Error creating header file name string for BIL file.");
-    let f = File::open(header_file).expect("Error opening BIL header (HDR) file.");
+    let f = File::open(header_file)?;
     let f = BufReader::new(f);
 
-    // let mut nbands = 1usize;
+    let mut nbands = 1usize;
     let mut band_row_bytes = 0usize;
-    let mut total_row_bytes = 0u64;
+    let mut total_row_bytes = 0usize;
+    let mut band_gap_bytes = 0usize;
+    let mut skip_bytes = 0usize;
     let mut pixel_type = String::new();
-    let mut nbits = 0usize;
+    let mut nbits = 8usize;
     let mut ulxmap = 0f64;
     let mut ulymap = 0f64;
     configs.nodata = -32768f64; // default in event that it is not in header file
 
+    // The layout defaults to that implied by the data file's extension.
+    configs.interleave = get_interleave_from_file_name(file_name);
+
     for line in f.lines() {
-        let line_unwrapped = line.unwrap();
+        let line_unwrapped = line?;
         let line_split = line_unwrapped.split(" ");
         let vec = line_split.collect::<Vec<&str>>();
         let key = vec[0].to_lowercase().trim().to_string();
@@ -50,26 +56,34 @@ pub fn read_esri_bil(
                 configs.endian = Endianness::BigEndian;
             }
         } else if key.contains("layout") {
-            if !value.contains("bil") {
-                println!("Warning: Only the Esri BIL layout is supported by WhiteboxTools. BSQ and BIP layouts are currently unsupported.")
+            if value.contains("bil") {
+                configs.interleave = BandInterleave::BIL;
+            } else if value.contains("bip") {
+                configs.interleave = BandInterleave::BIP;
+            } else if value.contains("bsq") {
+                configs.interleave = BandInterleave::BSQ;
+            } else {
+                return Err(Error::new(
+                    ErrorKind::InvalidData,
+                    format!("Unsupported Esri BIL layout '{}'.", value),
+                ));
             }
         } else if key.contains("nrows") {
             configs.rows = value.trim().parse::<f32>().unwrap() as usize;
         } else if key.contains("ncols") {
             configs.columns = value.trim().parse::<f32>().unwrap() as usize;
         } else if key.contains("nbands") {
-            let nbands = value.trim().parse::<f32>().unwrap() as usize;
-            if nbands > 1 {
-                if !value.contains("bil") {
-                    println!("Warning: The Esri BIL reader only supports single-band rasters. Only the first band will be read.")
-                }
-            }
+            nbands = value.trim().parse::<f32>().unwrap() as usize;
         } else if key.contains("nbits") {
             nbits = value.trim().parse::<f32>().unwrap() as usize;
         } else if key.contains("bandrowbytes") {
             band_row_bytes = value.trim().parse::<f64>().unwrap() as usize;
         } else if key.contains("totalrowbytes") {
-            total_row_bytes = value.trim().parse::<f64>().unwrap() as u64;
+            total_row_bytes = value.trim().parse::<f64>().unwrap() as usize;
+        } else if key.contains("bandgapbytes") {
+            band_gap_bytes = value.trim().parse::<f64>().unwrap() as usize;
+        } else if key.contains("skipbytes") {
+            skip_bytes = value.trim().parse::<f64>().unwrap() as usize;
         } else if key.contains("pixeltype") {
             pixel_type = value;
         } else if key.contains("ulxmap") {
@@ -86,28 +100,40 @@ pub fn read_esri_bil(
     }
 
     configs.photometric_interp = PhotometricInterpretation::Continuous;
+    configs.bands = nbands.max(1);
 
-    if pixel_type == "unsignedint" {
-        match nbits {
-            8 => configs.data_type = DataType::U8,
-            16 => configs.data_type = DataType::U16,
-            32 => configs.data_type = DataType::U32,
-            _ => panic!("Unrecognized data type"),
-        }
-    } else if pixel_type == "signedint" {
+    if pixel_type == "signedint" {
         match nbits {
             8 => configs.data_type = DataType::I8,
             16 => configs.data_type = DataType::I16,
             32 => configs.data_type = DataType::I32,
             _ => panic!("Unrecognized data type"),
         }
-    } else {
-        // float
+    } else if pixel_type == "float" {
         match nbits {
             32 => configs.data_type = DataType::F32,
             64 => configs.data_type = DataType::F64,
             _ => panic!("Unrecognized data type"),
         }
+    } else {
+        // unsigned integers are the default pixel type of the format
+        match nbits {
+            8 => configs.data_type = DataType::U8,
+            16 => configs.data_type = DataType::U16,
+            32 => configs.data_type = DataType::U32,
+            _ => panic!("Unrecognized data type"),
+        }
+    }
+
+    let data_size = nbits / 8;
+    if band_row_bytes == 0 {
+        band_row_bytes = configs.columns * data_size;
+    }
+    if total_row_bytes == 0 {
+        total_row_bytes = match configs.interleave {
+            BandInterleave::BIL | BandInterleave::BIP => band_row_bytes * configs.bands,
+            BandInterleave::BSQ => band_row_bytes,
+        };
     }
 
     configs.north = ulymap + configs.resolution_y / 2.0f64;
@@ -123,280 +149,200 @@ pub fn read_esri_bil(
         .expect("Error creating projection file name for BIL file.");
 
     if std::path::Path::new(&prj_file).exists() {
-        let f = File::open(prj_file.clone()).expect("Error opening BIL projection (PRJ) file.");
+        let f = File::open(prj_file.clone())?;
         let f = BufReader::new(f);
         configs.projection = String::new();
         for line in f.lines() {
-            let line_unwrapped = line.unwrap();
+            let line_unwrapped = line?;
             if !line_unwrapped.is_empty() {
                 configs.projection = format!("{}{}\n", configs.projection, line_unwrapped);
             }
         }
     }
 
-    // read the data file
-    data.reserve(configs.rows * configs.columns);
+    Ok(RawLayout {
+        skip_bytes: skip_bytes,
+        band_row_bytes: band_row_bytes,
+        total_row_bytes: total_row_bytes,
+        band_gap_bytes: band_gap_bytes,
+    })
+}
+
+/// Returns the band layout implied by the extension of the data file (bil, bip, or bsq).
+fn get_interleave_from_file_name(file_name: &str) -> BandInterleave {
+    get_interleave_from_extension(file_name).unwrap_or(BandInterleave::BIL)
+}
+
+fn get_interleave_from_extension(file_name: &str) -> Option<BandInterleave> {
+    let extension = Path::new(file_name)
+        .extension()
+        .and_then(|e| e.to_str())
+        .unwrap_or("")
+        .to_lowercase();
+    match extension.as_str() {
+        "bil" => Some(BandInterleave::BIL),
+        "bip" => Some(BandInterleave::BIP),
+        "bsq" => Some(BandInterleave::BSQ),
+        _ => None,
+    }
+}
 
-    let data_file = Path::new(&file_name)
-        .with_extension("bil")
+/// Returns the name of the data file. This is the file itself, unless it is the header
+/// (.hdr) file, in which case it is the .bil, .bip, or .bsq file that accompanies it.
+pub(crate) fn get_bil_data_file_name(file_name: &str) -> String {
+    let path = Path::new(file_name);
+    let is_header = path
+        .extension()
+        .and_then(|e| e.to_str())
+        .map(|e| e.eq_ignore_ascii_case("hdr"))
+        .unwrap_or(false);
+    if !is_header {
+        return file_name.to_string();
+    }
+    let data_file = ["bil", "bip", "bsq"]
+        .iter()
+        .map(|extension| path.with_extension(extension))
+        .find(|data_file| data_file.is_file())
+        .unwrap_or(path.with_extension("bil"));
+    data_file
         .into_os_string()
         .into_string()
-        .expect("Error creating file name string for BIL file.");
-    let mut f = File::open(data_file.clone()).expect("Error opening BIL data file.");
-
-    configs.minimum = f64::INFINITY;
-    configs.maximum = f64::NEG_INFINITY;
+        .expect("Error creating file name string for BIL file.")
+}
 
-    let mut data_size = 4;
-    let mut z: f64;
-    match configs.data_type {
-        DataType::U8 => {
-            data_size = 1;
-            for row in 0..configs.rows as u64 {
-                let mut buffer = vec![0; band_row_bytes];
-                f.seek(SeekFrom::Start(row * total_row_bytes))
-                    .expect("Error while seeking to position in BIL file.");
-                f.read(&mut buffer)
-                    .expect("Error while reading from BIL data file.");
-
-                let mut offset: usize;
-                for col in 0..configs.columns {
-                    offset = col * data_size;
-                    z = buffer[offset] as f64;
-                    data.push(z);
-
-                    if z != configs.nodata {
-                        if z < configs.minimum {
-                            configs.minimum = z;
-                        }
-                        if z > configs.maximum {
-                            configs.maximum = z;
-                        }
-                    }
-                }
+pub(crate) fn decode_value(buf: &[u8], data_type: DataType, endian: Endianness) -> f64 {
+    let le = endian == Endianness::LittleEndian;
+    match data_type {
+        DataType::U8 => buf[0] as f64,
+        DataType::I8 => (buf[0] as i8) as f64,
+        DataType::U16 => {
+            if le {
+                u16::from_le_bytes(get_two_bytes(buf)) as f64
+            } else {
+                u16::from_be_bytes(get_two_bytes(buf)) as f64
             }
         }
-        DataType::U16 => {
-            data_size = 2;
-            for row in 0..configs.rows as u64 {
-                let mut buffer = vec![0; band_row_bytes];
-                f.seek(SeekFrom::Start(row * total_row_bytes))
-                    .expect("Error while seeking to position in BIL file.");
-                f.read(&mut buffer)
-                    .expect("Error while reading from BIL data file.");
-
-                let mut offset: usize;
-                for col in 0..configs.columns {
-                    offset = col * data_size;
-                    z = if configs.endian == Endianness::LittleEndian {
-                        u16::from_le_bytes(get_two_bytes(&buffer[offset..offset + data_size]))
-                            as f64
-                    } else {
-                        u16::from_be_bytes(get_two_bytes(&buffer[offset..offset + data_size]))
-                            as f64
-                    };
-                    data.push(z);
-
-                    if z != configs.nodata {
-                        if z < configs.minimum {
-                            configs.minimum = z;
-                        }
-                        if z > configs.maximum {
-                            configs.maximum = z;
-                        }
-                    }
-                }
+        DataType::I16 => {
+            if le {
+                i16::from_le_bytes(get_two_bytes(buf)) as f64
+            } else {
+                i16::from_be_bytes(get_two_bytes(buf)) as f64
             }
         }
         DataType::U32 => {
-            data_size = 4;
-            for row in 0..configs.rows as u64 {
-                let mut buffer = vec![0; band_row_bytes];
-                f.seek(SeekFrom::Start(row * total_row_bytes))
-                    .expect("Error while seeking to position in BIL file.");
-                f.read(&mut buffer)
-                    .expect("Error while reading from BIL data file.");
-
-                let mut offset: usize;
-                for col in 0..configs.columns {
-                    offset = col * data_size;
-                    z = if configs.endian == Endianness::LittleEndian {
-                        u32::from_le_bytes(get_four_bytes(&buffer[offset..offset + data_size]))
-                            as f64
-                    } else {
-                        u32::from_be_bytes(get_four_bytes(&buffer[offset..offset + data_size]))
-                            as f64
-                    };
-                    data.push(z);
-
-                    if z != configs.nodata {
-                        if z < configs.minimum {
-                            configs.minimum = z;
-                        }
-                        if z > configs.maximum {
-                            configs.maximum = z;
-                        }
-                    }
-                }
+            if le {
+                u32::from_le_bytes(get_four_bytes(buf)) as f64
+            } else {
+                u32::from_be_bytes(get_four_bytes(buf)) as f64
             }
         }
-        DataType::I8 => {
-            data_size = 1;
-            for row in 0..configs.rows as u64 {
-                let mut buffer = vec![0; band_row_bytes];
-                f.seek(SeekFrom::Start(row * total_row_bytes))
-                    .expect("Error while seeking to position in BIL file.");
-                f.read(&mut buffer)
-                    .expect("Error while reading from BIL data file.");
-
-                let mut offset: usize;
-                for col in 0..configs.columns {
-                    offset = col * data_size;
-                    z = (buffer[offset] as i8) as f64;
-                    data.push(z);
-
-                    if z != configs.nodata {
-                        if z < configs.minimum {
-                            configs.minimum = z;
-                        }
-                        if z > configs.maximum {
-                            configs.maximum = z;
-                        }
-                    }
-                }
+        DataType::I32 => {
+            if le {
+                i32::from_le_bytes(get_four_bytes(buf)) as f64
+            } else {
+                i32::from_be_bytes(get_four_bytes(buf)) as f64
+            }
+        }
+        DataType::F32 => {
+            if le {
+                f32::from_le_bytes(get_four_bytes(buf)) as f64
+            } else {
+                f32::from_be_bytes(get_four_bytes(buf)) as f64
+            }
+        }
+        DataType::I64 => {
+            if le {
+                i64::from_le_bytes(get_eight_bytes(buf)) as f64
+            } else {
+                i64::from_be_bytes(get_eight_bytes(buf)) as f64
+            }
+        }
+        DataType::U64 => {
+            if le {
+                u64::from_le_bytes(get_eight_bytes(buf)) as f64
+            } else {
+                u64::from_be_bytes(get_eight_bytes(buf)) as f64
+            }
+        }
+        DataType::F64 => {
+            if le {
+                f64::from_le_bytes(get_eight_bytes(buf))
+            } else {
+                f64::from_be_bytes(get_eight_bytes(buf))
+            }
+        }
+        _ => panic!("Unsupported BIL data type."),
+    }
+}
+
+pub(crate) fn encode_value<W: Write>(
+    writer: &mut W,
+    data_type: DataType,
+    endian: Endianness,
+    value: f64,
+) -> Result<(), Error> {
+    let le = endian == Endianness::LittleEndian;
+    match data_type {
+        DataType::U8 => writer.write_all(&[value as u8]),
+        DataType::I8 => writer.write_all(&(value as i8).to_le_bytes()),
+        DataType::U16 => {
+            if le {
+                writer.write_all(&(value as u16).to_le_bytes())
+            } else {
+                writer.write_all(&(value as u16).to_be_bytes())
             }
         }
         DataType::I16 => {
-            data_size = 2;
-            for row in 0..configs.rows as u64 {
-                let mut buffer = vec![0; band_row_bytes];
-                f.seek(SeekFrom::Start(row * total_row_bytes))
-                    .expect("Error while seeking to position in BIL file.");
-                f.read(&mut buffer)
-                    .expect("Error while reading from BIL data file.");
-
-                let mut offset: usize;
-                for col in 0..configs.columns {
-                    offset = col * data_size;
-                    z = if configs.endian == Endianness::LittleEndian {
-                        i16::from_le_bytes(get_two_bytes(&buffer[offset..offset + data_size]))
-                            as f64
-                    } else {
-                        i16::from_be_bytes(get_two_bytes(&buffer[offset..offset + data_size]))
-                            as f64
-                    };
-                    data.push(z);
-
-                    if z != configs.nodata {
-                        if z < configs.minimum {
-                            configs.minimum = z;
-                        }
-                        if z > configs.maximum {
-                            configs.maximum = z;
-                        }
-                    }
-                }
+            if le {
+                writer.write_all(&(value as i16).to_le_bytes())
+            } else {
+                writer.write_all(&(value as i16).to_be_bytes())
+            }
+        }
+        DataType::U32 => {
+            if le {
+                writer.write_all(&(value as u32).to_le_bytes())
+            } else {
+                writer.write_all(&(value as u32).to_be_bytes())
             }
         }
         DataType::I32 => {
-            data_size = 4;
-            for row in 0..configs.rows as u64 {
-                let mut buffer = vec![0; band_row_bytes];
-                f.seek(SeekFrom::Start(row * total_row_bytes))
-                    .expect("Error while seeking to position in BIL file.");
-                f.read(&mut buffer)
-                    .expect("Error while reading from BIL data file.");
-
-                let mut offset: usize;
-                for col in 0..configs.columns {
-                    offset = col * data_size;
-                    z = if configs.endian == Endianness::LittleEndian {
-                        i32::from_le_bytes(get_four_bytes(&buffer[offset..offset + data_size]))
-                            as f64
-                    } else {
-                        i32::from_be_bytes(get_four_bytes(&buffer[offset..offset + data_size]))
-                            as f64
-                    };
-                    data.push(z);
-
-                    if z != configs.nodata {
-                        if z < configs.minimum {
-                            configs.minimum = z;
-                        }
-                        if z > configs.maximum {
-                            configs.maximum = z;
-                        }
-                    }
-                }
+            if le {
+                writer.write_all(&(value as i32).to_le_bytes())
+            } else {
+                writer.write_all(&(value as i32).to_be_bytes())
             }
         }
         DataType::F32 => {
-            for row in 0..configs.rows as u64 {
-                let mut buffer = vec![0; band_row_bytes];
-                f.seek(SeekFrom::Start(row * total_row_bytes))
-                    .expect("Error while seeking to position in BIL file.");
-                f.read(&mut buffer)
-                    .expect("Error while reading from BIL data file.");
-
-                let mut offset: usize;
-                for col in 0..configs.columns {
-                    offset = col * data_size;
-                    z = if configs.endian == Endianness::LittleEndian {
-                        f32::from_le_bytes(get_four_bytes(&buffer[offset..offset + data_size]))
-                            as f64
-                    } else {
-                        f32::from_be_bytes(get_four_bytes(&buffer[offset..offset + data_size]))
-                            as f64
-                    };
-                    data.push(z);
-
-                    if z != configs.nodata {
-                        if z < configs.minimum {
-                            configs.minimum = z;
-                        }
-                        if z > configs.maximum {
-                            configs.maximum = z;
-                        }
-                    }
-                }
+            if le {
+                writer.write_all(&(value as f32).to_le_bytes())
+            } else {
+                writer.write_all(&(value as f32).to_be_bytes())
             }
         }
-        DataType::F64 => {
-            data_size = 8;
-            for row in 0..configs.rows as u64 {
-                let mut buffer = vec![0; band_row_bytes];
-                f.seek(SeekFrom::Start(row * total_row_bytes))
-                    .expect("Error while seeking to position in BIL file.");
-                f.read(&mut buffer)
-                    .expect("Error while reading from BIL data file.");
-
-                let mut offset: usize;
-                for col in 0..configs.columns {
-                    offset = col * data_size;
-                    z = if configs.endian == Endianness::LittleEndian {
-                        f64::from_le_bytes(get_eight_bytes(&buffer[offset..offset + data_size]))
-                    } else {
-                        f64::from_be_bytes(get_eight_bytes(&buffer[offset..offset + data_size]))
-                    };
-                    data.push(z);
-
-                    if z != configs.nodata {
-                        if z < configs.minimum {
-                            configs.minimum = z;
-                        }
-                        if z > configs.maximum {
-                            configs.maximum = z;
-                        }
-                    }
-                }
+        DataType::I64 => {
+            if le {
+                writer.write_all(&(value as i64).to_le_bytes())
+            } else {
+                writer.write_all(&(value as i64).to_be_bytes())
             }
         }
-        _ => {
-            panic!("Unsupported BIL data type.");
+        DataType::U64 => {
+            if le {
+                writer.write_all(&(value as u64).to_le_bytes())
+            } else {
+                writer.write_all(&(value as u64).to_be_bytes())
+            }
+        }
+        DataType::F64 => {
+            if le {
+                writer.write_all(&value.to_le_bytes())
+            } else {
+                writer.write_all(&value.to_be_bytes())
+            }
         }
+        _ => panic!("The raster is of a data type that is not supported by the BIL raster format."),
     }
-
-    Ok(())
 }
 
 fn get_two_bytes(buf: &[u8]) -> [u8; 2] {
@@ -415,10 +361,61 @@ fn get_eight_bytes(buf: &[u8]) -> [u8; 8] {
 }
 
 pub fn write_esri_bil<'a>(r: &'a mut Raster) -> Result<(), Error> {
+    write_esri_bil_header(r)?;
+
+    // write the data file
+    let data_file = get_bil_data_file_name(&r.file_name);
+    write_band_data(r, &data_file)
+}
+
+/// Writes the uncompressed values of each of the raster's bands to `data_file`, arranged
+/// according to the raster's band interleave.
+pub(crate) fn write_band_data(r: &Raster, data_file: &str) -> Result<(), Error> {
+    let layout = r.configs.interleave;
+    let nbands = r.configs.bands.max(1);
+    let f = File::create(data_file)?;
+    let mut writer = BufWriter::new(f);
+
+    let num_cells = r.configs.rows * r.configs.columns;
+    let columns = r.configs.columns;
+    let data_type = r.configs.data_type;
+    let endian = r.configs.endian;
+    match layout {
+        BandInterleave::BSQ => {
+            for band in 0..nbands {
+                for i in 0..num_cells {
+                    encode_value(&mut writer, data_type, endian, r.get_cell(band * num_cells + i))?;
+                }
+            }
+        }
+        BandInterleave::BIL => {
+            for row in 0..r.configs.rows {
+                for band in 0..nbands {
+                    for col in 0..columns {
+                        encode_value(&mut writer, data_type, endian, r.get_cell(band * num_cells + row * columns + col))?;
+                    }
+                }
+            }
+        }
+        BandInterleave::BIP => {
+            for i in 0..num_cells {
+                for band in 0..nbands {
+                    encode_value(&mut writer, data_type, endian, r.get_cell(band * num_cells + i))?;
+                }
+            }
+        }
+    }
+
+    writer.flush()?;
+
+    Ok(())
+}
+
+/// Writes the header (HDR) and projection (PRJ) files of a BIL raster.
+pub(crate) fn write_esri_bil_header(r: &mut Raster) -> Result<(), Error> {
     if r.configs.photometric_interp == PhotometricInterpretation::RGB {
         panic!(
-            "Single-band Esri BIL files are not suitable for storing RGB data. WhiteboxTools 
-        presently only supports single-band BIL files. Use a GeoTiff format instead."
+            "Esri BIL files are not suitable for storing packed RGB data. Use a GeoTiff format instead."
         );
     }
 
@@ -448,36 +445,31 @@ pub fn write_esri_bil<'a>(r: &'a mut Raster) -> Result<(), Error> {
         .into_string()
         .expect("Error when trying to create BIL header (HDR) file.");
 
-    let f = File::create(header_file).expect("Error while creating BIL file.");
+    let f = File::create(header_file)?;
     let mut writer = BufWriter::new(f);
 
     if r.configs.endian == Endianness::LittleEndian {
-        writer
-            .write_all("BYTEORDER      I\n".as_bytes())
-            .expect("Error while writing to BIL file.");
+        writer.write_all("BYTEORDER      I\n".as_bytes())?;
     } else {
-        writer
-            .write_all("BYTEORDER      M\n".as_bytes())
-            .expect("Error while writing to BIL file.");
+        writer.write_all("BYTEORDER      M\n".as_bytes())?;
     }
 
-    writer
-        .write_all("LAYOUT         BIL\n".as_bytes())
-        .expect("Error while writing to BIL file.");
+    // The layout of the data file is determined by its extension (bil, bip, or bsq), if
+    // it has one of these.
+    let layout = get_interleave_from_extension(&r.file_name).unwrap_or(r.configs.interleave);
+    r.configs.interleave = layout;
+    let s = format!("LAYOUT         {:?}\n", layout);
+    writer.write_all(s.as_bytes())?;
 
     let s = format!("NROWS          {}\n", r.configs.rows);
-    writer
-        .write_all(s.as_bytes())
-        .expect("Error while writing to BIL file.");
+    writer.write_all(s.as_bytes())?;
 
     let s = format!("NCOLS          {}\n", r.configs.columns);
-    writer
-        .write_all(s.as_bytes())
-        .expect("Error while writing to BIL file.");
+    writer.write_all(s.as_bytes())?;
 
-    writer
-        .write_all("NBANDS         1\n".as_bytes())
-        .expect("Error while writing to BIL file.");
+    let nbands = r.configs.bands.max(1);
+    let s = format!("NBANDS         {}\n", nbands);
+    writer.write_all(s.as_bytes())?;
 
     let nbits: usize;
     let pixel_type: String;
@@ -518,57 +510,47 @@ pub fn write_esri_bil<'a>(r: &'a mut Raster) -> Result<(), Error> {
     }
 
     let s = format!("NBITS          {}\n", nbits);
-    writer
-        .write_all(s.as_bytes())
-        .expect("Error while writing to BIL file.");
+    writer.write_all(s.as_bytes())?;
 
     let s = format!("BANDROWBYTES   {}\n", nbits / 8 * r.configs.columns);
-    writer
-        .write_all(s.as_bytes())
-        .expect("Error while writing to BIL file.");
+    writer.write_all(s.as_bytes())?;
 
-    let s = format!("TOTALROWBYTES  {}\n", nbits / 8 * r.configs.columns);
-    writer
-        .write_all(s.as_bytes())
-        .expect("Error while writing to BIL file.");
+    let total_row_bytes = match layout {
+        BandInterleave::BIL | BandInterleave::BIP => nbits / 8 * r.configs.columns * nbands,
+        BandInterleave::BSQ => nbits / 8 * r.configs.columns,
+    };
+    let s = format!("TOTALROWBYTES  {}\n", total_row_bytes);
+    writer.write_all(s.as_bytes())?;
+
+    if layout == BandInterleave::BSQ {
+        writer.write_all("BANDGAPBYTES   0\n".as_bytes())?;
+    }
 
     let s = format!("PIXELTYPE      {}\n", pixel_type);
-    writer
-        .write_all(s.as_bytes())
-        .expect("Error while writing to BIL file.");
+    writer.write_all(s.as_bytes())?;
 
     let s = format!(
         "ULXMAP         {}\n",
         r.configs.west + r.configs.resolution_x / 2.0
     );
-    writer
-        .write_all(s.as_bytes())
-        .expect("Error while writing to BIL file.");
+    writer.write_all(s.as_bytes())?;
 
     let s = format!(
         "ULYMAP         {}\n",
         r.configs.north - r.configs.resolution_y / 2.0
     );
-    writer
-        .write_all(s.as_bytes())
-        .expect("Error while writing to BIL file.");
+    writer.write_all(s.as_bytes())?;
 
     let s = format!("XDIM           {}\n", r.configs.resolution_x);
-    writer
-        .write_all(s.as_bytes())
-        .expect("Error while writing to BIL file.");
+    writer.write_all(s.as_bytes())?;
 
     let s = format!("YDIM           {}\n", r.configs.resolution_y);
-    writer
-        .write_all(s.as_bytes())
-        .expect("Error while writing to BIL file.");
+    writer.write_all(s.as_bytes())?;
 
     let s = format!("NODATA         {}\n", r.configs.nodata);
-    writer
-        .write_all(s.as_bytes())
-        .expect("Error while writing to BIL file.");
+    writer.write_all(s.as_bytes())?;
 
-    let _ = writer.flush();
+    writer.flush()?;
 
     // output the projection file
     if !r.configs.projection.is_empty() {
@@ -580,83 +562,10 @@ pub fn write_esri_bil<'a>(r: &'a mut Raster) -> Result<(), Error> {
         let f = File::create(&prj_file)?;
         let mut writer = BufWriter::new(f);
 
-        writer
-            .write_all(r.configs.projection.as_bytes())
-            .expect("Error while writing to BIL file.");
-
-        let _ = writer.flush();
-    }
+        writer.write_all(r.configs.projection.as_bytes())?;
 
-    // write the data file
-    let data_file = Path::new(&r.file_name)
-        .with_extension("bil")
-        .into_os_string()
-        .into_string()
-        .expect("Error when trying to create BIL file.");
-    let f = File::create(&data_file)?;
-    let mut writer = BufWriter::new(f);
-
-    match r.configs.data_type {
-        DataType::U8 => {
-            for i in 0..r.data.len() {
-                writer
-                    .write(&([r.data[i] as u8]))
-                    .expect("Error writing bytes to BIL file.");
-            }
-        }
-        DataType::U16 => {
-            for i in 0..r.data.len() {
-                writer
-                    .write(&(r.data[i] as u16).to_le_bytes())
-                    .expect("Error writing bytes to BIL file.");
-            }
-        }
-        DataType::U32 => {
-            for i in 0..r.data.len() {
-                writer
-                    .write(&(r.data[i] as u32).to_le_bytes())
-                    .expect("Error writing bytes to BIL file.");
-            }
-        }
-        DataType::I8 => {
-            for i in 0..r.data.len() {
-                writer
-                    .write(&(r.data[i] as i8).to_le_bytes())
-                    .expect("Error writing bytes to BIL file.");
-            }
-        }
-        DataType::I16 => {
-            for i in 0..r.data.len() {
-                writer
-                    .write(&(r.data[i] as i16).to_le_bytes())
-                    .expect("Error writing bytes to BIL file.");
-            }
-        }
-        DataType::I32 => {
-            for i in 0..r.data.len() {
-                writer
-                    .write(&(r.data[i] as i32).to_le_bytes())
-                    .expect("Error writing bytes to BIL file.");
-            }
-        }
-        DataType::F32 => {
-            for i in 0..r.data.len() {
-                writer
-                    .write(&(r.data[i] as f32).to_le_bytes())
-                    .expect("Error writing bytes to BIL file.");
-            }
-        }
-        DataType::F64 => {
-            for i in 0..r.data.len() {
-                writer
-                    .write(&(r.data[i]).to_le_bytes())
-                    .expect("Error writing bytes to BIL file.");
-            }
-        }
-        _ => panic!("The raster is of a data type that is not supported by the BIL raster format."),
+        writer.flush()?;
     }
 
-    let _ = writer.flush();
-
     Ok(())
 }
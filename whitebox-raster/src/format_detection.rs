@@ -0,0 +1,331 @@
+use super::xyz_raster::split_xyz_line;
+use super::RasterType;
+use std::fs::File;
+use std::io::{Error, Read};
+use std::path::{Path, PathBuf};
+
+/// The number of bytes at the start of a file that are examined to identify its format.
+const HEADER_BYTES: usize = 4096;
+
+/// Identifies the format of an existing raster file from its contents, rather than its
+/// extension. Binary formats are recognized by their signatures and text formats by the
+/// keywords of their headers. Raw binary data files, which have neither, are identified
+/// by the header file that accompanies them. Returns `None` if the file does not exist
+/// or its format is ambiguous, in which case the extension should decide.
+pub(crate) fn detect_raster_type(file_name: &str) -> Result<Option<RasterType>, Error> {
+    let path = Path::new(file_name);
+    if !path.is_file() {
+        return Ok(None);
+    }
+    let header = read_header_bytes(path)?;
+
+    if let Some(raster_type) = detect_signature(&header) {
+        return Ok(Some(raster_type));
+    }
+
+    if is_text(&header) {
+        if let Some(raster_type) = detect_text_header(&String::from_utf8_lossy(&header)) {
+            return Ok(Some(raster_type));
+        }
+    }
+
+    // Otherwise, this may be a raw binary data file; look for its header file. Rasters of
+    // different formats sometimes share a base name in the same directory, in which case
+    // the header files disagree and the format is ambiguous.
+    let mut detected: Option<RasterType> = None;
+    let mut header_files: Vec<PathBuf> = ["hdr", "sgrd", "rdc", "dep"]
+        .iter()
+        .map(|extension| path.with_extension(extension))
+        .collect();
+    // ENVI header files may also be named by appending .hdr to the data file's name.
+    header_files.push(PathBuf::from(format!("{}.hdr", file_name)));
+    for header_file in header_files {
+        if header_file == path || !header_file.is_file() {
+            continue;
+        }
+        let header = read_header_bytes(&header_file)?;
+        if !is_text(&header) {
+            continue;
+        }
+        match detect_text_header(&String::from_utf8_lossy(&header)) {
+            Some(RasterType::ArcAscii) | Some(RasterType::GrassAscii) | None => {}
+            Some(raster_type) => match &detected {
+                Some(other) if *other != raster_type => return Ok(None),
+                _ => detected = Some(raster_type),
+            },
+        }
+    }
+
+    Ok(detected)
+}
+
+fn read_header_bytes(path: &Path) -> Result<Vec<u8>, Error> {
+    let mut header = Vec::with_capacity(HEADER_BYTES);
+    File::open(path)?
+        .take(HEADER_BYTES as u64)
+        .read_to_end(&mut header)?;
+    Ok(header)
+}
+
+/// Recognizes the formats whose files begin with a fixed signature.
+fn detect_signature(header: &[u8]) -> Option<RasterType> {
+    if header.len() < 4 {
+        return None;
+    }
+    // GeoPackages are SQLite databases whose application id is 'GPKG', or 'GP10' and
+    // 'GP11' in versions 1.0 and 1.1.
+    if header.starts_with(b"SQLite format 3\0") && header.len() >= 72 {
+        return match &header[68..72] {
+            b"GPKG" | b"GP10" | b"GP11" => Some(RasterType::GeoPackage),
+            _ => None,
+        };
+    }
+    match &header[0..4] {
+        // Classic TIFF (42) and BigTIFF (43), in either byte order
+        b"II*\0" | b"MM\0*" | b"II+\0" | b"MM\0+" => Some(RasterType::GeoTiff),
+        b"DSAA" => Some(RasterType::SurferAscii),
+        b"DSRB" => Some(RasterType::Surfer7Binary),
+        // NetCDF classic and 64-bit offset files, and the formats that the reader
+        // reports as unsupported: CDF-5 and NetCDF-4, which is HDF5
+        b"CDF\x01" | b"CDF\x02" | b"CDF\x05" | b"\x89HDF" => Some(RasterType::NetCdf),
+        _ => None,
+    }
+}
+
+/// Returns true if the bytes appear to be text, i.e. they are valid UTF-8 (allowing for
+/// a character cut off at the end), with no NUL bytes and few other control characters.
+fn is_text(header: &[u8]) -> bool {
+    if header.is_empty() || header.contains(&0u8) {
+        return false;
+    }
+    if let Err(e) = std::str::from_utf8(header) {
+        if e.error_len().is_some() {
+            return false;
+        }
+    }
+    let control_chars = header
+        .iter()
+        .filter(|&&b| b < 32 && b != b'\n' && b != b'\r' && b != b'\t')
+        .count();
+    control_chars * 100 < header.len()
+}
+
+/// Recognizes the formats with text headers, or that are entirely text, from the
+/// keywords at the start of the file.
+fn detect_text_header(text: &str) -> Option<RasterType> {
+    let keys: Vec<String> = text
+        .lines()
+        .take(64)
+        .filter_map(|line| {
+            let line = line.trim().to_lowercase();
+            line.split(|c: char| c == ':' || c == '=' || c.is_whitespace())
+                .next()
+                .filter(|key| !key.is_empty())
+                .map(|key| key.to_string())
+        })
+        .collect();
+    let lower = text.to_lowercase();
+    let has_key = |key: &str| keys.iter().any(|k| k == key);
+
+    if keys.first().map(|key| key == "envi").unwrap_or(false) {
+        // ENVI header (.hdr)
+        Some(RasterType::Envi)
+    } else if lower.contains("data type:") && has_key("north") && has_key("rows") {
+        // Whitebox header (.dep)
+        Some(RasterType::Whitebox)
+    } else if has_key("dataformat") && has_key("cellsize") {
+        // SAGA header (.sgrd)
+        Some(RasterType::SagaBinary)
+    } else if lower.contains("file format") && lower.contains("file type") {
+        // Idrisi raster documentation file (.rdc)
+        Some(RasterType::IdrisiBinary)
+    } else if has_key("nrows") && (has_key("nbits") || has_key("layout") || has_key("ulxmap")) {
+        // Esri BIL header (.hdr)
+        Some(RasterType::EsriBil)
+    } else if has_key("ncols") && (has_key("xllcorner") || has_key("xllcenter")) {
+        if has_key("byteorder") {
+            // Esri float grid header (.hdr)
+            Some(RasterType::ArcBinary)
+        } else {
+            Some(RasterType::ArcAscii)
+        }
+    } else if has_key("north") && has_key("south") && has_key("rows") && has_key("cols") {
+        Some(RasterType::GrassAscii)
+    } else if is_xyz(text) {
+        Some(RasterType::XyzAscii)
+    } else {
+        None
+    }
+}
+
+/// Returns true if the text appears to be an XYZ grid, i.e. its first lines, after an
+/// optional header line, each hold three numbers.
+fn is_xyz(text: &str) -> bool {
+    let mut lines: Vec<&str> = text
+        .lines()
+        .map(|line| line.trim())
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .take(11)
+        .collect();
+    if lines.len() == 11 {
+        // The last line may have been cut off.
+        lines.pop();
+    }
+    let is_point = |line: &&str| {
+        let fields = split_xyz_line(line);
+        fields.len() == 3 && fields.iter().all(|f| f.parse::<f64>().is_ok())
+    };
+    match lines.split_first() {
+        Some((first, rest)) if is_point(first) => rest.iter().all(is_point),
+        Some((_, rest)) => !rest.is_empty() && rest.iter().all(is_point),
+        None => false,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::detect_raster_type;
+    use crate::get_raster_type_from_file;
+    use crate::test_utils::*;
+    use crate::*;
+    use std::fs;
+
+    /// Writes `contents` to a temporary file and returns the format that is detected.
+    fn detect(name: &str, contents: &[u8]) -> Option<RasterType> {
+        let file_name = temp_file(name);
+        fs::write(&file_name, contents).unwrap();
+        let raster_type = detect_raster_type(&file_name).unwrap();
+        fs::remove_file(&file_name).unwrap();
+        raster_type
+    }
+
+    #[test]
+    fn signatures() {
+        let mut gpkg = b"SQLite format 3\0".to_vec();
+        gpkg.resize(100, 0);
+        gpkg[68..72].copy_from_slice(b"GPKG");
+        let mut sqlite = gpkg.clone();
+        sqlite[68..72].copy_from_slice(b"\0\0\0\0");
+        for (signature, expected) in [
+            (&b"II*\0"[..], Some(RasterType::GeoTiff)),
+            (&b"MM\0*"[..], Some(RasterType::GeoTiff)),
+            (&b"II+\0"[..], Some(RasterType::GeoTiff)),
+            (&b"MM\0+"[..], Some(RasterType::GeoTiff)),
+            (&b"DSAA"[..], Some(RasterType::SurferAscii)),
+            (&b"DSRB"[..], Some(RasterType::Surfer7Binary)),
+            (&b"CDF\x01"[..], Some(RasterType::NetCdf)),
+            (&b"CDF\x02"[..], Some(RasterType::NetCdf)),
+            (&b"CDF\x05"[..], Some(RasterType::NetCdf)),
+            (&b"\x89HDF\r\n\x1a\n"[..], Some(RasterType::NetCdf)),
+            (&gpkg[..], Some(RasterType::GeoPackage)),
+            // An SQLite database that isn't a GeoPackage is left to the extension.
+            (&sqlite[..], None),
+        ] {
+            let mut contents = signature.to_vec();
+            contents.extend_from_slice(&[0u8; 64]);
+            assert_eq!(detect("signature.bin", &contents), expected, "{:?}", signature);
+        }
+    }
+
+    #[test]
+    fn text_headers() {
+        for (header, expected) in [
+            (
+                "ENVI\nsamples = 3\nlines = 2\nbands = 1\ndata type = 4\n",
+                RasterType::Envi,
+            ),
+            (
+                "Min:\t0\nMax:\t1\nNorth:\t10\nSouth:\t0\nEast:\t10\nWest:\t0\n\
+                 Cols:\t10\nRows:\t10\nData Type:\tFLOAT\n",
+                RasterType::Whitebox,
+            ),
+            (
+                "NAME\t= dem\nDATAFORMAT\t= FLOAT\nPOSITION_XMIN\t= 0\n\
+                 CELLSIZE\t= 1\nCELLCOUNT_X\t= 10\n",
+                RasterType::SagaBinary,
+            ),
+            (
+                "file format : IDRISI Raster A.1\nfile title  : \n\
+                 data type   : real\nfile type   : binary\n",
+                RasterType::IdrisiBinary,
+            ),
+            (
+                "BYTEORDER I\nLAYOUT BIL\nNROWS 10\nNCOLS 10\nNBANDS 1\nNBITS 32\nULXMAP 0.5\n",
+                RasterType::EsriBil,
+            ),
+            (
+                "ncols 10\nnrows 10\nxllcorner 0\nyllcorner 0\ncellsize 1\nbyteorder LSBFIRST\n",
+                RasterType::ArcBinary,
+            ),
+            (
+                "ncols 2\nnrows 2\nxllcenter 0.5\nyllcenter 0.5\ncellsize 1\n1 2\n3 4\n",
+                RasterType::ArcAscii,
+            ),
+            (
+                "north: 2\nsouth: 0\neast: 2\nwest: 0\nrows: 2\ncols: 2\n1 2\n3 4\n",
+                RasterType::GrassAscii,
+            ),
+            ("x,y,z\n0.5,1.5,3\n1.5,1.5,4\n0.5,0.5,5\n", RasterType::XyzAscii),
+            ("0.5 1.5 3\n1.5 1.5 4\n0.5 0.5 5\n", RasterType::XyzAscii),
+        ] {
+            assert_eq!(detect("header.txt", header.as_bytes()), Some(expected), "{}", header);
+        }
+        assert_eq!(detect("notes.txt", b"A note about a raster.\n"), None);
+    }
+
+    #[test]
+    fn raw_data_files_are_identified_by_their_header_files() {
+        let data_file = temp_file("raw.sdat");
+        let header_file = temp_file("raw.sgrd");
+        fs::write(&data_file, [0u8, 0, 128, 63, 0, 0, 0, 64]).unwrap();
+        fs::write(&header_file, "DATAFORMAT\t= FLOAT\nCELLSIZE\t= 1\n").unwrap();
+        assert_eq!(detect_raster_type(&data_file).unwrap(), Some(RasterType::SagaBinary));
+
+        // A header file of another format with the same base name makes the format ambiguous.
+        let other_header = temp_file("raw.rdc");
+        let idrisi_header = "file format : IDRISI Raster A.1\nfile type   : binary\n";
+        fs::write(&other_header, idrisi_header).unwrap();
+        assert_eq!(detect_raster_type(&data_file).unwrap(), None);
+        for file_name in [&data_file, &header_file, &other_header] {
+            fs::remove_file(file_name).unwrap();
+        }
+    }
+
+    #[test]
+    fn contents_decide_over_a_wrong_extension() {
+        // A GeoTIFF named as if it were an Arc ASCII grid.
+        let tiff_name = temp_file("misnamed.tif");
+        let file_name = temp_file("misnamed.asc");
+        let output = sample_raster(&tiff_name, 4, 5, DataType::F32);
+        output.clone().write().unwrap();
+        fs::rename(&tiff_name, &file_name).unwrap();
+        assert_eq!(get_raster_type_from_file(&file_name, "r").unwrap(), RasterType::GeoTiff);
+        let input = Raster::new(&file_name, "r").unwrap();
+        assert_eq!(input.raster_type, RasterType::GeoTiff);
+        assert_same_cells(&output, &input);
+        fs::remove_file(&file_name).unwrap();
+    }
+
+    #[test]
+    fn unknown_contents_fall_back_to_the_extension() {
+        let file_name = temp_file("unknown.flt");
+        fs::write(&file_name, [0u8, 0, 128, 63, 0, 0, 0, 64]).unwrap();
+        assert_eq!(detect_raster_type(&file_name).unwrap(), None);
+        assert_eq!(get_raster_type_from_file(&file_name, "r").unwrap(), RasterType::ArcBinary);
+        fs::remove_file(&file_name).unwrap();
+
+        // Files that don't exist yet are named by their extension alone.
+        let file_name = temp_file("new.dep");
+        assert_eq!(detect_raster_type(&file_name).unwrap(), None);
+        assert_eq!(get_raster_type_from_file(&file_name, "w").unwrap(), RasterType::Whitebox);
+
+        // Without an extension, unknown contents leave the format undetermined.
+        let file_name = temp_file("unknown");
+        fs::write(&file_name, [0u8, 0, 128, 63, 0, 0, 0, 64]).unwrap();
+        assert!(matches!(
+            get_raster_type_from_file(&file_name, "r"),
+            Err(RasterError::UnknownFormat { .. })
+        ));
+        fs::remove_file(&file_name).unwrap();
+    }
+}
@@ -0,0 +1,630 @@
+use super::geotiff::read_tiff_from_bytes;
+use super::*;
+use rusqlite::{Connection, OpenFlags, OptionalExtension};
+use whitebox_common::spatial_ref_system::esri_wkt_from_epsg;
+use std::f64;
+use std::io::{Cursor, Error, ErrorKind};
+
+/*
+GeoPackage files (http://www.geopackage.org/spec/) are SQLite databases. A raster is held
+in a tile pyramid user data table, whose tiles are PNG, JPEG, or, for the floating-point
+data of the 2D gridded coverage extension, TIFF images. Each zoom level of the pyramid is
+a matrix of tiles, described by the gpkg_tile_matrix table, that covers the bounding box
+given in the gpkg_tile_matrix_set table. The tiles of gridded coverages hold integer or
+floating-point values, which are scaled using the gpkg_2d_gridded_coverage_ancillary and
+gpkg_2d_gridded_tile_ancillary tables; those of other tile tables hold imagery.
+*/
+
+/// Splits a GeoPackage file name of the form `file.gpkg:table` into the name of the file
+/// and that of the table. Other file names are returned unchanged.
+pub(crate) fn split_geopackage_file_name(file_name: &str) -> (&str, Option<&str>) {
+    match file_name.to_ascii_lowercase().rfind(".gpkg:") {
+        Some(i) if i + 6 < file_name.len() => (&file_name[..i + 5], Some(&file_name[i + 6..])),
+        Some(i) => (&file_name[..i + 5], None),
+        None => (file_name, None),
+    }
+}
+
+/// The scaling of the values of a gridded coverage.
+struct GriddedCoverage {
+    is_float: bool,
+    scale: f64,
+    offset: f64,
+    data_null: Option<f64>,
+    grid_cell_encoding: String,
+    uom: Option<String>,
+}
+
+/// Reads the tiles of the most detailed zoom level of a GeoPackage tile pyramid or 2D
+/// gridded coverage. The table is named in the file name, e.g. `file.gpkg:dem`; otherwise
+/// the first raster table of the file is read. Gridded coverages are read as continuous
+/// data, with the scale factors and offsets of the coverage and of each tile applied, and
+/// imagery tiles as RGBA colour composites. Missing tiles are NoData.
+pub fn read_geopackage<'a>(
+    file_name: &'a String,
+    configs: &'a mut RasterConfigs,
+    data: &'a mut Vec<f64>,
+) -> Result<(), Error> {
+    let (gpkg_file, table_name) = split_geopackage_file_name(file_name);
+    let conn = Connection::open_with_flags(gpkg_file, OpenFlags::SQLITE_OPEN_READ_ONLY)
+        .map_err(sql_error)?;
+
+    // Find the raster table within the contents of the GeoPackage.
+    let mut tables: Vec<(String, String, Option<String>, [Option<f64>; 4])> = vec![];
+    {
+        let mut stmt = conn
+            .prepare(
+                "SELECT table_name, data_type, identifier, min_x, min_y, max_x, max_y FROM gpkg_contents \
+                 WHERE data_type IN ('tiles', '2d-gridded-coverage') ORDER BY rowid",
+            )
+            .map_err(sql_error)?;
+        let mut rows = stmt.query([]).map_err(sql_error)?;
+        while let Some(row) = rows.next().map_err(sql_error)? {
+            tables.push((
+                row.get(0).map_err(sql_error)?,
+                row.get(1).map_err(sql_error)?,
+                row.get(2).map_err(sql_error)?,
+                [
+                    row.get(3).map_err(sql_error)?,
+                    row.get(4).map_err(sql_error)?,
+                    row.get(5).map_err(sql_error)?,
+                    row.get(6).map_err(sql_error)?,
+                ],
+            ));
+        }
+    }
+    if tables.is_empty() {
+        return Err(Error::new(
+            ErrorKind::InvalidData,
+            format!("The GeoPackage {} does not contain any raster tables.", gpkg_file),
+        ));
+    }
+    let index = match table_name {
+        Some(name) => tables.iter().position(|t| t.0 == name).ok_or_else(|| {
+            Error::new(
+                ErrorKind::NotFound,
+                format!(
+                    "The GeoPackage does not contain a raster table named '{}'. Its raster tables are: {}",
+                    name,
+                    tables.iter().map(|t| t.0.as_str()).collect::<Vec<&str>>().join(", ")
+                ),
+            )
+        })?,
+        None => {
+            if tables.len() > 1 {
+                println!(
+                    "Warning: The GeoPackage contains {} raster tables and only the first, '{}', is read. Use a file name of the form file.gpkg:table to select another.",
+                    tables.len(),
+                    tables[0].0
+                );
+            }
+            0
+        }
+    };
+    let (table, data_type, identifier, contents_bounds) = tables.swap_remove(index);
+    let quoted_table = format!("\"{}\"", table.replace('"', "\"\""));
+
+    let (srs_id, tms_min_x, tms_max_y): (i64, f64, f64) = conn
+        .query_row(
+            "SELECT srs_id, min_x, max_y FROM gpkg_tile_matrix_set WHERE table_name = ?1",
+            [&table],
+            |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
+        )
+        .map_err(sql_error)?;
+
+    // The most detailed zoom level that has tiles.
+    let zoom_level: i64 = conn
+        .query_row(&format!("SELECT MAX(zoom_level) FROM {}", quoted_table), [], |row| {
+            row.get::<_, Option<i64>>(0)
+        })
+        .map_err(sql_error)?
+        .ok_or_else(|| {
+            Error::new(
+                ErrorKind::InvalidData,
+                format!("The GeoPackage table '{}' does not contain any tiles.", table),
+            )
+        })?;
+    let (matrix_width, matrix_height, tile_width, tile_height, pixel_x_size, pixel_y_size): (
+        i64,
+        i64,
+        i64,
+        i64,
+        f64,
+        f64,
+    ) = conn
+        .query_row(
+            "SELECT matrix_width, matrix_height, tile_width, tile_height, pixel_x_size, pixel_y_size \
+             FROM gpkg_tile_matrix WHERE table_name = ?1 AND zoom_level = ?2",
+            rusqlite::params![&table, zoom_level],
+            |row| {
+                Ok((
+                    row.get(0)?,
+                    row.get(1)?,
+                    row.get(2)?,
+                    row.get(3)?,
+                    row.get(4)?,
+                    row.get(5)?,
+                ))
+            },
+        )
+        .map_err(sql_error)?;
+    let tile_width = tile_width as usize;
+    let tile_height = tile_height as usize;
+
+    let coverage = if data_type == "2d-gridded-coverage" {
+        Some(read_gridded_coverage(&conn, &table)?)
+    } else {
+        None
+    };
+
+    // The raster is the part of the tile matrix that lies within the bounds of the
+    // contents, which may be smaller than the tile matrix set.
+    let full_columns = matrix_width as usize * tile_width;
+    let full_rows = matrix_height as usize * tile_height;
+    let (mut first_col, mut last_col, mut first_row, mut last_row) = (0, full_columns, 0, full_rows);
+    if let [Some(min_x), Some(min_y), Some(max_x), Some(max_y)] = contents_bounds {
+        let c0 = ((min_x - tms_min_x) / pixel_x_size).round().max(0f64) as usize;
+        let c1 = (((max_x - tms_min_x) / pixel_x_size).round().max(0f64) as usize).min(full_columns);
+        let r0 = ((tms_max_y - max_y) / pixel_y_size).round().max(0f64) as usize;
+        let r1 = (((tms_max_y - min_y) / pixel_y_size).round().max(0f64) as usize).min(full_rows);
+        if c1 > c0 && r1 > r0 {
+            first_col = c0;
+            last_col = c1;
+            first_row = r0;
+            last_row = r1;
+        }
+    }
+    let columns = last_col - first_col;
+    let rows = last_row - first_row;
+
+    configs.title = identifier.unwrap_or_else(|| table.clone());
+    configs.rows = rows;
+    configs.columns = columns;
+    configs.bands = 1;
+    configs.resolution_x = pixel_x_size;
+    configs.resolution_y = pixel_y_size;
+    configs.west = tms_min_x + first_col as f64 * pixel_x_size;
+    configs.north = tms_max_y - first_row as f64 * pixel_y_size;
+    if let Some(coverage) = &coverage {
+        // Values at the corners of the grid cells are centred on the raster's cells.
+        if coverage.grid_cell_encoding == "grid-value-is-corner" {
+            configs.west -= pixel_x_size / 2f64;
+            configs.north += pixel_y_size / 2f64;
+        }
+    }
+    configs.east = configs.west + columns as f64 * pixel_x_size;
+    configs.south = configs.north - rows as f64 * pixel_y_size;
+    configs.pixel_is_area = true;
+
+    match &coverage {
+        Some(coverage) => {
+            configs.photometric_interp = PhotometricInterpretation::Continuous;
+            configs.data_type = if coverage.is_float {
+                DataType::F32
+            } else if coverage.scale == 1f64 && coverage.offset == 0f64 {
+                DataType::I32
+            } else {
+                DataType::F64
+            };
+            configs.nodata = match coverage.data_null {
+                Some(v) if coverage.is_float => v,
+                _ => -32768f64,
+            };
+            if let Some(uom) = &coverage.uom {
+                configs.z_units = uom.clone();
+            }
+        }
+        None => {
+            configs.photometric_interp = PhotometricInterpretation::RGB;
+            configs.data_type = DataType::RGBA32;
+            configs.nodata = 0f64;
+        }
+    }
+
+    // The coordinate reference system.
+    let srs: Option<(String, i64, String)> = conn
+        .query_row(
+            "SELECT organization, organization_coordsys_id, definition FROM gpkg_spatial_ref_sys WHERE srs_id = ?1",
+            [srs_id],
+            |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
+        )
+        .optional()
+        .map_err(sql_error)?;
+    if let Some((organization, code, definition)) = srs {
+        let is_epsg = organization.eq_ignore_ascii_case("epsg") && code > 0 && code <= u16::MAX as i64;
+        if is_epsg {
+            configs.epsg_code = code as u16;
+        }
+        let definition = definition.trim();
+        let wkt = if !definition.is_empty() && definition != "undefined" {
+            definition.to_string()
+        } else if is_epsg {
+            esri_wkt_from_epsg(code as u16)
+        } else {
+            String::new()
+        };
+        if !wkt.is_empty() && wkt != "Unknown EPSG Code" {
+            configs.projection = wkt.clone();
+            configs.coordinate_ref_system_wkt = wkt;
+            if CoordinateReferenceSystem::from_wkt_or_epsg(&configs.coordinate_ref_system_wkt, 0)
+                .map_or(false, |crs| crs.is_geographic())
+            {
+                configs.xy_units = "degrees".to_string();
+            }
+        }
+    }
+
+    // The scale factors and offsets of the tiles of a gridded coverage.
+    let mut tile_scaling: HashMap<i64, (f64, f64)> = HashMap::new();
+    if coverage.is_some() {
+        let mut stmt = conn
+            .prepare("SELECT tpudt_id, scale, offset FROM gpkg_2d_gridded_tile_ancillary WHERE tpudt_name = ?1")
+            .map_err(sql_error)?;
+        let mut ancillary = stmt.query([&table]).map_err(sql_error)?;
+        while let Some(row) = ancillary.next().map_err(sql_error)? {
+            let scale: Option<f64> = row.get(1).map_err(sql_error)?;
+            let offset: Option<f64> = row.get(2).map_err(sql_error)?;
+            tile_scaling.insert(
+                row.get(0).map_err(sql_error)?,
+                (scale.unwrap_or(1f64), offset.unwrap_or(0f64)),
+            );
+        }
+    }
+
+    // Read the tiles, copying the part of each that lies within the raster.
+    let nodata = configs.nodata;
+    data.clear();
+    data.resize(rows * columns, nodata);
+    let mut stmt = conn
+        .prepare(&format!(
+            "SELECT id, tile_column, tile_row, tile_data FROM {} WHERE zoom_level = ?1",
+            quoted_table
+        ))
+        .map_err(sql_error)?;
+    let mut tiles = stmt.query([zoom_level]).map_err(sql_error)?;
+    while let Some(row) = tiles.next().map_err(sql_error)? {
+        let id: i64 = row.get(0).map_err(sql_error)?;
+        let tile_column: i64 = row.get(1).map_err(sql_error)?;
+        let tile_row: i64 = row.get(2).map_err(sql_error)?;
+        let tile_data: Vec<u8> = row.get(3).map_err(sql_error)?;
+        if tile_column < 0 || tile_row < 0 || tile_column >= matrix_width || tile_row >= matrix_height {
+            continue;
+        }
+        let x0 = tile_column as usize * tile_width;
+        let y0 = tile_row as usize * tile_height;
+        if x0 >= last_col || x0 + tile_width <= first_col || y0 >= last_row || y0 + tile_height <= first_row {
+            continue;
+        }
+        let tile = decode_tile(tile_data, coverage.is_some(), tile_width, tile_height)?;
+        let (tile_scale, tile_offset) = tile_scaling.get(&id).copied().unwrap_or((1f64, 0f64));
+        for ty in 0..tile_height {
+            let y = y0 + ty;
+            if y < first_row || y >= last_row {
+                continue;
+            }
+            for tx in 0..tile_width {
+                let x = x0 + tx;
+                if x < first_col || x >= last_col {
+                    continue;
+                }
+                let value = tile[ty * tile_width + tx];
+                data[(y - first_row) * columns + x - first_col] = match &coverage {
+                    Some(c) => {
+                        if value.is_nan() || Some(value) == c.data_null {
+                            nodata
+                        } else {
+                            (value * tile_scale + tile_offset) * c.scale + c.offset
+                        }
+                    }
+                    None => value,
+                };
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// GeoPackage rasters are read-only.
+pub fn write_geopackage<'a>(_r: &'a mut Raster) -> Result<(), Error> {
+    Err(Error::new(
+        ErrorKind::Other,
+        "Writing GeoPackage rasters is not supported. Use a GeoTiff format instead.",
+    ))
+}
+
+fn read_gridded_coverage(conn: &Connection, table: &str) -> Result<GriddedCoverage, Error> {
+    conn.query_row(
+        "SELECT datatype, scale, offset, data_null, grid_cell_encoding, uom \
+         FROM gpkg_2d_gridded_coverage_ancillary WHERE tile_matrix_set_name = ?1",
+        [table],
+        |row| {
+            let datatype: String = row.get(0)?;
+            let scale: Option<f64> = row.get(1)?;
+            let offset: Option<f64> = row.get(2)?;
+            let encoding: Option<String> = row.get(4)?;
+            Ok(GriddedCoverage {
+                is_float: datatype == "float",
+                scale: scale.unwrap_or(1f64),
+                offset: offset.unwrap_or(0f64),
+                data_null: row.get(3)?,
+                grid_cell_encoding: encoding.unwrap_or_else(|| "grid-value-is-center".to_string()),
+                uom: row.get(5)?,
+            })
+        },
+    )
+    .map_err(sql_error)
+}
+
+/// Decodes a PNG, JPEG, or TIFF tile into `tile_width * tile_height` values. The samples of
+/// gridded coverage tiles are returned as they are, and imagery is packed into RGBA values.
+fn decode_tile(
+    tile_data: Vec<u8>,
+    is_coverage: bool,
+    tile_width: usize,
+    tile_height: usize,
+) -> Result<Vec<f64>, Error> {
+    let (width, height, samples, values) = if tile_data.starts_with(b"\x89PNG") {
+        decode_png(&tile_data, is_coverage)?
+    } else if tile_data.starts_with(&[0xFF, 0xD8]) {
+        let mut decoder = jpeg_decoder::Decoder::new(Cursor::new(tile_data));
+        let pixels = decoder.decode().map_err(|e| {
+            Error::new(
+                ErrorKind::InvalidData,
+                format!("Error decoding a JPEG tile of the GeoPackage: {}", e),
+            )
+        })?;
+        let info = decoder
+            .info()
+            .ok_or_else(|| Error::new(ErrorKind::InvalidData, "Error decoding a JPEG tile of the GeoPackage."))?;
+        let samples = match info.pixel_format {
+            jpeg_decoder::PixelFormat::L8 => 1,
+            jpeg_decoder::PixelFormat::RGB24 => 3,
+            _ => {
+                return Err(Error::new(
+                    ErrorKind::InvalidData,
+                    "Only greyscale and RGB JPEG tiles are supported.",
+                ))
+            }
+        };
+        let values = pixels.iter().map(|&v| v as f64).collect();
+        (info.width as usize, info.height as usize, samples, values)
+    } else if tile_data.starts_with(b"II*\0") || tile_data.starts_with(b"MM\0*") {
+        let mut configs = RasterConfigs {
+            ..Default::default()
+        };
+        let mut values = vec![];
+        read_tiff_from_bytes(tile_data, &mut configs, &mut values)?;
+        if configs.bands != 1 {
+            return Err(Error::new(
+                ErrorKind::InvalidData,
+                "The TIFF tiles of a GeoPackage gridded coverage must have a single band.",
+            ));
+        }
+        (configs.columns, configs.rows, 1, values)
+    } else {
+        return Err(Error::new(
+            ErrorKind::InvalidData,
+            "The GeoPackage contains tiles of an unsupported image format. Only PNG, JPEG, and TIFF tiles can be read.",
+        ));
+    };
+    if width < tile_width || height < tile_height {
+        return Err(Error::new(
+            ErrorKind::InvalidData,
+            "A tile of the GeoPackage is smaller than the tile size of its tile matrix.",
+        ));
+    }
+
+    let mut tile = Vec::with_capacity(tile_width * tile_height);
+    for y in 0..tile_height {
+        for x in 0..tile_width {
+            let i = (y * width + x) * samples;
+            if is_coverage {
+                tile.push(values[i]);
+            } else {
+                let (r, g, b, a) = match samples {
+                    1 => (values[i], values[i], values[i], 255f64),
+                    2 => (values[i], values[i], values[i], values[i + 1]),
+                    3 => (values[i], values[i + 1], values[i + 2], 255f64),
+                    _ => (values[i], values[i + 1], values[i + 2], values[i + 3]),
+                };
+                let value = ((a as u32) << 24) | ((b as u32) << 16) | ((g as u32) << 8) | r as u32;
+                tile.push(value as f64);
+            }
+        }
+    }
+    Ok(tile)
+}
+
+/// Decodes a PNG image, returning its width, height, number of samples per pixel, and
+/// samples. Palettes are expanded into RGB(A) samples. The 16-bit samples of gridded
+/// coverages are kept as they are, while those of imagery are reduced to 8 bits.
+fn decode_png(bytes: &[u8], is_coverage: bool) -> Result<(usize, usize, usize, Vec<f64>), Error> {
+    let png_error = |e: png::DecodingError| {
+        Error::new(
+            ErrorKind::InvalidData,
+            format!("Error decoding a PNG tile of the GeoPackage: {}", e),
+        )
+    };
+    let mut decoder = png::Decoder::new(Cursor::new(bytes));
+    decoder.set_transformations(png::Transformations::EXPAND);
+    let mut reader = decoder.read_info().map_err(png_error)?;
+    let mut buf = vec![0u8; reader.output_buffer_size()];
+    let info = reader.next_frame(&mut buf).map_err(png_error)?;
+    let samples = info.color_type.samples();
+    let width = info.width as usize;
+    let height = info.height as usize;
+    let mut values = Vec::with_capacity(width * height * samples);
+    for row in buf.chunks(info.line_size).take(height) {
+        if info.bit_depth == png::BitDepth::Sixteen {
+            values.extend(
+                row.chunks_exact(2)
+                    .take(width * samples)
+                    .map(|b| {
+                        let v = u16::from_be_bytes([b[0], b[1]]);
+                        if is_coverage {
+                            v as f64
+                        } else {
+                            (v >> 8) as f64
+                        }
+                    }),
+            );
+        } else {
+            values.extend(row.iter().take(width * samples).map(|&v| v as f64));
+        }
+    }
+    Ok((width, height, samples, values))
+}
+
+fn sql_error(e: rusqlite::Error) -> Error {
+    Error::new(
+        ErrorKind::InvalidData,
+        format!("Error reading the GeoPackage: {}", e),
+    )
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn png_tile(
+        width: u32,
+        height: u32,
+        color_type: png::ColorType,
+        depth: png::BitDepth,
+        data: &[u8],
+    ) -> Vec<u8> {
+        let mut bytes = vec![];
+        {
+            let mut encoder = png::Encoder::new(&mut bytes, width, height);
+            encoder.set_color(color_type);
+            encoder.set_depth(depth);
+            let mut writer = encoder.write_header().unwrap();
+            writer.write_image_data(data).unwrap();
+        }
+        bytes
+    }
+
+    /// Creates a GeoPackage with a 16-bit gridded coverage, 'dem', whose most detailed zoom
+    /// level is a 2 x 1 matrix of 3 x 2 tiles, and an RGB tile table, 'photo'.
+    fn create_geopackage(file_name: &str) {
+        let conn = Connection::open(file_name).unwrap();
+        conn.execute_batch(
+            "CREATE TABLE gpkg_spatial_ref_sys (srs_name TEXT, srs_id INTEGER PRIMARY KEY, organization TEXT, \
+                 organization_coordsys_id INTEGER, definition TEXT);
+             INSERT INTO gpkg_spatial_ref_sys VALUES ('WGS 84', 4326, 'EPSG', 4326, 'undefined');
+             CREATE TABLE gpkg_contents (table_name TEXT PRIMARY KEY, data_type TEXT, identifier TEXT, \
+                 min_x DOUBLE, min_y DOUBLE, max_x DOUBLE, max_y DOUBLE, srs_id INTEGER);
+             INSERT INTO gpkg_contents VALUES ('dem', '2d-gridded-coverage', 'Elevation', 1, 0, 6, 2, 4326);
+             INSERT INTO gpkg_contents VALUES ('photo', 'tiles', NULL, NULL, NULL, NULL, NULL, 4326);
+             CREATE TABLE gpkg_tile_matrix_set (table_name TEXT PRIMARY KEY, srs_id INTEGER, \
+                 min_x DOUBLE, min_y DOUBLE, max_x DOUBLE, max_y DOUBLE);
+             INSERT INTO gpkg_tile_matrix_set VALUES ('dem', 4326, 0, 0, 6, 2);
+             INSERT INTO gpkg_tile_matrix_set VALUES ('photo', 4326, 10, 20, 12, 22);
+             CREATE TABLE gpkg_tile_matrix (table_name TEXT, zoom_level INTEGER, matrix_width INTEGER, \
+                 matrix_height INTEGER, tile_width INTEGER, tile_height INTEGER, pixel_x_size DOUBLE, pixel_y_size DOUBLE);
+             INSERT INTO gpkg_tile_matrix VALUES ('dem', 0, 1, 1, 3, 1, 2, 2);
+             INSERT INTO gpkg_tile_matrix VALUES ('dem', 1, 2, 1, 3, 2, 1, 1);
+             INSERT INTO gpkg_tile_matrix VALUES ('photo', 0, 1, 1, 2, 2, 1, 1);
+             CREATE TABLE gpkg_2d_gridded_coverage_ancillary (tile_matrix_set_name TEXT, datatype TEXT, \
+                 scale REAL, offset REAL, data_null REAL, grid_cell_encoding TEXT, uom TEXT);
+             INSERT INTO gpkg_2d_gridded_coverage_ancillary VALUES ('dem', 'integer', 0.5, 100, 65535, 'grid-value-is-area', 'm');
+             CREATE TABLE gpkg_2d_gridded_tile_ancillary (tpudt_name TEXT, tpudt_id INTEGER, scale REAL, offset REAL);
+             CREATE TABLE dem (id INTEGER PRIMARY KEY AUTOINCREMENT, zoom_level INTEGER, tile_column INTEGER, \
+                 tile_row INTEGER, tile_data BLOB);
+             CREATE TABLE photo (id INTEGER PRIMARY KEY AUTOINCREMENT, zoom_level INTEGER, tile_column INTEGER, \
+                 tile_row INTEGER, tile_data BLOB);",
+        )
+        .unwrap();
+
+        // The less detailed zoom level is not read, so its tile need not be an image.
+        conn.execute(
+            "INSERT INTO dem (zoom_level, tile_column, tile_row, tile_data) VALUES (0, 0, 0, x'00')",
+            [],
+        )
+        .unwrap();
+        for tile_column in 0..2 {
+            let mut data = vec![];
+            for y in 0..2 {
+                for x in 0..3 {
+                    let v: u16 = if (y, tile_column * 3 + x) == (1, 5) {
+                        65535
+                    } else {
+                        10 * y + tile_column * 3 + x
+                    };
+                    data.extend_from_slice(&v.to_be_bytes());
+                }
+            }
+            let tile = png_tile(3, 2, png::ColorType::Grayscale, png::BitDepth::Sixteen, &data);
+            conn.execute(
+                "INSERT INTO dem (zoom_level, tile_column, tile_row, tile_data) VALUES (1, ?1, 0, ?2)",
+                rusqlite::params![tile_column, tile],
+            )
+            .unwrap();
+            let id = conn.last_insert_rowid();
+            if tile_column == 1 {
+                conn.execute(
+                    "INSERT INTO gpkg_2d_gridded_tile_ancillary VALUES ('dem', ?1, 2, 1)",
+                    [id],
+                )
+                .unwrap();
+            }
+        }
+
+        let data = [0u8, 0, 7, 100, 0, 7, 0, 100, 7, 100, 100, 7];
+        let tile = png_tile(2, 2, png::ColorType::Rgb, png::BitDepth::Eight, &data);
+        conn.execute(
+            "INSERT INTO photo (zoom_level, tile_column, tile_row, tile_data) VALUES (0, 0, 0, ?1)",
+            [tile],
+        )
+        .unwrap();
+    }
+
+    #[test]
+    fn read_tile_matrix() {
+        let file_name = std::env::temp_dir()
+            .join(format!("wbt_tiles_{}.gpkg", std::process::id()))
+            .to_string_lossy()
+            .to_string();
+        let _ = std::fs::remove_file(&file_name);
+        create_geopackage(&file_name);
+
+        // The coverage is cropped to the bounds of its contents, and its values are scaled.
+        let dem = Raster::new(&format!("{}:dem", file_name), "r").unwrap();
+        assert_eq!((dem.configs.rows, dem.configs.columns), (2, 5));
+        assert_eq!((dem.configs.west, dem.configs.north), (1.0, 2.0));
+        assert_eq!((dem.configs.east, dem.configs.south), (6.0, 0.0));
+        assert_eq!(dem.configs.title, "Elevation");
+        assert_eq!(dem.configs.data_type, DataType::F64);
+        assert_eq!(dem.configs.epsg_code, 4326);
+        assert_eq!(dem.configs.z_units, "m");
+        for row in 0..2isize {
+            for col in 0..5isize {
+                let x = col + 1;
+                let v = (10 * row + x) as f64;
+                let expected = if (row, x) == (1, 5) {
+                    dem.configs.nodata
+                } else if x < 3 {
+                    v * 0.5 + 100.0
+                } else {
+                    (v * 2.0 + 1.0) * 0.5 + 100.0
+                };
+                assert_eq!(dem.get_value(row, col), expected);
+            }
+        }
+
+        // Imagery tiles are read as RGBA values.
+        let photo = Raster::new(&format!("{}:photo", file_name), "r").unwrap();
+        assert_eq!((photo.configs.rows, photo.configs.columns), (2, 2));
+        assert_eq!(photo.configs.data_type, DataType::RGBA32);
+        assert_eq!((photo.configs.west, photo.configs.north), (10.0, 22.0));
+        for row in 0..2isize {
+            for col in 0..2isize {
+                let (r, g) = (col as u32 * 100, row as u32 * 100);
+                let expected = (255u32 << 24) | (7 << 16) | (g << 8) | r;
+                assert_eq!(photo.get_value(row, col), expected as f64);
+            }
+        }
+        std::fs::remove_file(&file_name).unwrap();
+    }
+}
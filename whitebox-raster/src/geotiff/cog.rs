@@ -0,0 +1,599 @@
+/*
+This code is part of the WhiteboxTools geospatial analysis library.
+License: MIT
+*/
+
+// Writes Cloud-Optimized GeoTIFFs (COGs). A COG is a tiled GeoTIFF that stores
+// reduced-resolution copies of its image (overviews) internally, and which is laid
+// out so that a client can find any tile of any level after reading only the start
+// of the file. Following the layout used by GDAL, the file contains:
+//
+// 1. the TIFF header;
+// 2. a 'ghost' area of ASCII text that describes the layout to readers;
+// 3. the IFDs of the full-resolution image and then of each overview, from largest
+//    to smallest, each followed by its larger tag values;
+// 4. the tiles of each overview, from smallest to largest, and then those of the
+//    full-resolution image, each in row-major order.
+
+use super::*;
+use std::io::Seek;
+
+/// The methods used to resample the full-resolution image to build overviews.
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Resampling {
+    /// The value of the cell nearest the centre of each block of cells.
+    Nearest,
+    /// The mean of the valid cells in each block; colour channels are averaged separately.
+    Average,
+    /// The most frequent valid value in each block.
+    Mode,
+}
+
+impl Resampling {
+    fn from_str(s: &str) -> Result<Resampling, Error> {
+        match s.trim().to_lowercase().as_str() {
+            "nearest" | "nn" => Ok(Resampling::Nearest),
+            "average" | "mean" => Ok(Resampling::Average),
+            "mode" => Ok(Resampling::Mode),
+            _ => Err(Error::new(
+                ErrorKind::InvalidInput,
+                format!(
+                    "Unrecognized COG overview resampling method '{}'. Use 'nearest', 'average', or 'mode'.",
+                    s
+                ),
+            )),
+        }
+    }
+}
+
+/// The arrangement of the tiles of the images of a COG.
+struct TileLayout<'a> {
+    tile_size: usize,
+    /// The number of samples per pixel, i.e. the bands, or 1 for packed RGB(A) values.
+    bands: usize,
+    /// Whether the bands are stored one after another, rather than interleaved by pixel.
+    planar: bool,
+    encoding: &'a BlockEncoding,
+    is_big_tiff: bool,
+}
+
+/// One of the images stored within a COG, i.e. either the full-resolution image or
+/// one of its overviews.
+struct CogImage {
+    columns: usize,
+    rows: usize,
+    /// The values of an overview, band after band, or none for the full-resolution
+    /// image, whose values are read from the raster.
+    values: Option<Vec<f64>>,
+    tile_offsets: Vec<u64>,
+    tile_byte_counts: Vec<u64>,
+}
+
+impl CogImage {
+    fn new(columns: usize, rows: usize, values: Option<Vec<f64>>) -> CogImage {
+        CogImage {
+            columns,
+            rows,
+            values,
+            tile_offsets: vec![],
+            tile_byte_counts: vec![],
+        }
+    }
+
+    #[inline]
+    fn get_value(&self, r: &Raster, band: usize, row: usize, col: usize) -> f64 {
+        let idx = (band * self.rows + row) * self.columns + col;
+        match &self.values {
+            Some(values) => values[idx],
+            None => r.get_cell(idx),
+        }
+    }
+}
+
+/// Writes a raster as a Cloud-Optimized GeoTIFF, with square tiles of `tile_size`
+/// pixels and `overview_levels` overviews, each half the size of the last. If
+/// `overview_levels` is negative, overviews are added until the image fits within
+/// a single tile.
+pub(super) fn write_cog(
+    r: &mut Raster,
+    encoding: &BlockEncoding,
+    tile_size: usize,
+    overview_levels: isize,
+    resampling: &str,
+) -> Result<(), Error> {
+    if tile_size == 0 || !tile_size.is_multiple_of(16) {
+        return Err(Error::new(
+            ErrorKind::InvalidInput,
+            "The COG tile size must be a positive multiple of 16.",
+        ));
+    }
+    let resampling = Resampling::from_str(resampling)?;
+
+    let bytes_per_sample = r.configs.data_type.get_data_size();
+    if bytes_per_sample == 0 {
+        return Err(Error::new(
+            ErrorKind::InvalidData,
+            format!(
+                "Unknown data type: {:?}. Photomet interp: {:?}",
+                r.configs.data_type, r.configs.photometric_interp
+            ),
+        ));
+    }
+
+    // Categorical rasters are written as paletted images if their colour table can be
+    // stored as a ColorMap, and otherwise as greyscale images.
+    if (r.configs.photometric_interp == PhotometricInterpretation::Categorical
+        || r.configs.photometric_interp == PhotometricInterpretation::Paletted)
+        && !has_colour_map(r)
+    {
+        r.configs.photometric_interp = PhotometricInterpretation::Continuous;
+    }
+
+    // As with strip-based GeoTIFFs, multi-band rasters are written with one sample per
+    // band and colour images as a single set of packed RGB(A) samples.
+    let bands = if r.configs.photometric_interp == PhotometricInterpretation::RGB {
+        1
+    } else {
+        r.configs.bands.max(1)
+    };
+    let planar = bands > 1 && r.configs.interleave != BandInterleave::BIP;
+
+    /////////////////////////
+    // Build the overviews //
+    /////////////////////////
+    let mut images = vec![CogImage::new(r.configs.columns, r.configs.rows, None)];
+    let mut level = 1;
+    loop {
+        let (columns, rows) = (images[level - 1].columns, images[level - 1].rows);
+        let add_level = if overview_levels < 0 {
+            columns > tile_size || rows > tile_size
+        } else {
+            level as isize <= overview_levels && (columns > 1 || rows > 1)
+        };
+        if !add_level {
+            break;
+        }
+        let values = build_overview(r, bands, level, resampling);
+        images.push(CogImage::new(
+            (r.configs.columns + (1 << level) - 1) >> level,
+            (r.configs.rows + (1 << level) - 1) >> level,
+            Some(values),
+        ));
+        level += 1;
+    }
+
+    // is it a BigTiff?
+    let tile_bytes = (tile_size * tile_size * bands * bytes_per_sample) as u64;
+    let mut data_size = 0u64;
+    for image in &images {
+        data_size += num_tiles(image, tile_size, bands, planar) as u64 * tile_bytes;
+    }
+    let is_big_tiff = data_size >= 4_000_000_000;
+    let header_size = if !is_big_tiff { 8u64 } else { 16u64 };
+    let layout = TileLayout {
+        tile_size,
+        bands,
+        planar,
+        encoding,
+        is_big_tiff,
+    };
+
+    //////////////////////
+    // Write the header //
+    //////////////////////
+    let f = File::create(r.file_name.clone())?;
+    let mut writer = BufWriter::new(f);
+
+    let ghost_area = get_ghost_area();
+    let first_ifd = header_size + ghost_area.len() as u64;
+    if r.configs.endian == Endianness::LittleEndian {
+        write_bytes(&mut writer, "II".as_bytes())?;
+    } else {
+        write_bytes(&mut writer, "MM".as_bytes())?;
+    }
+    if !is_big_tiff {
+        write_u16(&mut writer, r.configs.endian, 42u16)?; // magic number
+        write_u32(&mut writer, r.configs.endian, first_ifd as u32)?; // offset to first IFD
+    } else {
+        write_u16(&mut writer, r.configs.endian, 43u16)?; // magic number
+        write_u16(&mut writer, r.configs.endian, 8u16)?; // Bytesize of offsets
+        write_u16(&mut writer, r.configs.endian, 0u16)?; // Always 0
+        write_u64(&mut writer, r.configs.endian, first_ifd)?; // offset to first IFD
+    }
+    write_bytes(&mut writer, &ghost_area)?;
+
+    ////////////////////////////////////////////////////////////////////////////
+    // Write the IFDs, with placeholders for the tile offsets and byte counts //
+    ////////////////////////////////////////////////////////////////////////////
+    for image in images.iter_mut() {
+        let n = num_tiles(image, tile_size, bands, planar);
+        image.tile_offsets = vec![0u64; n];
+        image.tile_byte_counts = vec![0u64; n];
+    }
+    let data_start = write_ifds(&mut writer, r, &images, &layout, first_ifd)?;
+
+    ////////////////////////////////////////////////////////////////
+    // Write the tiles, from the smallest overview to the largest //
+    ////////////////////////////////////////////////////////////////
+    let mut current_offset = data_start;
+    for image in images.iter_mut().rev() {
+        write_tiles(&mut writer, r, image, &layout, &mut current_offset)?;
+    }
+
+    /////////////////////////////////////////////////////
+    // Rewrite the IFDs with the tiles' actual offsets //
+    /////////////////////////////////////////////////////
+    writer.seek(SeekFrom::Start(first_ifd))?;
+    write_ifds(&mut writer, r, &images, &layout, first_ifd)?;
+    writer.flush()?;
+
+    Ok(())
+}
+
+/// Returns the text of the 'ghost' area that GDAL places after the TIFF header to
+/// tell readers that the file is laid out as a COG.
+fn get_ghost_area() -> Vec<u8> {
+    let mut layout =
+        "LAYOUT=IFDS_BEFORE_DATA\nBLOCK_ORDER=ROW_MAJOR\nKNOWN_INCOMPATIBLE_EDITION=NO\n".to_string();
+    let mut ghost_area = format!("GDAL_STRUCTURAL_METADATA_SIZE={:06} bytes\n{}", layout.len(), layout);
+    if ghost_area.len() % 2 != 0 {
+        // Keeps the IFDs that follow on a word boundary.
+        layout.push(' ');
+        ghost_area = format!("GDAL_STRUCTURAL_METADATA_SIZE={:06} bytes\n{}", layout.len(), layout);
+    }
+    ghost_area.into_bytes()
+}
+
+/// Returns the number of tiles of an image, counting those of each band separately
+/// if the bands are stored one after another.
+fn num_tiles(image: &CogImage, tile_size: usize, bands: usize, planar: bool) -> usize {
+    let tiles_across = image.columns.div_ceil(tile_size);
+    let tiles_down = image.rows.div_ceil(tile_size);
+    tiles_across * tiles_down * if planar { bands } else { 1 }
+}
+
+/// Writes the IFDs of the images one after another, starting at `first_ifd`, and
+/// returns the file offset that follows them.
+fn write_ifds<W: Write>(
+    writer: &mut BufWriter<W>,
+    r: &Raster,
+    images: &[CogImage],
+    layout: &TileLayout,
+    first_ifd: u64,
+) -> Result<u64, Error> {
+    let TileLayout {
+        tile_size,
+        bands,
+        planar,
+        encoding,
+        is_big_tiff,
+    } = *layout;
+    let mut ifd_start = first_ifd;
+    for (level, image) in images.iter().enumerate() {
+        let mut ifd_entries: Vec<Entry> = vec![];
+        let mut larger_values_data = ByteOrderWriter::<Vec<u8>>::new(vec![], r.configs.endian);
+
+        if level > 0 {
+            // NewSubfileType tag (254); this is a reduced-resolution image
+            ifd_entries.push(Entry::new(TAG_NEWSUBFILETYPE, DT_LONG, 1u64, 1u64));
+        }
+
+        // ImageWidth tag (256)
+        ifd_entries.push(Entry::new(
+            TAG_IMAGEWIDTH,
+            DT_LONG,
+            1u64,
+            image.columns as u64,
+        ));
+
+        // ImageLength tag (257)
+        ifd_entries.push(Entry::new(
+            TAG_IMAGELENGTH,
+            DT_LONG,
+            1u64,
+            image.rows as u64,
+        ));
+
+        push_sample_entries(r, bands, planar, &mut ifd_entries, &mut larger_values_data)?;
+
+        // Compression (259) and Predictor (317) tags
+        encoding.push_entries(&mut ifd_entries);
+
+        // TileWidth (322) and TileLength (323) tags
+        ifd_entries.push(Entry::new(TAG_TILEWIDTH, DT_LONG, 1u64, tile_size as u64));
+        ifd_entries.push(Entry::new(TAG_TILELENGTH, DT_LONG, 1u64, tile_size as u64));
+
+        // TileOffsets (324) and TileByteCounts (325) tags
+        push_array_entry(
+            TAG_TILEOFFSETS,
+            &image.tile_offsets,
+            is_big_tiff,
+            &mut ifd_entries,
+            &mut larger_values_data,
+        )?;
+        push_array_entry(
+            TAG_TILEBYTECOUNTS,
+            &image.tile_byte_counts,
+            is_big_tiff,
+            &mut ifd_entries,
+            &mut larger_values_data,
+        )?;
+
+        push_nodata_entry(r, is_big_tiff, &mut ifd_entries, &mut larger_values_data)?;
+        if level == 0 {
+            // Overviews share the georeferencing of the full-resolution image.
+            push_descriptive_entries(&mut ifd_entries, &mut larger_values_data)?;
+            push_georeferencing_entries(r, &mut ifd_entries, &mut larger_values_data)?;
+        }
+
+        if !larger_values_data.len().is_multiple_of(2) {
+            // Keeps the next IFD on a word boundary.
+            larger_values_data.write_u8(0u8)?;
+        }
+        let ifd_size = get_ifd_size(is_big_tiff, ifd_entries.len(), larger_values_data.len());
+        let next_ifd = if level + 1 < images.len() {
+            ifd_start + ifd_size
+        } else {
+            0u64
+        };
+        write_ifd(
+            writer,
+            r.configs.endian,
+            is_big_tiff,
+            ifd_start,
+            ifd_entries,
+            larger_values_data.get_inner(),
+            next_ifd,
+        )?;
+        ifd_start += ifd_size;
+    }
+    Ok(ifd_start)
+}
+
+/// Writes the tiles of an image, recording their offsets and byte counts. Tiles that
+/// extend beyond the edges of the image are padded with NoData.
+fn write_tiles<W: Write>(
+    writer: &mut BufWriter<W>,
+    r: &Raster,
+    image: &mut CogImage,
+    layout: &TileLayout,
+    current_offset: &mut u64,
+) -> Result<(), Error> {
+    let (tile_size, encoding) = (layout.tile_size, layout.encoding);
+    let (planes, samples) = if layout.planar { (layout.bands, 1) } else { (1, layout.bands) };
+    let tiles_across = image.columns.div_ceil(tile_size);
+    let tiles_down = image.rows.div_ceil(tile_size);
+    let nodata = r.configs.nodata;
+    let mut tile_num = 0;
+    for plane in 0..planes {
+        for tile_row in 0..tiles_down {
+            for tile_col in 0..tiles_across {
+                let mut data = Vec::with_capacity(
+                    tile_size * tile_size * samples * r.configs.data_type.get_data_size(),
+                );
+                for row in tile_row * tile_size..(tile_row + 1) * tile_size {
+                    for col in tile_col * tile_size..(tile_col + 1) * tile_size {
+                        for b in plane..plane + samples {
+                            let value = if row < image.rows && col < image.columns {
+                                image.get_value(r, b, row, col)
+                            } else {
+                                nodata
+                            };
+                            write_pixel(&mut data, r.configs.data_type, r.configs.endian, value)?;
+                        }
+                    }
+                }
+                let tile = encoding.encode(data, tile_size, samples, r.configs.data_type, r.configs.endian)?;
+                write_bytes(writer, &tile)?;
+                image.tile_offsets[tile_num] = *current_offset;
+                image.tile_byte_counts[tile_num] = tile.len() as u64;
+                *current_offset += tile.len() as u64;
+                if tile.len() % 2 != 0 {
+                    // This is just because the data must start on a word (i.e. an even value).
+                    write_u8(writer, 0u8)?;
+                    *current_offset += 1;
+                }
+                tile_num += 1;
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Builds an overview of each band of the raster, reduced in size by a factor of
+/// 2^`level`. Each cell of the overview is computed from the corresponding block of
+/// cells of the full-resolution image, rather than from the previous overview, so
+/// that averages are not weighted unevenly by blocks that are cut off by the edges.
+fn build_overview(r: &Raster, bands: usize, level: usize, resampling: Resampling) -> Vec<f64> {
+    let factor = 1usize << level;
+    let (rows, columns) = (r.configs.rows, r.configs.columns);
+    let ov_rows = rows.div_ceil(factor);
+    let ov_columns = columns.div_ceil(factor);
+    let nodata = r.configs.nodata;
+    let is_rgb = r.configs.photometric_interp == PhotometricInterpretation::RGB;
+    let mut values = Vec::with_capacity(bands * ov_rows * ov_columns);
+    let mut counts: HashMap<u64, usize> = HashMap::new();
+    for b in 0..bands {
+        for ov_row in 0..ov_rows {
+            let row_start = ov_row * factor;
+            let row_end = (row_start + factor).min(rows);
+            for ov_col in 0..ov_columns {
+                let col_start = ov_col * factor;
+                let col_end = (col_start + factor).min(columns);
+                let cell = |row: usize, col: usize| r.get_cell((b * rows + row) * columns + col);
+                let value = match resampling {
+                    Resampling::Nearest => cell(
+                        (row_start + factor / 2).min(row_end - 1),
+                        (col_start + factor / 2).min(col_end - 1),
+                    ),
+                    Resampling::Average => {
+                        let mut sums = [0f64; 4];
+                        let mut n = 0usize;
+                        for row in row_start..row_end {
+                            for col in col_start..col_end {
+                                let z = cell(row, col);
+                                if z != nodata {
+                                    if is_rgb {
+                                        let val = z as u32;
+                                        for (c, sum) in sums.iter_mut().enumerate() {
+                                            *sum += ((val >> (8 * c)) & 0xFF) as f64;
+                                        }
+                                    } else {
+                                        sums[0] += z;
+                                    }
+                                    n += 1;
+                                }
+                            }
+                        }
+                        if n == 0 {
+                            nodata
+                        } else if is_rgb {
+                            let mut val = 0u32;
+                            for (c, sum) in sums.iter().enumerate() {
+                                val |= ((sum / n as f64).round() as u32) << (8 * c);
+                            }
+                            val as f64
+                        } else if r.configs.data_type.is_integer() {
+                            (sums[0] / n as f64).round()
+                        } else {
+                            sums[0] / n as f64
+                        }
+                    }
+                    Resampling::Mode => {
+                        counts.clear();
+                        for row in row_start..row_end {
+                            for col in col_start..col_end {
+                                let z = cell(row, col);
+                                if z != nodata {
+                                    *counts.entry(z.to_bits()).or_insert(0) += 1;
+                                }
+                            }
+                        }
+                        // Ties go to the lowest value, so that the result doesn't
+                        // depend upon the order in which the values were counted.
+                        let mut mode = nodata;
+                        let mut max_count = 0;
+                        for (&bits, &count) in &counts {
+                            let z = f64::from_bits(bits);
+                            if count > max_count || (count == max_count && z < mode) {
+                                mode = z;
+                                max_count = count;
+                            }
+                        }
+                        mode
+                    }
+                };
+                values.push(value);
+            }
+        }
+    }
+    values
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::test_utils::*;
+
+    const ENCODING: BlockEncoding = BlockEncoding {
+        compression: COMPRESS_DEFLATE,
+        level: 6,
+        predictor: 3,
+    };
+
+    fn read_overview(file_name: &str, level: usize) -> (RasterConfigs, Vec<f64>) {
+        let mut configs = RasterConfigs::default();
+        let mut data = vec![];
+        read_geotiff_overview(&file_name.to_string(), level, &mut configs, &mut data).unwrap();
+        (configs, data)
+    }
+
+    #[test]
+    fn cog_round_trip() {
+        // The image is not a whole number of tiles, so the tiles at its edges are partial.
+        let file_name = temp_file("cog.tif");
+        let mut output = sample_raster(&file_name, 70, 90, DataType::F32);
+        write_cog(&mut output, &ENCODING, 16, -1, "average").unwrap();
+        let input = Raster::new(&file_name, "r").unwrap();
+        assert_same_cells(&output, &input);
+
+        // Overviews are added until the image fits within a tile, and each cell holds the
+        // mean of the valid cells of its block of the full-resolution image.
+        assert_eq!(get_geotiff_overview_count(&file_name).unwrap(), 3);
+        for level in 1..=3 {
+            let factor = 1isize << level;
+            let (configs, data) = read_overview(&file_name, level);
+            assert_eq!(configs.rows, (70 + factor as usize - 1) / factor as usize);
+            assert_eq!(configs.columns, (90 + factor as usize - 1) / factor as usize);
+            for row in 0..configs.rows as isize {
+                for col in 0..configs.columns as isize {
+                    let (mut sum, mut n) = (0f64, 0);
+                    for r in row * factor..((row + 1) * factor).min(70) {
+                        for c in col * factor..((col + 1) * factor).min(90) {
+                            if c > 0 {
+                                sum += sample_value(r, c, DataType::F32);
+                                n += 1;
+                            }
+                        }
+                    }
+                    let value = data[row as usize * configs.columns + col as usize];
+                    assert!((value - sum / n as f64).abs() < 1e-4);
+                }
+            }
+        }
+        std::fs::remove_file(&file_name).unwrap();
+    }
+
+    #[test]
+    fn cog_layout() {
+        let file_name = temp_file("cog_layout.tif");
+        let mut output = sample_raster(&file_name, 70, 90, DataType::I16);
+        output.set_num_bands(2);
+        for row in 0..70 {
+            for col in 0..90 {
+                output.set_band_value(1, row, col, (row * col) as f64);
+            }
+        }
+        write_cog(&mut output, &ENCODING, 32, 2, "nearest").unwrap();
+        let input = Raster::new(&file_name, "r").unwrap();
+        assert_same_cells(&output, &input);
+
+        // The ghost area follows the header.
+        let bytes = std::fs::read(&file_name).unwrap();
+        assert!(bytes[8..].starts_with(b"GDAL_STRUCTURAL_METADATA_SIZE="));
+
+        // The IFDs are stored before the tiles, which are stored from the smallest
+        // overview to the full-resolution image.
+        let mut configs = RasterConfigs::default();
+        let f = File::open(&file_name).unwrap();
+        let (mut th, is_big_tiff, ifd_offset) =
+            read_tiff_header(BufReader::new(f), &mut configs).unwrap();
+        let images =
+            read_image_ifds(&mut th, is_big_tiff, configs.endian, ifd_offset, usize::MAX).unwrap();
+        assert_eq!(images.len(), 3);
+        let offsets = images
+            .iter()
+            .map(|ifd_map| {
+                assert_eq!(ifd_map[&TAG_TILEWIDTH].interpret_as_u16(), vec![32]);
+                assert_eq!(ifd_map[&TAG_PLANARCONFIGURATION].interpret_as_u16(), vec![2]);
+                ifd_map[&TAG_TILEOFFSETS].interpret_as_u32()
+            })
+            .collect::<Vec<Vec<u32>>>();
+        assert_eq!(offsets[0].len(), 2 * 3 * 3);
+        assert!(*offsets[2].iter().min().unwrap() as usize > th.pos());
+        for i in 0..2 {
+            assert!(offsets[i].iter().min().unwrap() > offsets[i + 1].iter().max().unwrap());
+        }
+
+        // Nearest-neighbour overviews sample the cell nearest the centre of each block.
+        let (configs, data) = read_overview(&file_name, 2);
+        assert_eq!((configs.rows, configs.columns, configs.bands), (18, 23, 2));
+        for row in 0..18 {
+            for col in 0..23 {
+                let (r, c) = ((row * 4 + 2).min(69), (col * 4 + 2).min(89));
+                assert_eq!(data[(18 + row) * 23 + col], (r * c) as f64);
+            }
+        }
+        std::fs::remove_file(&file_name).unwrap();
+    }
+}
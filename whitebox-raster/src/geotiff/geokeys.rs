@@ -899,7 +899,8 @@ pub fn get_keyword_map() -> HashMap<u16, HashMap<u16, &'static str>> {
         7u16 => "JPEG",
         8u16 => "Deflate",
         32773u16 => "PackBits",
-        32946u16 => "DeflateOld"
+        32946u16 => "DeflateOld",
+        50000u16 => "ZSTD"
     ];
     kw.insert(259u16, compression_map);
 
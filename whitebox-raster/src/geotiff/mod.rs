@@ -1,4 +1,5 @@
 #![allow(unused_assignments, dead_code)]
+mod cog;
 pub mod geokeys;
 pub mod ifd;
 pub mod tiff_consts;
@@ -16,7 +17,7 @@ use miniz_oxide::inflate::decompress_to_vec_zlib;
 // use libflate::zlib::Decoder;
 // use libflate::deflate::Encoder;
 use byteorder::{BigEndian, LittleEndian, WriteBytesExt};
-use std::cmp::min;
+use std::cmp::{max, min};
 use std::collections::HashMap;
 use std::default::Default;
 use std::f64;
@@ -233,180 +234,111 @@ pub fn read_geotiff<'a>(
     configs: &'a mut RasterConfigs,
     data: &'a mut Vec<f64>,
 ) -> Result<(), Error> {
-    let f = File::open(file_name.clone())?;
+    read_geotiff_overview(file_name, 0, configs, data)
+}
 
-    //////////////////////////
-    // Read the TIFF header //
-    //////////////////////////
-    let br = BufReader::new(f);
-    let mut th = ByteOrderReader::<BufReader<File>>::new(br, configs.endian);
+/// Reads one of the overviews stored within a GeoTIFF file, e.g. a Cloud-Optimized
+/// GeoTIFF. Overviews are numbered from 1, for the largest, to the number returned
+/// by `get_geotiff_overview_count`; level 0 is the full-resolution image.
+pub fn read_geotiff_overview<'a>(
+    file_name: &'a String,
+    overview: usize,
+    configs: &'a mut RasterConfigs,
+    data: &'a mut Vec<f64>,
+) -> Result<(), Error> {
+    let (mut th, layout) = read_geotiff_header(file_name, configs, overview)?;
 
-    let bo_indicator1 = th.read_u8()?;
-    let bo_indicator2 = th.read_u8()?;
-    if bo_indicator1 == 73 && bo_indicator2 == 73 {
-        configs.endian = Endianness::LittleEndian;
-    } else if bo_indicator1 == 77 && bo_indicator2 == 77 {
-        configs.endian = Endianness::BigEndian;
-    } else {
-        return Err(Error::new(
-            ErrorKind::InvalidData,
-            "Incorrect TIFF header. Unrecognized byte-order indicator.",
-        ));
+    let num_cells = configs.rows * configs.columns;
+    if data.len() > 0 {
+        data.clear();
     }
-
-    if th.get_byte_order() != configs.endian {
-        th.set_byte_order(configs.endian);
+    data.reserve_exact(num_cells * configs.bands);
+    unsafe {
+        // The memory will be initialized when we read
+        // the pixel values.
+        data.set_len(num_cells * configs.bands);
     }
+    layout.read_rows(&mut th, 0..configs.bands, 0, configs.rows, data)?;
 
-    let is_big_tiff = match th.read_u16()? {
-        42 => false,
-        43 => true,
-        _ => {
-            return Err(Error::new(
-                ErrorKind::InvalidData,
-                "Incorrect TIFF header. Unrecognized magic number.",
-            ))
-        }
-    };
+    Ok(())
+}
 
-    if is_big_tiff && mem::size_of::<usize>() != 8 {
-        return Err(Error::new(
-            ErrorKind::InvalidData,
-            "The BigTIFF raster format cannot be read on a 32-bit system.",
-        ));
-    }
+/// Reads the tags of a GeoTIFF file into `configs` and returns the file reader,
+/// along with the layout of the image data, without reading any pixel values.
+pub(crate) fn read_geotiff_header<'a>(
+    file_name: &'a String,
+    configs: &'a mut RasterConfigs,
+    overview: usize,
+) -> Result<(ByteOrderReader<BufReader<File>>, TiffImageLayout), Error> {
+    let f = File::open(file_name.clone())?;
+    read_tiff_image_header(BufReader::new(f), configs, overview)
+}
 
-    let mut ifd_offset = if !is_big_tiff {
-        th.read_u32()? as usize
-    } else {
-        // Bytesize of offsets
-        if th.read_u16()? != 8 {
-            return Err(Error::new(
-                ErrorKind::InvalidData,
-                "Incorrect BigTIFF header. Unsupported bytesize of offsets.",
-            ));
-        }
-        // the next two bytes must be set to zero
-        if th.read_u16()? != 0 {
-            return Err(Error::new(
-                ErrorKind::InvalidData,
-                "Incorrect BigTIFF header.",
-            ));
-        }
-        th.read_u64()? as usize
-    };
+/// Reads a TIFF image that is held in memory, e.g. a tile of a GeoPackage gridded
+/// coverage, into `configs` and `data`.
+pub(crate) fn read_tiff_from_bytes(
+    bytes: Vec<u8>,
+    configs: &mut RasterConfigs,
+    data: &mut Vec<f64>,
+) -> Result<(), Error> {
+    let (mut th, layout) = read_tiff_image_header(Cursor::new(bytes), configs, 0)?;
+    data.clear();
+    data.resize(configs.rows * configs.columns * configs.bands, 0f64);
+    layout.read_rows(&mut th, 0..configs.bands, 0, configs.rows, data)
+}
+
+/// Reads the tags of a TIFF image from a reader, which is positioned at the start of the
+/// TIFF header.
+fn read_tiff_image_header<R: Read + Seek>(
+    reader: R,
+    configs: &mut RasterConfigs,
+    overview: usize,
+) -> Result<(ByteOrderReader<R>, TiffImageLayout), Error> {
+    let (mut th, is_big_tiff, ifd_offset) = read_tiff_header(reader, configs)?;
 
     //////////////////
     // Read the IFD //
     //////////////////
 
-    let mut ifd_map = HashMap::new();
+    // Only the first IFD, which holds the full-resolution image, is read unless an
+    // overview is requested. GeoTIFFs often contain other images, e.g. the pyramids
+    // that users have built, and treating these as part of the raster causes erratic
+    // behaviour of certain tools (see issue # 102).
     let mut geokeys: GeoKeys = Default::default();
-    let mut cur_pos: usize;
-    while ifd_offset > 0 {
-        th.seek(ifd_offset);
-        let num_directories = if !is_big_tiff {
-            th.read_u16()? as u64
-        } else {
-            th.read_u64()?
-        };
-
-        for _ in 0..num_directories {
-            let tag_id = th.read_u16()?;
-            let field_type = th.read_u16()?;
-
-            let num_values = if !is_big_tiff {
-                th.read_u32()? as u64
-            } else {
-                th.read_u64()?
-            };
-
-            let value_offset = if !is_big_tiff {
-                th.read_u32()? as u64
-            } else {
-                th.read_u64()?
-            };
-
-            let data_size = match field_type {
-                1u16 | 2u16 | 6u16 | 7u16 => 1u64,
-                3u16 | 8u16 => 2u64,
-                4u16 | 9u16 | 11u16 => 4u64,
-                5u16 | 10u16 | 12u16 => 8u64,
-                16u16 | 17u16 | 18u16 => 8u64,
-                _ => {
-                    return Err(Error::new(
-                        ErrorKind::InvalidInput,
-                        "Error reading the IFDs.",
-                    ))
-                }
-            };
-
-            // read the tag data
-            let mut data: Vec<u8> = vec![];
-            if !is_big_tiff {
-                if (data_size * num_values) > 4 {
-                    // the values are stored at the offset location
-                    cur_pos = th.pos();
-                    th.seek(value_offset as usize);
-                    for _ in 0..num_values * data_size {
-                        data.push(th.read_u8()?);
-                    }
-                    th.seek(cur_pos);
-                } else {
-                    // the value(s) are contained in the offset
-                    cur_pos = th.pos();
-                    th.seek(cur_pos - 4);
-                    for _ in 0..num_values * data_size {
-                        data.push(th.read_u8()?);
-                    }
-                    th.seek(cur_pos);
-                }
-            } else {
-                if (data_size * num_values) > 8 {
-                    // the values are stored at the offset location
-                    cur_pos = th.pos();
-                    th.seek(value_offset as usize);
-                    for _ in 0..num_values * data_size {
-                        data.push(th.read_u8()?);
-                    }
-                    th.seek(cur_pos);
-                } else {
-                    // the value(s) are contained in the offset
-                    cur_pos = th.pos();
-                    th.seek(cur_pos - 8);
-                    for _ in 0..num_values * data_size {
-                        data.push(th.read_u8()?);
-                    }
-                    th.seek(cur_pos);
+    let mut images = read_image_ifds(&mut th, is_big_tiff, configs.endian, ifd_offset, overview)?;
+    if images.is_empty() {
+        return Err(Error::new(
+            ErrorKind::InvalidData,
+            "The TIFF file does not contain any images.",
+        ));
+    }
+    if images.len() <= overview {
+        return Err(Error::new(
+            ErrorKind::InvalidInput,
+            format!(
+                "The GeoTIFF file does not contain overview level {}. It has {} overview levels.",
+                overview,
+                images.len() - 1
+            ),
+        ));
+    }
+    let mut ifd_map = images.swap_remove(overview);
+    let mut full_resolution_size = None;
+    if overview > 0 {
+        // An overview carries only the tags that describe its own image. Its location
+        // is that of the full-resolution image, scaled to the overview's size below.
+        let full_resolution = &images[0];
+        for tag in GEO_TAGS {
+            if !ifd_map.contains_key(&tag) {
+                if let Some(ifd) = full_resolution.get(&tag) {
+                    ifd_map.insert(tag, ifd.clone());
                 }
             }
-
-            let ifd = Ifd::new(
-                tag_id,
-                field_type,
-                num_values,
-                value_offset,
-                data,
-                configs.endian,
-            );
-
-            ifd_map.insert(tag_id, ifd.clone());
         }
-        // WhiteboxTools currently only supports single-band rasters.
-        // Sometimes GeoTIFF contain multiple bands. When this is the case,
-        // only the first band should be read. This is often the case when
-        // users have used pyramiding on their file. To get the tags of the
-        // other bands, uncomment the code below; however, doing so may
-        // cause erratic behaviour of certain tools. e.g. see issue # 102
-        // clip_raster_to_polygon issue
-
-        // if !is_big_tiff {
-        //     ifd_offset = th.read_u32()? as usize;
-        // } else {
-        //     ifd_offset = th.read_u64()? as usize;
-        // }
-
-        ifd_offset = 0; // comment this out if you want to read additional images.
+        full_resolution_size = Some((
+            get_u32_value(full_resolution.get(&TAG_IMAGEWIDTH))? as usize,
+            get_u32_value(full_resolution.get(&TAG_IMAGELENGTH))? as usize,
+        ));
     }
 
     configs.columns = match ifd_map.get(&256) {
@@ -467,11 +399,13 @@ pub fn read_geotiff<'a>(
         && compression != COMPRESS_PACKBITS
         && compression != COMPRESS_LZW
         && compression != COMPRESS_DEFLATE
+        && compression != COMPRESS_ZSTD
+        && compression != COMPRESS_JPEG
     {
         println!("Compression: {}", compression);
         return Err(Error::new(
             ErrorKind::InvalidData,
-            "The WhiteboxTools GeoTIFF decoder currently only supports PACKBITS, LZW, and DEFLATE compression.",
+            "The WhiteboxTools GeoTIFF decoder currently only supports PACKBITS, LZW, DEFLATE, ZSTD, and JPEG compression.",
         ));
     }
 
@@ -511,7 +445,17 @@ pub fn read_geotiff<'a>(
 
     let sample_format = match ifd_map.get(&339) {
         Some(ifd) => ifd.interpret_as_u16(),
-        _ => [0].to_vec(),
+        _ => [1].to_vec(), // unsigned integer data are the TIFF default
+    };
+
+    let samples_per_pixel = match ifd_map.get(&277) {
+        Some(ifd) => ifd.interpret_as_u16()[0].max(1) as usize,
+        _ => 1,
+    };
+
+    let planar_config = match ifd_map.get(&284) {
+        Some(ifd) => ifd.interpret_as_u16()[0],
+        _ => 1,
     };
 
     configs.nodata = match ifd_map.get(&TAG_GDAL_NODATA) {
@@ -557,8 +501,6 @@ pub fn read_geotiff<'a>(
         _ => {}
     };
 
-    let geokeys_map = geokeys.get_ifd_map(configs.endian);
-
     // ModelTiePointTag
     configs.model_tiepoint = match ifd_map.get(&33922) {
         Some(ifd) => ifd.interpret_as_f64(),
@@ -614,6 +556,22 @@ pub fn read_geotiff<'a>(
         _ => {}
     }
 
+    if let Some((full_columns, full_rows)) = full_resolution_size {
+        // Scale the georeferencing of the full-resolution image to the overview.
+        let scale_x = full_columns as f64 / configs.columns as f64;
+        let scale_y = full_rows as f64 / configs.rows as f64;
+        configs.model_pixel_scale[0] *= scale_x;
+        configs.model_pixel_scale[1] *= scale_y;
+        for i in 0..configs.model_tiepoint.len() / 6 {
+            configs.model_tiepoint[i * 6] /= scale_x;
+            configs.model_tiepoint[i * 6 + 1] /= scale_y;
+        }
+        for i in [0, 4, 8] {
+            configs.model_transformation[i] *= scale_x;
+            configs.model_transformation[i + 1] *= scale_y;
+        }
+    }
+
     if configs.model_tiepoint.len() == 6 {
         // see if the model_pixel_scale tag was actually specified
         if configs.model_pixel_scale[0] == 0.0 {
@@ -789,8 +747,9 @@ pub fn read_geotiff<'a>(
     let photomet_str: String = photomet_map.get(&photometric_interp).unwrap().to_string();
     // let mode: ImageMode;
     let mode: u16;
-    let mut palette = vec![];
-    if photomet_str == "RGB" {
+    // JPEG compressed colour images are usually stored as YCbCr, which the JPEG
+    // decoder converts to RGB.
+    if photomet_str == "RGB" || (photomet_str == "pYCbCr" && compression == COMPRESS_JPEG) {
         configs.photometric_interp = PhotometricInterpretation::RGB;
         if bits_per_sample[0] == 16 {
             if bits_per_sample[1] != 16 || bits_per_sample[2] != 16 {
@@ -837,7 +796,7 @@ pub fn read_geotiff<'a>(
     } else if photomet_str == "Paletted" {
         configs.photometric_interp = PhotometricInterpretation::Categorical;
         mode = IM_PALETTED; //ImageMode::Paletted;
-                            // retrieve the palette colour data
+                            // retrieve the palette colour data, which is kept as the colour table
         let color_map = match ifd_map.get(&320) {
             Some(ifd) => ifd.interpret_as_u16(),
             _ => {
@@ -848,18 +807,23 @@ pub fn read_geotiff<'a>(
             }
         };
         let num_colors = color_map.len() / 3;
-        if color_map.len() % 3 != 0 || num_colors <= 0 || num_colors > 256 {
+        if color_map.len() % 3 != 0 || num_colors <= 0 || num_colors > 65536 {
             return Err(Error::new(ErrorKind::InvalidData, "bad ColorMap length"));
         }
+        if bits_per_sample[0] != 8 && bits_per_sample[0] != 16 {
+            return Err(Error::new(
+                ErrorKind::InvalidData,
+                "Only 8-bit and 16-bit paletted TIFFs are supported.",
+            ));
+        }
+        configs.colour_table.clear();
         for i in 0..num_colors {
             // colours in the colour map are given in 16-bit channels
             // and need to be rescaled to an 8-bit format.
-            let red = (color_map[i] as f64 / 65535.0 * 255.0) as u32;
-            let green = (color_map[i + num_colors] as f64 / 65535.0 * 255.0) as u32;
-            let blue = (color_map[i + 2 * num_colors] as f64 / 65535.0 * 255.0) as u32;
-            let a = 255u32;
-            let val = ((a << 24) | (red << 16) | (green << 8) | blue) as u32;
-            palette.push(val);
+            let red = (color_map[i] as f64 / 65535.0 * 255.0).round() as u8;
+            let green = (color_map[i + num_colors] as f64 / 65535.0 * 255.0).round() as u8;
+            let blue = (color_map[i + 2 * num_colors] as f64 / 65535.0 * 255.0).round() as u8;
+            configs.colour_table.set_colour(i as i64, (red, green, blue, 255u8));
         }
     } else if photomet_str == "WhiteIsZero" {
         configs.photometric_interp = PhotometricInterpretation::Continuous;
@@ -1082,1314 +1046,919 @@ pub fn read_geotiff<'a>(
         };
     }
 
-    ////////////////////
-    // Read the data! //
-    ////////////////////
-    if data.len() > 0 {
-        data.clear();
-    }
-    data.reserve_exact(configs.rows * configs.columns);
-    unsafe {
-        // The memory will be initialized when we read
-        // the pixel values.
-        data.set_len(configs.rows * configs.columns);
+    /////////////////////////
+    // Describe the layout //
+    /////////////////////////
+    // Greyscale images with more than one sample per pixel are read as multi-band
+    // rasters. Colour images are packed into a single band.
+    configs.bands = 1;
+    if mode == IM_GRAY || mode == IM_GRAYINVERT {
+        configs.bands = samples_per_pixel;
+    } else if planar_config == 2 {
+        return Err(Error::new(
+            ErrorKind::InvalidData,
+            "The GeoTIFF reader does not support planar colour images.",
+        ));
     }
+    configs.interleave = if planar_config == 2 {
+        BandInterleave::BSQ
+    } else {
+        BandInterleave::BIP
+    };
 
-    for i in 0..blocks_across {
-        let mut blk_w = block_width;
-        if !block_padding && i == blocks_across - 1 && width % block_width != 0 {
-            blk_w = width % block_width;
+    match mode {
+        IM_GRAYINVERT | IM_GRAY => {
+            configs.photometric_interp = PhotometricInterpretation::Continuous;
+            configs.data_type = match (sample_format[0], bits_per_sample[0]) {
+                (1, 8) => DataType::U8,
+                (1, 16) => DataType::U16,
+                (1, 32) => DataType::U32,
+                (1, 64) => DataType::U64,
+                (2, 8) => DataType::I8,
+                (2, 16) => DataType::I16,
+                (2, 32) => DataType::I32,
+                (2, 64) => DataType::I64,
+                (3, 32) => DataType::F32,
+                (3, 64) => DataType::F64,
+                _ => {
+                    return Err(Error::new(
+                        ErrorKind::InvalidData,
+                        "The raster was not read correctly",
+                    ))
+                }
+            };
+        }
+        IM_PALETTED => {
+            // The values are the indices of the colours in the colour table.
+            configs.photometric_interp = PhotometricInterpretation::Categorical;
+            configs.data_type = if bits_per_sample[0] == 16 {
+                DataType::U16
+            } else {
+                DataType::U8
+            };
         }
-        for j in 0..blocks_down {
-            let mut blk_h = block_height;
-            if !block_padding && j == blocks_down - 1 && height % block_height != 0 {
-                blk_h = height % block_height;
+        IM_RGB => {
+            configs.photometric_interp = PhotometricInterpretation::RGB;
+            if bits_per_sample[0] == 8 {
+                configs.data_type = DataType::U8;
+            } else if bits_per_sample[0] == 16 {
+                configs.data_type = DataType::U16;
+            } else {
+                return Err(Error::new(
+                    ErrorKind::InvalidData,
+                    "The raster was not read correctly",
+                ));
             }
-            let offset = block_offsets[j * blocks_across + i] as usize;
-            let n = block_counts[j * blocks_across + i] as usize;
-            let mut buf: Vec<u8> = vec![];
-            if n != 0 {
-                // it's not a sparse tile
-                match compression {
-                    COMPRESS_NONE => {
-                        // no compression
-                        // buf = vec![0u8; n];
-                        buf.reserve_exact(n);
-                        unsafe { buf.set_len(n); }
-                        th.seek(offset);
-                        th.read_exact(&mut buf)?;
-                    }
-                    COMPRESS_PACKBITS => {
-                        // buf = packbits_decoder(th.buffer[offset..(offset + n)].to_vec());
-                        let mut b = vec![0u8; n];
-                        th.seek(offset);
-                        th.read_exact(&mut b).expect("Error reading bytes from file.");
-                        buf = packbits_decoder(b);
-                    }
-                    COMPRESS_LZW => {
-                        let mut compressed = vec![0; n];
-                        th.seek(offset);
-                        th.read_exact(&mut compressed).expect("Error reading bytes from file.");
-                        let max_uncompressed_length = block_width * block_height * bits_per_sample.len() * bits_per_sample[0] as usize / 8;
-                        buf = Vec::with_capacity(max_uncompressed_length);
-                        let mut decoder = lzw::DecoderEarlyChange::new(lzw::MsbReader::new(), 8);
-                        let mut bytes_read = 0;
-                        while bytes_read < n && buf.len() < max_uncompressed_length {
-                            let (len, bytes) = decoder.decode_bytes(&compressed[bytes_read..]).expect("Error encountered while decoding the LZW compressed GeoTIFF file.");
-                            bytes_read += len;
-                            buf.extend_from_slice(bytes);
-                        }
-                    }
-                    COMPRESS_DEFLATE => {
-                        // let mut dec = GzDecoder::new(th.buffer[offset..(offset + n)].to_vec());
-                        // let compressed = &th.buffer[offset..(offset + n)];
-                        // let mut decoder = Decoder::new(&compressed[..]).unwrap();
-                        // decoder.read_to_end(&mut buf).unwrap();
-                        th.seek(offset);
-                        let mut compressed = vec![0u8; n];
-                        th.read_exact(&mut compressed).expect("Error reading bytes from file.");
-                        // let mut decoder = Decoder::new(&compressed[..])?;
-                        // decoder.read_to_end(&mut buf).unwrap();
-                        buf.extend(decompress_to_vec_zlib(&compressed).expect("Error encountered while decoding the DEFLATE compressed GeoTIFF file."));
-                    }
-                    _ => {
-                        return Err(Error::new(
-                            ErrorKind::InvalidData,
-                            "The WhiteboxTools GeoTIFF decoder currently only supports PACKBITS and DEFLATE compression.",
-                        ))
-                    }
-                }
+        }
+        IM_NRGBA | IM_RGBA => {
+            if bits_per_sample[0] == 8 && bits_per_sample.len() == 4 {
+                configs.data_type = DataType::RGBA32;
+            } else if bits_per_sample[0] == 8 && bits_per_sample.len() == 3 {
+                configs.data_type = DataType::RGB24;
+            } else if bits_per_sample[0] == 16 {
+                configs.data_type = DataType::U16;
+            } else {
+                return Err(Error::new(
+                    ErrorKind::InvalidData,
+                    "The raster was not read correctly",
+                ));
             }
+        }
+        _ => {
+            return Err(Error::new(
+                ErrorKind::InvalidData,
+                "The raster was not read correctly",
+            ))
+        }
+    }
 
-            // println!("{:?}", &buf[0..8]);
-            let mut bor = ByteOrderReader::<Cursor<Vec<u8>>>::new(Cursor::new(buf), configs.endian);
+    // Check to see if a predictor is used with LZW, DEFLATE, and ZSTD
+    let predictor = match ifd_map.get(&317) {
+        Some(ifd) => ifd.interpret_as_u16()[0],
+        _ => 1,
+    };
+    if predictor > 3 {
+        return Err(Error::new(
+            ErrorKind::InvalidData,
+            format!("Unrecognized TIFF predictor ({}).", predictor),
+        ));
+    }
 
-            let xmin = i * block_width;
-            let ymin = j * block_height;
-            let mut xmax = xmin + blk_w;
-            let mut ymax = ymin + blk_h;
+    // The quantization and Huffman tables shared by each block of a JPEG compressed image.
+    let jpeg_tables = match ifd_map.get(&TAG_JPEGTABLES) {
+        Some(ifd) => ifd.data.clone(),
+        _ => vec![],
+    };
 
-            xmax = min(xmax, width);
-            ymax = min(ymax, height);
+    let layout = TiffImageLayout {
+        width: width,
+        height: height,
+        block_width: block_width,
+        block_height: block_height,
+        blocks_across: blocks_across,
+        blocks_down: blocks_down,
+        block_padding: block_padding,
+        block_offsets: block_offsets,
+        block_counts: block_counts,
+        compression: compression,
+        predictor: predictor,
+        jpeg_tables: jpeg_tables,
+        photometric: photometric_interp,
+        mode: mode,
+        bits_per_sample: bits_per_sample.clone(),
+        sample_format: sample_format[0],
+        samples_per_pixel: samples_per_pixel.max(bits_per_sample.len()),
+        planar_config: planar_config,
+        bands: configs.bands,
+        nodata: configs.nodata,
+        endian: configs.endian,
+    };
 
-            let skip_bytes = if xmin + blk_w > width {
-                xmin + blk_w - width
-            } else {
-                0
-            };
+    Ok((th, layout))
+}
 
-            let mut off = 0;
-            let mut i: usize;
-            let (mut red, mut green, mut blue): (u32, u32, u32);
-            if n != 0 {
-                match mode {
-                    IM_GRAYINVERT | IM_GRAY => {
-                        match sample_format[0] {
-                            1 => {
-                                // unsigned integer
-                                match bits_per_sample[0] {
-                                    8 => {
-                                        for y in ymin..ymax {
-                                            for x in xmin..xmax {
-                                                if off <= bor.len() {
-                                                    i = y * width + x;
-                                                    data[i] = bor.read_u8()? as f64;
-                                                    off += 1;
-                                                }
-                                            }
-                                            if skip_bytes > 0 {
-                                                bor.inc_pos(skip_bytes);
-                                            }
-                                        }
-                                    }
-                                    16 => {
-                                        for y in ymin..ymax {
-                                            for x in xmin..xmax {
-                                                if off <= bor.len() {
-                                                    i = y * width + x;
-                                                    data[i] = bor.read_u16()? as f64;
-                                                    off += 2;
-                                                }
-                                            }
-                                            if skip_bytes > 0 {
-                                                bor.inc_pos(skip_bytes * 2);
-                                            }
-                                        }
-                                    }
-                                    32 => {
-                                        for y in ymin..ymax {
-                                            for x in xmin..xmax {
-                                                if off <= bor.len() {
-                                                    i = y * width + x;
-                                                    data[i] = bor.read_u32()? as f64;
-                                                    off += 4;
-                                                }
-                                            }
-                                            if skip_bytes > 0 {
-                                                bor.inc_pos(skip_bytes * 4);
-                                            }
-                                        }
-                                    }
-                                    64 => {
-                                        for y in ymin..ymax {
-                                            for x in xmin..xmax {
-                                                if off <= bor.len() {
-                                                    i = y * width + x;
-                                                    data[i] = bor.read_u64()? as f64;
-                                                    off += 8;
-                                                }
-                                            }
-                                            if skip_bytes > 0 {
-                                                bor.inc_pos(skip_bytes * 8);
-                                            }
-                                        }
-                                    }
-                                    _ => {
-                                        return Err(Error::new(
-                                            ErrorKind::InvalidData,
-                                            "The raster was not read correctly",
-                                        ))
-                                    }
-                                }
-                            }
-                            2 => {
-                                // signed integer
-                                match bits_per_sample[0] {
-                                    8 => {
-                                        for y in ymin..ymax {
-                                            for x in xmin..xmax {
-                                                if off <= bor.len() {
-                                                    i = y * width + x;
-                                                    data[i] = bor.read_i8()? as f64;
-                                                    off += 1;
-                                                }
-                                            }
-                                            if skip_bytes > 0 {
-                                                bor.inc_pos(skip_bytes);
-                                            }
-                                        }
-                                    }
-                                    16 => {
-                                        for y in ymin..ymax {
-                                            for x in xmin..xmax {
-                                                if off <= bor.len() {
-                                                    i = y * width + x;
-                                                    data[i] = bor.read_i16()? as f64;
-                                                    off += 2;
-                                                }
-                                            }
-                                            if skip_bytes > 0 {
-                                                bor.inc_pos(skip_bytes * 2);
-                                            }
-                                        }
-                                    }
-                                    32 => {
-                                        for y in ymin..ymax {
-                                            for x in xmin..xmax {
-                                                if off <= bor.len() {
-                                                    i = y * width + x;
-                                                    data[i] = bor.read_i32()? as f64;
-                                                    off += 4;
-                                                }
-                                            }
-                                            if skip_bytes > 0 {
-                                                bor.inc_pos(skip_bytes * 4);
-                                            }
-                                        }
-                                    }
-                                    64 => {
-                                        for y in ymin..ymax {
-                                            for x in xmin..xmax {
-                                                if off <= bor.len() {
-                                                    i = y * width + x;
-                                                    data[i] = bor.read_i64()? as f64;
-                                                    off += 8;
-                                                }
-                                            }
-                                            if skip_bytes > 0 {
-                                                bor.inc_pos(skip_bytes * 8);
-                                            }
-                                        }
-                                    }
-                                    _ => {
-                                        return Err(Error::new(
-                                            ErrorKind::InvalidData,
-                                            "The raster was not read correctly",
-                                        ))
-                                    }
-                                }
-                            }
-                            3 => {
-                                // floating point
-                                match bits_per_sample[0] {
-                                    32 => {
-                                        for y in ymin..ymax {
-                                            for x in xmin..xmax {
-                                                i = y * width + x;
-                                                data[i] = bor.read_f32()? as f64;
-                                                off += 4;
-                                            }
-                                            if skip_bytes > 0 {
-                                                bor.inc_pos(skip_bytes * 4);
-                                            }
-                                        }
-                                    }
-                                    64 => {
-                                        for y in ymin..ymax {
-                                            for x in xmin..xmax {
-                                                if off <= bor.len() {
-                                                    i = y * width + x;
-                                                    data[i] = bor.read_f64()?;
-                                                    off += 8;
-                                                }
-                                            }
-                                            if skip_bytes > 0 {
-                                                bor.inc_pos(skip_bytes * 8);
-                                            }
-                                        }
-                                    }
-                                    _ => {
-                                        return Err(Error::new(
-                                            ErrorKind::InvalidData,
-                                            "The raster was not read correctly",
-                                        ))
-                                    }
-                                }
-                            }
-                            _ => {
-                                return Err(Error::new(
-                                    ErrorKind::InvalidData,
-                                    "The raster was not read correctly",
-                                ))
-                            }
-                        }
-                    }
-                    IM_PALETTED => {
-                        let mut value: usize;
-                        for y in ymin..ymax {
-                            for x in xmin..xmax {
-                                i = y * width + x;
-                                value = bor.read_u8()? as usize;
-                                data[i] = palette[value] as f64;
-                            }
-                        }
-                    }
-                    IM_RGB => {
-                        let mut value: u32;
-                        let mut a: u32;
-                        if bits_per_sample[0] == 8 {
-                            for y in ymin..ymax {
-                                for x in xmin..xmax {
-                                    red = bor.read_u8()? as u32; //uint32(g.buf[g.off]);
-                                    green = bor.read_u8()? as u32; //uint32(g.buf[g.off+1]);
-                                    blue = bor.read_u8()? as u32; //uint32(g.buf[g.off+2]);
-                                    a = 255u32;
-                                    value = (a << 24) | (blue << 16) | (green << 8) | red;
-                                    i = y * width + x;
-                                    data[i] = value as f64;
-                                }
-                            }
-                        } else if bits_per_sample[0] == 16 {
-                            // the spec doesn't talk about 16-bit RGB images so
-                            // I'm not sure why I bother with this. They specifically
-                            // say that RGB images are 8-bits per channel. Anyhow,
-                            // I rescale the 16-bits to an 8-bit channel for simplicity.
-                            let mut value: u32;
-                            let mut a: u32;
-                            for y in ymin..ymax {
-                                for x in xmin..xmax {
-                                    red = (bor.read_u16()? as f64 / 65535f64 * 255f64) as u32;
-                                    green = (bor.read_u16()? as f64 / 65535f64 * 255f64) as u32;
-                                    blue = (bor.read_u16()? as f64 / 65535f64 * 255f64) as u32;
-                                    a = 255u32;
-                                    value = (a << 24) | (blue << 16) | (green << 8) | red;
-                                    i = y * width + x;
-                                    data[i] = value as f64;
-                                }
-                            }
-                        } else {
-                            return Err(Error::new(
-                                ErrorKind::InvalidData,
-                                "The raster was not read correctly",
-                            ));
-                        }
-                    }
-                    IM_NRGBA | IM_RGBA => {
-                        let mut value: u32;
-                        let mut a: u32;
-                        if bits_per_sample[0] == 8 {
-                            for y in ymin..ymax {
-                                for x in xmin..xmax {
-                                    red = bor.read_u8()? as u32; //uint32(g.buf[g.off]);
-                                    green = bor.read_u8()? as u32; //uint32(g.buf[g.off+1]);
-                                    blue = bor.read_u8()? as u32; //uint32(g.buf[g.off+2]);
-                                    a = bor.read_u8()? as u32;
-                                    value = (a << 24) | (blue << 16) | (green << 8) | red;
-                                    i = y * width + x;
-                                    data[i] = value as f64;
-                                }
-                            }
-                        } else if bits_per_sample[0] == 16 {
-                            // the spec doesn't talk about 16-bit RGB images so
-                            // I'm not sure why I bother with this. They specifically
-                            // say that RGB images are 8-bits per channel. Anyhow,
-                            // I rescale the 16-bits to an 8-bit channel for simplicity.
-                            let mut value: u32;
-                            let mut a: u32;
-                            for y in ymin..ymax {
-                                for x in xmin..xmax {
-                                    red = (bor.read_u16()? as f64 / 65535f64 * 255f64) as u32;
-                                    green = (bor.read_u16()? as f64 / 65535f64 * 255f64) as u32;
-                                    blue = (bor.read_u16()? as f64 / 65535f64 * 255f64) as u32;
-                                    a = (bor.read_u16()? as f64 / 65535f64 * 255f64) as u32;
-                                    value = (a << 24) | (blue << 16) | (green << 8) | red;
-                                    i = y * width + x;
-                                    data[i] = value as f64;
-                                }
-                            }
-                        } else {
-                            return Err(Error::new(
-                                ErrorKind::InvalidData,
-                                "The raster was not read correctly",
-                            ));
-                        }
-                    }
-                    _ => {
-                        return Err(Error::new(
-                            ErrorKind::InvalidData,
-                            "The raster was not read correctly",
-                        ))
-                    }
-                }
-            } else {
-                // GDAL supports sparse tiles. That is, if the block count is zero,
-                // instead of reading the block, simply assume it is filled with either
-                // nodata, if the value is defined, or zeros otherwise.
-                for y in ymin..ymax {
-                    for x in xmin..xmax {
-                        i = y * width + x;
-                        data[i] = configs.nodata;
-                    }
-                }
-            }
-
-            match mode {
-                IM_GRAYINVERT | IM_GRAY => {
-                    //ImageMode::GrayInvert | ImageMode::Gray => {
-                    configs.photometric_interp = PhotometricInterpretation::Continuous;
-                    match sample_format[0] {
-                        1 => {
-                            // unsigned integer
-                            match bits_per_sample[0] {
-                                8 => {
-                                    configs.data_type = DataType::U8;
-                                }
-                                16 => {
-                                    configs.data_type = DataType::U16;
-                                }
-                                32 => {
-                                    configs.data_type = DataType::U32;
-                                }
-                                64 => {
-                                    configs.data_type = DataType::U64;
-                                }
-                                _ => {
-                                    return Err(Error::new(
-                                        ErrorKind::InvalidData,
-                                        "The raster was not read correctly",
-                                    ))
-                                }
-                            }
-                        }
-                        2 => {
-                            // signed integer
-                            match bits_per_sample[0] {
-                                8 => {
-                                    configs.data_type = DataType::I8;
-                                }
-                                16 => {
-                                    configs.data_type = DataType::I16;
-                                }
-                                32 => {
-                                    configs.data_type = DataType::I32;
-                                }
-                                64 => {
-                                    configs.data_type = DataType::I64;
-                                }
-                                _ => {
-                                    return Err(Error::new(
-                                        ErrorKind::InvalidData,
-                                        "The raster was not read correctly",
-                                    ))
-                                }
-                            }
-                        }
-                        3 => {
-                            // floating point
-                            match bits_per_sample[0] {
-                                32 => {
-                                    configs.data_type = DataType::F32;
-                                }
-                                64 => {
-                                    configs.data_type = DataType::F64;
-                                }
-                                _ => {
-                                    return Err(Error::new(
-                                        ErrorKind::InvalidData,
-                                        "The raster was not read correctly",
-                                    ))
-                                }
-                            }
-                        }
-                        _ => {
-                            return Err(Error::new(
-                                ErrorKind::InvalidData,
-                                "The raster was not read correctly",
-                            ))
-                        }
-                    }
-                }
-                IM_PALETTED => {
-                    //ImageMode::Paletted => {
-                    configs.photometric_interp = PhotometricInterpretation::Categorical;
-                    configs.data_type = DataType::U8;
-                }
-                IM_RGB => {
-                    configs.photometric_interp = PhotometricInterpretation::RGB;
-                    if bits_per_sample[0] == 8 {
-                        configs.data_type = DataType::U8;
-                    } else if bits_per_sample[0] == 16 {
-                        configs.data_type = DataType::U16;
-                    } else {
-                        return Err(Error::new(
-                            ErrorKind::InvalidData,
-                            "The raster was not read correctly",
-                        ));
-                    }
-                }
-                IM_NRGBA | IM_RGBA => {
-                    // if bits_per_sample[0] == 8 {
-                    //     configs.data_type = DataType::U32;
-                    // } else if bits_per_sample[0] == 16 {
-                    //     configs.data_type = DataType::U64;
-                    // } else {
-                    //     return Err(Error::new(
-                    //         ErrorKind::InvalidData,
-                    //         "The raster was not read correctly",
-                    //     ));
-                    // }
-                    if bits_per_sample[0] == 8 && bits_per_sample.len() == 4 {
-                        configs.data_type = DataType::RGBA32;
-                    } else if bits_per_sample[0] == 8 && bits_per_sample.len() == 3 {
-                        configs.data_type = DataType::RGB24;
-                    } else if bits_per_sample[0] == 16 {
-                        configs.data_type = DataType::U16;
-                    } else {
-                        return Err(Error::new(
-                            ErrorKind::InvalidData,
-                            "The raster was not read correctly",
-                        ));
-                    }
-                }
-                _ => {
-                    return Err(Error::new(
-                        ErrorKind::InvalidData,
-                        "The raster was not read correctly",
-                    ))
-                }
-            }
-        }
-    }
-
-    // Check to see if a predictor is used with LZW and DEFLATE
-    match ifd_map.get(&317) {
-        Some(ifd) => {
-            if ifd.interpret_as_u16()[0] == 2 {
-                // Horizontal predictor
-                // transform the data
-                let mut idx: usize;
-                for row in 0..configs.rows {
-                    for col in 1..configs.columns {
-                        idx = row * configs.columns + col;
-                        data[idx] += data[idx - 1];
-                    }
-                }
-            }
-            // if ifd.interpret_as_u16()[0] == 3 {
-            //     if configs.endian == Endianness::LittleEndian {
-            //         if configs.data_type == DataType::F32 {
-            //             let mut in_val: f32;
-            //             let mut out_val: f32;
-            //             let mut in_bytes: [u8; 4];
-            //             let mut out_bytes = [0u8; 4];
-            //             let mut idx: usize;
-            //             for row in 0..configs.rows {
-            //                 for col in 1..configs.columns {
-            //                     idx = row * configs.columns + col;
-            //                     in_val = data[idx] as f32;
-            //                     in_bytes = in_val.to_le_bytes();
-            //                     for b in 0..4 {
-            //                         out_bytes[4-b-1] = in_bytes[b];
-            //                     }
-            //                     out_val = f32::from_le_bytes(out_bytes);
-            //                     data[idx] = out_val as f64;
-            //                 }
-            //             }
-            //         } else { // F64
-
-            //         }
-            //     } else {
-            //         if configs.data_type == DataType::F32 {
-
-            //         } else { // F64
-
-            //         }
-            //     }
-
-            if ifd.interpret_as_u16()[0] == 3 {
-                return Err(Error::new(
-                    ErrorKind::InvalidData,
-                    "The GeoTIFF reader does not currently support floating-point predictors (PREDICTOR=3).",
-                ));
-            }
-        }
-        _ => {} // do nothing,
-    }
-
-    // match geokeys_map.get(&1024) {
-    //     Some(ifd) => geokeys.add_key_directory(&ifd.data),
-    //     _ => return Err(Error::new(ErrorKind::InvalidData, "The TIFF file does not contain geokeys")),
-    // };
+/// Opens a TIFF file and reads its header, setting the byte order in `configs`.
+/// Returns the file reader, whether the file is a BigTIFF, and the offset of the
+/// first IFD.
+fn read_tiff_header<R: Read + Seek>(
+    reader: R,
+    configs: &mut RasterConfigs,
+) -> Result<(ByteOrderReader<R>, bool, usize), Error> {
+    //////////////////////////
+    // Read the TIFF header //
+    //////////////////////////
+    let mut th = ByteOrderReader::<R>::new(reader, configs.endian);
 
-    let mut map_sorter = vec![];
-    for (key, _) in ifd_map.iter() {
-        map_sorter.push(key);
+    let bo_indicator1 = th.read_u8()?;
+    let bo_indicator2 = th.read_u8()?;
+    if bo_indicator1 == 73 && bo_indicator2 == 73 {
+        configs.endian = Endianness::LittleEndian;
+    } else if bo_indicator1 == 77 && bo_indicator2 == 77 {
+        configs.endian = Endianness::BigEndian;
+    } else {
+        return Err(Error::new(
+            ErrorKind::InvalidData,
+            "Incorrect TIFF header. Unrecognized byte-order indicator.",
+        ));
     }
-    map_sorter.sort();
 
-    map_sorter.clear();
-    for (key, _) in geokeys_map.iter() {
-        map_sorter.push(key);
+    if th.get_byte_order() != configs.endian {
+        th.set_byte_order(configs.endian);
     }
 
-    Ok(())
-}
-
-pub fn write_geotiff<'a>(r: &'a mut Raster) -> Result<(), Error> {
-    // We'll need to look at the configurations to see if compression should be used
-    let configs = whitebox_common::configs::get_configs()?;
-    let use_compression = configs.compress_rasters;
-
-    
-    // get the ByteOrderWriter
-    let f = File::create(r.file_name.clone())?;
-    let mut writer = BufWriter::new(f);
-
-    // let mut bow = ByteOrderWriter::<BufWriter<File>>::new(writer, r.configs.endian);
+    let is_big_tiff = match th.read_u16()? {
+        42 => false,
+        43 => true,
+        _ => {
+            return Err(Error::new(
+                ErrorKind::InvalidData,
+                "Incorrect TIFF header. Unrecognized magic number.",
+            ))
+        }
+    };
 
-    // get the bytes per pixel
-    let total_bytes_per_pixel = r.configs.data_type.get_data_size();
-    if total_bytes_per_pixel == 0 {
+    if is_big_tiff && mem::size_of::<usize>() != 8 {
         return Err(Error::new(
             ErrorKind::InvalidData,
-            format!(
-                "Unknown data type: {:?}. Photomet interp: {:?}",
-                r.configs.data_type, r.configs.photometric_interp
-            ),
+            "The BigTIFF raster format cannot be read on a 32-bit system.",
         ));
     }
 
-    // is it a BigTiff?
-    let is_big_tiff = if 8usize
-        + (r.configs.rows * r.configs.columns) as usize * total_bytes_per_pixel
-        >= 4_000_000_000
-    {
-        true
+    let ifd_offset = if !is_big_tiff {
+        th.read_u32()? as usize
     } else {
-        false
+        // Bytesize of offsets
+        if th.read_u16()? != 8 {
+            return Err(Error::new(
+                ErrorKind::InvalidData,
+                "Incorrect BigTIFF header. Unsupported bytesize of offsets.",
+            ));
+        }
+        // the next two bytes must be set to zero
+        if th.read_u16()? != 0 {
+            return Err(Error::new(
+                ErrorKind::InvalidData,
+                "Incorrect BigTIFF header.",
+            ));
+        }
+        th.read_u64()? as usize
     };
 
-    let header_size = if !is_big_tiff { 8u64 } else { 16u64 };
+    Ok((th, is_big_tiff, ifd_offset))
+}
 
-    // get the offset to the first ifd
-    let mut ifd_start_needs_extra_byte = false;
-    let mut ifd_start = if !use_compression {
-        let mut val = header_size
-            + (r.configs.rows * r.configs.columns) as u64 * total_bytes_per_pixel as u64;
-        if val % 2 == 1 {
-            val += 1;
-            ifd_start_needs_extra_byte = true;
-        }
-        val
-    } else {
-        0u64
+/// The tags that locate an image in model space and describe its coordinate reference
+/// system, which overviews share with the full-resolution image.
+const GEO_TAGS: [u16; 8] = [
+    TAG_MODELPIXELSCALETAG,
+    TAG_MODELTIEPOINTTAG,
+    TAG_MODELTRANSFORMATIONTAG,
+    33920,
+    TAG_GEOKEYDIRECTORYTAG,
+    TAG_GEODOUBLEPARAMSTAG,
+    TAG_GEOASCIIPARAMSTAG,
+    TAG_GDAL_NODATA,
+];
+
+/// Returns the number of overviews, i.e. reduced-resolution copies of the image, that
+/// are stored within a GeoTIFF file.
+pub fn get_geotiff_overview_count<'a>(file_name: &'a String) -> Result<usize, Error> {
+    let mut configs = RasterConfigs {
+        ..Default::default()
     };
+    let f = File::open(file_name.clone())?;
+    let (mut th, is_big_tiff, ifd_offset) = read_tiff_header(BufReader::new(f), &mut configs)?;
+    let images = read_image_ifds(&mut th, is_big_tiff, configs.endian, ifd_offset, usize::MAX)?;
+    Ok(images.len().max(1) - 1)
+}
 
-    //////////////////////
-    // Write the header //
-    //////////////////////
-    if r.configs.endian == Endianness::LittleEndian {
-        write_bytes(&mut writer, "II".as_bytes()).expect("Error writing byte data.");
-    } else {
-        write_bytes(&mut writer, "MM".as_bytes()).expect("Error writing byte data.");
+/// Reads the chain of IFDs that begins at `ifd_offset`, returning the tags of the
+/// full-resolution image followed by those of at most `max_overviews` of its
+/// overviews, in the order in which they are stored. Other subfiles, such as
+/// transparency masks, are skipped.
+fn read_image_ifds<R: Read + Seek>(
+    th: &mut ByteOrderReader<R>,
+    is_big_tiff: bool,
+    endian: Endianness,
+    mut ifd_offset: usize,
+    max_overviews: usize,
+) -> Result<Vec<HashMap<u16, Ifd>>, Error> {
+    let mut images = vec![];
+    while ifd_offset > 0 && images.len() <= max_overviews {
+        th.seek(ifd_offset);
+        let ifd_map = read_ifd(th, is_big_tiff, endian)?;
+        ifd_offset = if !is_big_tiff {
+            th.read_u32()? as usize
+        } else {
+            th.read_u64()? as usize
+        };
+        // Bit 0 of the NewSubfileType marks a reduced-resolution image and bit 2 a mask.
+        let subfile_type = match ifd_map.get(&TAG_NEWSUBFILETYPE) {
+            Some(ifd) => get_u32_value(Some(ifd))?,
+            None => 0,
+        };
+        if images.is_empty() || subfile_type & 5 == 1 {
+            images.push(ifd_map);
+        }
     }
+    Ok(images)
+}
 
-    if !is_big_tiff {
-        // magic number
-        write_u16(&mut writer, r.configs.endian, 42u16).expect("Error writing byte data.");
-        // offset to first IFD
-        write_u32(&mut writer, r.configs.endian, ifd_start as u32)?;
+/// Reads the entries of the IFD at the reader's current position, leaving the reader
+/// positioned at the offset of the next IFD.
+fn read_ifd<R: Read + Seek>(
+    th: &mut ByteOrderReader<R>,
+    is_big_tiff: bool,
+    endian: Endianness,
+) -> Result<HashMap<u16, Ifd>, Error> {
+    let mut ifd_map = HashMap::new();
+    let mut cur_pos: usize;
+    let num_directories = if !is_big_tiff {
+        th.read_u16()? as u64
     } else {
-        // magic number
-        write_u16(&mut writer, r.configs.endian, 43u16).expect("Error writing byte data.");
-        // Bytesize of offsets
-        write_u16(&mut writer, r.configs.endian, 8u16).expect("Error writing byte data.");
+        th.read_u64()?
+    };
 
-        write_u16(&mut writer, r.configs.endian, 0u16).expect("Error writing byte data."); // Always 0
+    for _ in 0..num_directories {
+        let tag_id = th.read_u16()?;
+        let field_type = th.read_u16()?;
 
-        // offset to first IFD
-        write_u64(&mut writer, r.configs.endian, ifd_start).expect("Error writing byte data.");
-    }
+        let num_values = if !is_big_tiff {
+            th.read_u32()? as u64
+        } else {
+            th.read_u64()?
+        };
 
-    // At the moment, categorical and paletted output is not supported.
-    if r.configs.photometric_interp == PhotometricInterpretation::Categorical
-        || r.configs.photometric_interp == PhotometricInterpretation::Paletted
-    {
-        r.configs.photometric_interp = PhotometricInterpretation::Continuous;
-    }
+        let value_offset = if !is_big_tiff {
+            th.read_u32()? as u64
+        } else {
+            th.read_u64()?
+        };
 
-    //////////////////////////
-    // Write the image data //
-    //////////////////////////
-    let mut strip_offsets = vec![];
-    let mut strip_byte_counts = vec![];
-    let mut current_offset = header_size;
-    if use_compression {
-        // DEFLATE is the only supported compression method at present
+        let data_size = match field_type {
+            1u16 | 2u16 | 6u16 | 7u16 => 1u64,
+            3u16 | 8u16 => 2u64,
+            4u16 | 9u16 | 11u16 => 4u64,
+            5u16 | 10u16 | 12u16 => 8u64,
+            16u16 | 17u16 | 18u16 => 8u64,
+            _ => {
+                return Err(Error::new(
+                    ErrorKind::InvalidInput,
+                    "Error reading the IFDs.",
+                ))
+            }
+        };
 
-        // let mut current_offset = header_size;
-        let mut row_length_in_bytes: u64;
-        match r.configs.photometric_interp {
-            PhotometricInterpretation::Continuous
-            | PhotometricInterpretation::Categorical
-            | PhotometricInterpretation::Boolean => match r.configs.data_type {
-                DataType::F64 => {
-                    let mut i: usize;
-                    for row in 0..r.configs.rows {
-                        let mut data = Vec::with_capacity(r.configs.columns * 8);
-                        if r.configs.endian == Endianness::LittleEndian {
-                            for col in 0..r.configs.columns {
-                                i = row * r.configs.columns + col;
-                                data.write_f64::<LittleEndian>(r.data[i] as f64)
-                                    .expect("Error writing byte data.");
-                            }
-                        } else {
-                            for col in 0..r.configs.columns {
-                                i = row * r.configs.columns + col;
-                                data.write_f64::<BigEndian>(r.data[i] as f64)
-                                    .expect("Error writing byte data.");
-                            }
-                        }
-                        // compress the data vec
-                        let compressed = compress_to_vec_zlib(&data, 6);
-                        write_bytes(&mut writer, &compressed)
-                            .expect("Error writing byte data to file.");
-                        row_length_in_bytes = compressed.len() as u64;
-                        strip_byte_counts.push(row_length_in_bytes);
-                        strip_offsets.push(current_offset);
-                        current_offset += row_length_in_bytes;
-                        if row_length_in_bytes % 2 != 0 {
-                            // This is just because the data must start on a word (i.e. an even value).
-                            write_u8(&mut writer, 0u8).expect("Error writing data to file.");
-                            current_offset += 1;
-                        }
-                    }
-                }
-                DataType::F32 => {
-                    let mut i: usize;
-                    for row in 0..r.configs.rows {
-                        let mut data = Vec::with_capacity(r.configs.columns * 4);
-                        if r.configs.endian == Endianness::LittleEndian {
-                            for col in 0..r.configs.columns {
-                                i = row * r.configs.columns + col;
-                                data.write_f32::<LittleEndian>(r.data[i] as f32)
-                                    .expect("Error writing byte data.");
-                            }
-                        } else {
-                            for col in 0..r.configs.columns {
-                                i = row * r.configs.columns + col;
-                                data.write_f32::<BigEndian>(r.data[i] as f32)
-                                    .expect("Error writing byte data.");
-                            }
-                        }
-                        // compress the data vec
-                        let compressed = compress_to_vec_zlib(&data, 6);
-                        write_bytes(&mut writer, &compressed)
-                            .expect("Error writing byte data to file.");
-                        row_length_in_bytes = compressed.len() as u64;
-                        strip_byte_counts.push(row_length_in_bytes);
-                        strip_offsets.push(current_offset);
-                        current_offset += row_length_in_bytes;
-                        if row_length_in_bytes % 2 != 0 {
-                            // This is just because the data must start on a word (i.e. an even value).
-                            write_u8(&mut writer, 0u8).expect("Error writing data to file.");
-                            current_offset += 1;
-                        }
-                    }
-                }
-                DataType::U64 => {
-                    let mut i: usize;
-                    for row in 0..r.configs.rows {
-                        let mut data = Vec::with_capacity(r.configs.columns * 8);
-                        if r.configs.endian == Endianness::LittleEndian {
-                            for col in 0..r.configs.columns {
-                                i = row * r.configs.columns + col;
-                                data.write_u64::<LittleEndian>(r.data[i] as u64)
-                                    .expect("Error writing byte data.");
-                            }
-                        } else {
-                            for col in 0..r.configs.columns {
-                                i = row * r.configs.columns + col;
-                                data.write_u64::<BigEndian>(r.data[i] as u64)
-                                    .expect("Error writing byte data.");
-                            }
-                        }
-                        // compress the data vec
-                        let compressed = compress_to_vec_zlib(&data, 6);
-                        write_bytes(&mut writer, &compressed)
-                            .expect("Error writing byte data to file.");
-                        row_length_in_bytes = compressed.len() as u64;
-                        strip_byte_counts.push(row_length_in_bytes);
-                        strip_offsets.push(current_offset);
-                        current_offset += row_length_in_bytes;
-                        if row_length_in_bytes % 2 != 0 {
-                            // This is just because the data must start on a word (i.e. an even value).
-                            write_u8(&mut writer, 0u8).expect("Error writing data to file.");
-                            current_offset += 1;
-                        }
-                    }
-                }
-                DataType::U32 => {
-                    let mut i: usize;
-                    for row in 0..r.configs.rows {
-                        let mut data = Vec::with_capacity(r.configs.columns * 4);
-                        if r.configs.endian == Endianness::LittleEndian {
-                            for col in 0..r.configs.columns {
-                                i = row * r.configs.columns + col;
-                                data.write_u32::<LittleEndian>(r.data[i] as u32)
-                                    .expect("Error writing byte data.");
-                            }
-                        } else {
-                            for col in 0..r.configs.columns {
-                                i = row * r.configs.columns + col;
-                                data.write_u32::<BigEndian>(r.data[i] as u32)
-                                    .expect("Error writing byte data.");
-                            }
-                        }
-                        // compress the data vec
-                        let compressed = compress_to_vec_zlib(&data, 6);
-                        write_bytes(&mut writer, &compressed)
-                            .expect("Error writing byte data to file.");
-                        row_length_in_bytes = compressed.len() as u64;
-                        strip_byte_counts.push(row_length_in_bytes);
-                        strip_offsets.push(current_offset);
-                        current_offset += row_length_in_bytes;
-                        if row_length_in_bytes % 2 != 0 {
-                            // This is just because the data must start on a word (i.e. an even value).
-                            write_u8(&mut writer, 0u8).expect("Error writing data to file.");
-                            current_offset += 1;
-                        }
-                    }
-                }
-                DataType::U16 => {
-                    let mut i: usize;
-                    for row in 0..r.configs.rows {
-                        let mut data = Vec::with_capacity(r.configs.columns * 2);
-                        if r.configs.endian == Endianness::LittleEndian {
-                            for col in 0..r.configs.columns {
-                                i = row * r.configs.columns + col;
-                                data.write_u16::<LittleEndian>(r.data[i] as u16)
-                                    .expect("Error writing byte data.");
-                            }
-                        } else {
-                            for col in 0..r.configs.columns {
-                                i = row * r.configs.columns + col;
-                                data.write_u16::<BigEndian>(r.data[i] as u16)
-                                    .expect("Error writing byte data.");
-                            }
-                        }
-                        // compress the data vec
-                        let compressed = compress_to_vec_zlib(&data, 6);
-                        write_bytes(&mut writer, &compressed)
-                            .expect("Error writing byte data to file.");
-                        row_length_in_bytes = compressed.len() as u64;
-                        strip_byte_counts.push(row_length_in_bytes);
-                        strip_offsets.push(current_offset);
-                        current_offset += row_length_in_bytes;
-                        if row_length_in_bytes % 2 != 0 {
-                            // This is just because the data must start on a word (i.e. an even value).
-                            write_u8(&mut writer, 0u8).expect("Error writing data to file.");
-                            current_offset += 1;
-                        }
-                    }
-                }
-                DataType::U8 => {
-                    let mut i: usize;
-                    for row in 0..r.configs.rows {
-                        let mut data = Vec::with_capacity(r.configs.columns);
-                        if r.configs.endian == Endianness::LittleEndian {
-                            for col in 0..r.configs.columns {
-                                i = row * r.configs.columns + col;
-                                data.write_u8(r.data[i] as u8)
-                                    .expect("Error writing byte data.");
-                            }
-                        } else {
-                            for col in 0..r.configs.columns {
-                                i = row * r.configs.columns + col;
-                                data.write_u8(r.data[i] as u8)
-                                    .expect("Error writing byte data.");
-                            }
-                        }
-                        // compress the data vec
-                        let compressed = compress_to_vec_zlib(&data, 6);
-                        write_bytes(&mut writer, &compressed)
-                            .expect("Error writing byte data to file.");
-                        row_length_in_bytes = compressed.len() as u64;
-                        strip_byte_counts.push(row_length_in_bytes);
-                        strip_offsets.push(current_offset);
-                        current_offset += row_length_in_bytes;
-                        if row_length_in_bytes % 2 != 0 {
-                            // This is just because the data must start on a word (i.e. an even value).
-                            write_u8(&mut writer, 0u8).expect("Error writing data to file.");
-                            current_offset += 1;
-                        }
-                    }
-                }
-                DataType::I64 => {
-                    let mut i: usize;
-                    for row in 0..r.configs.rows {
-                        let mut data = Vec::with_capacity(r.configs.columns * 8);
-                        if r.configs.endian == Endianness::LittleEndian {
-                            for col in 0..r.configs.columns {
-                                i = row * r.configs.columns + col;
-                                data.write_i64::<LittleEndian>(r.data[i] as i64)
-                                    .expect("Error writing byte data.");
-                            }
-                        } else {
-                            for col in 0..r.configs.columns {
-                                i = row * r.configs.columns + col;
-                                data.write_i64::<BigEndian>(r.data[i] as i64)
-                                    .expect("Error writing byte data.");
-                            }
-                        }
-                        // compress the data vec
-                        let compressed = compress_to_vec_zlib(&data, 6);
-                        write_bytes(&mut writer, &compressed)
-                            .expect("Error writing byte data to file.");
-                        row_length_in_bytes = compressed.len() as u64;
-                        strip_byte_counts.push(row_length_in_bytes);
-                        strip_offsets.push(current_offset);
-                        current_offset += row_length_in_bytes;
-                        if row_length_in_bytes % 2 != 0 {
-                            // This is just because the data must start on a word (i.e. an even value).
-                            write_u8(&mut writer, 0u8).expect("Error writing data to file.");
-                            current_offset += 1;
-                        }
-                    }
-                }
-                DataType::I32 => {
-                    let mut i: usize;
-                    for row in 0..r.configs.rows {
-                        let mut data = Vec::with_capacity(r.configs.columns * 4);
-                        if r.configs.endian == Endianness::LittleEndian {
-                            for col in 0..r.configs.columns {
-                                i = row * r.configs.columns + col;
-                                data.write_i32::<LittleEndian>(r.data[i] as i32)
-                                    .expect("Error writing byte data.");
-                            }
-                        } else {
-                            for col in 0..r.configs.columns {
-                                i = row * r.configs.columns + col;
-                                data.write_i32::<BigEndian>(r.data[i] as i32)
-                                    .expect("Error writing byte data.");
-                            }
-                        }
-                        // compress the data vec
-                        let compressed = compress_to_vec_zlib(&data, 6);
-                        write_bytes(&mut writer, &compressed)
-                            .expect("Error writing byte data to file.");
-                        row_length_in_bytes = compressed.len() as u64;
-                        strip_byte_counts.push(row_length_in_bytes);
-                        strip_offsets.push(current_offset);
-                        current_offset += row_length_in_bytes;
-                        if row_length_in_bytes % 2 != 0 {
-                            // This is just because the data must start on a word (i.e. an even value).
-                            write_u8(&mut writer, 0u8).expect("Error writing data to file.");
-                            current_offset += 1;
-                        }
-                    }
+        // read the tag data
+        let mut data: Vec<u8> = vec![];
+        if !is_big_tiff {
+            if (data_size * num_values) > 4 {
+                // the values are stored at the offset location
+                cur_pos = th.pos();
+                th.seek(value_offset as usize);
+                for _ in 0..num_values * data_size {
+                    data.push(th.read_u8()?);
                 }
-                DataType::I16 => {
-                    let mut i: usize;
-                    for row in 0..r.configs.rows {
-                        let mut data = Vec::with_capacity(r.configs.columns * 2);
-                        if r.configs.endian == Endianness::LittleEndian {
-                            for col in 0..r.configs.columns {
-                                i = row * r.configs.columns + col;
-                                data.write_i16::<LittleEndian>(r.data[i] as i16)
-                                    .expect("Error writing byte data.");
-                            }
-                        } else {
-                            for col in 0..r.configs.columns {
-                                i = row * r.configs.columns + col;
-                                data.write_i16::<BigEndian>(r.data[i] as i16)
-                                    .expect("Error writing byte data.");
-                            }
-                        }
-                        // compress the data vec
-                        let compressed = compress_to_vec_zlib(&data, 6);
-                        write_bytes(&mut writer, &compressed)
-                            .expect("Error writing byte data to file.");
-                        row_length_in_bytes = compressed.len() as u64;
-                        strip_byte_counts.push(row_length_in_bytes);
-                        strip_offsets.push(current_offset);
-                        current_offset += row_length_in_bytes;
-                        if row_length_in_bytes % 2 != 0 {
-                            // This is just because the data must start on a word (i.e. an even value).
-                            write_u8(&mut writer, 0u8).expect("Error writing data to file.");
-                            current_offset += 1;
-                        }
-                    }
+                th.seek(cur_pos);
+            } else {
+                // the value(s) are contained in the offset
+                cur_pos = th.pos();
+                th.seek(cur_pos - 4);
+                for _ in 0..num_values * data_size {
+                    data.push(th.read_u8()?);
                 }
-                DataType::I8 => {
-                    let mut i: usize;
-                    for row in 0..r.configs.rows {
-                        let mut data = Vec::with_capacity(r.configs.columns);
-                        if r.configs.endian == Endianness::LittleEndian {
-                            for col in 0..r.configs.columns {
-                                i = row * r.configs.columns + col;
-                                data.write_i8(r.data[i] as i8)
-                                    .expect("Error writing byte data.");
-                            }
-                        } else {
-                            for col in 0..r.configs.columns {
-                                i = row * r.configs.columns + col;
-                                data.write_i8(r.data[i] as i8)
-                                    .expect("Error writing byte data.");
-                            }
-                        }
-                        // compress the data vec
-                        let compressed = compress_to_vec_zlib(&data, 6);
-                        write_bytes(&mut writer, &compressed)
-                            .expect("Error writing byte data to file.");
-                        row_length_in_bytes = compressed.len() as u64;
-                        strip_byte_counts.push(row_length_in_bytes);
-                        strip_offsets.push(current_offset);
-                        current_offset += row_length_in_bytes;
-                        if row_length_in_bytes % 2 != 0 {
-                            // This is just because the data must start on a word (i.e. an even value).
-                            write_u8(&mut writer, 0u8).expect("Error writing data to file.");
-                            current_offset += 1;
-                        }
-                    }
+                th.seek(cur_pos);
+            }
+        } else {
+            if (data_size * num_values) > 8 {
+                // the values are stored at the offset location
+                cur_pos = th.pos();
+                th.seek(value_offset as usize);
+                for _ in 0..num_values * data_size {
+                    data.push(th.read_u8()?);
                 }
-                _ => {
-                    return Err(Error::new(
-                        ErrorKind::InvalidData,
-                        format!(
-                            "Unknown data type: {:?}. Photomet interp: {:?}",
-                            r.configs.data_type, r.configs.photometric_interp
-                        ),
-                    ));
+                th.seek(cur_pos);
+            } else {
+                // the value(s) are contained in the offset
+                cur_pos = th.pos();
+                th.seek(cur_pos - 8);
+                for _ in 0..num_values * data_size {
+                    data.push(th.read_u8()?);
                 }
-            },
-            PhotometricInterpretation::RGB => {
-                match r.configs.data_type {
-                    DataType::RGB24 => {
-                        // let mut bytes: [u8; 3] = [0u8; 3];
-                        let mut i: usize;
-                        let mut val: u32;
-                        for row in 0..r.configs.rows {
-                            let mut data = Vec::with_capacity(r.configs.columns * 3);
-                            for col in 0..r.configs.columns {
-                                i = row * r.configs.columns + col;
-                                val = r.data[i] as u32;
-                                data.write_u8((val & 0xFF) as u8)
-                                    .expect("Error writing byte data."); // red
+                th.seek(cur_pos);
+            }
+        }
 
-                                data.write_u8(((val >> 8u32) & 0xFF) as u8)
-                                    .expect("Error writing byte data."); // green
+        let ifd = Ifd::new(
+            tag_id,
+            field_type,
+            num_values,
+            value_offset,
+            data,
+            endian,
+        );
 
-                                data.write_u8(((val >> 16u32) & 0xFF) as u8)
-                                    .expect("Error writing byte data."); // blue
-                            }
-                            // compress the data vec
-                            let compressed = compress_to_vec_zlib(&data, 6);
-                            write_bytes(&mut writer, &compressed)
-                                .expect("Error writing byte data to file.");
-                            row_length_in_bytes = compressed.len() as u64;
-                            strip_byte_counts.push(row_length_in_bytes);
-                            strip_offsets.push(current_offset);
-                            current_offset += row_length_in_bytes;
-                            if row_length_in_bytes % 2 != 0 {
-                                // This is just because the data must start on a word (i.e. an even value).
-                                write_u8(&mut writer, 0u8).expect("Error writing data to file.");
-                                current_offset += 1;
-                            }
-                        }
-                    }
-                    DataType::RGBA32 | DataType::U32 => {
-                        let mut i: usize;
-                        // let mut bytes: [u8; 4] = [0u8; 4];
-                        let mut val: u32;
-                        for row in 0..r.configs.rows {
-                            let mut data = Vec::with_capacity(r.configs.columns * 4);
-                            for col in 0..r.configs.columns {
-                                i = row * r.configs.columns + col;
-                                val = r.data[i] as u32;
-                                data.write_u8((val & 0xFF) as u8)
-                                    .expect("Error writing byte data."); // red
+        ifd_map.insert(tag_id, ifd);
+    }
+    Ok(ifd_map)
+}
 
-                                data.write_u8(((val >> 8u32) & 0xFF) as u8)
-                                    .expect("Error writing byte data."); // green
+/// Returns the single integer value of a SHORT or LONG tag.
+fn get_u32_value(ifd: Option<&Ifd>) -> Result<u32, Error> {
+    match ifd {
+        Some(ifd) if ifd.ifd_type == DT_SHORT && ifd.num_values > 0 => {
+            Ok(ifd.interpret_as_u16()[0] as u32)
+        }
+        Some(ifd) if ifd.ifd_type == DT_LONG && ifd.num_values > 0 => Ok(ifd.interpret_as_u32()[0]),
+        _ => Err(Error::new(
+            ErrorKind::InvalidData,
+            "A required TIFF tag is missing or was not read correctly.",
+        )),
+    }
+}
 
-                                data.write_u8(((val >> 16u32) & 0xFF) as u8)
-                                    .expect("Error writing byte data."); // blue
+/// Describes how the pixel values of a TIFF image are divided into strips or tiles
+/// (collectively, blocks) and how each of these blocks is encoded.
+#[derive(Clone, Debug)]
+pub(crate) struct TiffImageLayout {
+    pub width: usize,
+    pub height: usize,
+    pub block_width: usize,
+    pub block_height: usize,
+    pub blocks_across: usize,
+    pub blocks_down: usize,
+    pub block_padding: bool,
+    pub block_offsets: Vec<u64>,
+    pub block_counts: Vec<u64>,
+    pub compression: u16,
+    pub predictor: u16,
+    pub jpeg_tables: Vec<u8>,
+    pub photometric: u16,
+    pub mode: u16,
+    pub bits_per_sample: Vec<u16>,
+    pub sample_format: u16,
+    pub samples_per_pixel: usize,
+    pub planar_config: u16,
+    pub bands: usize,
+    pub nodata: f64,
+    pub endian: Endianness,
+}
 
-                                data.write_u8(((val >> 24u32) & 0xFF) as u8)
-                                    .expect("Error writing byte data."); // a
-                            }
-                            // for col in 0..r.configs.columns {
-                            //     i = row * r.configs.columns + col;
-                            //     val = r.data[i] as u32;
-                            //     bytes[2] = ((val >> 16u32) & 0xFF) as u8; // blue
-                            //     bytes[1] = ((val >> 8u32) & 0xFF) as u8; // green
-                            //     bytes[0] = (val & 0xFF) as u8; // red
-                            //     bytes[3] = ((val >> 24u32) & 0xFF) as u8; // a
-                            //     write_bytes(&mut writer, &bytes)?;
-                            // }
-
-                            // compress the data vec
-                            let compressed = compress_to_vec_zlib(&data, 6);
-                            write_bytes(&mut writer, &compressed)
-                                .expect("Error writing byte data to file.");
-                            row_length_in_bytes = compressed.len() as u64;
-                            strip_byte_counts.push(row_length_in_bytes);
-                            strip_offsets.push(current_offset);
-                            current_offset += row_length_in_bytes;
-                            if row_length_in_bytes % 2 != 0 {
-                                // This is just because the data must start on a word (i.e. an even value).
-                                write_u8(&mut writer, 0u8).expect("Error writing data to file.");
-                                current_offset += 1;
-                            }
-                        }
-                    }
-                    _ => {
-                        return Err(Error::new(
-                            ErrorKind::InvalidData,
-                            format!(
-                                "Unknown data type: {:?}. Photomet interp: {:?}",
-                                r.configs.data_type, r.configs.photometric_interp
-                            ),
-                        ));
-                    }
+impl TiffImageLayout {
+    fn is_planar(&self) -> bool {
+        self.planar_config == 2
+    }
+
+    fn bytes_per_sample(&self) -> usize {
+        (self.bits_per_sample[0] as usize + 7) / 8
+    }
+
+    /// The number of samples, per pixel, stored within each block.
+    fn block_samples(&self) -> usize {
+        if self.is_planar() {
+            1
+        } else {
+            self.samples_per_pixel
+        }
+    }
+
+    /// Reads and decompresses the block with the specified index. An empty
+    /// vector is returned for sparse blocks, i.e. those without any data.
+    fn read_block<R: Read + Seek>(
+        &self,
+        th: &mut ByteOrderReader<R>,
+        index: usize,
+    ) -> Result<Vec<u8>, Error> {
+        let offset = self.block_offsets[index] as usize;
+        let n = self.block_counts[index] as usize;
+        let mut buf: Vec<u8> = vec![];
+        if n == 0 {
+            return Ok(buf);
+        }
+        match self.compression {
+            COMPRESS_NONE => {
+                // no compression
+                buf.reserve_exact(n);
+                unsafe {
+                    buf.set_len(n);
                 }
+                th.seek(offset);
+                th.read_exact(&mut buf)?;
             }
-            PhotometricInterpretation::Paletted => {
-                return Err(Error::new(
-                    ErrorKind::InvalidData,
-                    "Paletted GeoTIFFs are currently unsupported for writing.",
-                ));
+            COMPRESS_PACKBITS => {
+                let mut b = vec![0u8; n];
+                th.seek(offset);
+                th.read_exact(&mut b)?;
+                buf = packbits_decoder(b);
             }
-            PhotometricInterpretation::Unknown => {
+            COMPRESS_LZW => {
+                let mut compressed = vec![0; n];
+                th.seek(offset);
+                th.read_exact(&mut compressed)?;
+                let max_uncompressed_length = self.block_width
+                    * self.block_height
+                    * self.block_samples()
+                    * self.bytes_per_sample();
+                buf = Vec::with_capacity(max_uncompressed_length);
+                let mut decoder = lzw::DecoderEarlyChange::new(lzw::MsbReader::new(), 8);
+                let mut bytes_read = 0;
+                while bytes_read < n && buf.len() < max_uncompressed_length {
+                    let (len, bytes) = decoder.decode_bytes(&compressed[bytes_read..])?;
+                    bytes_read += len;
+                    buf.extend_from_slice(bytes);
+                }
+            }
+            COMPRESS_DEFLATE => {
+                th.seek(offset);
+                let mut compressed = vec![0u8; n];
+                th.read_exact(&mut compressed)?;
+                buf = decompress_to_vec_zlib(&compressed).map_err(|_| {
+                    Error::new(
+                        ErrorKind::InvalidData,
+                        "Error encountered while decoding the DEFLATE compressed GeoTIFF file.",
+                    )
+                })?;
+            }
+            COMPRESS_ZSTD => {
+                th.seek(offset);
+                let mut compressed = vec![0u8; n];
+                th.read_exact(&mut compressed)?;
+                buf = zstd::stream::decode_all(&compressed[..])?;
+            }
+            COMPRESS_JPEG => {
+                th.seek(offset);
+                let mut compressed = vec![0u8; n];
+                th.read_exact(&mut compressed)?;
+                // JPEG blocks are always decoded to unpredicted samples.
+                return self.decode_jpeg(compressed);
+            }
+            _ => {
                 return Err(Error::new(
                     ErrorKind::InvalidData,
-                    "Error while writing GeoTIFF file.",
-                ));
+                    "The WhiteboxTools GeoTIFF decoder currently only supports PACKBITS, LZW, DEFLATE, ZSTD, and JPEG compression.",
+                ))
             }
         }
-    } else {
-        match r.configs.photometric_interp {
-            PhotometricInterpretation::Continuous
-            | PhotometricInterpretation::Categorical
-            | PhotometricInterpretation::Boolean => match r.configs.data_type {
-                DataType::F64 => {
-                    let mut i: usize;
-                    for row in 0..r.configs.rows {
-                        for col in 0..r.configs.columns {
-                            i = row * r.configs.columns + col;
-                            write_f64(&mut writer, r.configs.endian, r.data[i])?;
-                        }
-                    }
-                }
-                DataType::F32 => {
-                    let mut i: usize;
-                    for row in 0..r.configs.rows {
-                        for col in 0..r.configs.columns {
-                            i = row * r.configs.columns + col;
-                            write_f32(&mut writer, r.configs.endian, r.data[i] as f32)?;
-                        }
-                    }
-                }
-                DataType::U64 => {
-                    let mut i: usize;
-                    for row in 0..r.configs.rows {
-                        for col in 0..r.configs.columns {
-                            i = row * r.configs.columns + col;
-                            write_u64(&mut writer, r.configs.endian, r.data[i] as u64)?;
-                        }
-                    }
-                }
-                DataType::U32 => {
-                    let mut i: usize;
-                    for row in 0..r.configs.rows {
-                        for col in 0..r.configs.columns {
-                            i = row * r.configs.columns + col;
-                            write_u32(&mut writer, r.configs.endian, r.data[i] as u32)?;
-                        }
-                    }
-                }
-                DataType::U16 => {
-                    let mut i: usize;
-                    for row in 0..r.configs.rows {
-                        for col in 0..r.configs.columns {
-                            i = row * r.configs.columns + col;
-                            write_u16(&mut writer, r.configs.endian, r.data[i] as u16)?;
-                        }
-                    }
-                }
-                DataType::U8 => {
-                    let mut i: usize;
-                    for row in 0..r.configs.rows {
-                        for col in 0..r.configs.columns {
-                            i = row * r.configs.columns + col;
-                            write_u8(&mut writer, r.data[i] as u8)?;
-                        }
-                    }
-                }
-                DataType::I64 => {
-                    let mut i: usize;
-                    for row in 0..r.configs.rows {
-                        for col in 0..r.configs.columns {
-                            i = row * r.configs.columns + col;
-                            write_i64(&mut writer, r.configs.endian, r.data[i] as i64)?;
-                        }
-                    }
+        if self.predictor > 1 {
+            self.undo_predictor(&mut buf);
+        }
+        Ok(buf)
+    }
+
+    /// Decodes a JPEG compressed block. The blocks of a TIFF are usually abbreviated
+    /// JPEG streams that omit the tables stored in the JPEGTables tag; these are
+    /// spliced into the start of the stream.
+    fn decode_jpeg(&self, block: Vec<u8>) -> Result<Vec<u8>, Error> {
+        let stream = if self.jpeg_tables.len() > 4 && block.len() > 2 {
+            // Drop the tables' end-of-image marker and the block's start-of-image marker.
+            let mut stream = self.jpeg_tables[..self.jpeg_tables.len() - 2].to_vec();
+            stream.extend_from_slice(&block[2..]);
+            stream
+        } else {
+            block
+        };
+        let mut decoder = jpeg_decoder::Decoder::new(Cursor::new(stream));
+        if self.photometric != PI_YCBCR {
+            decoder.set_color_transform(jpeg_decoder::ColorTransform::None);
+        }
+        decoder.decode().map_err(|e| {
+            Error::new(
+                ErrorKind::InvalidData,
+                format!(
+                    "Error encountered while decoding the JPEG compressed GeoTIFF file: {}",
+                    e
+                ),
+            )
+        })
+    }
+
+    /// Reverses the predictor that was applied to each row of a decompressed block.
+    /// Horizontal differencing (2) stores each sample as its difference from the same
+    /// sample of the previous pixel. The floating-point predictor (3) first splits the
+    /// bytes of a row's samples into planes, from the most significant byte to the
+    /// least, and then differences adjacent bytes.
+    fn undo_predictor(&self, buf: &mut Vec<u8>) {
+        let bytes_per_sample = self.bytes_per_sample();
+        let samples = self.block_samples();
+        let row_samples = self.block_width * samples;
+        let row_bytes = row_samples * bytes_per_sample;
+        if row_bytes == 0 {
+            return;
+        }
+        let mut values = vec![0u8; row_bytes];
+        for row in buf.chunks_exact_mut(row_bytes) {
+            if self.predictor == 2 {
+                for i in samples..row_samples {
+                    let value = get_uint(row, i, bytes_per_sample, self.endian)
+                        .wrapping_add(get_uint(row, i - samples, bytes_per_sample, self.endian));
+                    set_uint(row, i, bytes_per_sample, self.endian, value);
                 }
-                DataType::I32 => {
-                    let mut i: usize;
-                    for row in 0..r.configs.rows {
-                        for col in 0..r.configs.columns {
-                            i = row * r.configs.columns + col;
-                            write_i32(&mut writer, r.configs.endian, r.data[i] as i32)?;
-                        }
-                    }
+            } else {
+                for i in samples..row_bytes {
+                    row[i] = row[i].wrapping_add(row[i - samples]);
                 }
-                DataType::I16 => {
-                    let mut i: usize;
-                    for row in 0..r.configs.rows {
-                        for col in 0..r.configs.columns {
-                            i = row * r.configs.columns + col;
-                            write_i16(&mut writer, r.configs.endian, r.data[i] as i16)?;
+                for i in 0..row_samples {
+                    for b in 0..bytes_per_sample {
+                        // byte b of each sample, counted from the most significant
+                        let byte = row[b * row_samples + i];
+                        if self.endian == Endianness::BigEndian {
+                            values[i * bytes_per_sample + b] = byte;
+                        } else {
+                            values[i * bytes_per_sample + bytes_per_sample - 1 - b] = byte;
                         }
                     }
                 }
-                DataType::I8 => {
-                    let mut i: usize;
-                    for row in 0..r.configs.rows {
-                        for col in 0..r.configs.columns {
-                            i = row * r.configs.columns + col;
-                            write_i8(&mut writer, r.data[i] as i8)?;
-                        }
+                row.copy_from_slice(&values);
+            }
+        }
+    }
+
+    /// Reads the rows `row_start..row_end` of a range of bands into `data`. The
+    /// bands are stored sequentially within `data`, each holding
+    /// `(row_end - row_start) * width` values.
+    pub fn read_rows<R: Read + Seek>(
+        &self,
+        th: &mut ByteOrderReader<R>,
+        bands: std::ops::Range<usize>,
+        row_start: usize,
+        row_end: usize,
+        data: &mut [f64],
+    ) -> Result<(), Error> {
+        let width = self.width;
+        let window_cells = (row_end - row_start) * width;
+        let blocks_per_plane = self.blocks_across * self.blocks_down;
+        let block_samples = self.block_samples();
+        let bytes_per_pixel = block_samples * self.bytes_per_sample();
+        let planes = if self.is_planar() {
+            bands.clone()
+        } else {
+            0..1
+        };
+        let j_start = row_start / self.block_height;
+        let j_end = min((row_end + self.block_height - 1) / self.block_height, self.blocks_down);
+        let (mut red, mut green, mut blue, mut a): (u32, u32, u32, u32);
+        let mut value: u32;
+        let mut i: usize;
+        for plane in planes {
+            // The bands whose samples are contained within the blocks of this plane.
+            let first_band = if self.is_planar() { plane } else { 0 };
+            for j in j_start..j_end {
+                for k in 0..self.blocks_across {
+                    let mut blk_w = self.block_width;
+                    if !self.block_padding
+                        && k == self.blocks_across - 1
+                        && width % self.block_width != 0
+                    {
+                        blk_w = width % self.block_width;
                     }
-                }
-                _ => {
-                    return Err(Error::new(
-                        ErrorKind::InvalidData,
-                        format!(
-                            "Unknown data type: {:?}. Photomet interp: {:?}",
-                            r.configs.data_type, r.configs.photometric_interp
-                        ),
-                    ));
-                }
-            },
-            PhotometricInterpretation::RGB => {
-                match r.configs.data_type {
-                    DataType::RGB24 => {
-                        let mut bytes: [u8; 3] = [0u8; 3];
-                        let mut i: usize;
-                        for row in 0..r.configs.rows {
-                            for col in 0..r.configs.columns {
-                                i = row * r.configs.columns + col;
-                                let val = r.data[i] as u32;
-                                bytes[2] = ((val >> 16u32) & 0xFF) as u8; // blue
-                                bytes[1] = ((val >> 8u32) & 0xFF) as u8; // green
-                                bytes[0] = (val & 0xFF) as u8; // red
-                                write_bytes(&mut writer, &bytes)?;
+                    let row_bytes = if self.block_padding {
+                        self.block_width * bytes_per_pixel
+                    } else {
+                        blk_w * bytes_per_pixel
+                    };
+                    let xmin = k * self.block_width;
+                    let xmax = min(xmin + blk_w, width);
+                    let ymin = j * self.block_height;
+                    let ys = max(ymin, row_start);
+                    let ye = min(min(ymin + self.block_height, self.height), row_end);
+
+                    let buf = self.read_block(th, plane * blocks_per_plane + j * self.blocks_across + k)?;
+                    if buf.is_empty() {
+                        // GDAL supports sparse tiles. That is, if the block count is zero,
+                        // instead of reading the block, simply assume it is filled with either
+                        // nodata, if the value is defined, or zeros otherwise.
+                        for b in first_band..first_band + block_samples {
+                            if !bands.contains(&b) {
+                                continue;
+                            }
+                            for y in ys..ye {
+                                for x in xmin..xmax {
+                                    i = (y - row_start) * width + x;
+                                    data[(b - bands.start) * window_cells + i] = self.nodata;
+                                }
                             }
                         }
+                        continue;
                     }
-                    DataType::RGBA32 | DataType::U32 => {
-                        let mut i: usize;
-                        let mut bytes: [u8; 4] = [0u8; 4];
-                        for row in 0..r.configs.rows {
-                            for col in 0..r.configs.columns {
-                                i = row * r.configs.columns + col;
-                                let val = r.data[i] as u32;
-                                bytes[2] = ((val >> 16u32) & 0xFF) as u8; // blue
-                                bytes[1] = ((val >> 8u32) & 0xFF) as u8; // green
-                                bytes[0] = (val & 0xFF) as u8; // red
-                                bytes[3] = ((val >> 24u32) & 0xFF) as u8; // a
-                                write_bytes(&mut writer, &bytes)?;
+
+                    let mut bor = ByteOrderReader::<Cursor<Vec<u8>>>::new(Cursor::new(buf), self.endian);
+                    for y in ys..ye {
+                        bor.seek((y - ymin) * row_bytes);
+                        match self.mode {
+                            IM_GRAYINVERT | IM_GRAY => {
+                                // With a planar configuration, each block holds the samples
+                                // of a single band; otherwise the samples are interleaved.
+                                for x in xmin..xmax {
+                                    i = (y - row_start) * width + x;
+                                    for b in first_band..first_band + block_samples {
+                                        let z = read_sample(
+                                            &mut bor,
+                                            self.sample_format,
+                                            self.bits_per_sample[0],
+                                        )?;
+                                        if !bands.contains(&b) {
+                                            continue;
+                                        }
+                                        data[(b - bands.start) * window_cells + i] = z;
+                                    }
+                                }
+                            }
+                            IM_PALETTED => {
+                                for x in xmin..xmax {
+                                    i = (y - row_start) * width + x;
+                                    data[i] = read_sample(&mut bor, 1, self.bits_per_sample[0])?;
+                                }
+                            }
+                            IM_RGB | IM_NRGBA | IM_RGBA => {
+                                let has_alpha = self.mode != IM_RGB;
+                                for x in xmin..xmax {
+                                    if self.bits_per_sample[0] == 8 {
+                                        red = bor.read_u8()? as u32;
+                                        green = bor.read_u8()? as u32;
+                                        blue = bor.read_u8()? as u32;
+                                        a = if has_alpha { bor.read_u8()? as u32 } else { 255u32 };
+                                    } else if self.bits_per_sample[0] == 16 {
+                                        // the spec doesn't talk about 16-bit RGB images so
+                                        // I'm not sure why I bother with this. They specifically
+                                        // say that RGB images are 8-bits per channel. Anyhow,
+                                        // I rescale the 16-bits to an 8-bit channel for simplicity.
+                                        red = (bor.read_u16()? as f64 / 65535f64 * 255f64) as u32;
+                                        green = (bor.read_u16()? as f64 / 65535f64 * 255f64) as u32;
+                                        blue = (bor.read_u16()? as f64 / 65535f64 * 255f64) as u32;
+                                        a = if has_alpha {
+                                            (bor.read_u16()? as f64 / 65535f64 * 255f64) as u32
+                                        } else {
+                                            255u32
+                                        };
+                                    } else {
+                                        return Err(Error::new(
+                                            ErrorKind::InvalidData,
+                                            "The raster was not read correctly",
+                                        ));
+                                    }
+                                    value = (a << 24) | (blue << 16) | (green << 8) | red;
+                                    i = (y - row_start) * width + x;
+                                    data[i] = value as f64;
+                                }
+                            }
+                            _ => {
+                                return Err(Error::new(
+                                    ErrorKind::InvalidData,
+                                    "The raster was not read correctly",
+                                ))
                             }
                         }
                     }
-                    _ => {
-                        return Err(Error::new(
-                            ErrorKind::InvalidData,
-                            format!(
-                                "Unknown data type: {:?}. Photomet interp: {:?}",
-                                r.configs.data_type, r.configs.photometric_interp
-                            ),
-                        ));
-                    }
                 }
             }
-            PhotometricInterpretation::Paletted => {
-                return Err(Error::new(
-                    ErrorKind::InvalidData,
-                    "Paletted GeoTIFFs are currently unsupported for writing.",
-                ));
-            }
-            PhotometricInterpretation::Unknown => {
-                return Err(Error::new(
-                    ErrorKind::InvalidData,
-                    "Error while writing GeoTIFF file.",
-                ));
-            }
         }
+        Ok(())
+    }
+}
+
+/// Reads the pixel values of a GeoTIFF file a block of rows at a time.
+pub(crate) struct GeoTiffBlockSource {
+    reader: ByteOrderReader<BufReader<File>>,
+    layout: TiffImageLayout,
+}
+
+impl GeoTiffBlockSource {
+    pub fn new(
+        reader: ByteOrderReader<BufReader<File>>,
+        layout: TiffImageLayout,
+    ) -> GeoTiffBlockSource {
+        GeoTiffBlockSource {
+            reader: reader,
+            layout: layout,
+        }
+    }
+}
+
+impl BlockSource for GeoTiffBlockSource {
+    fn read_rows(&mut self, first_row: usize, data: &mut [f64]) -> Result<(), Error> {
+        let (width, height) = (self.layout.width, self.layout.height);
+        let mut row = first_row;
+        let mut offset = 0;
+        while offset < data.len() {
+            // a block may span the end of one band and the start of the next
+            let (band, r) = (row / height, row % height);
+            let n = min(height - r, (data.len() - offset) / width);
+            self.layout.read_rows(
+                &mut self.reader,
+                band..band + 1,
+                r,
+                r + n,
+                &mut data[offset..offset + n * width],
+            )?;
+            row += n;
+            offset += n * width;
+        }
+        Ok(())
+    }
+
+    fn preferred_rows(&self) -> usize {
+        self.layout.block_height
+    }
+}
+
+pub fn write_geotiff<'a>(r: &'a mut Raster) -> Result<(), Error> {
+    // We'll need to look at the configurations to see if compression should be used
+    let configs = whitebox_common::configs::get_configs()?;
+    let encoding = BlockEncoding::from_configs(&configs, r)?;
+    if configs.cog {
+        return cog::write_cog(
+            r,
+            &encoding,
+            configs.cog_tile_size,
+            configs.cog_overview_levels,
+            &configs.cog_resampling,
+        );
+    }
+    write_strip_geotiff(r, &encoding)
+}
+
+/// Writes a raster as a GeoTIFF whose image is stored in strips, each encoded with `encoding`.
+fn write_strip_geotiff(r: &mut Raster, encoding: &BlockEncoding) -> Result<(), Error> {
+    // get the ByteOrderWriter
+    let f = File::create(r.file_name.clone())?;
+    let mut writer = BufWriter::new(f);
+
+    // let mut bow = ByteOrderWriter::<BufWriter<File>>::new(writer, r.configs.endian);
+
+    // get the bytes per pixel
+    let total_bytes_per_pixel = r.configs.data_type.get_data_size();
+    if total_bytes_per_pixel == 0 {
+        return Err(Error::new(
+            ErrorKind::InvalidData,
+            format!(
+                "Unknown data type: {:?}. Photomet interp: {:?}",
+                r.configs.data_type, r.configs.photometric_interp
+            ),
+        ));
+    }
+
+    // Multi-band rasters are written with one sample per band, either interleaved
+    // by pixel (chunky) or one band after another (planar). Colour images are
+    // always written as a single set of packed RGB(A) samples.
+    let bands = if r.configs.photometric_interp == PhotometricInterpretation::RGB {
+        1
+    } else {
+        r.configs.bands.max(1)
+    };
+    let planar = bands > 1 && r.configs.interleave != BandInterleave::BIP;
+
+    // is it a BigTiff?
+    let is_big_tiff = if 8usize
+        + (r.configs.rows * r.configs.columns * bands) as usize * total_bytes_per_pixel
+        >= 4_000_000_000
+    {
+        true
+    } else {
+        false
+    };
+
+    let header_size = if !is_big_tiff { 8u64 } else { 16u64 };
+
+    // get the offset to the first ifd
+    let mut ifd_start_needs_extra_byte = false;
+    let mut ifd_start = if !encoding.is_compressed() {
+        let mut val = header_size
+            + (r.configs.rows * r.configs.columns * bands) as u64 * total_bytes_per_pixel as u64;
+        if val % 2 == 1 {
+            val += 1;
+            ifd_start_needs_extra_byte = true;
+        }
+        val
+    } else {
+        0u64
+    };
+
+    //////////////////////
+    // Write the header //
+    //////////////////////
+    if r.configs.endian == Endianness::LittleEndian {
+        write_bytes(&mut writer, "II".as_bytes())?;
+    } else {
+        write_bytes(&mut writer, "MM".as_bytes())?;
     }
 
-    if use_compression {
+    if !is_big_tiff {
+        // magic number
+        write_u16(&mut writer, r.configs.endian, 42u16)?;
+        // offset to first IFD
+        write_u32(&mut writer, r.configs.endian, ifd_start as u32)?;
+    } else {
+        // magic number
+        write_u16(&mut writer, r.configs.endian, 43u16)?;
+        // Bytesize of offsets
+        write_u16(&mut writer, r.configs.endian, 8u16)?;
+
+        write_u16(&mut writer, r.configs.endian, 0u16)?; // Always 0
+
+        // offset to first IFD
+        write_u64(&mut writer, r.configs.endian, ifd_start)?;
+    }
+
+    // Categorical rasters are written as paletted images if their colour table can be
+    // stored as a ColorMap, and otherwise as greyscale images.
+    if (r.configs.photometric_interp == PhotometricInterpretation::Categorical
+        || r.configs.photometric_interp == PhotometricInterpretation::Paletted)
+        && !has_colour_map(r)
+    {
+        r.configs.photometric_interp = PhotometricInterpretation::Continuous;
+    }
+
+    //////////////////////////
+    // Write the image data //
+    //////////////////////////
+    let mut strip_offsets = vec![];
+    let mut strip_byte_counts = vec![];
+    let mut current_offset = header_size;
+    match r.configs.photometric_interp {
+        PhotometricInterpretation::Continuous
+        | PhotometricInterpretation::Categorical
+        | PhotometricInterpretation::Paletted
+        | PhotometricInterpretation::Boolean
+        | PhotometricInterpretation::RGB => {
+            write_strips(
+                &mut writer,
+                r,
+                bands,
+                planar,
+                encoding,
+                &mut strip_offsets,
+                &mut strip_byte_counts,
+                &mut current_offset,
+            )?;
+        }
+        PhotometricInterpretation::Unknown => {
+            return Err(Error::new(
+                ErrorKind::InvalidData,
+                "Error while writing GeoTIFF file.",
+            ));
+        }
+    }
+
+    if encoding.is_compressed() {
         ifd_start = current_offset; // header_size + strip_byte_counts.iter().sum();
         if ifd_start % 2 == 1 {
             ifd_start += 1;
             ifd_start_needs_extra_byte = true;
         }
         if !is_big_tiff {
-            let _ = writer.seek(SeekFrom::Start(4));
+            writer.seek(SeekFrom::Start(4))?;
             write_u32(&mut writer, r.configs.endian, ifd_start as u32)?;
         } else {
-            let _ = writer.seek(SeekFrom::Start(8));
+            writer.seek(SeekFrom::Start(8))?;
             write_u64(&mut writer, r.configs.endian, ifd_start)?;
         }
-        let _ = writer.seek(SeekFrom::End(0));
+        writer.seek(SeekFrom::End(0))?;
     }
 
     // This is just because the IFD must start on a word (i.e. an even value). If the data are
     // single bytes, then this may not be the case.
     if ifd_start_needs_extra_byte {
-        write_u8(&mut writer, 0u8).expect("Error writing byte data.");
+        write_u8(&mut writer, 0u8)?;
     }
 
     ////////////////////////////
@@ -2495,6 +2064,135 @@ pub fn write_geotiff<'a>(r: &'a mut Raster) -> Result<(), Error> {
         r.configs.rows as u64,
     ));
 
+    push_sample_entries(r, bands, planar, &mut ifd_entries, &mut larger_values_data)?;
+
+    // Compression (259) and Predictor (317) tags
+    encoding.push_entries(&mut ifd_entries);
+
+    // StripOffsets tag (273)
+    push_array_entry(
+        TAG_STRIPOFFSETS,
+        &strip_offsets,
+        is_big_tiff,
+        &mut ifd_entries,
+        &mut larger_values_data,
+    )?;
+    // if !is_big_tiff {
+    //     ifd_entries.push(Entry::new(
+    //         TAG_STRIPOFFSETS,
+    //         DT_LONG,
+    //         r.configs.rows as u64,
+    //         larger_values_data.len() as u64,
+    //     ));
+    //     let row_length_in_bytes: u32 = r.configs.columns as u32 * total_bytes_per_pixel as u32;
+    //     for i in 0..r.configs.rows as u32 {
+    //         larger_values_data.write_u32(8u32 + row_length_in_bytes * i)?;
+    //     }
+    // } else {
+    //     ifd_entries.push(Entry::new(
+    //         TAG_STRIPOFFSETS,
+    //         DT_TIFF_LONG8,
+    //         r.configs.rows as u64,
+    //         larger_values_data.len() as u64,
+    //     ));
+    //     let row_length_in_bytes: u64 = r.configs.columns as u64 * total_bytes_per_pixel as u64;
+    //     for i in 0..r.configs.rows as u64 {
+    //         larger_values_data.write_u64(8u64 + row_length_in_bytes * i)?;
+    //     }
+    // }
+
+    // RowsPerStrip tag (278)
+    ifd_entries.push(Entry::new(TAG_ROWSPERSTRIP, DT_SHORT, 1u64, 1u64));
+
+    // StripByteCounts tag (279)
+    push_array_entry(
+        TAG_STRIPBYTECOUNTS,
+        &strip_byte_counts,
+        is_big_tiff,
+        &mut ifd_entries,
+        &mut larger_values_data,
+    )?;
+
+    /*
+    if !is_big_tiff {
+        ifd_entries.push(Entry::new(
+            TAG_STRIPBYTECOUNTS,
+            DT_LONG,
+            r.configs.rows as u64,
+            larger_values_data.len() as u64,
+        ));
+        let total_bytes_per_pixel = match r.configs.data_type {
+            DataType::I8 | DataType::U8 => 1u32,
+            DataType::I16 | DataType::U16 => 2u32,
+            DataType::I32 | DataType::U32 | DataType::F32 => 4u32,
+            DataType::I64 | DataType::U64 | DataType::F64 => 8u32,
+            DataType::RGB24 => 3u32,
+            DataType::RGBA32 => 4u32,
+            DataType::RGB48 => 6u32,
+            _ => {
+                return Err(Error::new(ErrorKind::InvalidData, "Unknown data type."));
+            }
+        };
+        let row_length_in_bytes: u32 = r.configs.columns as u32 * total_bytes_per_pixel;
+        for _ in 0..r.configs.rows as u32 {
+            larger_values_data.write_u32(row_length_in_bytes)?;
+        }
+    } else {
+        ifd_entries.push(Entry::new(
+            TAG_STRIPBYTECOUNTS,
+            DT_TIFF_LONG8,
+            r.configs.rows as u64,
+            larger_values_data.len() as u64,
+        ));
+        let total_bytes_per_pixel = match r.configs.data_type {
+            DataType::I8 | DataType::U8 => 1u64,
+            DataType::I16 | DataType::U16 => 2u64,
+            DataType::I32 | DataType::U32 | DataType::F32 => 4u64,
+            DataType::I64 | DataType::U64 | DataType::F64 => 8u64,
+            DataType::RGB24 => 3u64,
+            DataType::RGBA32 => 4u64,
+            DataType::RGB48 => 6u64,
+            _ => {
+                return Err(Error::new(ErrorKind::InvalidData, "Unknown data type."));
+            }
+        };
+        let row_length_in_bytes: u64 = r.configs.columns as u64 * total_bytes_per_pixel;
+        for _ in 0..r.configs.rows as u32 {
+            larger_values_data.write_u64(row_length_in_bytes)?;
+        }
+    }
+    */
+
+    push_descriptive_entries(&mut ifd_entries, &mut larger_values_data)?;
+    push_nodata_entry(r, is_big_tiff, &mut ifd_entries, &mut larger_values_data)?;
+    push_georeferencing_entries(r, &mut ifd_entries, &mut larger_values_data)?;
+
+    ///////////////////
+    // Write the IFD //
+    ///////////////////
+    write_ifd(
+        &mut writer,
+        r.configs.endian,
+        is_big_tiff,
+        ifd_start,
+        ifd_entries,
+        larger_values_data.get_inner(),
+        0u64,
+    )?;
+    writer.flush()?;
+
+    Ok(())
+}
+
+/// Adds the entries describing the samples of each pixel, i.e. their number, size,
+/// format, arrangement and interpretation, to an IFD.
+fn push_sample_entries(
+    r: &Raster,
+    bands: usize,
+    planar: bool,
+    ifd_entries: &mut Vec<Entry>,
+    larger_values_data: &mut ByteOrderWriter<Vec<u8>>,
+) -> Result<(), Error> {
     let bits_per_sample = match r.configs.data_type {
         DataType::I8 | DataType::U8 => 8u16,
         DataType::I16 | DataType::U16 => 16u16,
@@ -2509,10 +2207,10 @@ pub fn write_geotiff<'a>(r: &'a mut Raster) -> Result<(), Error> {
     };
 
     let samples_per_pixel = match r.configs.data_type {
-        DataType::I8 | DataType::U8 => 1u16,
-        DataType::I16 | DataType::U16 => 1u16,
-        DataType::I32 | DataType::U32 | DataType::F32 => 1u16,
-        DataType::I64 | DataType::U64 | DataType::F64 => 1u16,
+        DataType::I8 | DataType::U8 => bands as u16,
+        DataType::I16 | DataType::U16 => bands as u16,
+        DataType::I32 | DataType::U32 | DataType::F32 => bands as u16,
+        DataType::I64 | DataType::U64 | DataType::F64 => bands as u16,
         DataType::RGB24 => 3u16,
         DataType::RGBA32 => 4u16,
         DataType::RGB48 => 3u16,
@@ -2543,23 +2241,6 @@ pub fn write_geotiff<'a>(r: &'a mut Raster) -> Result<(), Error> {
         }
     }
 
-    // Compression tag (259)
-    if use_compression {
-        ifd_entries.push(Entry::new(
-            TAG_COMPRESSION,
-            DT_SHORT,
-            1u64,
-            COMPRESS_DEFLATE as u64,
-        ));
-    } else {
-        ifd_entries.push(Entry::new(
-            TAG_COMPRESSION,
-            DT_SHORT,
-            1u64,
-            COMPRESS_NONE as u64,
-        ));
-    }
-
     // PhotometricInterpretation tag (262)
     let pi = match r.configs.photometric_interp {
         PhotometricInterpretation::Continuous => PI_BLACKISZERO,
@@ -2580,74 +2261,27 @@ pub fn write_geotiff<'a>(r: &'a mut Raster) -> Result<(), Error> {
         pi as u64,
     ));
 
-    // StripOffsets tag (273)
-    if !is_big_tiff {
-        ifd_entries.push(Entry::new(
-            TAG_STRIPOFFSETS,
-            DT_LONG,
-            r.configs.rows as u64,
-            larger_values_data.len() as u64,
-        ));
-        if use_compression {
-            for val in strip_offsets {
-                larger_values_data
-                    .write_u32(val as u32)
-                    .expect("Error writing the TIFF strip offsets tag");
-            }
-        } else {
-            let row_length_in_bytes: u32 = r.configs.columns as u32 * total_bytes_per_pixel as u32;
-            for i in 0..r.configs.rows as u32 {
-                larger_values_data
-                    .write_u32(header_size as u32 + row_length_in_bytes * i)
-                    .expect("Error writing the TIFF strip offsets tag");
-            }
-        }
-    } else {
-        ifd_entries.push(Entry::new(
-            TAG_STRIPOFFSETS,
-            DT_TIFF_LONG8,
-            r.configs.rows as u64,
-            larger_values_data.len() as u64,
-        ));
-        if use_compression {
-            for val in strip_offsets {
-                larger_values_data
-                    .write_u64(val)
-                    .expect("Error writing the TIFF strip offsets tag");
-            }
-        } else {
-            let row_length_in_bytes: u64 = r.configs.columns as u64 * total_bytes_per_pixel as u64;
-            for i in 0..r.configs.rows as u64 {
-                larger_values_data
-                    .write_u64(header_size + row_length_in_bytes * i)
-                    .expect("Error writing the TIFF strip offsets");
-            }
-        }
-    }
-    // if !is_big_tiff {
-    //     ifd_entries.push(Entry::new(
-    //         TAG_STRIPOFFSETS,
-    //         DT_LONG,
-    //         r.configs.rows as u64,
-    //         larger_values_data.len() as u64,
-    //     ));
-    //     let row_length_in_bytes: u32 = r.configs.columns as u32 * total_bytes_per_pixel as u32;
-    //     for i in 0..r.configs.rows as u32 {
-    //         larger_values_data.write_u32(8u32 + row_length_in_bytes * i)?;
-    //     }
-    // } else {
-    //     ifd_entries.push(Entry::new(
-    //         TAG_STRIPOFFSETS,
-    //         DT_TIFF_LONG8,
-    //         r.configs.rows as u64,
-    //         larger_values_data.len() as u64,
-    //     ));
-    //     let row_length_in_bytes: u64 = r.configs.columns as u64 * total_bytes_per_pixel as u64;
-    //     for i in 0..r.configs.rows as u64 {
-    //         larger_values_data.write_u64(8u64 + row_length_in_bytes * i)?;
-    //     }
-    // }
-
+    if pi == PI_PALETTED {
+        // ColorMap tag (320); the red, green, and blue components of each of the 2^bits
+        // colours, in turn, as 16-bit values.
+        let num_colours = 1usize << bits_per_sample;
+        ifd_entries.push(Entry::new(
+            TAG_COLORMAP,
+            DT_SHORT,
+            3 * num_colours as u64,
+            larger_values_data.len() as u64,
+        ));
+        let colours: Vec<Colour> = (0..num_colours)
+            .map(|i| r.configs.colour_table.get_colour(i as i64).unwrap_or((0, 0, 0, 255)))
+            .collect();
+        for component in 0..3 {
+            for &(red, green, blue, _) in &colours {
+                let c = [red, green, blue][component];
+                larger_values_data.write_u16(c as u16 * 257)?;
+            }
+        }
+    }
+
     // SamplesPerPixel tag (277)
     ifd_entries.push(Entry::new(
         TAG_SAMPLESPERPIXEL,
@@ -2656,127 +2290,88 @@ pub fn write_geotiff<'a>(r: &'a mut Raster) -> Result<(), Error> {
         samples_per_pixel as u64,
     ));
 
-    // RowsPerStrip tag (278)
-    ifd_entries.push(Entry::new(TAG_ROWSPERSTRIP, DT_SHORT, 1u64, 1u64));
-
-    // StripByteCounts tag (279)
-    if !is_big_tiff {
+    if bands > 1 {
+        // PlanarConfiguration tag (284)
         ifd_entries.push(Entry::new(
-            TAG_STRIPBYTECOUNTS,
-            DT_LONG,
-            r.configs.rows as u64,
-            larger_values_data.len() as u64,
+            TAG_PLANARCONFIGURATION,
+            DT_SHORT,
+            1u64,
+            if planar { 2u64 } else { 1u64 },
         ));
-        let total_bytes_per_pixel = match r.configs.data_type {
-            DataType::I8 | DataType::U8 => 1u32,
-            DataType::I16 | DataType::U16 => 2u32,
-            DataType::I32 | DataType::U32 | DataType::F32 => 4u32,
-            DataType::I64 | DataType::U64 | DataType::F64 => 8u32,
-            DataType::RGB24 => 3u32,
-            DataType::RGBA32 => 4u32,
-            DataType::RGB48 => 6u32,
-            _ => {
-                return Err(Error::new(ErrorKind::InvalidData, "Unknown data type."));
-            }
-        };
-        if use_compression {
-            for val in strip_byte_counts {
-                larger_values_data
-                    .write_u32(val as u32)
-                    .expect("Error writing the TIFF strip byte counts tag");
-            }
-        } else {
-            let row_length_in_bytes: u32 = r.configs.columns as u32 * total_bytes_per_pixel;
-            for _ in 0..r.configs.rows as u32 {
-                larger_values_data
-                    .write_u32(row_length_in_bytes)
-                    .expect("Error writing the TIFF strip byte counts tag");
-            }
+    }
+    if r.configs.photometric_interp == PhotometricInterpretation::RGB {
+        if samples_per_pixel == 4 {
+            // ExtraSamples tag (338)
+            ifd_entries.push(Entry::new(TAG_EXTRASAMPLES, DT_SHORT, 1u64, 2u64));
         }
-    } else {
-        ifd_entries.push(Entry::new(
-            TAG_STRIPBYTECOUNTS,
-            DT_TIFF_LONG8,
-            r.configs.rows as u64,
-            larger_values_data.len() as u64,
-        ));
-        let total_bytes_per_pixel = match r.configs.data_type {
-            DataType::I8 | DataType::U8 => 1u64,
-            DataType::I16 | DataType::U16 => 2u64,
-            DataType::I32 | DataType::U32 | DataType::F32 => 4u64,
-            DataType::I64 | DataType::U64 | DataType::F64 => 8u64,
-            DataType::RGB24 => 3u64,
-            DataType::RGBA32 => 4u64,
-            DataType::RGB48 => 6u64,
-            _ => {
-                return Err(Error::new(ErrorKind::InvalidData, "Unknown data type."));
-            }
-        };
-        if use_compression {
-            for val in strip_byte_counts {
-                larger_values_data
-                    .write_u64(val)
-                    .expect("Error writing the TIFF strip byte counts tag");
-            }
+    } else if bands > 1 {
+        // ExtraSamples tag (338); all but the first band are unspecified extra samples.
+        if bands == 2 {
+            ifd_entries.push(Entry::new(TAG_EXTRASAMPLES, DT_SHORT, 1u64, 0u64));
         } else {
-            let row_length_in_bytes: u64 = r.configs.columns as u64 * total_bytes_per_pixel;
-            for _ in 0..r.configs.rows as u32 {
-                larger_values_data
-                    .write_u64(row_length_in_bytes)
-                    .expect("Error writing the TIFF strip byte counts tag");
+            ifd_entries.push(Entry::new(
+                TAG_EXTRASAMPLES,
+                DT_SHORT,
+                (bands - 1) as u64,
+                larger_values_data.len() as u64,
+            ));
+            for _ in 1..bands {
+                larger_values_data.write_u16(0u16)?;
             }
         }
     }
-    /*
-    if !is_big_tiff {
+
+    // SampleFormat tag (339)
+    let samples_format = match r.configs.data_type {
+        DataType::U8 | DataType::U16 | DataType::U32 | DataType::U64 => 1u16,
+        DataType::I8 | DataType::I16 | DataType::I32 | DataType::I64 => 2u16,
+        DataType::F32 | DataType::F64 => 3u16,
+        DataType::RGB24 | DataType::RGBA32 | DataType::RGB48 => 1u16,
+        _ => {
+            return Err(Error::new(ErrorKind::InvalidData, "Unknown data type."));
+        }
+    };
+    if samples_per_pixel == 1 {
         ifd_entries.push(Entry::new(
-            TAG_STRIPBYTECOUNTS,
-            DT_LONG,
-            r.configs.rows as u64,
-            larger_values_data.len() as u64,
+            TAG_SAMPLEFORMAT,
+            DT_SHORT,
+            samples_per_pixel as u64,
+            samples_format as u64,
         ));
-        let total_bytes_per_pixel = match r.configs.data_type {
-            DataType::I8 | DataType::U8 => 1u32,
-            DataType::I16 | DataType::U16 => 2u32,
-            DataType::I32 | DataType::U32 | DataType::F32 => 4u32,
-            DataType::I64 | DataType::U64 | DataType::F64 => 8u32,
-            DataType::RGB24 => 3u32,
-            DataType::RGBA32 => 4u32,
-            DataType::RGB48 => 6u32,
-            _ => {
-                return Err(Error::new(ErrorKind::InvalidData, "Unknown data type."));
-            }
-        };
-        let row_length_in_bytes: u32 = r.configs.columns as u32 * total_bytes_per_pixel;
-        for _ in 0..r.configs.rows as u32 {
-            larger_values_data.write_u32(row_length_in_bytes)?;
-        }
     } else {
         ifd_entries.push(Entry::new(
-            TAG_STRIPBYTECOUNTS,
-            DT_TIFF_LONG8,
-            r.configs.rows as u64,
+            TAG_SAMPLEFORMAT,
+            DT_SHORT,
+            samples_per_pixel as u64,
             larger_values_data.len() as u64,
         ));
-        let total_bytes_per_pixel = match r.configs.data_type {
-            DataType::I8 | DataType::U8 => 1u64,
-            DataType::I16 | DataType::U16 => 2u64,
-            DataType::I32 | DataType::U32 | DataType::F32 => 4u64,
-            DataType::I64 | DataType::U64 | DataType::F64 => 8u64,
-            DataType::RGB24 => 3u64,
-            DataType::RGBA32 => 4u64,
-            DataType::RGB48 => 6u64,
-            _ => {
-                return Err(Error::new(ErrorKind::InvalidData, "Unknown data type."));
-            }
-        };
-        let row_length_in_bytes: u64 = r.configs.columns as u64 * total_bytes_per_pixel;
-        for _ in 0..r.configs.rows as u32 {
-            larger_values_data.write_u64(row_length_in_bytes)?;
+        for _ in 0..samples_per_pixel {
+            larger_values_data.write_u16(samples_format)?;
         }
     }
-    */
+    Ok(())
+}
+
+/// Returns true if a categorical raster's colour table can be written as the ColorMap of
+/// a paletted image, which requires a single band of 8-bit or 16-bit unsigned values.
+pub(crate) fn has_colour_map(r: &Raster) -> bool {
+    let max_value = match r.configs.data_type {
+        DataType::U8 => 255,
+        DataType::U16 => 65535,
+        _ => return false,
+    };
+    r.configs.bands <= 1
+        && match r.configs.colour_table.value_range() {
+            Some((min, max)) => min >= 0 && max <= max_value,
+            None => false,
+        }
+}
 
+/// Adds the resolution and software entries to an IFD.
+fn push_descriptive_entries(
+    ifd_entries: &mut Vec<Entry>,
+    larger_values_data: &mut ByteOrderWriter<Vec<u8>>,
+) -> Result<(), Error> {
     // There is currently no support for storing the image resolution, so give a bogus value of 72x72 dpi.
     // XResolution tag (282)
     ifd_entries.push(Entry::new(
@@ -2812,41 +2407,68 @@ pub fn write_geotiff<'a>(r: &'a mut Raster) -> Result<(), Error> {
         larger_values_data.len() as u64,
     ));
     larger_values_data.write_bytes(&soft_bytes)?;
+    Ok(())
+}
 
-    if samples_per_pixel == 4 {
-        // ExtraSamples tag (338)
-        ifd_entries.push(Entry::new(TAG_EXTRASAMPLES, DT_SHORT, 1u64, 2u64));
-    }
-
-    // SampleFormat tag (339)
-    let samples_format = match r.configs.data_type {
-        DataType::U8 | DataType::U16 | DataType::U32 | DataType::U64 => 1u16,
-        DataType::I8 | DataType::I16 | DataType::I32 | DataType::I64 => 2u16,
-        DataType::F32 | DataType::F64 => 3u16,
-        DataType::RGB24 | DataType::RGBA32 | DataType::RGB48 => 1u16,
-        _ => {
-            return Err(Error::new(ErrorKind::InvalidData, "Unknown data type."));
+/// Adds the GDAL NoData entry to an IFD.
+fn push_nodata_entry(
+    r: &Raster,
+    is_big_tiff: bool,
+    ifd_entries: &mut Vec<Entry>,
+    larger_values_data: &mut ByteOrderWriter<Vec<u8>>,
+) -> Result<(), Error> {
+    // TAG_GDAL_NODATA tag (42113)
+    let nodata_str = format!("{}", r.configs.nodata);
+    let mut nodata_bytes = nodata_str.into_bytes();
+    if !is_big_tiff {
+        // we buffer this string with spaces to ensure that it is
+        // long enough to be printed to larger_values_data.
+        if nodata_bytes.len() < 4 {
+            for _ in 0..(4 - nodata_bytes.len()) {
+                nodata_bytes.push(32);
+            }
         }
-    };
-    if samples_per_pixel == 1 {
+        if nodata_bytes.len() % 2 == 0 {
+            nodata_bytes.push(32);
+        }
+        nodata_bytes.push(0);
         ifd_entries.push(Entry::new(
-            TAG_SAMPLEFORMAT,
-            DT_SHORT,
-            samples_per_pixel as u64,
-            samples_format as u64,
+            TAG_GDAL_NODATA,
+            DT_ASCII,
+            nodata_bytes.len() as u64,
+            larger_values_data.len() as u64,
         ));
+        larger_values_data.write_bytes(&nodata_bytes)?;
     } else {
+        // we buffer this string with spaces to ensure that it is
+        // long enough to be printed to larger_values_data.
+        if nodata_bytes.len() < 8 {
+            for _ in 0..(8 - nodata_bytes.len()) {
+                nodata_bytes.push(32);
+            }
+        }
+        if nodata_bytes.len() % 2 == 0 {
+            nodata_bytes.push(32);
+        }
+        nodata_bytes.push(0);
         ifd_entries.push(Entry::new(
-            TAG_SAMPLEFORMAT,
-            DT_SHORT,
-            samples_per_pixel as u64,
+            TAG_GDAL_NODATA,
+            DT_ASCII,
+            nodata_bytes.len() as u64,
             larger_values_data.len() as u64,
         ));
-        for _ in 0..samples_per_pixel {
-            larger_values_data.write_u16(samples_format)?;
-        }
+        larger_values_data.write_bytes(&nodata_bytes)?;
     }
+    Ok(())
+}
 
+/// Adds the GeoTIFF entries, which locate the image in model space and describe
+/// its coordinate reference system, to an IFD.
+fn push_georeferencing_entries(
+    r: &Raster,
+    ifd_entries: &mut Vec<Entry>,
+    larger_values_data: &mut ByteOrderWriter<Vec<u8>>,
+) -> Result<(), Error> {
     // ModelPixelScaleTag tag (33550)
     if r.configs.model_pixel_scale[0] == 0f64
         && r.configs.model_tiepoint.is_empty()
@@ -2890,70 +2512,27 @@ pub fn write_geotiff<'a>(r: &'a mut Raster) -> Result<(), Error> {
     } else if !r.configs.model_tiepoint.is_empty() {
         // ModelTiepointTag tag (33922)
         ifd_entries.push(Entry::new(
-            TAG_MODELTIEPOINTTAG,
-            DT_DOUBLE,
-            r.configs.model_tiepoint.len() as u64,
-            larger_values_data.len() as u64,
-        ));
-        for i in 0..r.configs.model_tiepoint.len() {
-            larger_values_data.write_f64(r.configs.model_tiepoint[i])?;
-        }
-    }
-
-    if r.configs.model_transformation[0] != 0f64 {
-        // ModelTransformationTag tag (33920)
-        ifd_entries.push(Entry::new(
-            TAG_MODELTRANSFORMATIONTAG,
-            DT_DOUBLE,
-            16u64,
-            larger_values_data.len() as u64,
-        ));
-        for i in 0..16 {
-            larger_values_data.write_f64(r.configs.model_transformation[i])?;
-        }
-    }
-
-    // TAG_GDAL_NODATA tag (42113)
-    let nodata_str = format!("{}", r.configs.nodata);
-    let mut nodata_bytes = nodata_str.into_bytes();
-    if !is_big_tiff {
-        // we buffer this string with spaces to ensure that it is
-        // long enough to be printed to larger_values_data.
-        if nodata_bytes.len() < 4 {
-            for _ in 0..(4 - nodata_bytes.len()) {
-                nodata_bytes.push(32);
-            }
-        }
-        if nodata_bytes.len() % 2 == 0 {
-            nodata_bytes.push(32);
-        }
-        nodata_bytes.push(0);
-        ifd_entries.push(Entry::new(
-            TAG_GDAL_NODATA,
-            DT_ASCII,
-            nodata_bytes.len() as u64,
-            larger_values_data.len() as u64,
-        ));
-        larger_values_data.write_bytes(&nodata_bytes)?;
-    } else {
-        // we buffer this string with spaces to ensure that it is
-        // long enough to be printed to larger_values_data.
-        if nodata_bytes.len() < 8 {
-            for _ in 0..(8 - nodata_bytes.len()) {
-                nodata_bytes.push(32);
-            }
-        }
-        if nodata_bytes.len() % 2 == 0 {
-            nodata_bytes.push(32);
+            TAG_MODELTIEPOINTTAG,
+            DT_DOUBLE,
+            r.configs.model_tiepoint.len() as u64,
+            larger_values_data.len() as u64,
+        ));
+        for i in 0..r.configs.model_tiepoint.len() {
+            larger_values_data.write_f64(r.configs.model_tiepoint[i])?;
         }
-        nodata_bytes.push(0);
+    }
+
+    if r.configs.model_transformation[0] != 0f64 {
+        // ModelTransformationTag tag (33920)
         ifd_entries.push(Entry::new(
-            TAG_GDAL_NODATA,
-            DT_ASCII,
-            nodata_bytes.len() as u64,
+            TAG_MODELTRANSFORMATIONTAG,
+            DT_DOUBLE,
+            16u64,
             larger_values_data.len() as u64,
         ));
-        larger_values_data.write_bytes(&nodata_bytes)?;
+        for i in 0..16 {
+            larger_values_data.write_f64(r.configs.model_transformation[i])?;
+        }
     }
 
     let kw_map = get_keyword_map();
@@ -3261,102 +2840,165 @@ pub fn write_geotiff<'a>(r: &'a mut Raster) -> Result<(), Error> {
             larger_values_data.write_bytes(&ascii_params_bytes)?;
         }
     }
+    Ok(())
+}
+
+/// Adds an entry holding an array of offsets or byte counts to an IFD. A single value
+/// is stored within the entry itself.
+fn push_array_entry(
+    tag: u16,
+    values: &[u64],
+    is_big_tiff: bool,
+    ifd_entries: &mut Vec<Entry>,
+    larger_values_data: &mut ByteOrderWriter<Vec<u8>>,
+) -> Result<(), Error> {
+    let ifd_type = if !is_big_tiff { DT_LONG } else { DT_TIFF_LONG8 };
+    if values.len() == 1 {
+        ifd_entries.push(Entry::new(tag, ifd_type, 1u64, values[0]));
+        return Ok(());
+    }
+    ifd_entries.push(Entry::new(
+        tag,
+        ifd_type,
+        values.len() as u64,
+        larger_values_data.len() as u64,
+    ));
+    for &val in values {
+        if !is_big_tiff {
+            larger_values_data.write_u32(val as u32)?;
+        } else {
+            larger_values_data.write_u64(val)?;
+        }
+    }
+    Ok(())
+}
+
+/// Returns the size, in bytes, of an IFD and the values that follow it.
+fn get_ifd_size(is_big_tiff: bool, num_entries: usize, larger_values_len: usize) -> u64 {
+    if !is_big_tiff {
+        2u64 + num_entries as u64 * 12u64 + 4u64 + larger_values_len as u64
+    } else {
+        8u64 + num_entries as u64 * 20u64 + 8u64 + larger_values_len as u64
+    }
+}
 
+/// Writes an IFD, which begins at file offset `ifd_start`, followed by the values of
+/// its entries that are too large to fit within the entries themselves. `next_ifd` is
+/// the offset of the following IFD, or zero if this is the last one.
+///
+/// An entry holding a single SHORT, LONG, or LONG8 holds that value in its `offset`;
+/// the values of every other entry are in `larger_values_data`, beginning at `offset`.
+fn write_ifd<W: Write>(
+    writer: &mut BufWriter<W>,
+    endian: Endianness,
+    is_big_tiff: bool,
+    ifd_start: u64,
+    mut ifd_entries: Vec<Entry>,
+    larger_values_data: &[u8],
+    next_ifd: u64,
+) -> Result<(), Error> {
     ///////////////////
     // Write the IFD //
     ///////////////////
 
     // Number of Directory Entries.
     if !is_big_tiff {
-        write_u16(&mut writer, r.configs.endian, ifd_entries.len() as u16)?;
-
-        // Sort the IFD entries
-        ifd_entries.sort_by(|a, b| a.tag.cmp(&b.tag));
-
-        // Write the entries
-        let ifd_length = 2u64 + ifd_entries.len() as u64 * 12u64 + 4u64;
-
-        for ifde in ifd_entries {
-            write_u16(&mut writer, r.configs.endian, ifde.tag)?; // Tag
-            write_u16(&mut writer, r.configs.endian, ifde.ifd_type)?; // Field type
-            write_u32(&mut writer, r.configs.endian, ifde.num_values as u32)?; // Num of values
-            if ifde.ifd_type == DT_SHORT && ifde.num_values == 1 {
-                // it's a value
-                write_u16(&mut writer, r.configs.endian, ifde.offset as u16)?; // Value
-                write_u16(&mut writer, r.configs.endian, 0u16)?; // Fill the remaining 2 right bytes of the u32
-            } else if ifde.ifd_type == DT_LONG && ifde.num_values == 1 {
-                // it's a value
-                write_u32(&mut writer, r.configs.endian, ifde.offset as u32)?;
-            } else if ifde.ifd_type == DT_SHORT && ifde.num_values == 2 {
-                // I'm not really sure about this one. Two shorts will fit in the value_offset, but will they be interpreted correctly?
-                write_u32(&mut writer, r.configs.endian, ifde.offset as u32)?; // Value
-            } else {
-                // it's an offset
-                write_u32(
-                    &mut writer,
-                    r.configs.endian,
-                    ifd_start as u32 + ifd_length as u32 + ifde.offset as u32,
-                )?;
-            }
-        }
-
-        // 4-byte offset of the next IFD; Note, only single image TIFFs are currently supported
-        // and therefore, this will always be set to '0'.
-        write_u32(&mut writer, r.configs.endian, 0u32)?;
+        write_u16(writer, endian, ifd_entries.len() as u16)?;
     } else {
-        write_u64(&mut writer, r.configs.endian, ifd_entries.len() as u64)?;
-
-        // Sort the IFD entries
-        ifd_entries.sort_by(|a, b| a.tag.cmp(&b.tag));
+        write_u64(writer, endian, ifd_entries.len() as u64)?;
+    }
 
-        // Write the entries
-        let ifd_length = 8u64 + ifd_entries.len() as u64 * 20u64 + 8u64;
+    // Sort the IFD entries
+    ifd_entries.sort_by(|a, b| a.tag.cmp(&b.tag));
 
-        for ifde in ifd_entries {
-            write_u16(&mut writer, r.configs.endian, ifde.tag)?; // Tag
-            write_u16(&mut writer, r.configs.endian, ifde.ifd_type)?; // Field type
-            write_u64(&mut writer, r.configs.endian, ifde.num_values)?; // Num of values
-            if ifde.ifd_type == DT_SHORT && ifde.num_values == 1 {
-                // it's a value
-                write_u16(&mut writer, r.configs.endian, ifde.offset as u16)?; // Value
-                write_u16(&mut writer, r.configs.endian, 0u16)?; // Fill the remaining bytes of the u64
-                write_u32(&mut writer, r.configs.endian, 0u32)?; // Fill the remaining bytes of the u64
-            } else if ifde.ifd_type == DT_SHORT && ifde.num_values == 2 {
-                // I'm not really sure about this one. Two shorts will fit in the value_offset, but will they be interpreted correctly?
-                write_u32(&mut writer, r.configs.endian, ifde.offset as u32)?; // Value
-                write_u32(&mut writer, r.configs.endian, 0u32)?; // Fill the remaining bytes of the u64
-            } else if ifde.ifd_type == DT_LONG && ifde.num_values == 1 {
-                // it's a value
-                write_u32(&mut writer, r.configs.endian, ifde.offset as u32)?;
-                write_u32(&mut writer, r.configs.endian, 0u32)?; // Fill the remaining bytes of the u64
-            } else if (ifde.ifd_type == DT_LONG && ifde.num_values == 2)
-                || (ifde.ifd_type == DT_TIFF_LONG8 && ifde.num_values == 1)
-            {
-                // it's a value
-                write_u64(&mut writer, r.configs.endian, ifde.offset)?;
-            } else {
-                // it's an offset
-                write_u64(
-                    &mut writer,
-                    r.configs.endian,
-                    ifd_start + ifd_length + ifde.offset,
-                )?;
-            }
+    // Write the entries
+    let ifd_length = get_ifd_size(is_big_tiff, ifd_entries.len(), 0);
+    for ifde in ifd_entries {
+        write_u16(writer, endian, ifde.tag)?; // Tag
+        write_u16(writer, endian, ifde.ifd_type)?; // Field type
+        if !is_big_tiff {
+            write_u32(writer, endian, ifde.num_values as u32)?; // Num of values
+        } else {
+            write_u64(writer, endian, ifde.num_values)?; // Num of values
         }
+        write_entry_value(
+            writer,
+            endian,
+            is_big_tiff,
+            &ifde,
+            ifd_start + ifd_length,
+            larger_values_data,
+        )?;
+    }
 
-        // 4-byte offset of the next IFD; Note, only single image TIFFs are currently supported
-        // and therefore, this will always be set to '0'.
-        write_u64(&mut writer, r.configs.endian, 0u64)?;
+    // Offset of the next IFD
+    if !is_big_tiff {
+        write_u32(writer, endian, next_ifd as u32)?;
+    } else {
+        write_u64(writer, endian, next_ifd)?;
     }
 
     //////////////////////////////////
     // Write the larger_values_data //
     //////////////////////////////////
-    write_bytes(&mut writer, larger_values_data.get_inner())?;
+    write_bytes(writer, larger_values_data)?;
+
+    Ok(())
+}
 
+/// Writes the value field of an IFD entry, which is 4 bytes in a classic TIFF and 8 in a
+/// BigTIFF. Values that fit within the field are stored there, left-justified; otherwise
+/// the field holds the file offset of the values, which follow the IFD at `values_start`.
+fn write_entry_value<W: Write>(
+    writer: &mut BufWriter<W>,
+    endian: Endianness,
+    is_big_tiff: bool,
+    ifde: &Entry,
+    values_start: u64,
+    larger_values_data: &[u8],
+) -> Result<(), Error> {
+    let field_size = if !is_big_tiff { 4usize } else { 8usize };
+    let written = if ifde.num_values == 1 && ifde.ifd_type == DT_SHORT {
+        write_u16(writer, endian, ifde.offset as u16)?;
+        2
+    } else if ifde.num_values == 1 && ifde.ifd_type == DT_LONG {
+        write_u32(writer, endian, ifde.offset as u32)?;
+        4
+    } else if ifde.num_values == 1 && ifde.ifd_type == DT_TIFF_LONG8 {
+        write_u64(writer, endian, ifde.offset)?;
+        8
+    } else {
+        let size = field_type_size(ifde.ifd_type) * ifde.num_values as usize;
+        if size <= field_size {
+            let start = ifde.offset as usize;
+            write_bytes(writer, &larger_values_data[start..start + size])?;
+            size
+        } else if !is_big_tiff {
+            write_u32(writer, endian, (values_start + ifde.offset) as u32)?;
+            4
+        } else {
+            write_u64(writer, endian, values_start + ifde.offset)?;
+            8
+        }
+    };
+    // Fill the remaining bytes of the field
+    for _ in written..field_size {
+        write_u8(writer, 0u8)?;
+    }
     Ok(())
 }
 
+/// Returns the size, in bytes, of a single value of an IFD field type.
+fn field_type_size(ifd_type: u16) -> usize {
+    match ifd_type {
+        DT_SHORT | DT_SSHORT => 2,
+        DT_LONG | DT_SLONG | DT_FLOAT => 4,
+        DT_RATIONAL | DT_SRATIONAL | DT_DOUBLE => 8,
+        DT_TIFF_LONG8 | DT_TIFF_SLONG8 | DT_TIFF_IFD8 => 8,
+        _ => 1,
+    }
+}
+
 /*
 pub fn write_geotiff<'a>(r: &'a mut Raster) -> Result<(), Error> {
     // get the ByteOrderWriter
@@ -3463,7 +3105,7 @@ pub fn write_geotiff<'a>(r: &'a mut Raster) -> Result<(), Error> {
                     for row in 0..r.configs.rows {
                         for col in 0..r.configs.columns {
                             i = row * r.configs.columns + col;
-                            bow.write_f64(r.data[i])?;
+                            bow.write_f64(r.get_cell(i))?;
                         }
                     }
                 }
@@ -3474,12 +3116,12 @@ pub fn write_geotiff<'a>(r: &'a mut Raster) -> Result<(), Error> {
                         if r.configs.endian == Endianness::LittleEndian {
                             for col in 0..r.configs.columns {
                                 i = row * r.configs.columns + col;
-                                data.write_f32::<LittleEndian>(r.data[i] as f32).expect("Error writing byte data.");
+                                data.write_f32::<LittleEndian>(r.get_cell(i) as f32).expect("Error writing byte data.");
                             }
                         } else {
                             for col in 0..r.configs.columns {
                                 i = row * r.configs.columns + col;
-                                data.write_f32::<BigEndian>(r.data[i] as f32).expect("Error writing byte data.");
+                                data.write_f32::<BigEndian>(r.get_cell(i) as f32).expect("Error writing byte data.");
                             }
                         }
                         // compress the data vec
@@ -3502,7 +3144,7 @@ pub fn write_geotiff<'a>(r: &'a mut Raster) -> Result<(), Error> {
                     for row in 0..r.configs.rows {
                         for col in 0..r.configs.columns {
                             i = row * r.configs.columns + col;
-                            bow.write_u64(r.data[i] as u64)?;
+                            bow.write_u64(r.get_cell(i) as u64)?;
                         }
                     }
                 }
@@ -3511,7 +3153,7 @@ pub fn write_geotiff<'a>(r: &'a mut Raster) -> Result<(), Error> {
                     for row in 0..r.configs.rows {
                         for col in 0..r.configs.columns {
                             i = row * r.configs.columns + col;
-                            bow.write_u32(r.data[i] as u32)?;
+                            bow.write_u32(r.get_cell(i) as u32)?;
                         }
                     }
                 }
@@ -3520,7 +3162,7 @@ pub fn write_geotiff<'a>(r: &'a mut Raster) -> Result<(), Error> {
                     for row in 0..r.configs.rows {
                         for col in 0..r.configs.columns {
                             i = row * r.configs.columns + col;
-                            bow.write_u16(r.data[i] as u16)?;
+                            bow.write_u16(r.get_cell(i) as u16)?;
                         }
                     }
                 }
@@ -3529,7 +3171,7 @@ pub fn write_geotiff<'a>(r: &'a mut Raster) -> Result<(), Error> {
                     for row in 0..r.configs.rows {
                         for col in 0..r.configs.columns {
                             i = row * r.configs.columns + col;
-                            bow.write_u8(r.data[i] as u8)?;
+                            bow.write_u8(r.get_cell(i) as u8)?;
                         }
                     }
                 }
@@ -3538,7 +3180,7 @@ pub fn write_geotiff<'a>(r: &'a mut Raster) -> Result<(), Error> {
                     for row in 0..r.configs.rows {
                         for col in 0..r.configs.columns {
                             i = row * r.configs.columns + col;
-                            bow.write_i64(r.data[i] as i64)?;
+                            bow.write_i64(r.get_cell(i) as i64)?;
                         }
                     }
                 }
@@ -3547,7 +3189,7 @@ pub fn write_geotiff<'a>(r: &'a mut Raster) -> Result<(), Error> {
                     for row in 0..r.configs.rows {
                         for col in 0..r.configs.columns {
                             i = row * r.configs.columns + col;
-                            bow.write_i32(r.data[i] as i32)?;
+                            bow.write_i32(r.get_cell(i) as i32)?;
                         }
                     }
                 }
@@ -3556,7 +3198,7 @@ pub fn write_geotiff<'a>(r: &'a mut Raster) -> Result<(), Error> {
                     for row in 0..r.configs.rows {
                         for col in 0..r.configs.columns {
                             i = row * r.configs.columns + col;
-                            bow.write_i16(r.data[i] as i16)?;
+                            bow.write_i16(r.get_cell(i) as i16)?;
                         }
                     }
                 }
@@ -3565,7 +3207,7 @@ pub fn write_geotiff<'a>(r: &'a mut Raster) -> Result<(), Error> {
                     for row in 0..r.configs.rows {
                         for col in 0..r.configs.columns {
                             i = row * r.configs.columns + col;
-                            bow.write_i8(r.data[i] as i8)?;
+                            bow.write_i8(r.get_cell(i) as i8)?;
                         }
                     }
                 }
@@ -3587,7 +3229,7 @@ pub fn write_geotiff<'a>(r: &'a mut Raster) -> Result<(), Error> {
                         for row in 0..r.configs.rows {
                             for col in 0..r.configs.columns {
                                 i = row * r.configs.columns + col;
-                                let val = r.data[i] as u32;
+                                let val = r.get_cell(i) as u32;
                                 bytes[2] = ((val >> 16u32) & 0xFF) as u8; // blue
                                 bytes[1] = ((val >> 8u32) & 0xFF) as u8; // green
                                 bytes[0] = (val & 0xFF) as u8; // red
@@ -3601,7 +3243,7 @@ pub fn write_geotiff<'a>(r: &'a mut Raster) -> Result<(), Error> {
                         for row in 0..r.configs.rows {
                             for col in 0..r.configs.columns {
                                 i = row * r.configs.columns + col;
-                                let val = r.data[i] as u32;
+                                let val = r.get_cell(i) as u32;
                                 bytes[2] = ((val >> 16u32) & 0xFF) as u8; // blue
                                 bytes[1] = ((val >> 8u32) & 0xFF) as u8; // green
                                 bytes[0] = (val & 0xFF) as u8; // red
@@ -3645,7 +3287,7 @@ pub fn write_geotiff<'a>(r: &'a mut Raster) -> Result<(), Error> {
                     for row in 0..r.configs.rows {
                         for col in 0..r.configs.columns {
                             i = row * r.configs.columns + col;
-                            bow.write_f64(r.data[i]).expect("Error writing byte data to file.");
+                            bow.write_f64(r.get_cell(i)).expect("Error writing byte data to file.");
                         }
                     }
                 }
@@ -3654,7 +3296,7 @@ pub fn write_geotiff<'a>(r: &'a mut Raster) -> Result<(), Error> {
                     for row in 0..r.configs.rows {
                         for col in 0..r.configs.columns {
                             i = row * r.configs.columns + col;
-                            bow.write_f32(r.data[i] as f32).expect("Error writing byte data to file.");
+                            bow.write_f32(r.get_cell(i) as f32).expect("Error writing byte data to file.");
                         }
                     }
                 }
@@ -3663,7 +3305,7 @@ pub fn write_geotiff<'a>(r: &'a mut Raster) -> Result<(), Error> {
                     for row in 0..r.configs.rows {
                         for col in 0..r.configs.columns {
                             i = row * r.configs.columns + col;
-                            bow.write_u64(r.data[i] as u64).expect("Error writing byte data to file.");
+                            bow.write_u64(r.get_cell(i) as u64).expect("Error writing byte data to file.");
                         }
                     }
                 }
@@ -3672,7 +3314,7 @@ pub fn write_geotiff<'a>(r: &'a mut Raster) -> Result<(), Error> {
                     for row in 0..r.configs.rows {
                         for col in 0..r.configs.columns {
                             i = row * r.configs.columns + col;
-                            bow.write_u32(r.data[i] as u32).expect("Error writing byte data to file.");
+                            bow.write_u32(r.get_cell(i) as u32).expect("Error writing byte data to file.");
                         }
                     }
                 }
@@ -3681,7 +3323,7 @@ pub fn write_geotiff<'a>(r: &'a mut Raster) -> Result<(), Error> {
                     for row in 0..r.configs.rows {
                         for col in 0..r.configs.columns {
                             i = row * r.configs.columns + col;
-                            bow.write_u16(r.data[i] as u16).expect("Error writing byte data to file.");
+                            bow.write_u16(r.get_cell(i) as u16).expect("Error writing byte data to file.");
                         }
                     }
                 }
@@ -3690,7 +3332,7 @@ pub fn write_geotiff<'a>(r: &'a mut Raster) -> Result<(), Error> {
                     for row in 0..r.configs.rows {
                         for col in 0..r.configs.columns {
                             i = row * r.configs.columns + col;
-                            bow.write_u8(r.data[i] as u8).expect("Error writing byte data to file.");
+                            bow.write_u8(r.get_cell(i) as u8).expect("Error writing byte data to file.");
                         }
                     }
                 }
@@ -3699,7 +3341,7 @@ pub fn write_geotiff<'a>(r: &'a mut Raster) -> Result<(), Error> {
                     for row in 0..r.configs.rows {
                         for col in 0..r.configs.columns {
                             i = row * r.configs.columns + col;
-                            bow.write_i64(r.data[i] as i64).expect("Error writing byte data to file.");
+                            bow.write_i64(r.get_cell(i) as i64).expect("Error writing byte data to file.");
                         }
                     }
                 }
@@ -3708,7 +3350,7 @@ pub fn write_geotiff<'a>(r: &'a mut Raster) -> Result<(), Error> {
                     for row in 0..r.configs.rows {
                         for col in 0..r.configs.columns {
                             i = row * r.configs.columns + col;
-                            bow.write_i32(r.data[i] as i32).expect("Error writing byte data to file.");
+                            bow.write_i32(r.get_cell(i) as i32).expect("Error writing byte data to file.");
                         }
                     }
                 }
@@ -3717,7 +3359,7 @@ pub fn write_geotiff<'a>(r: &'a mut Raster) -> Result<(), Error> {
                     for row in 0..r.configs.rows {
                         for col in 0..r.configs.columns {
                             i = row * r.configs.columns + col;
-                            bow.write_i16(r.data[i] as i16).expect("Error writing byte data to file.");
+                            bow.write_i16(r.get_cell(i) as i16).expect("Error writing byte data to file.");
                         }
                     }
                 }
@@ -3726,7 +3368,7 @@ pub fn write_geotiff<'a>(r: &'a mut Raster) -> Result<(), Error> {
                     for row in 0..r.configs.rows {
                         for col in 0..r.configs.columns {
                             i = row * r.configs.columns + col;
-                            bow.write_i8(r.data[i] as i8).expect("Error writing byte data to file.");
+                            bow.write_i8(r.get_cell(i) as i8).expect("Error writing byte data to file.");
                         }
                     }
                 }
@@ -3748,7 +3390,7 @@ pub fn write_geotiff<'a>(r: &'a mut Raster) -> Result<(), Error> {
                         for row in 0..r.configs.rows {
                             for col in 0..r.configs.columns {
                                 i = row * r.configs.columns + col;
-                                let val = r.data[i] as u32;
+                                let val = r.get_cell(i) as u32;
                                 bytes[2] = ((val >> 16u32) & 0xFF) as u8; // blue
                                 bytes[1] = ((val >> 8u32) & 0xFF) as u8; // green
                                 bytes[0] = (val & 0xFF) as u8; // red
@@ -3762,7 +3404,7 @@ pub fn write_geotiff<'a>(r: &'a mut Raster) -> Result<(), Error> {
                         for row in 0..r.configs.rows {
                             for col in 0..r.configs.columns {
                                 i = row * r.configs.columns + col;
-                                let val = r.data[i] as u32;
+                                let val = r.get_cell(i) as u32;
                                 bytes[2] = ((val >> 16u32) & 0xFF) as u8; // blue
                                 bytes[1] = ((val >> 8u32) & 0xFF) as u8; // green
                                 bytes[0] = (val & 0xFF) as u8; // red
@@ -4717,6 +4359,430 @@ pub fn write_geotiff<'a>(r: &'a mut Raster) -> Result<(), Error> {
 */
 
 // An implementation of a PackBits reader
+/// The compression method, level, and predictor used to encode the strips or tiles of
+/// an output GeoTIFF.
+struct BlockEncoding {
+    compression: u16,
+    level: i32,
+    predictor: u16,
+}
+
+impl BlockEncoding {
+    /// Chooses the encoding of a raster from the compress_rasters, compression,
+    /// compression_level and predictor settings. The floating-point predictor is only
+    /// applied to floating-point data; integer data use horizontal differencing instead.
+    fn from_configs(configs: &whitebox_common::configs::Configs, r: &Raster) -> Result<BlockEncoding, Error> {
+        if !configs.compress_rasters {
+            return Ok(BlockEncoding {
+                compression: COMPRESS_NONE,
+                level: 0,
+                predictor: 1,
+            });
+        }
+        let (compression, default_level, max_level) =
+            match configs.compression.trim().to_lowercase().as_str() {
+                "deflate" | "" => (COMPRESS_DEFLATE, 6, 10),
+                "lzw" => (COMPRESS_LZW, 0, 0),
+                "packbits" => (COMPRESS_PACKBITS, 0, 0),
+                "zstd" => (COMPRESS_ZSTD, 9, 22),
+                "none" => (COMPRESS_NONE, 0, 0),
+                _ => {
+                    return Err(Error::new(
+                        ErrorKind::InvalidInput,
+                        format!(
+                            "Unsupported GeoTIFF compression method '{}'. Supported methods include 'deflate', 'lzw', 'packbits', 'zstd', and 'none'.",
+                            configs.compression
+                        ),
+                    ))
+                }
+            };
+        let level = if configs.compression_level < 0 {
+            default_level
+        } else {
+            (configs.compression_level as i32).max(1).min(max_level)
+        };
+        let predictor = match configs.predictor {
+            0 | 1 => 1,
+            2 => 2,
+            3 => match r.configs.data_type {
+                DataType::F32 | DataType::F64 => 3,
+                _ => 2,
+            },
+            p => {
+                return Err(Error::new(
+                    ErrorKind::InvalidInput,
+                    format!("Unsupported GeoTIFF predictor {}. Use 1 (none), 2 (horizontal), or 3 (floating-point).", p),
+                ))
+            }
+        };
+        // Predictors only pay off with the dictionary-based methods.
+        let predictor = match compression {
+            COMPRESS_LZW | COMPRESS_DEFLATE | COMPRESS_ZSTD => predictor,
+            _ => 1,
+        };
+        Ok(BlockEncoding {
+            compression,
+            level,
+            predictor,
+        })
+    }
+
+    fn is_compressed(&self) -> bool {
+        self.compression != COMPRESS_NONE
+    }
+
+    /// Adds the Compression (259) and, where one is used, Predictor (317) tags to an IFD.
+    fn push_entries(&self, ifd_entries: &mut Vec<Entry>) {
+        ifd_entries.push(Entry::new(
+            TAG_COMPRESSION,
+            DT_SHORT,
+            1u64,
+            self.compression as u64,
+        ));
+        if self.predictor > 1 {
+            ifd_entries.push(Entry::new(
+                TAG_PREDICTOR,
+                DT_SHORT,
+                1u64,
+                self.predictor as u64,
+            ));
+        }
+    }
+
+    /// Encodes the pixel data of a strip or tile that is `width` pixels wide, with
+    /// `samples` samples of `data_type` per pixel.
+    fn encode(
+        &self,
+        mut data: Vec<u8>,
+        width: usize,
+        samples: usize,
+        data_type: DataType,
+        endian: Endianness,
+    ) -> Result<Vec<u8>, Error> {
+        if self.predictor > 1 {
+            // Colour pixels are predicted channel by channel.
+            let (samples, bytes_per_sample) = match data_type {
+                DataType::RGB24 => (samples * 3, 1),
+                DataType::RGBA32 => (samples * 4, 1),
+                _ => (samples, data_type.get_data_size()),
+            };
+            apply_predictor(&mut data, self.predictor, width * samples, samples, bytes_per_sample, endian);
+        }
+        Ok(match self.compression {
+            COMPRESS_DEFLATE => compress_to_vec_zlib(&data, self.level as u8),
+            COMPRESS_LZW => lzw_encoder(&data),
+            COMPRESS_PACKBITS => packbits_encoder(&data),
+            COMPRESS_ZSTD => zstd::stream::encode_all(&data[..], self.level)?,
+            _ => data,
+        })
+    }
+}
+
+/// Applies a TIFF predictor to each row of `row_samples` samples of a block; the reverse
+/// of `TiffImageLayout::undo_predictor`. Horizontal differencing (2) replaces each sample
+/// with its difference from the same sample of the previous pixel, while the
+/// floating-point predictor (3) first splits each row into planes of the samples' bytes,
+/// most significant first, and then differences the bytes.
+fn apply_predictor(
+    buf: &mut [u8],
+    predictor: u16,
+    row_samples: usize,
+    samples: usize,
+    bytes_per_sample: usize,
+    endian: Endianness,
+) {
+    let row_bytes = row_samples * bytes_per_sample;
+    if row_bytes == 0 {
+        return;
+    }
+    let mut planes = vec![0u8; row_bytes];
+    for row in buf.chunks_exact_mut(row_bytes) {
+        if predictor == 2 {
+            for i in (samples..row_samples).rev() {
+                let value = get_uint(row, i, bytes_per_sample, endian)
+                    .wrapping_sub(get_uint(row, i - samples, bytes_per_sample, endian));
+                set_uint(row, i, bytes_per_sample, endian, value);
+            }
+        } else {
+            for i in 0..row_samples {
+                for b in 0..bytes_per_sample {
+                    planes[b * row_samples + i] = if endian == Endianness::BigEndian {
+                        row[i * bytes_per_sample + b]
+                    } else {
+                        row[i * bytes_per_sample + bytes_per_sample - 1 - b]
+                    };
+                }
+            }
+            for i in (samples..row_bytes).rev() {
+                planes[i] = planes[i].wrapping_sub(planes[i - samples]);
+            }
+            row.copy_from_slice(&planes);
+        }
+    }
+}
+
+/// Writes the image data of a raster, one row per strip, recording the offset and byte count
+/// of each strip. Multi-band rasters are written either with all of the samples of a pixel
+/// together (chunky) or one band after another (planar).
+fn write_strips<W: Write + Seek>(
+    writer: &mut BufWriter<W>,
+    r: &Raster,
+    bands: usize,
+    planar: bool,
+    encoding: &BlockEncoding,
+    strip_offsets: &mut Vec<u64>,
+    strip_byte_counts: &mut Vec<u64>,
+    current_offset: &mut u64,
+) -> Result<(), Error> {
+    let num_cells = r.configs.rows * r.configs.columns;
+    let (planes, samples) = if planar { (bands, 1) } else { (1, bands) };
+    // Colour images stored as U32 values are packed as RGBA.
+    let data_type = if r.configs.photometric_interp == PhotometricInterpretation::RGB
+        && r.configs.data_type == DataType::U32
+    {
+        DataType::RGBA32
+    } else {
+        r.configs.data_type
+    };
+    let bytes_per_pixel = samples * data_type.get_data_size();
+    let mut idx: usize;
+    for plane in 0..planes {
+        for row in 0..r.configs.rows {
+            let mut data = Vec::with_capacity(r.configs.columns * bytes_per_pixel);
+            for col in 0..r.configs.columns {
+                for b in plane..plane + samples {
+                    idx = b * num_cells + row * r.configs.columns + col;
+                    write_pixel(&mut data, data_type, r.configs.endian, r.get_cell(idx))?;
+                }
+            }
+            let strip = encoding.encode(data, r.configs.columns, samples, data_type, r.configs.endian)?;
+            write_bytes(writer, &strip)?;
+            strip_offsets.push(*current_offset);
+            strip_byte_counts.push(strip.len() as u64);
+            *current_offset += strip.len() as u64;
+            if encoding.is_compressed() && strip.len() % 2 != 0 {
+                // This is just because the data must start on a word (i.e. an even value).
+                write_u8(writer, 0u8)?;
+                *current_offset += 1;
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Appends the samples of a pixel to a byte buffer. Colour values are split into
+/// their 8-bit red, green, blue, and, for RGBA32 data, alpha channels.
+fn write_pixel(
+    data: &mut Vec<u8>,
+    data_type: DataType,
+    endian: Endianness,
+    value: f64,
+) -> Result<(), Error> {
+    match data_type {
+        DataType::RGB24 | DataType::RGBA32 => {
+            let val = value as u32;
+            let channels = if data_type == DataType::RGB24 { 3 } else { 4 };
+            for c in 0..channels {
+                data.push(((val >> (8 * c)) & 0xFF) as u8);
+            }
+            Ok(())
+        }
+        _ => write_sample(data, data_type, endian, value),
+    }
+}
+
+/// Appends a single sample value to a byte buffer using the specified data type and byte order.
+fn write_sample(
+    data: &mut Vec<u8>,
+    data_type: DataType,
+    endian: Endianness,
+    value: f64,
+) -> Result<(), Error> {
+    if endian == Endianness::LittleEndian {
+        match data_type {
+            DataType::F64 => data.write_f64::<LittleEndian>(value),
+            DataType::F32 => data.write_f32::<LittleEndian>(value as f32),
+            DataType::U64 => data.write_u64::<LittleEndian>(value as u64),
+            DataType::U32 => data.write_u32::<LittleEndian>(value as u32),
+            DataType::U16 => data.write_u16::<LittleEndian>(value as u16),
+            DataType::U8 => data.write_u8(value as u8),
+            DataType::I64 => data.write_i64::<LittleEndian>(value as i64),
+            DataType::I32 => data.write_i32::<LittleEndian>(value as i32),
+            DataType::I16 => data.write_i16::<LittleEndian>(value as i16),
+            DataType::I8 => data.write_i8(value as i8),
+            _ => Err(Error::new(
+                ErrorKind::InvalidData,
+                format!("Unknown data type: {:?}.", data_type),
+            )),
+        }
+    } else {
+        match data_type {
+            DataType::F64 => data.write_f64::<BigEndian>(value),
+            DataType::F32 => data.write_f32::<BigEndian>(value as f32),
+            DataType::U64 => data.write_u64::<BigEndian>(value as u64),
+            DataType::U32 => data.write_u32::<BigEndian>(value as u32),
+            DataType::U16 => data.write_u16::<BigEndian>(value as u16),
+            DataType::U8 => data.write_u8(value as u8),
+            DataType::I64 => data.write_i64::<BigEndian>(value as i64),
+            DataType::I32 => data.write_i32::<BigEndian>(value as i32),
+            DataType::I16 => data.write_i16::<BigEndian>(value as i16),
+            DataType::I8 => data.write_i8(value as i8),
+            _ => Err(Error::new(
+                ErrorKind::InvalidData,
+                format!("Unknown data type: {:?}.", data_type),
+            )),
+        }
+    }
+}
+
+/// Reads a single pixel sample of the specified TIFF SampleFormat and bit depth.
+fn read_sample(
+    bor: &mut ByteOrderReader<Cursor<Vec<u8>>>,
+    sample_format: u16,
+    bits_per_sample: u16,
+) -> Result<f64, Error> {
+    match (sample_format, bits_per_sample) {
+        (1, 8) => Ok(bor.read_u8()? as f64),
+        (1, 16) => Ok(bor.read_u16()? as f64),
+        (1, 32) => Ok(bor.read_u32()? as f64),
+        (1, 64) => Ok(bor.read_u64()? as f64),
+        (2, 8) => Ok(bor.read_i8()? as f64),
+        (2, 16) => Ok(bor.read_i16()? as f64),
+        (2, 32) => Ok(bor.read_i32()? as f64),
+        (2, 64) => Ok(bor.read_i64()? as f64),
+        (3, 32) => Ok(bor.read_f32()? as f64),
+        (3, 64) => bor.read_f64(),
+        _ => Err(Error::new(
+            ErrorKind::InvalidData,
+            "The raster was not read correctly",
+        )),
+    }
+}
+
+/// Returns the `i`th unsigned integer of `bytes_per_sample` bytes within a buffer.
+#[inline]
+fn get_uint(buf: &[u8], i: usize, bytes_per_sample: usize, endian: Endianness) -> u64 {
+    let bytes = &buf[i * bytes_per_sample..(i + 1) * bytes_per_sample];
+    let mut value = 0u64;
+    for b in 0..bytes_per_sample {
+        let byte = if endian == Endianness::BigEndian {
+            bytes[b]
+        } else {
+            bytes[bytes_per_sample - 1 - b]
+        };
+        value = (value << 8) | byte as u64;
+    }
+    value
+}
+
+/// Sets the `i`th unsigned integer of `bytes_per_sample` bytes within a buffer,
+/// discarding any higher-order bits of `value`.
+#[inline]
+fn set_uint(buf: &mut [u8], i: usize, bytes_per_sample: usize, endian: Endianness, value: u64) {
+    let bytes = &mut buf[i * bytes_per_sample..(i + 1) * bytes_per_sample];
+    for b in 0..bytes_per_sample {
+        let byte = (value >> (8 * (bytes_per_sample - 1 - b))) as u8;
+        if endian == Endianness::BigEndian {
+            bytes[b] = byte;
+        } else {
+            bytes[bytes_per_sample - 1 - b] = byte;
+        }
+    }
+}
+
+/// Compresses data using the PackBits run-length scheme. Runs of three or more
+/// repeated bytes are written as a repeat code and everything else as literals.
+fn packbits_encoder(input_data: &[u8]) -> Vec<u8> {
+    let mut output_data = Vec::with_capacity(input_data.len() + input_data.len() / 128 + 1);
+    let n = input_data.len();
+    let mut i = 0usize;
+    while i < n {
+        // measure the run starting at i
+        let mut run = 1usize;
+        while i + run < n && run < 128 && input_data[i + run] == input_data[i] {
+            run += 1;
+        }
+        if run >= 3 {
+            output_data.push((257 - run) as u8);
+            output_data.push(input_data[i]);
+            i += run;
+        } else {
+            // gather literals until the next run of three or more
+            let start = i;
+            while i < n && i - start < 128 {
+                if i + 2 < n && input_data[i] == input_data[i + 1] && input_data[i] == input_data[i + 2] {
+                    break;
+                }
+                i += 1;
+            }
+            output_data.push((i - start - 1) as u8);
+            output_data.extend_from_slice(&input_data[start..i]);
+        }
+    }
+    output_data
+}
+
+/// Compresses data using the TIFF variant of LZW, with 'early change' code widths,
+/// as written by libtiff.
+fn lzw_encoder(input_data: &[u8]) -> Vec<u8> {
+    const CLEAR_CODE: u32 = 256;
+    const EOI_CODE: u32 = 257;
+    const FIRST_CODE: u32 = 258;
+    const MAX_CODE: u32 = 4094;
+
+    let mut output_data = Vec::with_capacity(input_data.len() / 2 + 16);
+    let mut bit_buffer = 0u64;
+    let mut bit_count = 0u32;
+    let mut put_code = |code: u32, code_width: u32, output_data: &mut Vec<u8>| {
+        bit_buffer = (bit_buffer << code_width) | code as u64;
+        bit_count += code_width;
+        while bit_count >= 8 {
+            bit_count -= 8;
+            output_data.push((bit_buffer >> bit_count) as u8);
+        }
+    };
+
+    // the dictionary maps (prefix code, next byte) to a code
+    let mut dictionary: HashMap<(u32, u8), u32> = HashMap::new();
+    let mut next_code = FIRST_CODE;
+    let mut code_width = 9u32;
+    put_code(CLEAR_CODE, code_width, &mut output_data);
+    if input_data.is_empty() {
+        put_code(EOI_CODE, code_width, &mut output_data);
+    } else {
+        let mut prefix = input_data[0] as u32;
+        for &byte in &input_data[1..] {
+            if let Some(&code) = dictionary.get(&(prefix, byte)) {
+                prefix = code;
+                continue;
+            }
+            put_code(prefix, code_width, &mut output_data);
+            dictionary.insert((prefix, byte), next_code);
+            next_code += 1;
+            if next_code == MAX_CODE {
+                put_code(CLEAR_CODE, code_width, &mut output_data);
+                dictionary.clear();
+                next_code = FIRST_CODE;
+                code_width = 9;
+            } else if next_code > (1 << code_width) - 1 {
+                code_width += 1;
+            }
+            prefix = byte as u32;
+        }
+        put_code(prefix, code_width, &mut output_data);
+        // the decoder adds an entry after reading the final code too
+        next_code += 1;
+        if next_code > (1 << code_width) - 1 && code_width < 12 {
+            code_width += 1;
+        }
+        put_code(EOI_CODE, code_width, &mut output_data);
+    }
+    if bit_count > 0 {
+        output_data.push((bit_buffer << (8 - bit_count)) as u8);
+    }
+    output_data
+}
+
 pub fn packbits_decoder(input_data: Vec<u8>) -> Vec<u8> {
     let mut output_data = vec![];
     let mut i: usize = 0;
@@ -4846,3 +4912,193 @@ pub fn write_f64<W: Write>(
         writer.write_f64::<BigEndian>(value)
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::test_utils::*;
+    use whitebox_common::configs::Configs;
+
+    fn multi_band_raster(file_name: &str, bands: usize) -> Raster {
+        let mut configs = RasterConfigs::default();
+        configs.rows = 3;
+        configs.columns = 4;
+        configs.nodata = -32768.0;
+        configs.north = 3.0;
+        configs.south = 0.0;
+        configs.east = 4.0;
+        configs.west = 0.0;
+        configs.resolution_x = 1.0;
+        configs.resolution_y = 1.0;
+        configs.data_type = DataType::I16;
+        configs.photometric_interp = PhotometricInterpretation::Continuous;
+        let mut r = Raster::initialize_using_config(file_name, &configs);
+        r.set_num_bands(bands);
+        for band in 0..bands {
+            for row in 0..3 {
+                for col in 0..4 {
+                    r.set_band_value(band, row, col, (band * 100) as f64 + (row * 4 + col) as f64);
+                }
+            }
+        }
+        r
+    }
+
+    /// Writes the sample entries of a multi-band image to an IFD and reads them back.
+    fn sample_tags_round_trip(bands: usize, is_big_tiff: bool) {
+        let r = multi_band_raster("", bands);
+        let endian = Endianness::LittleEndian;
+        let mut ifd_entries = vec![];
+        let mut larger_values_data = ByteOrderWriter::<Vec<u8>>::new(vec![], endian);
+        push_sample_entries(&r, bands, true, &mut ifd_entries, &mut larger_values_data).unwrap();
+        let mut writer = BufWriter::new(Cursor::new(vec![]));
+        write_ifd(
+            &mut writer,
+            endian,
+            is_big_tiff,
+            0u64,
+            ifd_entries,
+            larger_values_data.get_inner(),
+            0u64,
+        )
+        .unwrap();
+        let bytes = writer.into_inner().unwrap().into_inner();
+
+        let mut th = ByteOrderReader::new(Cursor::new(bytes), endian);
+        let ifd_map = read_ifd(&mut th, is_big_tiff, endian).unwrap();
+        assert_eq!(ifd_map[&TAG_SAMPLESPERPIXEL].interpret_as_u16(), vec![bands as u16]);
+        assert_eq!(ifd_map[&TAG_BITSPERSAMPLE].interpret_as_u16(), vec![16u16; bands]);
+        assert_eq!(ifd_map[&TAG_SAMPLEFORMAT].interpret_as_u16(), vec![2u16; bands]);
+        assert_eq!(ifd_map[&TAG_EXTRASAMPLES].interpret_as_u16(), vec![0u16; bands - 1]);
+    }
+
+    #[test]
+    fn sample_tags_of_three_and_four_bands() {
+        for &is_big_tiff in &[false, true] {
+            sample_tags_round_trip(3, is_big_tiff);
+            sample_tags_round_trip(4, is_big_tiff);
+        }
+    }
+
+    #[test]
+    fn multi_band_geotiff_round_trip() {
+        for &bands in &[3usize, 4usize] {
+            let file_name = std::env::temp_dir()
+                .join(format!("wbt_geotiff_{}_{}.tif", bands, std::process::id()))
+                .to_string_lossy()
+                .to_string();
+            let mut output = multi_band_raster(&file_name, bands);
+            output.write().unwrap();
+
+            let input = Raster::new(&file_name, "r").unwrap();
+            assert_eq!(input.num_bands(), bands);
+            assert_eq!(input.configs.data_type, DataType::I16);
+            for band in 0..bands {
+                for row in 0..3 {
+                    for col in 0..4 {
+                        assert_eq!(
+                            input.get_band_value(band, row, col),
+                            output.get_band_value(band, row, col)
+                        );
+                    }
+                }
+            }
+            std::fs::remove_file(&file_name).unwrap();
+        }
+    }
+
+    #[test]
+    fn packbits_round_trip() {
+        let mut mixed: Vec<u8> = (0..300).map(|i| (i * 7 % 256) as u8).collect();
+        mixed.extend(vec![9u8; 300]);
+        mixed.extend_from_slice(&[1, 1, 2, 2, 2, 3, 4, 4]);
+        for input in [vec![], vec![5u8], vec![0u8; 300], mixed] {
+            let encoded = packbits_encoder(&input);
+            assert_eq!(packbits_decoder(encoded), input);
+        }
+        assert!(packbits_encoder(&[0u8; 300]).len() <= 6);
+    }
+
+    #[test]
+    fn lzw_round_trip() {
+        // Enough varied data that the code table fills and is cleared several times.
+        let mut seed = 1u32;
+        let input = (0..200_000)
+            .map(|i| {
+                seed = seed.wrapping_mul(1103515245).wrapping_add(12345);
+                if i % 1000 < 300 {
+                    (i % 7) as u8
+                } else {
+                    (seed >> 24) as u8
+                }
+            })
+            .collect::<Vec<u8>>();
+        for input in [vec![], vec![5u8], vec![0u8; 10_000], input] {
+            let encoded = lzw_encoder(&input);
+            let mut decoder = lzw::DecoderEarlyChange::new(lzw::MsbReader::new(), 8);
+            let mut decoded = vec![];
+            let mut bytes_read = 0;
+            while bytes_read < encoded.len() && decoded.len() < input.len() {
+                let (len, bytes) = decoder.decode_bytes(&encoded[bytes_read..]).unwrap();
+                bytes_read += len;
+                decoded.extend_from_slice(bytes);
+            }
+            assert_eq!(decoded, input);
+        }
+    }
+
+    #[test]
+    fn compressed_geotiff_round_trip() {
+        let file_name = temp_file("compressed.tif");
+        let data_types = [DataType::U8, DataType::I16, DataType::U32, DataType::F32, DataType::F64];
+        for compression in ["deflate", "lzw", "packbits", "zstd", "none"] {
+            for predictor in 1..=3 {
+                for &data_type in &data_types {
+                    let mut output = sample_raster(&file_name, 9, 13, data_type);
+                    if data_type == DataType::I16 {
+                        // The samples of a pixel are differenced separately.
+                        output.configs.interleave = BandInterleave::BIP;
+                        output.set_num_bands(3);
+                        for row in 0..9 {
+                            for col in 0..13 {
+                                output.set_band_value(1, row, col, (row * col) as f64 - 50.0);
+                                output.set_band_value(2, row, col, -((row + col) as f64));
+                            }
+                        }
+                    }
+                    let mut configs = Configs::new();
+                    configs.compression = compression.to_string();
+                    configs.predictor = predictor;
+                    let encoding = BlockEncoding::from_configs(&configs, &output).unwrap();
+                    write_strip_geotiff(&mut output, &encoding).unwrap();
+
+                    let input = Raster::new(&file_name, "r").unwrap();
+                    assert_eq!(input.configs.data_type, data_type);
+                    assert_same_cells(&output, &input);
+
+                    let mut configs = RasterConfigs::default();
+                    let f = File::open(&file_name).unwrap();
+                    let (mut th, is_big_tiff, ifd_offset) =
+                        read_tiff_header(BufReader::new(f), &mut configs).unwrap();
+                    th.seek(ifd_offset);
+                    let ifd_map = read_ifd(&mut th, is_big_tiff, configs.endian).unwrap();
+                    assert_eq!(
+                        ifd_map[&TAG_COMPRESSION].interpret_as_u16(),
+                        vec![encoding.compression]
+                    );
+                    let expected_predictor = match (compression, predictor, data_type.is_float()) {
+                        ("packbits", _, _) | ("none", _, _) => None,
+                        (_, 1, _) => None,
+                        (_, 3, false) => Some(2),
+                        (_, p, _) => Some(p as u16),
+                    };
+                    assert_eq!(
+                        ifd_map.get(&TAG_PREDICTOR).map(|ifd| ifd.interpret_as_u16()[0]),
+                        expected_predictor
+                    );
+                }
+            }
+        }
+        std::fs::remove_file(&file_name).unwrap();
+    }
+}
@@ -17,6 +17,7 @@ pub const COMPRESS_JPEG: u16 = 7;
 pub const COMPRESS_DEFLATE: u16 = 8; // zlib compression.
 pub const COMPRESS_PACKBITS: u16 = 32773;
 pub const COMPRESS_DEFLATEOLD: u16 = 32946; // Superseded by cDeflate.
+pub const COMPRESS_ZSTD: u16 = 50000; // Zstandard compression.
 
 pub const DT_BYTE: u16 = 1;
 pub const DT_ASCII: u16 = 2;
@@ -40,7 +41,7 @@ pub const PI_RGB: u16 = 2;
 pub const PI_PALETTED: u16 = 3;
 // pub const PI_TRANSMASK: u16   = 4; // transparency mask
 // const PI_CMYK: u16        = 5;
-// const PI_YCBCR: u16       = 6;
+pub const PI_YCBCR: u16 = 6;
 // const PI_CIELAB: u16      = 8;
 
 // Tags (see p. 28-41 of the spec).
@@ -20,7 +20,7 @@ pub fn read_grass_raster(
     let mut null_str = String::from("");
     let mut null_is_str = false;
     for line in f.lines() {
-        let line_unwrapped = line.unwrap();
+        let line_unwrapped = line?;
         let line_split = line_unwrapped.split(":");
         let vec = line_split.collect::<Vec<&str>>();
         if vec[0].to_lowercase().contains("rows") {
@@ -172,9 +172,9 @@ pub fn write_grass_raster<'a>(r: &'a mut Raster) -> Result<(), Error> {
     if r.configs.data_type == DataType::F32 || r.configs.data_type == DataType::F64 {
         for i in 0..num_cells {
             if col < r.configs.columns - 1 {
-                s2 += &format!("{:.*} ", 2, r.data[i]);
+                s2 += &format!("{:.*} ", 2, r.get_cell(i));
             } else {
-                s2 += &format!("{:.*}\n", 2, r.data[i]);
+                s2 += &format!("{:.*}\n", 2, r.get_cell(i));
             }
             col += 1;
             if col == r.configs.columns {
@@ -186,9 +186,9 @@ pub fn write_grass_raster<'a>(r: &'a mut Raster) -> Result<(), Error> {
     } else {
         for i in 0..num_cells {
             if col < r.configs.columns - 1 {
-                s2 += &format!("{:.*} ", 0, r.data[i]);
+                s2 += &format!("{:.*} ", 0, r.get_cell(i));
             } else {
-                s2 += &format!("{:.*}\n", 0, r.data[i]);
+                s2 += &format!("{:.*}\n", 0, r.get_cell(i));
             }
             col += 1;
             if col == r.configs.columns {
@@ -199,7 +199,7 @@ pub fn write_grass_raster<'a>(r: &'a mut Raster) -> Result<(), Error> {
         }
     }
 
-    let _ = writer.flush();
+    writer.flush()?;
 
     Ok(())
 }
@@ -25,7 +25,7 @@ pub fn read_idrisi(
     let f = BufReader::new(f);
 
     for line in f.lines() {
-        let line_unwrapped = line.unwrap();
+        let line_unwrapped = line?;
         configs.photometric_interp = PhotometricInterpretation::Continuous;
         let line_split = line_unwrapped.split(":");
         let vec = line_split.collect::<Vec<&str>>();
@@ -131,11 +131,7 @@ pub fn read_idrisi(
 
     // read the data file
     // let data_file = file_name.replace(".rdc", ".rst");
-    let data_file = Path::new(&file_name)
-        .with_extension("rst")
-        .into_os_string()
-        .into_string()
-        .unwrap();
+    let data_file = get_data_file_name(&file_name, "rdc", "rst");
     let mut f = File::open(data_file.clone())?;
 
     let data_size = if configs.data_type == DataType::F32 {
@@ -234,8 +230,8 @@ pub fn read_idrisi(
 
 pub fn write_idrisi<'a>(r: &'a mut Raster) -> Result<(), Error> {
     // figure out the minimum and maximum values
-    for val in &r.data {
-        let v = *val;
+    for i in 0..r.configs.rows * r.configs.columns {
+        let v = r.get_cell(i);
         if v != r.configs.nodata {
             if v < r.configs.minimum {
                 r.configs.minimum = v;
@@ -354,15 +350,11 @@ pub fn write_idrisi<'a>(r: &'a mut Raster) -> Result<(), Error> {
         writer.write_all(s.as_bytes())?;
     }
 
-    let _ = writer.flush();
+    writer.flush()?;
 
     // read the data file
     // let data_file = r.file_name.replace(".rdc", ".rst");
-    let data_file = Path::new(&r.file_name)
-        .with_extension("rst")
-        .into_os_string()
-        .into_string()
-        .unwrap();
+    let data_file = get_data_file_name(&r.file_name, "rdc", "rst");
     let f = File::create(&data_file)?;
     let mut writer = BufWriter::new(f);
 
@@ -374,7 +366,7 @@ pub fn write_idrisi<'a>(r: &'a mut Raster) -> Result<(), Error> {
     match r.configs.data_type {
         DataType::F32 => {
             for i in 0..num_cells {
-                u32_bytes = unsafe { mem::transmute(r.data[i] as f32) };
+                u32_bytes = unsafe { mem::transmute(r.get_cell(i) as f32) };
                 writer.write(&u32_bytes)?;
             }
         }
@@ -385,19 +377,19 @@ pub fn write_idrisi<'a>(r: &'a mut Raster) -> Result<(), Error> {
                 "Writing RGB24 raster is not currently supported.",
             ));
             // for i in 0..num_cells {
-            //     u24_bytes = unsafe { mem::transmute(r.data[i] as u32) };
+            //     u24_bytes = unsafe { mem::transmute(r.get_cell(i) as u32) };
             //     writer.write(&u16_bytes)?;
             // }
         }
         DataType::I16 => {
             for i in 0..num_cells {
-                u16_bytes = unsafe { mem::transmute(r.data[i] as u16) };
+                u16_bytes = unsafe { mem::transmute(r.get_cell(i) as u16) };
                 writer.write(&u16_bytes)?;
             }
         }
         DataType::U8 => {
             for i in 0..num_cells {
-                writer.write(&[r.data[i] as u8])?;
+                writer.write(&[r.get_cell(i) as u8])?;
             }
         }
         _ => {
@@ -408,7 +400,7 @@ pub fn write_idrisi<'a>(r: &'a mut Raster) -> Result<(), Error> {
         }
     }
 
-    let _ = writer.flush();
+    writer.flush()?;
 
     Ok(())
 }
@@ -16,46 +16,76 @@ extern crate num_traits;
 
 mod arcascii_raster;
 mod arcbinary_raster;
+mod attribute_table;
+mod block_cache;
+mod envi_raster;
 mod esri_bil;
+mod format_detection;
+mod geopackage_raster;
 pub mod geotiff;
 mod grass_raster;
 mod idrisi_raster;
+mod netcdf_raster;
+mod raster_data;
+mod raster_error;
+mod resampling;
 mod saga_raster;
+mod srtm_raster;
 mod surfer7_raster;
 mod surfer_ascii_raster;
 mod whitebox_raster;
+mod xyz_raster;
+
+#[cfg(test)]
+mod test_utils;
 
 use self::arcascii_raster::*;
 use self::arcbinary_raster::*;
+use self::attribute_table::{read_aux_file, write_aux_file};
+pub use self::attribute_table::{AttributeTable, AttributeTableEntry, Colour, ColourTable};
+use self::block_cache::*;
+use self::envi_raster::*;
 use self::esri_bil::*;
+use self::format_detection::detect_raster_type;
+use self::geopackage_raster::*;
 use self::geotiff::*;
 use self::grass_raster::*;
 use self::idrisi_raster::*;
+use self::netcdf_raster::*;
+use self::raster_data::*;
+pub use self::raster_error::RasterError;
+pub use self::resampling::ResamplingMethod;
 use self::saga_raster::*;
+use self::srtm_raster::*;
 use self::surfer7_raster::*;
 use self::surfer_ascii_raster::*;
 use self::whitebox_raster::*;
+use self::xyz_raster::*;
 use num_traits::cast::AsPrimitive;
+use whitebox_common::spatial_ref_system::CoordinateReferenceSystem;
 use whitebox_common::structures::{Array2D, BoundingBox};
 use whitebox_common::utils::*;
 use std::cmp::Ordering::Equal;
+use std::collections::HashMap;
 use std::default::Default;
 use std::f64;
-use std::fs::File;
 use std::io::prelude::*;
-use std::io::BufReader;
 use std::io::Error;
 use std::io::ErrorKind;
-use std::ops::{AddAssign, Index, IndexMut, SubAssign};
+use std::mem;
+use std::ops::{AddAssign, SubAssign};
 use std::path::Path;
 use std::sync::mpsc;
-use std::sync::Arc;
 use std::thread;
 // use rayon::prelude::*;
 
 /// Raster is a common data structure that abstracts over several raster data formats,
-/// including GeoTIFFs, ArcGIS ASCII and binary rasters, Whitebox rasters, Idrisi
-/// rasters, Saga rasters, and GRASS ASCII rasters.
+/// including GeoTIFFs, ArcGIS ASCII and binary rasters, Esri BIL rasters, ENVI rasters,
+/// Whitebox rasters, Idrisi rasters, Saga rasters, GRASS ASCII rasters, SRTM height
+/// files, XYZ grids, and NetCDF classic files, as well as the tiles of GeoPackage files,
+/// which can be read but not written. A variable of a NetCDF file, or a table of a
+/// GeoPackage, is selected by appending its name to the file name, e.g.
+/// `climate.nc:precip` or `terrain.gpkg:dem`.
 ///
 /// Examples:
 ///
@@ -67,64 +97,14 @@ use std::thread;
 /// // and location of an existing file.
 /// let mut output = Raster::initialize_using_file(&output_file, &input);
 /// ```
-#[derive(Default, Clone)]
+#[derive(Default)]
 pub struct Raster {
     pub file_name: String,
     pub file_mode: String,
     pub raster_type: RasterType,
     pub configs: RasterConfigs,
-    data: Vec<f64>,
-}
-
-impl Index<(isize, isize)> for Raster {
-    type Output = f64;
-
-    fn index<'a>(&'a self, index: (isize, isize)) -> &'a f64 {
-        let row = index.0;
-        let column = index.1;
-
-        if column < 0 {
-            return &self.configs.nodata;
-        }
-        if row < 0 {
-            return &self.configs.nodata;
-        }
-
-        let c: usize = column as usize;
-        let r: usize = row as usize;
-
-        if c >= self.configs.columns {
-            return &self.configs.nodata;
-        }
-        if r >= self.configs.rows {
-            return &self.configs.nodata;
-        }
-        let idx: usize = r * self.configs.columns + c;
-        &self.data[idx]
-    }
-}
-
-impl IndexMut<(isize, isize)> for Raster {
-    fn index_mut<'a>(&'a mut self, index: (isize, isize)) -> &'a mut f64 {
-        let row = index.0;
-        let column = index.1;
-        if column < 0 {
-            return &mut self.configs.nodata;
-        }
-        if row < 0 {
-            return &mut self.configs.nodata;
-        }
-        let c: usize = column as usize;
-        let r: usize = row as usize;
-        if c >= self.configs.columns {
-            return &mut self.configs.nodata;
-        }
-        if r >= self.configs.rows {
-            return &mut self.configs.nodata;
-        }
-        let idx = r * self.configs.columns + c;
-        &mut self.data[idx as usize]
-    }
+    data: RasterData,
+    cache: Option<BlockCache>,
 }
 
 impl Raster {
@@ -135,66 +115,136 @@ impl Raster {
     ///
     /// To create a new `Raster` file, most applications should prefer the
     /// `initialize_using_config` or `initialize_using_file` functions instead.
-    pub fn new<'a>(file_name: &'a str, file_mode: &'a str) -> Result<Raster, Error> {
+    ///
+    /// Rasters that are larger than the memory budget set by the `max_memory`
+    /// setting are read from disk as their values are accessed, rather than
+    /// all at once; see `new_with_max_memory`.
+    ///
+    /// A `RasterError` is returned if the format of the file cannot be determined
+    /// or, in 'r' mode, if the file cannot be read.
+    pub fn new<'a>(file_name: &'a str, file_mode: &'a str) -> Result<Raster, RasterError> {
+        Raster::new_with_max_memory(file_name, file_mode, get_max_memory())
+    }
+
+    /// Creates a `Raster` object in the same manner as `new`, except that if its
+    /// values would occupy more than `max_memory` bytes, they are read from disk one
+    /// block of rows at a time, as they are accessed. At most about `max_memory` bytes
+    /// of values are held in memory at once, with the least recently used blocks being
+    /// released first. A `max_memory` of zero reads the entire raster into memory.
+    ///
+    /// Reading on demand is supported for GeoTIFF, Whitebox, Esri BIL, ENVI, and SRTM rasters;
+    /// other formats are always read entirely into memory. When a Whitebox, BIL, or ENVI
+    /// raster that is opened in 'rw' mode is written, only the modified blocks are
+    /// written back to the file.
+    pub fn new_with_max_memory<'a>(
+        file_name: &'a str,
+        file_mode: &'a str,
+        max_memory: usize,
+    ) -> Result<Raster, RasterError> {
         let fm: String = file_mode.to_lowercase();
         let mut r = Raster {
             file_name: file_name.to_string(),
             file_mode: fm.clone(),
-            raster_type: get_raster_type_from_file(file_name.to_string(), fm.clone()),
+            raster_type: get_raster_type_from_file(file_name, &fm)?,
             ..Default::default()
         };
         if r.file_mode.contains("r") {
-            match get_raster_type_from_file(file_name.to_string(), fm) {
-                RasterType::ArcBinary => {
-                    let _ = read_arcbinary(&r.file_name, &mut r.configs, &mut r.data)?;
-                    return Ok(r);
-                }
-                RasterType::ArcAscii => {
-                    let _ = read_arcascii(&r.file_name, &mut r.configs, &mut r.data)?;
-                    return Ok(r);
-                }
-                RasterType::EsriBil => {
-                    let _ = read_esri_bil(&r.file_name, &mut r.configs, &mut r.data)?;
-                    return Ok(r);
-                }
-                RasterType::GeoTiff => {
-                    let _ = read_geotiff(&r.file_name, &mut r.configs, &mut r.data)?;
-                    r.update_min_max();
-                    return Ok(r);
-                }
-                RasterType::GrassAscii => {
-                    let _ = read_grass_raster(&r.file_name, &mut r.configs, &mut r.data)?;
-                    return Ok(r);
-                }
-                RasterType::IdrisiBinary => {
-                    let _ = read_idrisi(&r.file_name, &mut r.configs, &mut r.data)?;
-                    return Ok(r);
-                }
-                RasterType::SagaBinary => {
-                    let _ = read_saga(&r.file_name, &mut r.configs, &mut r.data)?;
-                    return Ok(r);
-                }
-                RasterType::Surfer7Binary => {
-                    let _ = read_surfer7(&r.file_name, &mut r.configs, &mut r.data)?;
-                    return Ok(r);
-                }
-                RasterType::SurferAscii => {
-                    let _ = read_surfer_ascii_raster(&r.file_name, &mut r.configs, &mut r.data)?;
-                    return Ok(r);
-                }
-                RasterType::Whitebox => {
-                    let _ = read_whitebox(&r.file_name, &mut r.configs, &mut r.data)?;
-                    return Ok(r);
-                }
-                RasterType::Unknown => {
-                    return Err(Error::new(ErrorKind::Other, "Unrecognized raster type"));
-                }
+            r.read(max_memory).map_err(|e| RasterError::read(file_name, e))?;
+        }
+        Ok(r)
+    }
+
+    /// Reads the header and, unless it is to be read by blocks, the data of the raster file.
+    fn read(&mut self, max_memory: usize) -> Result<(), Error> {
+        if self.read_by_blocks(max_memory)? {
+            return self.read_attribute_tables();
+        }
+        let mut data = vec![];
+        match self.raster_type {
+            RasterType::ArcBinary => {
+                let _ = read_arcbinary(&self.file_name, &mut self.configs, &mut data)?;
             }
-        } else {
-            // write
-            return Ok(r);
+            RasterType::ArcAscii => {
+                let _ = read_arcascii(&self.file_name, &mut self.configs, &mut data)?;
+            }
+            RasterType::GeoPackage => {
+                let _ = read_geopackage(&self.file_name, &mut self.configs, &mut data)?;
+            }
+            RasterType::GrassAscii => {
+                let _ = read_grass_raster(&self.file_name, &mut self.configs, &mut data)?;
+            }
+            RasterType::IdrisiBinary => {
+                let _ = read_idrisi(&self.file_name, &mut self.configs, &mut data)?;
+            }
+            RasterType::NetCdf => {
+                let _ = read_netcdf(&self.file_name, &mut self.configs, &mut data)?;
+            }
+            RasterType::SagaBinary => {
+                let _ = read_saga(&self.file_name, &mut self.configs, &mut data)?;
+            }
+            RasterType::Surfer7Binary => {
+                let _ = read_surfer7(&self.file_name, &mut self.configs, &mut data)?;
+            }
+            RasterType::SurferAscii => {
+                let _ = read_surfer_ascii_raster(&self.file_name, &mut self.configs, &mut data)?;
+            }
+            RasterType::Whitebox => {
+                // RGB rasters, which can't be read by blocks
+                let _ = read_whitebox(&self.file_name, &mut self.configs, &mut data)?;
+            }
+            RasterType::XyzAscii => {
+                let _ = read_xyz(&self.file_name, &mut self.configs, &mut data)?;
+            }
+            RasterType::Envi
+            | RasterType::EsriBil
+            | RasterType::GeoTiff
+            | RasterType::SrtmHgt => {
+                // these formats are always read by blocks
+            }
+            RasterType::Unknown => {
+                return Err(Error::new(ErrorKind::Other, "Unrecognized raster type"));
+            }
+        }
+        self.data = RasterData::from_values(self.configs.data_type, data);
+        self.read_attribute_tables()
+    }
+
+    /// Reads the colour table and attribute table of the raster from its auxiliary
+    /// (.aux.xml) file. Whitebox rasters store these tables within their header files.
+    fn read_attribute_tables(&mut self) -> Result<(), Error> {
+        if self.raster_type == RasterType::Whitebox {
+            return Ok(());
+        }
+        let file_name = get_container_file_name(&self.file_name);
+        read_aux_file(
+            file_name,
+            &mut self.configs.colour_table,
+            &mut self.configs.attribute_table,
+        )
+    }
+
+    /// Reads one of the overviews stored within a GeoTIFF file, such as a
+    /// Cloud-Optimized GeoTIFF, into a read-only in-memory `Raster`. Overviews are
+    /// numbered from 1, for the largest; level 0 is the full-resolution image.
+    pub fn new_overview<'a>(file_name: &'a str, level: usize) -> Result<Raster, RasterError> {
+        let mut r = Raster {
+            file_name: file_name.to_string(),
+            file_mode: "r".to_string(),
+            raster_type: get_raster_type_from_file(file_name, "r")?,
+            ..Default::default()
+        };
+        if r.raster_type != RasterType::GeoTiff {
+            return Err(RasterError::UnknownFormat {
+                file_name: file_name.to_string(),
+                reason: "overviews can only be read from GeoTIFF files".to_string(),
+            });
         }
-        // Err(Error::new(ErrorKind::Other, "Error creating raster"))
+        let mut data = vec![];
+        read_geotiff_overview(&r.file_name, level, &mut r.configs, &mut data)
+            .map_err(|e| RasterError::read(file_name, e))?;
+        r.data = RasterData::from_values(r.configs.data_type, data);
+        r.update_min_max();
+        Ok(r)
     }
 
     /// Creates a new in-memory `Raster` object with grid extent and location
@@ -212,7 +262,8 @@ impl Raster {
             ..Default::default()
         };
         output.file_mode = "w".to_string();
-        output.raster_type = get_raster_type_from_file(new_file_name.clone(), "w".to_string());
+        output.raster_type =
+            get_raster_type_from_file(&new_file_name, "w").unwrap_or(RasterType::Unknown);
 
         output.configs.rows = configs.rows;
         output.configs.columns = configs.columns;
@@ -230,6 +281,7 @@ impl Raster {
         output.configs.xy_units = configs.xy_units.clone();
         output.configs.z_units = configs.z_units.clone();
         output.configs.endian = configs.endian.clone();
+        output.configs.interleave = configs.interleave;
         output.configs.pixel_is_area = configs.pixel_is_area;
         output.configs.epsg_code = configs.epsg_code;
         output.configs.coordinate_ref_system_wkt = configs.coordinate_ref_system_wkt.clone();
@@ -245,10 +297,7 @@ impl Raster {
         {
             output.configs.nodata = 1.71041e38;
         }
-        output
-            .data
-            .reserve(output.configs.rows * output.configs.columns);
-        output.data = vec![output.configs.nodata; output.configs.rows * output.configs.columns];
+        output.allocate_data(output.configs.nodata);
 
         output
     }
@@ -268,7 +317,8 @@ impl Raster {
             ..Default::default()
         };
         output.file_mode = "w".to_string();
-        output.raster_type = get_raster_type_from_file(new_file_name.clone(), "w".to_string());
+        output.raster_type =
+            get_raster_type_from_file(&new_file_name, "w").unwrap_or(RasterType::Unknown);
 
         output.configs.rows = configs.rows;
         output.configs.columns = configs.columns;
@@ -286,6 +336,7 @@ impl Raster {
         output.configs.xy_units = configs.xy_units.clone();
         output.configs.z_units = configs.z_units.clone();
         output.configs.endian = configs.endian.clone();
+        output.configs.interleave = configs.interleave;
         output.configs.pixel_is_area = configs.pixel_is_area;
         output.configs.epsg_code = configs.epsg_code;
         output.configs.coordinate_ref_system_wkt = configs.coordinate_ref_system_wkt.clone();
@@ -301,15 +352,14 @@ impl Raster {
         {
             output.configs.nodata = 1.71041e38;
         }
-        output
-            .data
-            .reserve(output.configs.rows * output.configs.columns);
+        let mut values = Vec::with_capacity(output.configs.rows * output.configs.columns);
         // output.data = vec![output.configs.nodata; output.configs.rows * output.configs.columns];
         for row in 0..output.configs.rows {
             for col in 0..output.configs.columns {
-                output.data.push(data.get_value(row as isize, col as isize).as_());
+                values.push(data.get_value(row as isize, col as isize).as_());
             }
         }
+        output.data = RasterData::from_values(output.configs.data_type, values);
 
         output
     }
@@ -328,7 +378,8 @@ impl Raster {
             ..Default::default()
         };
         output.file_mode = "w".to_string();
-        output.raster_type = get_raster_type_from_file(new_file_name.clone(), "w".to_string());
+        output.raster_type =
+            get_raster_type_from_file(&new_file_name, "w").unwrap_or(RasterType::Unknown);
         output.configs.rows = input.configs.rows;
         output.configs.columns = input.configs.columns;
         output.configs.north = input.configs.north;
@@ -345,6 +396,7 @@ impl Raster {
         output.configs.xy_units = input.configs.xy_units.clone();
         output.configs.z_units = input.configs.z_units.clone();
         output.configs.endian = input.configs.endian.clone();
+        output.configs.interleave = input.configs.interleave;
         // output.configs.palette_nonlinearity = input.configs.palette_nonlinearity;
         output.configs.pixel_is_area = input.configs.pixel_is_area;
         output.configs.epsg_code = input.configs.epsg_code;
@@ -361,10 +413,7 @@ impl Raster {
         {
             output.configs.nodata = 1.71041e38;
         }
-        output
-            .data
-            .reserve(output.configs.rows * output.configs.columns);
-        output.data = vec![output.configs.nodata; output.configs.rows * output.configs.columns];
+        output.allocate_data(output.configs.nodata);
         output
     }
 
@@ -387,7 +436,8 @@ impl Raster {
             eprintln!("Warning: the Array2D and configs don't share the same dimensions. This may cause problems.");
         }
         output.file_mode = "w".to_string();
-        output.raster_type = get_raster_type_from_file(new_file_name.clone(), "w".to_string());
+        output.raster_type =
+            get_raster_type_from_file(&new_file_name, "w").unwrap_or(RasterType::Unknown);
         output.configs.rows = array.rows as usize;
         output.configs.columns = array.columns as usize;
         output.configs.north = configs.north;
@@ -404,6 +454,7 @@ impl Raster {
         output.configs.xy_units = configs.xy_units.clone();
         output.configs.z_units = configs.z_units.clone();
         output.configs.endian = configs.endian.clone();
+        output.configs.interleave = configs.interleave;
         output.configs.pixel_is_area = configs.pixel_is_area;
         output.configs.epsg_code = configs.epsg_code;
         output.configs.coordinate_ref_system_wkt = configs.coordinate_ref_system_wkt.clone();
@@ -419,14 +470,13 @@ impl Raster {
         {
             output.configs.nodata = 1.71041e38;
         }
-        output
-            .data
-            .reserve_exact(output.configs.rows * output.configs.columns);
+        let mut values = Vec::with_capacity(output.configs.rows * output.configs.columns);
         for row in 0..array.rows {
             for col in 0..array.columns {
-                output.data.push(array.get_value(row, col).into());
+                values.push(array.get_value(row, col).into());
             }
         }
+        output.data = RasterData::from_values(output.configs.data_type, values);
         output
     }
 
@@ -446,9 +496,216 @@ impl Raster {
         e.to_string()
     }
 
+    /// Fills the cells of a newly created raster with `value`. Rasters that exceed
+    /// the memory budget are backed by a scratch file rather than held in memory.
+    fn allocate_data(&mut self, value: f64) {
+        let num_cells = self.configs.rows * self.configs.columns * self.configs.bands;
+        let max_memory = get_max_memory();
+        if max_memory > 0 && num_cells * RasterData::cell_size(self.configs.data_type) > max_memory {
+            match BlockCache::new(
+                Box::new(FillSource::new(value)),
+                &self.file_name,
+                self.configs.rows * self.configs.bands,
+                self.configs.columns,
+                max_memory,
+            ) {
+                Ok(cache) => {
+                    self.data = RasterData::default();
+                    self.cache = Some(cache);
+                    return;
+                }
+                Err(e) => println!(
+                    "Warning: Unable to create a scratch file for {} ({}). The raster will be held in memory.",
+                    self.file_name, e
+                ),
+            }
+        }
+        self.data = RasterData::new(self.configs.data_type, num_cells, value);
+    }
+
+    /// Reads the raster file one block of rows at a time. If the values would occupy
+    /// more than `max_memory` bytes (unless it is zero), the file is only opened and
+    /// the values are instead read as they are accessed. Returns false, leaving the
+    /// raster unchanged, if the format can't be read one block at a time.
+    fn read_by_blocks(&mut self, max_memory: usize) -> Result<bool, Error> {
+        let mut configs = RasterConfigs {
+            ..Default::default()
+        };
+        let writable = self.file_mode.contains("w");
+        let source: Box<dyn BlockSource> = match self.raster_type {
+            RasterType::GeoTiff => {
+                let (reader, layout) = read_geotiff_header(&self.file_name, &mut configs, 0)?;
+                Box::new(GeoTiffBlockSource::new(reader, layout))
+            }
+            RasterType::Whitebox => {
+                read_whitebox_header(&self.file_name, &mut configs)?;
+                if configs.data_type == DataType::RGBA32 {
+                    return Ok(false);
+                }
+                let row_bytes = configs.columns * configs.data_type.get_data_size();
+                let layout = RawLayout {
+                    band_row_bytes: row_bytes,
+                    total_row_bytes: row_bytes,
+                    ..Default::default()
+                };
+                let data_file = get_data_file_name(&self.file_name, "dep", "tas");
+                // The header of an I32 raster is rewritten as FLOAT, so those are always written in full.
+                let writable = writable
+                    && match configs.data_type {
+                        DataType::F64 | DataType::F32 | DataType::I16 | DataType::U8 => true,
+                        _ => false,
+                    };
+                Box::new(RawBlockSource::new(&data_file, &configs, layout, writable)?)
+            }
+            RasterType::EsriBil => {
+                let layout = read_esri_bil_header(&self.file_name, &mut configs)?;
+                // Rewriting the header would discard any non-standard arrangement of the data file.
+                let row_bytes = configs.columns * configs.data_type.get_data_size();
+                let writable = writable
+                    && layout.skip_bytes == 0
+                    && layout.band_gap_bytes == 0
+                    && layout.band_row_bytes == row_bytes
+                    && layout.total_row_bytes
+                        == match configs.interleave {
+                            BandInterleave::BSQ => row_bytes,
+                            _ => row_bytes * configs.bands,
+                        };
+                Box::new(RawBlockSource::new(
+                    &get_bil_data_file_name(&self.file_name),
+                    &configs,
+                    layout,
+                    writable,
+                )?)
+            }
+            RasterType::SrtmHgt => {
+                let layout = read_srtm_header(&self.file_name, &mut configs)?;
+                Box::new(RawBlockSource::new(&self.file_name, &configs, layout, writable)?)
+            }
+            RasterType::Envi => {
+                let layout = read_envi_header(&self.file_name, &mut configs)?;
+                // The header is rewritten without the original header offset.
+                let writable = writable && layout.skip_bytes == 0;
+                Box::new(RawBlockSource::new(
+                    &get_envi_data_file_name(&self.file_name),
+                    &configs,
+                    layout,
+                    writable,
+                )?)
+            }
+            _ => return Ok(false),
+        };
+        let num_values = configs.rows * configs.columns * configs.bands;
+        if max_memory > 0 && num_values * RasterData::cell_size(configs.data_type) > max_memory {
+            self.cache = Some(BlockCache::new(
+                source,
+                &self.file_name,
+                configs.rows * configs.bands,
+                configs.columns,
+                max_memory,
+            )?);
+        } else {
+            let mut source = source;
+            self.data = RasterData::read(
+                source.as_mut(),
+                configs.data_type,
+                configs.rows * configs.bands,
+                configs.columns,
+            )?;
+        }
+        self.configs = configs;
+        if self.raster_type != RasterType::Whitebox {
+            // The range of values isn't stored in the file's header.
+            self.update_min_max();
+        }
+        Ok(true)
+    }
+
+    /// Returns true if the raster's values are read from disk as they are accessed,
+    /// rather than being held in memory.
+    pub fn is_out_of_core(&self) -> bool {
+        self.cache.is_some()
+    }
+
+    /// Returns the type in which the raster's values are held in memory. This is
+    /// usually the raster's `DataType`, or `F64` if a value has been assigned that
+    /// the data type can't represent, or the values are read as they are accessed.
+    pub fn get_storage_data_type(&self) -> DataType {
+        match &self.cache {
+            Some(_) => DataType::F64,
+            None => self.data.data_type(),
+        }
+    }
+
+    #[inline]
+    fn try_get_cell(&self, idx: usize) -> Result<f64, RasterError> {
+        match &self.cache {
+            Some(cache) => cache.get(idx).map_err(|e| RasterError::read(cache.file_name(), e)),
+            None => Ok(self.data.get(idx)),
+        }
+    }
+
+    #[inline]
+    fn try_set_cell(&mut self, idx: usize, value: f64) -> Result<(), RasterError> {
+        match &mut self.cache {
+            Some(cache) => cache
+                .set(idx, value)
+                .map_err(|e| RasterError::read(&self.file_name, e)),
+            None => {
+                self.data.set(idx, value);
+                Ok(())
+            }
+        }
+    }
+
+    /// The infallible accessors panic if the values of an out-of-core raster can't be
+    /// read from its file; `try_get_value` and `try_set_value` return the error instead.
+    #[inline]
+    fn get_cell(&self, idx: usize) -> f64 {
+        match self.try_get_cell(idx) {
+            Ok(value) => value,
+            Err(e) => panic!("{}", e),
+        }
+    }
+
+    #[inline]
+    fn set_cell(&mut self, idx: usize, value: f64) {
+        if let Err(e) = self.try_set_cell(idx, value) {
+            panic!("{}", e);
+        }
+    }
+
+    /// Returns the number of cells, of every band, for which values are held.
+    fn num_stored_cells(&self) -> usize {
+        match &self.cache {
+            Some(cache) => cache.len(),
+            None => self.data.len(),
+        }
+    }
+
     /// Returns the value contained within a grid cell specified
     /// by `row` and `column`.
+    ///
+    /// Panics if the raster's values are read as they are accessed and the file can't
+    /// be read; use `try_get_value` to handle the error instead.
     pub fn get_value(&self, row: isize, column: isize) -> f64 {
+        match self.get_cell_index(row, column) {
+            Some(idx) => self.get_cell(idx),
+            None => self.configs.nodata,
+        }
+    }
+
+    /// Returns the value contained within a grid cell, like `get_value`, or an error if
+    /// the raster's values are read as they are accessed and the file can't be read.
+    pub fn try_get_value(&self, row: isize, column: isize) -> Result<f64, RasterError> {
+        match self.get_cell_index(row, column) {
+            Some(idx) => self.try_get_cell(idx),
+            None => Ok(self.configs.nodata),
+        }
+    }
+
+    /// Returns the index of the cell whose value is returned by `get_value`, or None if
+    /// the cell is off the grid and NoData is returned.
+    fn get_cell_index(&self, row: isize, column: isize) -> Option<usize> {
         // if row < 0 || column < 0 { return self.configs.nodata; }
         // if row as usize >= self.configs.rows || column as usize >= self.configs.columns { return self.configs.nodata; }
         // self.data[row as usize * self.configs.columns + column as usize]
@@ -462,12 +719,12 @@ impl Raster {
             let r: usize = row as usize;
 
             let idx: usize = r * self.configs.columns + c;
-            return self.data[idx];
+            return Some(idx);
         }
 
         // it's not within the area of the data
         if !self.configs.reflect_at_edges {
-            return self.configs.nodata;
+            return None;
         }
 
         let mut c = column;
@@ -491,22 +748,33 @@ impl Raster {
             && row >= 0
             && row < self.configs.rows as isize
         {
-            return self.get_value(r, c);
+            return self.get_cell_index(r, c);
         }
 
         // it was too off grid to be reflected.
-        self.configs.nodata
+        None
     }
 
+    /// Panics if the raster's values are read as they are accessed and the file can't
+    /// be read; use `try_set_value` to handle the error instead.
     pub fn set_value(&mut self, row: isize, column: isize, value: f64) {
+        if let Err(e) = self.try_set_value(row, column, value) {
+            panic!("{}", e);
+        }
+    }
+
+    /// Assigns the value of a grid cell, like `set_value`, or returns an error if the
+    /// raster's values are read as they are accessed and the file can't be read.
+    pub fn try_set_value(&mut self, row: isize, column: isize, value: f64) -> Result<(), RasterError> {
         if column >= 0 && row >= 0 {
             let c: usize = column as usize;
             let r: usize = row as usize;
             if c < self.configs.columns && r < self.configs.rows {
                 let idx = r * self.configs.columns + c;
-                self.data[idx] = value;
+                self.try_set_cell(idx, value)?;
             }
         }
+        Ok(())
     }
 
     pub fn decrement(&mut self, row: isize, column: isize, value: f64) {
@@ -515,10 +783,10 @@ impl Raster {
             let r: usize = row as usize;
             if c < self.configs.columns && r < self.configs.rows {
                 let idx = r * self.configs.columns + c;
-                if self.data[idx] != self.configs.nodata {
-                    self.data[idx] -= value;
+                if self.get_cell(idx) != self.configs.nodata {
+                    self.set_cell(idx, self.get_cell(idx) - value);
                 } else {
-                    self.data[idx] = value;
+                    self.set_cell(idx, value);
                 }
             }
         }
@@ -530,10 +798,10 @@ impl Raster {
             let r: usize = row as usize;
             if c < self.configs.columns && r < self.configs.rows {
                 let idx = r * self.configs.columns + c;
-                if self.data[idx] != self.configs.nodata {
-                    self.data[idx] += value;
+                if self.get_cell(idx) != self.configs.nodata {
+                    self.set_cell(idx, self.get_cell(idx) + value);
                 } else {
-                    self.data[idx] = value;
+                    self.set_cell(idx, value);
                 }
             }
         }
@@ -546,7 +814,7 @@ impl Raster {
                 let r: usize = row as usize;
                 if c < self.configs.columns && r < self.configs.rows {
                     let idx = r * self.configs.columns + c;
-                    self.data[idx] = values[c];
+                    self.set_cell(idx, values[c]);
                 }
             }
         }
@@ -556,12 +824,222 @@ impl Raster {
         let mut values: Vec<f64> = vec![self.configs.nodata; self.configs.columns];
         if row >= 0 && row < self.configs.rows as isize {
             for column in 0..values.len() {
-                values[column] = self.data[row as usize * self.configs.columns + column];
+                values[column] = self.get_cell(row as usize * self.configs.columns + column);
             }
         }
         values
     }
 
+    /// Returns a copy of the cell values of the first band.
+    fn get_first_band_values(&self) -> Vec<f64> {
+        let n = self.num_cells().min(self.num_stored_cells());
+        (0..n).map(|i| self.get_cell(i)).collect()
+    }
+
+    /// Returns the number of bands contained within the raster.
+    pub fn num_bands(&self) -> usize {
+        self.configs.bands
+    }
+
+    /// Sets the number of bands contained within the raster. Newly added bands
+    /// are filled with their NoData value; surplus bands are discarded.
+    ///
+    /// Panics if the raster's values are read as they are accessed and the file can't
+    /// be read.
+    pub fn set_num_bands(&mut self, bands: usize) {
+        let bands = bands.max(1);
+        let num_cells = self.num_cells();
+        let old_bands = self.configs.bands;
+        self.configs.bands = bands;
+        if let Some(old_cache) = self.cache.take() {
+            // The size of a cache is fixed, so the values are copied into a new one.
+            match BlockCache::new(
+                Box::new(FillSource::new(self.configs.nodata)),
+                &self.file_name,
+                self.configs.rows * bands,
+                self.configs.columns,
+                old_cache.max_memory(),
+            ) {
+                Ok(mut cache) => {
+                    for idx in 0..num_cells * bands.min(old_bands) {
+                        // the new cache is filled in memory, so only reading the old one can fail
+                        let _ = cache.set(idx, read_cached_cell(&old_cache, idx));
+                    }
+                    for b in old_bands..bands {
+                        let nodata = self.get_band_nodata(b);
+                        if nodata != self.configs.nodata {
+                            for idx in b * num_cells..(b + 1) * num_cells {
+                                let _ = cache.set(idx, nodata);
+                            }
+                        }
+                    }
+                    self.cache = Some(cache);
+                }
+                Err(e) => {
+                    println!(
+                        "Warning: Unable to create a scratch file for {} ({}). The raster will be held in memory.",
+                        self.file_name, e
+                    );
+                    self.data = read_cache_into_memory(&old_cache);
+                }
+            }
+        }
+        if self.cache.is_none() {
+            self.data.truncate(num_cells * bands.min(old_bands));
+            for b in old_bands..bands {
+                let nodata = self.get_band_nodata(b);
+                self.data.extend(nodata, num_cells);
+            }
+        }
+        self.configs.band_nodata.truncate(bands);
+        self.configs.band_names.truncate(bands);
+        self.configs.wavelengths.truncate(bands);
+    }
+
+    /// Updates the cell counts of the classes listed in the attribute table, i.e. the
+    /// number of cells of the first band that have the value of each class.
+    pub fn update_attribute_counts(&mut self) {
+        if self.configs.attribute_table.is_empty() {
+            return;
+        }
+        let mut counts = HashMap::new();
+        let nodata = self.configs.nodata;
+        for idx in 0..self.num_cells() {
+            let z = self.get_cell(idx);
+            if z != nodata {
+                *counts.entry(z.round() as i64).or_insert(0usize) += 1;
+            }
+        }
+        let values: Vec<i64> = self
+            .configs
+            .attribute_table
+            .entries()
+            .iter()
+            .map(|e| e.value)
+            .collect();
+        for value in values {
+            if let Some(entry) = self.configs.attribute_table.get_entry_mut(value) {
+                entry.count = *counts.get(&value).unwrap_or(&0);
+            }
+        }
+    }
+
+    /// Returns the NoData value of a band. Bands without an explicitly
+    /// assigned value share the raster's `nodata` value.
+    pub fn get_band_nodata(&self, band: usize) -> f64 {
+        if band < self.configs.band_nodata.len() {
+            return self.configs.band_nodata[band];
+        }
+        self.configs.nodata
+    }
+
+    /// Assigns the NoData value of a band. Setting the value of the
+    /// first band also updates the raster's `nodata` value.
+    pub fn set_band_nodata(&mut self, band: usize, value: f64) {
+        if band >= self.configs.bands {
+            return;
+        }
+        if self.configs.band_nodata.len() < self.configs.bands {
+            let nodata = self.configs.nodata;
+            self.configs.band_nodata.resize(self.configs.bands, nodata);
+        }
+        self.configs.band_nodata[band] = value;
+        if band == 0 {
+            self.configs.nodata = value;
+        }
+    }
+
+    /// Returns the value contained within a grid cell of a band. Cells that
+    /// are outside of the grid, or bands that don't exist, return NoData.
+    pub fn get_band_value(&self, band: usize, row: isize, column: isize) -> f64 {
+        if band == 0 {
+            return self.get_value(row, column);
+        }
+        if band < self.configs.bands
+            && column >= 0
+            && row >= 0
+            && column < self.configs.columns as isize
+            && row < self.configs.rows as isize
+        {
+            let idx = band * self.num_cells() + row as usize * self.configs.columns + column as usize;
+            return self.get_cell(idx);
+        }
+        self.get_band_nodata(band)
+    }
+
+    pub fn set_band_value(&mut self, band: usize, row: isize, column: isize, value: f64) {
+        if band < self.configs.bands && column >= 0 && row >= 0 {
+            let c: usize = column as usize;
+            let r: usize = row as usize;
+            if c < self.configs.columns && r < self.configs.rows {
+                let idx = band * self.num_cells() + r * self.configs.columns + c;
+                self.set_cell(idx, value);
+            }
+        }
+    }
+
+    pub fn get_band_row_data(&self, band: usize, row: isize) -> Vec<f64> {
+        let mut values: Vec<f64> = vec![self.get_band_nodata(band); self.configs.columns];
+        if band < self.configs.bands && row >= 0 && row < self.configs.rows as isize {
+            let start = band * self.num_cells() + row as usize * self.configs.columns;
+            for column in 0..self.configs.columns {
+                values[column] = self.get_cell(start + column);
+            }
+        }
+        values
+    }
+
+    pub fn set_band_row_data(&mut self, band: usize, row: isize, values: Vec<f64>) {
+        if band < self.configs.bands && row >= 0 && (row as usize) < self.configs.rows {
+            let start = band * self.num_cells() + row as usize * self.configs.columns;
+            for column in 0..values.len().min(self.configs.columns) {
+                self.set_cell(start + column, values[column]);
+            }
+        }
+    }
+
+    /// Returns the minimum, maximum, mean, standard deviation and number of
+    /// valid cells of a band, ignoring the band's NoData cells.
+    pub fn get_band_statistics(&self, band: usize) -> BandStatistics {
+        let mut stats = BandStatistics {
+            minimum: f64::INFINITY,
+            maximum: f64::NEG_INFINITY,
+            mean: 0f64,
+            std_dev: 0f64,
+            num_valid_cells: 0usize,
+        };
+        if band >= self.configs.bands {
+            return stats;
+        }
+        let nodata = self.get_band_nodata(band);
+        let num_cells = self.num_cells();
+        let values = (band * num_cells..(band + 1) * num_cells).map(|i| self.get_cell(i));
+        let mut sum = 0f64;
+        let mut sq_sum = 0f64;
+        for z in values.clone() {
+            if z != nodata && !z.is_nan() {
+                if z < stats.minimum {
+                    stats.minimum = z;
+                }
+                if z > stats.maximum {
+                    stats.maximum = z;
+                }
+                sum += z;
+                stats.num_valid_cells += 1;
+            }
+        }
+        if stats.num_valid_cells > 0 {
+            stats.mean = sum / stats.num_valid_cells as f64;
+            for z in values {
+                if z != nodata && !z.is_nan() {
+                    sq_sum += (z - stats.mean) * (z - stats.mean);
+                }
+            }
+            stats.std_dev = (sq_sum / stats.num_valid_cells as f64).sqrt();
+        }
+        stats
+    }
+
     pub fn increment_row_data(&mut self, row: isize, values: Vec<f64>) {
         assert!(values.len() == self.configs.columns);
         if row < 0 {
@@ -573,10 +1051,10 @@ impl Raster {
         }
         for column in 0..self.configs.columns {
             let idx = r * self.configs.columns + column;
-            if self.data[idx] != self.configs.nodata {
-                self.data[idx] += values[column];
+            if self.get_cell(idx) != self.configs.nodata {
+                self.set_cell(idx, self.get_cell(idx) + values[column]);
             } else {
-                self.data[idx] = values[column];
+                self.set_cell(idx, values[column]);
             }
         }
     }
@@ -592,10 +1070,10 @@ impl Raster {
         }
         for column in 0..self.configs.columns {
             let idx = r * self.configs.columns + column;
-            if self.data[idx] != self.configs.nodata {
-                self.data[idx] -= values[column];
+            if self.get_cell(idx) != self.configs.nodata {
+                self.set_cell(idx, self.get_cell(idx) - values[column]);
             } else {
-                self.data[idx] = values[column];
+                self.set_cell(idx, values[column]);
             }
         }
     }
@@ -654,7 +1132,7 @@ impl Raster {
         array: &'a Array2D<T>,
     ) -> Result<(), Error> {
         // quality control
-        if array.rows * array.columns != self.data.len() as isize {
+        if array.rows * array.columns != self.num_cells() as isize {
             return Err(Error::new(
                 ErrorKind::Other,
                 "Rasters must have the same dimensions and extent.",
@@ -664,7 +1142,7 @@ impl Raster {
         for row in 0..array.rows {
             for col in 0..array.columns {
                 i = row as usize * self.configs.columns + col as usize;
-                self.data[i] = array.get_value(row, col).into();
+                self.set_cell(i, array.get_value(row, col).into());
             }
         }
         self.configs.nodata = array.nodata().into();
@@ -672,7 +1150,12 @@ impl Raster {
     }
 
     pub fn reinitialize_values(&mut self, value: f64) {
-        self.data = vec![value; self.configs.rows * self.configs.columns];
+        if let Some(cache) = &mut self.cache {
+            cache.fill(value);
+            return;
+        }
+        let num_cells = self.configs.rows * self.configs.columns * self.configs.bands;
+        self.data = RasterData::new(self.configs.data_type, num_cells, value);
     }
 
     pub fn get_value_as_rgba(&self, row: isize, column: isize) -> (u8, u8, u8, u8) {
@@ -693,7 +1176,7 @@ impl Raster {
             return (0, 0, 0, 0);
         }
         let idx: usize = r * self.configs.columns + c;
-        let z = self.data[idx];
+        let z = self.get_cell(idx);
 
         let r = (z as u32 & 0xFF) as u8;
         let g = ((z as u32 >> 8) & 0xFF) as u8;
@@ -710,15 +1193,18 @@ impl Raster {
             if c < self.configs.columns && r < self.configs.rows {
                 let idx = r * self.configs.columns + c;
                 let (r, g, b, a) = rgba;
-                self.data[idx] += ((a << 24) | (b << 16) | (g << 8) | r) as f64;
+                let z = self.get_cell(idx) + ((a << 24) | (b << 16) | (g << 8) | r) as f64;
+                self.set_cell(idx, z);
             }
         }
     }
 
     /// Returns the size of the pixel data in bytes.
     pub fn get_data_size_in_bytes(&self) -> usize {
-        use std::mem;
-        mem::size_of_val(&*self.data)
+        if let Some(cache) = &self.cache {
+            return cache.len() * mem::size_of::<f64>();
+        }
+        self.data.size_in_bytes()
     }
 
     pub fn get_x_from_column(&self, column: isize) -> f64 {
@@ -746,7 +1232,7 @@ impl Raster {
 
     pub fn clip_display_min_max(&mut self, percent: f64) {
         let t = (percent / 100.0 * (self.configs.rows * self.configs.columns) as f64) as usize;
-        let mut d = self.data.clone();
+        let mut d = self.get_first_band_values();
         d.sort_by(|a, b| a.partial_cmp(b).unwrap_or(Equal));
         let mut sum = 0;
         for i in 0..d.len() {
@@ -773,7 +1259,7 @@ impl Raster {
 
     pub fn clip_display_min(&mut self, percent: f64) {
         let t = (percent / 100.0 * (self.configs.rows * self.configs.columns) as f64) as usize;
-        let mut d = self.data.clone();
+        let mut d = self.get_first_band_values();
         d.sort_by(|a, b| a.partial_cmp(b).unwrap_or(Equal));
         let mut sum = 0;
         for i in 0..d.len() {
@@ -789,7 +1275,7 @@ impl Raster {
 
     pub fn clip_display_max(&mut self, percent: f64) {
         let t = (percent / 100.0 * (self.configs.rows * self.configs.columns) as f64) as usize;
-        let mut d = self.data.clone();
+        let mut d = self.get_first_band_values();
         d.sort_by(|a, b| a.partial_cmp(b).unwrap_or(Equal));
         let mut sum = 0;
         for i in (0..d.len()).rev() {
@@ -841,7 +1327,7 @@ impl Raster {
 
     pub fn clip_min_by_percent(&mut self, percent: f64) {
         let t = (percent / 100.0 * (self.configs.rows * self.configs.columns) as f64) as usize;
-        let mut d = self.data.clone();
+        let mut d = self.get_first_band_values();
         d.sort_by(|a, b| a.partial_cmp(b).unwrap_or(Equal));
         let mut sum = 0;
         let mut val = 0.0;
@@ -855,10 +1341,10 @@ impl Raster {
             }
         }
 
-        for i in 0..self.data.len() {
-            if self.data[i] != self.configs.nodata {
-                if self.data[i] < val {
-                    self.data[i] = val;
+        for i in 0..d.len() {
+            if self.get_cell(i) != self.configs.nodata {
+                if self.get_cell(i) < val {
+                    self.set_cell(i, val);
                 }
             }
         }
@@ -868,7 +1354,7 @@ impl Raster {
 
     pub fn clip_max_by_percent(&mut self, percent: f64) {
         let t = (percent / 100.0 * (self.configs.rows * self.configs.columns) as f64) as usize;
-        let mut d = self.data.clone();
+        let mut d = self.get_first_band_values();
         d.sort_by(|a, b| a.partial_cmp(b).unwrap_or(Equal));
         let mut sum = 0;
         let mut val = 0.0;
@@ -882,10 +1368,10 @@ impl Raster {
             }
         }
 
-        for i in 0..self.data.len() {
-            if self.data[i] != self.configs.nodata {
-                if self.data[i] > val {
-                    self.data[i] = val;
+        for i in 0..d.len() {
+            if self.get_cell(i) != self.configs.nodata {
+                if self.get_cell(i) > val {
+                    self.set_cell(i, val);
                 }
             }
         }
@@ -895,7 +1381,7 @@ impl Raster {
 
     pub fn clip_min_and_max_by_percent(&mut self, percent: f64) {
         let t = (percent / 100.0 * (self.configs.rows * self.configs.columns) as f64) as usize;
-        let mut d = self.data.clone();
+        let mut d = self.get_first_band_values();
         d.sort_by(|a, b| a.partial_cmp(b).unwrap_or(Equal));
         let mut sum = 0;
         let mut lower_val = 0.0;
@@ -921,12 +1407,12 @@ impl Raster {
             }
         }
 
-        for i in 0..self.data.len() {
-            if self.data[i] != self.configs.nodata {
-                if self.data[i] < lower_val {
-                    self.data[i] = lower_val;
-                } else if self.data[i] > upper_val {
-                    self.data[i] = upper_val;
+        for i in 0..d.len() {
+            if self.get_cell(i) != self.configs.nodata {
+                if self.get_cell(i) < lower_val {
+                    self.set_cell(i, lower_val);
+                } else if self.get_cell(i) > upper_val {
+                    self.set_cell(i, upper_val);
                 }
             }
         }
@@ -940,29 +1426,31 @@ impl Raster {
         self.configs.maximum = f64::NEG_INFINITY;
         let num_procs = num_cpus::get();
         let nodata = self.configs.nodata;
-        let values = Arc::new(self.data.clone());
+        let raster = &*self;
+        let num_cells = self.num_cells().min(self.num_stored_cells());
         let (tx, rx) = mpsc::channel();
-        for tid in 0..num_procs {
-            let values = values.clone();
-            let tx = tx.clone();
-            thread::spawn(move || {
-                let mut min_val = f64::INFINITY;
-                let mut max_val = f64::NEG_INFINITY;
-                let mut value: f64;
-                for i in (0..values.len()).filter(|v| v % num_procs == tid) {
-                    value = values[i];
-                    if value != nodata {
-                        if value < min_val {
-                            min_val = value;
-                        }
-                        if value > max_val {
-                            max_val = value;
+        thread::scope(|scope| {
+            for tid in 0..num_procs {
+                let tx = tx.clone();
+                scope.spawn(move || {
+                    let mut min_val = f64::INFINITY;
+                    let mut max_val = f64::NEG_INFINITY;
+                    let mut value: f64;
+                    for i in (0..num_cells).filter(|v| v % num_procs == tid) {
+                        value = raster.get_cell(i);
+                        if value != nodata {
+                            if value < min_val {
+                                min_val = value;
+                            }
+                            if value > max_val {
+                                max_val = value;
+                            }
                         }
                     }
-                }
-                tx.send((min_val, max_val)).unwrap();
-            });
-        }
+                    tx.send((min_val, max_val)).unwrap();
+                });
+            }
+        });
 
         for _ in 0..num_procs {
             let (min_val, max_val) = rx.recv().expect("Error receiving data from thread.");
@@ -996,27 +1484,28 @@ impl Raster {
     }
 
     pub fn num_valid_cells(&self) -> usize {
-        if self.data.len() == 0 {
+        if self.num_stored_cells() == 0 {
             return 0usize;
         }
         let nodata = self.configs.nodata;
-        let values = Arc::new(self.data.clone());
+        let raster = &*self;
         let num_procs = num_cpus::get();
         let num_cells = self.num_cells();
         let (tx, rx) = mpsc::channel();
-        for tid in 0..num_procs {
-            let values = values.clone();
-            let tx = tx.clone();
-            thread::spawn(move || {
-                let mut count = 0usize;
-                for i in (0..num_cells).filter(|r| r % num_procs == tid) {
-                    if values[i] != nodata {
-                        count += 1;
+        thread::scope(|scope| {
+            for tid in 0..num_procs {
+                let tx = tx.clone();
+                scope.spawn(move || {
+                    let mut count = 0usize;
+                    for i in (0..num_cells).filter(|r| r % num_procs == tid) {
+                        if raster.get_cell(i) != nodata {
+                            count += 1;
+                        }
                     }
-                }
-                tx.send(count).unwrap();
-            });
-        }
+                    tx.send(count).unwrap();
+                });
+            }
+        });
 
         let mut count = 0usize;
         for _ in 0..num_procs {
@@ -1027,29 +1516,31 @@ impl Raster {
     }
 
     pub fn calculate_mean(&self) -> f64 {
-        if self.data.len() == 0 {
+        if self.num_stored_cells() == 0 {
             return 0.0;
         }
         let nodata = self.configs.nodata;
-        let values = Arc::new(self.data.clone());
+        let raster = &*self;
         let num_procs = num_cpus::get();
         let num_cells = self.num_cells();
         let (tx, rx) = mpsc::channel();
-        for tid in 0..num_procs {
-            let values = values.clone();
-            let tx = tx.clone();
-            thread::spawn(move || {
-                let mut sum = 0.0f64;
-                let mut count = 0.0f64;
-                for i in (0..num_cells).filter(|r| r % num_procs == tid) {
-                    if values[i] != nodata {
-                        sum += values[i];
-                        count += 1.0;
+        thread::scope(|scope| {
+            for tid in 0..num_procs {
+                let tx = tx.clone();
+                scope.spawn(move || {
+                    let mut sum = 0.0f64;
+                    let mut count = 0.0f64;
+                    for i in (0..num_cells).filter(|r| r % num_procs == tid) {
+                        let value = raster.get_cell(i);
+                        if value != nodata {
+                            sum += value;
+                            count += 1.0;
+                        }
                     }
-                }
-                tx.send((sum, count)).unwrap();
-            });
-        }
+                    tx.send((sum, count)).unwrap();
+                });
+            }
+        });
 
         let mut sum = 0.0f64;
         let mut count = 0.0f64;
@@ -1063,31 +1554,33 @@ impl Raster {
     }
 
     pub fn calculate_mean_and_stdev(&self) -> (f64, f64) {
-        if self.data.len() == 0 {
+        if self.num_stored_cells() == 0 {
             return (0.0, 0.0);
         }
 
         let mean = self.calculate_mean();
         let nodata = self.configs.nodata;
-        let values = Arc::new(self.data.clone());
+        let raster = &*self;
         let num_procs = num_cpus::get();
         let num_cells = self.num_cells();
         let (tx, rx) = mpsc::channel();
-        for tid in 0..num_procs {
-            let values = values.clone();
-            let tx = tx.clone();
-            thread::spawn(move || {
-                let mut sq_diff_sum = 0.0f64;
-                let mut count = 0.0f64;
-                for i in (0..num_cells).filter(|r| r % num_procs == tid) {
-                    if values[i] != nodata {
-                        sq_diff_sum += (values[i] - mean) * (values[i] - mean);
-                        count += 1.0;
+        thread::scope(|scope| {
+            for tid in 0..num_procs {
+                let tx = tx.clone();
+                scope.spawn(move || {
+                    let mut sq_diff_sum = 0.0f64;
+                    let mut count = 0.0f64;
+                    for i in (0..num_cells).filter(|r| r % num_procs == tid) {
+                        let value = raster.get_cell(i);
+                        if value != nodata {
+                            sq_diff_sum += (value - mean) * (value - mean);
+                            count += 1.0;
+                        }
+                        tx.send((sq_diff_sum, count)).unwrap();
                     }
-                    tx.send((sq_diff_sum, count)).unwrap();
-                }
-            });
-        }
+                });
+            }
+        });
 
         let mut sq_diff_sum = 0.0f64;
         let mut count = 0.0f64;
@@ -1104,7 +1597,7 @@ impl Raster {
         let t = (percent / 100.0 * (self.configs.rows * self.configs.columns) as f64) as usize;
         let mut lower_tail = f64::NEG_INFINITY;
         let mut upper_tail = f64::NEG_INFINITY;
-        let mut d = self.data.clone();
+        let mut d = self.get_first_band_values();
         d.sort_by(|a, b| a.partial_cmp(b).unwrap_or(Equal));
         let mut sum = 0;
         for i in 0..d.len() {
@@ -1131,79 +1624,81 @@ impl Raster {
         (lower_tail, upper_tail)
     }
 
-    pub fn write(&mut self) -> Result<(), Error> {
+    /// Writes the raster to its file, in the format given by the file extension. Errors
+    /// are returned, rather than reported, so that a failed write, e.g. to a full disk,
+    /// is not mistaken for a successful one.
+    pub fn write(&mut self) -> Result<(), RasterError> {
         if !self.file_mode.contains("w") {
-            return Err(Error::new(
-                ErrorKind::Other,
-                "Cannot write raster that is not created in write mmode ('w').",
-            ));
+            return Err(RasterError::NotWritable {
+                file_name: self.file_name.clone(),
+            });
         }
-        match self.raster_type {
-            RasterType::ArcAscii => {
-                let _ = match write_arcascii(self) {
-                    Ok(_) => (),
-                    Err(e) => println!("error while writing: {:?}", e),
-                };
-            }
-            RasterType::ArcBinary => {
-                let _ = match write_arcbinary(self) {
-                    Ok(_) => (),
-                    Err(e) => println!("error while writing: {:?}", e),
-                };
-            }
-            RasterType::EsriBil => {
-                let _ = match write_esri_bil(self) {
-                    Ok(_) => (),
-                    Err(e) => println!("error while writing: {:?}", e),
-                };
-            }
-            RasterType::GeoTiff => {
-                let _ = match write_geotiff(self) {
-                    Ok(_) => (),
-                    Err(e) => println!("error while writing: {:?}", e),
-                };
-            }
-            RasterType::GrassAscii => {
-                let _ = match write_grass_raster(self) {
-                    Ok(_) => (),
-                    Err(e) => println!("error while writing: {:?}", e),
-                };
-            }
-            RasterType::IdrisiBinary => {
-                let _ = match write_idrisi(self) {
-                    Ok(_) => (),
-                    Err(e) => println!("error while writing: {:?}", e),
-                };
-            }
-            RasterType::SagaBinary => {
-                let _ = match write_saga(self) {
-                    Ok(_) => (),
-                    Err(e) => println!("error while writing: {:?}", e),
-                };
-            }
-            RasterType::Surfer7Binary => {
-                let _ = match write_surfer7(self) {
-                    Ok(_) => (),
-                    Err(e) => println!("error while writing: {:?}", e),
-                };
-            }
-            RasterType::SurferAscii => {
-                let _ = match write_surfer_ascii_raster(self) {
-                    Ok(_) => (),
-                    Err(e) => println!("error while writing: {:?}", e),
-                };
-            }
-            RasterType::Whitebox => {
-                let _ = match write_whitebox(self) {
-                    Ok(_) => (),
-                    Err(e) => println!("error while writing: {:?}", e),
-                };
-            }
-            RasterType::Unknown => {
-                return Err(Error::new(ErrorKind::Other, "Unrecognized raster type"));
+        if self.raster_type == RasterType::Unknown {
+            // The file name was not checked when the raster was initialized.
+            get_raster_type_from_file(&self.file_name, "w")?;
+        }
+        let file_name = self.file_name.clone();
+        self.write_file()
+            .and_then(|_| self.write_attribute_tables())
+            .map_err(|e| RasterError::write(&file_name, e))
+    }
+
+    /// Writes the colour table and attribute table of the raster, if it has either, to an
+    /// auxiliary (.aux.xml) file. Whitebox rasters store these tables within their headers.
+    fn write_attribute_tables(&self) -> Result<(), Error> {
+        if self.raster_type == RasterType::Whitebox
+            || (self.configs.colour_table.is_empty() && self.configs.attribute_table.is_empty())
+        {
+            return Ok(());
+        }
+        let file_name = get_container_file_name(&self.file_name);
+        write_aux_file(
+            file_name,
+            &self.configs.colour_table,
+            &self.configs.attribute_table,
+        )
+    }
+
+    fn write_file(&mut self) -> Result<(), Error> {
+        if let Some(cache) = &mut self.cache {
+            if cache.file_name() == self.file_name && cache.is_writable(&self.configs) {
+                // Only the modified blocks need to be written; the headers are rewritten in full.
+                cache.write_back()?;
+                match self.raster_type {
+                    RasterType::Whitebox => write_whitebox_header(self)?,
+                    RasterType::EsriBil => write_esri_bil_header(self)?,
+                    RasterType::Envi => write_envi_header(self)?,
+                    _ => {}
+                }
+                return Ok(());
             }
+            // The raster may be overwriting its own source file.
+            cache.detach()?;
+        }
+        if self.configs.bands > 1 && !self.raster_type.supports_multiple_bands() {
+            println!(
+                "Warning: The {:?} raster format only supports single-band rasters. Only the first of {} bands will be written.",
+                self.raster_type, self.configs.bands
+            );
+        }
+        match self.raster_type {
+            RasterType::ArcAscii => write_arcascii(self),
+            RasterType::ArcBinary => write_arcbinary(self),
+            RasterType::Envi => write_envi(self),
+            RasterType::EsriBil => write_esri_bil(self),
+            RasterType::GeoPackage => write_geopackage(self),
+            RasterType::GeoTiff => write_geotiff(self),
+            RasterType::GrassAscii => write_grass_raster(self),
+            RasterType::IdrisiBinary => write_idrisi(self),
+            RasterType::NetCdf => write_netcdf(self),
+            RasterType::SagaBinary => write_saga(self),
+            RasterType::SrtmHgt => write_srtm(self),
+            RasterType::Surfer7Binary => write_surfer7(self),
+            RasterType::SurferAscii => write_surfer_ascii_raster(self),
+            RasterType::Whitebox => write_whitebox(self),
+            RasterType::XyzAscii => write_xyz(self),
+            RasterType::Unknown => Err(Error::new(ErrorKind::Other, "Unrecognized raster type")),
         }
-        Ok(())
     }
 
     pub fn add_metadata_entry(&mut self, value: String) {
@@ -1226,6 +1721,15 @@ impl Raster {
         )
     }
 
+    /// Returns the coordinate reference system of the raster, read from its WKT or, if it
+    /// has none, its EPSG code. None is returned if the system is not specified.
+    pub fn get_coordinate_reference_system(&self) -> Option<CoordinateReferenceSystem> {
+        CoordinateReferenceSystem::from_wkt_or_epsg(
+            &self.configs.coordinate_ref_system_wkt,
+            self.configs.epsg_code,
+        )
+    }
+
     pub fn is_in_geographic_coordinates(&self) -> bool {
         if self.configs.west < -180f64
             || self.configs.east > 180f64
@@ -1252,13 +1756,69 @@ impl Raster {
     }
 }
 
+impl Clone for Raster {
+    /// The copy of a raster whose values are read as they are accessed holds them in a
+    /// scratch file of its own, or in memory if the scratch file can't be created.
+    ///
+    /// Panics if the raster's values are read as they are accessed and the file can't
+    /// be read.
+    fn clone(&self) -> Raster {
+        let (data, cache) = match &self.cache {
+            Some(cache) => {
+                if let Err(e) = cache.load_all() {
+                    panic!("{}", RasterError::read(cache.file_name(), e));
+                }
+                // Once every block has been read, copying can only fail to create the scratch file.
+                match cache.try_clone() {
+                    Ok(copy) => (RasterData::default(), Some(copy)),
+                        Err(e) => {
+                        println!(
+                            "Warning: Unable to create a scratch file for a copy of {} ({}). The copy will be held in memory.",
+                            self.file_name, e
+                        );
+                        (read_cache_into_memory(cache), None)
+                    }
+                }
+            }
+            None => (self.data.clone(), None),
+        };
+        Raster {
+            file_name: self.file_name.clone(),
+            file_mode: self.file_mode.clone(),
+            raster_type: self.raster_type.clone(),
+            configs: self.configs.clone(),
+            data,
+            cache,
+        }
+    }
+}
+
+/// Reads a cell of a block cache, panicking if it can't be read from the raster file.
+fn read_cached_cell(cache: &BlockCache, idx: usize) -> f64 {
+    match cache.get(idx) {
+        Ok(value) => value,
+        Err(e) => panic!("{}", RasterError::read(cache.file_name(), e)),
+    }
+}
+
+/// Copies the values of a block cache into memory.
+fn read_cache_into_memory(cache: &BlockCache) -> RasterData {
+    let values = (0..cache.len()).map(|idx| read_cached_cell(cache, idx)).collect();
+    RasterData::from_values(DataType::F64, values)
+}
+
 #[derive(Debug, Clone)]
 pub struct RasterConfigs {
     pub title: String,
     pub rows: usize,
     pub columns: usize,
-    pub bands: u8,
+    pub bands: usize,
     pub nodata: f64,
+    pub band_nodata: Vec<f64>,
+    pub band_names: Vec<String>,
+    pub wavelengths: Vec<f64>,
+    pub wavelength_units: String,
+    pub interleave: BandInterleave,
     pub north: f64,
     pub south: f64,
     pub east: f64,
@@ -1288,6 +1848,10 @@ pub struct RasterConfigs {
     pub geo_double_params: Vec<f64>,
     pub geo_ascii_params: String,
     pub metadata: Vec<String>,
+    /// The colours of the classes of a categorical raster.
+    pub colour_table: ColourTable,
+    /// The names, colours, and sizes of the classes of a categorical raster.
+    pub attribute_table: AttributeTable,
 }
 
 impl Default for RasterConfigs {
@@ -1298,6 +1862,11 @@ impl Default for RasterConfigs {
             rows: 0,
             columns: 0,
             nodata: -32768.0,
+            band_nodata: vec![],
+            band_names: vec![],
+            wavelengths: vec![],
+            wavelength_units: String::new(),
+            interleave: BandInterleave::BSQ,
             north: f64::NEG_INFINITY,
             south: f64::INFINITY,
             east: f64::NEG_INFINITY,
@@ -1327,6 +1896,8 @@ impl Default for RasterConfigs {
             geo_double_params: vec![],
             geo_ascii_params: String::new(),
             metadata: vec![],
+            colour_table: ColourTable::new(),
+            attribute_table: AttributeTable::new(),
         }
     }
 }
@@ -1336,14 +1907,19 @@ pub enum RasterType {
     Unknown,
     ArcAscii,
     ArcBinary,
+    Envi,
     EsriBil,
+    GeoPackage,
     GeoTiff,
     GrassAscii,
     IdrisiBinary,
+    NetCdf,
     SagaBinary,
+    SrtmHgt,
     Surfer7Binary,
     SurferAscii,
     Whitebox,
+    XyzAscii,
 }
 
 impl Default for RasterType {
@@ -1352,85 +1928,120 @@ impl Default for RasterType {
     }
 }
 
-fn get_raster_type_from_file(file_name: String, file_mode: String) -> RasterType {
+impl RasterType {
+    /// Returns true if the format is capable of storing more than one band.
+    pub fn supports_multiple_bands(&self) -> bool {
+        match *self {
+            RasterType::Envi
+            | RasterType::EsriBil
+            | RasterType::GeoTiff
+            | RasterType::NetCdf
+            | RasterType::Whitebox => true,
+            _ => false,
+        }
+    }
+}
+
+/// Determines the format of a raster file. Existing files that are opened for reading are
+/// identified by their contents where possible, and otherwise, as are new files, by their
+/// extensions.
+fn get_raster_type_from_file(file_name: &str, file_mode: &str) -> Result<RasterType, RasterError> {
+    // A variable of a NetCDF file, or a table of a GeoPackage, may be named after the file,
+    // e.g. file.nc:elevation.
+    let file_name = get_container_file_name(file_name);
+    if file_mode.contains("r") {
+        if let Some(raster_type) =
+            detect_raster_type(file_name).map_err(|e| RasterError::read(file_name, e))?
+        {
+            return Ok(raster_type);
+        }
+    }
+
     // get the file extension
-    let extension: String = match Path::new(&file_name).extension().unwrap().to_str() {
+    let extension: String = match Path::new(file_name).extension().and_then(|e| e.to_str()) {
         Some(n) => n.to_string().to_lowercase(),
         None => "".to_string(),
     };
     if extension.is_empty() {
-        panic!(
-            "The file type could not be determined for the file:\n{}\n due to missing extension.",
-            file_name
-        );
+        return Err(RasterError::UnknownFormat {
+            file_name: file_name.to_string(),
+            reason: "the file name has no extension and its contents were not recognized"
+                .to_string(),
+        });
     }
     if extension == "tas" || extension == "dep" {
-        return RasterType::Whitebox;
+        return Ok(RasterType::Whitebox);
     } else if extension == "tif"
         || extension == "tiff"
         || extension == "gtif"
         || extension == "gtiff"
     {
-        return RasterType::GeoTiff;
-    } else if extension == "bil" {
-        return RasterType::EsriBil;
+        return Ok(RasterType::GeoTiff);
+    } else if extension == "bil" || extension == "bip" || extension == "bsq" {
+        return Ok(RasterType::EsriBil);
     } else if extension == "flt" {
-        return RasterType::ArcBinary;
+        return Ok(RasterType::ArcBinary);
+    } else if extension == "img" {
+        return Ok(RasterType::Envi);
+    } else if extension == "hgt" {
+        return Ok(RasterType::SrtmHgt);
+    } else if extension == "xyz" {
+        return Ok(RasterType::XyzAscii);
+    } else if extension == "nc" || extension == "cdf" {
+        return Ok(RasterType::NetCdf);
+    } else if extension == "gpkg" {
+        return Ok(RasterType::GeoPackage);
     } else if extension == "rdc" || extension == "rst" {
-        return RasterType::IdrisiBinary;
+        return Ok(RasterType::IdrisiBinary);
     } else if extension == "sdat" || extension == "sgrd" {
-        return RasterType::SagaBinary;
+        return Ok(RasterType::SagaBinary);
     } else if extension == "grd" {
-        if file_mode == "r" {
-            // It could be a SurferAscii or a Surfer7Binary.
-            let mut f = File::open(file_name).unwrap();
-            let mut buffer = [0; 4];
-            f.read_exact(&mut buffer).unwrap();
-            //let small_chunk = String::from_utf8_lossy(&buffer[0..8]).to_string();
-            //if small_chunk.contains("DSAA") {
-            if buffer[0] == 68 && buffer[1] == 83 && buffer[2] == 65 && buffer[3] == 65 {
-                // DSAA signature
-                return RasterType::SurferAscii;
-            } else {
-                return RasterType::Surfer7Binary;
-            }
-        }
-        return RasterType::Surfer7Binary;
-    } else if extension == "asc" || extension == "txt" || extension == "" {
-        // what mode is this raster in?
-        if file_mode == "r" {
-            // It could be an ArcAscii or a GrassAscii.
-            let f = File::open(file_name).unwrap();
-            let file = BufReader::new(&f);
-            let mut line_count = 0;
-            for line in file.lines() {
-                let l = line.unwrap();
-                if l.contains("north")
-                    || l.contains("south")
-                    || l.contains("east")
-                    || l.contains("west")
-                {
-                    return RasterType::GrassAscii;
-                }
-                if l.contains("xllcorner")
-                    || l.contains("yllcorner")
-                    || l.contains("xllcenter")
-                    || l.contains("yllcenter")
-                {
-                    return RasterType::ArcAscii;
-                }
-                if line_count > 7 {
-                    break;
-                }
-                line_count += 1;
-            }
-        }
+        // Surfer ASCII grids, which share the extension, carry a signature that is
+        // recognized when they are read.
+        return Ok(RasterType::Surfer7Binary);
+    } else if extension == "asc" || extension == "txt" {
         // For a file_mode "w", there is not way of knowing if it is an Arc or GRASS ASCII raster.
         // Default to ArcAscii.
-        return RasterType::ArcAscii;
+        return Ok(RasterType::ArcAscii);
     }
 
-    RasterType::Unknown
+    Err(RasterError::UnknownFormat {
+        file_name: file_name.to_string(),
+        reason: format!("the extension '.{}' is not a supported raster format", extension),
+    })
+}
+
+/// Returns the name of the file that contains a raster, without the name of the NetCDF
+/// variable or GeoPackage table that may be appended to it.
+fn get_container_file_name(file_name: &str) -> &str {
+    let (file_name, _) = split_netcdf_file_name(file_name);
+    let (file_name, _) = split_geopackage_file_name(file_name);
+    file_name
+}
+
+/// Returns the name of the data file of a raster that is stored as a header file and a
+/// separate data file. If `file_name` is that of the header file, the data file has the
+/// same name with `data_extension`; otherwise `file_name` is the data file itself, which
+/// allows data files with non-standard extensions (e.g. .bin or .img) to be read.
+pub(crate) fn get_data_file_name(
+    file_name: &str,
+    header_extension: &str,
+    data_extension: &str,
+) -> String {
+    let path = Path::new(file_name);
+    let is_header = path
+        .extension()
+        .and_then(|e| e.to_str())
+        .map(|e| e.eq_ignore_ascii_case(header_extension))
+        .unwrap_or(false);
+    if is_header {
+        path.with_extension(data_extension)
+            .into_os_string()
+            .into_string()
+            .unwrap()
+    } else {
+        file_name.to_string()
+    }
 }
 
 #[derive(Debug, Copy, Clone, PartialEq)]
@@ -1535,3 +2146,31 @@ impl Default for PhotometricInterpretation {
         PhotometricInterpretation::Unknown
     }
 }
+
+/// The order in which the cells of a multi-band raster are arranged on disk.
+/// In memory, a `Raster` always stores its bands sequentially.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum BandInterleave {
+    /// Band sequential; each band is stored in its entirety before the next.
+    BSQ,
+    /// Band interleaved by line; each row of every band is stored before the next row.
+    BIL,
+    /// Band interleaved by pixel; all band values of a cell are stored together.
+    BIP,
+}
+
+impl Default for BandInterleave {
+    fn default() -> BandInterleave {
+        BandInterleave::BSQ
+    }
+}
+
+/// Summary statistics for a single band of a `Raster`, excluding NoData cells.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct BandStatistics {
+    pub minimum: f64,
+    pub maximum: f64,
+    pub mean: f64,
+    pub std_dev: f64,
+    pub num_valid_cells: usize,
+}
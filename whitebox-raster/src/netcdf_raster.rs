@@ -0,0 +1,1153 @@
+use super::*;
+use byteorder::{BigEndian, ReadBytesExt, WriteBytesExt};
+use whitebox_common::spatial_ref_system::esri_wkt_from_epsg;
+use std::f64;
+use std::fs::File;
+use std::io::prelude::*;
+use std::io::{BufReader, BufWriter, Error, ErrorKind, SeekFrom};
+
+/*
+NetCDF classic (CDF-1) and 64-bit offset (CDF-2) files are described at
+https://docs.unidata.ucar.edu/netcdf-c/current/file_format_specifications.html.
+All values are big-endian, and the header is followed by the values of the
+fixed-size variables and then by the records of the record variables, which
+share the unlimited dimension.
+*/
+
+const NC_DIMENSION: u32 = 10;
+const NC_VARIABLE: u32 = 11;
+const NC_ATTRIBUTE: u32 = 12;
+const STREAMING: u32 = 0xFFFF_FFFF;
+
+const NC_BYTE: u32 = 1;
+const NC_CHAR: u32 = 2;
+const NC_SHORT: u32 = 3;
+const NC_INT: u32 = 4;
+const NC_FLOAT: u32 = 5;
+const NC_DOUBLE: u32 = 6;
+
+/// The name of the variable that is written when none is given in the file name.
+const DEFAULT_VARIABLE: &str = "data";
+
+struct NcDimension {
+    name: String,
+    length: usize,
+}
+
+enum NcValues {
+    Text(String),
+    Numbers(Vec<f64>),
+}
+
+struct NcAttribute {
+    name: String,
+    nc_type: u32,
+    values: NcValues,
+}
+
+struct NcVariable {
+    name: String,
+    dim_ids: Vec<usize>,
+    attributes: Vec<NcAttribute>,
+    nc_type: u32,
+    begin: u64,
+}
+
+struct NcHeader {
+    num_records: usize,
+    dimensions: Vec<NcDimension>,
+    attributes: Vec<NcAttribute>,
+    variables: Vec<NcVariable>,
+}
+
+impl NcVariable {
+    fn attribute(&self, name: &str) -> Option<&NcAttribute> {
+        self.attributes.iter().find(|a| a.name == name)
+    }
+
+    fn text_attribute(&self, name: &str) -> Option<&str> {
+        match self.attribute(name).map(|a| &a.values) {
+            Some(NcValues::Text(s)) => Some(s.as_str()),
+            _ => None,
+        }
+    }
+
+    fn number_attribute(&self, name: &str) -> Option<f64> {
+        match self.attribute(name).map(|a| &a.values) {
+            Some(NcValues::Numbers(v)) if !v.is_empty() => Some(v[0]),
+            _ => None,
+        }
+    }
+}
+
+impl NcHeader {
+    fn is_record_variable(&self, var: &NcVariable) -> bool {
+        var.dim_ids
+            .first()
+            .map(|&d| self.dimensions[d].length == 0)
+            .unwrap_or(false)
+    }
+
+    fn dimension_length(&self, dim_id: usize) -> usize {
+        match self.dimensions[dim_id].length {
+            0 => self.num_records,
+            length => length,
+        }
+    }
+
+    /// Returns true if the variable is a coordinate variable, i.e. a one-dimensional
+    /// variable with the same name as its dimension.
+    fn is_coordinate_variable(&self, var: &NcVariable) -> bool {
+        var.dim_ids.len() == 1 && self.dimensions[var.dim_ids[0]].name == var.name
+    }
+
+    /// Returns the coordinate variable of a dimension, if there is one.
+    fn coordinate_variable(&self, dim_id: usize) -> Option<&NcVariable> {
+        self.variables
+            .iter()
+            .find(|v| v.dim_ids == [dim_id] && v.name == self.dimensions[dim_id].name)
+    }
+
+    /// Returns the number of bytes in one record of a record variable. The values of the
+    /// record variables are interleaved, one record of each variable at a time.
+    fn record_size(&self) -> usize {
+        let record_vars: Vec<&NcVariable> = self
+            .variables
+            .iter()
+            .filter(|v| self.is_record_variable(v))
+            .collect();
+        let slice_size = |v: &NcVariable| {
+            v.dim_ids[1..]
+                .iter()
+                .map(|&d| self.dimension_length(d))
+                .product::<usize>()
+                * type_size(v.nc_type)
+        };
+        if record_vars.len() == 1 {
+            // The record of a lone record variable isn't padded.
+            slice_size(record_vars[0])
+        } else {
+            record_vars.iter().map(|v| padded(slice_size(v))).sum()
+        }
+    }
+}
+
+fn type_size(nc_type: u32) -> usize {
+    match nc_type {
+        NC_BYTE | NC_CHAR => 1,
+        NC_SHORT => 2,
+        NC_INT | NC_FLOAT => 4,
+        _ => 8,
+    }
+}
+
+fn padded(num_bytes: usize) -> usize {
+    (num_bytes + 3) / 4 * 4
+}
+
+/// Splits a NetCDF file name of the form `file.nc:variable` into the name of the file
+/// and that of the variable. Other file names are returned unchanged.
+pub(crate) fn split_netcdf_file_name(file_name: &str) -> (&str, Option<&str>) {
+    if let Some(i) = file_name.rfind(':') {
+        let (base, variable) = (&file_name[..i], &file_name[i + 1..]);
+        let lower = base.to_lowercase();
+        if !variable.is_empty()
+            && !variable.contains(|c| c == '/' || c == '\\')
+            && (lower.ends_with(".nc") || lower.ends_with(".cdf"))
+        {
+            return (base, Some(variable));
+        }
+    }
+    (file_name, None)
+}
+
+fn invalid_data<S: Into<String>>(message: S) -> Error {
+    Error::new(ErrorKind::InvalidData, message.into())
+}
+
+fn read_name<R: Read>(reader: &mut R) -> Result<String, Error> {
+    let length = reader.read_u32::<BigEndian>()? as usize;
+    let mut bytes = vec![0u8; padded(length)];
+    reader.read_exact(&mut bytes)?;
+    bytes.truncate(length);
+    Ok(String::from_utf8_lossy(&bytes).to_string())
+}
+
+fn read_values<R: Read>(reader: &mut R, nc_type: u32, count: usize) -> Result<NcValues, Error> {
+    let mut bytes = vec![0u8; padded(count * type_size(nc_type))];
+    reader.read_exact(&mut bytes)?;
+    if nc_type == NC_CHAR {
+        bytes.truncate(count);
+        let text = String::from_utf8_lossy(&bytes);
+        return Ok(NcValues::Text(text.trim_end_matches('\0').to_string()));
+    }
+    let size = type_size(nc_type);
+    Ok(NcValues::Numbers(
+        (0..count)
+            .map(|i| decode_nc_value(&bytes[i * size..(i + 1) * size], nc_type))
+            .collect(),
+    ))
+}
+
+fn decode_nc_value(bytes: &[u8], nc_type: u32) -> f64 {
+    let mut b = bytes;
+    match nc_type {
+        NC_BYTE => bytes[0] as i8 as f64,
+        NC_SHORT => b.read_i16::<BigEndian>().unwrap() as f64,
+        NC_INT => b.read_i32::<BigEndian>().unwrap() as f64,
+        NC_FLOAT => b.read_f32::<BigEndian>().unwrap() as f64,
+        _ => b.read_f64::<BigEndian>().unwrap(),
+    }
+}
+
+fn read_attributes<R: Read>(reader: &mut R) -> Result<Vec<NcAttribute>, Error> {
+    let tag = reader.read_u32::<BigEndian>()?;
+    let count = reader.read_u32::<BigEndian>()? as usize;
+    if tag != NC_ATTRIBUTE && !(tag == 0 && count == 0) {
+        return Err(invalid_data("The NetCDF attribute list is not formatted correctly."));
+    }
+    let mut attributes = Vec::with_capacity(count);
+    for _ in 0..count {
+        let name = read_name(reader)?;
+        let nc_type = reader.read_u32::<BigEndian>()?;
+        if nc_type < NC_BYTE || nc_type > NC_DOUBLE {
+            return Err(invalid_data(format!(
+                "The NetCDF attribute '{}' is of an unsupported type ({}).",
+                name, nc_type
+            )));
+        }
+        let num_values = reader.read_u32::<BigEndian>()? as usize;
+        let values = read_values(reader, nc_type, num_values)?;
+        attributes.push(NcAttribute {
+            name,
+            nc_type,
+            values,
+        });
+    }
+    Ok(attributes)
+}
+
+fn read_header<R: Read>(reader: &mut R) -> Result<NcHeader, Error> {
+    let mut magic = [0u8; 4];
+    reader.read_exact(&mut magic)?;
+    if &magic[0..3] != b"CDF" {
+        if &magic == b"\x89HDF" {
+            return Err(invalid_data(
+                "NetCDF-4 (HDF5) files are not supported; only NetCDF classic and 64-bit offset files can be read.",
+            ));
+        }
+        return Err(invalid_data("The file is not a NetCDF file."));
+    }
+    let version = magic[3];
+    if version != 1 && version != 2 {
+        return Err(invalid_data(format!(
+            "NetCDF format version {} is not supported; only NetCDF classic and 64-bit offset files can be read.",
+            version
+        )));
+    }
+
+    let num_records = reader.read_u32::<BigEndian>()?;
+
+    let tag = reader.read_u32::<BigEndian>()?;
+    let count = reader.read_u32::<BigEndian>()? as usize;
+    if tag != NC_DIMENSION && !(tag == 0 && count == 0) {
+        return Err(invalid_data("The NetCDF dimension list is not formatted correctly."));
+    }
+    let mut dimensions = Vec::with_capacity(count);
+    for _ in 0..count {
+        let name = read_name(reader)?;
+        let length = reader.read_u32::<BigEndian>()? as usize;
+        dimensions.push(NcDimension { name, length });
+    }
+
+    let attributes = read_attributes(reader)?;
+
+    let tag = reader.read_u32::<BigEndian>()?;
+    let count = reader.read_u32::<BigEndian>()? as usize;
+    if tag != NC_VARIABLE && !(tag == 0 && count == 0) {
+        return Err(invalid_data("The NetCDF variable list is not formatted correctly."));
+    }
+    let mut variables = Vec::with_capacity(count);
+    for _ in 0..count {
+        let name = read_name(reader)?;
+        let num_dims = reader.read_u32::<BigEndian>()? as usize;
+        let mut dim_ids = Vec::with_capacity(num_dims);
+        for _ in 0..num_dims {
+            let dim_id = reader.read_u32::<BigEndian>()? as usize;
+            if dim_id >= dimensions.len() {
+                return Err(invalid_data(format!(
+                    "The NetCDF variable '{}' refers to an undefined dimension.",
+                    name
+                )));
+            }
+            dim_ids.push(dim_id);
+        }
+        let var_attributes = read_attributes(reader)?;
+        let nc_type = reader.read_u32::<BigEndian>()?;
+        let _vsize = reader.read_u32::<BigEndian>()?;
+        let begin = if version == 1 {
+            reader.read_u32::<BigEndian>()? as u64
+        } else {
+            reader.read_u64::<BigEndian>()?
+        };
+        variables.push(NcVariable {
+            name,
+            dim_ids,
+            attributes: var_attributes,
+            nc_type,
+            begin,
+        });
+    }
+
+    Ok(NcHeader {
+        num_records: if num_records == STREAMING {
+            usize::MAX
+        } else {
+            num_records as usize
+        },
+        dimensions,
+        attributes,
+        variables,
+    })
+}
+
+/// Reads the values of a variable, a band (i.e. one of the grids spanning its last two
+/// dimensions) at a time.
+struct NcVariableReader<'a> {
+    header: &'a NcHeader,
+    var: &'a NcVariable,
+    band_values: usize,
+    bands_per_record: usize,
+    record_size: usize,
+}
+
+impl<'a> NcVariableReader<'a> {
+    fn read_band<R: Read + Seek>(
+        &self,
+        reader: &mut R,
+        band: usize,
+        buffer: &mut Vec<u8>,
+    ) -> Result<Vec<f64>, Error> {
+        let size = type_size(self.var.nc_type);
+        let band_bytes = self.band_values * size;
+        let position = if self.header.is_record_variable(self.var) {
+            let (record, band) = (band / self.bands_per_record, band % self.bands_per_record);
+            self.var.begin + (record * self.record_size + band * band_bytes) as u64
+        } else {
+            self.var.begin + (band * band_bytes) as u64
+        };
+        buffer.resize(band_bytes, 0u8);
+        reader.seek(SeekFrom::Start(position))?;
+        reader.read_exact(buffer)?;
+        Ok((0..self.band_values)
+            .map(|i| decode_nc_value(&buffer[i * size..(i + 1) * size], self.var.nc_type))
+            .collect())
+    }
+}
+
+/// Reads the values of a one-dimensional (coordinate) variable.
+fn read_coordinates<R: Read + Seek>(
+    reader: &mut R,
+    header: &NcHeader,
+    var: &NcVariable,
+) -> Result<Vec<f64>, Error> {
+    let var_reader = NcVariableReader {
+        header: header,
+        var: var,
+        band_values: header.dimension_length(var.dim_ids[0]),
+        bands_per_record: 1,
+        record_size: 0,
+    };
+    if header.is_record_variable(var) {
+        // Record coordinates (e.g. time) are stored one value per record.
+        let var_reader = NcVariableReader {
+            band_values: 1,
+            record_size: header.record_size(),
+            ..var_reader
+        };
+        let mut buffer = vec![];
+        let mut values = vec![];
+        for record in 0..header.num_records {
+            values.extend(var_reader.read_band(reader, record, &mut buffer)?);
+        }
+        return Ok(values);
+    }
+    var_reader.read_band(reader, 0, &mut vec![])
+}
+
+/// Reads a variable of a NetCDF classic or 64-bit offset file. The variable is given by
+/// the file name, e.g. `file.nc:elevation`, or is otherwise the first variable with two
+/// or more dimensions that isn't a coordinate variable. The last two dimensions of the
+/// variable are the rows and columns of the grid; any others, e.g. time, are bands.
+pub fn read_netcdf(
+    file_name: &String,
+    configs: &mut RasterConfigs,
+    data: &mut Vec<f64>,
+) -> Result<(), Error> {
+    let (nc_file, var_name) = split_netcdf_file_name(file_name);
+    let f = File::open(nc_file)?;
+    let file_size = f.metadata()?.len() as usize;
+    let mut reader = BufReader::new(f);
+    let mut header = read_header(&mut reader)?;
+
+    if header.num_records == usize::MAX {
+        // The number of records of a file that is still being written is implied by its size.
+        let record_size = header.record_size();
+        header.num_records = match header
+            .variables
+            .iter()
+            .filter(|v| header.is_record_variable(v))
+            .map(|v| v.begin as usize)
+            .min()
+        {
+            Some(begin) if record_size > 0 => file_size.saturating_sub(begin) / record_size,
+            _ => 0,
+        };
+    }
+
+    let var = match var_name {
+        Some(name) => header.variables.iter().find(|v| v.name == name).ok_or_else(|| {
+            invalid_data(format!(
+                "The NetCDF file does not contain the variable '{}'. It contains: {}.",
+                name,
+                header
+                    .variables
+                    .iter()
+                    .map(|v| v.name.as_str())
+                    .collect::<Vec<&str>>()
+                    .join(", ")
+            ))
+        })?,
+        None => header
+            .variables
+            .iter()
+            .find(|v| {
+                v.dim_ids.len() >= 2 && v.nc_type != NC_CHAR && !header.is_coordinate_variable(v)
+            })
+            .ok_or_else(|| invalid_data("The NetCDF file does not contain a gridded variable."))?,
+    };
+    if var.dim_ids.len() < 2 {
+        return Err(invalid_data(format!(
+            "The NetCDF variable '{}' is not a grid; it has fewer than two dimensions.",
+            var.name
+        )));
+    }
+    if var.nc_type < NC_BYTE || var.nc_type > NC_DOUBLE || var.nc_type == NC_CHAR {
+        return Err(invalid_data(format!(
+            "The NetCDF variable '{}' is not numeric.",
+            var.name
+        )));
+    }
+
+    let n = var.dim_ids.len();
+    let (y_dim, x_dim) = (var.dim_ids[n - 2], var.dim_ids[n - 1]);
+    let rows = header.dimension_length(y_dim);
+    let columns = header.dimension_length(x_dim);
+    let bands = var.dim_ids[..n - 2]
+        .iter()
+        .map(|&d| header.dimension_length(d))
+        .product::<usize>();
+    configs.rows = rows;
+    configs.columns = columns;
+    configs.bands = bands.max(1);
+
+    // Locate the grid using the coordinate variables of the last two dimensions, which
+    // give the centres of the cells. The rows are ordered from north to south.
+    let mut flip_rows = false;
+    let mut flip_columns = false;
+    let mut geographic = false;
+    match header.coordinate_variable(x_dim) {
+        Some(x_var) => {
+            let xs = read_coordinates(&mut reader, &header, x_var)?;
+            configs.resolution_x = if columns > 1 {
+                (xs[columns - 1] - xs[0]) / (columns - 1) as f64
+            } else {
+                1f64
+            };
+            if configs.resolution_x < 0f64 {
+                flip_columns = true;
+                configs.resolution_x = -configs.resolution_x;
+            }
+            configs.west = xs[0].min(xs[columns - 1]) - configs.resolution_x / 2f64;
+            if let Some(units) = x_var.text_attribute("units") {
+                if units.to_lowercase().starts_with("degree") {
+                    geographic = true;
+                } else {
+                    configs.xy_units = units.to_string();
+                }
+            }
+        }
+        None => {
+            configs.resolution_x = 1f64;
+            configs.west = 0f64;
+        }
+    }
+    match header.coordinate_variable(y_dim) {
+        Some(y_var) => {
+            let ys = read_coordinates(&mut reader, &header, y_var)?;
+            configs.resolution_y = if rows > 1 {
+                (ys[0] - ys[rows - 1]) / (rows - 1) as f64
+            } else {
+                configs.resolution_x
+            };
+            if configs.resolution_y < 0f64 {
+                // The first row is the southernmost, as is usual.
+                flip_rows = true;
+                configs.resolution_y = -configs.resolution_y;
+            }
+            configs.north = ys[0].max(ys[rows - 1]) + configs.resolution_y / 2f64;
+            geographic |= y_var
+                .text_attribute("units")
+                .map(|u| u.to_lowercase().starts_with("degree"))
+                .unwrap_or(false)
+                || y_var.text_attribute("standard_name") == Some("latitude");
+        }
+        None => {
+            configs.resolution_y = configs.resolution_x;
+            configs.north = rows as f64 * configs.resolution_y;
+        }
+    }
+    configs.east = configs.west + configs.resolution_x * columns as f64;
+    configs.south = configs.north - configs.resolution_y * rows as f64;
+
+    // The bands are named after the coordinates of the leading dimension, e.g. time=0.
+    if n == 3 {
+        if let Some(band_var) = header.coordinate_variable(var.dim_ids[0]) {
+            configs.band_names = read_coordinates(&mut reader, &header, band_var)?
+                .iter()
+                .map(|value| format!("{}={}", band_var.name, value))
+                .collect();
+        }
+    }
+
+    // The coordinate reference system is given by the variable's grid mapping.
+    let wkt = var
+        .text_attribute("grid_mapping")
+        .and_then(|name| header.variables.iter().find(|v| v.name == name))
+        .and_then(|crs| {
+            crs.text_attribute("crs_wkt")
+                .or_else(|| crs.text_attribute("spatial_ref"))
+        });
+    if let Some(wkt) = wkt {
+        configs.projection = wkt.to_string();
+        configs.coordinate_ref_system_wkt = wkt.to_string();
+        if geographic && wkt.trim_start().starts_with("GEOGCS[") && wkt.contains("WGS_1984") {
+            configs.epsg_code = 4326;
+        }
+    } else if geographic {
+        // Latitude and longitude grids without a grid mapping are assumed to be WGS84.
+        configs.epsg_code = 4326;
+        configs.coordinate_ref_system_wkt = esri_wkt_from_epsg(4326);
+        configs.projection = configs.coordinate_ref_system_wkt.clone();
+    }
+    if geographic {
+        configs.xy_units = "degrees".to_string();
+    }
+    if let Some(units) = var.text_attribute("units") {
+        configs.z_units = units.to_string();
+    }
+    if let Some(title) = var
+        .text_attribute("long_name")
+        .or_else(|| match header.attributes.iter().find(|a| a.name == "title") {
+            Some(NcAttribute {
+                values: NcValues::Text(s),
+                ..
+            }) => Some(s.as_str()),
+            _ => None,
+        })
+    {
+        configs.title = title.to_string();
+    }
+
+    // Values equal to the fill value are missing. Packed values are unpacked using the
+    // scale factor and offset, following the CF conventions.
+    let fill_value = var
+        .number_attribute("_FillValue")
+        .or_else(|| var.number_attribute("missing_value"))
+        .or_else(|| match var.nc_type {
+            NC_SHORT => Some(-32767f64),
+            NC_INT => Some(-2147483647f64),
+            NC_FLOAT => Some(9.969_209_968_386_869e36f32 as f64),
+            NC_DOUBLE => Some(9.969_209_968_386_869e36f64),
+            _ => None,
+        });
+    let scale_factor = var.number_attribute("scale_factor");
+    let add_offset = var.number_attribute("add_offset");
+    let packed = scale_factor.is_some() || add_offset.is_some();
+    let scale_factor = scale_factor.unwrap_or(1f64);
+    let add_offset = add_offset.unwrap_or(0f64);
+    configs.nodata = match fill_value {
+        Some(fill) if !fill.is_nan() => fill,
+        _ => -32768f64,
+    };
+    configs.data_type = if packed {
+        match var.attribute("scale_factor").or(var.attribute("add_offset")) {
+            Some(a) if a.nc_type == NC_FLOAT => DataType::F32,
+            _ => DataType::F64,
+        }
+    } else {
+        match var.nc_type {
+            NC_BYTE => DataType::I8,
+            NC_SHORT => DataType::I16,
+            NC_INT => DataType::I32,
+            NC_FLOAT => DataType::F32,
+            _ => DataType::F64,
+        }
+    };
+    configs.photometric_interp = PhotometricInterpretation::Continuous;
+
+    let var_reader = NcVariableReader {
+        header: &header,
+        var: var,
+        band_values: rows * columns,
+        bands_per_record: if header.is_record_variable(var) {
+            var.dim_ids[1..n - 2]
+                .iter()
+                .map(|&d| header.dimension_length(d))
+                .product::<usize>()
+        } else {
+            1
+        },
+        record_size: header.record_size(),
+    };
+    let nodata = configs.nodata;
+    data.clear();
+    data.reserve(configs.bands * rows * columns);
+    let mut buffer = vec![];
+    for band in 0..configs.bands {
+        let values = var_reader.read_band(&mut reader, band, &mut buffer)?;
+        for row in 0..rows {
+            let file_row = if flip_rows { rows - 1 - row } else { row };
+            for col in 0..columns {
+                let file_col = if flip_columns { columns - 1 - col } else { col };
+                let value = values[file_row * columns + file_col];
+                data.push(if value.is_nan() || Some(value) == fill_value {
+                    nodata
+                } else if packed {
+                    value * scale_factor + add_offset
+                } else {
+                    value
+                });
+            }
+        }
+    }
+
+    Ok(())
+}
+
+fn write_name<W: Write>(writer: &mut W, name: &str) -> Result<(), Error> {
+    writer.write_u32::<BigEndian>(name.len() as u32)?;
+    writer.write_all(name.as_bytes())?;
+    writer.write_all(&vec![0u8; padded(name.len()) - name.len()])
+}
+
+fn write_nc_value<W: Write>(writer: &mut W, nc_type: u32, value: f64) -> Result<(), Error> {
+    match nc_type {
+        NC_BYTE => writer.write_i8(value as i8),
+        NC_SHORT => writer.write_i16::<BigEndian>(value as i16),
+        NC_INT => writer.write_i32::<BigEndian>(value as i32),
+        NC_FLOAT => writer.write_f32::<BigEndian>(value as f32),
+        _ => writer.write_f64::<BigEndian>(value),
+    }
+}
+
+fn write_attributes<W: Write>(writer: &mut W, attributes: &[NcAttribute]) -> Result<(), Error> {
+    if attributes.is_empty() {
+        writer.write_u32::<BigEndian>(0)?;
+        return writer.write_u32::<BigEndian>(0);
+    }
+    writer.write_u32::<BigEndian>(NC_ATTRIBUTE)?;
+    writer.write_u32::<BigEndian>(attributes.len() as u32)?;
+    for a in attributes {
+        write_name(writer, &a.name)?;
+        writer.write_u32::<BigEndian>(a.nc_type)?;
+        match &a.values {
+            NcValues::Text(s) => {
+                writer.write_u32::<BigEndian>(s.len() as u32)?;
+                writer.write_all(s.as_bytes())?;
+                writer.write_all(&vec![0u8; padded(s.len()) - s.len()])?;
+            }
+            NcValues::Numbers(v) => {
+                writer.write_u32::<BigEndian>(v.len() as u32)?;
+                for &value in v {
+                    write_nc_value(writer, a.nc_type, value)?;
+                }
+                let num_bytes = v.len() * type_size(a.nc_type);
+                writer.write_all(&vec![0u8; padded(num_bytes) - num_bytes])?;
+            }
+        }
+    }
+    Ok(())
+}
+
+fn write_header<W: Write>(writer: &mut W, header: &NcHeader, version: u8) -> Result<(), Error> {
+    writer.write_all(b"CDF")?;
+    writer.write_u8(version)?;
+    writer.write_u32::<BigEndian>(header.num_records as u32)?;
+
+    writer.write_u32::<BigEndian>(NC_DIMENSION)?;
+    writer.write_u32::<BigEndian>(header.dimensions.len() as u32)?;
+    for d in &header.dimensions {
+        write_name(writer, &d.name)?;
+        writer.write_u32::<BigEndian>(d.length as u32)?;
+    }
+
+    write_attributes(writer, &header.attributes)?;
+
+    writer.write_u32::<BigEndian>(NC_VARIABLE)?;
+    writer.write_u32::<BigEndian>(header.variables.len() as u32)?;
+    for v in &header.variables {
+        write_name(writer, &v.name)?;
+        writer.write_u32::<BigEndian>(v.dim_ids.len() as u32)?;
+        for &d in &v.dim_ids {
+            writer.write_u32::<BigEndian>(d as u32)?;
+        }
+        write_attributes(writer, &v.attributes)?;
+        writer.write_u32::<BigEndian>(v.nc_type)?;
+        let vsize = variable_size(header, v);
+        writer.write_u32::<BigEndian>(vsize.min(u32::MAX as usize) as u32)?;
+        if version == 1 {
+            writer.write_u32::<BigEndian>(v.begin as u32)?;
+        } else {
+            writer.write_u64::<BigEndian>(v.begin)?;
+        }
+    }
+    Ok(())
+}
+
+/// The number of bytes, including padding, occupied by the values of a (fixed-size) variable.
+fn variable_size(header: &NcHeader, var: &NcVariable) -> usize {
+    padded(
+        var.dim_ids
+            .iter()
+            .map(|&d| header.dimension_length(d))
+            .product::<usize>()
+            * type_size(var.nc_type),
+    )
+}
+
+fn text(name: &str, value: &str) -> NcAttribute {
+    NcAttribute {
+        name: name.to_string(),
+        nc_type: NC_CHAR,
+        values: NcValues::Text(value.to_string()),
+    }
+}
+
+fn number(name: &str, nc_type: u32, value: f64) -> NcAttribute {
+    NcAttribute {
+        name: name.to_string(),
+        nc_type: nc_type,
+        values: NcValues::Numbers(vec![value]),
+    }
+}
+
+/// Writes the raster as a CF-compliant NetCDF classic file. The values are written to the
+/// variable named in the file name, e.g. `file.nc:elevation`, or otherwise to a variable
+/// named `data`. Multi-band rasters are written as three-dimensional variables.
+pub fn write_netcdf<'a>(r: &'a mut Raster) -> Result<(), Error> {
+    if r.configs.photometric_interp == PhotometricInterpretation::RGB {
+        return Err(Error::new(
+            ErrorKind::InvalidInput,
+            "NetCDF files are not suitable for storing packed RGB data. Use a GeoTiff format instead.",
+        ));
+    }
+    let (nc_file, var_name) = split_netcdf_file_name(&r.file_name);
+    let nc_file = nc_file.to_string();
+    let var_name = var_name.unwrap_or(DEFAULT_VARIABLE).to_string();
+
+    // NetCDF classic files have no unsigned types, so unsigned values are widened, as are
+    // values whose type can't represent the NoData value, which becomes the fill value.
+    let nodata = r.configs.nodata;
+    let nc_type = match r.configs.data_type {
+        DataType::I8 if nodata >= -128f64 && nodata <= 127f64 => NC_BYTE,
+        DataType::I8 | DataType::U8 | DataType::I16 if nodata >= -32768f64 && nodata <= 32767f64 => {
+            NC_SHORT
+        }
+        DataType::I8 | DataType::U8 | DataType::I16 | DataType::U16 | DataType::I32
+            if nodata >= i32::MIN as f64 && nodata <= i32::MAX as f64 =>
+        {
+            NC_INT
+        }
+        DataType::F32 => NC_FLOAT,
+        _ => NC_DOUBLE,
+    };
+    let rows = r.configs.rows;
+    let columns = r.configs.columns;
+    let bands = r.configs.bands.max(1);
+
+    let epsg = r.configs.epsg_code;
+    let geographic = epsg == 4326
+        || r.configs.xy_units.to_lowercase().starts_with("deg")
+        || r.configs.projection.trim_start().starts_with("GEOGCS[");
+    let wkt = if is_wkt(&r.configs.projection) {
+        r.configs.projection.clone()
+    } else if is_wkt(&r.configs.coordinate_ref_system_wkt) {
+        r.configs.coordinate_ref_system_wkt.clone()
+    } else if epsg != 0 {
+        esri_wkt_from_epsg(epsg)
+    } else {
+        String::new()
+    };
+    let wkt = if is_wkt(&wkt) { wkt } else { String::new() };
+
+    let (x_name, y_name) = if geographic { ("lon", "lat") } else { ("x", "y") };
+    let mut dimensions = vec![];
+    if bands > 1 {
+        dimensions.push(NcDimension {
+            name: "band".to_string(),
+            length: bands,
+        });
+    }
+    dimensions.push(NcDimension {
+        name: y_name.to_string(),
+        length: rows,
+    });
+    dimensions.push(NcDimension {
+        name: x_name.to_string(),
+        length: columns,
+    });
+    let (y_dim, x_dim) = (dimensions.len() - 2, dimensions.len() - 1);
+
+    let mut variables = vec![];
+    if bands > 1 {
+        variables.push(NcVariable {
+            name: "band".to_string(),
+            dim_ids: vec![0],
+            attributes: vec![text("long_name", "band number")],
+            nc_type: NC_INT,
+            begin: 0,
+        });
+    }
+    let (x_attributes, y_attributes) = if geographic {
+        (
+            vec![
+                text("standard_name", "longitude"),
+                text("long_name", "longitude"),
+                text("units", "degrees_east"),
+            ],
+            vec![
+                text("standard_name", "latitude"),
+                text("long_name", "latitude"),
+                text("units", "degrees_north"),
+            ],
+        )
+    } else {
+        let units = match r.configs.xy_units.to_lowercase().as_str() {
+            "" | "not specified" | "metres" | "meters" | "metre" | "meter" | "m" => "m".to_string(),
+            units => units.to_string(),
+        };
+        (
+            vec![
+                text("standard_name", "projection_x_coordinate"),
+                text("long_name", "x coordinate of projection"),
+                text("units", &units),
+            ],
+            vec![
+                text("standard_name", "projection_y_coordinate"),
+                text("long_name", "y coordinate of projection"),
+                text("units", &units),
+            ],
+        )
+    };
+    variables.push(NcVariable {
+        name: y_name.to_string(),
+        dim_ids: vec![y_dim],
+        attributes: y_attributes,
+        nc_type: NC_DOUBLE,
+        begin: 0,
+    });
+    variables.push(NcVariable {
+        name: x_name.to_string(),
+        dim_ids: vec![x_dim],
+        attributes: x_attributes,
+        nc_type: NC_DOUBLE,
+        begin: 0,
+    });
+    if !wkt.is_empty() {
+        let mut attributes = vec![];
+        if geographic {
+            attributes.push(text("grid_mapping_name", "latitude_longitude"));
+        }
+        attributes.push(text("crs_wkt", &wkt));
+        attributes.push(text("spatial_ref", &wkt));
+        variables.push(NcVariable {
+            name: "crs".to_string(),
+            dim_ids: vec![],
+            attributes: attributes,
+            nc_type: NC_INT,
+            begin: 0,
+        });
+    }
+    let mut attributes = vec![];
+    if !r.configs.title.is_empty() {
+        attributes.push(text("long_name", &r.configs.title));
+    }
+    if !r.configs.z_units.is_empty() && r.configs.z_units != "not specified" {
+        attributes.push(text("units", &r.configs.z_units));
+    }
+    attributes.push(number("_FillValue", nc_type, nodata));
+    if !wkt.is_empty() {
+        attributes.push(text("grid_mapping", "crs"));
+    }
+    variables.push(NcVariable {
+        name: var_name,
+        dim_ids: (0..dimensions.len()).collect(),
+        attributes: attributes,
+        nc_type: nc_type,
+        begin: 0,
+    });
+
+    let mut global_attributes = vec![text("Conventions", "CF-1.6")];
+    if !r.configs.title.is_empty() {
+        global_attributes.push(text("title", &r.configs.title));
+    }
+    global_attributes.push(text("source", "WhiteboxTools"));
+
+    let mut header = NcHeader {
+        num_records: 0,
+        dimensions: dimensions,
+        attributes: global_attributes,
+        variables: variables,
+    };
+
+    // The offsets of the variables follow the header. Files too large for 32-bit offsets
+    // are written in the 64-bit offset format.
+    let data_size: usize = header.variables.iter().map(|v| variable_size(&header, v)).sum();
+    let mut header_bytes = vec![];
+    write_header(&mut header_bytes, &header, 1)?;
+    let version = if header_bytes.len() + data_size > i32::MAX as usize {
+        2u8
+    } else {
+        1u8
+    };
+    if version == 2 {
+        header_bytes.clear();
+        write_header(&mut header_bytes, &header, 2)?;
+    }
+    let mut begin = header_bytes.len() as u64;
+    for i in 0..header.variables.len() {
+        header.variables[i].begin = begin;
+        begin += variable_size(&header, &header.variables[i]) as u64;
+    }
+
+    let f = File::create(&nc_file)?;
+    let mut writer = BufWriter::new(f);
+    write_header(&mut writer, &header, version)?;
+
+    // The coordinates are those of the cell centres, with the rows ordered from south
+    // to north.
+    for var in &header.variables {
+        match var.name.as_str() {
+            "band" if bands > 1 => {
+                for b in 0..bands {
+                    writer.write_i32::<BigEndian>(b as i32 + 1)?;
+                }
+            }
+            name if name == y_name => {
+                for row in (0..rows).rev() {
+                    writer.write_f64::<BigEndian>(r.get_y_from_row(row as isize))?;
+                }
+            }
+            name if name == x_name => {
+                for col in 0..columns {
+                    writer.write_f64::<BigEndian>(r.get_x_from_column(col as isize))?;
+                }
+            }
+            "crs" => {
+                writer.write_i32::<BigEndian>(0)?;
+            }
+            _ => {
+                let num_cells = rows * columns;
+                for band in 0..bands {
+                    for row in (0..rows).rev() {
+                        for col in 0..columns {
+                            let value = r.get_cell(band * num_cells + row * columns + col);
+                            write_nc_value(&mut writer, nc_type, value)?;
+                        }
+                    }
+                }
+            }
+        }
+        let num_bytes = variable_size(&header, var)
+            - var
+                .dim_ids
+                .iter()
+                .map(|&d| header.dimension_length(d))
+                .product::<usize>()
+                * type_size(var.nc_type);
+        writer.write_all(&vec![0u8; num_bytes])?;
+    }
+
+    writer.flush()?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::test_utils::*;
+
+    #[test]
+    fn netcdf_file_names() {
+        assert_eq!(
+            split_netcdf_file_name("/data/climate.nc:precip"),
+            ("/data/climate.nc", Some("precip"))
+        );
+        assert_eq!(
+            split_netcdf_file_name("C:\\data\\dem.CDF:z"),
+            ("C:\\data\\dem.CDF", Some("z"))
+        );
+        assert_eq!(split_netcdf_file_name("C:\\data\\dem.nc"), ("C:\\data\\dem.nc", None));
+        assert_eq!(split_netcdf_file_name("dem.tif:band"), ("dem.tif:band", None));
+    }
+
+    #[test]
+    fn netcdf_round_trip() {
+        let nc_file = temp_file("round_trip.nc");
+        let file_name = format!("{}:elevation", nc_file);
+        // Unsigned values are widened to the next larger signed type.
+        for (data_type, read_type, bands, epsg) in [
+            (DataType::I16, DataType::I16, 1, 32617),
+            (DataType::U8, DataType::I16, 1, 32617),
+            (DataType::F32, DataType::F32, 1, 0),
+            (DataType::F64, DataType::F64, 3, 4326),
+        ] {
+            let mut output = sample_raster(&file_name, 6, 5, data_type);
+            output.configs.epsg_code = epsg;
+            output.configs.title = "Sample".to_string();
+            output.configs.z_units = "m".to_string();
+            if bands > 1 {
+                output.set_num_bands(bands);
+                for band in 1..bands {
+                    for row in 0..6 {
+                        for col in 0..5 {
+                            let value = (band * 10) as f64 + (row * col) as f64;
+                            output.set_band_value(band, row, col, value);
+                        }
+                    }
+                }
+            }
+            output.write().unwrap();
+
+            let input = Raster::new(&file_name, "r").unwrap();
+            assert_eq!(input.configs.data_type, read_type);
+            assert_eq!(input.configs.title, "Sample");
+            assert_eq!(input.configs.z_units, "m");
+            match epsg {
+                4326 => assert_eq!(input.configs.epsg_code, 4326),
+                0 => assert!(!is_wkt(&input.configs.projection)),
+                _ => assert!(input.configs.projection.contains("UTM")),
+            }
+            if bands > 1 {
+                assert_eq!(input.configs.band_names, vec!["band=1", "band=2", "band=3"]);
+            }
+            assert_same_extent(&output, &input);
+            assert_same_cells(&output, &input);
+
+            // The variable is also found when it isn't named.
+            let input = Raster::new(&nc_file, "r").unwrap();
+            assert_same_cells(&output, &input);
+        }
+        assert!(Raster::new(&format!("{}:precip", nc_file), "r").is_err());
+        std::fs::remove_file(&nc_file).unwrap();
+    }
+
+    #[test]
+    fn read_packed_record_variable() {
+        // A 2 x 3 grid of packed temperatures at two times, whose rows are ordered from
+        // south to north, is stored in the records of the unlimited dimension along with
+        // the times.
+        let dimension = |name: &str, length: usize| NcDimension {
+            name: name.to_string(),
+            length: length,
+        };
+        let variable = |name: &str, dim_ids: Vec<usize>, nc_type: u32, attributes| NcVariable {
+            name: name.to_string(),
+            dim_ids: dim_ids,
+            attributes: attributes,
+            nc_type: nc_type,
+            begin: 0,
+        };
+        let mut header = NcHeader {
+            num_records: 2,
+            dimensions: vec![dimension("time", 0), dimension("lat", 2), dimension("lon", 3)],
+            attributes: vec![text("title", "Temperature")],
+            variables: vec![
+                variable("lat", vec![1], NC_DOUBLE, vec![text("units", "degrees_north")]),
+                variable("lon", vec![2], NC_DOUBLE, vec![text("units", "degrees_east")]),
+                variable("time", vec![0], NC_INT, vec![text("units", "hours")]),
+                variable(
+                    "temp",
+                    vec![0, 1, 2],
+                    NC_SHORT,
+                    vec![
+                        text("units", "degC"),
+                        number("scale_factor", NC_FLOAT, 0.5),
+                        number("add_offset", NC_FLOAT, 10.0),
+                        number("_FillValue", NC_SHORT, -1.0),
+                    ],
+                ),
+            ],
+        };
+        let mut header_bytes = vec![];
+        write_header(&mut header_bytes, &header, 1).unwrap();
+        let header_size = header_bytes.len() as u64;
+        header.variables[0].begin = header_size;
+        header.variables[1].begin = header_size + 16;
+        header.variables[2].begin = header_size + 40;
+        header.variables[3].begin = header_size + 44;
+        let packed = |record: usize, file_row: usize, col: usize| {
+            if (record, file_row, col) == (1, 0, 2) {
+                -1.0
+            } else {
+                (record * 10 + file_row * 3 + col) as f64
+            }
+        };
+
+        let mut bytes = vec![];
+        write_header(&mut bytes, &header, 1).unwrap();
+        for value in [-1.0, 1.0, 100.0, 101.0, 102.0] {
+            write_nc_value(&mut bytes, NC_DOUBLE, value).unwrap();
+        }
+        for record in 0..2 {
+            write_nc_value(&mut bytes, NC_INT, (record * 6) as f64).unwrap();
+            for file_row in 0..2 {
+                for col in 0..3 {
+                    write_nc_value(&mut bytes, NC_SHORT, packed(record, file_row, col)).unwrap();
+                }
+            }
+        }
+        let file_name = temp_file("records.nc");
+        for num_records in [2u32, STREAMING] {
+            bytes[4..8].copy_from_slice(&num_records.to_be_bytes());
+            std::fs::write(&file_name, &bytes).unwrap();
+
+            let input = Raster::new(&format!("{}:temp", file_name), "r").unwrap();
+            assert_eq!(input.configs.rows, 2);
+            assert_eq!(input.configs.columns, 3);
+            assert_eq!(input.configs.bands, 2);
+            assert_eq!(input.configs.band_names, vec!["time=0", "time=6"]);
+            assert_eq!(input.configs.data_type, DataType::F32);
+            assert_eq!(input.configs.epsg_code, 4326);
+            assert_eq!(input.configs.z_units, "degC");
+            assert_eq!(input.configs.title, "Temperature");
+            assert_eq!((input.configs.west, input.configs.north), (99.5, 2.0));
+            assert_eq!((input.configs.resolution_x, input.configs.resolution_y), (1.0, 2.0));
+            for band in 0..2 {
+                for row in 0..2 {
+                    for col in 0..3 {
+                        let value = input.get_band_value(band, row as isize, col as isize);
+                        let expected = match packed(band, 1 - row, col) {
+                            fill if fill == -1.0 => input.configs.nodata,
+                            v => v * 0.5 + 10.0,
+                        };
+                        assert_eq!(value, expected, "at ({}, {}, {})", band, row, col);
+                    }
+                }
+            }
+        }
+        std::fs::remove_file(&file_name).unwrap();
+    }
+}
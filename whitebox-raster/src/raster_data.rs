@@ -0,0 +1,289 @@
+use super::*;
+
+/// The number of bytes of cell values that are read from a file at once.
+const READ_BUFFER_BYTES: usize = 1 << 22;
+
+#[derive(Clone, Debug)]
+enum Values {
+    F64(Vec<f64>),
+    F32(Vec<f32>),
+    I32(Vec<i32>),
+    U32(Vec<u32>),
+    I16(Vec<i16>),
+    U16(Vec<u16>),
+    I8(Vec<i8>),
+    U8(Vec<u8>),
+}
+
+macro_rules! for_each_type {
+    ($values:expr, $v:ident => $body:expr) => {
+        match $values {
+            Values::F64($v) => $body,
+            Values::F32($v) => $body,
+            Values::I32($v) => $body,
+            Values::U32($v) => $body,
+            Values::I16($v) => $body,
+            Values::U16($v) => $body,
+            Values::I8($v) => $body,
+            Values::U8($v) => $body,
+        }
+    };
+}
+
+/// The cell values of an in-memory raster, held in the type given by the raster's
+/// `DataType` rather than as f64s. A value that can't be represented exactly by that
+/// type, e.g. a fractional value assigned to a U8 raster, converts the storage to
+/// f64s, so the values that are read back are always those that were assigned.
+#[derive(Clone, Debug)]
+pub(crate) struct RasterData {
+    values: Values,
+}
+
+impl Default for RasterData {
+    fn default() -> RasterData {
+        RasterData::from_values(DataType::F64, vec![])
+    }
+}
+
+impl RasterData {
+    /// Creates storage for `len` cells of the type best suited to `data_type`, each holding `value`.
+    pub fn new(data_type: DataType, len: usize, value: f64) -> RasterData {
+        let mut data = RasterData::with_capacity(data_type, len);
+        if !data.fits(value) {
+            data.values = Values::F64(Vec::with_capacity(len));
+        }
+        for_each_type!(&mut data.values, v => v.resize(len, value.as_()));
+        data
+    }
+
+    /// Creates storage holding `values`, which are converted to the type best
+    /// suited to `data_type` if all of them can be represented by it.
+    pub fn from_values(data_type: DataType, values: Vec<f64>) -> RasterData {
+        let mut data = RasterData::with_capacity(data_type, values.len());
+        if values.iter().all(|&z| data.fits(z)) {
+            for &z in &values {
+                data.push(z);
+            }
+        } else {
+            data.values = Values::F64(values);
+        }
+        data
+    }
+
+    /// Reads `num_rows` rows of `columns` values from `source`, a few rows at a time.
+    pub fn read(
+        source: &mut dyn BlockSource,
+        data_type: DataType,
+        num_rows: usize,
+        columns: usize,
+    ) -> Result<RasterData, Error> {
+        let mut data = RasterData::with_capacity(data_type, num_rows * columns);
+        let preferred_rows = source.preferred_rows().max(1);
+        let buffer_rows = (READ_BUFFER_BYTES / (columns * mem::size_of::<f64>()).max(1))
+            .max(1)
+            .div_ceil(preferred_rows)
+            * preferred_rows;
+        let mut buffer = vec![0f64; buffer_rows.min(num_rows) * columns];
+        let mut row = 0;
+        while row < num_rows {
+            let n = buffer_rows.min(num_rows - row);
+            let block = &mut buffer[..n * columns];
+            source.read_rows(row, block)?;
+            for &z in block.iter() {
+                data.push(z);
+            }
+            row += n;
+        }
+        Ok(data)
+    }
+
+    /// The number of bytes occupied by each cell of storage suited to `data_type`.
+    pub fn cell_size(data_type: DataType) -> usize {
+        match storage_type(data_type) {
+            DataType::F32 | DataType::I32 | DataType::U32 => 4,
+            DataType::I16 | DataType::U16 => 2,
+            DataType::I8 | DataType::U8 => 1,
+            _ => 8,
+        }
+    }
+
+    fn with_capacity(data_type: DataType, len: usize) -> RasterData {
+        let values = match storage_type(data_type) {
+            DataType::F32 => Values::F32(Vec::with_capacity(len)),
+            DataType::I32 => Values::I32(Vec::with_capacity(len)),
+            DataType::U32 => Values::U32(Vec::with_capacity(len)),
+            DataType::I16 => Values::I16(Vec::with_capacity(len)),
+            DataType::U16 => Values::U16(Vec::with_capacity(len)),
+            DataType::I8 => Values::I8(Vec::with_capacity(len)),
+            DataType::U8 => Values::U8(Vec::with_capacity(len)),
+            _ => Values::F64(Vec::with_capacity(len)),
+        };
+        RasterData { values: values }
+    }
+
+    pub fn len(&self) -> usize {
+        for_each_type!(&self.values, v => v.len())
+    }
+
+    /// Returns the number of bytes occupied by the values.
+    pub fn size_in_bytes(&self) -> usize {
+        for_each_type!(&self.values, v => mem::size_of_val(&v[..]))
+    }
+
+    /// Returns the type in which the values are held.
+    pub fn data_type(&self) -> DataType {
+        match &self.values {
+            Values::F64(_) => DataType::F64,
+            Values::F32(_) => DataType::F32,
+            Values::I32(_) => DataType::I32,
+            Values::U32(_) => DataType::U32,
+            Values::I16(_) => DataType::I16,
+            Values::U16(_) => DataType::U16,
+            Values::I8(_) => DataType::I8,
+            Values::U8(_) => DataType::U8,
+        }
+    }
+
+    #[inline]
+    pub fn get(&self, idx: usize) -> f64 {
+        for_each_type!(&self.values, v => v[idx].as_())
+    }
+
+    #[inline]
+    pub fn set(&mut self, idx: usize, value: f64) {
+        if !self.fits(value) {
+            self.widen();
+        }
+        for_each_type!(&mut self.values, v => v[idx] = value.as_());
+    }
+
+    pub fn push(&mut self, value: f64) {
+        if !self.fits(value) {
+            self.widen();
+        }
+        for_each_type!(&mut self.values, v => v.push(value.as_()));
+    }
+
+    pub fn truncate(&mut self, len: usize) {
+        for_each_type!(&mut self.values, v => v.truncate(len));
+    }
+
+    /// Appends `n` cells holding `value`.
+    pub fn extend(&mut self, value: f64, n: usize) {
+        let len = self.len() + n;
+        if !self.fits(value) {
+            self.widen();
+        }
+        for_each_type!(&mut self.values, v => v.resize(len, value.as_()));
+    }
+
+    /// Returns true if `value` can be held without loss.
+    #[inline]
+    fn fits(&self, value: f64) -> bool {
+        match &self.values {
+            Values::F64(_) => true,
+            Values::F32(_) => value as f32 as f64 == value || value.is_nan(),
+            Values::I32(_) => value as i32 as f64 == value,
+            Values::U32(_) => value as u32 as f64 == value,
+            Values::I16(_) => value as i16 as f64 == value,
+            Values::U16(_) => value as u16 as f64 == value,
+            Values::I8(_) => value as i8 as f64 == value,
+            Values::U8(_) => value as u8 as f64 == value,
+        }
+    }
+
+    /// Converts the storage to f64s.
+    fn widen(&mut self) {
+        if let Values::F64(_) = self.values {
+            return;
+        }
+        self.values = Values::F64((0..self.len()).map(|i| self.get(i)).collect());
+    }
+}
+
+/// Returns the type in which values of `data_type` are held. Types of more than 32
+/// bits, and colour values, are held as f64s.
+fn storage_type(data_type: DataType) -> DataType {
+    match data_type {
+        DataType::F32
+        | DataType::I32
+        | DataType::U32
+        | DataType::I16
+        | DataType::U16
+        | DataType::I8
+        | DataType::U8 => data_type,
+        _ => DataType::F64,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::RasterData;
+    use crate::test_utils::*;
+    use crate::*;
+
+    const DATA_TYPES: [DataType; 8] = [
+        DataType::F64,
+        DataType::F32,
+        DataType::I32,
+        DataType::U32,
+        DataType::I16,
+        DataType::U16,
+        DataType::I8,
+        DataType::U8,
+    ];
+
+    #[test]
+    fn values_are_stored_in_their_data_type() {
+        for &data_type in &DATA_TYPES {
+            let mut data = RasterData::new(data_type, 10, 3.0);
+            assert_eq!(data.data_type(), data_type);
+            assert_eq!(data.size_in_bytes(), 10 * data_type.get_data_size());
+            data.set(4, 100.0);
+            data.push(7.0);
+            assert_eq!(data.data_type(), data_type);
+            assert_eq!((data.len(), data.get(0), data.get(4), data.get(10)), (11, 3.0, 100.0, 7.0));
+        }
+        for &data_type in &[DataType::I64, DataType::U64, DataType::RGBA32] {
+            assert_eq!(RasterData::new(data_type, 10, 3.0).data_type(), DataType::F64);
+        }
+    }
+
+    #[test]
+    fn unrepresentable_values_widen_the_storage() {
+        for (data_type, value) in [
+            (DataType::U8, 256.0),
+            (DataType::U8, -1.0),
+            (DataType::I16, 0.5),
+            (DataType::U32, 5e9),
+            (DataType::F32, 0.1),
+        ] {
+            let mut data = RasterData::from_values(data_type, vec![1.0, 2.0, 3.0]);
+            assert_eq!(data.data_type(), data_type);
+            data.set(1, value);
+            assert_eq!(data.data_type(), DataType::F64);
+            assert_eq!((data.get(0), data.get(1), data.get(2)), (1.0, value, 3.0));
+        }
+        let data = RasterData::from_values(DataType::I8, vec![1.0, -200.0]);
+        assert_eq!(data.data_type(), DataType::F64);
+        let mut data = RasterData::new(DataType::U16, 2, -32768.0);
+        assert_eq!(data.data_type(), DataType::F64);
+        data.extend(f64::NAN, 1);
+        assert!(data.get(2).is_nan());
+    }
+
+    #[test]
+    fn rasters_are_read_into_storage_of_their_data_type() {
+        for &data_type in &DATA_TYPES {
+            let file_name = temp_file(&format!("storage_{:?}.tif", data_type));
+            let mut output = sample_raster(&file_name, 5, 7, data_type);
+            assert_eq!(output.get_storage_data_type(), data_type);
+            output.write().unwrap();
+            let input = Raster::new(&file_name, "r").unwrap();
+            assert_eq!(input.configs.data_type, data_type);
+            assert_eq!(input.get_storage_data_type(), data_type);
+            assert_same_cells(&output, &input);
+            std::fs::remove_file(&file_name).unwrap();
+        }
+    }
+}
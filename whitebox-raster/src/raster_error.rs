@@ -0,0 +1,100 @@
+use std::error;
+use std::fmt;
+use std::io;
+
+/// The errors that may occur while determining the format of, reading, or writing
+/// a raster file.
+///
+/// `RasterError` converts to and from `std::io::Error`, so that it may be returned
+/// with the `?` operator from functions that report I/O errors.
+#[derive(Debug)]
+pub enum RasterError {
+    /// The format of a raster file could not be determined from its file name or contents.
+    UnknownFormat { file_name: String, reason: String },
+    /// A raster was written that was not created in write mode ('w').
+    NotWritable { file_name: String },
+    /// A raster file could not be read, either because the file could not be accessed or
+    /// because its contents are invalid or unsupported.
+    Read { file_name: String, source: io::Error },
+    /// A raster file could not be written, e.g. because the disk is full or the
+    /// directory is not writable.
+    Write { file_name: String, source: io::Error },
+    /// Any other I/O error.
+    Io(io::Error),
+}
+
+impl RasterError {
+    pub(crate) fn read(file_name: &str, source: io::Error) -> RasterError {
+        RasterError::Read {
+            file_name: file_name.to_string(),
+            source,
+        }
+    }
+
+    pub(crate) fn write(file_name: &str, source: io::Error) -> RasterError {
+        RasterError::Write {
+            file_name: file_name.to_string(),
+            source,
+        }
+    }
+
+    /// Returns the kind of I/O error that best describes this error.
+    pub fn kind(&self) -> io::ErrorKind {
+        match self {
+            RasterError::UnknownFormat { .. } | RasterError::NotWritable { .. } => {
+                io::ErrorKind::InvalidInput
+            }
+            RasterError::Read { source, .. } | RasterError::Write { source, .. } => source.kind(),
+            RasterError::Io(e) => e.kind(),
+        }
+    }
+}
+
+impl fmt::Display for RasterError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            RasterError::UnknownFormat { file_name, reason } => write!(
+                f,
+                "The raster format of the file '{}' could not be determined: {}",
+                file_name, reason
+            ),
+            RasterError::NotWritable { file_name } => write!(
+                f,
+                "Cannot write the raster '{}' because it was not created in write mode ('w').",
+                file_name
+            ),
+            RasterError::Read { file_name, source } => {
+                write!(f, "Error reading the raster file '{}': {}", file_name, source)
+            }
+            RasterError::Write { file_name, source } => {
+                write!(f, "Error writing the raster file '{}': {}", file_name, source)
+            }
+            RasterError::Io(e) => e.fmt(f),
+        }
+    }
+}
+
+impl error::Error for RasterError {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self {
+            RasterError::Read { source, .. } | RasterError::Write { source, .. } => Some(source),
+            RasterError::Io(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for RasterError {
+    fn from(e: io::Error) -> RasterError {
+        RasterError::Io(e)
+    }
+}
+
+impl From<RasterError> for io::Error {
+    fn from(e: RasterError) -> io::Error {
+        match e {
+            RasterError::Io(e) => e,
+            e => io::Error::new(e.kind(), e),
+        }
+    }
+}
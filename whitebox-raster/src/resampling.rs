@@ -0,0 +1,167 @@
+/*
+This code is part of the WhiteboxTools geospatial analysis library.
+License: MIT
+*/
+
+use super::Raster;
+
+/// The methods with which a raster can be sampled at locations that fall between the
+/// centres of its grid cells, e.g. when it is resampled or reprojected.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ResamplingMethod {
+    /// The value of the cell containing the location.
+    NearestNeighbour,
+    /// The inverse-distance weighted average of the 2 x 2 cells surrounding the location.
+    Bilinear,
+    /// The inverse-distance weighted average of the 4 x 4 cells surrounding the location.
+    CubicConvolution,
+    /// The most common value of the cells within a window around the location, which suits
+    /// categorical data.
+    Mode,
+}
+
+impl ResamplingMethod {
+    /// Reads a method from the names accepted by the tools' `--method` parameters, i.e.
+    /// 'nn' (or 'nearest'), 'bilinear', 'cc' (or 'cubic'), and 'mode'.
+    pub fn from_name(name: &str) -> Option<ResamplingMethod> {
+        let name = name.to_lowercase();
+        if name.contains("nn") || name.contains("nearest") {
+            Some(ResamplingMethod::NearestNeighbour)
+        } else if name.contains("mode") || name.contains("majority") {
+            Some(ResamplingMethod::Mode)
+        } else if name.contains("bi") {
+            Some(ResamplingMethod::Bilinear)
+        } else if name.contains("cc") || name.contains("cubic") {
+            Some(ResamplingMethod::CubicConvolution)
+        } else {
+            None
+        }
+    }
+
+    /// Returns true if the method interpolates new values, rather than choosing one of
+    /// the values of the raster, in which case its output is continuous data.
+    pub fn interpolates(&self) -> bool {
+        match self {
+            ResamplingMethod::Bilinear | ResamplingMethod::CubicConvolution => true,
+            _ => false,
+        }
+    }
+}
+
+impl Raster {
+    /// Samples the raster at a location given as a fractional row and column, where the
+    /// centre of the upper-left cell is at (0, 0), e.g. the row of a y coordinate is
+    /// `(north - y) / resolution_y - 0.5`. NoData cells are left out of the interpolating
+    /// methods, and None is returned where there is no valid value to sample. The mode is
+    /// taken within a window the size of one cell; see `get_mode_in_window`.
+    pub fn get_value_resampled(
+        &self,
+        row: f64,
+        column: f64,
+        method: ResamplingMethod,
+    ) -> Option<f64> {
+        match method {
+            ResamplingMethod::NearestNeighbour => {
+                self.valid_value(row.round() as isize, column.round() as isize)
+            }
+            ResamplingMethod::Bilinear => self.inverse_distance_weighted(row, column, 0, 1),
+            ResamplingMethod::CubicConvolution => {
+                self.inverse_distance_weighted(row, column, -1, 2)
+            }
+            ResamplingMethod::Mode => {
+                self.get_mode_in_window(row - 0.5, column - 0.5, row + 0.5, column + 0.5)
+            }
+        }
+    }
+
+    /// Returns the most common valid value of the cells whose centres fall within a
+    /// window, given by the fractional rows and columns of its edges (see
+    /// `get_value_resampled`). Ties go to the value nearest the centre of the window. A
+    /// window that is too small to contain a cell centre takes the value of the cell that
+    /// contains its centre.
+    pub fn get_mode_in_window(&self, top: f64, left: f64, bottom: f64, right: f64) -> Option<f64> {
+        let centre_row = (top + bottom) / 2f64;
+        let centre_column = (left + right) / 2f64;
+        let (first_row, last_row) = (top.ceil() as isize, bottom.floor() as isize);
+        let (first_column, last_column) = (left.ceil() as isize, right.floor() as isize);
+        if first_row > last_row || first_column > last_column {
+            return self.valid_value(centre_row.round() as isize, centre_column.round() as isize);
+        }
+        // (value, count, squared distance of the nearest cell with the value)
+        let mut counts: Vec<(f64, usize, f64)> = vec![];
+        for r in first_row..=last_row {
+            for c in first_column..=last_column {
+                let z = match self.valid_value(r, c) {
+                    Some(z) => z,
+                    None => continue,
+                };
+                let dist = (r as f64 - centre_row).powi(2) + (c as f64 - centre_column).powi(2);
+                match counts.iter_mut().find(|entry| entry.0 == z) {
+                    Some(entry) => {
+                        entry.1 += 1;
+                        entry.2 = entry.2.min(dist);
+                    }
+                    None => counts.push((z, 1, dist)),
+                }
+            }
+        }
+        counts
+            .into_iter()
+            .max_by(|a, b| a.1.cmp(&b.1).then(b.2.partial_cmp(&a.2).unwrap()))
+            .map(|entry| entry.0)
+    }
+
+    /// Returns the value of a cell, or None if it is NoData or outside of the raster.
+    fn valid_value(&self, row: isize, column: isize) -> Option<f64> {
+        if row < 0
+            || column < 0
+            || row >= self.configs.rows as isize
+            || column >= self.configs.columns as isize
+        {
+            return None;
+        }
+        let z = self.get_value(row, column);
+        if z == self.configs.nodata || z.is_nan() {
+            None
+        } else {
+            Some(z)
+        }
+    }
+
+    /// Interpolates a value from the cells from `first` to `last` rows and columns away
+    /// from the cell that is above and to the left of the location, weighting each by its
+    /// inverse squared distance. A location at a cell's centre takes that cell's value.
+    fn inverse_distance_weighted(
+        &self,
+        row: f64,
+        column: f64,
+        first: isize,
+        last: isize,
+    ) -> Option<f64> {
+        let origin_row = row.floor() as isize;
+        let origin_column = column.floor() as isize;
+        let mut sum_weights = 0f64;
+        let mut sum = 0f64;
+        for r in origin_row + first..=origin_row + last {
+            for c in origin_column + first..=origin_column + last {
+                let z = match self.valid_value(r, c) {
+                    Some(z) => z,
+                    None => continue,
+                };
+                let dy = r as f64 - row;
+                let dx = c as f64 - column;
+                let dist = dx * dx + dy * dy;
+                if dist == 0f64 {
+                    return Some(z);
+                }
+                sum_weights += 1f64 / dist;
+                sum += z / dist;
+            }
+        }
+        if sum_weights > 0f64 {
+            Some(sum / sum_weights)
+        } else {
+            None
+        }
+    }
+}
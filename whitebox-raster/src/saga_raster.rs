@@ -25,7 +25,7 @@ pub fn read_saga(
     let mut top_to_bottom = false;
     let mut z_factor = 1.0;
     for line in f.lines() {
-        let line_unwrapped = line.unwrap();
+        let line_unwrapped = line?;
         //let line_split = line_unwrapped.split("\t");
         let line_split = line_unwrapped.split("=");
         let vec = line_split.collect::<Vec<&str>>();
@@ -162,11 +162,7 @@ pub fn read_saga(
 
     // read the data file
     // let data_file = file_name.replace(".sgrd", ".sdat");
-    let data_file = Path::new(&file_name)
-        .with_extension("sdat")
-        .into_os_string()
-        .into_string()
-        .unwrap();
+    let data_file = get_data_file_name(&file_name, "sgrd", "sdat");
     let mut f = File::open(data_file.clone())?;
     f.seek(SeekFrom::Start(data_file_offset))?;
 
@@ -389,7 +385,7 @@ pub fn read_saga(
             configs.projection = String::new();
             let f = BufReader::new(f);
             for line in f.lines() {
-                let line_unwrapped = line.unwrap();
+                let line_unwrapped = line?;
                 configs
                     .projection
                     .push_str(&format!("{}\n", line_unwrapped));
@@ -403,8 +399,8 @@ pub fn read_saga(
 
 pub fn write_saga<'a>(r: &'a mut Raster) -> Result<(), Error> {
     // figure out the minimum and maximum values
-    for val in &r.data {
-        let v = *val;
+    for i in 0..r.configs.rows * r.configs.columns {
+        let v = r.get_cell(i);
         if v != r.configs.nodata {
             if v < r.configs.minimum {
                 r.configs.minimum = v;
@@ -518,15 +514,11 @@ pub fn write_saga<'a>(r: &'a mut Raster) -> Result<(), Error> {
 
     writer.write_all("TOPTOBOTTOM\t= FALSE\n".as_bytes())?;
 
-    let _ = writer.flush();
+    writer.flush()?;
 
     // write the data file
     // let data_file = r.file_name.replace(".sgrd", ".sdat");
-    let data_file = Path::new(&r.file_name)
-        .with_extension("sdat")
-        .into_os_string()
-        .into_string()
-        .unwrap();
+    let data_file = get_data_file_name(&r.file_name, "sgrd", "sdat");
     let f = File::create(&data_file)?;
     let mut writer = BufWriter::new(f);
 
@@ -539,7 +531,7 @@ pub fn write_saga<'a>(r: &'a mut Raster) -> Result<(), Error> {
             for row in (0..r.configs.rows).rev() {
                 for col in 0..r.configs.columns {
                     i = row * r.configs.columns + col;
-                    u64_bytes = unsafe { mem::transmute(r.data[i]) };
+                    u64_bytes = unsafe { mem::transmute(r.get_cell(i)) };
                     writer.write(&u64_bytes)?;
                 }
             }
@@ -548,7 +540,7 @@ pub fn write_saga<'a>(r: &'a mut Raster) -> Result<(), Error> {
             for row in (0..r.configs.rows).rev() {
                 for col in 0..r.configs.columns {
                     i = row * r.configs.columns + col;
-                    u32_bytes = unsafe { mem::transmute(r.data[i] as f32) };
+                    u32_bytes = unsafe { mem::transmute(r.get_cell(i) as f32) };
                     writer.write(&u32_bytes)?;
                 }
             }
@@ -557,7 +549,7 @@ pub fn write_saga<'a>(r: &'a mut Raster) -> Result<(), Error> {
             for row in (0..r.configs.rows).rev() {
                 for col in 0..r.configs.columns {
                     i = row * r.configs.columns + col;
-                    u32_bytes = unsafe { mem::transmute(r.data[i] as i32) };
+                    u32_bytes = unsafe { mem::transmute(r.get_cell(i) as i32) };
                     writer.write(&u32_bytes)?;
                 }
             }
@@ -566,7 +558,7 @@ pub fn write_saga<'a>(r: &'a mut Raster) -> Result<(), Error> {
             for row in (0..r.configs.rows).rev() {
                 for col in 0..r.configs.columns {
                     i = row * r.configs.columns + col;
-                    u32_bytes = unsafe { mem::transmute(r.data[i] as u32) };
+                    u32_bytes = unsafe { mem::transmute(r.get_cell(i) as u32) };
                     writer.write(&u32_bytes)?;
                 }
             }
@@ -575,7 +567,7 @@ pub fn write_saga<'a>(r: &'a mut Raster) -> Result<(), Error> {
             for row in (0..r.configs.rows).rev() {
                 for col in 0..r.configs.columns {
                     i = row * r.configs.columns + col;
-                    u16_bytes = unsafe { mem::transmute(r.data[i] as i16) };
+                    u16_bytes = unsafe { mem::transmute(r.get_cell(i) as i16) };
                     writer.write(&u16_bytes)?;
                 }
             }
@@ -584,7 +576,7 @@ pub fn write_saga<'a>(r: &'a mut Raster) -> Result<(), Error> {
             for row in (0..r.configs.rows).rev() {
                 for col in 0..r.configs.columns {
                     i = row * r.configs.columns + col;
-                    u16_bytes = unsafe { mem::transmute(r.data[i] as u16) };
+                    u16_bytes = unsafe { mem::transmute(r.get_cell(i) as u16) };
                     writer.write(&u16_bytes)?;
                 }
             }
@@ -593,7 +585,7 @@ pub fn write_saga<'a>(r: &'a mut Raster) -> Result<(), Error> {
             for row in (0..r.configs.rows).rev() {
                 for col in 0..r.configs.columns {
                     i = row * r.configs.columns + col;
-                    writer.write(&[r.data[i] as u8])?;
+                    writer.write(&[r.get_cell(i) as u8])?;
                 }
             }
         }
@@ -605,7 +597,7 @@ pub fn write_saga<'a>(r: &'a mut Raster) -> Result<(), Error> {
         }
     }
 
-    let _ = writer.flush();
+    writer.flush()?;
 
     ///////////////////////////////
     // Write the projection file //
@@ -620,6 +612,7 @@ pub fn write_saga<'a>(r: &'a mut Raster) -> Result<(), Error> {
         let f = File::create(&prj_file)?;
         let mut writer = BufWriter::new(f);
         writer.write_all(r.configs.projection.as_bytes())?;
+        writer.flush()?;
     }
 
     Ok(())
@@ -0,0 +1,173 @@
+use super::*;
+use whitebox_common::spatial_ref_system::esri_wkt_from_epsg;
+use whitebox_common::utils::Endianness;
+use std::f64;
+use std::fs;
+use std::fs::File;
+use std::io::prelude::*;
+use std::io::{BufWriter, Error, ErrorKind};
+
+/// The value of voids in SRTM height (.hgt) files.
+const SRTM_NODATA: f64 = -32768f64;
+
+/// Returns the latitude and longitude of the south-west corner of an SRTM tile, which
+/// are encoded in its file name, e.g. N45W080.hgt, or NASADEM_HGT_n45w080.hgt.
+fn parse_srtm_file_name(file_name: &str) -> Option<(f64, f64)> {
+    let stem = Path::new(file_name)
+        .file_stem()
+        .and_then(|s| s.to_str())?
+        .to_lowercase();
+    let chars: Vec<char> = stem.chars().collect();
+    for i in 0..chars.len().saturating_sub(6) {
+        let lat_hemisphere = chars[i];
+        let lon_hemisphere = chars[i + 3];
+        if (lat_hemisphere == 'n' || lat_hemisphere == 's')
+            && (lon_hemisphere == 'e' || lon_hemisphere == 'w')
+            && chars[i + 1..i + 3].iter().all(|c| c.is_ascii_digit())
+            && chars[i + 4..i + 7].iter().all(|c| c.is_ascii_digit())
+        {
+            let lat: f64 = chars[i + 1..i + 3].iter().collect::<String>().parse().ok()?;
+            let lon: f64 = chars[i + 4..i + 7].iter().collect::<String>().parse().ok()?;
+            return Some((
+                if lat_hemisphere == 's' { -lat } else { lat },
+                if lon_hemisphere == 'w' { -lon } else { lon },
+            ));
+        }
+    }
+    None
+}
+
+/// Reads the georeferencing of an SRTM height (.hgt) file, returning the arrangement of
+/// the values within the file. The files have no header; they hold a square grid of
+/// big-endian 16-bit integers covering a one-degree tile, the size of which is implied by
+/// the size of the file, and the location of the tile is given by the file's name.
+pub(crate) fn read_srtm_header(
+    file_name: &String,
+    configs: &mut RasterConfigs,
+) -> Result<RawLayout, Error> {
+    let (lat, lon) = parse_srtm_file_name(file_name).ok_or_else(|| {
+        Error::new(
+            ErrorKind::InvalidData,
+            "The name of an SRTM height (.hgt) file must give the location of the tile, e.g. N45W080.hgt.",
+        )
+    })?;
+
+    let num_values = fs::metadata(file_name)?.len() as usize / 2;
+    let size = (num_values as f64).sqrt().round() as usize;
+    if size < 2 || size * size != num_values {
+        return Err(Error::new(
+            ErrorKind::InvalidData,
+            "The SRTM height (.hgt) file does not contain a square grid of 16-bit values.",
+        ));
+    }
+
+    configs.rows = size;
+    configs.columns = size;
+    configs.bands = 1;
+    configs.data_type = DataType::I16;
+    configs.endian = Endianness::BigEndian;
+    configs.nodata = SRTM_NODATA;
+    configs.photometric_interp = PhotometricInterpretation::Continuous;
+
+    // The values are located at the tile's edges, e.g. 1201 values span a degree at a
+    // 3 arc-second resolution, so the grid extends half a cell beyond the tile.
+    configs.resolution_x = 1f64 / (size - 1) as f64;
+    configs.resolution_y = configs.resolution_x;
+    configs.west = lon - configs.resolution_x / 2f64;
+    configs.north = lat + 1f64 + configs.resolution_y / 2f64;
+    configs.east = configs.west + configs.resolution_x * size as f64;
+    configs.south = configs.north - configs.resolution_y * size as f64;
+
+    configs.epsg_code = 4326;
+    configs.coordinate_ref_system_wkt = esri_wkt_from_epsg(4326);
+    configs.projection = configs.coordinate_ref_system_wkt.clone();
+    configs.xy_units = "degrees".to_string();
+    configs.z_units = "metres".to_string();
+
+    Ok(RawLayout {
+        band_row_bytes: size * 2,
+        total_row_bytes: size * 2,
+        ..Default::default()
+    })
+}
+
+pub fn write_srtm<'a>(r: &'a mut Raster) -> Result<(), Error> {
+    if r.configs.rows != r.configs.columns {
+        return Err(Error::new(
+            ErrorKind::InvalidInput,
+            "SRTM height (.hgt) files must contain a square grid.",
+        ));
+    }
+    let (lat, lon) = parse_srtm_file_name(&r.file_name).ok_or_else(|| {
+        Error::new(
+            ErrorKind::InvalidInput,
+            "The name of an SRTM height (.hgt) file must give the location of the tile, e.g. N45W080.hgt.",
+        )
+    })?;
+    let res_x = r.configs.resolution_x;
+    let res_y = r.configs.resolution_y;
+    if (r.configs.west + res_x / 2f64 - lon).abs() > res_x / 2f64
+        || (r.configs.north - res_y / 2f64 - lat - 1f64).abs() > res_y / 2f64
+    {
+        println!(
+            "Warning: The extent of the raster does not match the SRTM tile named by {}.",
+            r.file_name
+        );
+    }
+
+    let f = File::create(&r.file_name)?;
+    let mut writer = BufWriter::new(f);
+    let nodata = r.configs.nodata;
+    let mut value: f64;
+    for i in 0..r.configs.rows * r.configs.columns {
+        value = r.get_cell(i);
+        let v = if value == nodata {
+            SRTM_NODATA as i16
+        } else {
+            value.round().max(-32767f64).min(32767f64) as i16
+        };
+        writer.write_all(&v.to_be_bytes())?;
+    }
+
+    writer.flush()?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::parse_srtm_file_name;
+    use crate::test_utils::*;
+    use crate::*;
+
+    #[test]
+    fn srtm_file_names() {
+        assert_eq!(parse_srtm_file_name("N45W080.hgt"), Some((45.0, -80.0)));
+        assert_eq!(parse_srtm_file_name("/data/s09e142.hgt"), Some((-9.0, 142.0)));
+        assert_eq!(parse_srtm_file_name("NASADEM_HGT_n45w080.hgt"), Some((45.0, -80.0)));
+        assert_eq!(parse_srtm_file_name("dem.hgt"), None);
+    }
+
+    #[test]
+    fn srtm_round_trip() {
+        // A 5 x 5 grid spans the one-degree tile, with its outer cells centred on the edges.
+        let file_name = temp_file("S09E142.hgt");
+        let mut output = sample_raster(&file_name, 5, 5, DataType::I16);
+        output.configs.resolution_x = 0.25;
+        output.configs.resolution_y = 0.25;
+        output.configs.west = 141.875;
+        output.configs.east = 143.125;
+        output.configs.north = -7.875;
+        output.configs.south = -9.125;
+        output.write().unwrap();
+        assert_eq!(std::fs::metadata(&file_name).unwrap().len(), 5 * 5 * 2);
+
+        let input = Raster::new(&file_name, "r").unwrap();
+        assert_eq!(input.configs.data_type, DataType::I16);
+        assert_eq!(input.configs.epsg_code, 4326);
+        assert_eq!(input.configs.nodata, -32768.0);
+        assert_same_extent(&output, &input);
+        assert_same_cells(&output, &input);
+        std::fs::remove_file(&file_name).unwrap();
+    }
+}
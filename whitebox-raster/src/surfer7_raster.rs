@@ -310,8 +310,8 @@ pub fn read_surfer7(
 
 pub fn write_surfer7<'a>(r: &'a mut Raster) -> Result<(), Error> {
     // figure out the minimum and maximum values
-    for val in &r.data {
-        let v = *val;
+    for i in 0..r.configs.rows * r.configs.columns {
+        let v = r.get_cell(i);
         if v != r.configs.nodata {
             if v < r.configs.minimum {
                 r.configs.minimum = v;
@@ -385,12 +385,12 @@ pub fn write_surfer7<'a>(r: &'a mut Raster) -> Result<(), Error> {
     for row in (0..r.configs.rows).rev() {
         for col in 0..r.configs.columns {
             i = row * r.configs.columns + col;
-            u64_bytes = unsafe { mem::transmute(r.data[i]) };
+            u64_bytes = unsafe { mem::transmute(r.get_cell(i)) };
             writer.write(&u64_bytes)?;
         }
     }
 
-    let _ = writer.flush();
+    writer.flush()?;
 
     Ok(())
 }
@@ -24,7 +24,7 @@ pub fn read_surfer_ascii_raster(
     let mut num_cells = 0usize;
     let mut line_num = 0;
     for line in f.lines() {
-        let line_unwrapped = line.unwrap();
+        let line_unwrapped = line?;
         let mut line_split = line_unwrapped.split(" ");
         let mut vec = line_split.collect::<Vec<&str>>();
         if vec.is_empty() && line_num > 0 {
@@ -122,8 +122,8 @@ pub fn write_surfer_ascii_raster<'a>(r: &'a mut Raster) -> Result<(), Error> {
     }
 
     // figure out the minimum and maximum values
-    for val in &r.data {
-        let v = *val;
+    for i in 0..r.configs.rows * r.configs.columns {
+        let v = r.get_cell(i);
         if v != r.configs.nodata {
             if v < r.configs.minimum {
                 r.configs.minimum = v;
@@ -155,14 +155,14 @@ pub fn write_surfer_ascii_raster<'a>(r: &'a mut Raster) -> Result<(), Error> {
         for col in 0..r.configs.columns {
             let i = row * r.configs.columns + col;
             if col < r.configs.columns - 1 {
-                if r.data[i] != r.configs.nodata {
-                    s2 += &format!("{:.*} ", num_decimals, r.data[i]);
+                if r.get_cell(i) != r.configs.nodata {
+                    s2 += &format!("{:.*} ", num_decimals, r.get_cell(i));
                 } else {
                     s2 += &format!("1.71041e38 ");
                 }
             } else {
-                if r.data[i] != r.configs.nodata {
-                    s2 += &format!("{:.*}\n", num_decimals, r.data[i]);
+                if r.get_cell(i) != r.configs.nodata {
+                    s2 += &format!("{:.*}\n", num_decimals, r.get_cell(i));
                 } else {
                     s2 += &format!("1.71041e38\n");
                 }
@@ -172,7 +172,7 @@ pub fn write_surfer_ascii_raster<'a>(r: &'a mut Raster) -> Result<(), Error> {
         s2 = String::new();
     }
 
-    let _ = writer.flush();
+    writer.flush()?;
 
     Ok(())
 }
@@ -0,0 +1,85 @@
+// Data shared by the unit tests of the raster formats.
+
+use crate::*;
+
+/// Returns the name of a file in the temporary directory that is unique to this process.
+pub fn temp_file(name: &str) -> String {
+    std::env::temp_dir()
+        .join(format!("wbt_{}_{}", std::process::id(), name))
+        .to_string_lossy()
+        .to_string()
+}
+
+/// The value of a cell of `sample_raster`, which the data type can represent exactly.
+pub fn sample_value(row: isize, col: isize, data_type: DataType) -> f64 {
+    let v = ((row * 31 + col * 17) % 100) as f64;
+    if data_type.is_float() {
+        v / 4.0 - 5.0
+    } else if data_type.is_unsigned_integer() {
+        v
+    } else {
+        v - 50.0
+    }
+}
+
+/// Returns a raster of a data type whose cells hold `sample_value`, except the first cell
+/// of each row, which is NoData. The north-west corner is at (100, 200), and the cells are
+/// 2 units wide and 3 high.
+pub fn sample_raster(file_name: &str, rows: usize, columns: usize, data_type: DataType) -> Raster {
+    let mut configs = RasterConfigs::default();
+    configs.rows = rows;
+    configs.columns = columns;
+    configs.nodata = match data_type {
+        DataType::I8 => -128.0,
+        _ if data_type.is_unsigned_integer() => 255.0,
+        _ => -32768.0,
+    };
+    configs.resolution_x = 2.0;
+    configs.resolution_y = 3.0;
+    configs.west = 100.0;
+    configs.east = 100.0 + 2.0 * columns as f64;
+    configs.north = 200.0;
+    configs.south = 200.0 - 3.0 * rows as f64;
+    configs.data_type = data_type;
+    configs.photometric_interp = PhotometricInterpretation::Continuous;
+    let mut r = Raster::initialize_using_config(file_name, &configs);
+    for row in 0..rows as isize {
+        for col in 1..columns as isize {
+            r.set_value(row, col, sample_value(row, col, data_type));
+        }
+    }
+    r
+}
+
+/// Asserts that two rasters have the same extent and cell size.
+pub fn assert_same_extent(a: &Raster, b: &Raster) {
+    for (u, v) in [
+        (a.configs.north, b.configs.north),
+        (a.configs.south, b.configs.south),
+        (a.configs.east, b.configs.east),
+        (a.configs.west, b.configs.west),
+        (a.configs.resolution_x, b.configs.resolution_x),
+        (a.configs.resolution_y, b.configs.resolution_y),
+    ] {
+        assert!((u - v).abs() < 1e-9, "The extents differ: {} and {}", u, v);
+    }
+}
+
+/// Asserts that two rasters have the same dimensions and cell values.
+pub fn assert_same_cells(a: &Raster, b: &Raster) {
+    assert_eq!(a.configs.rows, b.configs.rows);
+    assert_eq!(a.configs.columns, b.configs.columns);
+    assert_eq!(a.configs.bands, b.configs.bands);
+    for band in 0..a.configs.bands {
+        for row in 0..a.configs.rows as isize {
+            for col in 0..a.configs.columns as isize {
+                let (u, v) = (a.get_band_value(band, row, col), b.get_band_value(band, row, col));
+                let (u_nodata, v_nodata) = (u == a.get_band_nodata(band), v == b.get_band_nodata(band));
+                assert_eq!(u_nodata, v_nodata, "NoData differs at ({}, {}, {})", band, row, col);
+                if !u_nodata {
+                    assert_eq!(u, v, "The values differ at ({}, {}, {})", band, row, col);
+                }
+            }
+        }
+    }
+}
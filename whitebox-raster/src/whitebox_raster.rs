@@ -15,7 +15,128 @@ pub fn read_whitebox(
     configs: &mut RasterConfigs,
     data: &mut Vec<f64>,
 ) -> Result<(), Error> {
-    // read the header file
+    read_whitebox_header(file_name, configs)?;
+
+    // read the data file
+    // let data_file = file_name.replace(".dep", ".tas");
+    let data_file = get_data_file_name(&file_name, "dep", "tas");
+    let mut f = File::open(data_file.clone())?;
+    //let br = BufReader::new(f);
+    // let metadata = fs::metadata(data_file.clone())?;
+    // let file_size: usize = metadata.len() as usize;
+    // let mut buffer = vec![0; file_size];
+
+    let data_size = if configs.data_type == DataType::F64 {
+        8
+    } else if configs.data_type == DataType::F32
+        || configs.data_type == DataType::I32
+        || configs.data_type == DataType::RGBA32
+    {
+        4
+    } else if configs.data_type == DataType::I16 {
+        2
+    } else {
+        // DataType::Byte
+        1
+    };
+
+    // Multi-band (stacked) rasters store each band sequentially.
+    let num_cells = configs.rows * configs.columns * configs.bands;
+    data.reserve(num_cells);
+
+    let buf_size = if num_cells > 10_000_000usize {
+        10_000_000usize
+    } else {
+        num_cells
+    };
+    while data.len() < num_cells {
+        // let mut buffer = vec![0u8; buf_size * data_size];
+        let mut buffer = vec![];
+        buffer.reserve_exact(buf_size * data_size);
+        unsafe {
+            buffer.set_len(buf_size * data_size);
+        }
+
+        f.read(&mut buffer)?;
+
+        let num_values = if data.len() + buf_size <= num_cells {
+            buf_size
+        } else {
+            num_cells - data.len()
+        };
+
+        // read the file's bytes into a buffer
+        let mut bor = ByteOrderReader::<Cursor<Vec<u8>>>::new(Cursor::new(buffer), configs.endian);
+
+        match configs.data_type {
+            DataType::F64 => {
+                bor.seek(0);
+                for _ in 0..num_values {
+                    data.push(bor.read_f64()? as f64);
+                }
+                if data.len() == num_cells {
+                    break;
+                }
+            }
+            DataType::F32 => {
+                bor.seek(0);
+                for _ in 0..num_values {
+                    data.push(bor.read_f32()? as f64);
+                }
+                if data.len() == num_cells {
+                    break;
+                }
+            }
+            DataType::I32 => {
+                bor.seek(0);
+                for _ in 0..num_values {
+                    data.push(bor.read_i32()? as f64);
+                }
+                if data.len() == num_cells {
+                    break;
+                }
+            }
+            DataType::I16 => {
+                bor.seek(0);
+                for _ in 0..num_values {
+                    data.push(bor.read_i16()? as f64);
+                }
+                if data.len() == num_cells {
+                    break;
+                }
+            }
+            DataType::U8 => {
+                bor.seek(0);
+                for _ in 0..num_values {
+                    data.push(bor.read_u8()? as f64);
+                }
+                if data.len() == num_cells {
+                    break;
+                }
+            }
+            DataType::RGBA32 => {
+                bor.seek(0);
+                for _ in 0..num_values {
+                    data.push(bor.read_f32()? as i32 as u32 as f64);
+                }
+                if data.len() == num_cells {
+                    break;
+                }
+            }
+            _ => {
+                return Err(Error::new(
+                    ErrorKind::NotFound,
+                    "Raster data type is unknown.",
+                ));
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Reads the header (.dep) file of a Whitebox raster.
+pub(crate) fn read_whitebox_header(file_name: &String, configs: &mut RasterConfigs) -> Result<(), Error> {
     // let header_file = file_name.replace(".tas", ".dep");
     let header_file = Path::new(&file_name)
         .with_extension("dep")
@@ -26,16 +147,39 @@ pub fn read_whitebox(
     let f = BufReader::new(f);
 
     for line in f.lines() {
-        let line_unwrapped = line.unwrap();
+        let line_unwrapped = line?;
         // println!("{}", line_unwrapped);
         let line_split = line_unwrapped.split(":");
         let vec = line_split.collect::<Vec<&str>>();
-        if vec[0].to_lowercase().contains("rows") {
+        if vec[0].to_lowercase().contains("palette entry") {
+            // e.g. Palette Entry:	3	255,0,0,255
+            let fields = vec[1].trim().split('\t').collect::<Vec<&str>>();
+            if fields.len() >= 2 {
+                if let (Ok(value), Some(colour)) =
+                    (fields[0].trim().parse::<i64>(), parse_colour(fields[1]))
+                {
+                    configs.colour_table.set_colour(value, colour);
+                }
+            }
+        } else if vec[0].to_lowercase().contains("category") {
+            // e.g. Category:	3	1024	255,0,0,255	Water; the name may contain colons.
+            let text = line_unwrapped.splitn(2, ':').nth(1).unwrap_or("");
+            let fields = text.trim_start().splitn(4, '\t').collect::<Vec<&str>>();
+            if let Ok(value) = fields[0].trim().parse::<i64>() {
+                let name = fields.get(3).map(|n| n.trim()).unwrap_or("");
+                let entry = configs.attribute_table.add_entry(value, name);
+                entry.count = fields
+                    .get(1)
+                    .and_then(|c| c.trim().parse::<usize>().ok())
+                    .unwrap_or(0);
+                entry.colour = fields.get(2).and_then(|c| parse_colour(c));
+            }
+        } else if vec[0].to_lowercase().contains("rows") {
             configs.rows = vec[1].trim().parse::<f32>().unwrap() as usize;
         } else if vec[0].to_lowercase().contains("col") {
             configs.columns = vec[1].trim().parse::<f32>().unwrap() as usize;
         } else if vec[0].to_lowercase().contains("stacks") {
-            configs.bands = vec[1].trim().to_string().parse::<u8>().unwrap();
+            configs.bands = vec[1].trim().to_string().parse::<usize>().unwrap().max(1);
         } else if vec[0].to_lowercase().contains("north") {
             configs.north = vec[1].trim().to_string().parse::<f64>().unwrap();
         } else if vec[0].to_lowercase().contains("south") {
@@ -117,131 +261,95 @@ pub fn read_whitebox(
     configs.resolution_x = (configs.east - configs.west) / configs.columns as f64;
     configs.resolution_y = (configs.north - configs.south) / configs.rows as f64;
 
-    // read the data file
-    // let data_file = file_name.replace(".dep", ".tas");
-    let data_file = Path::new(&file_name)
-        .with_extension("tas")
-        .into_os_string()
-        .into_string()
-        .unwrap();
-    let mut f = File::open(data_file.clone())?;
-    //let br = BufReader::new(f);
-    // let metadata = fs::metadata(data_file.clone())?;
-    // let file_size: usize = metadata.len() as usize;
-    // let mut buffer = vec![0; file_size];
-
-    let data_size = if configs.data_type == DataType::F64 {
-        8
-    } else if configs.data_type == DataType::F32
-        || configs.data_type == DataType::I32
-        || configs.data_type == DataType::RGBA32
-    {
-        4
-    } else if configs.data_type == DataType::I16 {
-        2
-    } else {
-        // DataType::Byte
-        1
-    };
-
-    data.reserve(configs.rows * configs.columns);
-
-    let num_cells = configs.rows * configs.columns;
-    let buf_size = if num_cells > 10_000_000usize {
-        10_000_000usize
-    } else {
-        num_cells
-    };
-    while data.len() < num_cells {
-        // let mut buffer = vec![0u8; buf_size * data_size];
-        let mut buffer = vec![];
-        buffer.reserve_exact(buf_size * data_size);
-        unsafe {
-            buffer.set_len(buf_size * data_size);
-        }
+    Ok(())
+}
 
-        f.read(&mut buffer)?;
+pub fn write_whitebox<'a>(r: &'a mut Raster) -> Result<(), Error> {
+    write_whitebox_header(r)?;
 
-        let num_values = if data.len() + buf_size <= num_cells {
-            buf_size
-        } else {
-            num_cells - data.len() + 1
-        };
+    // write the data file
+    // let data_file = r.file_name.replace(".dep", ".tas");
+    let data_file = get_data_file_name(&r.file_name, "dep", "tas");
+    let f = File::create(&data_file)?;
+    let mut writer = BufWriter::new(f);
 
-        // read the file's bytes into a buffer
-        let mut bor = ByteOrderReader::<Cursor<Vec<u8>>>::new(Cursor::new(buffer), configs.endian);
+    // let mut u16_bytes: [u8; 2];
+    let mut u32_bytes: [u8; 4];
+    let mut u64_bytes: [u8; 8];
 
-        match configs.data_type {
-            DataType::F64 => {
-                bor.seek(0);
-                for _ in 0..num_values {
-                    data.push(bor.read_f64()? as f64);
+    let num_cells: usize = r.configs.rows * r.configs.columns * r.configs.bands;
+    match r.configs.data_type {
+        DataType::F64 | DataType::U32 => {
+            if r.configs.photometric_interp != PhotometricInterpretation::RGB {
+                for i in 0..num_cells {
+                    u64_bytes = unsafe { mem::transmute(r.get_cell(i)) };
+                    writer.write(&u64_bytes)?;
                 }
-                if data.len() == num_cells {
-                    break;
+            } else {
+                for i in 0..num_cells {
+                    u32_bytes = unsafe { mem::transmute(r.get_cell(i) as u32) };
+                    writer.write(&u32_bytes)?;
                 }
             }
-            DataType::F32 => {
-                bor.seek(0);
-                for _ in 0..num_values {
-                    data.push(bor.read_f32()? as f64);
-                }
-                if data.len() == num_cells {
-                    break;
-                }
+        }
+        DataType::F32 | DataType::U16 => {
+            for i in 0..num_cells {
+                writer.write_f32::<LittleEndian>(r.get_cell(i) as f32)?;
             }
-            DataType::I32 => {
-                bor.seek(0);
-                for _ in 0..num_values {
-                    data.push(bor.read_i32()? as f64);
-                }
-                if data.len() == num_cells {
-                    break;
-                }
+        }
+        DataType::I32 => {
+            for i in 0..num_cells {
+                writer.write_f32::<LittleEndian>(r.get_cell(i) as f32)?;
             }
-            DataType::I16 => {
-                bor.seek(0);
-                for _ in 0..num_values {
-                    data.push(bor.read_i16()? as f64);
-                }
-                if data.len() == num_cells {
-                    break;
-                }
+        }
+        DataType::RGBA32 => {
+            for i in 0..num_cells {
+                u32_bytes = unsafe { mem::transmute(r.get_cell(i) as u32 as i32 as f32) };
+                writer.write(&u32_bytes)?;
             }
-            DataType::U8 => {
-                bor.seek(0);
-                for _ in 0..num_values {
-                    data.push(bor.read_u8()? as f64);
-                }
-                if data.len() == num_cells {
-                    break;
-                }
+        }
+        DataType::RGB24 => {
+            // The Whitebox raster format doesn't really support a 24-bit RGB;
+            // instead use a 32-bit RGBa with saturated alpha channel.
+            let mut val: u32;
+            let alpha_mask = (255 << 24) as u32;
+            for i in 0..num_cells {
+                val = alpha_mask | (r.get_cell(i) as u32);
+                u32_bytes = unsafe { mem::transmute(val) };
+                writer.write(&u32_bytes)?;
             }
-            DataType::RGBA32 => {
-                bor.seek(0);
-                for _ in 0..num_values {
-                    data.push(bor.read_f32()? as i32 as u32 as f64);
-                }
-                if data.len() == num_cells {
-                    break;
-                }
+        }
+        DataType::I16 => {
+            for i in 0..num_cells {
+                // u16_bytes = unsafe { mem::transmute(r.get_cell(i) as u16) };
+                // writer.write(&u16_bytes)?;
+                writer.write_i16::<LittleEndian>(r.get_cell(i) as i16)?;
             }
-            _ => {
-                return Err(Error::new(
-                    ErrorKind::NotFound,
-                    "Raster data type is unknown.",
-                ));
+        }
+        DataType::U8 | DataType::I8 => {
+            for i in 0..num_cells {
+                writer.write(&[r.get_cell(i) as u8])?;
             }
         }
+        _ => {
+            return Err(Error::new(
+                ErrorKind::NotFound,
+                "Raster data type is unknown.",
+            ));
+        }
     }
 
+    writer.flush()?;
+
     Ok(())
 }
 
-pub fn write_whitebox<'a>(r: &'a mut Raster) -> Result<(), Error> {
+/// Writes the header (.dep) file of a Whitebox raster, updating the range of values.
+pub(crate) fn write_whitebox_header(r: &mut Raster) -> Result<(), Error> {
     // figure out the minimum and maximum values
-    for val in &r.data {
-        let v = *val;
+    let num_cells: usize = r.configs.rows * r.configs.columns;
+    for i in 0..num_cells {
+        let v = r.get_cell(i);
         if v != r.configs.nodata {
             if v < r.configs.minimum {
                 r.configs.minimum = v;
@@ -410,85 +518,41 @@ pub fn write_whitebox<'a>(r: &'a mut Raster) -> Result<(), Error> {
         writer.write_all(s.as_bytes())?;
     }
 
-    let _ = writer.flush();
-
-    // write the data file
-    // let data_file = r.file_name.replace(".dep", ".tas");
-    let data_file = Path::new(&r.file_name)
-        .with_extension("tas")
-        .into_os_string()
-        .into_string()
-        .unwrap();
-    let f = File::create(&data_file)?;
-    let mut writer = BufWriter::new(f);
-
-    // let mut u16_bytes: [u8; 2];
-    let mut u32_bytes: [u8; 4];
-    let mut u64_bytes: [u8; 8];
+    for (value, (red, green, blue, a)) in r.configs.colour_table.iter() {
+        let s = format!("Palette Entry:\t{}\t{},{},{},{}\n", value, red, green, blue, a);
+        writer.write_all(s.as_bytes())?;
+    }
 
-    let num_cells: usize = r.configs.rows * r.configs.columns;
-    match r.configs.data_type {
-        DataType::F64 | DataType::U32 => {
-            if r.configs.photometric_interp != PhotometricInterpretation::RGB {
-                for i in 0..num_cells {
-                    u64_bytes = unsafe { mem::transmute(r.data[i]) };
-                    writer.write(&u64_bytes)?;
-                }
-            } else {
-                for i in 0..num_cells {
-                    u32_bytes = unsafe { mem::transmute(r.data[i] as u32) };
-                    writer.write(&u32_bytes)?;
-                }
-            }
-        }
-        DataType::F32 | DataType::U16 => {
-            for i in 0..num_cells {
-                writer.write_f32::<LittleEndian>(r.data[i] as f32)?;
-            }
-        }
-        DataType::I32 => {
-            for i in 0..num_cells {
-                writer.write_f32::<LittleEndian>(r.data[i] as f32)?;
-            }
-        }
-        DataType::RGBA32 => {
-            for i in 0..num_cells {
-                u32_bytes = unsafe { mem::transmute(r.data[i] as u32 as i32 as f32) };
-                writer.write(&u32_bytes)?;
-            }
-        }
-        DataType::RGB24 => {
-            // The Whitebox raster format doesn't really support a 24-bit RGB;
-            // instead use a 32-bit RGBa with saturated alpha channel.
-            let mut val: u32;
-            let alpha_mask = (255 << 24) as u32;
-            for i in 0..num_cells {
-                val = alpha_mask | (r.data[i] as u32);
-                u32_bytes = unsafe { mem::transmute(val) };
-                writer.write(&u32_bytes)?;
-            }
-        }
-        DataType::I16 => {
-            for i in 0..num_cells {
-                // u16_bytes = unsafe { mem::transmute(r.data[i] as u16) };
-                // writer.write(&u16_bytes)?;
-                writer.write_i16::<LittleEndian>(r.data[i] as i16)?;
-            }
-        }
-        DataType::U8 | DataType::I8 => {
-            for i in 0..num_cells {
-                writer.write(&[r.data[i] as u8])?;
-            }
-        }
-        _ => {
-            return Err(Error::new(
-                ErrorKind::NotFound,
-                "Raster data type is unknown.",
-            ));
-        }
+    for entry in r.configs.attribute_table.entries() {
+        let colour = match entry.colour {
+            Some((red, green, blue, a)) => format!("{},{},{},{}", red, green, blue, a),
+            None => String::new(),
+        };
+        let s = format!(
+            "Category:\t{}\t{}\t{}\t{}\n",
+            entry.value,
+            entry.count,
+            colour,
+            entry.name.replace(|c| c == '\n' || c == '\r', " ")
+        );
+        writer.write_all(s.as_bytes())?;
     }
 
-    let _ = writer.flush();
+    writer.flush()?;
 
     Ok(())
 }
+
+/// Parses a colour given as its comma-separated red, green, blue, and alpha components.
+fn parse_colour(text: &str) -> Option<Colour> {
+    let components = text
+        .split(',')
+        .map(|c| c.trim().parse::<u8>())
+        .collect::<Result<Vec<u8>, _>>()
+        .ok()?;
+    match components.len() {
+        3 => Some((components[0], components[1], components[2], 255)),
+        4 => Some((components[0], components[1], components[2], components[3])),
+        _ => None,
+    }
+}
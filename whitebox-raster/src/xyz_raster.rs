@@ -0,0 +1,218 @@
+use super::*;
+use std::f64;
+use std::fs::File;
+use std::io::prelude::*;
+use std::io::BufReader;
+use std::io::BufWriter;
+use std::io::{Error, ErrorKind};
+
+/// Splits a line of an XYZ file into its fields, which may be separated by spaces, tabs,
+/// commas, or semicolons.
+pub(crate) fn split_xyz_line(line: &str) -> Vec<&str> {
+    line.split(|c: char| c == ',' || c == ';' || c.is_whitespace())
+        .filter(|s| !s.is_empty())
+        .collect()
+}
+
+/// Reads an XYZ grid, a text file listing the x and y coordinates of the centre of each
+/// grid cell and its value, one cell per line. The points may be in any order, but must
+/// lie on a regular grid; cells that are not listed are NoData. A header line naming
+/// the columns, e.g. `X,Y,Z`, is allowed.
+pub fn read_xyz(
+    file_name: &String,
+    configs: &mut RasterConfigs,
+    data: &mut Vec<f64>,
+) -> Result<(), Error> {
+    // read the file
+    let f = File::open(file_name)?;
+    let f = BufReader::new(f);
+
+    let mut points: Vec<(f64, f64, f64)> = vec![];
+    let mut likely_float = false;
+    for (line_num, line) in f.lines().enumerate() {
+        let line_unwrapped = line?;
+        let line_trimmed = line_unwrapped.trim();
+        if line_trimmed.is_empty() || line_trimmed.starts_with('#') {
+            continue;
+        }
+        let vec = split_xyz_line(line_trimmed);
+        let xyz = if vec.len() >= 3 {
+            match (
+                vec[0].parse::<f64>(),
+                vec[1].parse::<f64>(),
+                vec[2].parse::<f64>(),
+            ) {
+                (Ok(x), Ok(y), Ok(z)) => Some((x, y, z)),
+                _ => None,
+            }
+        } else {
+            None
+        };
+        match xyz {
+            Some(p) => {
+                if vec[2].contains('.') || vec[2].contains('e') || vec[2].contains('E') {
+                    likely_float = true;
+                }
+                points.push(p);
+            }
+            None if points.is_empty() => {
+                // a header line
+            }
+            None => {
+                return Err(Error::new(
+                    ErrorKind::InvalidData,
+                    format!(
+                        "Line {} of the XYZ file does not contain x, y, and z values.",
+                        line_num + 1
+                    ),
+                ))
+            }
+        }
+    }
+    if points.is_empty() {
+        return Err(Error::new(
+            ErrorKind::InvalidData,
+            "The XYZ file does not contain any points.",
+        ));
+    }
+
+    // The grid resolution is given by the spacing between distinct coordinates.
+    let (min_x, max_x, res_x) = grid_spacing(points.iter().map(|p| p.0).collect());
+    let (min_y, max_y, res_y) = grid_spacing(points.iter().map(|p| p.1).collect());
+    let (res_x, res_y) = match (res_x, res_y) {
+        (Some(rx), Some(ry)) => (rx, ry),
+        (Some(rx), None) => (rx, rx),
+        (None, Some(ry)) => (ry, ry),
+        (None, None) => (1f64, 1f64),
+    };
+
+    configs.columns = ((max_x - min_x) / res_x).round() as usize + 1;
+    configs.rows = ((max_y - min_y) / res_y).round() as usize + 1;
+    configs.resolution_x = res_x;
+    configs.resolution_y = res_y;
+    configs.west = min_x - res_x / 2f64;
+    configs.east = configs.west + res_x * configs.columns as f64;
+    configs.north = max_y + res_y / 2f64;
+    configs.south = configs.north - res_y * configs.rows as f64;
+    configs.nodata = -32768f64;
+    configs.data_type = if likely_float {
+        DataType::F32
+    } else {
+        DataType::I32
+    };
+    configs.photometric_interp = PhotometricInterpretation::Continuous;
+
+    data.clear();
+    data.resize(configs.rows * configs.columns, configs.nodata);
+    for (x, y, z) in points {
+        let col = (x - min_x) / res_x;
+        let row = (max_y - y) / res_y;
+        if (col - col.round()).abs() > 0.01 || (row - row.round()).abs() > 0.01 {
+            return Err(Error::new(
+                ErrorKind::InvalidData,
+                format!(
+                    "The point ({}, {}) of the XYZ file does not lie on a regular grid.",
+                    x, y
+                ),
+            ));
+        }
+        data[row.round() as usize * configs.columns + col.round() as usize] = z;
+    }
+
+    Ok(())
+}
+
+/// Returns the range of a set of coordinates and the spacing of the grid that they lie on,
+/// if there is more than one distinct value.
+fn grid_spacing(mut values: Vec<f64>) -> (f64, f64, Option<f64>) {
+    values.sort_by(|a, b| a.partial_cmp(b).unwrap_or(Equal));
+    let min = values[0];
+    let max = values[values.len() - 1];
+    // Coordinates that differ only by rounding error are the same row or column.
+    let tolerance = 1e-9 * max.abs().max(min.abs()).max(1f64);
+    let spacing = values
+        .windows(2)
+        .map(|w| w[1] - w[0])
+        .filter(|&d| d > tolerance)
+        .fold(None, |spacing: Option<f64>, d| {
+            Some(spacing.map_or(d, |s| s.min(d)))
+        })
+        // The range spans a whole number of cells, which is less affected by rounding.
+        .map(|d| (max - min) / ((max - min) / d).round());
+    (min, max, spacing)
+}
+
+/// Writes the raster as an XYZ grid, listing the centre of each cell from the top row to
+/// the bottom row. NoData cells are omitted.
+pub fn write_xyz<'a>(r: &'a mut Raster) -> Result<(), Error> {
+    let f = File::create(&r.file_name)?;
+    let mut writer = BufWriter::new(f);
+
+    let nodata = r.configs.nodata;
+    let columns = r.configs.columns;
+    let mut value: f64;
+    for row in 0..r.configs.rows {
+        let y = r.get_y_from_row(row as isize);
+        for col in 0..columns {
+            value = r.get_cell(row * columns + col);
+            if value != nodata {
+                let x = r.get_x_from_column(col as isize);
+                writer.write_all(format!("{} {} {}\n", x, y, value).as_bytes())?;
+            }
+        }
+    }
+
+    writer.flush()?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use crate::test_utils::*;
+    use crate::*;
+
+    #[test]
+    fn xyz_round_trip() {
+        // Cells that are NoData are omitted, so those of the outer rows and columns are
+        // given values to keep the extent of the grid.
+        let file_name = temp_file("grid.xyz");
+        for data_type in [DataType::I32, DataType::F32] {
+            let mut output = sample_raster(&file_name, 4, 6, data_type);
+            for row in 0..4 {
+                output.set_value(row, 0, 7.0);
+            }
+            output.set_value(2, 3, output.configs.nodata);
+            output.write().unwrap();
+
+            let input = Raster::new(&file_name, "r").unwrap();
+            assert_eq!(input.configs.data_type, data_type);
+            assert_same_extent(&output, &input);
+            assert_same_cells(&output, &input);
+        }
+        std::fs::remove_file(&file_name).unwrap();
+    }
+
+    #[test]
+    fn read_unordered_xyz() {
+        // The points may be in any order and separated by commas, and missing cells are NoData.
+        let file_name = temp_file("unordered.xyz");
+        std::fs::write(
+            &file_name,
+            "X,Y,Z\n# a comment\n20.5,10,3\n0.5,30,1\n20.5,20,6\n10.5,30,2\n0.5,10,4.5\n",
+        )
+        .unwrap();
+        let input = Raster::new(&file_name, "r").unwrap();
+        assert_eq!((input.configs.rows, input.configs.columns), (3, 3));
+        assert_eq!((input.configs.resolution_x, input.configs.resolution_y), (10.0, 10.0));
+        assert_eq!((input.configs.west, input.configs.north), (-4.5, 35.0));
+        assert_eq!(input.configs.data_type, DataType::F32);
+        assert_eq!(input.get_value(0, 0), 1.0);
+        assert_eq!(input.get_value(0, 1), 2.0);
+        assert_eq!(input.get_value(2, 0), 4.5);
+        assert_eq!(input.get_value(1, 2), 6.0);
+        assert_eq!(input.get_value(2, 2), 3.0);
+        assert_eq!(input.get_value(1, 1), input.configs.nodata);
+        std::fs::remove_file(&file_name).unwrap();
+    }
+}
@@ -311,6 +311,124 @@ fn run() -> Result<(), Error> {
                 configs.max_procs = val;
                 configs_modified = true;
             }
+        } else if arg.starts_with("-max_memory") || arg.starts_with("--max_memory") {
+            let mut v = arg
+                .replace("--max_memory", "")
+                .replace("-max_memory", "")
+                .replace("\"", "")
+                .replace("\'", "");
+            if v.starts_with("=") {
+                v = v[1..v.len()].to_string();
+            }
+            let val = v.parse::<isize>().expect(&format!("Error parsing {}", v));
+            if val != configs.max_memory {
+                configs.max_memory = val;
+                configs_modified = true;
+            }
+        } else if arg.starts_with("-compression_level") || arg.starts_with("--compression_level") {
+            let mut v = arg
+                .replace("--compression_level", "")
+                .replace("-compression_level", "")
+                .replace("\"", "")
+                .replace("\'", "");
+            if v.starts_with("=") {
+                v = v[1..v.len()].to_string();
+            }
+            let val = v.parse::<isize>().expect(&format!("Error parsing {}", v));
+            if val != configs.compression_level {
+                configs.compression_level = val;
+                configs_modified = true;
+            }
+        } else if arg.starts_with("-compression") || arg.starts_with("--compression") {
+            let mut v = arg
+                .replace("--compression", "")
+                .replace("-compression", "")
+                .replace("\"", "")
+                .replace("\'", "");
+            if v.starts_with("=") {
+                v = v[1..v.len()].to_string();
+            }
+            let val = v.to_lowercase();
+            if val != configs.compression {
+                configs.compression = val;
+                configs_modified = true;
+            }
+        } else if arg.starts_with("-predictor") || arg.starts_with("--predictor") {
+            let mut v = arg
+                .replace("--predictor", "")
+                .replace("-predictor", "")
+                .replace("\"", "")
+                .replace("\'", "");
+            if v.starts_with("=") {
+                v = v[1..v.len()].to_string();
+            }
+            let val = v.parse::<usize>().expect(&format!("Error parsing {}", v));
+            if val != configs.predictor {
+                configs.predictor = val;
+                configs_modified = true;
+            }
+        } else if arg.starts_with("-cog_tile_size") || arg.starts_with("--cog_tile_size") {
+            let mut v = arg
+                .replace("--cog_tile_size", "")
+                .replace("-cog_tile_size", "")
+                .replace("\"", "")
+                .replace("\'", "");
+            if v.starts_with("=") {
+                v = v[1..v.len()].to_string();
+            }
+            let val = v.parse::<usize>().expect(&format!("Error parsing {}", v));
+            if val != configs.cog_tile_size {
+                configs.cog_tile_size = val;
+                configs_modified = true;
+            }
+        } else if arg.starts_with("-cog_overviews") || arg.starts_with("--cog_overviews") {
+            let mut v = arg
+                .replace("--cog_overviews", "")
+                .replace("-cog_overviews", "")
+                .replace("\"", "")
+                .replace("\'", "");
+            if v.starts_with("=") {
+                v = v[1..v.len()].to_string();
+            }
+            let val = v.parse::<isize>().expect(&format!("Error parsing {}", v));
+            if val != configs.cog_overview_levels {
+                configs.cog_overview_levels = val;
+                configs_modified = true;
+            }
+        } else if arg.starts_with("-cog_resampling") || arg.starts_with("--cog_resampling") {
+            let mut v = arg
+                .replace("--cog_resampling", "")
+                .replace("-cog_resampling", "")
+                .replace("\"", "")
+                .replace("\'", "");
+            if v.starts_with("=") {
+                v = v[1..v.len()].to_string();
+            }
+            let val = v.to_lowercase();
+            if val != configs.cog_resampling {
+                configs.cog_resampling = val;
+                configs_modified = true;
+            }
+        } else if arg.starts_with("-cog") || arg.starts_with("--cog") {
+            let mut v = arg
+                .replace("--cog", "")
+                .replace("-cog", "")
+                .replace("\"", "")
+                .replace("\'", "");
+            if v.starts_with("=") {
+                v = v[1..v.len()].to_string();
+            }
+            if v.to_lowercase().contains("t") || v.is_empty() {
+                if !configs.cog { // update value
+                    configs.cog = true;
+                    configs_modified = true;
+                }
+            } else {
+                if configs.cog { // update value
+                    configs.cog = false;
+                    configs_modified = true;
+                }
+            }
         } else if arg.starts_with("-version") || arg.starts_with("--version") {
             version();
             return Ok(());
@@ -408,11 +526,19 @@ fn help() {
 
 The following commands are recognized:
 --cd, --wd          Changes the working directory; used in conjunction with --run flag.
+--cog               Sets the cog option in the settings.json file; determines if GeoTIFF outputs are written as Cloud-Optimized GeoTIFFs. e.g. --cog=true
+--cog_overviews     Sets the number of internal overview levels of COG outputs. -1 = enough to fit the image within one tile. e.g. --cog_overviews=4
+--cog_resampling    Sets the resampling method used to build COG overviews; 'nearest', 'average', or 'mode'. e.g. --cog_resampling=average
+--cog_tile_size     Sets the tile width and height (in pixels) of COG outputs. e.g. --cog_tile_size=512
 --compress_rasters  Sets the compress_raster option in the settings.json file; determines if newly created rasters are compressed. e.g. --compress_rasters=true
+--compression       Sets the method used to compress GeoTIFF outputs; 'deflate', 'lzw', 'packbits', or 'zstd'. e.g. --compression=zstd
+--compression_level Sets the DEFLATE (1-9) or ZSTD (1-22) compression level. -1 = the method's default. e.g. --compression_level=9
 -h, --help          Prints help information.
 -l, --license       Prints the whitebox-tools license. Tool names may also be used, --license=\"Slope\"
 --listtools         Lists all available tools. Keywords may also be used, --listtools slope.
+--max_memory        Sets the memory budget (in MB) of a single raster; larger rasters are read from disk as needed. -1 = no limit. e.g. --max_memory=4096
 --max_procs         Sets the maximum number of processors used. -1 = all available processors. e.g. --max_procs=2
+--predictor         Sets the predictor applied to compressed GeoTIFF outputs; 1 = none, 2 = horizontal, 3 = floating-point. e.g. --predictor=3
 -r, --run           Runs a tool; used in conjunction with --wd flag; -r=\"LidarInfo\".
 --toolbox           Prints the toolbox associated with a tool; --toolbox=Slope.
 --toolhelp          Prints the help associated with a tool; --toolhelp=\"LidarInfo\".
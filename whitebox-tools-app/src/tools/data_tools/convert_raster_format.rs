@@ -184,6 +184,8 @@ impl WhiteboxTool for ConvertRasterFormat {
             Ok(_) => (), // do nothings
             Err(err) => return Err(err),
         }
+        output.configs.colour_table = input.configs.colour_table.clone();
+        output.configs.attribute_table = input.configs.attribute_table.clone();
         drop(input);
 
         let elapsed_time = get_formatted_elapsed_time(start);
@@ -203,7 +205,7 @@ impl WhiteboxTool for ConvertRasterFormat {
                     println!("Output file written")
                 }
             }
-            Err(e) => return Err(e),
+            Err(e) => return Err(e.into()),
         };
 
         if verbose {
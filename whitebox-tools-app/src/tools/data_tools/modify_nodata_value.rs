@@ -207,7 +207,7 @@ impl WhiteboxTool for ModifyNoDataValue {
                     println!("File written")
                 }
             }
-            Err(e) => return Err(e),
+            Err(e) => return Err(e.into()),
         };
 
         if verbose {
@@ -12,7 +12,7 @@ use std::env;
 use std::f64;
 use std::io::{Error, ErrorKind};
 use std::path;
-use whitebox_vector::Shapefile;
+use whitebox_vector::{is_vector_file, Shapefile};
 
 /// This tool can be used to create a new raster with the same coordinates and dimensions
 /// (i.e. rows and columns) as an existing base image, or the same spatial extent as an input
@@ -239,9 +239,7 @@ impl WhiteboxTool for NewRasterFromBase {
         }
 
         // Get the spatial extent
-        let mut output = if base_file.to_lowercase().ends_with(".shp") {
-            // Note that this only works because at the moment, Shapefiles are the only supported vector.
-            // If additional vector formats are added in the future, this will need updating.
+        let mut output = if is_vector_file(&base_file) {
 
             if cell_size <= 0f64 {
                 return Err(Error::new(
@@ -313,7 +311,7 @@ impl WhiteboxTool for NewRasterFromBase {
                     println!("Output file written")
                 }
             }
-            Err(e) => return Err(e),
+            Err(e) => return Err(e.into()),
         };
 
         if verbose {
@@ -270,7 +270,7 @@ impl WhiteboxTool for SetNodataValue {
                     println!("Output file written")
                 }
             }
-            Err(e) => return Err(e),
+            Err(e) => return Err(e.into()),
         };
 
         if verbose {
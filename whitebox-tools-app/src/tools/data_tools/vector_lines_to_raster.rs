@@ -363,9 +363,9 @@ impl WhiteboxTool for VectorLinesToRaster {
                     FieldData::Int(val) => {
                         attribute_data[record_num] = val as f64;
                     }
-                    // FieldData::Int64(val) => {
-                    //     attribute_data[record_num] = val as f64;
-                    // },
+                    FieldData::BigInt(val) => {
+                        attribute_data[record_num] = val as f64;
+                    }
                     FieldData::Real(val) => {
                         attribute_data[record_num] = val;
                     }
@@ -547,7 +547,7 @@ impl WhiteboxTool for VectorLinesToRaster {
                     println!("Output file written")
                 }
             }
-            Err(e) => return Err(e),
+            Err(e) => return Err(e.into()),
         };
 
         if !output_something && verbose {
@@ -371,9 +371,9 @@ impl WhiteboxTool for VectorPointsToRaster {
                     FieldData::Int(val) => {
                         attribute_data[record_num] = val as f64;
                     }
-                    // FieldData::Int64(val) => {
-                    //     attribute_data[record_num] = val as f64;
-                    // },
+                    FieldData::BigInt(val) => {
+                        attribute_data[record_num] = val as f64;
+                    }
                     FieldData::Real(val) => {
                         attribute_data[record_num] = val;
                     }
@@ -547,7 +547,7 @@ impl WhiteboxTool for VectorPointsToRaster {
                     println!("Output file written")
                 }
             }
-            Err(e) => return Err(e),
+            Err(e) => return Err(e.into()),
         };
 
         if verbose {
@@ -286,6 +286,7 @@ impl WhiteboxTool for VectorPolygonsToRaster {
             for record_num in 0..vector_data.num_records {
                 key = match vector_data.attributes.get_value(record_num, &field_name) {
                     FieldData::Int(val) => val.to_string(),
+                    FieldData::BigInt(val) => val.to_string(),
                     FieldData::Real(val) => val.to_string(),
                     FieldData::Text(val) => val.to_string(),
                     FieldData::Date(val) => val.to_string(),
@@ -358,6 +359,9 @@ impl WhiteboxTool for VectorPolygonsToRaster {
                     FieldData::Int(val) => {
                         attribute_data[record_num] = val as f64;
                     }
+                    FieldData::BigInt(val) => {
+                        attribute_data[record_num] = val as f64;
+                    }
                     FieldData::Real(val) => {
                         attribute_data[record_num] = val;
                     }
@@ -619,7 +623,7 @@ impl WhiteboxTool for VectorPolygonsToRaster {
                     println!("Output file written")
                 }
             }
-            Err(e) => return Err(e),
+            Err(e) => return Err(e.into()),
         };
 
         if !output_something && verbose {
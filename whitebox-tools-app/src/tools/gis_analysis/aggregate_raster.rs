@@ -540,7 +540,7 @@ impl WhiteboxTool for AggregateRaster {
                     println!("Output file written")
                 }
             }
-            Err(e) => return Err(e),
+            Err(e) => return Err(e.into()),
         };
 
         if verbose {
@@ -7,6 +7,7 @@ License: MIT
 */
 
 use whitebox_raster::*;
+use whitebox_common::spatial_ref_system::CrsCheck;
 use whitebox_common::structures::Array2D;
 use crate::tools::*;
 use std::env;
@@ -201,6 +202,7 @@ impl WhiteboxTool for AverageOverlay {
         let mut z: f64;
         let mut read_first_file = false;
         let mut i = 1;
+        let mut crs_check = CrsCheck::new();
         for value in vec {
             if !value.trim().is_empty() {
                 if verbose {
@@ -212,6 +214,12 @@ impl WhiteboxTool for AverageOverlay {
                     input_file = format!("{}{}", working_directory, input_file);
                 }
                 let input = Raster::new(&input_file, "r")?;
+                if let Some(warning) = crs_check.check(
+                    &input.get_short_filename(),
+                    input.get_coordinate_reference_system(),
+                ) {
+                    println!("Warning: {}", warning);
+                }
                 in_nodata = input.configs.nodata;
                 if !read_first_file {
                     read_first_file = true;
@@ -232,13 +240,13 @@ impl WhiteboxTool for AverageOverlay {
 
                 for row in 0..rows {
                     for col in 0..columns {
-                        z = input[(row, col)];
+                        z = input.get_value(row, col);
                         if z != in_nodata {
-                            if output[(row, col)] != out_nodata {
+                            if output.get_value(row, col) != out_nodata {
                                 output.increment(row, col, z);
                                 n.increment(row, col, 1i16);
                             } else {
-                                output[(row, col)] = z;
+                                output.set_value(row, col, z);
                                 n[(row, col)] = 1i16;
                             }
                         }
@@ -257,12 +265,12 @@ impl WhiteboxTool for AverageOverlay {
 
         for row in 0..rows {
             for col in 0..columns {
-                z = output[(row, col)];
+                z = output.get_value(row, col);
                 if z != out_nodata {
                     if n[(row, col)] > 0i16 {
-                        output[(row, col)] = z / n[(row, col)] as f64;
+                        output.set_value(row, col, z / n[(row, col)] as f64);
                     } else {
-                        output[(row, col)] = 0.0f64;
+                        output.set_value(row, col, 0.0f64);
                     }
                 }
             }
@@ -296,7 +304,7 @@ impl WhiteboxTool for AverageOverlay {
                     println!("Output file written")
                 }
             }
-            Err(e) => return Err(e),
+            Err(e) => return Err(e.into()),
         };
 
         if verbose {
@@ -337,6 +337,7 @@ impl WhiteboxTool for BlockMaximumGridding {
                 y = record.points[0].y;
                 z = match vector_data.attributes.get_value(record_num, &field_name) {
                     FieldData::Int(val) => val as f64,
+                    FieldData::BigInt(val) => val as f64,
                     FieldData::Real(val) => val,
                     _ => nodata,
                 };
@@ -418,7 +419,7 @@ impl WhiteboxTool for BlockMaximumGridding {
                     println!("Output file written")
                 }
             }
-            Err(e) => return Err(e),
+            Err(e) => return Err(e.into()),
         };
 
         if verbose {
@@ -218,9 +218,9 @@ impl WhiteboxTool for BoundaryShapeComplexity {
                 for row in (0..rows).filter(|r| r % num_procs == tid) {
                     let mut data: Vec<f64> = vec![nodata; columns as usize];
                     for col in 0..columns {
-                        if input[(row, col)] > 0.0 && input[(row, col)] != nodata {
+                        if input.get_value(row, col) > 0.0 && input.get_value(row, col) != nodata {
                             data[col as usize] = 1.0;
-                        } else if input[(row, col)] == 0.0 {
+                        } else if input.get_value(row, col) == 0.0 {
                             data[col as usize] = 0.0;
                         }
                     }
@@ -284,11 +284,11 @@ impl WhiteboxTool for BoundaryShapeComplexity {
             for a in 0..4 {
                 for row in 0..rows {
                     for col in 0..columns {
-                        z = output[(row, col)];
+                        z = output.get_value(row, col);
                         if z > 0.0 && z != nodata {
                             // fill the neighbours array
                             for i in 0..8 {
-                                neighbours[i] = output[(row + dy[i], col + dx[i])];
+                                neighbours[i] = output.get_value(row + dy[i], col + dx[i]);
                             }
 
                             // scan through element
@@ -300,7 +300,7 @@ impl WhiteboxTool for BoundaryShapeComplexity {
                             }
 
                             if pattern_match {
-                                output[(row, col)] = 0.0;
+                                output.set_value(row, col, 0.0);
                                 did_something = true;
                             } else {
                                 pattern_match = true;
@@ -311,7 +311,7 @@ impl WhiteboxTool for BoundaryShapeComplexity {
                                 }
 
                                 if pattern_match {
-                                    output[(row, col)] = 0.0;
+                                    output.set_value(row, col, 0.0);
                                     did_something = true;
                                 }
                             }
@@ -408,18 +408,18 @@ impl WhiteboxTool for BoundaryShapeComplexity {
         let mut polyid: f64;
         for row in 0..rows {
             for col in 0..columns {
-                z = output[(row, col)];
+                z = output.get_value(row, col);
                 if z > 0f64 {
-                    polyid = input[(row, col)];
+                    polyid = input.get_value(row, col);
                     num_line_thinned_neighbours = 0;
                     for a in 0..8 {
-                        zn = output[(row + dy[a], col + dx[a])];
-                        if zn == 1f64 && input[(row + dy[a], col + dx[a])] == polyid {
+                        zn = output.get_value(row + dy[a], col + dx[a]);
+                        if zn == 1f64 && input.get_value(row + dy[a], col + dx[a]) == polyid {
                             num_line_thinned_neighbours += 1
                         }
                     }
 
-                    bin = (input[(row, col)] - min_val).floor() as usize;
+                    bin = (input.get_value(row, col) - min_val).floor() as usize;
                     num_cells[bin] += 1;
                     if num_line_thinned_neighbours == 1 {
                         num_end_nodes[bin] += 1f64;
@@ -433,8 +433,8 @@ impl WhiteboxTool for BoundaryShapeComplexity {
                             num_line_thinned_neighbours = 0;
                             next_n = 8;
                             for a in 0..8 {
-                                zn = output[(row_n + dy[a], col_n + dx[a])];
-                                if zn == 1f64 && input[(row_n + dy[a], col_n + dx[a])] == polyid {
+                                zn = output.get_value(row_n + dy[a], col_n + dx[a]);
+                                if zn == 1f64 && input.get_value(row_n + dy[a], col_n + dx[a]) == polyid {
                                     num_line_thinned_neighbours += 1;
                                     if visited.get_value(row_n + dy[a], col_n + dx[a]) == 0 {
                                         next_n = a;
@@ -519,12 +519,12 @@ impl WhiteboxTool for BoundaryShapeComplexity {
 
         for row in 0..rows {
             for col in 0..columns {
-                z = input[(row, col)];
+                z = input.get_value(row, col);
                 if z != nodata && z != 0f64 {
                     bin = (z - min_val).floor() as usize;
-                    output[(row, col)] = num_end_nodes[bin];
+                    output.set_value(row, col, num_end_nodes[bin]);
                 } else if z == 0f64 {
-                    output[(row, col)] = 0f64;
+                    output.set_value(row, col, 0f64);
                 }
             }
             if verbose {
@@ -553,7 +553,7 @@ impl WhiteboxTool for BoundaryShapeComplexity {
                     println!("Output file written")
                 }
             }
-            Err(e) => return Err(e),
+            Err(e) => return Err(e.into()),
         };
 
         if verbose {
@@ -257,11 +257,11 @@ impl WhiteboxTool for BufferRaster {
 
         for row in 0..rows {
             for col in 0..columns {
-                z = input[(row, col)];
+                z = input.get_value(row, col);
                 if z != 0.0 {
-                    output[(row, col)] = 0.0;
+                    output.set_value(row, col, 0.0);
                 } else {
-                    output[(row, col)] = inf_val;
+                    output.set_value(row, col, inf_val);
                 }
             }
             if verbose {
@@ -275,14 +275,14 @@ impl WhiteboxTool for BufferRaster {
 
         for row in 0..rows {
             for col in 0..columns {
-                z = output[(row, col)];
+                z = output.get_value(row, col);
                 if z != 0.0 {
                     z_min = inf_val;
                     which_cell = 0;
                     for i in 0..4 {
                         x = col + d_x[i];
                         y = row + d_y[i];
-                        z2 = output[(y, x)];
+                        z2 = output.get_value(y, x);
                         if z2 != nodata {
                             h = match i {
                                 0 => 2.0 * r_x[(y, x)] + 1.0,
@@ -298,7 +298,7 @@ impl WhiteboxTool for BufferRaster {
                         }
                     }
                     if z_min < z {
-                        output[(row, col)] = z_min;
+                        output.set_value(row, col, z_min);
                         x = col + d_x[which_cell];
                         y = row + d_y[which_cell];
                         r_x[(row, col)] = r_x[(y, x)] + g_x[which_cell];
@@ -317,14 +317,14 @@ impl WhiteboxTool for BufferRaster {
 
         for row in (0..rows).rev() {
             for col in (0..columns).rev() {
-                z = output[(row, col)];
+                z = output.get_value(row, col);
                 if z != 0.0 {
                     z_min = inf_val;
                     which_cell = 0;
                     for i in 4..8 {
                         x = col + d_x[i];
                         y = row + d_y[i];
-                        z2 = output[(y, x)];
+                        z2 = output.get_value(y, x);
                         if z2 != nodata {
                             h = match i {
                                 5 => 2.0 * (r_x[(y, x)] + r_y[(y, x)] + 1.0),
@@ -340,7 +340,7 @@ impl WhiteboxTool for BufferRaster {
                         }
                     }
                     if z_min < z {
-                        output[(row, col)] = z_min;
+                        output.set_value(row, col, z_min);
                         x = col + d_x[which_cell];
                         y = row + d_y[which_cell];
                         r_x[(row, col)] = r_x[(y, x)] + g_x[which_cell];
@@ -365,16 +365,16 @@ impl WhiteboxTool for BufferRaster {
         let mut dist: f64;
         for row in 0..rows {
             for col in 0..columns {
-                z = input[(row, col)];
+                z = input.get_value(row, col);
                 if z != nodata {
-                    dist = output[(row, col)].sqrt() * cell_size;
+                    dist = output.get_value(row, col).sqrt() * cell_size;
                     if dist <= buffer_size {
-                        output[(row, col)] = 1.0;
+                        output.set_value(row, col, 1.0);
                     } else {
-                        output[(row, col)] = 0.0;
+                        output.set_value(row, col, 0.0);
                     }
                 } else {
-                    output[(row, col)] = nodata;
+                    output.set_value(row, col, nodata);
                 }
             }
             if verbose {
@@ -407,7 +407,7 @@ impl WhiteboxTool for BufferRaster {
                     println!("Output file written")
                 }
             }
-            Err(e) => return Err(e),
+            Err(e) => return Err(e.into()),
         };
 
         if verbose {
@@ -215,7 +215,7 @@ impl WhiteboxTool for Centroid {
         let mut a: usize;
         for row in 0..rows {
             for col in 0..columns {
-                z = input[(row, col)];
+                z = input.get_value(row, col);
                 if z > 0f64 && z != nodata {
                     a = (z - min_val as f64) as usize;
                     total_columns[a] += col as usize;
@@ -272,7 +272,7 @@ impl WhiteboxTool for Centroid {
                     println!("Output file written")
                 }
             }
-            Err(e) => return Err(e),
+            Err(e) => return Err(e.into()),
         };
 
         if verbose {
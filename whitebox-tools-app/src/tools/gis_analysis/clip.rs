@@ -241,14 +241,19 @@ impl WhiteboxTool for Clip {
             ));
         }
 
-        // Get the bounding boxes of each of the clip parts.
+        // Get the bounding boxes of each of the clip parts, and the range of the parts of
+        // each clip record, so that the parts that may overlap a feature can be found from
+        // the spatial index of the clip records.
         let mut clip_bb: Vec<BoundingBox> = vec![];
         let mut clip_polylines: Vec<Polyline> = vec![];
         let mut is_clip_part_a_hole: Vec<bool> = vec![];
+        let mut clip_parts: Vec<(usize, usize)> = Vec::with_capacity(clip.num_records);
         let mut first_point_in_part: usize;
         let mut last_point_in_part: usize;
         for record_num in 0..clip.num_records {
             let record = clip.get_record(record_num);
+            let first_part = clip_polylines.len();
+            clip_parts.push((first_part, first_part + record.num_parts as usize));
             for part in 0..record.num_parts as usize {
                 first_point_in_part = record.parts[part] as usize;
                 last_point_in_part = if part < record.num_parts as usize - 1 {
@@ -291,6 +296,7 @@ impl WhiteboxTool for Clip {
 
         let clip_bb = Arc::new(clip_bb);
         let is_clip_part_a_hole = Arc::new(is_clip_part_a_hole);
+        let clip_parts = Arc::new(clip_parts);
 
         let num_procs = num_cpus::get();
         let (tx, rx) = mpsc::channel();
@@ -300,6 +306,8 @@ impl WhiteboxTool for Clip {
                 let clip_polylines = Arc::new(clip_polylines);
                 for tid in 0..num_procs {
                     let input = input.clone();
+                    let clip = clip.clone();
+                    let clip_parts = clip_parts.clone();
                     let clip_bb = clip_bb.clone();
                     let clip_polylines = clip_polylines.clone();
                     let is_clip_part_a_hole = is_clip_part_a_hole.clone();
@@ -311,7 +319,7 @@ impl WhiteboxTool for Clip {
                             out = false;
                             let record = input.get_record(record_num);
                             p = record.points[0];
-                            for a in 0..clip_polylines.len() {
+                            for a in get_point_candidate_parts(&clip, &clip_parts, p) {
                                 if clip_bb[a].is_point_in_box(p.x, p.y) {
                                     if point_in_poly(&p, &(clip_polylines[a].vertices)) {
                                         if !is_clip_part_a_hole[a] {
@@ -377,6 +385,8 @@ impl WhiteboxTool for Clip {
                     let num_points = record.num_points as usize;
                     for tid in 0..num_procs {
                         let record = record.clone();
+                        let clip = clip.clone();
+                        let clip_parts = clip_parts.clone();
                         let clip_bb = clip_bb.clone();
                         let clip_polylines = clip_polylines.clone();
                         let is_clip_part_a_hole = is_clip_part_a_hole.clone();
@@ -387,7 +397,7 @@ impl WhiteboxTool for Clip {
                             for point_num in (0..num_points).filter(|r| r % num_procs == tid) {
                                 p = record.points[point_num].clone();
                                 out = false;
-                                for a in 0..clip_polylines.len() {
+                                for a in get_point_candidate_parts(&clip, &clip_parts, p) {
                                     if clip_bb[a].is_point_in_box(p.x, p.y) {
                                         if point_in_poly(&p, &(clip_polylines[a].vertices)) {
                                             if !is_clip_part_a_hole[a] {
@@ -501,7 +511,8 @@ impl WhiteboxTool for Clip {
 
                 // hunt for intersections in the overlapping bounding boxes
                 for record_num1 in 0..features_polylines.len() {
-                    for record_num2 in 0..clip_polylines.len() {
+                    let bb = features_bb[record_num1];
+                    for record_num2 in get_candidate_parts(&clip, &clip_parts, &bb) {
                         if features_bb[record_num1].overlaps(clip_bb[record_num2]) {
                             // find any intersections between the polylines
                             find_split_points_at_line_intersections(
@@ -519,7 +530,7 @@ impl WhiteboxTool for Clip {
                         if split_lines[j].len() > 1 {
                             let mut out = false;
                             let p = Point2D::midpoint(&split_lines[j][0], &split_lines[j][1]); // lies along the polyline
-                            for record_num2 in 0..clip_polylines.len() {
+                            for record_num2 in get_point_candidate_parts(&clip, &clip_parts, p) {
                                 if clip_bb[record_num2].is_point_in_box(p.x, p.y) {
                                     if point_in_poly(&p, &(clip_polylines[record_num2].vertices)) {
                                         if !is_clip_part_a_hole[record_num2] {
@@ -599,7 +610,7 @@ impl WhiteboxTool for Clip {
                         pl.source_file = 2;
                         let plbb = pl.get_bounding_box();
                         let mut overlaps_with_clip = false;
-                        for i in 0..clip_polylines.len() {
+                        for i in get_candidate_parts(&clip, &clip_parts, &plbb) {
                             if plbb.overlaps(clip_bb[i]) {
                                 if poly_overlaps_poly(&(pl.vertices), &(clip_polylines[i].vertices))
                                 {
@@ -2607,6 +2618,32 @@ impl PartialOrd for Link {
     }
 }
 
+/// Returns the indices of the parts of the clip records whose bounding boxes overlap `bb`,
+/// in ascending order. `clip_parts` holds the range of the part indices of each record.
+fn get_candidate_parts(
+    clip: &Shapefile,
+    clip_parts: &[(usize, usize)],
+    bb: &BoundingBox,
+) -> Vec<usize> {
+    clip.query_bbox(bb)
+        .into_iter()
+        .flat_map(|record_num| clip_parts[record_num].0..clip_parts[record_num].1)
+        .collect()
+}
+
+/// Returns the indices of the parts of the clip records whose bounding boxes contain `p`,
+/// in ascending order.
+fn get_point_candidate_parts(
+    clip: &Shapefile,
+    clip_parts: &[(usize, usize)],
+    p: Point2D,
+) -> Vec<usize> {
+    clip.query_point(p.x, p.y)
+        .into_iter()
+        .flat_map(|record_num| clip_parts[record_num].0..clip_parts[record_num].1)
+        .collect()
+}
+
 fn get_other_endnode(index: usize) -> usize {
     if index % 2 == 0 {
         // it's a starting node and we need the end
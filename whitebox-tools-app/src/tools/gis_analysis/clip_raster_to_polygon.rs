@@ -9,6 +9,7 @@ NOTES: This tool differs from the Whitebox GAT tool in that it only takes a sing
 */
 
 use whitebox_common::algorithms::point_in_poly;
+use whitebox_common::spatial_ref_system::CrsCheck;
 use whitebox_raster::*;
 use whitebox_common::structures::BoundingBox;
 use whitebox_common::structures::Point2D;
@@ -237,6 +238,20 @@ impl WhiteboxTool for ClipRasterToPolygon {
             ));
         }
 
+        let mut crs_check = CrsCheck::new();
+        if let Some(warning) = crs_check.check(
+            &input.get_short_filename(),
+            input.get_coordinate_reference_system(),
+        ) {
+            println!("Warning: {}", warning);
+        }
+        if let Some(warning) = crs_check.check(
+            &polygons.get_short_filename(),
+            polygons.get_coordinate_reference_system(),
+        ) {
+            println!("Warning: {}", warning);
+        }
+
         if maintain_dimensions {
             // Output raster has same dimensions as the input
             let mut output = Raster::initialize_using_file(&output_file, &input);
@@ -404,7 +419,7 @@ impl WhiteboxTool for ClipRasterToPolygon {
                         println!("Output file written")
                     }
                 }
-                Err(e) => return Err(e),
+                Err(e) => return Err(e.into()),
             };
 
             if verbose {
@@ -628,7 +643,7 @@ impl WhiteboxTool for ClipRasterToPolygon {
                         println!("Output file written")
                     }
                 }
-                Err(e) => return Err(e),
+                Err(e) => return Err(e.into()),
             };
 
             if verbose {
@@ -25,6 +25,8 @@ use std::path;
 /// can optionally include diagonally neighbouring cells if the `--diag` flag is
 /// specified.
 ///
+/// The output raster's attribute table lists the number of grid cells in each clump.
+///
 /// # See Also
 /// `Reclass`, `GreaterThan`, `LessThan`, `EqualTo`, `NotEqualTo`
 pub struct Clump {
@@ -252,11 +254,11 @@ impl WhiteboxTool for Clump {
         let mut count: usize; // this is just used to update the progress after every 1000 cells solved.
         for row in 0..rows {
             for col in 0..columns {
-                zin = input[(row, col)];
-                zout = output[(row, col)];
+                zin = input.get_value(row, col);
+                zout = output.get_value(row, col);
                 if zin != nodata && zin != back_val && zout == out_nodata {
                     fid += 1f64;
-                    output[(row, col)] = fid;
+                    output.set_value(row, col, fid);
                     num_solved_cells += 1;
                     stack.push((row, col));
                     count = 0;
@@ -278,10 +280,10 @@ impl WhiteboxTool for Clump {
                             }
                         }
                         for i in 0..num_neighbours {
-                            zn = input[(r + dy[i], c + dx[i])];
-                            zout = output[(r + dy[i], c + dx[i])];
+                            zn = input.get_value(r + dy[i], c + dx[i]);
+                            zout = output.get_value(r + dy[i], c + dx[i]);
                             if zn == zin && zout == out_nodata {
-                                output[(r + dy[i], c + dx[i])] = fid;
+                                output.set_value(r + dy[i], c + dx[i], fid);
                                 num_solved_cells += 1;
                                 stack.push((r + dy[i], c + dx[i]));
                             }
@@ -291,7 +293,7 @@ impl WhiteboxTool for Clump {
                     num_solved_cells += 1;
                 } else if zin == back_val {
                     num_solved_cells += 1;
-                    output[(row, col)] = back_val;
+                    output.set_value(row, col, back_val);
                 }
             }
             if verbose {
@@ -303,6 +305,15 @@ impl WhiteboxTool for Clump {
             }
         }
 
+        // The attribute table gives the size of each clump, unless there are so many
+        // clumps that the table would be unwieldy.
+        if fid <= 65535f64 {
+            for id in 1..=fid as i64 {
+                output.configs.attribute_table.add_entry(id, "");
+            }
+            output.update_attribute_counts();
+        }
+
         let elapsed_time = get_formatted_elapsed_time(start);
         output.configs.palette = "qual.plt".to_string();
         output.add_metadata_entry(format!(
@@ -322,7 +333,7 @@ impl WhiteboxTool for Clump {
                     println!("Output file written")
                 }
             }
-            Err(e) => return Err(e),
+            Err(e) => return Err(e.into()),
         };
 
         if verbose {
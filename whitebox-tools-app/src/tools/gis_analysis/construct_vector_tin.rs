@@ -346,6 +346,9 @@ impl WhiteboxTool for ConstructVectorTIN {
                         FieldData::Int(val) => {
                             z_values.push(val as f64);
                         }
+                        FieldData::BigInt(val) => {
+                            z_values.push(val as f64);
+                        }
                         FieldData::Real(val) => {
                             z_values.push(val);
                         }
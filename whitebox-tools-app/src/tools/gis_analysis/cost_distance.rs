@@ -669,7 +669,7 @@ impl WhiteboxTool for CostDistance {
                     println!("Output file written")
                 }
             }
-            Err(e) => return Err(e),
+            Err(e) => return Err(e.into()),
         };
 
         backlink.configs.palette = "qual.plt".to_string();
@@ -687,7 +687,7 @@ impl WhiteboxTool for CostDistance {
                     println!("Output file written")
                 }
             }
-            Err(e) => return Err(e),
+            Err(e) => return Err(e.into()),
         };
 
         if verbose {
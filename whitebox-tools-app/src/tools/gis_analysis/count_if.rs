@@ -7,6 +7,7 @@ License: MIT
 */
 
 use whitebox_raster::*;
+use whitebox_common::spatial_ref_system::CrsCheck;
 use crate::tools::*;
 use std::env;
 use std::f64;
@@ -222,6 +223,7 @@ impl WhiteboxTool for CountIf {
         let mut z: f64;
         let mut read_first_file = false;
         let mut i = 1;
+        let mut crs_check = CrsCheck::new();
         for value in vec {
             if !value.trim().is_empty() {
                 if verbose {
@@ -233,6 +235,12 @@ impl WhiteboxTool for CountIf {
                     input_file = format!("{}{}", working_directory, input_file);
                 }
                 let input = Raster::new(&input_file, "r")?;
+                if let Some(warning) = crs_check.check(
+                    &input.get_short_filename(),
+                    input.get_coordinate_reference_system(),
+                ) {
+                    println!("Warning: {}", warning);
+                }
                 in_nodata = input.configs.nodata;
                 if !read_first_file {
                     read_first_file = true;
@@ -252,7 +260,7 @@ impl WhiteboxTool for CountIf {
 
                 for row in 0..rows {
                     for col in 0..columns {
-                        z = input[(row, col)];
+                        z = input.get_value(row, col);
                         if z == comparison_value && z != in_nodata {
                             if output.get_value(row, col) != out_nodata {
                                 output.increment(row, col, 1f64);
@@ -289,7 +297,7 @@ impl WhiteboxTool for CountIf {
                     println!("Output file written")
                 }
             }
-            Err(e) => return Err(e),
+            Err(e) => return Err(e.into()),
         };
 
         if verbose {
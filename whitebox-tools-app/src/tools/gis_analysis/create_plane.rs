@@ -342,7 +342,7 @@ impl WhiteboxTool for CreatePlane {
                     println!("Output file written")
                 }
             }
-            Err(e) => return Err(e),
+            Err(e) => return Err(e.into()),
         };
 
         if verbose {
@@ -231,13 +231,13 @@ impl WhiteboxTool for EdgeProportion {
                 let mut bin: usize;
                 for row in (0..rows).filter(|r| r % num_procs == tid) {
                     for col in 0..columns {
-                        z = input[(row, col)];
+                        z = input.get_value(row, col);
                         if z > 0f64 && z != nodata {
                             bin = z.floor() as usize;
                             num_cells[bin] += 1;
                             is_edge = false;
                             for n in 0..8 {
-                                zn = input[(row + dy[n], col + dx[n])];
+                                zn = input.get_value(row + dy[n], col + dx[n]);
                                 if zn != z {
                                     is_edge = true;
                                     break;
@@ -289,7 +289,7 @@ impl WhiteboxTool for EdgeProportion {
                 for row in (0..rows).filter(|r| r % num_procs == tid) {
                     let mut data = vec![nodata; columns as usize];
                     for col in 0..columns {
-                        z = input[(row, col)];
+                        z = input.get_value(row, col);
                         if z > 0f64 && z != nodata {
                             bin = z.floor() as usize;
                             data[col as usize] = edge_props[bin];
@@ -334,7 +334,7 @@ impl WhiteboxTool for EdgeProportion {
                     println!("Output file written")
                 }
             }
-            Err(e) => return Err(e),
+            Err(e) => return Err(e.into()),
         };
 
         if output_text {
@@ -379,7 +379,7 @@ impl WhiteboxTool for ErasePolygonFromRaster {
                     println!("Output file written")
                 }
             }
-            Err(e) => return Err(e),
+            Err(e) => return Err(e.into()),
         };
 
         if verbose {
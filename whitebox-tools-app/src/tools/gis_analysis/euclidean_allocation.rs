@@ -209,13 +209,13 @@ impl WhiteboxTool for EuclideanAllocation {
 
         for row in 0..rows {
             for col in 0..columns {
-                z = input[(row, col)];
+                z = input.get_value(row, col);
                 if z != 0.0 {
                     distance[(row, col)] = 0.0;
-                    allocation[(row, col)] = input[(row, col)];
+                    allocation.set_value(row, col, input.get_value(row, col));
                 } else {
                     distance[(row, col)] = inf_val;
-                    allocation[(row, col)] = inf_val;
+                    allocation.set_value(row, col, inf_val);
                 }
             }
             if verbose {
@@ -257,7 +257,7 @@ impl WhiteboxTool for EuclideanAllocation {
                         y = row + d_y[which_cell];
                         r_x[(row, col)] = r_x[(y, x)] + g_x[which_cell];
                         r_y[(row, col)] = r_y[(y, x)] + g_y[which_cell];
-                        allocation[(row, col)] = allocation[(y, x)];
+                        allocation.set_value(row, col, allocation.get_value(y, x));
                     }
                 }
             }
@@ -300,7 +300,7 @@ impl WhiteboxTool for EuclideanAllocation {
                         y = row + d_y[which_cell];
                         r_x[(row, col)] = r_x[(y, x)] + g_x[which_cell];
                         r_y[(row, col)] = r_y[(y, x)] + g_y[which_cell];
-                        allocation[(row, col)] = allocation[(y, x)];
+                        allocation.set_value(row, col, allocation.get_value(y, x));
                     }
                 }
             }
@@ -315,9 +315,9 @@ impl WhiteboxTool for EuclideanAllocation {
 
         for row in 0..rows {
             for col in 0..columns {
-                z = input[(row, col)];
+                z = input.get_value(row, col);
                 if z == nodata {
-                    allocation[(row, col)] = nodata;
+                    allocation.set_value(row, col, nodata);
                 }
             }
             if verbose {
@@ -347,7 +347,7 @@ impl WhiteboxTool for EuclideanAllocation {
                     println!("Output file written")
                 }
             }
-            Err(e) => return Err(e),
+            Err(e) => return Err(e.into()),
         };
 
         if verbose {
@@ -302,7 +302,7 @@ impl WhiteboxTool for EuclideanDistance {
                         }
                     }
                     if z_min < z {
-                        output[(row, col)] = z_min;
+                        output.set_value(row, col, z_min);
                         x = col + dx[which_cell];
                         y = row + dy[which_cell];
                         rx.set_value(row, col, rx.get_value(y, x) + gx[which_cell]);
@@ -355,7 +355,7 @@ impl WhiteboxTool for EuclideanDistance {
                     println!("Output file written")
                 }
             }
-            Err(e) => return Err(e),
+            Err(e) => return Err(e.into()),
         };
 
         if verbose {
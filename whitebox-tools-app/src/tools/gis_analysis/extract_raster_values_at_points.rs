@@ -7,6 +7,7 @@ License: MIT
 */
 
 use whitebox_raster::*;
+use whitebox_common::spatial_ref_system::CrsCheck;
 use crate::tools::*;
 use whitebox_vector::*;
 use std::env;
@@ -216,6 +217,14 @@ impl WhiteboxTool for ExtractRasterValuesAtPoints {
             ));
         }
 
+        let mut crs_check = CrsCheck::new();
+        if let Some(warning) = crs_check.check(
+            &points.get_short_filename(),
+            points.get_coordinate_reference_system(),
+        ) {
+            println!("Warning: {}", warning);
+        }
+
         let (mut row, mut col): (isize, isize);
         let mut x_vals = Vec::with_capacity(num_records);
         let mut y_vals = Vec::with_capacity(num_records);
@@ -248,6 +257,12 @@ impl WhiteboxTool for ExtractRasterValuesAtPoints {
                     input_file = format!("{}{}", working_directory, input_file);
                 }
                 let input = Raster::new(&input_file, "r")?;
+                if let Some(warning) = crs_check.check(
+                    &input.get_short_filename(),
+                    input.get_coordinate_reference_system(),
+                ) {
+                    println!("Warning: {}", warning);
+                }
 
                 for record_num in 0..num_records {
                     row = input.get_row_from_y(y_vals[record_num]);
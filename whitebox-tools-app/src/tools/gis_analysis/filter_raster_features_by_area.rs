@@ -310,7 +310,7 @@ impl WhiteboxTool for FilterRasterFeaturesByArea {
                     println!("Output file written")
                 }
             }
-            Err(e) => return Err(e),
+            Err(e) => return Err(e.into()),
         };
 
         if verbose {
@@ -258,7 +258,7 @@ impl WhiteboxTool for FindPatchOrClassEdgeCells {
                     println!("Output file written")
                 }
             }
-            Err(e) => return Err(e),
+            Err(e) => return Err(e.into()),
         };
 
         if verbose {
@@ -7,6 +7,7 @@ License: MIT
 */
 
 use whitebox_raster::*;
+use whitebox_common::spatial_ref_system::CrsCheck;
 use whitebox_common::structures::Array2D;
 use crate::tools::*;
 use std::env;
@@ -198,6 +199,7 @@ impl WhiteboxTool for HighestPosition {
         let mut z: f64;
         let mut read_first_file = false;
         let mut i = 1;
+        let mut crs_check = CrsCheck::new();
         for value in vec {
             if !value.trim().is_empty() {
                 if verbose {
@@ -209,6 +211,12 @@ impl WhiteboxTool for HighestPosition {
                     input_file = format!("{}{}", working_directory, input_file);
                 }
                 let input = Raster::new(&input_file, "r")?;
+                if let Some(warning) = crs_check.check(
+                    &input.get_short_filename(),
+                    input.get_coordinate_reference_system(),
+                ) {
+                    println!("Warning: {}", warning);
+                }
                 in_nodata = input.configs.nodata;
                 if !read_first_file {
                     read_first_file = true;
@@ -228,11 +236,11 @@ impl WhiteboxTool for HighestPosition {
 
                 for row in 0..rows {
                     for col in 0..columns {
-                        z = input[(row, col)];
+                        z = input.get_value(row, col);
                         if z != in_nodata {
                             if z > high_val[(row, col)] {
                                 high_val[(row, col)] = z;
-                                output[(row, col)] = i as f64;
+                                output.set_value(row, col, i as f64);
                             }
                         }
                     }
@@ -264,7 +272,7 @@ impl WhiteboxTool for HighestPosition {
                     println!("Output file written")
                 }
             }
-            Err(e) => return Err(e),
+            Err(e) => return Err(e.into()),
         };
 
         if verbose {
@@ -477,9 +477,9 @@ impl WhiteboxTool for IdwInterpolation {
                     FieldData::Int(val) => {
                         frs.insert(x, y, val as f64);
                     }
-                    // FieldData::Int64(val) => {
-                    //     frs.insert(x, y, val as f64);
-                    // },
+                    FieldData::BigInt(val) => {
+                        frs.insert(x, y, val as f64);
+                    }
                     FieldData::Real(val) => {
                         frs.insert(x, y, val);
                     }
@@ -741,7 +741,7 @@ impl WhiteboxTool for IdwInterpolation {
                     println!("Output file written")
                 }
             }
-            Err(e) => return Err(e),
+            Err(e) => return Err(e.into()),
         };
 
         if verbose {
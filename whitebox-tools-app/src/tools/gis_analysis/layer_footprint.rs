@@ -199,7 +199,7 @@ impl WhiteboxTool for LayerFootprint {
         }
 
         // is it a vector or a raster file?
-        if input_file.to_lowercase().ends_with(".shp") {
+        if is_vector_file(&input_file) {
             // The input file is a vector
             let input = Shapefile::read(&input_file)?;
 
@@ -7,6 +7,7 @@ License: MIT
 */
 
 use whitebox_raster::*;
+use whitebox_common::spatial_ref_system::CrsCheck;
 use crate::tools::*;
 use std::env;
 use std::f64;
@@ -195,6 +196,7 @@ impl WhiteboxTool for MinOverlay {
         let mut out_val: f64;
         let mut read_first_file = false;
         let mut i = 1;
+        let mut crs_check = CrsCheck::new();
         for value in vec {
             if !value.trim().is_empty() {
                 if verbose {
@@ -206,6 +208,12 @@ impl WhiteboxTool for MinOverlay {
                     input_file = format!("{}{}", working_directory, input_file);
                 }
                 let input = Raster::new(&input_file, "r")?;
+                if let Some(warning) = crs_check.check(
+                    &input.get_short_filename(),
+                    input.get_coordinate_reference_system(),
+                ) {
+                    println!("Warning: {}", warning);
+                }
                 in_nodata = input.configs.nodata;
                 if !read_first_file {
                     read_first_file = true;
@@ -226,15 +234,15 @@ impl WhiteboxTool for MinOverlay {
 
                 for row in 0..rows {
                     for col in 0..columns {
-                        in_val = input[(row, col)];
+                        in_val = input.get_value(row, col);
                         if in_val != in_nodata {
-                            out_val = output[(row, col)];
+                            out_val = output.get_value(row, col);
                             if out_val != out_nodata {
                                 if in_val < out_val {
-                                    output[(row, col)] = in_val;
+                                    output.set_value(row, col, in_val);
                                 }
                             } else {
-                                output[(row, col)] = in_val;
+                                output.set_value(row, col, in_val);
                             }
                         }
                     }
@@ -266,7 +274,7 @@ impl WhiteboxTool for MinOverlay {
                     println!("Output file written")
                 }
             }
-            Err(e) => return Err(e),
+            Err(e) => return Err(e.into()),
         };
 
         if verbose {
@@ -5,7 +5,7 @@ mod block_maximum;
 mod block_minimum;
 mod boundary_shape_complexity;
 mod buffer_raster;
-// mod buffer_vector;
+mod buffer_vector;
 mod centroid;
 mod centroid_vector;
 mod clip;
@@ -77,6 +77,8 @@ mod reclass;
 mod reclass_equal_interval;
 mod reclass_from_file;
 mod related_circumscribing_circle;
+mod reproject_raster;
+mod reproject_vector;
 mod shape_complexity_index;
 mod shape_complexity_raster;
 mod smooth_vectors;
@@ -84,6 +86,8 @@ mod split_with_lines;
 mod sum_overlay;
 mod symmetrical_difference;
 mod tin_gridding;
+mod transform_raster_heights;
+mod transform_vector_heights;
 mod union;
 mod update_nodata_cells;
 mod vector_hex_bin;
@@ -98,7 +102,7 @@ pub use self::block_maximum::BlockMaximumGridding;
 pub use self::block_minimum::BlockMinimumGridding;
 pub use self::boundary_shape_complexity::BoundaryShapeComplexity;
 pub use self::buffer_raster::BufferRaster;
-// pub use self::buffer_vector::BufferVector;
+pub use self::buffer_vector::BufferVector;
 pub use self::centroid::Centroid;
 pub use self::centroid_vector::CentroidVector;
 pub use self::clip::Clip;
@@ -170,6 +174,8 @@ pub use self::reclass::Reclass;
 pub use self::reclass_equal_interval::ReclassEqualInterval;
 pub use self::reclass_from_file::ReclassFromFile;
 pub use self::related_circumscribing_circle::RelatedCircumscribingCircle;
+pub use self::reproject_raster::ReprojectRaster;
+pub use self::reproject_vector::ReprojectVector;
 pub use self::shape_complexity_index::ShapeComplexityIndex;
 pub use self::shape_complexity_raster::ShapeComplexityIndexRaster;
 pub use self::smooth_vectors::SmoothVectors;
@@ -177,6 +183,8 @@ pub use self::split_with_lines::SplitWithLines;
 pub use self::sum_overlay::SumOverlay;
 pub use self::symmetrical_difference::SymmetricalDifference;
 pub use self::tin_gridding::TINGridding;
+pub use self::transform_raster_heights::TransformRasterHeights;
+pub use self::transform_vector_heights::TransformVectorHeights;
 pub use self::union::Union;
 pub use self::update_nodata_cells::UpdateNodataCells;
 pub use self::vector_hex_bin::VectorHexBinning;
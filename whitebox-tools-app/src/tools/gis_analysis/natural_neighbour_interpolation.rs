@@ -424,6 +424,9 @@ impl WhiteboxTool for NaturalNeighbourInterpolation {
                         FieldData::Int(val) => {
                             z_values.push(val as f64);
                         }
+                        FieldData::BigInt(val) => {
+                            z_values.push(val as f64);
+                        }
                         FieldData::Real(val) => {
                             z_values.push(val);
                         }
@@ -756,7 +759,7 @@ impl WhiteboxTool for NaturalNeighbourInterpolation {
                     println!("Output file written")
                 }
             }
-            Err(e) => return Err(e),
+            Err(e) => return Err(e.into()),
         };
 
         let elapsed_time = get_formatted_elapsed_time(start);
@@ -324,6 +324,9 @@ impl WhiteboxTool for NearestNeighbourGridding {
                     FieldData::Int(val) => {
                         frs.insert(x, y, val as f64);
                     }
+                    FieldData::BigInt(val) => {
+                        frs.insert(x, y, val as f64);
+                    }
                     FieldData::Real(val) => {
                         frs.insert(x, y, val);
                     }
@@ -490,7 +493,7 @@ impl WhiteboxTool for NearestNeighbourGridding {
                     println!("Output file written")
                 }
             }
-            Err(e) => return Err(e),
+            Err(e) => return Err(e.into()),
         };
 
         if verbose {
@@ -7,6 +7,7 @@ License: MIT
 */
 
 use whitebox_raster::*;
+use whitebox_common::spatial_ref_system::CrsCheck;
 use whitebox_common::structures::Array2D;
 use crate::tools::*;
 use std::env;
@@ -220,6 +221,13 @@ impl WhiteboxTool for PercentLessThan {
         let mut in_nodata: f64;
         let mut z: f64;
         let mut i = 1;
+        let mut crs_check = CrsCheck::new();
+        if let Some(warning) = crs_check.check(
+            &comparison.get_short_filename(),
+            comparison.get_coordinate_reference_system(),
+        ) {
+            println!("Warning: {}", warning);
+        }
         for value in vec {
             if !value.trim().is_empty() {
                 if verbose {
@@ -231,6 +239,12 @@ impl WhiteboxTool for PercentLessThan {
                     input_file = format!("{}{}", working_directory, input_file);
                 }
                 let input = Raster::new(&input_file, "r")?;
+                if let Some(warning) = crs_check.check(
+                    &input.get_short_filename(),
+                    input.get_coordinate_reference_system(),
+                ) {
+                    println!("Warning: {}", warning);
+                }
                 in_nodata = input.configs.nodata;
                 // check to ensure that all inputs have the same rows and columns
                 if input.configs.rows as isize != rows || input.configs.columns as isize != columns
@@ -241,11 +255,11 @@ impl WhiteboxTool for PercentLessThan {
 
                 for row in 0..rows {
                     for col in 0..columns {
-                        z = input[(row, col)];
+                        z = input.get_value(row, col);
                         if z != in_nodata {
                             n_images[(row, col)] += 1;
-                            if z < comparison[(row, col)] {
-                                output[(row, col)] += 1.0;
+                            if z < comparison.get_value(row, col) {
+                                output.set_value(row, col, output.get_value(row, col) + 1.0);
                             }
                         }
                     }
@@ -263,13 +277,16 @@ impl WhiteboxTool for PercentLessThan {
 
         for row in 0..rows {
             for col in 0..columns {
-                z = comparison[(row, col)];
+                z = comparison.get_value(row, col);
                 if z != nodata {
                     if n_images[(row, col)] > 0 {
-                        output[(row, col)] =
-                            100.0 * output[(row, col)] / n_images[(row, col)] as f64;
+                        output.set_value(
+                            row,
+                            col,
+                            100.0 * output.get_value(row, col) / n_images[(row, col)] as f64,
+                        );
                     } else {
-                        output[(row, col)] = 0f64;
+                        output.set_value(row, col, 0f64);
                     }
                 }
             }
@@ -298,7 +315,7 @@ impl WhiteboxTool for PercentLessThan {
                     println!("Output file written")
                 }
             }
-            Err(e) => return Err(e),
+            Err(e) => return Err(e.into()),
         };
 
         if verbose {
@@ -7,6 +7,7 @@ License: MIT
 */
 
 use whitebox_raster::*;
+use whitebox_common::spatial_ref_system::CrsCheck;
 use crate::tools::*;
 use std::env;
 use std::f64;
@@ -219,6 +220,13 @@ impl WhiteboxTool for PickFromList {
         let mut in_val: f64;
         let mut i = 1;
         let mut j = 0f64;
+        let mut crs_check = CrsCheck::new();
+        if let Some(warning) = crs_check.check(
+            &position.get_short_filename(),
+            position.get_coordinate_reference_system(),
+        ) {
+            println!("Warning: {}", warning);
+        }
         for value in vec {
             if !value.trim().is_empty() {
                 if verbose {
@@ -230,6 +238,12 @@ impl WhiteboxTool for PickFromList {
                     input_file = format!("{}{}", working_directory, input_file);
                 }
                 let input = Raster::new(&input_file, "r")?;
+                if let Some(warning) = crs_check.check(
+                    &input.get_short_filename(),
+                    input.get_coordinate_reference_system(),
+                ) {
+                    println!("Warning: {}", warning);
+                }
                 let in_nodata = input.configs.nodata;
 
                 // check to ensure that all inputs have the same rows and columns
@@ -241,10 +255,10 @@ impl WhiteboxTool for PickFromList {
 
                 for row in 0..rows {
                     for col in 0..columns {
-                        if position[(row, col)] == j {
-                            in_val = input[(row, col)];
+                        if position.get_value(row, col) == j {
+                            in_val = input.get_value(row, col);
                             if in_val != in_nodata {
-                                output[(row, col)] = in_val;
+                                output.set_value(row, col, in_val);
                             }
                         }
                     }
@@ -277,7 +291,7 @@ impl WhiteboxTool for PickFromList {
                     println!("Output file written")
                 }
             }
-            Err(e) => return Err(e),
+            Err(e) => return Err(e.into()),
         };
 
         if verbose {
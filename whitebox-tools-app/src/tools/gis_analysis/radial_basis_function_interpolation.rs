@@ -421,6 +421,7 @@ impl WhiteboxTool for RadialBasisFunctionInterpolation {
                     } else {
                         let val = match input.attributes.get_value(record_num, &field_name) {
                             FieldData::Int(val) => val as f64,
+                            FieldData::BigInt(val) => val as f64,
                             FieldData::Real(val) => val,
                             FieldData::Null => continue,
                             _ => {
@@ -619,7 +620,7 @@ impl WhiteboxTool for RadialBasisFunctionInterpolation {
                     println!("Output file written")
                 }
             }
-            Err(e) => return Err(e),
+            Err(e) => return Err(e.into()),
         };
 
         if verbose {
@@ -330,7 +330,7 @@ impl WhiteboxTool for RadiusOfGyration {
         let mut a: usize;
         for row in 0..rows {
             for col in 0..columns {
-                z = input[(row, col)];
+                z = input.get_value(row, col);
                 if z > 0f64 && z != nodata {
                     a = (z - min_val as f64) as usize;
                     output.set_value(row, col, gyradius[a]);
@@ -375,7 +375,7 @@ impl WhiteboxTool for RadiusOfGyration {
                     println!("Output file written")
                 }
             }
-            Err(e) => return Err(e),
+            Err(e) => return Err(e.into()),
         };
 
         if verbose {
@@ -353,7 +353,7 @@ impl WhiteboxTool for RasterArea {
                             println!("Output file written")
                         }
                     }
-                    Err(e) => return Err(e),
+                    Err(e) => return Err(e.into()),
                 };
             }
             if output_text {
@@ -473,7 +473,7 @@ impl WhiteboxTool for RasterArea {
                             println!("Output file written")
                         }
                     }
-                    Err(e) => return Err(e),
+                    Err(e) => return Err(e.into()),
                 };
             }
             if output_text {
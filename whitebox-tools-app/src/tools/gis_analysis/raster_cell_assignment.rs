@@ -311,7 +311,7 @@ impl WhiteboxTool for RasterCellAssignment {
                     println!("Output file written")
                 }
             }
-            Err(e) => return Err(e),
+            Err(e) => return Err(e.into()),
         };
 
         if verbose {
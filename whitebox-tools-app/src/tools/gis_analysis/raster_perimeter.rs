@@ -645,7 +645,7 @@ impl WhiteboxTool for RasterPerimeter {
                         println!("Output file written")
                     }
                 }
-                Err(e) => return Err(e),
+                Err(e) => return Err(e.into()),
             };
         }
         if output_text {
@@ -40,6 +40,9 @@ use std::thread;
 /// output raster. NoData values in the input raster will be assigned NoData values in the output raster, unless NoData is
 /// used in one of the user-defined reclass ranges (notice that it is valid to enter 'NoData' in these ranges).
 ///
+/// When the new values are integers, the output raster's attribute table labels each new class with the input
+/// values or ranges that were assigned to it, e.g. *[0, 1)*, and gives the number of grid cells in the class.
+///
 /// # See Also
 /// `ReclassEqualInterval`, `ReclassFromFile`
 pub struct Reclass {
@@ -295,7 +298,7 @@ impl WhiteboxTool for Reclass {
                     for row in (0..rows).filter(|r| r % num_procs == tid) {
                         let mut data: Vec<f64> = vec![nodata; columns as usize];
                         for col in 0..columns {
-                            z = input[(row, col)];
+                            z = input.get_value(row, col);
                             if z != nodata {
                                 // This is a shortcut intended to take advantage of the inherent
                                 // spatial autocorrelation in spatial distributions to speed up
@@ -362,7 +365,7 @@ impl WhiteboxTool for Reclass {
                     for row in (0..rows).filter(|r| r % num_procs == tid) {
                         let mut data: Vec<f64> = vec![nodata; columns as usize];
                         for col in 0..columns {
-                            z = input[(row, col)];
+                            z = input.get_value(row, col);
                             if z != nodata {
                                 // is z in the hashmap?
                                 if assign_map.contains_key(&((z * multiplier).round() as i64)) {
@@ -392,6 +395,28 @@ impl WhiteboxTool for Reclass {
             }
         }
 
+        // Label each of the new classes with the input values that it was assigned, provided
+        // that the new values are integers, i.e. classes.
+        let new_values: Vec<f64> = (0..num_ranges)
+            .map(|a| reclass_vals[a * if assign_mode { 2 } else { 3 }])
+            .collect();
+        if new_values.iter().all(|v| v.fract() == 0f64) {
+            for a in 0..num_ranges {
+                let label = if assign_mode {
+                    format!("{}", reclass_vals[a * 2 + 1])
+                } else {
+                    format!("[{}, {})", reclass_vals[a * 3 + 1], reclass_vals[a * 3 + 2])
+                };
+                let value = new_values[a] as i64;
+                let name = match output.configs.attribute_table.get_name(value) {
+                    Some(name) if !name.is_empty() => format!("{}; {}", name, label),
+                    _ => label,
+                };
+                output.configs.attribute_table.add_entry(value, &name);
+            }
+            output.update_attribute_counts();
+        }
+
         let elapsed_time = get_formatted_elapsed_time(start);
         output.add_metadata_entry(format!(
             "Created by whitebox_tools\' {} tool",
@@ -410,7 +435,7 @@ impl WhiteboxTool for Reclass {
                     println!("Output file written")
                 }
             }
-            Err(e) => return Err(e),
+            Err(e) => return Err(e.into()),
         };
 
         if verbose {
@@ -273,7 +273,7 @@ impl WhiteboxTool for ReclassEqualInterval {
                 for row in (0..rows).filter(|r| r % num_procs == tid) {
                     let mut data: Vec<f64> = vec![nodata; columns as usize];
                     for col in 0..columns {
-                        z = input[(row, col)];
+                        z = input.get_value(row, col);
                         if z != nodata {
                             if z >= start_val && z <= end_val {
                                 z = (z / interval_size).floor() * interval_size;
@@ -317,7 +317,7 @@ impl WhiteboxTool for ReclassEqualInterval {
                     println!("Output file written")
                 }
             }
-            Err(e) => return Err(e),
+            Err(e) => return Err(e.into()),
         };
 
         if verbose {
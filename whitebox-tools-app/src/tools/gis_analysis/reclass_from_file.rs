@@ -290,7 +290,7 @@ impl WhiteboxTool for ReclassFromFile {
                     for row in (0..rows).filter(|r| r % num_procs == tid) {
                         let mut data: Vec<f64> = vec![nodata; columns as usize];
                         for col in 0..columns {
-                            z = input[(row, col)];
+                            z = input.get_value(row, col);
                             if z != nodata {
                                 // This is a shortcut intended to take advantage of the inherent
                                 // spatial autocorrelation in spatial distributions to speed up
@@ -357,7 +357,7 @@ impl WhiteboxTool for ReclassFromFile {
                     for row in (0..rows).filter(|r| r % num_procs == tid) {
                         let mut data: Vec<f64> = vec![nodata; columns as usize];
                         for col in 0..columns {
-                            z = input[(row, col)];
+                            z = input.get_value(row, col);
                             if z != nodata {
                                 // is z in the hashmap?
                                 if assign_map.contains_key(&((z * multiplier).round() as i64)) {
@@ -405,7 +405,7 @@ impl WhiteboxTool for ReclassFromFile {
                     println!("Output file written")
                 }
             }
-            Err(e) => return Err(e),
+            Err(e) => return Err(e.into()),
         };
 
         if verbose {
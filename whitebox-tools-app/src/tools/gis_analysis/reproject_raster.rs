@@ -0,0 +1,589 @@
+/*
+This tool is part of the WhiteboxTools geospatial analysis library.
+License: MIT
+*/
+
+use whitebox_common::spatial_ref_system::{
+    esri_wkt_from_epsg, CoordinateReferenceSystem, CoordinateTransformation,
+};
+use whitebox_raster::*;
+use crate::tools::*;
+use num_cpus;
+use std::env;
+use std::f64;
+use std::fs;
+use std::io::{Error, ErrorKind};
+use std::path;
+use std::sync::mpsc;
+use std::sync::Arc;
+use std::thread;
+
+/// This tool transforms a raster (`--input`) into another coordinate reference system,
+/// i.e. it warps the raster onto a grid in the target system. The target system is given
+/// either by its EPSG code (`--epsg`), by a file containing its well-known text (WKT)
+/// description, such as a shapefile's .prj file (`--crs_file`), or by a base raster
+/// (`--base`). The input raster must have a known coordinate reference system.
+///
+/// The output grid matches that of the base raster, if one is specified. Otherwise, the
+/// output extent is that of the transformed input, or may be given in the target system's
+/// coordinates as 'xmin,ymin,xmax,ymax' (`--extent`), and the output cell size is given
+/// by `--cell_size` or, if it is not specified, chosen so that the output has about as
+/// many cells across its diagonal as the input.
+///
+/// Each output cell is assigned a value sampled from the input at the location of its
+/// centre, using one of the resampling methods (`--method`) of the `Resample` tool:
+/// nearest neighbour ('nn', the default), 'bilinear', cubic convolution ('cc'), or 'mode'.
+/// The bilinear and cubic convolution methods interpolate between input cells, ignoring
+/// NoData cells, and produce continuous (32-bit floating point) outputs. Nearest neighbour
+/// and mode keep the input values and should be used with categorical data; the mode
+/// method assigns the most common value of the input cells that fall within each output
+/// cell, which is preferable when the output cells are larger than the input cells.
+/// Output cells that fall outside of the input, or for which there is no valid input
+/// value, are assigned the NoData value of the input. Categorical outputs keep the colour
+/// table and attribute table of the input.
+///
+/// Only the first band of multi-band rasters is reprojected.
+///
+/// # See Also
+/// `Resample`, `ReprojectVector`
+pub struct ReprojectRaster {
+    name: String,
+    description: String,
+    toolbox: String,
+    parameters: Vec<ToolParameter>,
+    example_usage: String,
+}
+
+impl ReprojectRaster {
+    pub fn new() -> ReprojectRaster {
+        // public constructor
+        let name = "ReprojectRaster".to_string();
+        let toolbox = "GIS Analysis".to_string();
+        let description =
+            "Transforms a raster into another coordinate reference system.".to_string();
+
+        let mut parameters = vec![];
+        parameters.push(ToolParameter {
+            name: "Input File".to_owned(),
+            flags: vec!["-i".to_owned(), "--input".to_owned()],
+            description: "Input raster file.".to_owned(),
+            parameter_type: ParameterType::ExistingFile(ParameterFileType::Raster),
+            default_value: None,
+            optional: false,
+        });
+
+        parameters.push(ToolParameter {
+            name: "Output File".to_owned(),
+            flags: vec!["-o".to_owned(), "--output".to_owned()],
+            description: "Output raster file.".to_owned(),
+            parameter_type: ParameterType::NewFile(ParameterFileType::Raster),
+            default_value: None,
+            optional: false,
+        });
+
+        parameters.push(ToolParameter {
+            name: "Target EPSG Code (optional)".to_owned(),
+            flags: vec!["--epsg".to_owned()],
+            description: "EPSG code of the target coordinate reference system (e.g. 32617).".to_owned(),
+            parameter_type: ParameterType::Integer,
+            default_value: None,
+            optional: true,
+        });
+
+        parameters.push(ToolParameter {
+            name: "Target CRS File (optional)".to_owned(),
+            flags: vec!["--crs_file".to_owned()],
+            description: "File containing the WKT of the target coordinate reference system, e.g. a .prj file. Not used when an EPSG code is specified.".to_owned(),
+            parameter_type: ParameterType::ExistingFile(ParameterFileType::Any),
+            default_value: None,
+            optional: true,
+        });
+
+        parameters.push(ToolParameter {
+            name: "Base Raster File (optional)".to_owned(),
+            flags: vec!["--base".to_owned()],
+            description: "Optional base raster, whose grid, and unless otherwise specified, coordinate reference system the output takes.".to_owned(),
+            parameter_type: ParameterType::ExistingFile(ParameterFileType::Raster),
+            default_value: None,
+            optional: true,
+        });
+
+        parameters.push(ToolParameter {
+            name: "Cell Size (optional)".to_owned(),
+            flags: vec!["--cell_size".to_owned()],
+            description: "Optional cell size of the output raster, in the units of the target system. Not used when a base raster is specified.".to_owned(),
+            parameter_type: ParameterType::Float,
+            default_value: None,
+            optional: true,
+        });
+
+        parameters.push(ToolParameter {
+            name: "Output Extent (optional)".to_owned(),
+            flags: vec!["--extent".to_owned()],
+            description: "Optional extent of the output raster in the target system, as 'xmin,ymin,xmax,ymax'. Not used when a base raster is specified.".to_owned(),
+            parameter_type: ParameterType::String,
+            default_value: None,
+            optional: true,
+        });
+
+        parameters.push(ToolParameter {
+            name: "Resampling Method".to_owned(),
+            flags: vec!["--method".to_owned()],
+            description: "Resampling method; options include 'nn' (nearest neighbour), 'bilinear', 'cc' (cubic convolution), and 'mode' (majority, for categorical data).".to_owned(),
+            parameter_type: ParameterType::OptionList(vec![
+                "nn".to_owned(),
+                "bilinear".to_owned(),
+                "cc".to_owned(),
+                "mode".to_owned(),
+            ]),
+            default_value: Some("nn".to_owned()),
+            optional: true,
+        });
+
+        let sep: String = path::MAIN_SEPARATOR.to_string();
+        let e = format!("{}", env::current_exe().unwrap().display());
+        let mut parent = env::current_exe().unwrap();
+        parent.pop();
+        let p = format!("{}", parent.display());
+        let mut short_exe = e
+            .replace(&p, "")
+            .replace(".exe", "")
+            .replace(".", "")
+            .replace(&sep, "");
+        if e.contains(".exe") {
+            short_exe += ".exe";
+        }
+        let usage = format!(">>.*{0} -r={1} -v --wd=\"*path*to*data*\" -i=dem.tif -o=dem_utm.tif --epsg=32617 --cell_size=30.0 --method=bilinear
+>>.*{0} -r={1} -v --wd=\"*path*to*data*\" -i=landcover.tif -o=landcover_utm.tif --base=dem_utm.tif --method=mode", short_exe, name).replace("*", &sep);
+
+        ReprojectRaster {
+            name: name,
+            description: description,
+            toolbox: toolbox,
+            parameters: parameters,
+            example_usage: usage,
+        }
+    }
+}
+
+impl WhiteboxTool for ReprojectRaster {
+    fn get_source_file(&self) -> String {
+        String::from(file!())
+    }
+
+    fn get_tool_name(&self) -> String {
+        self.name.clone()
+    }
+
+    fn get_tool_description(&self) -> String {
+        self.description.clone()
+    }
+
+    fn get_tool_parameters(&self) -> String {
+        match serde_json::to_string(&self.parameters) {
+            Ok(json_str) => return format!("{{\"parameters\":{}}}", json_str),
+            Err(err) => return format!("{:?}", err),
+        }
+    }
+
+    fn get_example_usage(&self) -> String {
+        self.example_usage.clone()
+    }
+
+    fn get_toolbox(&self) -> String {
+        self.toolbox.clone()
+    }
+
+    fn run<'a>(
+        &self,
+        args: Vec<String>,
+        working_directory: &'a str,
+        verbose: bool,
+    ) -> Result<(), Error> {
+        let mut input_file = String::new();
+        let mut output_file = String::new();
+        let mut epsg = 0u16;
+        let mut crs_file = String::new();
+        let mut base_file = String::new();
+        let mut cell_size = 0f64;
+        let mut extent: Option<Vec<f64>> = None;
+        let mut method = ResamplingMethod::NearestNeighbour;
+
+        if args.len() == 0 {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                "Tool run with no parameters.",
+            ));
+        }
+        for i in 0..args.len() {
+            let mut arg = args[i].replace("\"", "");
+            arg = arg.replace("\'", "");
+            let cmd = arg.split("="); // in case an equals sign was used
+            let vec = cmd.collect::<Vec<&str>>();
+            let mut keyval = false;
+            if vec.len() > 1 {
+                keyval = true;
+            }
+            let flag_val = vec[0].to_lowercase().replace("--", "-");
+            let value = if keyval {
+                vec[1].to_string()
+            } else if i + 1 < args.len() {
+                args[i + 1].to_string()
+            } else {
+                String::new()
+            };
+            if flag_val == "-i" || flag_val == "-input" {
+                input_file = value;
+            } else if flag_val == "-o" || flag_val == "-output" {
+                output_file = value;
+            } else if flag_val == "-epsg" {
+                epsg = value
+                    .trim()
+                    .parse::<u16>()
+                    .expect(&format!("Error parsing {}", flag_val));
+            } else if flag_val == "-crs_file" {
+                crs_file = value;
+            } else if flag_val == "-base" {
+                base_file = value;
+            } else if flag_val == "-cell_size" {
+                cell_size = value
+                    .trim()
+                    .parse::<f64>()
+                    .expect(&format!("Error parsing {}", flag_val));
+                if cell_size <= 0f64 {
+                    return Err(Error::new(
+                        ErrorKind::InvalidInput,
+                        "When specified, the cell size (--cell_size) must be larger than zero.",
+                    ));
+                }
+            } else if flag_val == "-extent" {
+                let values = value
+                    .split(|c| c == ',' || c == ';')
+                    .map(|v| v.trim().parse::<f64>())
+                    .collect::<Result<Vec<f64>, _>>();
+                match values {
+                    Ok(v) if v.len() == 4 && v[0] < v[2] && v[1] < v[3] => extent = Some(v),
+                    _ => {
+                        return Err(Error::new(
+                            ErrorKind::InvalidInput,
+                            "The output extent (--extent) must be given as 'xmin,ymin,xmax,ymax'.",
+                        ))
+                    }
+                }
+            } else if flag_val == "-method" {
+                method = match ResamplingMethod::from_name(&value) {
+                    Some(m) => m,
+                    None => {
+                        return Err(Error::new(
+                            ErrorKind::InvalidInput,
+                            format!("Unrecognized resampling method '{}'.", value),
+                        ))
+                    }
+                };
+            }
+        }
+
+        if verbose {
+            let tool_name = self.get_tool_name();
+            let welcome_len = format!("* Welcome to {} *", tool_name).len().max(28);
+            // 28 = length of the 'Powered by' by statement.
+            println!("{}", "*".repeat(welcome_len));
+            println!("* Welcome to {} {}*", tool_name, " ".repeat(welcome_len - 15 - tool_name.len()));
+            println!("* Powered by WhiteboxTools {}*", " ".repeat(welcome_len - 28));
+            println!("* www.whiteboxgeo.com {}*", " ".repeat(welcome_len - 23));
+            println!("{}", "*".repeat(welcome_len));
+        }
+
+        let sep: String = path::MAIN_SEPARATOR.to_string();
+
+        let mut progress: usize;
+        let mut old_progress: usize = 1;
+
+        if !input_file.contains(&sep) && !input_file.contains("/") {
+            input_file = format!("{}{}", working_directory, input_file);
+        }
+        if !output_file.contains(&sep) && !output_file.contains("/") {
+            output_file = format!("{}{}", working_directory, output_file);
+        }
+        if !crs_file.is_empty() && !crs_file.contains(&sep) && !crs_file.contains("/") {
+            crs_file = format!("{}{}", working_directory, crs_file);
+        }
+        if !base_file.is_empty() && !base_file.contains(&sep) && !base_file.contains("/") {
+            base_file = format!("{}{}", working_directory, base_file);
+        }
+
+        if verbose {
+            println!("Reading data...")
+        };
+        let input = Raster::new(&input_file, "r")?;
+        let source_crs = input.get_coordinate_reference_system().ok_or_else(|| {
+            Error::new(
+                ErrorKind::InvalidInput,
+                "The coordinate reference system of the input raster is not specified.",
+            )
+        })?;
+        let base = if !base_file.is_empty() {
+            Some(Raster::new(&base_file, "r")?)
+        } else {
+            None
+        };
+
+        let start = Instant::now();
+
+        // the target system, and the WKT that describes it in the output
+        let (target_crs, target_wkt) = if epsg > 0 {
+            (
+                CoordinateReferenceSystem::from_epsg(epsg)?,
+                esri_wkt_from_epsg(epsg),
+            )
+        } else if !crs_file.is_empty() {
+            let wkt = fs::read_to_string(&crs_file)?.trim().to_string();
+            (CoordinateReferenceSystem::from_wkt(&wkt)?, wkt)
+        } else {
+            match base
+                .as_ref()
+                .and_then(|b| b.get_coordinate_reference_system().map(|crs| (b, crs)))
+            {
+                Some((b, crs)) => {
+                    let wkt = if b.configs.coordinate_ref_system_wkt.to_lowercase() == "not specified" {
+                        esri_wkt_from_epsg(b.configs.epsg_code)
+                    } else {
+                        b.configs.coordinate_ref_system_wkt.clone()
+                    };
+                    (crs, wkt)
+                }
+                None => {
+                    return Err(Error::new(
+                        ErrorKind::InvalidInput,
+                        "A target coordinate reference system must be given, either by an EPSG code (--epsg), a WKT file (--crs_file), or a base raster (--base) with a known system.",
+                    ))
+                }
+            }
+        };
+
+        // Each output cell centre is transformed back into the input's system to be sampled.
+        let transformation = CoordinateTransformation::new(&target_crs, &source_crs)?;
+
+        let mut configs = RasterConfigs {
+            ..Default::default()
+        };
+        if let Some(ref base) = base {
+            configs.rows = base.configs.rows;
+            configs.columns = base.configs.columns;
+            configs.north = base.configs.north;
+            configs.south = base.configs.south;
+            configs.east = base.configs.east;
+            configs.west = base.configs.west;
+            configs.resolution_x = base.configs.resolution_x;
+            configs.resolution_y = base.configs.resolution_y;
+        } else {
+            let (west, south, east, north) = match extent {
+                Some(e) => (e[0], e[1], e[2], e[3]),
+                None => transformed_extent(&input, &transformation.inverse()).ok_or_else(|| {
+                    Error::new(
+                        ErrorKind::InvalidInput,
+                        "The input raster does not lie within the area of the target coordinate reference system.",
+                    )
+                })?,
+            };
+            if cell_size <= 0f64 {
+                // keep about as many cells across the diagonal as the input has
+                let input_diagonal = ((input.configs.rows as f64).powi(2)
+                    + (input.configs.columns as f64).powi(2))
+                .sqrt();
+                cell_size = (east - west).hypot(north - south) / input_diagonal;
+            }
+            let rows = ((north - south) / cell_size).ceil().max(1f64) as usize;
+            let columns = ((east - west) / cell_size).ceil().max(1f64) as usize;
+            configs.rows = rows;
+            configs.columns = columns;
+            configs.north = north;
+            configs.south = north - rows as f64 * cell_size;
+            configs.west = west;
+            configs.east = west + columns as f64 * cell_size;
+            configs.resolution_x = cell_size;
+            configs.resolution_y = cell_size;
+        }
+        configs.nodata = input.configs.nodata;
+        configs.z_units = input.configs.z_units.clone();
+        configs.palette = input.configs.palette.clone();
+        if method.interpolates() {
+            configs.data_type = DataType::F32;
+            configs.photometric_interp = PhotometricInterpretation::Continuous;
+        } else {
+            configs.data_type = input.configs.data_type;
+            configs.photometric_interp = input.configs.photometric_interp;
+        }
+        configs.xy_units = if target_crs.is_geographic() {
+            "degrees".to_string()
+        } else if target_crs.linear_unit == 1f64 {
+            "metres".to_string()
+        } else {
+            "not specified".to_string()
+        };
+        configs.epsg_code = target_crs.identify_epsg().unwrap_or(0);
+        configs.coordinate_ref_system_wkt = target_wkt.clone();
+        configs.projection = target_wkt;
+
+        let mut output = Raster::initialize_using_config(&output_file, &configs);
+        let rows = output.configs.rows as isize;
+        let columns = output.configs.columns as isize;
+        let nodata = output.configs.nodata;
+        let (north, west) = (output.configs.north, output.configs.west);
+        let (resolution_x, resolution_y) =
+            (output.configs.resolution_x, output.configs.resolution_y);
+
+        let input = Arc::new(input);
+        let transformation = Arc::new(transformation);
+        let mut num_procs = num_cpus::get() as isize;
+        let configs = whitebox_common::configs::get_configs()?;
+        let max_procs = configs.max_procs;
+        if max_procs > 0 && max_procs < num_procs {
+            num_procs = max_procs;
+        }
+        let (tx, rx) = mpsc::channel();
+        for tid in 0..num_procs {
+            let input = input.clone();
+            let transformation = transformation.clone();
+            let tx = tx.clone();
+            thread::spawn(move || {
+                // the fractional row and column of the input at a location in the output,
+                // relative to the centre of the input's upper-left cell
+                let to_input = |x: f64, y: f64| {
+                    transformation.transform(x, y).map(|(sx, sy)| {
+                        (
+                            (input.configs.north - sy) / input.configs.resolution_y - 0.5,
+                            (sx - input.configs.west) / input.configs.resolution_x - 0.5,
+                        )
+                    })
+                };
+                for row in (0..rows).filter(|r| r % num_procs == tid) {
+                    let mut data = vec![nodata; columns as usize];
+                    if method == ResamplingMethod::Mode {
+                        // the mode is taken within the footprint of the output cell, which
+                        // is found from its transformed corners
+                        let edge = |r: isize| -> Vec<Option<(f64, f64)>> {
+                            (0..=columns)
+                                .map(|c| {
+                                    to_input(
+                                        west + c as f64 * resolution_x,
+                                        north - r as f64 * resolution_y,
+                                    )
+                                })
+                                .collect()
+                        };
+                        let top = edge(row);
+                        let bottom = edge(row + 1);
+                        for col in 0..columns as usize {
+                            let corners = [top[col], top[col + 1], bottom[col], bottom[col + 1]];
+                            if corners.iter().any(|corner| corner.is_none()) {
+                                continue;
+                            }
+                            let (mut min_r, mut min_c) = (f64::INFINITY, f64::INFINITY);
+                            let (mut max_r, mut max_c) = (f64::NEG_INFINITY, f64::NEG_INFINITY);
+                            for (r, c) in corners.iter().flatten() {
+                                min_r = min_r.min(*r);
+                                max_r = max_r.max(*r);
+                                min_c = min_c.min(*c);
+                                max_c = max_c.max(*c);
+                            }
+                            if let Some(z) = input.get_mode_in_window(min_r, min_c, max_r, max_c) {
+                                data[col] = z;
+                            }
+                        }
+                    } else {
+                        let y = north - (row as f64 + 0.5) * resolution_y;
+                        for col in 0..columns as usize {
+                            let x = west + (col as f64 + 0.5) * resolution_x;
+                            if let Some((r, c)) = to_input(x, y) {
+                                if let Some(z) = input.get_value_resampled(r, c, method) {
+                                    data[col] = z;
+                                }
+                            }
+                        }
+                    }
+                    tx.send((row, data)).unwrap();
+                }
+            });
+        }
+
+        for r in 0..rows {
+            let (row, data) = rx.recv().expect("Error receiving data from thread.");
+            output.set_row_data(row, data);
+            if verbose {
+                progress = (100.0_f64 * r as f64 / (rows - 1).max(1) as f64) as usize;
+                if progress != old_progress {
+                    println!("Progress: {}%", progress);
+                    old_progress = progress;
+                }
+            }
+        }
+
+        if !method.interpolates() {
+            output.configs.colour_table = input.configs.colour_table.clone();
+            output.configs.attribute_table = input.configs.attribute_table.clone();
+            output.update_attribute_counts();
+        }
+
+        let elapsed_time = get_formatted_elapsed_time(start);
+        output.add_metadata_entry(format!(
+            "Created by whitebox_tools\' {} tool",
+            self.get_tool_name()
+        ));
+        output.add_metadata_entry(format!("Input file: {}", input_file));
+        output.add_metadata_entry(format!("Resampling method: {:?}", method));
+        output.add_metadata_entry(format!("Elapsed Time (excluding I/O): {}", elapsed_time));
+
+        if verbose {
+            println!("Saving data...")
+        };
+        let _ = match output.write() {
+            Ok(_) => {
+                if verbose {
+                    println!("Output file written")
+                }
+            }
+            Err(e) => return Err(e.into()),
+        };
+        if verbose {
+            println!(
+                "{}",
+                &format!("Elapsed Time (excluding I/O): {}", elapsed_time)
+            );
+        }
+
+        Ok(())
+    }
+}
+
+/// Returns the extent (west, south, east, north) of the raster in the target system, found
+/// by transforming a grid of points spanning the raster, so that the curved edges and any
+/// bulges of the transformed raster are within it.
+fn transformed_extent(
+    input: &Raster,
+    transformation: &CoordinateTransformation,
+) -> Option<(f64, f64, f64, f64)> {
+    let n = 50;
+    let (mut west, mut south) = (f64::INFINITY, f64::INFINITY);
+    let (mut east, mut north) = (f64::NEG_INFINITY, f64::NEG_INFINITY);
+    for i in 0..=n {
+        let x = input.configs.west + (input.configs.east - input.configs.west) * i as f64 / n as f64;
+        for j in 0..=n {
+            let y = input.configs.south
+                + (input.configs.north - input.configs.south) * j as f64 / n as f64;
+            if let Some((tx, ty)) = transformation.transform(x, y) {
+                if tx.is_finite() && ty.is_finite() {
+                    west = west.min(tx);
+                    east = east.max(tx);
+                    south = south.min(ty);
+                    north = north.max(ty);
+                }
+            }
+        }
+    }
+    if west < east && south < north {
+        Some((west, south, east, north))
+    } else {
+        None
+    }
+}
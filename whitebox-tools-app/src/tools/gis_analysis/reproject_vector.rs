@@ -0,0 +1,400 @@
+/*
+This tool is part of the WhiteboxTools geospatial analysis library.
+License: MIT
+*/
+
+use whitebox_common::spatial_ref_system::{
+    esri_wkt_from_epsg, CoordinateReferenceSystem, CoordinateTransformation,
+};
+use whitebox_common::structures::Point2D;
+use crate::tools::*;
+use whitebox_vector::*;
+use std::env;
+use std::f64;
+use std::fs;
+use std::io::{Error, ErrorKind};
+use std::path;
+
+/// This tool transforms the coordinates of a vector file (`--input`) into another coordinate
+/// reference system, which is given either by its EPSG code (`--epsg`) or by a file containing
+/// its well-known text (WKT) description, such as another shapefile's .prj file (`--crs_file`).
+/// The input file must have a .prj file describing its coordinate reference system. The output
+/// file keeps the attributes of the input, and its .prj file describes the target system.
+///
+/// The z values of POINTZ, POLYLINEZ, POLYGONZ and MULTIPOINTZ files are taken to be heights
+/// above the ellipsoid, in metres, and so only change when the transformation shifts between
+/// datums. Measures (m values) are not changed.
+///
+/// A straight edge of a line or polygon in one coordinate reference system is generally a
+/// curve in another. The edges of POLYLINE and POLYGON features may therefore be densified,
+/// i.e. have vertices added along them before they are transformed, so that no two vertices
+/// are further apart than a distance (`--densify`), given in the units of the input. The z
+/// and m values of the added vertices are interpolated from the ends of their edges.
+///
+/// # See Also
+/// `ReprojectRaster`, `ReprojectLidar`
+pub struct ReprojectVector {
+    name: String,
+    description: String,
+    toolbox: String,
+    parameters: Vec<ToolParameter>,
+    example_usage: String,
+}
+
+impl ReprojectVector {
+    pub fn new() -> ReprojectVector {
+        // public constructor
+        let name = "ReprojectVector".to_string();
+        let toolbox = "GIS Analysis".to_string();
+        let description =
+            "Transforms a vector file into another coordinate reference system.".to_string();
+
+        let mut parameters = vec![];
+        parameters.push(ToolParameter {
+            name: "Input Vector File".to_owned(),
+            flags: vec!["-i".to_owned(), "--input".to_owned()],
+            description: "Input vector file.".to_owned(),
+            parameter_type: ParameterType::ExistingFile(ParameterFileType::Vector(
+                VectorGeometryType::Any,
+            )),
+            default_value: None,
+            optional: false,
+        });
+
+        parameters.push(ToolParameter {
+            name: "Output Vector File".to_owned(),
+            flags: vec!["-o".to_owned(), "--output".to_owned()],
+            description: "Output vector file.".to_owned(),
+            parameter_type: ParameterType::NewFile(ParameterFileType::Vector(
+                VectorGeometryType::Any,
+            )),
+            default_value: None,
+            optional: false,
+        });
+
+        parameters.push(ToolParameter {
+            name: "Target EPSG Code (optional)".to_owned(),
+            flags: vec!["--epsg".to_owned()],
+            description: "EPSG code of the target coordinate reference system (e.g. 32617).".to_owned(),
+            parameter_type: ParameterType::Integer,
+            default_value: None,
+            optional: true,
+        });
+
+        parameters.push(ToolParameter {
+            name: "Target CRS File (optional)".to_owned(),
+            flags: vec!["--crs_file".to_owned()],
+            description: "File containing the WKT of the target coordinate reference system, e.g. a .prj file. Not used when an EPSG code is specified.".to_owned(),
+            parameter_type: ParameterType::ExistingFile(ParameterFileType::Any),
+            default_value: None,
+            optional: true,
+        });
+
+        parameters.push(ToolParameter {
+            name: "Densification Distance (optional)".to_owned(),
+            flags: vec!["--densify".to_owned()],
+            description: "Optional maximum distance between the vertices of line and polygon edges, in the units of the input, to which edges are densified before they are transformed.".to_owned(),
+            parameter_type: ParameterType::Float,
+            default_value: None,
+            optional: true,
+        });
+
+        let sep: String = path::MAIN_SEPARATOR.to_string();
+        let e = format!("{}", env::current_exe().unwrap().display());
+        let mut parent = env::current_exe().unwrap();
+        parent.pop();
+        let p = format!("{}", parent.display());
+        let mut short_exe = e
+            .replace(&p, "")
+            .replace(".exe", "")
+            .replace(".", "")
+            .replace(&sep, "");
+        if e.contains(".exe") {
+            short_exe += ".exe";
+        }
+        let usage = format!(
+            ">>.*{0} -r={1} -v --wd=\"*path*to*data*\" -i=watersheds.shp -o=watersheds_wgs84.shp --epsg=4326 --densify=100.0",
+            short_exe, name
+        )
+        .replace("*", &sep);
+
+        ReprojectVector {
+            name: name,
+            description: description,
+            toolbox: toolbox,
+            parameters: parameters,
+            example_usage: usage,
+        }
+    }
+}
+
+impl WhiteboxTool for ReprojectVector {
+    fn get_source_file(&self) -> String {
+        String::from(file!())
+    }
+
+    fn get_tool_name(&self) -> String {
+        self.name.clone()
+    }
+
+    fn get_tool_description(&self) -> String {
+        self.description.clone()
+    }
+
+    fn get_tool_parameters(&self) -> String {
+        let mut s = String::from("{\"parameters\": [");
+        for i in 0..self.parameters.len() {
+            if i < self.parameters.len() - 1 {
+                s.push_str(&(self.parameters[i].to_string()));
+                s.push_str(",");
+            } else {
+                s.push_str(&(self.parameters[i].to_string()));
+            }
+        }
+        s.push_str("]}");
+        s
+    }
+
+    fn get_example_usage(&self) -> String {
+        self.example_usage.clone()
+    }
+
+    fn get_toolbox(&self) -> String {
+        self.toolbox.clone()
+    }
+
+    fn run<'a>(
+        &self,
+        args: Vec<String>,
+        working_directory: &'a str,
+        verbose: bool,
+    ) -> Result<(), Error> {
+        let mut input_file = String::new();
+        let mut output_file = String::new();
+        let mut epsg = 0u16;
+        let mut crs_file = String::new();
+        let mut densify = 0f64;
+
+        if args.len() == 0 {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                "Tool run with no parameters.",
+            ));
+        }
+        for i in 0..args.len() {
+            let mut arg = args[i].replace("\"", "");
+            arg = arg.replace("\'", "");
+            let cmd = arg.split("="); // in case an equals sign was used
+            let vec = cmd.collect::<Vec<&str>>();
+            let mut keyval = false;
+            if vec.len() > 1 {
+                keyval = true;
+            }
+            let flag_val = vec[0].to_lowercase().replace("--", "-");
+            let value = if keyval {
+                vec[1].to_string()
+            } else if i + 1 < args.len() {
+                args[i + 1].to_string()
+            } else {
+                String::new()
+            };
+            if flag_val == "-i" || flag_val == "-input" {
+                input_file = value;
+            } else if flag_val == "-o" || flag_val == "-output" {
+                output_file = value;
+            } else if flag_val == "-epsg" {
+                epsg = value
+                    .trim()
+                    .parse::<u16>()
+                    .expect(&format!("Error parsing {}", flag_val));
+            } else if flag_val == "-crs_file" {
+                crs_file = value;
+            } else if flag_val == "-densify" {
+                densify = value
+                    .trim()
+                    .parse::<f64>()
+                    .expect(&format!("Error parsing {}", flag_val));
+            }
+        }
+
+        if verbose {
+            let tool_name = self.get_tool_name();
+            let welcome_len = format!("* Welcome to {} *", tool_name).len().max(28);
+            // 28 = length of the 'Powered by' by statement.
+            println!("{}", "*".repeat(welcome_len));
+            println!("* Welcome to {} {}*", tool_name, " ".repeat(welcome_len - 15 - tool_name.len()));
+            println!("* Powered by WhiteboxTools {}*", " ".repeat(welcome_len - 28));
+            println!("* www.whiteboxgeo.com {}*", " ".repeat(welcome_len - 23));
+            println!("{}", "*".repeat(welcome_len));
+        }
+
+        let sep: String = path::MAIN_SEPARATOR.to_string();
+        let mut progress: usize;
+        let mut old_progress: usize = 1;
+
+        if !input_file.contains(&sep) && !input_file.contains("/") {
+            input_file = format!("{}{}", working_directory, input_file);
+        }
+        if !output_file.contains(&sep) && !output_file.contains("/") {
+            output_file = format!("{}{}", working_directory, output_file);
+        }
+        if !crs_file.is_empty() && !crs_file.contains(&sep) && !crs_file.contains("/") {
+            crs_file = format!("{}{}", working_directory, crs_file);
+        }
+
+        let input = Shapefile::read(&input_file)?;
+        let source_crs = input.get_coordinate_reference_system().ok_or_else(|| {
+            Error::new(
+                ErrorKind::InvalidInput,
+                "The coordinate reference system of the input vector is not specified; it must have a .prj file.",
+            )
+        })?;
+
+        let start = Instant::now();
+
+        let (target_crs, target_wkt) = if epsg > 0 {
+            (
+                CoordinateReferenceSystem::from_epsg(epsg)?,
+                esri_wkt_from_epsg(epsg),
+            )
+        } else if !crs_file.is_empty() {
+            let wkt = fs::read_to_string(&crs_file)?.trim().to_string();
+            (CoordinateReferenceSystem::from_wkt(&wkt)?, wkt)
+        } else {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                "A target coordinate reference system must be given, either by an EPSG code (--epsg) or a WKT file (--crs_file).",
+            ));
+        };
+        let transformation = CoordinateTransformation::new(&source_crs, &target_crs)?;
+
+        let mut output =
+            Shapefile::initialize_using_file(&output_file, &input, input.header.shape_type, true)?;
+        output.projection = target_wkt;
+
+        let base_shape_type = input.header.shape_type.base_shape_type();
+        if base_shape_type != ShapeType::PolyLine && base_shape_type != ShapeType::Polygon {
+            densify = 0f64;
+        }
+
+        for record_num in 0..input.num_records {
+            let record = input.get_record(record_num);
+            let out_record = if record.shape_type == ShapeType::Null {
+                record.clone()
+            } else {
+                reproject_geometry(record, &transformation, densify).ok_or_else(|| {
+                    Error::new(
+                        ErrorKind::InvalidData,
+                        format!("Feature {} could not be transformed into the target coordinate reference system.", record_num + 1),
+                    )
+                })?
+            };
+            output.add_record(out_record);
+
+            let atts = input.attributes.get_record(record_num);
+            output.attributes.add_record(atts.clone(), false);
+
+            if verbose {
+                progress =
+                    (100.0_f64 * (record_num + 1) as f64 / input.num_records as f64) as usize;
+                if progress != old_progress {
+                    println!("Progress: {}%", progress);
+                    old_progress = progress;
+                }
+            }
+        }
+
+        if verbose {
+            println!("Saving data...")
+        };
+        let _ = match output.write() {
+            Ok(_) => {
+                if verbose {
+                    println!("Output file written")
+                }
+            }
+            Err(e) => return Err(e),
+        };
+
+        let elapsed_time = get_formatted_elapsed_time(start);
+
+        if verbose {
+            println!("{}", &format!("Elapsed Time: {}", elapsed_time));
+        }
+
+        Ok(())
+    }
+}
+
+/// Transforms the vertices of a geometry, after densifying its edges so that no two are
+/// further apart than `densify`, if it is larger than zero. Returns None if any vertex
+/// cannot be transformed.
+fn reproject_geometry(
+    record: &ShapefileGeometry,
+    transformation: &CoordinateTransformation,
+    densify: f64,
+) -> Option<ShapefileGeometry> {
+    let has_z = record.has_z_data();
+    let has_m = record.has_m_data();
+    let mut geometry = ShapefileGeometry::new(record.shape_type);
+
+    // Points and multipoints have no parts, and are treated as a single part without edges.
+    let num_points = record.points.len();
+    let mut part_starts = if record.parts.is_empty() {
+        vec![0usize]
+    } else {
+        record.parts.iter().map(|p| *p as usize).collect::<Vec<usize>>()
+    };
+    part_starts.push(num_points);
+
+    for part in 0..part_starts.len() - 1 {
+        let (first, last) = (part_starts[part], part_starts[part + 1]);
+        if !record.parts.is_empty() {
+            geometry.parts.push(geometry.points.len() as i32);
+            geometry.num_parts += 1;
+        }
+        for i in first..last {
+            let z = if has_z { record.z_array[i] } else { 0f64 };
+            let m = if has_m { record.m_array[i] } else { 0f64 };
+            add_vertex(&mut geometry, transformation, record.points[i], z, m, has_z, has_m)?;
+            if densify > 0f64 && i + 1 < last {
+                let (p1, p2) = (record.points[i], record.points[i + 1]);
+                let num_segments = ((p2.x - p1.x).hypot(p2.y - p1.y) / densify).ceil() as usize;
+                for n in 1..num_segments {
+                    let t = n as f64 / num_segments as f64;
+                    let p = Point2D::new(p1.x + t * (p2.x - p1.x), p1.y + t * (p2.y - p1.y));
+                    let z = if has_z { z + t * (record.z_array[i + 1] - z) } else { 0f64 };
+                    let m = if has_m { m + t * (record.m_array[i + 1] - m) } else { 0f64 };
+                    add_vertex(&mut geometry, transformation, p, z, m, has_z, has_m)?;
+                }
+            }
+        }
+    }
+    Some(geometry)
+}
+
+/// Transforms a vertex and adds it, with its z value and measure if the geometry has them,
+/// to the geometry.
+fn add_vertex(
+    geometry: &mut ShapefileGeometry,
+    transformation: &CoordinateTransformation,
+    point: Point2D,
+    z: f64,
+    m: f64,
+    has_z: bool,
+    has_m: bool,
+) -> Option<()> {
+    let (x, y, z) = transformation.transform_3d(point.x, point.y, z)?;
+    geometry.add_point(Point2D::new(x, y));
+    if has_z {
+        geometry.z_array.push(z);
+        geometry.z_min = geometry.z_min.min(z);
+        geometry.z_max = geometry.z_max.max(z);
+    }
+    if has_m {
+        geometry.m_array.push(m);
+        geometry.m_min = geometry.m_min.min(m);
+        geometry.m_max = geometry.m_max.max(m);
+    }
+    Some(())
+}
@@ -350,7 +350,7 @@ impl WhiteboxTool for ShapeComplexityIndexRaster {
                     println!("Output file written")
                 }
             }
-            Err(e) => return Err(e),
+            Err(e) => return Err(e.into()),
         };
 
         if verbose {
@@ -292,7 +292,10 @@ impl WhiteboxTool for SplitWithLines {
 
         let mut polylines2: Vec<Polyline> = Vec::with_capacity(input2.get_total_num_parts());
         let mut bb2: Vec<BoundingBox> = Vec::with_capacity(input2.get_total_num_parts());
+        // the parts of record i of input2 are polylines2[first_part2[i]..first_part2[i + 1]]
+        let mut first_part2: Vec<usize> = Vec::with_capacity(input2.num_records + 1);
         for record_num in 0..input2.num_records {
+            first_part2.push(polylines2.len());
             let record = input2.get_record(record_num);
             for part in 0..record.num_parts as usize {
                 first_point_in_part = record.parts[part] as usize;
@@ -314,6 +317,7 @@ impl WhiteboxTool for SplitWithLines {
                 polylines2.push(pl);
             }
         }
+        first_part2.push(polylines2.len());
 
         if input1.header.shape_type.base_shape_type() == ShapeType::PolyLine {
             // create output file
@@ -515,13 +519,16 @@ impl WhiteboxTool for SplitWithLines {
             // hunt for intersections in the overlapping bounding boxes
             let mut fid = 1i32;
             for record_num1 in 0..polylines1.len() {
-                for record_num2 in 0..polylines2.len() {
-                    if bb1[record_num1].overlaps(bb2[record_num2]) {
-                        // find any intersections between the polylines
-                        find_split_points_at_line_intersections(
-                            &mut polylines1[record_num1],
-                            &mut polylines2[record_num2],
-                        );
+                // the spatial index of input2 gives the split features that may overlap
+                for record_num in input2.query_bbox(&bb1[record_num1]) {
+                    for record_num2 in first_part2[record_num]..first_part2[record_num + 1] {
+                        if bb1[record_num1].overlaps(bb2[record_num2]) {
+                            // find any intersections between the polylines
+                            find_split_points_at_line_intersections(
+                                &mut polylines1[record_num1],
+                                &mut polylines2[record_num2],
+                            );
+                        }
                     }
                 }
                 let split_lines = polylines1[record_num1].split();
@@ -645,13 +652,16 @@ impl WhiteboxTool for SplitWithLines {
             let mut polylines = vec![];
             let mut lengths = vec![];
             for record_num1 in 0..polylines1.len() {
-                for record_num2 in 0..polylines2.len() {
-                    if bb1[record_num1].overlaps(bb2[record_num2]) {
-                        // find any intersections between the polylines
-                        find_split_points_at_line_intersections(
-                            &mut polylines1[record_num1],
-                            &mut polylines2[record_num2],
-                        );
+                // the spatial index of input2 gives the split features that may overlap
+                for record_num in input2.query_bbox(&bb1[record_num1]) {
+                    for record_num2 in first_part2[record_num]..first_part2[record_num + 1] {
+                        if bb1[record_num1].overlaps(bb2[record_num2]) {
+                            // find any intersections between the polylines
+                            find_split_points_at_line_intersections(
+                                &mut polylines1[record_num1],
+                                &mut polylines2[record_num2],
+                            );
+                        }
                     }
                 }
                 let split_lines = polylines1[record_num1].split();
@@ -434,6 +434,9 @@ impl WhiteboxTool for TINGridding {
                         FieldData::Int(val) => {
                             z_values.push(val as f64);
                         }
+                        FieldData::BigInt(val) => {
+                            z_values.push(val as f64);
+                        }
                         FieldData::Real(val) => {
                             z_values.push(val);
                         }
@@ -564,7 +567,7 @@ impl WhiteboxTool for TINGridding {
                     println!("Output file written")
                 }
             }
-            Err(e) => return Err(e),
+            Err(e) => return Err(e.into()),
         };
 
         let elapsed_time = get_formatted_elapsed_time(start);
@@ -0,0 +1,352 @@
+/*
+This tool is part of the WhiteboxTools geospatial analysis library.
+License: MIT
+*/
+
+use whitebox_common::spatial_ref_system::{GeoidGrid, GeoidTransformation};
+use whitebox_raster::*;
+use crate::tools::*;
+use num_cpus;
+use std::env;
+use std::f64;
+use std::io::{Error, ErrorKind};
+use std::path;
+use std::sync::mpsc;
+use std::sync::Arc;
+use std::thread;
+
+/// This tool transforms the heights of a raster, such as a digital elevation model (DEM),
+/// between a vertical datum defined by a geoid model and the ellipsoid, using a grid of geoid
+/// heights (`--geoid`). By default, ellipsoidal heights, e.g. those of LiDAR or GNSS surveys,
+/// are converted to orthometric heights, i.e. heights above the geoid, by subtracting the
+/// height of the geoid above the ellipsoid at each grid cell. The `--to_ellipsoid` flag
+/// reverses the transformation, adding the geoid height to orthometric heights.
+///
+/// Geoid grids are read from local files in either the GTX format (.gtx) used by NOAA's
+/// VDatum and PROJ, or the NGS binary format (.bin) of the GEOID12B and GEOID18 models, and
+/// are interpolated bilinearly. The grid should be for the horizontal datum of the input
+/// raster, whose coordinate reference system must be known.
+///
+/// Geoid heights are in metres. If the z units of the input raster are feet or US survey
+/// feet, the geoid heights are converted into these units. Grid cells outside of the geoid
+/// grid are assigned NoData in the output. Rasters of integer data types are output as
+/// 32-bit floating point data.
+///
+/// # See Also
+/// `TransformLidarHeights`, `TransformVectorHeights`, `ReprojectRaster`
+pub struct TransformRasterHeights {
+    name: String,
+    description: String,
+    toolbox: String,
+    parameters: Vec<ToolParameter>,
+    example_usage: String,
+}
+
+impl TransformRasterHeights {
+    pub fn new() -> TransformRasterHeights {
+        // public constructor
+        let name = "TransformRasterHeights".to_string();
+        let toolbox = "GIS Analysis".to_string();
+        let description =
+            "Transforms raster heights between the ellipsoid and a geoid model.".to_string();
+
+        let mut parameters = vec![];
+        parameters.push(ToolParameter {
+            name: "Input File".to_owned(),
+            flags: vec!["-i".to_owned(), "--input".to_owned()],
+            description: "Input raster file.".to_owned(),
+            parameter_type: ParameterType::ExistingFile(ParameterFileType::Raster),
+            default_value: None,
+            optional: false,
+        });
+
+        parameters.push(ToolParameter {
+            name: "Geoid Grid File".to_owned(),
+            flags: vec!["--geoid".to_owned()],
+            description: "Input geoid grid file, in GTX (.gtx) or NGS (.bin) format.".to_owned(),
+            parameter_type: ParameterType::ExistingFile(ParameterFileType::Any),
+            default_value: None,
+            optional: false,
+        });
+
+        parameters.push(ToolParameter {
+            name: "Output File".to_owned(),
+            flags: vec!["-o".to_owned(), "--output".to_owned()],
+            description: "Output raster file.".to_owned(),
+            parameter_type: ParameterType::NewFile(ParameterFileType::Raster),
+            default_value: None,
+            optional: false,
+        });
+
+        parameters.push(ToolParameter {
+            name: "Convert orthometric heights to ellipsoidal heights?".to_owned(),
+            flags: vec!["--to_ellipsoid".to_owned()],
+            description: "Convert orthometric heights to ellipsoidal heights, rather than ellipsoidal heights to orthometric heights?".to_owned(),
+            parameter_type: ParameterType::Boolean,
+            default_value: Some("false".to_string()),
+            optional: true,
+        });
+
+        let sep: String = path::MAIN_SEPARATOR.to_string();
+        let e = format!("{}", env::current_exe().unwrap().display());
+        let mut parent = env::current_exe().unwrap();
+        parent.pop();
+        let p = format!("{}", parent.display());
+        let mut short_exe = e
+            .replace(&p, "")
+            .replace(".exe", "")
+            .replace(".", "")
+            .replace(&sep, "");
+        if e.contains(".exe") {
+            short_exe += ".exe";
+        }
+        let usage = format!(
+            ">>.*{0} -r={1} -v --wd=\"*path*to*data*\" -i=dem_ellipsoidal.tif --geoid=g2018u0.gtx -o=dem.tif",
+            short_exe, name
+        )
+        .replace("*", &sep);
+
+        TransformRasterHeights {
+            name: name,
+            description: description,
+            toolbox: toolbox,
+            parameters: parameters,
+            example_usage: usage,
+        }
+    }
+}
+
+impl WhiteboxTool for TransformRasterHeights {
+    fn get_source_file(&self) -> String {
+        String::from(file!())
+    }
+
+    fn get_tool_name(&self) -> String {
+        self.name.clone()
+    }
+
+    fn get_tool_description(&self) -> String {
+        self.description.clone()
+    }
+
+    fn get_tool_parameters(&self) -> String {
+        match serde_json::to_string(&self.parameters) {
+            Ok(json_str) => return format!("{{\"parameters\":{}}}", json_str),
+            Err(err) => return format!("{:?}", err),
+        }
+    }
+
+    fn get_example_usage(&self) -> String {
+        self.example_usage.clone()
+    }
+
+    fn get_toolbox(&self) -> String {
+        self.toolbox.clone()
+    }
+
+    fn run<'a>(
+        &self,
+        args: Vec<String>,
+        working_directory: &'a str,
+        verbose: bool,
+    ) -> Result<(), Error> {
+        let mut input_file = String::new();
+        let mut geoid_file = String::new();
+        let mut output_file = String::new();
+        let mut to_ellipsoid = false;
+
+        if args.len() == 0 {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                "Tool run with no parameters.",
+            ));
+        }
+        for i in 0..args.len() {
+            let mut arg = args[i].replace("\"", "");
+            arg = arg.replace("\'", "");
+            let cmd = arg.split("="); // in case an equals sign was used
+            let vec = cmd.collect::<Vec<&str>>();
+            let mut keyval = false;
+            if vec.len() > 1 {
+                keyval = true;
+            }
+            let flag_val = vec[0].to_lowercase().replace("--", "-");
+            let value = if keyval {
+                vec[1].to_string()
+            } else if i + 1 < args.len() {
+                args[i + 1].to_string()
+            } else {
+                String::new()
+            };
+            if flag_val == "-i" || flag_val == "-input" {
+                input_file = value;
+            } else if flag_val == "-geoid" {
+                geoid_file = value;
+            } else if flag_val == "-o" || flag_val == "-output" {
+                output_file = value;
+            } else if flag_val == "-to_ellipsoid" {
+                if vec.len() == 1 || !vec[1].to_string().to_lowercase().contains("false") {
+                    to_ellipsoid = true;
+                }
+            }
+        }
+
+        if verbose {
+            let tool_name = self.get_tool_name();
+            let welcome_len = format!("* Welcome to {} *", tool_name).len().max(28);
+            // 28 = length of the 'Powered by' by statement.
+            println!("{}", "*".repeat(welcome_len));
+            println!("* Welcome to {} {}*", tool_name, " ".repeat(welcome_len - 15 - tool_name.len()));
+            println!("* Powered by WhiteboxTools {}*", " ".repeat(welcome_len - 28));
+            println!("* www.whiteboxgeo.com {}*", " ".repeat(welcome_len - 23));
+            println!("{}", "*".repeat(welcome_len));
+        }
+
+        let sep: String = path::MAIN_SEPARATOR.to_string();
+
+        let mut progress: usize;
+        let mut old_progress: usize = 1;
+
+        if !input_file.contains(&sep) && !input_file.contains("/") {
+            input_file = format!("{}{}", working_directory, input_file);
+        }
+        if !geoid_file.contains(&sep) && !geoid_file.contains("/") {
+            geoid_file = format!("{}{}", working_directory, geoid_file);
+        }
+        if !output_file.contains(&sep) && !output_file.contains("/") {
+            output_file = format!("{}{}", working_directory, output_file);
+        }
+
+        if verbose {
+            println!("Reading data...")
+        };
+        let input = Raster::new(&input_file, "r")?;
+        let grid = GeoidGrid::read(&geoid_file)?;
+
+        let start = Instant::now();
+
+        if input.configs.photometric_interp == PhotometricInterpretation::RGB {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                "The input raster must not be an RGB colour composite.",
+            ));
+        }
+        let crs = input.get_coordinate_reference_system().ok_or_else(|| {
+            Error::new(
+                ErrorKind::InvalidInput,
+                "The coordinate reference system of the input raster is not specified.",
+            )
+        })?;
+        let transformation = GeoidTransformation::new(
+            grid,
+            &crs,
+            !to_ellipsoid,
+            metres_per_z_unit(&input.configs.z_units),
+        )?;
+
+        let mut configs = input.configs.clone();
+        if !configs.data_type.is_float() {
+            configs.data_type = DataType::F32;
+        }
+        let mut output = Raster::initialize_using_config(&output_file, &configs);
+        let rows = input.configs.rows as isize;
+        let columns = input.configs.columns as isize;
+        let nodata = input.configs.nodata;
+
+        let input = Arc::new(input);
+        let transformation = Arc::new(transformation);
+        let mut num_procs = num_cpus::get() as isize;
+        let configs = whitebox_common::configs::get_configs()?;
+        let max_procs = configs.max_procs;
+        if max_procs > 0 && max_procs < num_procs {
+            num_procs = max_procs;
+        }
+        let (tx, rx) = mpsc::channel();
+        for tid in 0..num_procs {
+            let input = input.clone();
+            let transformation = transformation.clone();
+            let tx = tx.clone();
+            thread::spawn(move || {
+                for row in (0..rows).filter(|r| r % num_procs == tid) {
+                    let y = input.get_y_from_row(row);
+                    let mut data = vec![nodata; columns as usize];
+                    let mut num_outside = 0usize;
+                    for col in 0..columns {
+                        let z = input.get_value(row, col);
+                        if z != nodata {
+                            let x = input.get_x_from_column(col);
+                            match transformation.transform(x, y, z) {
+                                Some(z) => data[col as usize] = z,
+                                None => num_outside += 1,
+                            }
+                        }
+                    }
+                    tx.send((row, data, num_outside)).unwrap();
+                }
+            });
+        }
+
+        let mut num_outside = 0usize;
+        for r in 0..rows {
+            let (row, data, n) = rx.recv().expect("Error receiving data from thread.");
+            output.set_row_data(row, data);
+            num_outside += n;
+            if verbose {
+                progress = (100.0_f64 * r as f64 / (rows - 1).max(1) as f64) as usize;
+                if progress != old_progress {
+                    println!("Progress: {}%", progress);
+                    old_progress = progress;
+                }
+            }
+        }
+        if num_outside > 0 {
+            println!(
+                "Warning: {} grid cells are outside of the geoid grid and have been assigned NoData.",
+                num_outside
+            );
+        }
+
+        let elapsed_time = get_formatted_elapsed_time(start);
+        output.add_metadata_entry(format!(
+            "Created by whitebox_tools\' {} tool",
+            self.get_tool_name()
+        ));
+        output.add_metadata_entry(format!("Input file: {}", input_file));
+        output.add_metadata_entry(format!("Geoid grid file: {}", geoid_file));
+        output.add_metadata_entry(format!("Elapsed Time (excluding I/O): {}", elapsed_time));
+
+        if verbose {
+            println!("Saving data...")
+        };
+        let _ = match output.write() {
+            Ok(_) => {
+                if verbose {
+                    println!("Output file written")
+                }
+            }
+            Err(e) => return Err(e.into()),
+        };
+        if verbose {
+            println!(
+                "{}",
+                &format!("Elapsed Time (excluding I/O): {}", elapsed_time)
+            );
+        }
+
+        Ok(())
+    }
+}
+
+/// Returns the length in metres of the z units of a raster, which are taken to be metres
+/// unless they are given as feet.
+fn metres_per_z_unit(z_units: &str) -> f64 {
+    let z_units = z_units.to_lowercase();
+    let is_feet = z_units.contains("feet") || z_units.contains("foot") || z_units == "ft";
+    if is_feet && z_units.contains("us") {
+        1200f64 / 3937f64
+    } else if is_feet {
+        0.3048
+    } else {
+        1f64
+    }
+}
@@ -0,0 +1,307 @@
+/*
+This tool is part of the WhiteboxTools geospatial analysis library.
+License: MIT
+*/
+
+use whitebox_common::spatial_ref_system::{GeoidGrid, GeoidTransformation};
+use crate::tools::*;
+use whitebox_vector::*;
+use std::env;
+use std::f64;
+use std::io::{Error, ErrorKind};
+use std::path;
+
+/// This tool transforms the z values of a 3-D vector file, i.e. a POINTZ, POLYLINEZ,
+/// POLYGONZ or MULTIPOINTZ shapefile, between the ellipsoid and a vertical datum defined by a
+/// geoid model, using a grid of geoid heights (`--geoid`). By default, ellipsoidal heights,
+/// e.g. those of GNSS surveys, are converted to orthometric heights, i.e. heights above the
+/// geoid, by subtracting the height of the geoid above the ellipsoid at each vertex. The
+/// `--to_ellipsoid` flag reverses the transformation.
+///
+/// Geoid grids are read from local files in either the GTX format (.gtx) used by NOAA's
+/// VDatum and PROJ, or the NGS binary format (.bin) of the GEOID12B and GEOID18 models, and
+/// are interpolated bilinearly. The grid should be for the horizontal datum of the input
+/// file, which must have a .prj file describing its coordinate reference system. The z values
+/// are taken to be in the linear units of a projected system and in metres otherwise. The
+/// tool reports an error if any vertex lies outside of the geoid grid. The output file keeps
+/// the coordinates, measures and attributes of the input.
+///
+/// # See Also
+/// `TransformRasterHeights`, `TransformLidarHeights`, `ReprojectVector`
+pub struct TransformVectorHeights {
+    name: String,
+    description: String,
+    toolbox: String,
+    parameters: Vec<ToolParameter>,
+    example_usage: String,
+}
+
+impl TransformVectorHeights {
+    pub fn new() -> TransformVectorHeights {
+        // public constructor
+        let name = "TransformVectorHeights".to_string();
+        let toolbox = "GIS Analysis".to_string();
+        let description =
+            "Transforms the z values of a vector file between the ellipsoid and a geoid model."
+                .to_string();
+
+        let mut parameters = vec![];
+        parameters.push(ToolParameter {
+            name: "Input Vector File".to_owned(),
+            flags: vec!["-i".to_owned(), "--input".to_owned()],
+            description: "Input vector file, with z values.".to_owned(),
+            parameter_type: ParameterType::ExistingFile(ParameterFileType::Vector(
+                VectorGeometryType::Any,
+            )),
+            default_value: None,
+            optional: false,
+        });
+
+        parameters.push(ToolParameter {
+            name: "Geoid Grid File".to_owned(),
+            flags: vec!["--geoid".to_owned()],
+            description: "Input geoid grid file, in GTX (.gtx) or NGS (.bin) format.".to_owned(),
+            parameter_type: ParameterType::ExistingFile(ParameterFileType::Any),
+            default_value: None,
+            optional: false,
+        });
+
+        parameters.push(ToolParameter {
+            name: "Output Vector File".to_owned(),
+            flags: vec!["-o".to_owned(), "--output".to_owned()],
+            description: "Output vector file.".to_owned(),
+            parameter_type: ParameterType::NewFile(ParameterFileType::Vector(
+                VectorGeometryType::Any,
+            )),
+            default_value: None,
+            optional: false,
+        });
+
+        parameters.push(ToolParameter {
+            name: "Convert orthometric heights to ellipsoidal heights?".to_owned(),
+            flags: vec!["--to_ellipsoid".to_owned()],
+            description: "Convert orthometric heights to ellipsoidal heights, rather than ellipsoidal heights to orthometric heights?".to_owned(),
+            parameter_type: ParameterType::Boolean,
+            default_value: Some("false".to_string()),
+            optional: true,
+        });
+
+        let sep: String = path::MAIN_SEPARATOR.to_string();
+        let e = format!("{}", env::current_exe().unwrap().display());
+        let mut parent = env::current_exe().unwrap();
+        parent.pop();
+        let p = format!("{}", parent.display());
+        let mut short_exe = e
+            .replace(&p, "")
+            .replace(".exe", "")
+            .replace(".", "")
+            .replace(&sep, "");
+        if e.contains(".exe") {
+            short_exe += ".exe";
+        }
+        let usage = format!(
+            ">>.*{0} -r={1} -v --wd=\"*path*to*data*\" -i=survey_points.shp --geoid=g2018u0.gtx -o=survey_points_orthometric.shp",
+            short_exe, name
+        )
+        .replace("*", &sep);
+
+        TransformVectorHeights {
+            name: name,
+            description: description,
+            toolbox: toolbox,
+            parameters: parameters,
+            example_usage: usage,
+        }
+    }
+}
+
+impl WhiteboxTool for TransformVectorHeights {
+    fn get_source_file(&self) -> String {
+        String::from(file!())
+    }
+
+    fn get_tool_name(&self) -> String {
+        self.name.clone()
+    }
+
+    fn get_tool_description(&self) -> String {
+        self.description.clone()
+    }
+
+    fn get_tool_parameters(&self) -> String {
+        let mut s = String::from("{\"parameters\": [");
+        for i in 0..self.parameters.len() {
+            if i < self.parameters.len() - 1 {
+                s.push_str(&(self.parameters[i].to_string()));
+                s.push_str(",");
+            } else {
+                s.push_str(&(self.parameters[i].to_string()));
+            }
+        }
+        s.push_str("]}");
+        s
+    }
+
+    fn get_example_usage(&self) -> String {
+        self.example_usage.clone()
+    }
+
+    fn get_toolbox(&self) -> String {
+        self.toolbox.clone()
+    }
+
+    fn run<'a>(
+        &self,
+        args: Vec<String>,
+        working_directory: &'a str,
+        verbose: bool,
+    ) -> Result<(), Error> {
+        let mut input_file = String::new();
+        let mut geoid_file = String::new();
+        let mut output_file = String::new();
+        let mut to_ellipsoid = false;
+
+        if args.len() == 0 {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                "Tool run with no parameters.",
+            ));
+        }
+        for i in 0..args.len() {
+            let mut arg = args[i].replace("\"", "");
+            arg = arg.replace("\'", "");
+            let cmd = arg.split("="); // in case an equals sign was used
+            let vec = cmd.collect::<Vec<&str>>();
+            let mut keyval = false;
+            if vec.len() > 1 {
+                keyval = true;
+            }
+            let flag_val = vec[0].to_lowercase().replace("--", "-");
+            let value = if keyval {
+                vec[1].to_string()
+            } else if i + 1 < args.len() {
+                args[i + 1].to_string()
+            } else {
+                String::new()
+            };
+            if flag_val == "-i" || flag_val == "-input" {
+                input_file = value;
+            } else if flag_val == "-geoid" {
+                geoid_file = value;
+            } else if flag_val == "-o" || flag_val == "-output" {
+                output_file = value;
+            } else if flag_val == "-to_ellipsoid" {
+                if vec.len() == 1 || !vec[1].to_string().to_lowercase().contains("false") {
+                    to_ellipsoid = true;
+                }
+            }
+        }
+
+        if verbose {
+            let tool_name = self.get_tool_name();
+            let welcome_len = format!("* Welcome to {} *", tool_name).len().max(28);
+            // 28 = length of the 'Powered by' by statement.
+            println!("{}", "*".repeat(welcome_len));
+            println!("* Welcome to {} {}*", tool_name, " ".repeat(welcome_len - 15 - tool_name.len()));
+            println!("* Powered by WhiteboxTools {}*", " ".repeat(welcome_len - 28));
+            println!("* www.whiteboxgeo.com {}*", " ".repeat(welcome_len - 23));
+            println!("{}", "*".repeat(welcome_len));
+        }
+
+        let sep: String = path::MAIN_SEPARATOR.to_string();
+        let mut progress: usize;
+        let mut old_progress: usize = 1;
+
+        if !input_file.contains(&sep) && !input_file.contains("/") {
+            input_file = format!("{}{}", working_directory, input_file);
+        }
+        if !geoid_file.contains(&sep) && !geoid_file.contains("/") {
+            geoid_file = format!("{}{}", working_directory, geoid_file);
+        }
+        if !output_file.contains(&sep) && !output_file.contains("/") {
+            output_file = format!("{}{}", working_directory, output_file);
+        }
+
+        let input = Shapefile::read(&input_file)?;
+        if input.header.shape_type.dimension() != ShapeTypeDimension::Z {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                "The input vector must be of a POINTZ, POLYLINEZ, POLYGONZ or MULTIPOINTZ shape type.",
+            ));
+        }
+        let crs = input.get_coordinate_reference_system().ok_or_else(|| {
+            Error::new(
+                ErrorKind::InvalidInput,
+                "The coordinate reference system of the input vector is not specified; it must have a .prj file.",
+            )
+        })?;
+        let grid = GeoidGrid::read(&geoid_file)?;
+
+        let start = Instant::now();
+
+        let metres_per_unit = if crs.is_projected() {
+            crs.linear_unit
+        } else {
+            1f64
+        };
+        let transformation = GeoidTransformation::new(grid, &crs, !to_ellipsoid, metres_per_unit)?;
+
+        let mut output =
+            Shapefile::initialize_using_file(&output_file, &input, input.header.shape_type, true)?;
+
+        for record_num in 0..input.num_records {
+            let mut record = input.get_record(record_num).clone();
+            if record.shape_type != ShapeType::Null {
+                record.z_min = f64::INFINITY;
+                record.z_max = f64::NEG_INFINITY;
+                for i in 0..record.points.len() {
+                    let p = record.points[i];
+                    let z = transformation
+                        .transform(p.x, p.y, record.z_array[i])
+                        .ok_or_else(|| {
+                            Error::new(
+                                ErrorKind::InvalidData,
+                                format!("Feature {} is outside of the geoid grid.", record_num + 1),
+                            )
+                        })?;
+                    record.z_array[i] = z;
+                    record.z_min = record.z_min.min(z);
+                    record.z_max = record.z_max.max(z);
+                }
+            }
+            output.add_record(record);
+
+            let atts = input.attributes.get_record(record_num);
+            output.attributes.add_record(atts.clone(), false);
+
+            if verbose {
+                progress =
+                    (100.0_f64 * (record_num + 1) as f64 / input.num_records as f64) as usize;
+                if progress != old_progress {
+                    println!("Progress: {}%", progress);
+                    old_progress = progress;
+                }
+            }
+        }
+
+        if verbose {
+            println!("Saving data...")
+        };
+        let _ = match output.write() {
+            Ok(_) => {
+                if verbose {
+                    println!("Output file written")
+                }
+            }
+            Err(e) => return Err(e),
+        };
+
+        let elapsed_time = get_formatted_elapsed_time(start);
+
+        if verbose {
+            println!("{}", &format!("Elapsed Time: {}", elapsed_time));
+        }
+
+        Ok(())
+    }
+}
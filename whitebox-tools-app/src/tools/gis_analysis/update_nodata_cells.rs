@@ -259,7 +259,7 @@ impl WhiteboxTool for UpdateNodataCells {
                     println!("Output file written")
                 }
             }
-            Err(e) => return Err(e),
+            Err(e) => return Err(e.into()),
         };
 
         if verbose {
@@ -7,6 +7,7 @@ License: MIT
 */
 
 use whitebox_raster::*;
+use whitebox_common::spatial_ref_system::CrsCheck;
 use crate::tools::*;
 use std::env;
 use std::f64;
@@ -242,6 +243,7 @@ impl WhiteboxTool for WeightedSum {
         let mut read_first_file = false;
         let mut i = 1;
         let mut j = 0usize;
+        let mut crs_check = CrsCheck::new();
         for value in vec {
             if !value.trim().is_empty() {
                 if verbose {
@@ -253,6 +255,12 @@ impl WhiteboxTool for WeightedSum {
                     input_file = format!("{}{}", working_directory, input_file);
                 }
                 let input = Raster::new(&input_file, "r")?;
+                if let Some(warning) = crs_check.check(
+                    &input.get_short_filename(),
+                    input.get_coordinate_reference_system(),
+                ) {
+                    println!("Warning: {}", warning);
+                }
                 in_nodata = input.configs.nodata;
                 if !read_first_file {
                     read_first_file = true;
@@ -273,12 +281,12 @@ impl WhiteboxTool for WeightedSum {
 
                 for row in 0..rows {
                     for col in 0..columns {
-                        if output[(row, col)] != out_nodata {
-                            in_val = input[(row, col)];
+                        if output.get_value(row, col) != out_nodata {
+                            in_val = input.get_value(row, col);
                             if in_val != in_nodata {
                                 output.increment(row, col, in_val * weights[j]);
                             } else {
-                                output[(row, col)] = out_nodata;
+                                output.set_value(row, col, out_nodata);
                             }
                         }
                     }
@@ -311,7 +319,7 @@ impl WhiteboxTool for WeightedSum {
                     println!("Output file written")
                 }
             }
-            Err(e) => return Err(e),
+            Err(e) => return Err(e.into()),
         };
 
         if verbose {
@@ -234,13 +234,13 @@ impl WhiteboxTool for AverageUpslopeFlowpathLength {
                 for row in (0..rows).filter(|r| r % num_procs == tid) {
                     let mut data: Vec<i8> = vec![-1i8; columns as usize];
                     for col in 0..columns {
-                        z = input[(row, col)];
+                        z = input.get_value(row, col);
                         if z != nodata {
                             dir = 0i8;
                             max_slope = f64::MIN;
                             neighbouring_nodata = false;
                             for i in 0..8 {
-                                z_n = input[(row + d_y[i], col + d_x[i])];
+                                z_n = input.get_value(row + d_y[i], col + d_x[i]);
                                 if z_n != nodata {
                                     slope = (z - z_n) / grid_lengths[i];
                                     if slope > max_slope && slope > 0f64 {
@@ -302,7 +302,7 @@ impl WhiteboxTool for AverageUpslopeFlowpathLength {
                 for row in (0..rows).filter(|r| r % num_procs == tid) {
                     let mut data: Vec<i8> = vec![-1i8; columns as usize];
                     for col in 0..columns {
-                        z = input[(row, col)];
+                        z = input.get_value(row, col);
                         if z != nodata {
                             count = 0i8;
                             for i in 0..8 {
@@ -330,7 +330,7 @@ impl WhiteboxTool for AverageUpslopeFlowpathLength {
             for col in 0..columns {
                 if num_inflowing[(row, col)] == 0i8 {
                     stack.push((row, col));
-                    output[(row, col)] = 0.0;
+                    output.set_value(row, col, 0.0);
                     num_flowpaths[(row, col)] = 1;
                 } else if num_inflowing[(row, col)] == -1i8 {
                     num_solved_cells += 1;
@@ -372,9 +372,9 @@ impl WhiteboxTool for AverageUpslopeFlowpathLength {
             if dir >= 0 {
                 row_n = row + d_y[dir as usize];
                 col_n = col + d_x[dir as usize];
-                length = output[(row, col)] + grid_lengths[dir as usize];
-                if output[(row_n, col_n)] == nodata {
-                    output[(row_n, col_n)] = length;
+                length = output.get_value(row, col) + grid_lengths[dir as usize];
+                if output.get_value(row_n, col_n) == nodata {
+                    output.set_value(row_n, col_n, length);
                 } else {
                     output.increment(row_n, col_n, length);
                 }
@@ -387,7 +387,11 @@ impl WhiteboxTool for AverageUpslopeFlowpathLength {
                 }
             }
 
-            output[(row, col)] = output[(row, col)] / num_flowpaths[(row, col)] as f64;
+            output.set_value(
+                row,
+                col,
+                output.get_value(row, col) / num_flowpaths[(row, col)] as f64,
+            );
 
             if verbose {
                 num_solved_cells += 1;
@@ -418,7 +422,7 @@ impl WhiteboxTool for AverageUpslopeFlowpathLength {
                     println!("Output file written")
                 }
             }
-            Err(e) => return Err(e),
+            Err(e) => return Err(e.into()),
         };
         if verbose {
             println!(
@@ -799,7 +799,7 @@ impl WhiteboxTool for BreachDepressions {
                     println!("Output file written")
                 }
             }
-            Err(e) => return Err(e),
+            Err(e) => return Err(e.into()),
         };
         if verbose {
             println!(
@@ -933,7 +933,7 @@ impl WhiteboxTool for BreachDepressionsLeastCost {
                     println!("Output file written")
                 }
             }
-            Err(e) => return Err(e),
+            Err(e) => return Err(e.into()),
         };
         if verbose {
             println!(
@@ -703,7 +703,7 @@ impl WhiteboxTool for BurnStreamsAtRoads {
                     println!("Output file written")
                 }
             }
-            Err(e) => return Err(e),
+            Err(e) => return Err(e.into()),
         };
         if verbose {
             println!(
@@ -343,7 +343,7 @@ impl WhiteboxTool for D8FlowAccumulation {
                                 max_slope = f64::MIN;
                                 neighbouring_nodata = false;
                                 for i in 0..8 {
-                                    z_n = input[(row + dy[i], col + dx[i])];
+                                    z_n = input.get_value(row + dy[i], col + dx[i]);
                                     if z_n != nodata {
                                         slope = (z - z_n) / grid_lengths[i];
                                         if slope > max_slope && slope > 0f64 {
@@ -547,7 +547,7 @@ impl WhiteboxTool for D8FlowAccumulation {
             let cell = stack.pop().expect("Error during pop operation.");
             row = cell.0;
             col = cell.1;
-            fa = output[(row, col)];
+            fa = output.get_value(row, col);
             num_inflowing.decrement(row, col, 1i8);
             dir = flow_dir.get_value(row, col);
             if dir >= 0 {
@@ -613,11 +613,19 @@ impl WhiteboxTool for D8FlowAccumulation {
                     } else {
                         dir = flow_dir.get_value(row, col);
                         if dir >= 0 {
-                            output[(row, col)] =
-                                (output[(row, col)] * cell_area / flow_widths[dir as usize]).ln();
+                            output.set_value(
+                                row,
+                                col,
+                                (output.get_value(row, col) * cell_area
+                                    / flow_widths[dir as usize])
+                                    .ln(),
+                            );
                         } else {
-                            output[(row, col)] =
-                                (output[(row, col)] * cell_area / flow_widths[3]).ln();
+                            output.set_value(
+                                row,
+                                col,
+                                (output.get_value(row, col) * cell_area / flow_widths[3]).ln(),
+                            );
                         }
                     }
                 }
@@ -685,7 +693,7 @@ impl WhiteboxTool for D8FlowAccumulation {
                     println!("Output file written")
                 }
             }
-            Err(e) => return Err(e),
+            Err(e) => return Err(e.into()),
         };
         if verbose {
             println!(
@@ -274,12 +274,12 @@ impl WhiteboxTool for D8Pointer {
                     // let mut data = vec![out_nodata; columns as usize];
                     let mut data = vec![out_nodata; columns as usize];
                     for col in 0..columns {
-                        z = input[(row, col)];
+                        z = input.get_value(row, col);
                         if z != nodata {
                             let mut dir = 0;
                             let mut max_slope = f64::MIN;
                             for i in 0..8 {
-                                z_n = input[(row + d_y[i], col + d_x[i])];
+                                z_n = input.get_value(row + d_y[i], col + d_x[i]);
                                 if z_n != nodata {
                                     slope = (z - z_n) / grid_lengths[i];
                                     if slope > max_slope && slope > 0f64 {
@@ -345,7 +345,7 @@ impl WhiteboxTool for D8Pointer {
                     println!("Output file written")
                 }
             }
-            Err(e) => return Err(e),
+            Err(e) => return Err(e.into()),
         };
         if verbose {
             println!(
@@ -471,7 +471,7 @@ impl WhiteboxTool for DepthInSink {
                     println!("Output file written")
                 }
             }
-            Err(e) => return Err(e),
+            Err(e) => return Err(e.into()),
         };
         if verbose {
             println!(
@@ -359,7 +359,7 @@ impl WhiteboxTool for DInfFlowAccumulation {
                     for row in (0..rows).filter(|r| r % num_procs == tid) {
                         let mut data: Vec<f64> = vec![nodata; columns as usize];
                         for col in 0..columns {
-                            e0 = input[(row, col)];
+                            e0 = input.get_value(row, col);
                             if e0 != nodata {
                                 dir = 360.0;
                                 max_slope = f64::MIN;
@@ -367,8 +367,8 @@ impl WhiteboxTool for DInfFlowAccumulation {
                                 for i in 0..8 {
                                     ac = ac_vals[i];
                                     af = af_vals[i];
-                                    e1 = input[(row + e1_row[i], col + e1_col[i])];
-                                    e2 = input[(row + e2_row[i], col + e2_col[i])];
+                                    e1 = input.get_value(row + e1_row[i], col + e1_col[i]);
+                                    e2 = input.get_value(row + e2_row[i], col + e2_col[i]);
                                     if e1 != nodata && e2 != nodata {
                                         if e0 > e1 && e0 > e2 {
                                             s1 = (e0 - e1) / grid_res;
@@ -536,7 +536,7 @@ impl WhiteboxTool for DInfFlowAccumulation {
             let cell = stack.pop().expect("Error during pop operation.");
             row = cell.0;
             col = cell.1;
-            fa = output[(row, col)];
+            fa = output.get_value(row, col);
             num_inflowing[(row, col)] = -1i8;
 
             dir = flow_dir[(row, col)];
@@ -659,10 +659,14 @@ impl WhiteboxTool for DInfFlowAccumulation {
         if log_transform {
             for row in 0..rows {
                 for col in 0..columns {
-                    if input[(row, col)] == nodata {
-                        output[(row, col)] = nodata;
+                    if input.get_value(row, col) == nodata {
+                        output.set_value(row, col, nodata);
                     } else {
-                        output[(row, col)] = (output[(row, col)] * cell_area / avg_cell_size).ln();
+                        output.set_value(
+                            row,
+                            col,
+                            (output.get_value(row, col) * cell_area / avg_cell_size).ln(),
+                        );
                     }
                 }
 
@@ -677,10 +681,14 @@ impl WhiteboxTool for DInfFlowAccumulation {
         } else {
             for row in 0..rows {
                 for col in 0..columns {
-                    if input[(row, col)] == nodata {
-                        output[(row, col)] = nodata;
+                    if input.get_value(row, col) == nodata {
+                        output.set_value(row, col, nodata);
                     } else {
-                        output[(row, col)] = output[(row, col)] * cell_area / avg_cell_size;
+                        output.set_value(
+                            row,
+                            col,
+                            output.get_value(row, col) * cell_area / avg_cell_size,
+                        );
                     }
                 }
 
@@ -715,7 +723,7 @@ impl WhiteboxTool for DInfFlowAccumulation {
                     println!("Output file written")
                 }
             }
-            Err(e) => return Err(e),
+            Err(e) => return Err(e.into()),
         };
         if verbose {
             println!(
@@ -447,7 +447,7 @@ impl WhiteboxTool for DownslopeFlowpathLength {
                     println!("Output file written")
                 }
             }
-            Err(e) => return Err(e),
+            Err(e) => return Err(e.into()),
         };
         if verbose {
             println!(
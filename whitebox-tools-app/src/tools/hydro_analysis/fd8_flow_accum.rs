@@ -417,8 +417,8 @@ impl WhiteboxTool for FD8FlowAccumulation {
             let cell = stack.pop().expect("Error during pop operation.");
             row = cell.0;
             col = cell.1;
-            z = input[(row, col)];
-            fa = output[(row, col)];
+            z = input.get_value(row, col);
+            fa = output.get_value(row, col);
             num_inflowing[(row, col)] = -1i8;
 
             total_weights = 0.0;
@@ -428,7 +428,7 @@ impl WhiteboxTool for FD8FlowAccumulation {
                 for i in 0..8 {
                     row_n = row + d_y[i];
                     col_n = col + d_x[i];
-                    z_n = input[(row_n, col_n)];
+                    z_n = input.get_value(row_n, col_n);
                     if z_n < z && z_n != nodata {
                         slope = (z - z_n) / grid_lengths[i];
                         weights[i] = slope.powf(exponent);
@@ -441,7 +441,7 @@ impl WhiteboxTool for FD8FlowAccumulation {
                 dir = 0i8;
                 max_slope = f64::MIN;
                 for i in 0..8 {
-                    z_n = input[(row + d_y[i], col + d_x[i])];
+                    z_n = input.get_value(row + d_y[i], col + d_x[i]);
                     if z_n != nodata {
                         slope = (z - z_n) / grid_lengths[i];
                         if slope > 0f64 {
@@ -495,10 +495,14 @@ impl WhiteboxTool for FD8FlowAccumulation {
         if log_transform {
             for row in 0..rows {
                 for col in 0..columns {
-                    if input[(row, col)] == nodata {
-                        output[(row, col)] = nodata;
+                    if input.get_value(row, col) == nodata {
+                        output.set_value(row, col, nodata);
                     } else {
-                        output[(row, col)] = (output[(row, col)] * cell_area / avg_cell_size).ln();
+                        output.set_value(
+                            row,
+                            col,
+                            (output.get_value(row, col) * cell_area / avg_cell_size).ln(),
+                        );
                     }
                 }
 
@@ -513,10 +517,14 @@ impl WhiteboxTool for FD8FlowAccumulation {
         } else {
             for row in 0..rows {
                 for col in 0..columns {
-                    if input[(row, col)] == nodata {
-                        output[(row, col)] = nodata;
+                    if input.get_value(row, col) == nodata {
+                        output.set_value(row, col, nodata);
                     } else {
-                        output[(row, col)] = output[(row, col)] * cell_area / avg_cell_size;
+                        output.set_value(
+                            row,
+                            col,
+                            output.get_value(row, col) * cell_area / avg_cell_size,
+                        );
                     }
                 }
 
@@ -551,7 +559,7 @@ impl WhiteboxTool for FD8FlowAccumulation {
                     println!("Output file written")
                 }
             }
-            Err(e) => return Err(e),
+            Err(e) => return Err(e.into()),
         };
         if verbose {
             println!(
@@ -231,11 +231,11 @@ impl WhiteboxTool for FD8Pointer {
                 for row in (0..rows).filter(|r| r % num_procs == tid) {
                     let mut data = vec![out_nodata; columns as usize];
                     for col in 0..columns {
-                        z = input[(row, col)];
+                        z = input.get_value(row, col);
                         if z != nodata {
                             dir = 0_f64;
                             for n in 0..8 {
-                                zn = input[(row + dy[n], col + dx[n])];
+                                zn = input.get_value(row + dy[n], col + dx[n]);
                                 if zn < z && zn != nodata {
                                     dir += (1 << n) as f64;
                                 }
@@ -281,7 +281,7 @@ impl WhiteboxTool for FD8Pointer {
                     println!("Output file written")
                 }
             }
-            Err(e) => return Err(e),
+            Err(e) => return Err(e.into()),
         };
         if verbose {
             println!(
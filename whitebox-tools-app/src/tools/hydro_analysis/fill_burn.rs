@@ -646,7 +646,7 @@ impl WhiteboxTool for FillBurn {
                     println!("Output file written")
                 }
             }
-            Err(e) => return Err(e),
+            Err(e) => return Err(e.into()),
         };
         if verbose {
             println!(
@@ -742,7 +742,7 @@ impl WhiteboxTool for FillDepressions {
                     println!("Output file written")
                 }
             }
-            Err(e) => return Err(e),
+            Err(e) => return Err(e.into()),
         };
         if verbose {
             println!(
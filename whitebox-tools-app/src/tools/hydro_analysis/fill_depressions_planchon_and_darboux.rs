@@ -577,7 +577,7 @@ impl WhiteboxTool for FillDepressionsPlanchonAndDarboux {
                     println!("Output file written")
                 }
             }
-            Err(e) => return Err(e),
+            Err(e) => return Err(e.into()),
         };
         if verbose {
             println!(
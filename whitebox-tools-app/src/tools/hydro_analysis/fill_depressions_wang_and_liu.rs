@@ -326,13 +326,13 @@ impl WhiteboxTool for FillDepressionsWangAndLiu {
                 row_n = row + dy[n];
                 col_n = col + dx[n];
                 zin_n = input.get_value(row_n, col_n);
-                zout_n = output[(row_n, col_n)];
+                zout_n = output.get_value(row_n, col_n);
                 if zout_n == background_val {
                     if zin_n == nodata {
                         output.set_value(row_n, col_n, nodata);
                         queue.push_back((row_n, col_n));
                     } else {
-                        output[(row_n, col_n)] = zin_n;
+                        output.set_value(row_n, col_n, zin_n);
                         // Push it onto the priority queue for the priority flood operation
                         minheap.push(GridCell {
                             row: row_n,
@@ -363,18 +363,18 @@ impl WhiteboxTool for FillDepressionsWangAndLiu {
             let cell = minheap.pop().expect("Error during pop operation.");
             row = cell.row;
             col = cell.column;
-            zout = output[(row, col)];
+            zout = output.get_value(row, col);
             for n in 0..8 {
                 row_n = row + dy[n];
                 col_n = col + dx[n];
-                zout_n = output[(row_n, col_n)];
+                zout_n = output.get_value(row_n, col_n);
                 if zout_n == background_val {
-                    zin_n = input[(row_n, col_n)];
+                    zin_n = input.get_value(row_n, col_n);
                     if zin_n != nodata {
                         if zin_n < (zout + small_num) {
                             zin_n = zout + small_num;
                         } // We're in a depression. Raise the elevation.
-                        output[(row_n, col_n)] = zin_n;
+                        output.set_value(row_n, col_n, zin_n);
                         minheap.push(GridCell {
                             row: row_n,
                             column: col_n,
@@ -382,7 +382,7 @@ impl WhiteboxTool for FillDepressionsWangAndLiu {
                         });
                     } else {
                         // Interior nodata cells are still treated as nodata and are not filled.
-                        output[(row_n, col_n)] = nodata;
+                        output.set_value(row_n, col_n, nodata);
                         num_solved_cells += 1;
                     }
                 }
@@ -636,7 +636,7 @@ impl WhiteboxTool for FillDepressionsWangAndLiu {
                     println!("Output file written")
                 }
             }
-            Err(e) => return Err(e),
+            Err(e) => return Err(e.into()),
         };
         if verbose {
             println!(
@@ -213,7 +213,7 @@ impl WhiteboxTool for FillSingleCellPits {
                 for row in (0..rows).filter(|r| r % num_procs == tid) {
                     let mut data = vec![nodata; columns as usize];
                     for col in 0..columns {
-                        z = input[(row, col)];
+                        z = input.get_value(row, col);
                         if z != nodata {
                             flag = true;
                             min_zn = f64::INFINITY;
@@ -268,7 +268,7 @@ impl WhiteboxTool for FillSingleCellPits {
                     println!("Output file written")
                 }
             }
-            Err(e) => return Err(e),
+            Err(e) => return Err(e.into()),
         };
         if verbose {
             println!(
@@ -257,7 +257,7 @@ impl WhiteboxTool for FindNoFlowCells {
                     println!("Output file written")
                 }
             }
-            Err(e) => return Err(e),
+            Err(e) => return Err(e.into()),
         };
 
         if verbose {
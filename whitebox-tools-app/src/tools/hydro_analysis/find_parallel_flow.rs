@@ -253,13 +253,13 @@ impl WhiteboxTool for FindParallelFlow {
                 for row in (0..rows).filter(|r| r % num_procs == tid) {
                     let mut data = vec![nodata; columns as usize];
                     for col in 0..columns {
-                        z = pntr[(row, col)];
+                        z = pntr.get_value(row, col);
                         stream_val = streams[(row, col)];
                         if z != nodata && stream_val != streams_nodata && stream_val > 0f64 {
                             is_parallel = false;
                             for n in 0..8 {
                                 if z != outflowing_vals[n] {
-                                    zn = pntr[(row + dy[n], col + dx[n])];
+                                    zn = pntr.get_value(row + dy[n], col + dx[n]);
                                     stream_valn = streams[(row + dy[n], col + dx[n])];
                                     if zn == z
                                         && zn != inflowing_vals[n]
@@ -315,7 +315,7 @@ impl WhiteboxTool for FindParallelFlow {
                     println!("Output file written")
                 }
             }
-            Err(e) => return Err(e),
+            Err(e) => return Err(e.into()),
         };
 
         if verbose {
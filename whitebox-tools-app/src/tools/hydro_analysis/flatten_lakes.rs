@@ -576,7 +576,7 @@ impl WhiteboxTool for FlattenLakes {
                     println!("Output file written")
                 }
             }
-            Err(e) => return Err(e),
+            Err(e) => return Err(e.into()),
         };
 
         if verbose {
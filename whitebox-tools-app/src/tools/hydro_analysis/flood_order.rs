@@ -261,12 +261,12 @@ impl WhiteboxTool for FloodOrder {
             for n in 0..8 {
                 row_n = row + dy[n];
                 col_n = col + dx[n];
-                zin_n = input[(row_n, col_n)];
+                zin_n = input.get_value(row_n, col_n);
                 zout_n = filled_dem[(row_n, col_n)];
                 if zout_n == background_val {
                     if zin_n == nodata {
                         filled_dem[(row_n, col_n)] = nodata;
-                        output[(row_n, col_n)] = nodata;
+                        output.set_value(row_n, col_n, nodata);
                         queue.push_back((row_n, col_n));
                     } else {
                         filled_dem[(row_n, col_n)] = zin_n;
@@ -297,14 +297,14 @@ impl WhiteboxTool for FloodOrder {
             row = cell.row;
             col = cell.column;
             zout = filled_dem[(row, col)];
-            output[(row, col)] = order_val;
+            output.set_value(row, col, order_val);
             order_val += 1f64;
             for n in 0..8 {
                 row_n = row + dy[n];
                 col_n = col + dx[n];
                 zout_n = filled_dem[(row_n, col_n)];
                 if zout_n == background_val {
-                    zin_n = input[(row_n, col_n)];
+                    zin_n = input.get_value(row_n, col_n);
                     if zin_n != nodata {
                         if zin_n < zout {
                             zin_n = zout;
@@ -317,7 +317,7 @@ impl WhiteboxTool for FloodOrder {
                         });
                     } else {
                         // Interior nodata cells are still treated as nodata and are not filled.
-                        output[(row_n, col_n)] = nodata;
+                        output.set_value(row_n, col_n, nodata);
                         num_solved_cells += 1;
                     }
                 }
@@ -350,7 +350,7 @@ impl WhiteboxTool for FloodOrder {
                     println!("Output file written")
                 }
             }
-            Err(e) => return Err(e),
+            Err(e) => return Err(e.into()),
         };
 
         if verbose {
@@ -340,10 +340,10 @@ impl WhiteboxTool for FlowAccumulationFullWorkflow {
                 for row in (0..rows).filter(|r| r % num_procs == tid) {
                     let mut data = vec![nodata; columns as usize];
                     for col in 0..columns {
-                        z = input[(row, col)];
+                        z = input.get_value(row, col);
                         if z != nodata {
                             for c in 0..8 {
-                                n[c] = input[(row + dy[c], col + dx[c])];
+                                n[c] = input.get_value(row + dy[c], col + dx[c]);
                                 if n[c] != nodata {
                                     n[c] = n[c] * z_factor;
                                 } else {
@@ -440,11 +440,11 @@ impl WhiteboxTool for FlowAccumulationFullWorkflow {
             for n in 0..8 {
                 row_n = row + dy[n];
                 col_n = col + dx[n];
-                zin_n = input[(row_n, col_n)];
-                zout_n = output[(row_n, col_n)];
+                zin_n = input.get_value(row_n, col_n);
+                zout_n = output.get_value(row_n, col_n);
                 if zout_n == background_val {
                     if zin_n == nodata {
-                        output[(row_n, col_n)] = nodata;
+                        output.set_value(row_n, col_n, nodata);
                         queue.push_back((row_n, col_n));
                     } else {
                         // see if it's the lowest of its neighbours
@@ -452,13 +452,13 @@ impl WhiteboxTool for FlowAccumulationFullWorkflow {
                         for p in 0..8 {
                             y = row_n + dy[p];
                             x = col_n + dx[p];
-                            if input[(y, x)] < zin_n && input[(y, x)] != nodata {
+                            if input.get_value(y, x) < zin_n && input.get_value(y, x) != nodata {
                                 is_lowest = false;
                                 break;
                             }
                         }
                         if is_lowest {
-                            output[(row_n, col_n)] = zin_n;
+                            output.set_value(row_n, col_n, zin_n);
                             // Push it onto the priority queue for the priority flood operation
                             minheap.push(GridCell {
                                 row: row_n,
@@ -493,13 +493,13 @@ impl WhiteboxTool for FlowAccumulationFullWorkflow {
             let cell = minheap.pop().expect("Error during pop operation.");
             row = cell.row;
             col = cell.column;
-            zout = output[(row, col)];
+            zout = output.get_value(row, col);
             for n in 0..8 {
                 row_n = row + dy[n];
                 col_n = col + dx[n];
-                zout_n = output[(row_n, col_n)];
+                zout_n = output.get_value(row_n, col_n);
                 if zout_n == background_val {
-                    zin_n = input[(row_n, col_n)];
+                    zin_n = input.get_value(row_n, col_n);
                     if zin_n != nodata {
                         flow_dir[(row_n, col_n)] = back_link[n];
 
@@ -507,7 +507,7 @@ impl WhiteboxTool for FlowAccumulationFullWorkflow {
                         // output[(row_n, col_n)] = zin_n;
                         // minheap.push(GridCell{ row: row_n, column: col_n, priority: zin_n });
 
-                        output[(row_n, col_n)] = zin_n;
+                        output.set_value(row_n, col_n, zin_n);
                         minheap.push(GridCell {
                             row: row_n,
                             column: col_n,
@@ -517,7 +517,7 @@ impl WhiteboxTool for FlowAccumulationFullWorkflow {
                             // Trace the flowpath back to a lower cell, if it exists.
                             x = col_n;
                             y = row_n;
-                            z_target = output[(row_n, col_n)];
+                            z_target = output.get_value(row_n, col_n);
                             flag = true;
                             while flag {
                                 dir = flow_dir[(y, x)];
@@ -525,8 +525,8 @@ impl WhiteboxTool for FlowAccumulationFullWorkflow {
                                     y += dy[dir as usize];
                                     x += dx[dir as usize];
                                     z_target -= small_num;
-                                    if output[(y, x)] > z_target {
-                                        output[(y, x)] = z_target;
+                                    if output.get_value(y, x) > z_target {
+                                        output.set_value(y, x, z_target);
                                     } else {
                                         flag = false;
                                     }
@@ -537,7 +537,7 @@ impl WhiteboxTool for FlowAccumulationFullWorkflow {
                         }
                     } else {
                         // Interior nodata cells are still treated as nodata and are not filled.
-                        output[(row_n, col_n)] = nodata;
+                        output.set_value(row_n, col_n, nodata);
                         num_solved_cells += 1;
                     }
                 } else if zout_n > zout && zout_n != nodata && aspect[(row_n, col_n)] != nodata {
@@ -607,7 +607,7 @@ impl WhiteboxTool for FlowAccumulationFullWorkflow {
                     println!("Output file written")
                 }
             }
-            Err(e) => return Err(e),
+            Err(e) => return Err(e.into()),
         };
 
         // calculate the number of inflowing cells
@@ -628,7 +628,7 @@ impl WhiteboxTool for FlowAccumulationFullWorkflow {
                 for row in (0..rows).filter(|r| r % num_procs == tid) {
                     let mut data: Vec<i8> = vec![-1i8; columns as usize];
                     for col in 0..columns {
-                        z = input[(row, col)];
+                        z = input.get_value(row, col);
                         if z != nodata {
                             count = 0i8;
                             for i in 0..8 {
@@ -676,7 +676,7 @@ impl WhiteboxTool for FlowAccumulationFullWorkflow {
             let cell = stack.pop().expect("Error during pop operation.");
             row = cell.0;
             col = cell.1;
-            fa = output[(row, col)];
+            fa = output.get_value(row, col);
             num_inflowing.decrement(row, col, 1i8);
             dir = flow_dir[(row, col)];
             if dir >= 0 {
@@ -726,18 +726,26 @@ impl WhiteboxTool for FlowAccumulationFullWorkflow {
         if log_transform {
             for row in 0..rows {
                 for col in 0..columns {
-                    if input[(row, col)] == nodata {
-                        output[(row, col)] = nodata;
+                    if input.get_value(row, col) == nodata {
+                        output.set_value(row, col, nodata);
                     } else {
                         let dir = flow_dir[(row, col)];
                         if dir >= 0 {
-                            output[(row, col)] =
-                                (output[(row, col)] * cell_area / flow_widths[dir as usize]).ln();
-                            pntr[(row, col)] = pntr_vals[flow_dir[(row, col)] as usize];
+                            output.set_value(
+                                row,
+                                col,
+                                (output.get_value(row, col) * cell_area
+                                    / flow_widths[dir as usize])
+                                    .ln(),
+                            );
+                            pntr.set_value(row, col, pntr_vals[flow_dir[(row, col)] as usize]);
                         } else {
-                            output[(row, col)] =
-                                (output[(row, col)] * cell_area / flow_widths[3]).ln();
-                            pntr[(row, col)] = 0f64;
+                            output.set_value(
+                                row,
+                                col,
+                                (output.get_value(row, col) * cell_area / flow_widths[3]).ln(),
+                            );
+                            pntr.set_value(row, col, 0f64);
                         }
                     }
                 }
@@ -753,17 +761,24 @@ impl WhiteboxTool for FlowAccumulationFullWorkflow {
         } else {
             for row in 0..rows {
                 for col in 0..columns {
-                    if input[(row, col)] == nodata {
-                        output[(row, col)] = nodata;
+                    if input.get_value(row, col) == nodata {
+                        output.set_value(row, col, nodata);
                     } else {
                         let dir = flow_dir[(row, col)];
                         if dir >= 0 {
-                            output[(row, col)] =
-                                output[(row, col)] * cell_area / flow_widths[dir as usize];
-                            pntr[(row, col)] = pntr_vals[flow_dir[(row, col)] as usize];
+                            output.set_value(
+                                row,
+                                col,
+                                output.get_value(row, col) * cell_area / flow_widths[dir as usize],
+                            );
+                            pntr.set_value(row, col, pntr_vals[flow_dir[(row, col)] as usize]);
                         } else {
-                            output[(row, col)] = output[(row, col)] * cell_area / flow_widths[3];
-                            pntr[(row, col)] = 0f64;
+                            output.set_value(
+                                row,
+                                col,
+                                output.get_value(row, col) * cell_area / flow_widths[3],
+                            );
+                            pntr.set_value(row, col, 0f64);
                         }
                     }
                 }
@@ -798,7 +813,7 @@ impl WhiteboxTool for FlowAccumulationFullWorkflow {
                     println!("Output file written")
                 }
             }
-            Err(e) => return Err(e),
+            Err(e) => return Err(e.into()),
         };
 
         output.configs.palette = "blueyellow.plt".to_string();
@@ -821,7 +836,7 @@ impl WhiteboxTool for FlowAccumulationFullWorkflow {
                     println!("Output file written")
                 }
             }
-            Err(e) => return Err(e),
+            Err(e) => return Err(e.into()),
         };
         if verbose {
             println!(
@@ -249,14 +249,14 @@ impl WhiteboxTool for FlowLengthDiff {
         let (mut x, mut y): (isize, isize);
         for row in 0..rows {
             for col in 0..columns {
-                if pntr[(row, col)] >= 0.0 && pntr[(row, col)] != nodata {
+                if pntr.get_value(row, col) >= 0.0 && pntr.get_value(row, col) != nodata {
                     dist = 0f64;
                     flag = false;
                     x = col;
                     y = row;
                     while !flag {
                         // find its downslope neighbour
-                        dir = pntr[(y, x)];
+                        dir = pntr.get_value(y, x);
                         if dir > 0f64 && dir != nodata {
                             if dir > 128f64 || pntr_matches[dir as usize] == 999 {
                                 return Err(Error::new(ErrorKind::InvalidInput,
@@ -285,7 +285,7 @@ impl WhiteboxTool for FlowLengthDiff {
                         dfl[(y, x)] = dist;
 
                         // find its downslope neighbour
-                        dir = pntr[(y, x)];
+                        dir = pntr.get_value(y, x);
                         if dir > 0f64 && dir != nodata {
                             // move x and y accordingly
                             c = pntr_matches[dir as usize];
@@ -337,12 +337,12 @@ impl WhiteboxTool for FlowLengthDiff {
                         }
                     }
                     if max_abs_diff != f64::NEG_INFINITY {
-                        output[(row, col)] = max_abs_diff;
+                        output.set_value(row, col, max_abs_diff);
                     } else {
-                        output[(row, col)] = out_nodata;
+                        output.set_value(row, col, out_nodata);
                     }
                 } else {
-                    output[(row, col)] = out_nodata;
+                    output.set_value(row, col, out_nodata);
                 }
             }
             if verbose {
@@ -374,7 +374,7 @@ impl WhiteboxTool for FlowLengthDiff {
                     println!("Output file written")
                 }
             }
-            Err(e) => return Err(e),
+            Err(e) => return Err(e.into()),
         };
         if verbose {
             println!(
@@ -831,7 +831,7 @@ impl WhiteboxTool for ImpoundmentSizeIndex {
                         println!("Output file written")
                     }
                 }
-                Err(e) => return Err(e),
+                Err(e) => return Err(e.into()),
             };
         }
         
@@ -884,7 +884,7 @@ impl WhiteboxTool for ImpoundmentSizeIndex {
                         println!("Output file written")
                     }
                 }
-                Err(e) => return Err(e),
+                Err(e) => return Err(e.into()),
             };
         }
         
@@ -903,7 +903,7 @@ impl WhiteboxTool for ImpoundmentSizeIndex {
                         println!("Output file written")
                     }
                 }
-                Err(e) => return Err(e),
+                Err(e) => return Err(e.into()),
             };
         }
 
@@ -921,7 +921,7 @@ impl WhiteboxTool for ImpoundmentSizeIndex {
                         println!("Output file written")
                     }
                 }
-                Err(e) => return Err(e),
+                Err(e) => return Err(e.into()),
             };
         }
 
@@ -940,7 +940,7 @@ impl WhiteboxTool for ImpoundmentSizeIndex {
                         println!("Output file written")
                     }
                 }
-                Err(e) => return Err(e),
+                Err(e) => return Err(e.into()),
             };
         }
 
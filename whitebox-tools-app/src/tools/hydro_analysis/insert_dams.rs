@@ -488,7 +488,7 @@ impl WhiteboxTool for InsertDams {
                     println!("Output file written")
                 }
             }
-            Err(e) => return Err(e),
+            Err(e) => return Err(e.into()),
         };
 
         if verbose {
@@ -619,7 +619,7 @@ impl WhiteboxTool for RaiseWalls {
                     println!("Output file written")
                 }
             }
-            Err(e) => return Err(e),
+            Err(e) => return Err(e.into()),
         };
 
         if !output_something && verbose {
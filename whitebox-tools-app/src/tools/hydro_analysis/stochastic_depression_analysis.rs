@@ -786,7 +786,7 @@ impl WhiteboxTool for StochasticDepressionAnalysis {
                     println!("Output file written")
                 }
             }
-            Err(e) => return Err(e),
+            Err(e) => return Err(e.into()),
         };
         if verbose {
             println!(
@@ -285,13 +285,13 @@ impl WhiteboxTool for StrahlerOrderBasins {
         let mut dir: usize;
         for row in 0..rows {
             for col in 0..columns {
-                if streams[(row, col)] > 0.0 {
+                if streams.get_value(row, col) > 0.0 {
                     // see if it is a headwater location
                     num_neighbouring_stream_cells = 0i8;
                     for c in 0..8 {
                         x = col + dx[c];
                         y = row + dy[c];
-                        if streams[(y, x)] > 0.0 && pntr[(y, x)] == inflowing_vals[c] {
+                        if streams.get_value(y, x) > 0.0 && pntr.get_value(y, x) == inflowing_vals[c] {
                             num_neighbouring_stream_cells += 1;
                         }
                     }
@@ -304,8 +304,8 @@ impl WhiteboxTool for StrahlerOrderBasins {
                         flag = true;
                         while flag {
                             // find the downslope neighbour
-                            if pntr[(y, x)] > 0.0 {
-                                dir = pntr[(y, x)] as usize;
+                            if pntr.get_value(y, x) > 0.0 {
+                                dir = pntr.get_value(y, x) as usize;
                                 if dir > 128 || pntr_matches[dir] == 999 {
                                     return Err(Error::new(ErrorKind::InvalidInput,
                                         "An unexpected value has been identified in the pointer image. This tool requires a pointer grid that has been created using either the D8 or Rho8 tools."));
@@ -314,7 +314,7 @@ impl WhiteboxTool for StrahlerOrderBasins {
                                 x += dx[pntr_matches[dir]];
                                 y += dy[pntr_matches[dir]];
 
-                                if streams[(y, x)] <= 0.0 {
+                                if streams.get_value(y, x) <= 0.0 {
                                     //it's not a stream cell
                                     flag = false;
                                 } else {
@@ -328,8 +328,8 @@ impl WhiteboxTool for StrahlerOrderBasins {
                                         for d in 0..8 {
                                             x2 = x + dx[d];
                                             y2 = y + dy[d];
-                                            if streams[(y2, x2)] > 0.0
-                                                && pntr[(y2, x2)] == inflowing_vals[d]
+                                            if streams.get_value(y2, x2) > 0.0
+                                                && pntr.get_value(y2, x2) == inflowing_vals[d]
                                                 && pourpts[(y2, x2)] == current_order
                                             {
                                                 num_neighbouring_stream_cells += 1;
@@ -347,7 +347,7 @@ impl WhiteboxTool for StrahlerOrderBasins {
                                     }
                                 }
                             } else {
-                                if streams[(y, x)] > 0.0 {
+                                if streams.get_value(y, x) > 0.0 {
                                     //it is a valid stream cell and probably just has no downslope neighbour (e.g. at the edge of the grid)
                                     pourpts.increment(y, x, 1.0);
                                 }
@@ -380,12 +380,12 @@ impl WhiteboxTool for StrahlerOrderBasins {
         let mut z: f64;
         for row in 0..rows {
             for col in 0..columns {
-                if pntr[(row, col)] == pntr_nodata {
-                    output[(row, col)] = nodata;
+                if pntr.get_value(row, col) == pntr_nodata {
+                    output.set_value(row, col, nodata);
                 }
                 z = pourpts[(row, col)];
                 if z != nodata && z > 0.0 {
-                    output[(row, col)] = z;
+                    output.set_value(row, col, z);
                 }
             }
             if verbose {
@@ -403,20 +403,20 @@ impl WhiteboxTool for StrahlerOrderBasins {
         let mut c: usize;
         for row in 0..rows {
             for col in 0..columns {
-                if output[(row, col)] == low_value {
+                if output.get_value(row, col) == low_value {
                     flag = false;
                     x = col;
                     y = row;
                     outlet_id = nodata;
                     while !flag {
-                        dir = pntr[(y, x)] as usize;
+                        dir = pntr.get_value(y, x) as usize;
                         if dir > 0 {
                             c = pntr_matches[dir];
                             y += dy[c];
                             x += dx[c];
 
                             // if the new cell already has a value in the output, use that as the outletID
-                            z = output[(y, x)];
+                            z = output.get_value(y, x);
                             if z != low_value {
                                 outlet_id = z;
                                 flag = true;
@@ -429,23 +429,23 @@ impl WhiteboxTool for StrahlerOrderBasins {
                     flag = false;
                     x = col;
                     y = row;
-                    output[(y, x)] = outlet_id;
+                    output.set_value(y, x, outlet_id);
                     while !flag {
                         // find its downslope neighbour
-                        dir = pntr[(y, x)] as usize;
+                        dir = pntr.get_value(y, x) as usize;
                         if dir > 0 {
                             c = pntr_matches[dir];
                             y += dy[c];
                             x += dx[c];
 
                             // if the new cell already has a value in the output, use that as the outletID
-                            if output[(y, x)] != low_value {
+                            if output.get_value(y, x) != low_value {
                                 flag = true;
                             }
                         } else {
                             flag = true;
                         }
-                        output[(y, x)] = outlet_id;
+                        output.set_value(y, x, outlet_id);
                     }
                 }
             }
@@ -476,7 +476,7 @@ impl WhiteboxTool for StrahlerOrderBasins {
                     println!("Output file written")
                 }
             }
-            Err(e) => return Err(e),
+            Err(e) => return Err(e.into()),
         };
         if verbose {
             println!(
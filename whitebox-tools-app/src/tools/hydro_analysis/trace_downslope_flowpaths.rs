@@ -432,7 +432,7 @@ impl WhiteboxTool for TraceDownslopeFlowpaths {
                     println!("Output file written")
                 }
             }
-            Err(e) => return Err(e),
+            Err(e) => return Err(e.into()),
         };
         if verbose {
             println!(
@@ -470,7 +470,7 @@ impl WhiteboxTool for UnnestBasins {
                         println!("Output file written")
                     }
                 }
-                Err(e) => return Err(e),
+                Err(e) => return Err(e.into()),
             };
         }
 
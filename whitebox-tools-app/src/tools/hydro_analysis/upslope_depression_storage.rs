@@ -583,7 +583,7 @@ impl WhiteboxTool for UpslopeDepressionStorage {
                     println!("Output file written")
                 }
             }
-            Err(e) => return Err(e),
+            Err(e) => return Err(e.into()),
         };
         if verbose {
             println!(
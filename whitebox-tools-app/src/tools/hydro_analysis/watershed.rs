@@ -304,9 +304,7 @@ impl WhiteboxTool for Watershed {
         let low_value = f64::MIN;
         output.reinitialize_values(low_value);
 
-        if pourpts_file.to_lowercase().ends_with(".shp") {
-            // Note that this only works because at the moment, Shapefiles are the only supported vector.
-            // If additional vector formats are added in the future, this will need updating.
+        if is_vector_file(&pourpts_file) {
             let pourpts = Shapefile::read(&pourpts_file)?;
 
             // make sure the input vector file is of points type
@@ -334,7 +332,7 @@ impl WhiteboxTool for Watershed {
 
             for row in 0..rows {
                 for col in 0..columns {
-                    z = pntr[(row, col)];
+                    z = pntr.get_value(row, col);
                     if z != pntr_nodata {
                         if z > 0.0 {
                             flow_dir.set_value(row, col, pntr_matches[z as usize]);
@@ -398,7 +396,7 @@ impl WhiteboxTool for Watershed {
         let mut outlet_id: f64;
         for row in 0..rows {
             for col in 0..columns {
-                if output[(row, col)] == low_value {
+                if output.get_value(row, col) == low_value {
                     flag = false;
                     x = col;
                     y = row;
@@ -412,7 +410,7 @@ impl WhiteboxTool for Watershed {
                             y += dy[dir as usize];
 
                             // if the new cell already has a value in the output, use that as the outletID
-                            z = output[(y, x)];
+                            z = output.get_value(y, x);
                             if z != low_value {
                                 outlet_id = z;
                                 flag = true;
@@ -425,7 +423,7 @@ impl WhiteboxTool for Watershed {
                     flag = false;
                     x = col;
                     y = row;
-                    output[(y, x)] = outlet_id;
+                    output.set_value(y, x, outlet_id);
                     while !flag {
                         // find its downslope neighbour
                         dir = flow_dir[(y, x)];
@@ -435,13 +433,13 @@ impl WhiteboxTool for Watershed {
                             y += dy[dir as usize];
 
                             // if the new cell already has a value in the output, use that as the outletID
-                            if output[(y, x)] != low_value {
+                            if output.get_value(y, x) != low_value {
                                 flag = true;
                             }
                         } else {
                             flag = true;
                         }
-                        output[(y, x)] = outlet_id;
+                        output.set_value(y, x, outlet_id);
                     }
                 }
             }
@@ -472,7 +470,7 @@ impl WhiteboxTool for Watershed {
                     println!("Output file written")
                 }
             }
-            Err(e) => return Err(e),
+            Err(e) => return Err(e.into()),
         };
         if verbose {
             println!(
@@ -512,7 +512,7 @@ impl WhiteboxTool for AdaptiveFilter {
                     println!("Output file written")
                 }
             }
-            Err(e) => return Err(e),
+            Err(e) => return Err(e.into()),
         };
 
         if verbose {
@@ -248,7 +248,7 @@ impl WhiteboxTool for BalanceContrastEnhancement {
                 let mut b_sqr_total = 0f64;
                 for row in (0..rows).filter(|rt| rt % num_procs == tid) {
                     for col in 0..columns {
-                        z = input[(row, col)];
+                        z = input.get_value(row, col);
                         if z != nodata {
                             num_pixels += 1f64;
                             r = z as u32 & 0xFF;
@@ -407,7 +407,7 @@ impl WhiteboxTool for BalanceContrastEnhancement {
                 for row in (0..rows).filter(|rt| rt % num_procs == tid) {
                     let mut data = vec![nodata; columns as usize];
                     for col in 0..columns {
-                        z = input[(row, col)];
+                        z = input.get_value(row, col);
                         if z != nodata {
                             r = z as u32 & 0xFF;
                             g = (z as u32 >> 8) & 0xFF;
@@ -484,7 +484,7 @@ impl WhiteboxTool for BalanceContrastEnhancement {
                     println!("Output file written")
                 }
             }
-            Err(e) => return Err(e),
+            Err(e) => return Err(e.into()),
         };
         if verbose {
             println!(
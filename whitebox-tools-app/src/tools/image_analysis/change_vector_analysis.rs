@@ -390,7 +390,7 @@ impl WhiteboxTool for ChangeVectorAnalysis {
                     println!("Output file written")
                 }
             }
-            Err(e) => return Err(e),
+            Err(e) => return Err(e.into()),
         };
 
         out_direction.configs.photometric_interp = PhotometricInterpretation::Categorical;
@@ -407,7 +407,7 @@ impl WhiteboxTool for ChangeVectorAnalysis {
                     println!("Output file written")
                 }
             }
-            Err(e) => return Err(e),
+            Err(e) => return Err(e.into()),
         };
 
         // print out a key for interpreting the direction image
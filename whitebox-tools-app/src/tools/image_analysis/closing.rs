@@ -458,7 +458,7 @@ impl WhiteboxTool for Closing {
                     println!("Output file written")
                 }
             }
-            Err(e) => return Err(e),
+            Err(e) => return Err(e.into()),
         };
         if verbose {
             println!(
@@ -454,7 +454,7 @@ impl WhiteboxTool for ConservativeSmoothingFilter {
                     println!("Output file written")
                 }
             }
-            Err(e) => return Err(e),
+            Err(e) => return Err(e.into()),
         };
         if verbose {
             println!(
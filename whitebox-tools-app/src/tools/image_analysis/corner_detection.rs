@@ -222,12 +222,12 @@ impl WhiteboxTool for CornerDetection {
                 for row in (0..rows).filter(|r| r % num_procs == tid) {
                     let mut data: Vec<f64> = vec![nodata; columns as usize];
                     for col in 0..columns {
-                        z = input[(row, col)];
+                        z = input.get_value(row, col);
                         if z > 0f64 && z != nodata {
                             // foreground cell
                             // fill the neighbours array
                             for i in 0..8 {
-                                z_n = input[(row + dy[i], col + dx[i])];
+                                z_n = input.get_value(row + dy[i], col + dx[i]);
                                 neighbours[i] = if z_n > 0f64 && z_n != nodata {
                                     1f64
                                 } else {
@@ -290,7 +290,7 @@ impl WhiteboxTool for CornerDetection {
                     println!("Output file written")
                 }
             }
-            Err(e) => return Err(e),
+            Err(e) => return Err(e.into()),
         };
         if verbose {
             println!(
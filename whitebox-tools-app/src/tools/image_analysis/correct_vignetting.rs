@@ -482,7 +482,7 @@ impl WhiteboxTool for CorrectVignetting {
                     println!("Output file written")
                 }
             }
-            Err(e) => return Err(e),
+            Err(e) => return Err(e.into()),
         };
         if verbose {
             println!(
@@ -380,9 +380,9 @@ impl WhiteboxTool for CreateColourComposite {
                 for row in (0..rows).filter(|r| r % num_procs == tid) {
                     let mut data = vec![nodata_r; columns as usize];
                     for col in 0..columns {
-                        red_val = input_r[(row, col)];
-                        green_val = input_g[(row, col)];
-                        blue_val = input_b[(row, col)];
+                        red_val = input_r.get_value(row, col);
+                        green_val = input_g.get_value(row, col);
+                        blue_val = input_b.get_value(row, col);
                         if red_val != nodata_r && green_val != nodata_g && blue_val != nodata_b {
                             red_val = (red_val - red_min) / red_range * 255f64;
                             if red_val < 0f64 {
@@ -467,7 +467,7 @@ impl WhiteboxTool for CreateColourComposite {
 
             for row in 0..rows {
                 for col in 0..columns {
-                    z = output[(row, col)];
+                    z = output.get_value(row, col);
                     if z != nodata_r {
                         num_pixels += 1f64;
                         r = z as u32 & 0xFF;
@@ -536,7 +536,7 @@ impl WhiteboxTool for CreateColourComposite {
 
             for row in 0..rows {
                 for col in 0..columns {
-                    z = output[(row, col)];
+                    z = output.get_value(row, col);
                     if z != nodata_r {
                         r = z as u32 & 0xFF;
                         g = (z as u32 >> 8) & 0xFF;
@@ -571,8 +571,11 @@ impl WhiteboxTool for CreateColourComposite {
                         g_out = g_outf as u32;
                         b_out = b_outf as u32;
 
-                        output[(row, col)] =
-                            ((a << 24) | (b_out << 16) | (g_out << 8) | r_out) as f64
+                        output.set_value(
+                            row,
+                            col,
+                            ((a << 24) | (b_out << 16) | (g_out << 8) | r_out) as f64,
+                        );
                     }
                 }
                 if verbose {
@@ -608,7 +611,7 @@ impl WhiteboxTool for CreateColourComposite {
                     println!("Output file written")
                 }
             }
-            Err(e) => return Err(e),
+            Err(e) => return Err(e.into()),
         };
         if verbose {
             println!(
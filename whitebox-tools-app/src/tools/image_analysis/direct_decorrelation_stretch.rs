@@ -290,7 +290,7 @@ impl WhiteboxTool for DirectDecorrelationStretch {
                     let mut histo_blue = [0usize; 256];
                     let mut num_cells = 0;
                     for col in 0..columns {
-                        z = input[(row, col)];
+                        z = input.get_value(row, col);
                         if z != nodata {
                             red = z as u32 & 0xFF;
                             green = (z as u32 >> 8) & 0xFF;
@@ -475,7 +475,7 @@ impl WhiteboxTool for DirectDecorrelationStretch {
                 for row in (0..rows).filter(|row_val| row_val % num_procs == tid) {
                     let mut data = vec![rgb_nodata; columns as usize];
                     for col in 0..columns {
-                        z = input[(row, col)];
+                        z = input.get_value(row, col);
                         if z != nodata {
                             red = red_band[(row, col)] as u32;
                             if red < stretch_min as u32 {
@@ -551,7 +551,7 @@ impl WhiteboxTool for DirectDecorrelationStretch {
                     println!("Output file written")
                 }
             }
-            Err(e) => return Err(e),
+            Err(e) => return Err(e.into()),
         };
 
         /* The following is a single-threaded version that was used for testing */
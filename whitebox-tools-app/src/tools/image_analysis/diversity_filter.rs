@@ -390,7 +390,7 @@ impl WhiteboxTool for DiversityFilter {
                     println!("Output file written")
                 }
             }
-            Err(e) => return Err(e),
+            Err(e) => return Err(e.into()),
         };
         if verbose {
             println!(
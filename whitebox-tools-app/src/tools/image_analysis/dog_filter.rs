@@ -495,7 +495,7 @@ impl WhiteboxTool for DiffOfGaussianFilter {
                     println!("Output file written")
                 }
             }
-            Err(e) => return Err(e),
+            Err(e) => return Err(e.into()),
         };
 
         if verbose {
@@ -410,7 +410,7 @@ impl WhiteboxTool for EmbossFilter {
                     println!("Output file written")
                 }
             }
-            Err(e) => return Err(e),
+            Err(e) => return Err(e.into()),
         };
 
         if verbose {
@@ -466,7 +466,7 @@ impl WhiteboxTool for FastAlmostGaussianFilter {
                     println!("Output file written")
                 }
             }
-            Err(e) => return Err(e),
+            Err(e) => return Err(e.into()),
         };
 
         if verbose {
@@ -226,7 +226,7 @@ impl WhiteboxTool for FlipImage {
                         for row in (0..rows).filter(|r| r % num_procs == tid) {
                             let mut data = vec![nodata; columns as usize];
                             for col in 0..columns {
-                                data[col as usize] = input[(rows_less_one - row, col)];
+                                data[col as usize] = input.get_value(rows_less_one - row, col);
                             }
                             tx.send((row, data)).unwrap();
                         }
@@ -235,7 +235,7 @@ impl WhiteboxTool for FlipImage {
                         for row in (0..rows).filter(|r| r % num_procs == tid) {
                             let mut data = vec![nodata; columns as usize];
                             for col in 0..columns {
-                                data[col as usize] = input[(row, cols_less_one - col)];
+                                data[col as usize] = input.get_value(row, cols_less_one - col);
                             }
                             tx.send((row, data)).unwrap();
                         }
@@ -246,7 +246,7 @@ impl WhiteboxTool for FlipImage {
                             let mut data = vec![nodata; columns as usize];
                             for col in 0..columns {
                                 data[col as usize] =
-                                    input[(rows_less_one - row, cols_less_one - col)];
+                                    input.get_value(rows_less_one - row, cols_less_one - col);
                             }
                             tx.send((row, data)).unwrap();
                         }
@@ -285,7 +285,7 @@ impl WhiteboxTool for FlipImage {
                     println!("Output file written")
                 }
             }
-            Err(e) => return Err(e),
+            Err(e) => return Err(e.into()),
         };
         if verbose {
             println!(
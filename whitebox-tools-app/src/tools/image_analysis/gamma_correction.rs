@@ -316,7 +316,7 @@ impl WhiteboxTool for GammaCorrection {
                     println!("Output file written")
                 }
             }
-            Err(e) => return Err(e),
+            Err(e) => return Err(e.into()),
         };
         if verbose {
             println!(
@@ -498,7 +498,7 @@ impl WhiteboxTool for GaussianContrastStretch {
                     println!("Output file written")
                 }
             }
-            Err(e) => return Err(e),
+            Err(e) => return Err(e.into()),
         };
 
         if verbose {
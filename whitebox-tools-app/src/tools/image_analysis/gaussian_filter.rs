@@ -401,7 +401,7 @@ impl WhiteboxTool for GaussianFilter {
                     println!("Output file written")
                 }
             }
-            Err(e) => return Err(e),
+            Err(e) => return Err(e.into()),
         };
 
         if verbose {
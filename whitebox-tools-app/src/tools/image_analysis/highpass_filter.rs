@@ -456,7 +456,7 @@ impl WhiteboxTool for HighPassFilter {
                     println!("Output file written")
                 }
             }
-            Err(e) => return Err(e),
+            Err(e) => return Err(e.into()),
         };
 
         if verbose {
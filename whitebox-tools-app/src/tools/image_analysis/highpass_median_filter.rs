@@ -557,7 +557,7 @@ impl WhiteboxTool for HighPassMedianFilter {
                     println!("Output file written")
                 }
             }
-            Err(e) => return Err(e),
+            Err(e) => return Err(e.into()),
         };
         if verbose {
             println!(
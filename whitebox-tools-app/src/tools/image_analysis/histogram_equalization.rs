@@ -378,7 +378,7 @@ impl WhiteboxTool for HistogramEqualization {
                 for row in (0..rows).filter(|r| r % num_procs == tid) {
                     let mut data: Vec<f64> = vec![nodata; columns as usize];
                     for col in 0..columns {
-                        z_in = input[(row, col)];
+                        z_in = input.get_value(row, col);
                         if z_in != nodata {
                             bin = input_fn(row, col);
                             z_out = ((cdf[bin] - min_nonempty_bin) / num_cells_less_one
@@ -423,7 +423,7 @@ impl WhiteboxTool for HistogramEqualization {
                     println!("Output file written")
                 }
             }
-            Err(e) => return Err(e),
+            Err(e) => return Err(e.into()),
         };
         if verbose {
             println!(
@@ -259,7 +259,7 @@ impl WhiteboxTool for HistogramMatching {
         let mut bin_num;
         for row in 0..rows {
             for col in 0..columns {
-                z = input[(row, col)];
+                z = input.get_value(row, col);
                 if z != nodata {
                     numcells += 1f64;
                     bin_num = ((z - min_value) / bin_size) as usize;
@@ -404,7 +404,7 @@ impl WhiteboxTool for HistogramMatching {
                 for row in (0..rows).filter(|r| r % num_procs == tid) {
                     let mut data: Vec<f64> = vec![nodata; columns as usize];
                     for col in 0..columns {
-                        z = input[(row, col)];
+                        z = input.get_value(row, col);
                         if z != nodata {
                             bin_num = ((z - min_value) / bin_size) as usize;
                             if bin_num > num_bins_less_one {
@@ -469,7 +469,7 @@ impl WhiteboxTool for HistogramMatching {
                     println!("Output file written")
                 }
             }
-            Err(e) => return Err(e),
+            Err(e) => return Err(e.into()),
         };
         if verbose {
             println!(
@@ -255,7 +255,7 @@ impl WhiteboxTool for HistogramMatchingTwoImages {
         let mut bin_num;
         for row in 0..rows1 {
             for col in 0..columns1 {
-                z = input1[(row, col)];
+                z = input1.get_value(row, col);
                 if z != nodata1 {
                     numcells1 += 1f64;
                     bin_num = ((z - min_value1) / bin_size) as usize;
@@ -296,7 +296,7 @@ impl WhiteboxTool for HistogramMatchingTwoImages {
 
         for row in 0..rows2 {
             for col in 0..columns2 {
-                z = input2[(row, col)];
+                z = input2.get_value(row, col);
                 if z != nodata2 {
                     numcells2 += 1f64;
                     bin_num = ((z - min_value2) / bin_size) as usize;
@@ -434,7 +434,7 @@ impl WhiteboxTool for HistogramMatchingTwoImages {
                 for row in (0..rows1).filter(|r| r % num_procs == tid) {
                     let mut data: Vec<f64> = vec![nodata1; columns1 as usize];
                     for col in 0..columns1 {
-                        z = input1[(row, col)];
+                        z = input1.get_value(row, col);
                         if z != nodata1 {
                             bin_num = ((z - min_value1) / bin_size) as usize;
                             if bin_num > num_bins_less_one1 {
@@ -499,7 +499,7 @@ impl WhiteboxTool for HistogramMatchingTwoImages {
                     println!("Output file written")
                 }
             }
-            Err(e) => return Err(e),
+            Err(e) => return Err(e.into()),
         };
         if verbose {
             println!(
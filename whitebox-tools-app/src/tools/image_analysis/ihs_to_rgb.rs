@@ -370,9 +370,9 @@ impl WhiteboxTool for IhsToRgb {
                         let mut green_data = vec![nodata_i; columns as usize];
                         let mut blue_data = vec![nodata_i; columns as usize];
                         for col in 0..columns {
-                            i = input_i[(row, col)];
-                            h = input_h[(row, col)];
-                            s = input_s[(row, col)];
+                            i = input_i.get_value(row, col);
+                            h = input_h.get_value(row, col);
+                            s = input_s.get_value(row, col);
                             if i != nodata_i && h != nodata_h && s != nodata_s {
                                 let (r, g, b) = hsi2rgb(h, s, i);
 
@@ -433,7 +433,7 @@ impl WhiteboxTool for IhsToRgb {
                         println!("Output file written")
                     }
                 }
-                Err(e) => return Err(e),
+                Err(e) => return Err(e.into()),
             };
 
             output_g.add_metadata_entry(format!(
@@ -457,7 +457,7 @@ impl WhiteboxTool for IhsToRgb {
                         println!("Output file written")
                     }
                 }
-                Err(e) => return Err(e),
+                Err(e) => return Err(e.into()),
             };
 
             output_b.add_metadata_entry(format!(
@@ -481,7 +481,7 @@ impl WhiteboxTool for IhsToRgb {
                         println!("Output file written")
                     }
                 }
-                Err(e) => return Err(e),
+                Err(e) => return Err(e.into()),
             };
 
             if verbose {
@@ -506,9 +506,9 @@ impl WhiteboxTool for IhsToRgb {
                     for row in (0..rows).filter(|r| r % num_procs == tid) {
                         let mut data = vec![0f64; columns as usize];
                         for col in 0..columns {
-                            i = input_i[(row, col)];
-                            h = input_h[(row, col)];
-                            s = input_s[(row, col)];
+                            i = input_i.get_value(row, col);
+                            h = input_h.get_value(row, col);
+                            s = input_s.get_value(row, col);
                             if i != nodata_i && h != nodata_h && s != nodata_s {
                                 value = hsi2value(h, s, i);
                                 data[col as usize] = value;
@@ -555,7 +555,7 @@ impl WhiteboxTool for IhsToRgb {
                         println!("Output file written")
                     }
                 }
-                Err(e) => return Err(e),
+                Err(e) => return Err(e.into()),
             };
             if verbose {
                 println!(
@@ -198,16 +198,16 @@ impl WhiteboxTool for IntegralImage {
         for row in 0..rows {
             sum = 0f64;
             for col in 0..columns {
-                val = input[(row, col)];
+                val = input.get_value(row, col);
                 if val == nodata {
                     val = 0f64;
                 }
                 sum += val;
                 if row > 0 {
-                    i_prev = output[(row - 1, col)];
-                    output[(row, col)] = sum + i_prev;
+                    i_prev = output.get_value(row - 1, col);
+                    output.set_value(row, col, sum + i_prev);
                 } else {
-                    output[(row, col)] = sum;
+                    output.set_value(row, col, sum);
                 }
             }
             if verbose {
@@ -236,7 +236,7 @@ impl WhiteboxTool for IntegralImage {
                     println!("Output file written")
                 }
             }
-            Err(e) => return Err(e),
+            Err(e) => return Err(e.into()),
         };
 
         if verbose {
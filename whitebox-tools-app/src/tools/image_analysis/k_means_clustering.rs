@@ -41,6 +41,9 @@ use std::thread;
 /// because the analysis is performed on a pixel-by-pixel basis. **NoData** values in any of the input images
 /// will result in the removal of the corresponding pixel from the analysis.
 ///
+/// The classified image carries a colour table and an attribute table that names each of the clusters
+/// and gives its size.
+///
 /// # See Also
 /// `ModifiedKMeansClustering`
 pub struct KMeansClustering {
@@ -678,6 +681,16 @@ impl WhiteboxTool for KMeansClustering {
         output.configs.data_type = DataType::I16;
         output.configs.palette = "qual.plt".to_string();
         output.configs.photometric_interp = PhotometricInterpretation::Categorical;
+        let classes: Vec<i64> = (1..=num_classes as i64).collect();
+        output.configs.colour_table = ColourTable::qualitative(&classes);
+        for &class in &classes {
+            let entry = output
+                .configs
+                .attribute_table
+                .add_entry(class, &format!("Cluster {}", class));
+            entry.colour = output.configs.colour_table.get_colour(class);
+        }
+        output.update_attribute_counts();
         output.add_metadata_entry(format!(
             "Created by whitebox_tools\' {} tool",
             self.get_tool_name()
@@ -703,7 +716,7 @@ impl WhiteboxTool for KMeansClustering {
                     println!("Output file written")
                 }
             }
-            Err(e) => return Err(e),
+            Err(e) => return Err(e.into()),
         };
         if verbose {
             println!(
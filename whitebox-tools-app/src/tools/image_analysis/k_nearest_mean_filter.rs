@@ -446,7 +446,7 @@ impl WhiteboxTool for KNearestMeanFilter {
                     println!("Output file written");
                 }
             }
-            Err(e) => return Err(e),
+            Err(e) => return Err(e.into()),
         };
 
         if verbose {
@@ -431,7 +431,7 @@ impl WhiteboxTool for LaplacianFilter {
                     println!("Output file written")
                 }
             }
-            Err(e) => return Err(e),
+            Err(e) => return Err(e.into()),
         };
 
         if verbose {
@@ -461,7 +461,7 @@ impl WhiteboxTool for LeeSigmaFilter {
                     println!("Output file written");
                 }
             }
-            Err(e) => return Err(e),
+            Err(e) => return Err(e.into()),
         };
 
         if verbose {
@@ -322,11 +322,11 @@ impl WhiteboxTool for LineDetectionFilter {
                     for row in (0..rows).filter(|r| r % num_procs == tid) {
                         let mut data = vec![nodata; columns as usize];
                         for col in 0..columns {
-                            z = input[(row, col)];
+                            z = input.get_value(row, col);
                             if z != nodata {
                                 sum = 0.0;
                                 for i in 0..num_pixels_in_filter {
-                                    zn = input[(row + dy[i], col + dx[i])];
+                                    zn = input.get_value(row + dy[i], col + dx[i]);
                                     if zn == nodata {
                                         zn = z; // replace it with z
                                     }
@@ -341,11 +341,11 @@ impl WhiteboxTool for LineDetectionFilter {
                     for row in (0..rows).filter(|r| r % num_procs == tid) {
                         let mut data = vec![nodata; columns as usize];
                         for col in 0..columns {
-                            z = input[(row, col)];
+                            z = input.get_value(row, col);
                             if z != nodata {
                                 sum = 0.0;
                                 for i in 0..num_pixels_in_filter {
-                                    zn = input[(row + dy[i], col + dx[i])];
+                                    zn = input.get_value(row + dy[i], col + dx[i]);
                                     if zn == nodata {
                                         zn = z; // replace it with z
                                     }
@@ -398,7 +398,7 @@ impl WhiteboxTool for LineDetectionFilter {
                     println!("Output file written")
                 }
             }
-            Err(e) => return Err(e),
+            Err(e) => return Err(e.into()),
         };
 
         if verbose {
@@ -388,7 +388,7 @@ impl WhiteboxTool for LaplacianOfGaussianFilter {
                     println!("Output file written")
                 }
             }
-            Err(e) => return Err(e),
+            Err(e) => return Err(e.into()),
         };
 
         if verbose {
@@ -430,7 +430,7 @@ impl WhiteboxTool for MajorityFilter {
                     println!("Output file written")
                 }
             }
-            Err(e) => return Err(e),
+            Err(e) => return Err(e.into()),
         };
         if verbose {
             println!(
@@ -417,7 +417,7 @@ impl WhiteboxTool for MaximumFilter {
                     println!("Output file written")
                 }
             }
-            Err(e) => return Err(e),
+            Err(e) => return Err(e.into()),
         };
         if verbose {
             println!(
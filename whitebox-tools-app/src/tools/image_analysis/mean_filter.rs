@@ -467,7 +467,7 @@ impl WhiteboxTool for MeanFilter {
                     println!("Output file written")
                 }
             }
-            Err(e) => return Err(e),
+            Err(e) => return Err(e.into()),
         };
 
         if verbose {
@@ -423,7 +423,7 @@ impl WhiteboxTool for MinimumFilter {
                     println!("Output file written")
                 }
             }
-            Err(e) => return Err(e),
+            Err(e) => return Err(e.into()),
         };
         if verbose {
             println!(
@@ -412,7 +412,7 @@ impl WhiteboxTool for MinMaxContrastStretch {
                     println!("Output file written")
                 }
             }
-            Err(e) => return Err(e),
+            Err(e) => return Err(e.into()),
         };
         if verbose {
             println!(
@@ -660,7 +660,7 @@ impl WhiteboxTool for ModifiedKMeansClustering {
                     println!("Output file written")
                 }
             }
-            Err(e) => return Err(e),
+            Err(e) => return Err(e.into()),
         };
         if verbose {
             println!(
@@ -718,7 +718,7 @@ impl WhiteboxTool for Mosaic {
                     println!("Output file written")
                 }
             }
-            Err(e) => return Err(e),
+            Err(e) => return Err(e.into()),
         };
         if verbose {
             println!(
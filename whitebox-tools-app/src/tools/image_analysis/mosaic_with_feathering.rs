@@ -766,7 +766,7 @@ impl WhiteboxTool for MosaicWithFeathering {
                     println!("Output file written")
                 }
             }
-            Err(e) => return Err(e),
+            Err(e) => return Err(e.into()),
         };
         if verbose {
             println!(
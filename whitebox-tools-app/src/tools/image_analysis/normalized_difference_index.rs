@@ -341,8 +341,8 @@ impl WhiteboxTool for NormalizedDifferenceIndex {
                 for row in (0..rows).filter(|r| r % num_procs == tid) {
                     let mut data = vec![nir_nodata; columns as usize];
                     for col in 0..columns {
-                        z_nir = nir[(row, col)];
-                        z_red = red[(row, col)];
+                        z_nir = nir.get_value(row, col);
+                        z_red = red.get_value(row, col);
                         if z_nir != nir_nodata && z_red != red_nodata {
                             if z_nir + z_red != 0.0 || correction_factor > 0f64 {
                                 data[col as usize] =
@@ -398,7 +398,7 @@ impl WhiteboxTool for NormalizedDifferenceIndex {
                     println!("Output file written")
                 }
             }
-            Err(e) => return Err(e),
+            Err(e) => return Err(e.into()),
         };
         if verbose {
             println!(
@@ -465,7 +465,7 @@ impl WhiteboxTool for OlympicFilter {
                     println!("Output file written")
                 }
             }
-            Err(e) => return Err(e),
+            Err(e) => return Err(e.into()),
         };
         if verbose {
             println!(
@@ -379,9 +379,9 @@ impl WhiteboxTool for PanchromaticSharpening {
             let b_range = input_b.configs.display_max - input_b.configs.display_min;
             for row in 0..rows_ms {
                 for col in 0..columns_ms {
-                    r = input_r[(row, col)];
-                    g = input_g[(row, col)];
-                    b = input_b[(row, col)];
+                    r = input_r.get_value(row, col);
+                    g = input_g.get_value(row, col);
+                    b = input_b.get_value(row, col);
                     if r != nodata_r && g != nodata_g && b != nodata_b {
                         r = (r - r_min) / r_range * 255f64;
                         if r < 0f64 {
@@ -462,7 +462,7 @@ impl WhiteboxTool for PanchromaticSharpening {
                         for col in 0..columns_pan {
                             x = pan.get_x_from_column(col);
                             source_col = get_column_from_x(x);
-                            z_pan = pan[(row, col)];
+                            z_pan = pan.get_value(row, col);
                             z_ms = input[(source_row, source_col)];
 
                             if z_ms != nodata_ms && z_pan != nodata_pan {
@@ -590,7 +590,7 @@ impl WhiteboxTool for PanchromaticSharpening {
                         for col in 0..columns_pan {
                             x = pan.get_x_from_column(col);
                             source_col = get_column_from_x(x);
-                            z_pan = pan[(row, col)];
+                            z_pan = pan.get_value(row, col);
                             z_ms = input[(source_row, source_col)];
                             if z_ms != nodata_ms && z_pan != nodata_pan {
                                 p = (z_pan - pan_min) / pan_range;
@@ -716,7 +716,7 @@ impl WhiteboxTool for PanchromaticSharpening {
                     println!("Output file written")
                 }
             }
-            Err(e) => return Err(e),
+            Err(e) => return Err(e.into()),
         };
         if verbose {
             println!(
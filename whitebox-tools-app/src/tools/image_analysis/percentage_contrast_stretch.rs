@@ -501,7 +501,7 @@ impl WhiteboxTool for PercentageContrastStretch {
                     println!("Output file written")
                 }
             }
-            Err(e) => return Err(e),
+            Err(e) => return Err(e.into()),
         };
 
         if verbose {
@@ -349,7 +349,7 @@ impl WhiteboxTool for PrewittFilter {
                     println!("Output file written")
                 }
             }
-            Err(e) => return Err(e),
+            Err(e) => return Err(e.into()),
         };
 
         if verbose {
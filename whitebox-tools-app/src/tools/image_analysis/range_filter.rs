@@ -436,7 +436,7 @@ impl WhiteboxTool for RangeFilter {
                     println!("Output file written")
                 }
             }
-            Err(e) => return Err(e),
+            Err(e) => return Err(e.into()),
         };
         if verbose {
             println!(
@@ -34,8 +34,15 @@ use std::thread;
 /// the output image that overlap with multiple input raster cells will be assigned the last
 /// input value in the stack. Thus, the order of input images is important.
 ///
+/// The resampling method (`--method`) may be nearest neighbour ('nn'), 'bilinear', cubic
+/// convolution ('cc'), or 'mode'. The bilinear and cubic convolution methods interpolate an
+/// inverse-distance weighted average of the 2 x 2 and 4 x 4 cells surrounding each output
+/// cell centre respectively, and produce continuous (32-bit floating point) outputs. The
+/// nearest neighbour and mode methods keep the input values, and are suited to categorical
+/// data; mode assigns the most common value of the input cells within each output cell.
+///
 /// # See Also
-/// `Mosaic`
+/// `Mosaic`, `ReprojectRaster`
 pub struct Resample {
     name: String,
     description: String,
@@ -92,8 +99,8 @@ impl Resample {
         parameters.push(ToolParameter{
             name: "Resampling Method".to_owned(), 
             flags: vec!["--method".to_owned()], 
-            description: "Resampling method; options include 'nn' (nearest neighbour), 'bilinear', and 'cc' (cubic convolution)".to_owned(),
-            parameter_type: ParameterType::OptionList(vec!["nn".to_owned(), "bilinear".to_owned(), "cc".to_owned()]),
+            description: "Resampling method; options include 'nn' (nearest neighbour), 'bilinear', 'cc' (cubic convolution), and 'mode' (majority, for categorical data)".to_owned(),
+            parameter_type: ParameterType::OptionList(vec!["nn".to_owned(), "bilinear".to_owned(), "cc".to_owned(), "mode".to_owned()]),
             default_value: Some("cc".to_owned()),
             optional: true
         });
@@ -163,7 +170,7 @@ impl WhiteboxTool for Resample {
         let mut cell_size = 0f64;
         let mut cell_size_specified = false;
         let mut base_file_specified = false;
-        let mut method = String::from("cc");
+        let mut method = ResamplingMethod::CubicConvolution;
 
         if args.len() == 0 {
             return Err(Error::new(
@@ -220,23 +227,20 @@ impl WhiteboxTool for Resample {
                 };
                 base_file_specified = true;
             } else if flag_val == "-method" {
-                method = if keyval {
+                let value = if keyval {
                     vec[1].to_string()
                 } else {
                     args[i + 1].to_string()
                 };
-                if method.to_lowercase().contains("nn") || method.to_lowercase().contains("nearest")
-                {
-                    method = "nn".to_string();
-                } else if method.to_lowercase().contains("bilinear")
-                    || method.to_lowercase().contains("bi")
-                {
-                    method = "bilinear".to_string();
-                } else if method.to_lowercase().contains("cc")
-                    || method.to_lowercase().contains("cubic")
-                {
-                    method = "cc".to_string();
-                }
+                method = match ResamplingMethod::from_name(&value) {
+                    Some(m) => m,
+                    None => {
+                        return Err(Error::new(
+                            ErrorKind::InvalidInput,
+                            format!("Unrecognized resampling method '{}'.", value),
+                        ))
+                    }
+                };
             }
         }
 
@@ -386,224 +390,58 @@ impl WhiteboxTool for Resample {
         let x = Arc::new(x);
         let y = Arc::new(y);
         let inputs = Arc::new(inputs);
-        let nodata_vals = Arc::new(nodata_vals);
         let mut num_procs = num_cpus::get() as isize;
         let configs = whitebox_common::configs::get_configs()?;
         let max_procs = configs.max_procs;
         if max_procs > 0 && max_procs < num_procs {
             num_procs = max_procs;
         }
-        let (tx, rx) = mpsc::channel();
-        if method == "nn" {
-            for tid in 0..num_procs {
-                let inputs = inputs.clone();
-                let nodata_vals = nodata_vals.clone();
-                let x = x.clone();
-                let y = y.clone();
-                let tx = tx.clone();
-                thread::spawn(move || {
-                    let mut z: f64;
-                    let (mut col_src, mut row_src): (isize, isize);
-                    for row in (0..rows).filter(|r| r % num_procs == tid) {
-                        let mut data = vec![nodata; columns as usize];
-                        for col in 0..columns {
-                            for i in 0..num_files {
-                                row_src = inputs[i].get_row_from_y(y[row as usize]);
-                                col_src = inputs[i].get_column_from_x(x[col as usize]);
-                                // row_src = ((inputs[i].configs.north - y[row as usize]) / inputs[i].configs.resolution_y).round() as isize;
-                                // col_src = ((x[col as usize] - inputs[i].configs.west) / inputs[i].configs.resolution_x).round() as isize;
-                                z = inputs[i].get_value(row_src, col_src);
-                                if z != nodata_vals[i] {
-                                    data[col as usize] = z;
-                                    break;
-                                }
-                            }
-                        }
-                        tx.send((row, data)).unwrap();
-                    }
-                });
-            }
-            for r in 0..rows {
-                let (row, data) = rx.recv().expect("Error receiving data from thread.");
-                for col in 0..columns {
-                    if data[col as usize] != nodata {
-                        output.set_value(row, col, data[col as usize]);
-                    }
-                }
-                if verbose {
-                    progress = (100.0_f64 * r as f64 / (rows - 1) as f64) as usize;
-                    if progress != old_progress {
-                        println!("Progress: {}%", progress);
-                        old_progress = progress;
-                    }
-                }
-            }
-        } else if method == "cc" {
+        if method.interpolates() {
             output.configs.photometric_interp = PhotometricInterpretation::Continuous;
             output.configs.data_type = DataType::F32;
-
-            for tid in 0..num_procs {
-                let inputs = inputs.clone();
-                let nodata_vals = nodata_vals.clone();
-                let x = x.clone();
-                let y = y.clone();
-                let tx = tx.clone();
-                thread::spawn(move || {
-                    let mut z: f64;
-                    let shift_x = [-1, 0, 1, 2, -1, 0, 1, 2, -1, 0, 1, 2, -1, 0, 1, 2];
-                    let shift_y = [-1, -1, -1, -1, 0, 0, 0, 0, 1, 1, 1, 1, 2, 2, 2, 2];
-                    let num_neighbours = 16;
-                    let mut neighbour = [[0f64; 2]; 16];
-                    let (mut col_src, mut row_src): (f64, f64);
-                    let (mut col_n, mut row_n): (isize, isize);
-                    let (mut origin_row, mut origin_col): (isize, isize);
-                    let (mut dx, mut dy): (f64, f64);
-                    let mut sum_dist: f64;
-                    for row in (0..rows).filter(|r| r % num_procs == tid) {
-                        let mut data = vec![nodata; columns as usize];
-                        for col in 0..columns {
-                            let mut flag = true;
-                            for i in 0..num_files {
-                                if !flag {
-                                    break;
-                                }
-                                // row_src = inputs[i].get_row_from_y(y[row as usize]);
-                                // col_src = inputs[i].get_column_from_x(x[col as usize]);
-                                row_src = (inputs[i].configs.north - y[row as usize])
-                                    / inputs[i].configs.resolution_y;
-                                col_src = (x[col as usize] - inputs[i].configs.west)
-                                    / inputs[i].configs.resolution_x;
-                                origin_row = row_src.floor() as isize;
-                                origin_col = col_src.floor() as isize;
-                                sum_dist = 0f64;
-                                for n in 0..num_neighbours {
-                                    row_n = origin_row + shift_y[n];
-                                    col_n = origin_col + shift_x[n];
-                                    neighbour[n][0] = inputs[i].get_value(row_n, col_n);
-                                    dy = row_n as f64 - row_src;
-                                    dx = col_n as f64 - col_src;
-
-                                    if (dx + dy) != 0f64 && neighbour[n][0] != nodata_vals[i] {
-                                        neighbour[n][1] = 1f64 / (dx * dx + dy * dy);
-                                        sum_dist += neighbour[n][1];
-                                    } else if neighbour[n][0] == nodata_vals[i] {
-                                        neighbour[n][1] = 0f64;
-                                    } else {
-                                        data[col as usize] = neighbour[n][0];
-                                        flag = false;
-                                    }
-                                }
-
-                                if sum_dist > 0f64 && flag {
-                                    z = 0f64;
-                                    for n in 0..num_neighbours {
-                                        z += (neighbour[n][0] * neighbour[n][1]) / sum_dist;
-                                    }
-                                    data[col as usize] = z;
-                                    flag = false;
-                                }
+        }
+        let (tx, rx) = mpsc::channel();
+        for tid in 0..num_procs {
+            let inputs = inputs.clone();
+            let x = x.clone();
+            let y = y.clone();
+            let tx = tx.clone();
+            thread::spawn(move || {
+                let (mut col_src, mut row_src): (f64, f64);
+                for row in (0..rows).filter(|r| r % num_procs == tid) {
+                    let mut data = vec![nodata; columns as usize];
+                    for col in 0..columns {
+                        for i in 0..num_files {
+                            // the fractional row and column, relative to the centre of the
+                            // input's upper-left cell
+                            row_src = (inputs[i].configs.north - y[row as usize])
+                                / inputs[i].configs.resolution_y
+                                - 0.5;
+                            col_src = (x[col as usize] - inputs[i].configs.west)
+                                / inputs[i].configs.resolution_x
+                                - 0.5;
+                            if let Some(z) = inputs[i].get_value_resampled(row_src, col_src, method) {
+                                data[col as usize] = z;
+                                break;
                             }
                         }
-                        tx.send((row, data)).unwrap();
-                    }
-                });
-            }
-            for r in 0..rows {
-                let (row, data) = rx.recv().expect("Error receiving data from thread.");
-                for col in 0..columns as usize {
-                    if data[col] != nodata {
-                        output.set_value(row, col as isize, data[col]);
                     }
+                    tx.send((row, data)).unwrap();
                 }
-                if verbose {
-                    progress = (100.0_f64 * r as f64 / (rows - 1) as f64) as usize;
-                    if progress != old_progress {
-                        println!("Progress: {}%", progress);
-                        old_progress = progress;
-                    }
+            });
+        }
+        for r in 0..rows {
+            let (row, data) = rx.recv().expect("Error receiving data from thread.");
+            for col in 0..columns as usize {
+                if data[col] != nodata {
+                    output.set_value(row, col as isize, data[col]);
                 }
             }
-        } else {
-            // bilinear
-            output.configs.photometric_interp = PhotometricInterpretation::Continuous;
-            output.configs.data_type = DataType::F32;
-            for tid in 0..num_procs {
-                let inputs = inputs.clone();
-                let nodata_vals = nodata_vals.clone();
-                let x = x.clone();
-                let y = y.clone();
-                let tx = tx.clone();
-                thread::spawn(move || {
-                    let mut z: f64;
-                    let shift_x = [0, 1, 0, 1];
-                    let shift_y = [0, 0, 1, 1];
-                    let num_neighbours = 4;
-                    let mut neighbour = [[0f64; 2]; 4];
-                    let (mut col_src, mut row_src): (f64, f64);
-                    let (mut col_n, mut row_n): (isize, isize);
-                    let (mut origin_col, mut origin_row): (isize, isize);
-                    let (mut dx, mut dy): (f64, f64);
-                    let mut sum_dist: f64;
-                    for row in (0..rows).filter(|r| r % num_procs == tid) {
-                        let mut data = vec![nodata; columns as usize];
-                        for col in 0..columns {
-                            let mut flag = true;
-                            for i in 0..num_files {
-                                if !flag {
-                                    break;
-                                }
-                                row_src = (inputs[i].configs.north - y[row as usize])
-                                    / inputs[i].configs.resolution_y;
-                                col_src = (x[col as usize] - inputs[i].configs.west)
-                                    / inputs[i].configs.resolution_x;
-                                origin_row = row_src.floor() as isize;
-                                origin_col = col_src.floor() as isize;
-                                sum_dist = 0f64;
-                                for n in 0..num_neighbours {
-                                    row_n = origin_row + shift_y[n];
-                                    col_n = origin_col + shift_x[n];
-                                    neighbour[n][0] = inputs[i].get_value(row_n, col_n);
-                                    dy = row_n as f64 - row_src;
-                                    dx = col_n as f64 - col_src;
-
-                                    if (dx + dy) != 0f64 && neighbour[n][0] != nodata_vals[i] {
-                                        neighbour[n][1] = 1f64 / (dx * dx + dy * dy);
-                                        sum_dist += neighbour[n][1];
-                                    } else if neighbour[n][0] == nodata_vals[i] {
-                                        neighbour[n][1] = 0f64;
-                                    } else {
-                                        data[col as usize] = neighbour[n][0];
-                                        flag = false;
-                                    }
-                                }
-
-                                if sum_dist > 0f64 && flag {
-                                    z = 0f64;
-                                    for n in 0..num_neighbours {
-                                        z += (neighbour[n][0] * neighbour[n][1]) / sum_dist;
-                                    }
-                                    data[col as usize] = z;
-                                    flag = false;
-                                }
-                            }
-                        }
-                        tx.send((row, data)).unwrap();
-                    }
-                });
-            }
-            for r in 0..rows {
-                let (row, data) = rx.recv().expect("Error receiving data from thread.");
-                for col in 0..columns as usize {
-                    if data[col] != nodata {
-                        output.set_value(row, col as isize, data[col]);
-                    }
-                }
-                if verbose {
-                    progress = (100.0_f64 * r as f64 / (rows - 1) as f64) as usize;
-                    if progress != old_progress {
-                        println!("Progress: {}%", progress);
-                        old_progress = progress;
-                    }
+            if verbose {
+                progress = (100.0_f64 * r as f64 / (rows - 1) as f64) as usize;
+                if progress != old_progress {
+                    println!("Progress: {}%", progress);
+                    old_progress = progress;
                 }
             }
         }
@@ -623,7 +461,7 @@ impl WhiteboxTool for Resample {
                     println!("Output file written")
                 }
             }
-            Err(e) => return Err(e),
+            Err(e) => return Err(e.into()),
         };
         if verbose {
             println!(
@@ -392,9 +392,9 @@ impl WhiteboxTool for RgbToIhs {
                         let mut hue_data = vec![nodata_r; columns as usize];
                         let mut saturation_data = vec![nodata_r; columns as usize];
                         for col in 0..columns {
-                            red = input_r[(row, col)];
-                            green = input_g[(row, col)];
-                            blue = input_b[(row, col)];
+                            red = input_r.get_value(row, col);
+                            green = input_g.get_value(row, col);
+                            blue = input_b.get_value(row, col);
                             if red != nodata_r && green != nodata_g && blue != nodata_b {
                                 // r = ((red - red_min) / (red_max - red_min) * 255f64) as u32;
                                 // if r > 255u32 {
@@ -471,7 +471,7 @@ impl WhiteboxTool for RgbToIhs {
                         println!("Output file written")
                     }
                 }
-                Err(e) => return Err(e),
+                Err(e) => return Err(e.into()),
             };
 
             output_h.add_metadata_entry(format!(
@@ -490,7 +490,7 @@ impl WhiteboxTool for RgbToIhs {
                         println!("Output file written")
                     }
                 }
-                Err(e) => return Err(e),
+                Err(e) => return Err(e.into()),
             };
 
             output_s.add_metadata_entry(format!(
@@ -511,7 +511,7 @@ impl WhiteboxTool for RgbToIhs {
                         println!("Output file written")
                     }
                 }
-                Err(e) => return Err(e),
+                Err(e) => return Err(e.into()),
             };
 
             if verbose {
@@ -543,7 +543,7 @@ impl WhiteboxTool for RgbToIhs {
                     let mut z: f64;
                     for row in (0..rows).filter(|r| r % num_procs == tid) {
                         for col in 0..columns {
-                            z = input[(row, col)];
+                            z = input.get_value(row, col);
                             if z != nodata {
                                 r = (z as u32 & 0xFF) as f64;
                                 g = ((z as u32 >> 8) & 0xFF) as f64;
@@ -611,7 +611,7 @@ impl WhiteboxTool for RgbToIhs {
                         let mut hue_data = vec![nodata; columns as usize];
                         let mut saturation_data = vec![nodata; columns as usize];
                         for col in 0..columns {
-                            z = input[(row, col)];
+                            z = input.get_value(row, col);
                             if z != nodata {
                                 // r = (z as u32 & 0xFF) as f64;
                                 // g = ((z as u32 >> 8) & 0xFF) as f64;
@@ -724,7 +724,7 @@ impl WhiteboxTool for RgbToIhs {
                         println!("Output file written")
                     }
                 }
-                Err(e) => return Err(e),
+                Err(e) => return Err(e.into()),
             };
 
             output_h.add_metadata_entry(format!(
@@ -745,7 +745,7 @@ impl WhiteboxTool for RgbToIhs {
                         println!("Output file written")
                     }
                 }
-                Err(e) => return Err(e),
+                Err(e) => return Err(e.into()),
             };
 
             output_s.add_metadata_entry(format!(
@@ -766,7 +766,7 @@ impl WhiteboxTool for RgbToIhs {
                         println!("Output file written")
                     }
                 }
-                Err(e) => return Err(e),
+                Err(e) => return Err(e.into()),
             };
             if verbose {
                 println!(
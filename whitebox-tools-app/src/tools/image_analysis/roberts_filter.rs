@@ -356,7 +356,7 @@ impl WhiteboxTool for RobertsCrossFilter {
                     println!("Output file written")
                 }
             }
-            Err(e) => return Err(e),
+            Err(e) => return Err(e.into()),
         };
 
         if verbose {
@@ -350,7 +350,7 @@ impl WhiteboxTool for ScharrFilter {
                     println!("Output file written")
                 }
             }
-            Err(e) => return Err(e),
+            Err(e) => return Err(e.into()),
         };
 
         if verbose {
@@ -438,7 +438,7 @@ impl WhiteboxTool for SigmoidalContrastStretch {
                     println!("Output file written")
                 }
             }
-            Err(e) => return Err(e),
+            Err(e) => return Err(e.into()),
         };
         if verbose {
             println!(
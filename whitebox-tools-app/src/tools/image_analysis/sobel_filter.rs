@@ -415,7 +415,7 @@ impl WhiteboxTool for SobelFilter {
                     println!("Output file written")
                 }
             }
-            Err(e) => return Err(e),
+            Err(e) => return Err(e.into()),
         };
 
         if verbose {
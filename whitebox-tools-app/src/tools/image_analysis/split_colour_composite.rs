@@ -316,7 +316,7 @@ impl WhiteboxTool for SplitColourComposite {
                     println!("Output file written")
                 }
             }
-            Err(e) => return Err(e),
+            Err(e) => return Err(e.into()),
         };
 
         output_g.add_metadata_entry(format!(
@@ -334,7 +334,7 @@ impl WhiteboxTool for SplitColourComposite {
                     println!("Output file written")
                 }
             }
-            Err(e) => return Err(e),
+            Err(e) => return Err(e.into()),
         };
 
         output_b.add_metadata_entry(format!(
@@ -352,7 +352,7 @@ impl WhiteboxTool for SplitColourComposite {
                     println!("Output file written")
                 }
             }
-            Err(e) => return Err(e),
+            Err(e) => return Err(e.into()),
         };
         if verbose {
             println!(
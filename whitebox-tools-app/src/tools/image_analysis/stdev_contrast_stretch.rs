@@ -420,7 +420,7 @@ impl WhiteboxTool for StandardDeviationContrastStretch {
                     println!("Output file written")
                 }
             }
-            Err(e) => return Err(e),
+            Err(e) => return Err(e.into()),
         };
         if verbose {
             println!(
@@ -295,7 +295,7 @@ impl WhiteboxTool for StandardDeviationFilter {
             sum_sqr = 0f64;
             sum_n = 0;
             for col in 0..columns {
-                val = input[(row, col)];
+                val = input.get_value(row, col);
                 if val == nodata {
                     val = 0f64;
                 } else {
@@ -366,7 +366,7 @@ impl WhiteboxTool for StandardDeviationFilter {
                     }
                     let mut data = vec![nodata; columns as usize];
                     for col in 0..columns {
-                        z = input_data[(row, col)];
+                        z = input_data.get_value(row, col);
                         if z != nodata {
                             x1 = col - midpoint_x - 1;
                             if x1 < 0 {
@@ -438,7 +438,7 @@ impl WhiteboxTool for StandardDeviationFilter {
                     println!("Output file written")
                 }
             }
-            Err(e) => return Err(e),
+            Err(e) => return Err(e.into()),
         };
 
         if verbose {
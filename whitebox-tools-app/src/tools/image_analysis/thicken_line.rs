@@ -223,14 +223,14 @@ impl WhiteboxTool for ThickenRasterLine {
         let (mut zn1, mut zn2, mut zn3): (f64, f64, f64);
         for row in 0..rows {
             for col in 0..columns {
-                z = input[(row, col)];
+                z = input.get_value(row, col);
                 if z == nodata || z == 0.0 {
                     for i in 0..4 {
-                        zn1 = output[(row + n1y[i], col + n1x[i])];
-                        zn2 = output[(row + n2y[i], col + n2x[i])];
-                        zn3 = output[(row + n3y[i], col + n3x[i])];
+                        zn1 = output.get_value(row + n1y[i], col + n1x[i]);
+                        zn2 = output.get_value(row + n2y[i], col + n2x[i]);
+                        zn3 = output.get_value(row + n3y[i], col + n3x[i]);
                         if (zn1 > 0.0 && zn3 > 0.0) && (zn2 == nodata || zn2 == 0.0) {
-                            output[(row, col)] = zn1;
+                            output.set_value(row, col, zn1);
                             break;
                         }
                     }
@@ -262,7 +262,7 @@ impl WhiteboxTool for ThickenRasterLine {
                     println!("Output file written")
                 }
             }
-            Err(e) => return Err(e),
+            Err(e) => return Err(e.into()),
         };
         if verbose {
             println!(
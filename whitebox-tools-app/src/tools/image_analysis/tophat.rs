@@ -457,7 +457,7 @@ impl WhiteboxTool for TophatTransform {
                                     }
                                 }
                                 if max_val > f64::NEG_INFINITY {
-                                    data[col as usize] = input_data[(row, col)] - max_val;
+                                    data[col as usize] = input_data.get_value(row, col) - max_val;
                                 }
                             }
                         }
@@ -499,7 +499,7 @@ impl WhiteboxTool for TophatTransform {
                         println!("Output file written")
                     }
                 }
-                Err(e) => return Err(e),
+                Err(e) => return Err(e.into()),
             };
 
             if verbose {
@@ -652,7 +652,7 @@ impl WhiteboxTool for TophatTransform {
                                     }
                                 }
                                 if min_val < f64::INFINITY {
-                                    data[col as usize] = min_val - input_data[(row, col)];
+                                    data[col as usize] = min_val - input_data.get_value(row, col);
                                 }
                             }
                         }
@@ -694,7 +694,7 @@ impl WhiteboxTool for TophatTransform {
                         println!("Output file written")
                     }
                 }
-                Err(e) => return Err(e),
+                Err(e) => return Err(e.into()),
             };
             if verbose {
                 println!(
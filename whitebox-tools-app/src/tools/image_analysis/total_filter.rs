@@ -288,7 +288,7 @@ impl WhiteboxTool for TotalFilter {
         for row in 0..rows {
             sum = 0f64;
             for col in 0..columns {
-                val = input[(row, col)];
+                val = input.get_value(row, col);
                 if val == nodata {
                     val = 0f64;
                 } else {
@@ -345,7 +345,7 @@ impl WhiteboxTool for TotalFilter {
                     }
                     let mut data = vec![nodata; columns as usize];
                     for col in 0..columns {
-                        z = input_data[(row, col)];
+                        z = input_data.get_value(row, col);
                         if z != nodata {
                             x1 = col - midpoint_x - 1;
                             if x1 < 0 {
@@ -403,7 +403,7 @@ impl WhiteboxTool for TotalFilter {
                     println!("Output file written")
                 }
             }
-            Err(e) => return Err(e),
+            Err(e) => return Err(e.into()),
         };
 
         if verbose {
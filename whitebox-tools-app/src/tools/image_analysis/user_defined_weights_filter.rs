@@ -433,7 +433,7 @@ impl WhiteboxTool for UserDefinedWeightsFilter {
                     println!("Output file written")
                 }
             }
-            Err(e) => return Err(e),
+            Err(e) => return Err(e.into()),
         };
 
         if verbose {
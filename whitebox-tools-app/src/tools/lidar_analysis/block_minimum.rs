@@ -387,7 +387,7 @@ impl WhiteboxTool for LidarBlockMinimum {
                 row = data.0;
                 col = data.1;
                 z = data.2;
-                if output[(row, col)] == nodata || z < output[(row, col)] {
+                if output.get_value(row, col) == nodata || z < output.get_value(row, col) {
                     output.set_value(row, col, z);
                 }
                 if verbose {
@@ -419,7 +419,7 @@ impl WhiteboxTool for LidarBlockMinimum {
                         println!("Output file written")
                     }
                 }
-                Err(e) => return Err(e),
+                Err(e) => return Err(e.into()),
             };
         }
 
@@ -429,7 +429,7 @@ impl WhiteboxTool for FlightlineOverlap {
                         println!("Output file written")
                     }
                 }
-                Err(e) => return Err(e),
+                Err(e) => return Err(e.into()),
             };
         }
 
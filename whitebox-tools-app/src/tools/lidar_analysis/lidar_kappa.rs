@@ -724,7 +724,7 @@ impl WhiteboxTool for LidarKappaIndex {
                     println!("Output file written")
                 }
             }
-            Err(e) => return Err(e),
+            Err(e) => return Err(e.into()),
         };
 
         if verbose {
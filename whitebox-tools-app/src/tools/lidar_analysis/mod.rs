@@ -46,7 +46,9 @@ mod lidar_tin_gridding;
 mod lidar_tophat_transform;
 mod normal_vectors;
 mod remove_duplicates;
+mod reproject_lidar;
 mod select_tiles_by_polygon;
+mod transform_lidar_heights;
 mod zlidar_to_las;
 
 // exports identifiers from private sub-modules in the current module namespace
@@ -97,5 +99,7 @@ pub use self::lidar_tin_gridding::LidarTINGridding;
 pub use self::lidar_tophat_transform::LidarTophatTransform;
 pub use self::normal_vectors::NormalVectors;
 pub use self::remove_duplicates::LidarRemoveDuplicates;
+pub use self::reproject_lidar::ReprojectLidar;
 pub use self::select_tiles_by_polygon::SelectTilesByPolygon;
+pub use self::transform_lidar_heights::TransformLidarHeights;
 pub use self::zlidar_to_las::ZlidarToLas;
@@ -0,0 +1,392 @@
+/*
+This tool is part of the WhiteboxTools geospatial analysis library.
+License: MIT
+*/
+
+use whitebox_common::spatial_ref_system::{
+    esri_wkt_from_epsg, CoordinateReferenceSystem, CoordinateTransformation, CrsKind,
+};
+use whitebox_lidar::*;
+use crate::tools::*;
+use num_cpus;
+use std::env;
+use std::f64;
+use std::fs;
+use std::io::{Error, ErrorKind};
+use std::path;
+use std::sync::mpsc;
+use std::sync::Arc;
+use std::thread;
+
+/// This tool transforms the coordinates of a LiDAR point cloud (`--input`) into another
+/// coordinate reference system, which is given either by its EPSG code (`--epsg`) or by a file
+/// containing its well-known text (WKT) description, such as a shapefile's .prj file
+/// (`--crs_file`). The coordinate reference system of the input is read from its WKT or GeoKey
+/// variable length records (VLRs). In the output file, these are replaced by a WKT record of
+/// the target system and, where its EPSG code is known, GeoKeys referring to it.
+///
+/// The scale factors and offsets of the output header are chosen for the target system, so
+/// that the coordinates keep the precision of the input, e.g. an input scale factor of 0.001 m
+/// becomes 0.00000001 degrees in geographic coordinates. The z values are taken to be
+/// heights above the ellipsoid, in metres, and so only change when the transformation shifts
+/// between datums. All other point attributes are copied from the input.
+///
+/// # See Also
+/// `ReprojectVector`, `ReprojectRaster`
+pub struct ReprojectLidar {
+    name: String,
+    description: String,
+    toolbox: String,
+    parameters: Vec<ToolParameter>,
+    example_usage: String,
+}
+
+impl ReprojectLidar {
+    pub fn new() -> ReprojectLidar {
+        // public constructor
+        let name = "ReprojectLidar".to_string();
+        let toolbox = "LiDAR Tools".to_string();
+        let description =
+            "Transforms a LiDAR point cloud into another coordinate reference system.".to_string();
+
+        let mut parameters = vec![];
+        parameters.push(ToolParameter {
+            name: "Input File".to_owned(),
+            flags: vec!["-i".to_owned(), "--input".to_owned()],
+            description: "Input LiDAR file.".to_owned(),
+            parameter_type: ParameterType::ExistingFile(ParameterFileType::Lidar),
+            default_value: None,
+            optional: false,
+        });
+
+        parameters.push(ToolParameter {
+            name: "Output File".to_owned(),
+            flags: vec!["-o".to_owned(), "--output".to_owned()],
+            description: "Output LiDAR file.".to_owned(),
+            parameter_type: ParameterType::NewFile(ParameterFileType::Lidar),
+            default_value: None,
+            optional: false,
+        });
+
+        parameters.push(ToolParameter {
+            name: "Target EPSG Code (optional)".to_owned(),
+            flags: vec!["--epsg".to_owned()],
+            description: "EPSG code of the target coordinate reference system (e.g. 32617).".to_owned(),
+            parameter_type: ParameterType::Integer,
+            default_value: None,
+            optional: true,
+        });
+
+        parameters.push(ToolParameter {
+            name: "Target CRS File (optional)".to_owned(),
+            flags: vec!["--crs_file".to_owned()],
+            description: "File containing the WKT of the target coordinate reference system, e.g. a .prj file. Not used when an EPSG code is specified.".to_owned(),
+            parameter_type: ParameterType::ExistingFile(ParameterFileType::Any),
+            default_value: None,
+            optional: true,
+        });
+
+        let sep: String = path::MAIN_SEPARATOR.to_string();
+        let e = format!("{}", env::current_exe().unwrap().display());
+        let mut parent = env::current_exe().unwrap();
+        parent.pop();
+        let p = format!("{}", parent.display());
+        let mut short_exe = e
+            .replace(&p, "")
+            .replace(".exe", "")
+            .replace(".", "")
+            .replace(&sep, "");
+        if e.contains(".exe") {
+            short_exe += ".exe";
+        }
+        let usage = format!(
+            ">>.*{0} -r={1} -v --wd=\"*path*to*data*\" -i=input.las -o=output.las --epsg=32617",
+            short_exe, name
+        )
+        .replace("*", &sep);
+
+        ReprojectLidar {
+            name: name,
+            description: description,
+            toolbox: toolbox,
+            parameters: parameters,
+            example_usage: usage,
+        }
+    }
+}
+
+impl WhiteboxTool for ReprojectLidar {
+    fn get_source_file(&self) -> String {
+        String::from(file!())
+    }
+
+    fn get_tool_name(&self) -> String {
+        self.name.clone()
+    }
+
+    fn get_tool_description(&self) -> String {
+        self.description.clone()
+    }
+
+    fn get_tool_parameters(&self) -> String {
+        match serde_json::to_string(&self.parameters) {
+            Ok(json_str) => return format!("{{\"parameters\":{}}}", json_str),
+            Err(err) => return format!("{:?}", err),
+        }
+    }
+
+    fn get_example_usage(&self) -> String {
+        self.example_usage.clone()
+    }
+
+    fn get_toolbox(&self) -> String {
+        self.toolbox.clone()
+    }
+
+    fn run<'a>(
+        &self,
+        args: Vec<String>,
+        working_directory: &'a str,
+        verbose: bool,
+    ) -> Result<(), Error> {
+        let mut input_file = String::new();
+        let mut output_file = String::new();
+        let mut epsg = 0u16;
+        let mut crs_file = String::new();
+
+        if args.len() == 0 {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                "Tool run with no parameters.",
+            ));
+        }
+        for i in 0..args.len() {
+            let mut arg = args[i].replace("\"", "");
+            arg = arg.replace("\'", "");
+            let cmd = arg.split("="); // in case an equals sign was used
+            let vec = cmd.collect::<Vec<&str>>();
+            let mut keyval = false;
+            if vec.len() > 1 {
+                keyval = true;
+            }
+            let flag_val = vec[0].to_lowercase().replace("--", "-");
+            let value = if keyval {
+                vec[1].to_string()
+            } else if i + 1 < args.len() {
+                args[i + 1].to_string()
+            } else {
+                String::new()
+            };
+            if flag_val == "-i" || flag_val == "-input" {
+                input_file = value;
+            } else if flag_val == "-o" || flag_val == "-output" {
+                output_file = value;
+            } else if flag_val == "-epsg" {
+                epsg = value
+                    .trim()
+                    .parse::<u16>()
+                    .expect(&format!("Error parsing {}", flag_val));
+            } else if flag_val == "-crs_file" {
+                crs_file = value;
+            }
+        }
+
+        if verbose {
+            let tool_name = self.get_tool_name();
+            let welcome_len = format!("* Welcome to {} *", tool_name).len().max(28);
+            // 28 = length of the 'Powered by' by statement.
+            println!("{}", "*".repeat(welcome_len));
+            println!("* Welcome to {} {}*", tool_name, " ".repeat(welcome_len - 15 - tool_name.len()));
+            println!("* Powered by WhiteboxTools {}*", " ".repeat(welcome_len - 28));
+            println!("* www.whiteboxgeo.com {}*", " ".repeat(welcome_len - 23));
+            println!("{}", "*".repeat(welcome_len));
+        }
+
+        let sep = path::MAIN_SEPARATOR;
+        if !input_file.contains(sep) && !input_file.contains("/") {
+            input_file = format!("{}{}", working_directory, input_file);
+        }
+        if !output_file.contains(sep) && !output_file.contains("/") {
+            output_file = format!("{}{}", working_directory, output_file);
+        }
+        if !crs_file.is_empty() && !crs_file.contains(sep) && !crs_file.contains("/") {
+            crs_file = format!("{}{}", working_directory, crs_file);
+        }
+
+        if verbose {
+            println!("reading input LiDAR file...");
+        }
+        let input = LasFile::new(&input_file, "r")?;
+        let source_crs = input.get_coordinate_reference_system().ok_or_else(|| {
+            Error::new(
+                ErrorKind::InvalidInput,
+                "The coordinate reference system of the input LiDAR file is not specified.",
+            )
+        })?;
+
+        let start = Instant::now();
+
+        let (target_crs, target_wkt) = if epsg > 0 {
+            (
+                CoordinateReferenceSystem::from_epsg(epsg)?,
+                esri_wkt_from_epsg(epsg),
+            )
+        } else if !crs_file.is_empty() {
+            let wkt = fs::read_to_string(&crs_file)?.trim().to_string();
+            (CoordinateReferenceSystem::from_wkt(&wkt)?, wkt)
+        } else {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                "A target coordinate reference system must be given, either by an EPSG code (--epsg) or a WKT file (--crs_file).",
+            ));
+        };
+        let transformation = CoordinateTransformation::new(&source_crs, &target_crs)?;
+
+        if verbose {
+            println!("Transforming points...");
+        }
+        let n_points = input.header.number_of_points as usize;
+        let num_points: f64 = (input.header.number_of_points - 1) as f64; // used for progress calculation only
+        let mut progress: i32;
+        let mut old_progress: i32 = -1;
+
+        let block_size = 10_000usize;
+        let num_blocks = (n_points + block_size - 1) / block_size;
+        let input = Arc::new(input);
+        let transformation = Arc::new(transformation);
+        let num_procs = num_cpus::get();
+        let (tx, rx) = mpsc::channel();
+        for tid in 0..num_procs {
+            let input = input.clone();
+            let transformation = transformation.clone();
+            let tx = tx.clone();
+            thread::spawn(move || {
+                for block in (0..num_blocks).filter(|b| b % num_procs == tid) {
+                    let first = block * block_size;
+                    let last = (first + block_size).min(n_points);
+                    let coords = (first..last)
+                        .map(|i| {
+                            let p = input.get_transformed_coords(i);
+                            transformation.transform_3d(p.x, p.y, p.z)
+                        })
+                        .collect::<Vec<Option<(f64, f64, f64)>>>();
+                    tx.send((first, coords)).unwrap();
+                }
+            });
+        }
+
+        let mut coords = vec![(0f64, 0f64, 0f64); n_points];
+        let mut num_failed = 0usize;
+        let (mut min_x, mut min_y, mut min_z) = (f64::INFINITY, f64::INFINITY, f64::INFINITY);
+        let (mut max_x, mut max_y, mut max_z) =
+            (f64::NEG_INFINITY, f64::NEG_INFINITY, f64::NEG_INFINITY);
+        for block in 0..num_blocks {
+            let (first, block_coords) = rx.recv().expect("Error receiving data from thread.");
+            for (i, c) in block_coords.into_iter().enumerate() {
+                match c {
+                    Some((x, y, z)) => {
+                        coords[first + i] = (x, y, z);
+                        min_x = min_x.min(x);
+                        max_x = max_x.max(x);
+                        min_y = min_y.min(y);
+                        max_y = max_y.max(y);
+                        min_z = min_z.min(z);
+                        max_z = max_z.max(z);
+                    }
+                    None => num_failed += 1,
+                }
+            }
+            if verbose {
+                progress = (100.0_f64 * block as f64 / (num_blocks - 1).max(1) as f64) as i32;
+                if progress != old_progress {
+                    println!("Progress: {}%", progress);
+                    old_progress = progress;
+                }
+            }
+        }
+        if num_failed > 0 {
+            return Err(Error::new(
+                ErrorKind::InvalidData,
+                format!("{} points could not be transformed into the target coordinate reference system.", num_failed),
+            ));
+        }
+
+        let mut output = LasFile::initialize_using_file(&output_file, &input);
+        let xy_scale = |scale: f64| {
+            let scale = scale * metres_per_unit(&source_crs) / metres_per_unit(&target_crs);
+            10f64.powf(scale.log10().round())
+        };
+        output.header.x_scale_factor = fit_scale(xy_scale(input.header.x_scale_factor), min_x, max_x);
+        output.header.y_scale_factor = fit_scale(xy_scale(input.header.y_scale_factor), min_y, max_y);
+        output.header.z_scale_factor = if target_crs.kind == CrsKind::Geocentric {
+            fit_scale(xy_scale(input.header.z_scale_factor), min_z, max_z)
+        } else {
+            fit_scale(input.header.z_scale_factor, min_z, max_z)
+        };
+        if n_points > 0 {
+            output.header.x_offset = min_x.floor();
+            output.header.y_offset = min_y.floor();
+            output.header.z_offset = min_z.floor();
+        }
+        output.set_coordinate_reference_system(&target_wkt, target_crs.identify_epsg().unwrap_or(0));
+
+        for i in 0..n_points {
+            let (x, y, z) = coords[i];
+            let mut record = input.get_record(i);
+            let mut p = record.get_point_data();
+            p.x = ((x - output.header.x_offset) / output.header.x_scale_factor).round() as i32;
+            p.y = ((y - output.header.y_offset) / output.header.y_scale_factor).round() as i32;
+            p.z = ((z - output.header.z_offset) / output.header.z_scale_factor).round() as i32;
+            record.set_point_data(p);
+            output.add_point_record(record);
+            if verbose {
+                progress = (100.0_f64 * i as f64 / num_points) as i32;
+                if progress != old_progress {
+                    println!("Saving data: {}%", progress);
+                    old_progress = progress;
+                }
+            }
+        }
+
+        let elapsed_time = get_formatted_elapsed_time(start);
+
+        if verbose {
+            println!("Writing output LAS file...");
+        }
+        let _ = match output.write() {
+            Ok(_) => {
+                if verbose {
+                    println!("Complete!")
+                }
+            }
+            Err(e) => return Err(e),
+        };
+        if verbose {
+            println!(
+                "{}",
+                &format!("Elapsed Time (excluding I/O): {}", elapsed_time)
+            );
+        }
+
+        Ok(())
+    }
+}
+
+/// Returns the approximate length in metres of a coordinate unit of the system, taking a
+/// degree to be its length along the equator.
+fn metres_per_unit(crs: &CoordinateReferenceSystem) -> f64 {
+    if crs.is_geographic() {
+        crs.angular_unit * crs.datum.ellipsoid.semi_major_axis
+    } else {
+        crs.linear_unit
+    }
+}
+
+/// Coarsens a scale factor until the range of the coordinates, offset by its minimum, can be
+/// stored in the 32-bit integers of the point records.
+fn fit_scale(mut scale: f64, min: f64, max: f64) -> f64 {
+    while (max - min.floor()) / scale > i32::MAX as f64 {
+        scale *= 10f64;
+    }
+    scale
+}
@@ -0,0 +1,331 @@
+/*
+This tool is part of the WhiteboxTools geospatial analysis library.
+License: MIT
+*/
+
+use whitebox_common::spatial_ref_system::{GeoidGrid, GeoidTransformation};
+use whitebox_lidar::*;
+use crate::tools::*;
+use num_cpus;
+use std::env;
+use std::f64;
+use std::io::{Error, ErrorKind};
+use std::path;
+use std::sync::mpsc;
+use std::sync::Arc;
+use std::thread;
+
+/// This tool transforms the heights of the points in a LiDAR file between the ellipsoid and a
+/// vertical datum defined by a geoid model, using a grid of geoid heights (`--geoid`). By
+/// default, the ellipsoidal heights that are typical of LiDAR surveys are converted to
+/// orthometric heights, i.e. heights above the geoid, by subtracting the height of the geoid
+/// above the ellipsoid at each point. The `--to_ellipsoid` flag reverses the transformation.
+///
+/// Geoid grids are read from local files in either the GTX format (.gtx) used by NOAA's
+/// VDatum and PROJ, or the NGS binary format (.bin) of the GEOID12B and GEOID18 models, and
+/// are interpolated bilinearly. The grid should be for the horizontal datum of the input
+/// file, whose coordinate reference system must be known. The z values are taken to be in
+/// the linear units of a projected system and in metres otherwise. The tool reports an error
+/// if any point lies outside of the geoid grid. The scale factors and offsets of the input
+/// header, and all other point attributes, are kept in the output.
+///
+/// # See Also
+/// `TransformRasterHeights`, `TransformVectorHeights`, `ReprojectLidar`
+pub struct TransformLidarHeights {
+    name: String,
+    description: String,
+    toolbox: String,
+    parameters: Vec<ToolParameter>,
+    example_usage: String,
+}
+
+impl TransformLidarHeights {
+    pub fn new() -> TransformLidarHeights {
+        // public constructor
+        let name = "TransformLidarHeights".to_string();
+        let toolbox = "LiDAR Tools".to_string();
+        let description =
+            "Transforms LiDAR point heights between the ellipsoid and a geoid model.".to_string();
+
+        let mut parameters = vec![];
+        parameters.push(ToolParameter {
+            name: "Input File".to_owned(),
+            flags: vec!["-i".to_owned(), "--input".to_owned()],
+            description: "Input LiDAR file.".to_owned(),
+            parameter_type: ParameterType::ExistingFile(ParameterFileType::Lidar),
+            default_value: None,
+            optional: false,
+        });
+
+        parameters.push(ToolParameter {
+            name: "Geoid Grid File".to_owned(),
+            flags: vec!["--geoid".to_owned()],
+            description: "Input geoid grid file, in GTX (.gtx) or NGS (.bin) format.".to_owned(),
+            parameter_type: ParameterType::ExistingFile(ParameterFileType::Any),
+            default_value: None,
+            optional: false,
+        });
+
+        parameters.push(ToolParameter {
+            name: "Output File".to_owned(),
+            flags: vec!["-o".to_owned(), "--output".to_owned()],
+            description: "Output LiDAR file.".to_owned(),
+            parameter_type: ParameterType::NewFile(ParameterFileType::Lidar),
+            default_value: None,
+            optional: false,
+        });
+
+        parameters.push(ToolParameter {
+            name: "Convert orthometric heights to ellipsoidal heights?".to_owned(),
+            flags: vec!["--to_ellipsoid".to_owned()],
+            description: "Convert orthometric heights to ellipsoidal heights, rather than ellipsoidal heights to orthometric heights?".to_owned(),
+            parameter_type: ParameterType::Boolean,
+            default_value: Some("false".to_string()),
+            optional: true,
+        });
+
+        let sep: String = path::MAIN_SEPARATOR.to_string();
+        let e = format!("{}", env::current_exe().unwrap().display());
+        let mut parent = env::current_exe().unwrap();
+        parent.pop();
+        let p = format!("{}", parent.display());
+        let mut short_exe = e
+            .replace(&p, "")
+            .replace(".exe", "")
+            .replace(".", "")
+            .replace(&sep, "");
+        if e.contains(".exe") {
+            short_exe += ".exe";
+        }
+        let usage = format!(
+            ">>.*{0} -r={1} -v --wd=\"*path*to*data*\" -i=input.las --geoid=g2018u0.gtx -o=output.las",
+            short_exe, name
+        )
+        .replace("*", &sep);
+
+        TransformLidarHeights {
+            name: name,
+            description: description,
+            toolbox: toolbox,
+            parameters: parameters,
+            example_usage: usage,
+        }
+    }
+}
+
+impl WhiteboxTool for TransformLidarHeights {
+    fn get_source_file(&self) -> String {
+        String::from(file!())
+    }
+
+    fn get_tool_name(&self) -> String {
+        self.name.clone()
+    }
+
+    fn get_tool_description(&self) -> String {
+        self.description.clone()
+    }
+
+    fn get_tool_parameters(&self) -> String {
+        match serde_json::to_string(&self.parameters) {
+            Ok(json_str) => return format!("{{\"parameters\":{}}}", json_str),
+            Err(err) => return format!("{:?}", err),
+        }
+    }
+
+    fn get_example_usage(&self) -> String {
+        self.example_usage.clone()
+    }
+
+    fn get_toolbox(&self) -> String {
+        self.toolbox.clone()
+    }
+
+    fn run<'a>(
+        &self,
+        args: Vec<String>,
+        working_directory: &'a str,
+        verbose: bool,
+    ) -> Result<(), Error> {
+        let mut input_file = String::new();
+        let mut geoid_file = String::new();
+        let mut output_file = String::new();
+        let mut to_ellipsoid = false;
+
+        if args.len() == 0 {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                "Tool run with no parameters.",
+            ));
+        }
+        for i in 0..args.len() {
+            let mut arg = args[i].replace("\"", "");
+            arg = arg.replace("\'", "");
+            let cmd = arg.split("="); // in case an equals sign was used
+            let vec = cmd.collect::<Vec<&str>>();
+            let mut keyval = false;
+            if vec.len() > 1 {
+                keyval = true;
+            }
+            let flag_val = vec[0].to_lowercase().replace("--", "-");
+            let value = if keyval {
+                vec[1].to_string()
+            } else if i + 1 < args.len() {
+                args[i + 1].to_string()
+            } else {
+                String::new()
+            };
+            if flag_val == "-i" || flag_val == "-input" {
+                input_file = value;
+            } else if flag_val == "-geoid" {
+                geoid_file = value;
+            } else if flag_val == "-o" || flag_val == "-output" {
+                output_file = value;
+            } else if flag_val == "-to_ellipsoid" {
+                if vec.len() == 1 || !vec[1].to_string().to_lowercase().contains("false") {
+                    to_ellipsoid = true;
+                }
+            }
+        }
+
+        if verbose {
+            let tool_name = self.get_tool_name();
+            let welcome_len = format!("* Welcome to {} *", tool_name).len().max(28);
+            // 28 = length of the 'Powered by' by statement.
+            println!("{}", "*".repeat(welcome_len));
+            println!("* Welcome to {} {}*", tool_name, " ".repeat(welcome_len - 15 - tool_name.len()));
+            println!("* Powered by WhiteboxTools {}*", " ".repeat(welcome_len - 28));
+            println!("* www.whiteboxgeo.com {}*", " ".repeat(welcome_len - 23));
+            println!("{}", "*".repeat(welcome_len));
+        }
+
+        let sep = path::MAIN_SEPARATOR;
+        if !input_file.contains(sep) && !input_file.contains("/") {
+            input_file = format!("{}{}", working_directory, input_file);
+        }
+        if !geoid_file.contains(sep) && !geoid_file.contains("/") {
+            geoid_file = format!("{}{}", working_directory, geoid_file);
+        }
+        if !output_file.contains(sep) && !output_file.contains("/") {
+            output_file = format!("{}{}", working_directory, output_file);
+        }
+
+        if verbose {
+            println!("reading input LiDAR file...");
+        }
+        let input = LasFile::new(&input_file, "r")?;
+        let grid = GeoidGrid::read(&geoid_file)?;
+        let crs = input.get_coordinate_reference_system().ok_or_else(|| {
+            Error::new(
+                ErrorKind::InvalidInput,
+                "The coordinate reference system of the input LiDAR file is not specified.",
+            )
+        })?;
+
+        let start = Instant::now();
+
+        let metres_per_unit = if crs.is_projected() {
+            crs.linear_unit
+        } else {
+            1f64
+        };
+        let transformation = GeoidTransformation::new(grid, &crs, !to_ellipsoid, metres_per_unit)?;
+
+        if verbose {
+            println!("Transforming points...");
+        }
+        let n_points = input.header.number_of_points as usize;
+        let num_points: f64 = (input.header.number_of_points - 1) as f64; // used for progress calculation only
+        let mut progress: i32;
+        let mut old_progress: i32 = -1;
+
+        let block_size = 10_000usize;
+        let num_blocks = (n_points + block_size - 1) / block_size;
+        let input = Arc::new(input);
+        let transformation = Arc::new(transformation);
+        let num_procs = num_cpus::get();
+        let (tx, rx) = mpsc::channel();
+        for tid in 0..num_procs {
+            let input = input.clone();
+            let transformation = transformation.clone();
+            let tx = tx.clone();
+            thread::spawn(move || {
+                for block in (0..num_blocks).filter(|b| b % num_procs == tid) {
+                    let first = block * block_size;
+                    let last = (first + block_size).min(n_points);
+                    let heights = (first..last)
+                        .map(|i| {
+                            let p = input.get_transformed_coords(i);
+                            transformation.transform(p.x, p.y, p.z)
+                        })
+                        .collect::<Vec<Option<f64>>>();
+                    tx.send((first, heights)).unwrap();
+                }
+            });
+        }
+
+        let mut heights = vec![0f64; n_points];
+        let mut num_outside = 0usize;
+        for block in 0..num_blocks {
+            let (first, block_heights) = rx.recv().expect("Error receiving data from thread.");
+            for (i, z) in block_heights.into_iter().enumerate() {
+                match z {
+                    Some(z) => heights[first + i] = z,
+                    None => num_outside += 1,
+                }
+            }
+            if verbose {
+                progress = (100.0_f64 * block as f64 / (num_blocks - 1).max(1) as f64) as i32;
+                if progress != old_progress {
+                    println!("Progress: {}%", progress);
+                    old_progress = progress;
+                }
+            }
+        }
+        if num_outside > 0 {
+            return Err(Error::new(
+                ErrorKind::InvalidData,
+                format!("{} points are outside of the geoid grid.", num_outside),
+            ));
+        }
+
+        let mut output = LasFile::initialize_using_file(&output_file, &input);
+        for i in 0..n_points {
+            let mut record = input.get_record(i);
+            let mut p = record.get_point_data();
+            p.z = ((heights[i] - output.header.z_offset) / output.header.z_scale_factor).round()
+                as i32;
+            record.set_point_data(p);
+            output.add_point_record(record);
+            if verbose {
+                progress = (100.0_f64 * i as f64 / num_points) as i32;
+                if progress != old_progress {
+                    println!("Saving data: {}%", progress);
+                    old_progress = progress;
+                }
+            }
+        }
+
+        let elapsed_time = get_formatted_elapsed_time(start);
+
+        if verbose {
+            println!("Writing output LAS file...");
+        }
+        let _ = match output.write() {
+            Ok(_) => {
+                if verbose {
+                    println!("Complete!")
+                }
+            }
+            Err(e) => return Err(e),
+        };
+        if verbose {
+            println!(
+                "{}",
+                &format!("Elapsed Time (excluding I/O): {}", elapsed_time)
+            );
+        }
+
+        Ok(())
+    }
+}
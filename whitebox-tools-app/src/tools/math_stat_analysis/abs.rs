@@ -208,7 +208,7 @@ impl WhiteboxTool for AbsoluteValue {
                 for row in (0..rows).filter(|r| r % num_procs == tid) {
                     let mut data: Vec<f64> = vec![nodata; columns as usize];
                     for col in 0..columns {
-                        z = input[(row, col)];
+                        z = input.get_value(row, col);
                         if z != nodata {
                             data[col as usize] = z.abs();
                         } else {
@@ -251,7 +251,7 @@ impl WhiteboxTool for AbsoluteValue {
                     println!("Output file written")
                 }
             }
-            Err(e) => return Err(e),
+            Err(e) => return Err(e.into()),
         };
         if verbose {
             println!(
@@ -256,7 +256,7 @@ impl WhiteboxTool for Add {
                     for row in (0..rows).filter(|r| r % num_procs == tid) {
                         let mut data: Vec<f64> = vec![nodata2; columns as usize];
                         for col in 0..columns {
-                            z2 = in2[(row, col)];
+                            z2 = in2.get_value(row, col);
                             if z2 != nodata2 {
                                 data[col as usize] = input1_constant + z2;
                             } else {
@@ -305,7 +305,7 @@ impl WhiteboxTool for Add {
                         println!("Output file written")
                     }
                 }
-                Err(e) => return Err(e),
+                Err(e) => return Err(e.into()),
             };
             if verbose {
                 println!(
@@ -339,7 +339,7 @@ impl WhiteboxTool for Add {
                     for row in (0..rows).filter(|r| r % num_procs == tid) {
                         let mut data: Vec<f64> = vec![nodata1; columns as usize];
                         for col in 0..columns {
-                            z1 = in1[(row, col)];
+                            z1 = in1.get_value(row, col);
                             if z1 != nodata1 {
                                 data[col as usize] = z1 + input2_constant;
                             } else {
@@ -388,7 +388,7 @@ impl WhiteboxTool for Add {
                         println!("Output file written")
                     }
                 }
-                Err(e) => return Err(e),
+                Err(e) => return Err(e.into()),
             };
             if verbose {
                 println!(
@@ -433,8 +433,8 @@ impl WhiteboxTool for Add {
                     for row in (0..rows).filter(|r| r % num_procs == tid) {
                         let mut data: Vec<f64> = vec![nodata1; columns as usize];
                         for col in 0..columns {
-                            z1 = in1[(row, col)];
-                            z2 = in2[(row, col)];
+                            z1 = in1.get_value(row, col);
+                            z2 = in2.get_value(row, col);
                             if z1 != nodata1 && z2 != nodata2 {
                                 data[col as usize] = z1 + z2;
                             } else {
@@ -476,7 +476,7 @@ impl WhiteboxTool for Add {
                         println!("Output file written")
                     }
                 }
-                Err(e) => return Err(e),
+                Err(e) => return Err(e.into()),
             };
             if verbose {
                 println!(
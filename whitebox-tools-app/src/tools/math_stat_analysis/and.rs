@@ -240,8 +240,8 @@ impl WhiteboxTool for And {
                 for row in (0..rows).filter(|r| r % num_procs == tid) {
                     let mut data: Vec<f64> = vec![nodata1; columns as usize];
                     for col in 0..columns {
-                        z1 = in1[(row, col)];
-                        z2 = in2[(row, col)];
+                        z1 = in1.get_value(row, col);
+                        z2 = in2.get_value(row, col);
                         if z1 != nodata1 && z2 != nodata2 {
                             if z1 != 0f64 {
                                 z1 = 1f64;
@@ -292,7 +292,7 @@ impl WhiteboxTool for And {
                     println!("Output file written")
                 }
             }
-            Err(e) => return Err(e),
+            Err(e) => return Err(e.into()),
         };
         if verbose {
             println!(
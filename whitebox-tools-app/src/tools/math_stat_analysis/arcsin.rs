@@ -211,7 +211,7 @@ impl WhiteboxTool for ArcSin {
                 for row in (0..rows).filter(|r| r % num_procs == tid) {
                     let mut data: Vec<f64> = vec![nodata; columns as usize];
                     for col in 0..columns {
-                        z = input[(row, col)];
+                        z = input.get_value(row, col);
                         if z != nodata {
                             if z >= -1.0 && z <= 1.0 {
                                 data[col as usize] = z.asin();
@@ -258,7 +258,7 @@ impl WhiteboxTool for ArcSin {
                     println!("Output file written")
                 }
             }
-            Err(e) => return Err(e),
+            Err(e) => return Err(e.into()),
         };
         if verbose {
             println!(
@@ -216,7 +216,7 @@ impl WhiteboxTool for ArcTan {
                 for row in (0..rows).filter(|r| r % num_procs == tid) {
                     let mut data: Vec<f64> = vec![nodata; columns as usize];
                     for col in 0..columns {
-                        z = input[(row, col)];
+                        z = input.get_value(row, col);
                         if z != nodata {
                             data[col as usize] = z.atan();
                         } else {
@@ -259,7 +259,7 @@ impl WhiteboxTool for ArcTan {
                     println!("Output file written")
                 }
             }
-            Err(e) => return Err(e),
+            Err(e) => return Err(e.into()),
         };
         if verbose {
             println!(
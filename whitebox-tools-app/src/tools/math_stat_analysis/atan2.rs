@@ -260,7 +260,7 @@ impl WhiteboxTool for Atan2 {
                     for row in (0..rows).filter(|r| r % num_procs == tid) {
                         let mut data: Vec<f64> = vec![nodata2; columns as usize];
                         for col in 0..columns {
-                            z2 = in2[(row, col)];
+                            z2 = in2.get_value(row, col);
                             if z2 != nodata2 {
                                 data[col as usize] = input1_constant.atan2(z2);
                             } else {
@@ -302,7 +302,7 @@ impl WhiteboxTool for Atan2 {
                         println!("Output file written")
                     }
                 }
-                Err(e) => return Err(e),
+                Err(e) => return Err(e.into()),
             };
             if verbose {
                 println!(
@@ -336,7 +336,7 @@ impl WhiteboxTool for Atan2 {
                     for row in (0..rows).filter(|r| r % num_procs == tid) {
                         let mut data: Vec<f64> = vec![nodata1; columns as usize];
                         for col in 0..columns {
-                            z1 = in1[(row, col)];
+                            z1 = in1.get_value(row, col);
                             if z1 != nodata1 {
                                 data[col as usize] = z1.atan2(input2_constant);
                             } else {
@@ -378,7 +378,7 @@ impl WhiteboxTool for Atan2 {
                         println!("Output file written")
                     }
                 }
-                Err(e) => return Err(e),
+                Err(e) => return Err(e.into()),
             };
             if verbose {
                 println!(
@@ -423,8 +423,8 @@ impl WhiteboxTool for Atan2 {
                     for row in (0..rows).filter(|r| r % num_procs == tid) {
                         let mut data: Vec<f64> = vec![nodata1; columns as usize];
                         for col in 0..columns {
-                            z1 = in1[(row, col)];
-                            z2 = in2[(row, col)];
+                            z1 = in1.get_value(row, col);
+                            z2 = in2.get_value(row, col);
                             if z1 != nodata1 && z2 != nodata2 {
                                 data[col as usize] = z1.atan2(z2);
                             } else {
@@ -466,7 +466,7 @@ impl WhiteboxTool for Atan2 {
                         println!("Output file written")
                     }
                 }
-                Err(e) => return Err(e),
+                Err(e) => return Err(e.into()),
             };
             if verbose {
                 println!(
@@ -255,10 +255,10 @@ impl WhiteboxTool for AttributeCorrelation {
                             field_totals[field_num] += val as f64;
                             field_n[field_num] += 1f64;
                         }
-                        // FieldData::Int64(val) => {
-                        //     field_totals[field_num] += val as f64;
-                        //     field_n[field_num] += 1f64;
-                        // },
+                        FieldData::BigInt(val) => {
+                            field_totals[field_num] += val as f64;
+                            field_n[field_num] += 1f64;
+                        }
                         FieldData::Real(val) => {
                             field_totals[field_num] += val;
                             field_n[field_num] += 1f64;
@@ -307,13 +307,13 @@ impl WhiteboxTool for AttributeCorrelation {
                                 let rec = vector_data.attributes.get_record(record_num);
                                 z1 = match rec[a] {
                                     FieldData::Int(val) => val as f64,
-                                    // FieldData::Int64(val) => val as f64,
+                                    FieldData::BigInt(val) => val as f64,
                                     FieldData::Real(val) => val,
                                     _ => nodata,
                                 };
                                 z2 = match rec[b] {
                                     FieldData::Int(val) => val as f64,
-                                    // FieldData::Int64(val) => val as f64,
+                                    FieldData::BigInt(val) => val as f64,
                                     FieldData::Real(val) => val,
                                     _ => nodata,
                                 };
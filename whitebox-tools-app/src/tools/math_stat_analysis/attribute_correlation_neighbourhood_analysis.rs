@@ -321,6 +321,7 @@ impl WhiteboxTool for AttributeCorrelationNeighbourhoodAnalysis {
                 for i in 0..record.num_points as usize {
                     z = match input.attributes.get_value(record_num, &field_name1) {
                         FieldData::Int(val) => val as f64,
+                        FieldData::BigInt(val) => val as f64,
                         FieldData::Real(val) => val,
                         _ => {
                             return Err(Error::new(
@@ -333,6 +334,7 @@ impl WhiteboxTool for AttributeCorrelationNeighbourhoodAnalysis {
 
                     z = match input.attributes.get_value(record_num, &field_name2) {
                         FieldData::Int(val) => val as f64,
+                        FieldData::BigInt(val) => val as f64,
                         FieldData::Real(val) => val,
                         _ => {
                             return Err(Error::new(
@@ -254,6 +254,15 @@ impl WhiteboxTool for AttributeHistogram {
                         max = valf64;
                     }
                 }
+                FieldData::BigInt(val) => {
+                    let valf64 = val as f64;
+                    if valf64 < min {
+                        min = valf64;
+                    }
+                    if valf64 > max {
+                        max = valf64;
+                    }
+                }
                 FieldData::Real(val) => {
                     if val < min {
                         min = val;
@@ -290,11 +299,11 @@ impl WhiteboxTool for AttributeHistogram {
                     bin = ((valf64 - min) / bin_width).floor() as usize;
                     freq_data[bin] += 1;
                 }
-                // FieldData::Int64(val) => {
-                //     let valf64 = val as f64;
-                //     bin = ((valf64 - min) / bin_width).floor() as usize;
-                //     freq_data[bin] += 1;
-                // },
+                FieldData::BigInt(val) => {
+                    let valf64 = val as f64;
+                    bin = ((valf64 - min) / bin_width).floor() as usize;
+                    freq_data[bin] += 1;
+                }
                 FieldData::Real(val) => {
                     bin = ((val - min) / bin_width).floor() as usize;
                     freq_data[bin] += 1;
@@ -300,9 +300,7 @@ impl WhiteboxTool for AttributeScattergram {
         for record_num in 0..vector_data.num_records {
             x = match vector_data.attributes.get_value(record_num, &field_name_x) {
                 FieldData::Int(val) => val as f64,
-                // FieldData::Int64(val) => {
-                //     val as f64
-                // },
+                FieldData::BigInt(val) => val as f64,
                 FieldData::Real(val) => val,
                 _ => {
                     nodata // likely a null field
@@ -311,9 +309,7 @@ impl WhiteboxTool for AttributeScattergram {
 
             y = match vector_data.attributes.get_value(record_num, &field_name_y) {
                 FieldData::Int(val) => val as f64,
-                // FieldData::Int64(val) => {
-                //     val as f64
-                // },
+                FieldData::BigInt(val) => val as f64,
                 FieldData::Real(val) => val,
                 _ => {
                     nodata // likely a null field
@@ -255,7 +255,7 @@ impl WhiteboxTool for Cos {
                     println!("Output file written")
                 }
             }
-            Err(e) => return Err(e),
+            Err(e) => return Err(e.into()),
         };
         if verbose {
             println!(
@@ -253,7 +253,7 @@ impl WhiteboxTool for CrispnessIndex {
                     let mut s = 0.0;
                     let mut warning = false;
                     for col in 0..columns {
-                        z = input[(row, col)];
+                        z = input.get_value(row, col);
                         if z != nodata {
                             if z < 0f64 || z > 1f64 {
                                 warning = true;
@@ -298,7 +298,7 @@ impl WhiteboxTool for CrispnessIndex {
                 for row in (0..rows).filter(|r| r % num_procs == tid) {
                     let mut total_dev = 0f64;
                     for col in 0..columns {
-                        z = input[(row, col)];
+                        z = input.get_value(row, col);
                         if z != nodata {
                             total_dev += (z - mean) * (z - mean);
                         }
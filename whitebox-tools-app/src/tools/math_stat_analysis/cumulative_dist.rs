@@ -207,7 +207,7 @@ impl WhiteboxTool for CumulativeDistribution {
         let mut z: f64;
         for row in 0..rows {
             for col in 0..columns {
-                z = input[(row, col)];
+                z = input.get_value(row, col);
                 if z != nodata {
                     num_cells += 1;
                     bin_num = ((z - min_val) / bin_size) as usize;
@@ -238,13 +238,13 @@ impl WhiteboxTool for CumulativeDistribution {
 
         for row in 0..rows {
             for col in 0..columns {
-                z = input[(row, col)];
+                z = input.get_value(row, col);
                 if z != nodata {
                     bin_num = ((z - min_val) / bin_size) as usize;
                     if bin_num > num_bins_less_one {
                         bin_num = num_bins_less_one;
                     }
-                    output[(row, col)] = cdf[bin_num];
+                    output.set_value(row, col, cdf[bin_num]);
                 }
             }
             if verbose {
@@ -273,7 +273,7 @@ impl WhiteboxTool for CumulativeDistribution {
                     println!("Output file written")
                 }
             }
-            Err(e) => return Err(e),
+            Err(e) => return Err(e.into()),
         };
         if verbose {
             println!(
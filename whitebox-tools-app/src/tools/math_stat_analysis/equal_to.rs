@@ -253,7 +253,7 @@ impl WhiteboxTool for EqualTo {
                     for row in (0..rows).filter(|r| r % num_procs == tid) {
                         let mut data: Vec<f64> = vec![nodata2; columns as usize];
                         for col in 0..columns {
-                            z2 = in2[(row, col)];
+                            z2 = in2.get_value(row, col);
                             if z2 != nodata2 {
                                 data[col as usize] = equal_to(input1_constant, z2);
                             } else {
@@ -298,7 +298,7 @@ impl WhiteboxTool for EqualTo {
                         println!("Output file written")
                     }
                 }
-                Err(e) => return Err(e),
+                Err(e) => return Err(e.into()),
             };
             if verbose {
                 println!(
@@ -332,7 +332,7 @@ impl WhiteboxTool for EqualTo {
                     for row in (0..rows).filter(|r| r % num_procs == tid) {
                         let mut data: Vec<f64> = vec![nodata1; columns as usize];
                         for col in 0..columns {
-                            z1 = in1[(row, col)];
+                            z1 = in1.get_value(row, col);
                             if z1 != nodata1 {
                                 data[col as usize] = equal_to(z1, input2_constant);
                             } else {
@@ -377,7 +377,7 @@ impl WhiteboxTool for EqualTo {
                         println!("Output file written")
                     }
                 }
-                Err(e) => return Err(e),
+                Err(e) => return Err(e.into()),
             };
             if verbose {
                 println!(
@@ -422,8 +422,8 @@ impl WhiteboxTool for EqualTo {
                     for row in (0..rows).filter(|r| r % num_procs == tid) {
                         let mut data: Vec<f64> = vec![nodata1; columns as usize];
                         for col in 0..columns {
-                            z1 = in1[(row, col)];
-                            z2 = in2[(row, col)];
+                            z1 = in1.get_value(row, col);
+                            z2 = in2.get_value(row, col);
                             if z1 != nodata1 && z2 != nodata2 {
                                 data[col as usize] = equal_to(z1, z2);
                             } else {
@@ -468,7 +468,7 @@ impl WhiteboxTool for EqualTo {
                         println!("Output file written")
                     }
                 }
-                Err(e) => return Err(e),
+                Err(e) => return Err(e.into()),
             };
             if verbose {
                 println!(
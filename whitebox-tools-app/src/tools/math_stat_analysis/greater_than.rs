@@ -275,7 +275,7 @@ impl WhiteboxTool for GreaterThan {
                     for row in (0..rows).filter(|r| r % num_procs == tid) {
                         let mut data: Vec<f64> = vec![nodata2; columns as usize];
                         for col in 0..columns {
-                            z2 = in2[(row, col)];
+                            z2 = in2.get_value(row, col);
                             if z2 != nodata2 {
                                 data[col as usize] = greater_than(input1_constant, z2, equal_to);
                             } else {
@@ -320,7 +320,7 @@ impl WhiteboxTool for GreaterThan {
                         println!("Output file written")
                     }
                 }
-                Err(e) => return Err(e),
+                Err(e) => return Err(e.into()),
             };
             if verbose {
                 println!(
@@ -354,7 +354,7 @@ impl WhiteboxTool for GreaterThan {
                     for row in (0..rows).filter(|r| r % num_procs == tid) {
                         let mut data: Vec<f64> = vec![nodata1; columns as usize];
                         for col in 0..columns {
-                            z1 = in1[(row, col)];
+                            z1 = in1.get_value(row, col);
                             if z1 != nodata1 {
                                 data[col as usize] = greater_than(z1, input2_constant, equal_to);
                             } else {
@@ -399,7 +399,7 @@ impl WhiteboxTool for GreaterThan {
                         println!("Output file written")
                     }
                 }
-                Err(e) => return Err(e),
+                Err(e) => return Err(e.into()),
             };
             if verbose {
                 println!(
@@ -444,8 +444,8 @@ impl WhiteboxTool for GreaterThan {
                     for row in (0..rows).filter(|r| r % num_procs == tid) {
                         let mut data: Vec<f64> = vec![nodata1; columns as usize];
                         for col in 0..columns {
-                            z1 = in1[(row, col)];
-                            z2 = in2[(row, col)];
+                            z1 = in1.get_value(row, col);
+                            z2 = in2.get_value(row, col);
                             if z1 != nodata1 && z2 != nodata2 {
                                 data[col as usize] = greater_than(z1, z2, equal_to);
                             } else {
@@ -490,7 +490,7 @@ impl WhiteboxTool for GreaterThan {
                         println!("Output file written")
                     }
                 }
-                Err(e) => return Err(e),
+                Err(e) => return Err(e.into()),
             };
             if verbose {
                 println!(
@@ -838,7 +838,7 @@ impl WhiteboxTool for ImageCorrelationNeighbourhoodAnalysis {
                     println!("Output file written")
                 }
             }
-            Err(e) => return Err(e),
+            Err(e) => return Err(e.into()),
         };
 
         output_sig.add_metadata_entry(format!(
@@ -859,7 +859,7 @@ impl WhiteboxTool for ImageCorrelationNeighbourhoodAnalysis {
                     println!(" ")
                 }
             }
-            Err(e) => return Err(e),
+            Err(e) => return Err(e.into()),
         };
 
         if verbose {
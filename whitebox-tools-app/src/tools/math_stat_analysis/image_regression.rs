@@ -399,8 +399,8 @@ impl WhiteboxTool for ImageRegression {
                 let mut ss_error = 0f64;
                 for row in (0..rows).filter(|r| r % num_procs == tid) {
                     for col in 0..columns {
-                        x = input1[(row, col)];
-                        y = input2[(row, col)];
+                        x = input1.get_value(row, col);
+                        y = input2.get_value(row, col);
                         if x != nodata1 && y != nodata2 {
                             y_estimate = slope * x + intercept;
                             ss_error += (y - y_estimate) * (y - y_estimate);
@@ -460,8 +460,8 @@ impl WhiteboxTool for ImageRegression {
                     for row in (0..rows).filter(|r| r % num_procs == tid) {
                         let mut data: Vec<f64> = vec![nodata1; columns as usize];
                         for col in 0..columns {
-                            x = input1[(row, col)];
-                            y = input2[(row, col)];
+                            x = input1.get_value(row, col);
+                            y = input2.get_value(row, col);
                             if x != nodata1 && y != nodata2 {
                                 y_estimate = slope * x + intercept;
                                 residual = if standardize_residuals {
@@ -509,7 +509,7 @@ impl WhiteboxTool for ImageRegression {
                         println!("Output file written")
                     }
                 }
-                Err(e) => return Err(e),
+                Err(e) => return Err(e.into()),
             };
         }
 
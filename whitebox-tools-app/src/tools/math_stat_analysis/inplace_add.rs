@@ -279,7 +279,7 @@ impl WhiteboxTool for InPlaceAdd {
                     println!("Output file written")
                 }
             }
-            Err(e) => return Err(e),
+            Err(e) => return Err(e.into()),
         };
 
         if verbose {
@@ -284,7 +284,7 @@ impl WhiteboxTool for InPlaceDivide {
                     println!("Output file written")
                 }
             }
-            Err(e) => return Err(e),
+            Err(e) => return Err(e.into()),
         };
 
         if verbose {
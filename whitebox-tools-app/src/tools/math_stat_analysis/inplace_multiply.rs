@@ -278,7 +278,7 @@ impl WhiteboxTool for InPlaceMultiply {
                     println!("Output file written")
                 }
             }
-            Err(e) => return Err(e),
+            Err(e) => return Err(e.into()),
         };
 
         if verbose {
@@ -280,7 +280,7 @@ impl WhiteboxTool for InPlaceSubtract {
                     println!("Output file written")
                 }
             }
-            Err(e) => return Err(e),
+            Err(e) => return Err(e.into()),
         };
 
         if verbose {
@@ -260,7 +260,7 @@ impl WhiteboxTool for IntegerDivision {
                     for row in (0..rows).filter(|r| r % num_procs == tid) {
                         let mut data: Vec<f64> = vec![nodata2; columns as usize];
                         for col in 0..columns {
-                            z2 = in2[(row, col)];
+                            z2 = in2.get_value(row, col);
                             if z2 != nodata2 {
                                 if z2 != 0f64 {
                                     data[col as usize] =
@@ -307,7 +307,7 @@ impl WhiteboxTool for IntegerDivision {
                         println!("Output file written")
                     }
                 }
-                Err(e) => return Err(e),
+                Err(e) => return Err(e.into()),
             };
             if verbose {
                 println!(
@@ -341,7 +341,7 @@ impl WhiteboxTool for IntegerDivision {
                     for row in (0..rows).filter(|r| r % num_procs == tid) {
                         let mut data: Vec<f64> = vec![nodata1; columns as usize];
                         for col in 0..columns {
-                            z1 = in1[(row, col)];
+                            z1 = in1.get_value(row, col);
                             if z1 != nodata1 {
                                 if input2_constant != 0f64 {
                                     data[col as usize] =
@@ -388,7 +388,7 @@ impl WhiteboxTool for IntegerDivision {
                         println!("Output file written")
                     }
                 }
-                Err(e) => return Err(e),
+                Err(e) => return Err(e.into()),
             };
             if verbose {
                 println!(
@@ -433,8 +433,8 @@ impl WhiteboxTool for IntegerDivision {
                     for row in (0..rows).filter(|r| r % num_procs == tid) {
                         let mut data: Vec<f64> = vec![nodata1; columns as usize];
                         for col in 0..columns {
-                            z1 = in1[(row, col)];
-                            z2 = in2[(row, col)];
+                            z1 = in1.get_value(row, col);
+                            z2 = in2.get_value(row, col);
                             if z1 != nodata1 && z2 != nodata2 {
                                 if z2 != 0f64 {
                                     data[col as usize] = (z1 as isize / z2 as isize) as f64;
@@ -480,7 +480,7 @@ impl WhiteboxTool for IntegerDivision {
                         println!("Output file written")
                     }
                 }
-                Err(e) => return Err(e),
+                Err(e) => return Err(e.into()),
             };
             if verbose {
                 println!(
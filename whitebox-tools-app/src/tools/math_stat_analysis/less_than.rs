@@ -272,7 +272,7 @@ impl WhiteboxTool for LessThan {
                     for row in (0..rows).filter(|r| r % num_procs == tid) {
                         let mut data: Vec<f64> = vec![nodata2; columns as usize];
                         for col in 0..columns {
-                            z2 = in2[(row, col)];
+                            z2 = in2.get_value(row, col);
                             if z2 != nodata2 {
                                 data[col as usize] = less_than(input1_constant, z2, equal_to);
                             } else {
@@ -317,7 +317,7 @@ impl WhiteboxTool for LessThan {
                         println!("Output file written")
                     }
                 }
-                Err(e) => return Err(e),
+                Err(e) => return Err(e.into()),
             };
             if verbose {
                 println!(
@@ -351,7 +351,7 @@ impl WhiteboxTool for LessThan {
                     for row in (0..rows).filter(|r| r % num_procs == tid) {
                         let mut data: Vec<f64> = vec![nodata1; columns as usize];
                         for col in 0..columns {
-                            z1 = in1[(row, col)];
+                            z1 = in1.get_value(row, col);
                             if z1 != nodata1 {
                                 data[col as usize] = less_than(z1, input2_constant, equal_to);
                             } else {
@@ -396,7 +396,7 @@ impl WhiteboxTool for LessThan {
                         println!("Output file written")
                     }
                 }
-                Err(e) => return Err(e),
+                Err(e) => return Err(e.into()),
             };
             if verbose {
                 println!(
@@ -441,8 +441,8 @@ impl WhiteboxTool for LessThan {
                     for row in (0..rows).filter(|r| r % num_procs == tid) {
                         let mut data: Vec<f64> = vec![nodata1; columns as usize];
                         for col in 0..columns {
-                            z1 = in1[(row, col)];
-                            z2 = in2[(row, col)];
+                            z1 = in1.get_value(row, col);
+                            z2 = in2.get_value(row, col);
                             if z1 != nodata1 && z2 != nodata2 {
                                 data[col as usize] = less_than(z1, z2, equal_to);
                             } else {
@@ -487,7 +487,7 @@ impl WhiteboxTool for LessThan {
                         println!("Output file written")
                     }
                 }
-                Err(e) => return Err(e),
+                Err(e) => return Err(e.into()),
             };
             if verbose {
                 println!(
@@ -209,7 +209,7 @@ impl WhiteboxTool for Log2 {
                 for row in (0..rows).filter(|r| r % num_procs == tid) {
                     let mut data: Vec<f64> = vec![nodata; columns as usize];
                     for col in 0..columns {
-                        z = input[(row, col)];
+                        z = input.get_value(row, col);
                         if z != nodata {
                             if z > 0.0 {
                                 data[col as usize] = z.log2();
@@ -257,7 +257,7 @@ impl WhiteboxTool for Log2 {
                     println!("Output file written")
                 }
             }
-            Err(e) => return Err(e),
+            Err(e) => return Err(e.into()),
         };
         if verbose {
             println!(
@@ -265,7 +265,7 @@ impl WhiteboxTool for Modulo {
                     for row in (0..rows).filter(|r| r % num_procs == tid) {
                         let mut data: Vec<f64> = vec![nodata2; columns as usize];
                         for col in 0..columns {
-                            z2 = in2[(row, col)];
+                            z2 = in2.get_value(row, col);
                             if z2 != nodata2 {
                                 if z2 != 0f64 {
                                     data[col as usize] = input1_constant % z2;
@@ -311,7 +311,7 @@ impl WhiteboxTool for Modulo {
                         println!("Output file written")
                     }
                 }
-                Err(e) => return Err(e),
+                Err(e) => return Err(e.into()),
             };
             if verbose {
                 println!(
@@ -345,7 +345,7 @@ impl WhiteboxTool for Modulo {
                     for row in (0..rows).filter(|r| r % num_procs == tid) {
                         let mut data: Vec<f64> = vec![nodata1; columns as usize];
                         for col in 0..columns {
-                            z1 = in1[(row, col)];
+                            z1 = in1.get_value(row, col);
                             if z1 != nodata1 {
                                 if input2_constant != 0f64 {
                                     data[col as usize] = z1 % input2_constant;
@@ -391,7 +391,7 @@ impl WhiteboxTool for Modulo {
                         println!("Output file written")
                     }
                 }
-                Err(e) => return Err(e),
+                Err(e) => return Err(e.into()),
             };
             if verbose {
                 println!(
@@ -436,8 +436,8 @@ impl WhiteboxTool for Modulo {
                     for row in (0..rows).filter(|r| r % num_procs == tid) {
                         let mut data: Vec<f64> = vec![nodata1; columns as usize];
                         for col in 0..columns {
-                            z1 = in1[(row, col)];
-                            z2 = in2[(row, col)];
+                            z1 = in1.get_value(row, col);
+                            z2 = in2.get_value(row, col);
                             if z1 != nodata1 && z2 != nodata2 {
                                 if z2 != 0f64 {
                                     data[col as usize] = z1 % z2;
@@ -483,7 +483,7 @@ impl WhiteboxTool for Modulo {
                         println!("Output file written")
                     }
                 }
-                Err(e) => return Err(e),
+                Err(e) => return Err(e.into()),
             };
             if verbose {
                 println!(
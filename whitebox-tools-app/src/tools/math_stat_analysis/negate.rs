@@ -214,7 +214,7 @@ impl WhiteboxTool for Negate {
                     for row in (0..rows).filter(|r| r % num_procs == tid) {
                         let mut data: Vec<f64> = vec![nodata; columns as usize];
                         for col in 0..columns {
-                            z = input[(row, col)];
+                            z = input.get_value(row, col);
                             if z != nodata {
                                 if z != 0.0 {
                                     data[col as usize] = -z;
@@ -229,7 +229,7 @@ impl WhiteboxTool for Negate {
                     for row in (0..rows).filter(|r| r % num_procs == tid) {
                         let mut data: Vec<f64> = vec![nodata; columns as usize];
                         for col in 0..columns {
-                            z = input[(row, col)];
+                            z = input.get_value(row, col);
                             if z != nodata {
                                 if z == 0.0 {
                                     data[col as usize] = 1.0;
@@ -275,7 +275,7 @@ impl WhiteboxTool for Negate {
                     println!("Output file written")
                 }
             }
-            Err(e) => return Err(e),
+            Err(e) => return Err(e.into()),
         };
         if verbose {
             println!(
@@ -657,7 +657,7 @@ impl WhiteboxTool for PrincipalComponentAnalysis {
             };
             let _ = match output.write() {
                 Ok(_) => (),
-                Err(e) => return Err(e),
+                Err(e) => return Err(e.into()),
             };
         }
 
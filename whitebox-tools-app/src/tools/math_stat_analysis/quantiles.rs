@@ -232,7 +232,7 @@ impl WhiteboxTool for Quantiles {
         let mut bin: isize;
         for row in 0..rows {
             for col in 0..columns {
-                z = input[(row, col)];
+                z = input.get_value(row, col);
                 if z != nodata {
                     bin = ((z - min_value) / highres_bin_size).floor() as isize;
                     if bin >= highres_num_bins {
@@ -272,7 +272,7 @@ impl WhiteboxTool for Quantiles {
         let mut z: f64;
         for row in 0..rows {
             for col in 0..columns {
-                z = input[(row, col)];
+                z = input.get_value(row, col);
                 if z != nodata {
                     let mut i = ((z - min_value) / highres_bin_size).floor() as usize;
                     if i >= highres_num_bins as usize {
@@ -280,7 +280,7 @@ impl WhiteboxTool for Quantiles {
                     }
                     let bin = primary_histo[i];
 
-                    output[(row, col)] = bin + 1.0;
+                    output.set_value(row, col, bin + 1.0);
                 }
             }
             if verbose {
@@ -311,7 +311,7 @@ impl WhiteboxTool for Quantiles {
                     println!("Output file written")
                 }
             }
-            Err(e) => return Err(e),
+            Err(e) => return Err(e.into()),
         };
         if verbose {
             println!(
@@ -254,7 +254,7 @@ impl WhiteboxTool for RandomField {
                     println!("Output file written")
                 }
             }
-            Err(e) => return Err(e),
+            Err(e) => return Err(e.into()),
         };
         if verbose {
             println!(
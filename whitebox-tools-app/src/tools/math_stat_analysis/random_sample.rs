@@ -273,7 +273,7 @@ impl WhiteboxTool for RandomSample {
                     println!("Output file written")
                 }
             }
-            Err(e) => return Err(e),
+            Err(e) => return Err(e.into()),
         };
         if verbose {
             println!(
@@ -216,7 +216,7 @@ impl WhiteboxTool for RasterSummaryStats {
                     let mut minz = f64::INFINITY;
                     let mut maxz = f64::NEG_INFINITY;
                     for col in 0..columns {
-                        z = input[(row, col)];
+                        z = input.get_value(row, col);
                         if z != nodata {
                             n += 1;
                             s += z;
@@ -332,7 +332,7 @@ impl WhiteboxTool for RescaleValueRange {
                 for row in (0..rows).filter(|r| r % num_procs == tid) {
                     let mut data: Vec<f64> = vec![nodata; columns as usize];
                     for col in 0..columns {
-                        z_in = input[(row, col)];
+                        z_in = input.get_value(row, col);
                         if z_in != nodata {
                             if z_in < min_val {
                                 z_in = min_val;
@@ -383,7 +383,7 @@ impl WhiteboxTool for RescaleValueRange {
                     println!("Output file written")
                 }
             }
-            Err(e) => return Err(e),
+            Err(e) => return Err(e.into()),
         };
         if verbose {
             println!(
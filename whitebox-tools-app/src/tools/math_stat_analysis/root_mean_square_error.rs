@@ -231,8 +231,8 @@ impl WhiteboxTool for RootMeanSquareError {
                         let mut sq = 0.0f64;
                         let mut data: Vec<f32> = Vec::with_capacity(columns as usize);
                         for col in 0..columns {
-                            z1 = input[(row, col)];
-                            z2 = base_raster[(row, col)];
+                            z1 = input.get_value(row, col);
+                            z2 = base_raster.get_value(row, col);
                             if z1 != nodata && z2 != nodata_base {
                                 z_diff = z2 - z1;
                                 n += 1;
@@ -330,7 +330,7 @@ impl WhiteboxTool for RootMeanSquareError {
                         let mut sq = 0.0f64;
                         let mut data: Vec<f32> = Vec::with_capacity(columns as usize);
                         for col in 0..columns {
-                            z1 = input[(row, col)];
+                            z1 = input.get_value(row, col);
                             if z1 != nodata {
                                 x = input.get_x_from_column(col);
                                 src_row = (src_north - y) / src_resolution_y;
@@ -340,10 +340,10 @@ impl WhiteboxTool for RootMeanSquareError {
                                 dx = src_col - src_col.floor();
                                 dy = src_row - src_row.floor();
 
-                                n0 = base_raster[(origin_row, origin_col)];
-                                n1 = base_raster[(origin_row, origin_col + 1)];
-                                n2 = base_raster[(origin_row + 1, origin_col)];
-                                n3 = base_raster[(origin_row + 1, origin_col + 1)];
+                                n0 = base_raster.get_value(origin_row, origin_col);
+                                n1 = base_raster.get_value(origin_row, origin_col + 1);
+                                n2 = base_raster.get_value(origin_row + 1, origin_col);
+                                n3 = base_raster.get_value(origin_row + 1, origin_col + 1);
 
                                 if n0 != nodata_base
                                     && n1 != nodata_base
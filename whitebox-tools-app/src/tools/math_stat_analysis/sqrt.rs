@@ -253,7 +253,7 @@ impl WhiteboxTool for SquareRoot {
                     println!("Output file written")
                 }
             }
-            Err(e) => return Err(e),
+            Err(e) => return Err(e.into()),
         };
         if verbose {
             println!(
@@ -343,9 +343,7 @@ impl WhiteboxTool for TrendSurfaceVectorPoints {
             y_val = record.points[0].y - min_y;
             z_val = match vector_data.attributes.get_value(record_num, &field_name) {
                 FieldData::Int(val) => val as f64,
-                // FieldData::Int64(val) => {
-                //     val as f64
-                // },
+                FieldData::BigInt(val) => val as f64,
                 FieldData::Real(val) => val,
                 _ => nodata,
             };
@@ -607,7 +605,7 @@ impl WhiteboxTool for TrendSurfaceVectorPoints {
                     println!("Output file written")
                 }
             }
-            Err(e) => return Err(e),
+            Err(e) => return Err(e.into()),
         };
 
         if verbose {
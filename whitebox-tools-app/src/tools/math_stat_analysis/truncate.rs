@@ -231,7 +231,7 @@ impl WhiteboxTool for Truncate {
                 for row in (0..rows).filter(|r| r % num_procs == tid) {
                     let mut data: Vec<f64> = vec![nodata; columns as usize];
                     for col in 0..columns {
-                        z = input[(row, col)];
+                        z = input.get_value(row, col);
                         if z != nodata {
                             data[col as usize] = (z * multiplier).trunc() / multiplier;
                         } else {
@@ -275,7 +275,7 @@ impl WhiteboxTool for Truncate {
                     println!("Output file written")
                 }
             }
-            Err(e) => return Err(e),
+            Err(e) => return Err(e.into()),
         };
         if verbose {
             println!(
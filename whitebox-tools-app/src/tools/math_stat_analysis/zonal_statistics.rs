@@ -507,7 +507,7 @@ impl WhiteboxTool for ZonalStatistics {
                         println!("Output file written")
                     }
                 }
-                Err(e) => return Err(e),
+                Err(e) => return Err(e.into()),
             };
         }
 
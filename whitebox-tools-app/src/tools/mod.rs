@@ -66,7 +66,7 @@ impl ToolManager {
         tool_names.push("BlockMinimumGridding".to_string());
         tool_names.push("BoundaryShapeComplexity".to_string());
         tool_names.push("BufferRaster".to_string());
-        // tool_names.push("BufferVector".to_string());
+        tool_names.push("BufferVector".to_string());
         tool_names.push("Centroid".to_string());
         tool_names.push("CentroidVector".to_string());
         tool_names.push("Clip".to_string());
@@ -138,6 +138,8 @@ impl ToolManager {
         tool_names.push("ReclassEqualInterval".to_string());
         tool_names.push("ReclassFromFile".to_string());
         tool_names.push("RelatedCircumscribingCircle".to_string());
+        tool_names.push("ReprojectRaster".to_string());
+        tool_names.push("ReprojectVector".to_string());
         tool_names.push("ShapeComplexityIndex".to_string());
         tool_names.push("ShapeComplexityIndexRaster".to_string());
         tool_names.push("SmoothVectors".to_string());
@@ -145,6 +147,8 @@ impl ToolManager {
         tool_names.push("SumOverlay".to_string());
         tool_names.push("SymmetricalDifference".to_string());
         tool_names.push("TINGridding".to_string());
+        tool_names.push("TransformRasterHeights".to_string());
+        tool_names.push("TransformVectorHeights".to_string());
         tool_names.push("Union".to_string());
         tool_names.push("UpdateNodataCells".to_string());
         tool_names.push("VectorHexBinning".to_string());
@@ -322,7 +326,9 @@ impl ToolManager {
         tool_names.push("LidarTINGridding".to_string());
         tool_names.push("LidarTophatTransform".to_string());
         tool_names.push("NormalVectors".to_string());
+        tool_names.push("ReprojectLidar".to_string());
         tool_names.push("SelectTilesByPolygon".to_string());
+        tool_names.push("TransformLidarHeights".to_string());
         tool_names.push("ZlidarToLas".to_string());
 
         // mathematical and statistical_analysis
@@ -558,7 +564,7 @@ impl ToolManager {
                 Some(Box::new(gis_analysis::BoundaryShapeComplexity::new()))
             }
             "bufferraster" => Some(Box::new(gis_analysis::BufferRaster::new())),
-            // "buffervector" => Some(Box::new(gis_analysis::BufferVector::new())),
+            "buffervector" => Some(Box::new(gis_analysis::BufferVector::new())),
             "centroid" => Some(Box::new(gis_analysis::Centroid::new())),
             "centroidvector" => Some(Box::new(gis_analysis::CentroidVector::new())),
             "clip" => Some(Box::new(gis_analysis::Clip::new())),
@@ -654,6 +660,8 @@ impl ToolManager {
             "relatedcircumscribingcircle" => {
                 Some(Box::new(gis_analysis::RelatedCircumscribingCircle::new()))
             }
+            "reprojectraster" => Some(Box::new(gis_analysis::ReprojectRaster::new())),
+            "reprojectvector" => Some(Box::new(gis_analysis::ReprojectVector::new())),
             "shapecomplexityindex" => Some(Box::new(gis_analysis::ShapeComplexityIndex::new())),
             "shapecomplexityindexraster" => {
                 Some(Box::new(gis_analysis::ShapeComplexityIndexRaster::new()))
@@ -663,6 +671,8 @@ impl ToolManager {
             "sumoverlay" => Some(Box::new(gis_analysis::SumOverlay::new())),
             "symmetricaldifference" => Some(Box::new(gis_analysis::SymmetricalDifference::new())),
             "tingridding" => Some(Box::new(gis_analysis::TINGridding::new())),
+            "transformrasterheights" => Some(Box::new(gis_analysis::TransformRasterHeights::new())),
+            "transformvectorheights" => Some(Box::new(gis_analysis::TransformVectorHeights::new())),
             "union" => Some(Box::new(gis_analysis::Union::new())),
             "updatenodatacells" => Some(Box::new(gis_analysis::UpdateNodataCells::new())),
             "vectorhexbinning" => Some(Box::new(gis_analysis::VectorHexBinning::new())),
@@ -916,7 +926,9 @@ impl ToolManager {
             "lidartingridding" => Some(Box::new(lidar_analysis::LidarTINGridding::new())),
             "lidartophattransform" => Some(Box::new(lidar_analysis::LidarTophatTransform::new())),
             "normalvectors" => Some(Box::new(lidar_analysis::NormalVectors::new())),
+            "reprojectlidar" => Some(Box::new(lidar_analysis::ReprojectLidar::new())),
             "selecttilesbypolygon" => Some(Box::new(lidar_analysis::SelectTilesByPolygon::new())),
+            "transformlidarheights" => Some(Box::new(lidar_analysis::TransformLidarHeights::new())),
             "zlidartolas" => Some(Box::new(lidar_analysis::ZlidarToLas::new())),
 
             // mathematical and statistical_analysis
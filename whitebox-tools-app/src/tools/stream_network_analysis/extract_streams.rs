@@ -265,7 +265,7 @@ impl WhiteboxTool for ExtractStreams {
                 for row in (0..rows).filter(|r| r % num_procs == tid) {
                     let mut data = vec![nodata; columns as usize];
                     for col in 0..columns {
-                        z = flow_accum[(row, col)];
+                        z = flow_accum.get_value(row, col);
                         if z != nodata && z > fa_threshold {
                             data[col as usize] = 1.0;
                         } else if z != nodata {
@@ -312,7 +312,7 @@ impl WhiteboxTool for ExtractStreams {
                     println!("Output file written")
                 }
             }
-            Err(e) => return Err(e),
+            Err(e) => return Err(e.into()),
         };
         if verbose {
             println!(
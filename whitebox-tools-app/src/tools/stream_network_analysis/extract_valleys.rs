@@ -419,15 +419,15 @@ impl WhiteboxTool for ExtractValleys {
                         for row in (0..rows).filter(|r| r % num_procs == tid) {
                             let mut data = vec![nodata; columns as usize];
                             for col in 0..columns {
-                                z = input[(row, col)];
+                                z = input.get_value(row, col);
                                 if z != nodata {
-                                    zn1 = input[(row + dy[0], col + dx[0])];
-                                    zn2 = input[(row + dy[1], col + dx[1])];
+                                    zn1 = input.get_value(row + dy[0], col + dx[0]);
+                                    zn2 = input.get_value(row + dy[1], col + dx[1]);
                                     if zn1 != nodata && zn2 != nodata && zn1 > z && zn2 > z {
                                         data[col as usize] = 1f64;
                                     } else {
-                                        zn1 = input[(row + dy[2], col + dx[2])];
-                                        zn2 = input[(row + dy[3], col + dx[3])];
+                                        zn1 = input.get_value(row + dy[2], col + dx[2]);
+                                        zn2 = input.get_value(row + dy[3], col + dx[3]);
                                         if zn1 != nodata && zn2 != nodata && zn1 > z && zn2 > z {
                                             data[col as usize] = 1f64;
                                         } else {
@@ -466,12 +466,12 @@ impl WhiteboxTool for ExtractValleys {
                 let num_scan_cells = dx.len();
                 for row in 0..rows {
                     for col in 0..columns {
-                        z = input[(row, col)];
+                        z = input.get_value(row, col);
                         if z != nodata {
                             maxz = z;
                             which_cell = 3;
                             for n in 0..num_scan_cells {
-                                z = input[(row + dy[n], col + dx[n])];
+                                z = input.get_value(row + dy[n], col + dx[n]);
                                 if z != nodata {
                                     if z > maxz {
                                         maxz = z;
@@ -481,7 +481,7 @@ impl WhiteboxTool for ExtractValleys {
                             }
                             output.set_value(row + dy[which_cell], col + dx[which_cell], 0f64);
                         } else {
-                            output[(row, col)] = nodata;
+                            output.set_value(row, col, nodata);
                         }
                     }
                     if verbose {
@@ -532,11 +532,11 @@ impl WhiteboxTool for ExtractValleys {
                 for a in 0..8 {
                     for row in 0..rows {
                         for col in 0..columns {
-                            z = output[(row, col)];
+                            z = output.get_value(row, col);
                             if z > 0.0 && z != nodata {
                                 // fill the neighbours array
                                 for i in 0..8 {
-                                    neighbours[i] = output[(row + dy[i], col + dx[i])];
+                                    neighbours[i] = output.get_value(row + dy[i], col + dx[i]);
                                 }
 
                                 // scan through element
@@ -547,7 +547,7 @@ impl WhiteboxTool for ExtractValleys {
                                     }
                                 }
                                 if pattern_match {
-                                    output[(row, col)] = 0.0;
+                                    output.set_value(row, col, 0.0);
                                     did_something = true;
                                 }
                             }
@@ -586,7 +586,7 @@ impl WhiteboxTool for ExtractValleys {
                     println!("Output file written")
                 }
             }
-            Err(e) => return Err(e),
+            Err(e) => return Err(e.into()),
         };
         if verbose {
             println!(
@@ -300,11 +300,11 @@ impl WhiteboxTool for FarthestChannelHead {
         let mut count: i8;
         for row in 0..rows {
             for col in 0..columns {
-                if streams[(row, col)] > 0.0 {
+                if streams.get_value(row, col) > 0.0 {
                     count = 0i8;
                     for i in 0..8 {
-                        if streams[(row + d_y[i], col + d_x[i])] > 0.0
-                            && pntr[(row + d_y[i], col + d_x[i])] == inflowing_vals[i]
+                        if streams.get_value(row + d_y[i], col + d_x[i]) > 0.0
+                            && pntr.get_value(row + d_y[i], col + d_x[i]) == inflowing_vals[i]
                         {
                             count += 1;
                         }
@@ -313,13 +313,13 @@ impl WhiteboxTool for FarthestChannelHead {
                     if count == 0 {
                         // It's a headwater; add it to the stack
                         stack.push((row, col));
-                        output[(row, col)] = 0f64;
+                        output.set_value(row, col, 0f64);
                     }
                 } else {
-                    if pntr[(row, col)] != pntr_nodata {
-                        output[(row, col)] = background_val;
+                    if pntr.get_value(row, col) != pntr_nodata {
+                        output.set_value(row, col, background_val);
                     } else {
-                        output[(row, col)] = nodata;
+                        output.set_value(row, col, nodata);
                     }
                     num_solved_cells += 1;
                 }
@@ -373,7 +373,7 @@ impl WhiteboxTool for FarthestChannelHead {
             col = cell.1;
 
             // find the downstream cell
-            dir = pntr[(row, col)] as usize;
+            dir = pntr.get_value(row, col) as usize;
             if dir > 0 {
                 if dir > 128 || pntr_matches[dir] == 999 {
                     return Err(Error::new(ErrorKind::InvalidInput,
@@ -384,9 +384,9 @@ impl WhiteboxTool for FarthestChannelHead {
                 row_n = row + d_y[c];
                 col_n = col + d_x[c];
 
-                length = output[(row, col)] + grid_lengths[c];
-                if output[(row_n, col_n)] < length || output[(row_n, col_n)] == nodata {
-                    output[(row_n, col_n)] = length;
+                length = output.get_value(row, col) + grid_lengths[c];
+                if output.get_value(row_n, col_n) < length || output.get_value(row_n, col_n) == nodata {
+                    output.set_value(row_n, col_n, length);
                 }
 
                 num_inflowing.decrement(row_n, col_n, 1);
@@ -423,7 +423,7 @@ impl WhiteboxTool for FarthestChannelHead {
                     println!("Output file written")
                 }
             }
-            Err(e) => return Err(e),
+            Err(e) => return Err(e.into()),
         };
         if verbose {
             println!(
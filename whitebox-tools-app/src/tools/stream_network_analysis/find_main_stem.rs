@@ -308,11 +308,11 @@ impl WhiteboxTool for FindMainStem {
         let mut current_id = 1f64;
         for row in 0..rows {
             for col in 0..columns {
-                if streams[(row, col)] > 0.0 {
+                if streams.get_value(row, col) > 0.0 {
                     count = 0i8;
                     for i in 0..8 {
-                        if streams[(row + d_y[i], col + d_x[i])] > 0.0
-                            && pntr[(row + d_y[i], col + d_x[i])] == inflowing_vals[i]
+                        if streams.get_value(row + d_y[i], col + d_x[i]) > 0.0
+                            && pntr.get_value(row + d_y[i], col + d_x[i]) == inflowing_vals[i]
                         {
                             count += 1;
                         }
@@ -321,15 +321,15 @@ impl WhiteboxTool for FindMainStem {
                     if count == 0 {
                         // It's a headwater; add it to the stack
                         stack.push((row, col));
-                        output[(row, col)] = current_id;
+                        output.set_value(row, col, current_id);
                         current_id += 1f64;
                         trib_length[(row, col)] = 0f64;
                     }
                 } else {
-                    if pntr[(row, col)] != pntr_nodata {
-                        output[(row, col)] = background_val;
+                    if pntr.get_value(row, col) != pntr_nodata {
+                        output.set_value(row, col, background_val);
                     } else {
-                        output[(row, col)] = nodata;
+                        output.set_value(row, col, nodata);
                     }
                     num_solved_cells += 1;
                 }
@@ -386,10 +386,10 @@ impl WhiteboxTool for FindMainStem {
             row = cell.0;
             col = cell.1;
 
-            val = output[(row, col)];
+            val = output.get_value(row, col);
 
             // find the downstream cell
-            dir = pntr[(row, col)] as usize;
+            dir = pntr.get_value(row, col) as usize;
             if dir > 0 {
                 if dir > 128 || pntr_matches[dir] == 999 {
                     return Err(Error::new(ErrorKind::InvalidInput,
@@ -403,7 +403,7 @@ impl WhiteboxTool for FindMainStem {
                 length = trib_length[(row, col)] + grid_lengths[c];
                 if trib_length[(row_n, col_n)] < length || trib_length[(row_n, col_n)] == nodata {
                     trib_length[(row_n, col_n)] = length;
-                    output[(row_n, col_n)] = val;
+                    output.set_value(row_n, col_n, val);
                 }
 
                 num_inflowing.decrement(row_n, col_n, 1);
@@ -425,15 +425,15 @@ impl WhiteboxTool for FindMainStem {
 
         for row in 0..rows {
             for col in 0..columns {
-                if streams[(row, col)] > 0.0 && streams[(row, col)] != nodata {
-                    val = output[(row, col)];
+                if streams.get_value(row, col) > 0.0 && streams.get_value(row, col) != nodata {
+                    val = output.get_value(row, col);
                     if trib_is_main[val as usize] {
-                        output[(row, col)] = streams[(row, col)];
+                        output.set_value(row, col, streams.get_value(row, col));
                     } else {
-                        output[(row, col)] = background_val;
+                        output.set_value(row, col, background_val);
                     }
                 } else {
-                    output[(row, col)] = background_val;
+                    output.set_value(row, col, background_val);
                 }
             }
             if verbose {
@@ -463,7 +463,7 @@ impl WhiteboxTool for FindMainStem {
                     println!("Output file written")
                 }
             }
-            Err(e) => return Err(e),
+            Err(e) => return Err(e.into()),
         };
         if verbose {
             println!(
@@ -487,7 +487,7 @@ impl WhiteboxTool for RasterizeStreams {
                     println!("Output file written")
                 }
             }
-            Err(e) => return Err(e),
+            Err(e) => return Err(e.into()),
         };
         if verbose {
             println!(
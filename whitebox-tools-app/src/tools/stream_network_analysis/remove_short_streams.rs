@@ -297,11 +297,11 @@ impl WhiteboxTool for RemoveShortStreams {
         let mut current_id = 1f64;
         for row in 0..rows {
             for col in 0..columns {
-                if streams[(row, col)] > 0.0 {
+                if streams.get_value(row, col) > 0.0 {
                     count = 0i8;
                     for i in 0..8 {
-                        if streams[(row + d_y[i], col + d_x[i])] > 0.0
-                            && pntr[(row + d_y[i], col + d_x[i])] == inflowing_vals[i]
+                        if streams.get_value(row + d_y[i], col + d_x[i]) > 0.0
+                            && pntr.get_value(row + d_y[i], col + d_x[i]) == inflowing_vals[i]
                         {
                             count += 1;
                         }
@@ -310,13 +310,13 @@ impl WhiteboxTool for RemoveShortStreams {
                     if count == 0 {
                         // It's a headwater; add it to the stack
                         stack.push((row, col));
-                        output[(row, col)] = current_id;
+                        output.set_value(row, col, current_id);
                         current_id += 1f64;
                         trib_length[(row, col)] = 0f64;
                     }
                 } else {
-                    output[(row, col)] = streams[(row, col)];
-                    if streams[(row, col)] == 0.0 {
+                    output.set_value(row, col, streams.get_value(row, col));
+                    if streams.get_value(row, col) == 0.0 {
                         contains_zeros = true;
                     }
                     num_solved_cells += 1;
@@ -376,10 +376,10 @@ impl WhiteboxTool for RemoveShortStreams {
             row = cell.0;
             col = cell.1;
 
-            val = output[(row, col)];
+            val = output.get_value(row, col);
 
             // find the downstream cell
-            dir = pntr[(row, col)] as usize;
+            dir = pntr.get_value(row, col) as usize;
             if dir > 0 {
                 if dir > 128 || pntr_matches[dir] == 999 {
                     return Err(Error::new(ErrorKind::InvalidInput,
@@ -395,7 +395,7 @@ impl WhiteboxTool for RemoveShortStreams {
 
                 if trib_length[(row_n, col_n)] < length || trib_length[(row_n, col_n)] == nodata {
                     trib_length[(row_n, col_n)] = length;
-                    output[(row_n, col_n)] = val;
+                    output.set_value(row_n, col_n, val);
                 }
 
                 num_inflowing.decrement(row_n, col_n, 1);
@@ -415,12 +415,12 @@ impl WhiteboxTool for RemoveShortStreams {
 
         for row in 0..rows {
             for col in 0..columns {
-                if streams[(row, col)] > 0.0 {
-                    current_id = output[(row, col)];
+                if streams.get_value(row, col) > 0.0 {
+                    current_id = output.get_value(row, col);
                     if stream_lengths[current_id as usize] > min_length {
-                        output[(row, col)] = streams[(row, col)];
+                        output.set_value(row, col, streams.get_value(row, col));
                     } else {
-                        output[(row, col)] = background_val;
+                        output.set_value(row, col, background_val);
                     }
                 }
             }
@@ -451,7 +451,7 @@ impl WhiteboxTool for RemoveShortStreams {
                     println!("Output file written")
                 }
             }
-            Err(e) => return Err(e),
+            Err(e) => return Err(e.into()),
         };
         if verbose {
             println!(
@@ -297,11 +297,11 @@ impl WhiteboxTool for StreamLinkClass {
         let mut count: i8;
         for row in 0..rows {
             for col in 0..columns {
-                if streams[(row, col)] > 0.0 {
+                if streams.get_value(row, col) > 0.0 {
                     count = 0i8;
                     for i in 0..8 {
-                        if streams[(row + dy[i], col + dx[i])] > 0.0
-                            && pntr[(row + dy[i], col + dx[i])] == inflowing_vals[i]
+                        if streams.get_value(row + dy[i], col + dx[i]) > 0.0
+                            && pntr.get_value(row + dy[i], col + dx[i]) == inflowing_vals[i]
                         {
                             count += 1;
                         }
@@ -310,13 +310,13 @@ impl WhiteboxTool for StreamLinkClass {
                     if count == 0 {
                         // It's a headwater; add it to the stack
                         stack.push((row, col));
-                        output[(row, col)] = 3f64;
+                        output.set_value(row, col, 3f64);
                     }
                 } else {
-                    if pntr[(row, col)] != pntr_nodata {
-                        output[(row, col)] = background_val;
+                    if pntr.get_value(row, col) != pntr_nodata {
+                        output.set_value(row, col, background_val);
                     } else {
-                        output[(row, col)] = nodata;
+                        output.set_value(row, col, nodata);
                     }
                     num_solved_cells += 1;
                 }
@@ -368,10 +368,10 @@ impl WhiteboxTool for StreamLinkClass {
             let cell = stack.pop().expect("Error during pop operation.");
             row = cell.0;
             col = cell.1;
-            val = output[(row, col)];
+            val = output.get_value(row, col);
 
             // find the downstream cell
-            dir = pntr[(row, col)] as usize;
+            dir = pntr.get_value(row, col) as usize;
             if dir > 0 {
                 if dir > 128 || pntr_matches[dir] == 999 {
                     return Err(Error::new(ErrorKind::InvalidInput,
@@ -383,14 +383,14 @@ impl WhiteboxTool for StreamLinkClass {
                 col_n = col + dx[c];
 
                 if num_inflowing[(row_n, col_n)] > 1 {
-                    output[(row_n, col_n)] = 4f64;
-                } else if output[(row_n, col_n)] == nodata {
+                    output.set_value(row_n, col_n, 4f64);
+                } else if output.get_value(row_n, col_n) == nodata {
                     // i.e. it hasn't already been assigned a value like at a confluence
                     if val == 3f64 || val == 1f64 {
-                        output[(row_n, col_n)] = 1f64;
+                        output.set_value(row_n, col_n, 1f64);
                     } else {
                         //if val == 4f64 || val == 2f64 {
-                        output[(row_n, col_n)] = 2f64;
+                        output.set_value(row_n, col_n, 2f64);
                     }
                 }
 
@@ -399,7 +399,7 @@ impl WhiteboxTool for StreamLinkClass {
                     stack.push((row_n, col_n));
                 }
             } else {
-                output[(row, col)] = 5f64;
+                output.set_value(row, col, 5f64);
             }
 
             if verbose {
@@ -440,7 +440,7 @@ impl WhiteboxTool for StreamLinkClass {
                     println!("Output file written")
                 }
             }
-            Err(e) => return Err(e),
+            Err(e) => return Err(e.into()),
         };
 
         println!("CLASSIFICATION KEY");
@@ -352,9 +352,9 @@ impl WhiteboxTool for StreamLinkSlope {
         let mut dir: usize;
         for row in 0..rows {
             for col in 0..columns {
-                if streams[(row, col)] > 0.0 && streams[(row, col)] != nodata {
-                    current_id = streams[(row, col)] as usize;
-                    z = dem[(row, col)];
+                if streams.get_value(row, col) > 0.0 && streams.get_value(row, col) != nodata {
+                    current_id = streams.get_value(row, col) as usize;
+                    z = dem.get_value(row, col);
                     if z < min_elev[current_id] {
                         min_elev[current_id] = z;
                     }
@@ -362,8 +362,8 @@ impl WhiteboxTool for StreamLinkSlope {
                         max_elev[current_id] = z;
                     }
 
-                    dir = pntr[(row, col)] as usize;
-                    if dir > 0 && pntr[(row, col)] != pntr_nodata {
+                    dir = pntr.get_value(row, col) as usize;
+                    if dir > 0 && pntr.get_value(row, col) != pntr_nodata {
                         if dir > 128 || pntr_matches[dir] == 999 {
                             return Err(Error::new(ErrorKind::InvalidInput,
                                 "An unexpected value has been identified in the pointer image. This tool requires a pointer grid that has been created using either the D8 or Rho8 tools."));
@@ -390,15 +390,15 @@ impl WhiteboxTool for StreamLinkSlope {
 
         for row in 0..rows {
             for col in 0..columns {
-                if streams[(row, col)] > 0.0 && streams[(row, col)] != nodata {
-                    current_id = streams[(row, col)] as usize;
+                if streams.get_value(row, col) > 0.0 && streams.get_value(row, col) != nodata {
+                    current_id = streams.get_value(row, col) as usize;
                     if link_length[current_id] > 0.0 {
-                        output[(row, col)] = max_elev[current_id];
+                        output.set_value(row, col, max_elev[current_id]);
                     } else {
-                        output[(row, col)] = 0.0;
+                        output.set_value(row, col, 0.0);
                     }
                 } else {
-                    output[(row, col)] = background_val;
+                    output.set_value(row, col, background_val);
                 }
             }
             if verbose {
@@ -440,7 +440,7 @@ impl WhiteboxTool for StreamLinkSlope {
                     println!("Output file written")
                 }
             }
-            Err(e) => return Err(e),
+            Err(e) => return Err(e.into()),
         };
         if verbose {
             println!(
@@ -381,21 +381,21 @@ impl WhiteboxTool for StreamSlopeContinuous {
                 for row in (0..rows).filter(|r| r % num_procs == tid) {
                     let mut data = vec![nodata; columns as usize];
                     for col in 0..columns {
-                        if streams[(row, col)] > 0f64
-                            && streams[(row, col)] != nodata
-                            && dem[(row, col)] != dem_nodata
-                            && pntr[(row, col)] != pntr_nodata
+                        if streams.get_value(row, col) > 0f64
+                            && streams.get_value(row, col) != nodata
+                            && dem.get_value(row, col) != dem_nodata
+                            && pntr.get_value(row, col) != pntr_nodata
                         {
                             // first get the average elevation and distance of the inflowing stream cells
                             z_inflowing = 0f64;
                             dist = 0f64;
                             n_inflowing = 0f64;
                             for n in 0..8 {
-                                if streams[(row + dy[n], col + dx[n])] > 0.0
-                                    && streams[(row + dy[n], col + dx[n])] != nodata
-                                    && pntr[(row + dy[n], col + dx[n])] == inflowing_vals[n]
+                                if streams.get_value(row + dy[n], col + dx[n]) > 0.0
+                                    && streams.get_value(row + dy[n], col + dx[n]) != nodata
+                                    && pntr.get_value(row + dy[n], col + dx[n]) == inflowing_vals[n]
                                 {
-                                    z_inflowing += dem[(row + dy[n], col + dx[n])];
+                                    z_inflowing += dem.get_value(row + dy[n], col + dx[n]);
                                     dist += grid_lengths[n];
                                     n_inflowing += 1f64;
                                 }
@@ -404,21 +404,21 @@ impl WhiteboxTool for StreamSlopeContinuous {
                                 z_inflowing = z_inflowing / n_inflowing;
                                 dist = dist / n_inflowing;
                             } else {
-                                z_inflowing = dem[(row, col)];
+                                z_inflowing = dem.get_value(row, col);
                             }
 
                             // now find the elevation of the downslope stream cell
-                            dir = pntr[(row, col)] as usize;
+                            dir = pntr.get_value(row, col) as usize;
                             if dir > 0 {
                                 c = pntr_matches[dir];
-                                z_dn = dem[(row + dy[c], col + dx[c])];
+                                z_dn = dem.get_value(row + dy[c], col + dx[c]);
                                 if z_dn != dem_nodata {
                                     dist += grid_lengths[c];
                                 } else {
-                                    z_dn = dem[(row, col)];
+                                    z_dn = dem.get_value(row, col);
                                 }
                             } else {
-                                z_dn = dem[(row, col)];
+                                z_dn = dem.get_value(row, col);
                             }
                             if dist > 0f64 {
                                 data[col as usize] =
@@ -474,7 +474,7 @@ impl WhiteboxTool for StreamSlopeContinuous {
                     println!("Output file written")
                 }
             }
-            Err(e) => return Err(e),
+            Err(e) => return Err(e.into()),
         };
         if verbose {
             println!(
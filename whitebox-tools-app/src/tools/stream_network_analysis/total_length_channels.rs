@@ -299,11 +299,11 @@ impl WhiteboxTool for LengthOfUpstreamChannels {
         let mut count: i8;
         for row in 0..rows {
             for col in 0..columns {
-                if streams[(row, col)] > 0.0 {
+                if streams.get_value(row, col) > 0.0 {
                     count = 0i8;
                     for n in 0..8 {
-                        if streams[(row + dy[n], col + dx[n])] > 0.0
-                            && pntr[(row + dy[n], col + dx[n])] == inflowing_vals[n]
+                        if streams.get_value(row + dy[n], col + dx[n]) > 0.0
+                            && pntr.get_value(row + dy[n], col + dx[n]) == inflowing_vals[n]
                         {
                             count += 1;
                         }
@@ -312,13 +312,13 @@ impl WhiteboxTool for LengthOfUpstreamChannels {
                     if count == 0 {
                         // It's a headwater; add it to the stack
                         stack.push((row, col));
-                        output[(row, col)] = 0.0;
+                        output.set_value(row, col, 0.0);
                     }
                 } else {
-                    if pntr[(row, col)] != pntr_nodata {
-                        output[(row, col)] = background_val;
+                    if pntr.get_value(row, col) != pntr_nodata {
+                        output.set_value(row, col, background_val);
                     } else {
-                        output[(row, col)] = nodata;
+                        output.set_value(row, col, nodata);
                     }
                     num_solved_cells += 1;
                 }
@@ -373,7 +373,7 @@ impl WhiteboxTool for LengthOfUpstreamChannels {
             col = cell.1;
 
             // find the downstream cell
-            dir = pntr[(row, col)] as usize;
+            dir = pntr.get_value(row, col) as usize;
             if dir > 0 {
                 if dir > 128 || pntr_matches[dir] == 999 {
                     return Err(Error::new(ErrorKind::InvalidInput,
@@ -381,11 +381,11 @@ impl WhiteboxTool for LengthOfUpstreamChannels {
                 }
                 c = pntr_matches[dir];
                 output.increment(row, col, grid_lengths[c]);
-                val = output[(row, col)];
+                val = output.get_value(row, col);
                 row_n = row + dy[c];
                 col_n = col + dx[c];
-                if output[(row_n, col_n)] == nodata {
-                    output[(row_n, col_n)] = val;
+                if output.get_value(row_n, col_n) == nodata {
+                    output.set_value(row_n, col_n, val);
                 } else {
                     output.increment(row_n, col_n, val);
                 }
@@ -428,7 +428,7 @@ impl WhiteboxTool for LengthOfUpstreamChannels {
                     println!("Output file written")
                 }
             }
-            Err(e) => return Err(e),
+            Err(e) => return Err(e.into()),
         };
         if verbose {
             println!(
@@ -320,7 +320,7 @@ impl WhiteboxTool for VectorStreamNetworkAnalysis {
         output.attributes.add_field(&AttributeField::new("TRIB_ID", FieldDataType::Int, 6u8, 0u8));
         output.attributes.add_field(&AttributeField::new("DISCONT", FieldDataType::Int, 4u8, 0u8));
         
-        let mut output_nodes = Shapefile::new(&output_file.replace(".shp", "_nodes.shp"), ShapeType::Point)?;
+        let mut output_nodes = Shapefile::new(&derived_file_name(&output_file, "_nodes"), ShapeType::Point)?;
         output_nodes.attributes.add_field(&AttributeField::new("FID", FieldDataType::Int, 6u8, 0u8));
         output_nodes.attributes.add_field(&AttributeField::new("TYPE", FieldDataType::Text, 14u8, 0u8));
         
@@ -738,7 +738,7 @@ impl WhiteboxTool for AverageNormalVectorAngularDeviation {
                     println!("Output file written")
                 }
             }
-            Err(e) => return Err(e),
+            Err(e) => return Err(e.into()),
         };
 
         if verbose {
@@ -737,7 +737,7 @@ impl WhiteboxTool for CircularVarianceOfAspect {
                     println!("Output file written")
                 }
             }
-            Err(e) => return Err(e),
+            Err(e) => return Err(e.into()),
         };
 
         if verbose {
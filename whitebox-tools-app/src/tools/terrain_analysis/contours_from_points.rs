@@ -403,6 +403,9 @@ impl WhiteboxTool for ContoursFromPoints {
                         FieldData::Int(val) => {
                             z_values.push(val as f64);
                         }
+                        FieldData::BigInt(val) => {
+                            z_values.push(val as f64);
+                        }
                         FieldData::Real(val) => {
                             z_values.push(val);
                         }
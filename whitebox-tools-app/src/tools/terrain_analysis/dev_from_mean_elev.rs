@@ -509,7 +509,7 @@ impl WhiteboxTool for DevFromMeanElev {
                     println!("Output file written")
                 }
             }
-            Err(e) => return Err(e),
+            Err(e) => return Err(e.into()),
         };
 
         if verbose {
@@ -494,7 +494,7 @@ impl WhiteboxTool for DiffFromMeanElev {
                     println!("Output file written")
                 }
             }
-            Err(e) => return Err(e),
+            Err(e) => return Err(e.into()),
         };
 
         if verbose {
@@ -336,7 +336,7 @@ impl WhiteboxTool for DirectionalRelief {
                 for row in (0..rows).filter(|r| r % num_procs == tid) {
                     let mut data: Vec<f64> = vec![nodata; columns as usize];
                     for col in 0..columns {
-                        current_val = input[(row, col)];
+                        current_val = input.get_value(row, col);
                         if current_val != nodata {
                             total_elevation = 0f64;
                             n_elevations = 0f64;
@@ -361,8 +361,8 @@ impl WhiteboxTool for DirectionalRelief {
                                         //estimate z
                                         y1 = y as isize;
                                         y2 = y1 + y_step * -1isize;
-                                        z1 = input[(y1, x as isize)];
-                                        z2 = input[(y2, x as isize)];
+                                        z1 = input.get_value(y1, x as isize);
+                                        z2 = input.get_value(y2, x as isize);
                                         if z1 != nodata && z2 != nodata {
                                             z = z1 + (y - y1 as f64) * (z2 - z1);
                                             total_elevation += z;
@@ -401,8 +401,8 @@ impl WhiteboxTool for DirectionalRelief {
                                         if x2 < 0 || x2 >= columns {
                                             flag = false;
                                         } else {
-                                            z1 = input[(-y as isize, x1)];
-                                            z2 = input[(y as isize, x2)];
+                                            z1 = input.get_value(-y as isize, x1);
+                                            z2 = input.get_value(y as isize, x2);
                                             if z1 != nodata && z2 != nodata {
                                                 z = z1 + (x - x1 as f64) * (z2 - z1);
                                                 total_elevation += z;
@@ -471,7 +471,7 @@ impl WhiteboxTool for DirectionalRelief {
                     println!("Output file written")
                 }
             }
-            Err(e) => return Err(e),
+            Err(e) => return Err(e.into()),
         };
         if verbose {
             println!(
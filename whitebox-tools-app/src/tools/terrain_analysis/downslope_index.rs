@@ -464,7 +464,7 @@ impl WhiteboxTool for DownslopeIndex {
                     println!("Output file written")
                 }
             }
-            Err(e) => return Err(e),
+            Err(e) => return Err(e.into()),
         };
         if verbose {
             println!(
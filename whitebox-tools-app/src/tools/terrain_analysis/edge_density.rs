@@ -591,7 +591,7 @@ impl WhiteboxTool for EdgeDensity {
                     println!("Output file written")
                 }
             }
-            Err(e) => return Err(e),
+            Err(e) => return Err(e.into()),
         };
         if verbose {
             println!(
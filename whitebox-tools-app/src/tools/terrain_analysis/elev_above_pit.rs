@@ -233,12 +233,12 @@ impl WhiteboxTool for ElevAbovePit {
                 for row in (0..rows).filter(|r| r % num_procs == tid) {
                     let mut data: Vec<i8> = vec![flow_nodata; columns as usize];
                     for col in 0..columns {
-                        z = dem[(row, col)];
+                        z = dem.get_value(row, col);
                         if z != nodata {
                             dir = 0i8;
                             max_slope = f64::MIN;
                             for i in 0..8 {
-                                z_n = dem[(row + dy[i], col + dx[i])];
+                                z_n = dem.get_value(row + dy[i], col + dx[i]);
                                 if z_n != nodata {
                                     slope = (z - z_n) / grid_lengths[i];
                                     if slope > max_slope && slope > 0f64 {
@@ -268,8 +268,8 @@ impl WhiteboxTool for ElevAbovePit {
             flow_dir.set_row_data(row, data);
             for col in 0..columns {
                 if flow_dir[(row, col)] == -1 {
-                    stack.push((row, col, dem[(row, col)]));
-                    output[(row, col)] = 0f64;
+                    stack.push((row, col, dem.get_value(row, col)));
+                    output.set_value(row, col, 0f64);
                     num_solved_cells += 1;
                 }
             }
@@ -300,7 +300,7 @@ impl WhiteboxTool for ElevAbovePit {
                 col_n = col + dx[n];
                 if flow_dir[(row_n, col_n)] == inflowing_vals[n] {
                     stack.push((row_n, col_n, basin_z));
-                    output[(row_n, col_n)] = dem[(row_n, col_n)] - basin_z;
+                    output.set_value(row_n, col_n, dem.get_value(row_n, col_n) - basin_z);
                 }
             }
 
@@ -332,7 +332,7 @@ impl WhiteboxTool for ElevAbovePit {
                     println!("Output file written")
                 }
             }
-            Err(e) => return Err(e),
+            Err(e) => return Err(e.into()),
         };
         if verbose {
             println!(
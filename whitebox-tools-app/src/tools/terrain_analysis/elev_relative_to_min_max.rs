@@ -216,7 +216,7 @@ impl WhiteboxTool for ElevRelativeToMinMax {
                 for row in (0..rows).filter(|r| r % num_procs == tid) {
                     let mut data = vec![nodata; columns as usize];
                     for col in 0..columns {
-                        z = input[(row, col)];
+                        z = input.get_value(row, col);
                         if z != nodata {
                             data[col as usize] = (z - min_val) / range * 100f64;
                         }
@@ -255,7 +255,7 @@ impl WhiteboxTool for ElevRelativeToMinMax {
                     println!("Output file written")
                 }
             }
-            Err(e) => return Err(e),
+            Err(e) => return Err(e.into()),
         };
         if verbose {
             println!(
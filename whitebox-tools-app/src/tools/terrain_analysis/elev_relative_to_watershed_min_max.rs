@@ -253,8 +253,8 @@ impl WhiteboxTool for ElevRelativeToWatershedMinMax {
                 let mut watershed: f64;
                 for row in (0..rows).filter(|r| r % num_procs == tid) {
                     for col in 0..columns {
-                        z = input[(row, col)];
-                        watershed = watersheds[(row, col)];
+                        z = input.get_value(row, col);
+                        watershed = watersheds.get_value(row, col);
                         if z != nodata && watershed != watershed_nodata {
                             watershed -= min_watershed;
                             if z < watershed_min_vals[watershed as usize] {
@@ -305,8 +305,8 @@ impl WhiteboxTool for ElevRelativeToWatershedMinMax {
                 for row in (0..rows).filter(|r| r % num_procs == tid) {
                     let mut data = vec![nodata; columns as usize];
                     for col in 0..columns {
-                        z = input[(row, col)];
-                        watershed = watersheds[(row, col)];
+                        z = input.get_value(row, col);
+                        watershed = watersheds.get_value(row, col);
                         if z != nodata && watershed != watershed_nodata {
                             watershed -= min_watershed;
                             data[col as usize] = (z - watershed_min_vals[watershed as usize])
@@ -350,7 +350,7 @@ impl WhiteboxTool for ElevRelativeToWatershedMinMax {
                     println!("Output file written")
                 }
             }
-            Err(e) => return Err(e),
+            Err(e) => return Err(e.into()),
         };
         if verbose {
             println!(
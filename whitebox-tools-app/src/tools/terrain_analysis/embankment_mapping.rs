@@ -961,7 +961,7 @@ impl WhiteboxTool for EmbankmentMapping {
                         println!("Output file written")
                     }
                 }
-                Err(e) => return Err(e),
+                Err(e) => return Err(e.into()),
             };
         }
 
@@ -981,7 +981,7 @@ impl WhiteboxTool for EmbankmentMapping {
                     println!("Output file written")
                 }
             }
-            Err(e) => return Err(e),
+            Err(e) => return Err(e.into()),
         };
         if verbose {
             println!(
@@ -680,7 +680,7 @@ impl WhiteboxTool for FeaturePreservingSmoothing {
                     println!("Output file written")
                 }
             }
-            Err(e) => return Err(e),
+            Err(e) => return Err(e.into()),
         };
         if verbose {
             println!(
@@ -331,7 +331,7 @@ impl WhiteboxTool for FetchAnalysis {
                 for row in (0..rows).filter(|r| r % num_procs == tid) {
                     let mut data: Vec<f64> = vec![nodata; columns as usize];
                     for col in 0..columns {
-                        current_val = input[(row, col)];
+                        current_val = input.get_value(row, col);
                         if current_val != nodata {
                             //calculate the y intercept of the line equation
                             y_intercept = -row as f64 - line_slope * col as f64;
@@ -362,8 +362,8 @@ impl WhiteboxTool for FetchAnalysis {
                                         //estimate z
                                         y1 = y as isize;
                                         y2 = y1 + y_step * -1isize;
-                                        z1 = input[(y1, x as isize)];
-                                        z2 = input[(y2, x as isize)];
+                                        z1 = input.get_value(y1, x as isize);
+                                        z2 = input.get_value(y2, x as isize);
                                         z = z1 + (y - y1 as f64) * (z2 - z1);
 
                                         if z >= current_val + dist * height_increment {
@@ -402,8 +402,8 @@ impl WhiteboxTool for FetchAnalysis {
                                             flag = false;
                                         // break;
                                         } else {
-                                            z1 = input[(-y as isize, x1)];
-                                            z2 = input[(y as isize, x2)];
+                                            z1 = input.get_value(-y as isize, x1);
+                                            z2 = input.get_value(y as isize, x2);
                                             z = z1 + (x - x1 as f64) * (z2 - z1);
 
                                             if z >= current_val + dist * height_increment {
@@ -467,7 +467,7 @@ impl WhiteboxTool for FetchAnalysis {
                     println!("Output file written")
                 }
             }
-            Err(e) => return Err(e),
+            Err(e) => return Err(e.into()),
         };
         if verbose {
             println!(
@@ -352,7 +352,7 @@ impl WhiteboxTool for FillMissingData {
                         if input.get_value(row_n, col_n) == nodata
                             && edge_connected_nodata.get_value(row_n, col_n) == 0
                         {
-                            frs.insert(col as f64, row as f64, input[(row, col)]);
+                            frs.insert(col as f64, row as f64, input.get_value(row, col));
                             break;
                         }
                     }
@@ -417,7 +417,7 @@ impl WhiteboxTool for FillMissingData {
                                         data[col as usize] = nodata;
                                     }
                                 } else {
-                                    data[col as usize] = input[(row, col)];
+                                    data[col as usize] = input.get_value(row, col);
                                 }
                             }
                             tx1.send((row, data)).unwrap();
@@ -451,7 +451,7 @@ impl WhiteboxTool for FillMissingData {
                                         data[col as usize] = nodata;
                                     }
                                 } else {
-                                    data[col as usize] = input[(row, col)];
+                                    data[col as usize] = input.get_value(row, col);
                                 }
                             }
                             tx1.send((row, data)).unwrap();
@@ -522,7 +522,7 @@ impl WhiteboxTool for FillMissingData {
                     println!("Output file written")
                 }
             }
-            Err(e) => return Err(e),
+            Err(e) => return Err(e.into()),
         };
         if verbose {
             println!(
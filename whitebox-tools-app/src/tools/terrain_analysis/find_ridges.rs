@@ -230,7 +230,7 @@ impl WhiteboxTool for FindRidges {
                 for row in (0..rows).filter(|r| r % num_procs == tid) {
                     let mut data = vec![nodata; columns as usize];
                     for col in 0..columns {
-                        z = input[(row, col)];
+                        z = input.get_value(row, col);
                         if z != nodata {
                             zn1 = input.get_value(row + dy[0], col + dx[0]);
                             zn2 = input.get_value(row + dy[1], col + dx[1]);
@@ -299,11 +299,11 @@ impl WhiteboxTool for FindRidges {
                 for a in 0..8 {
                     for row in 0..rows {
                         for col in 0..columns {
-                            z = output[(row, col)];
+                            z = output.get_value(row, col);
                             if z > 0.0 && z != nodata {
                                 // fill the neighbours array
                                 for i in 0..8 {
-                                    neighbours[i] = output[(row + dy[i], col + dx[i])];
+                                    neighbours[i] = output.get_value(row + dy[i], col + dx[i]);
                                 }
 
                                 // scan through element
@@ -314,7 +314,7 @@ impl WhiteboxTool for FindRidges {
                                     }
                                 }
                                 if pattern_match {
-                                    output[(row, col)] = 0.0;
+                                    output.set_value(row, col, 0.0);
                                     did_something = true;
                                 }
                             }
@@ -349,7 +349,7 @@ impl WhiteboxTool for FindRidges {
                     println!("Output file written")
                 }
             }
-            Err(e) => return Err(e),
+            Err(e) => return Err(e.into()),
         };
         if verbose {
             println!(
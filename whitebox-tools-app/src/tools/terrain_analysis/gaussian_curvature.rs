@@ -574,7 +574,7 @@ impl WhiteboxTool for GaussianCurvature {
                     println!("Output file written")
                 }
             }
-            Err(e) => return Err(e),
+            Err(e) => return Err(e.into()),
         };
 
         if verbose {
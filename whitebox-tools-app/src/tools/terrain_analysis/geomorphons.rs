@@ -409,7 +409,7 @@ impl WhiteboxTool for Geomorphons {
                         if row > 0 && row < (rows - 1) && col > 0 && col < (columns - 1) { // buffer 1 for edges
                             y1 = input.get_y_from_row(row);
                             x1 = input.get_x_from_column(col);
-                            z = input[(row, col)];
+                            z = input.get_value(row, col);
                             if z != nodata {
                                 pattern = [1; 8]; // 0 for balanced ternary
                                 count_pos = 0;
@@ -446,14 +446,14 @@ impl WhiteboxTool for Geomorphons {
                                     r = row + d * dy[dir];
                                     c = col + d * dx[dir];
                                     if r < 0 || r > (rows - 1) || c < 0 || c > (columns - 1) {continue;}
-                                    if input[(r,c)] == nodata {continue;}
+                                    if input.get_value(r, c) == nodata {continue;}
                                     y2 = input.get_y_from_row(r);
                                     x2 = input.get_x_from_column(c);
                                     ydif = y2 - y1;
                                     xdif = x2 - x1;
                                     distance = (ydif*ydif + xdif*xdif).sqrt();
                                     while distance < search_length {
-                                        z2 = input[(r, c)];
+                                        z2 = input.get_value(r, c);
                                         if z2 != nodata { // line-of-sight exists
                                             angle = (z2 - z).atan2(distance);
                                             if angle > z_ang { // get max angle
@@ -569,7 +569,7 @@ impl WhiteboxTool for Geomorphons {
                     println!("Output file written")
                 }
             }
-            Err(e) => return Err(e),
+            Err(e) => return Err(e.into()),
         };
         if verbose {
             println!("{}",
@@ -443,7 +443,7 @@ impl WhiteboxTool for Hillshade {
                     println!("Output file written")
                 }
             }
-            Err(e) => return Err(e),
+            Err(e) => return Err(e.into()),
         };
         if verbose {
             println!(
@@ -489,7 +489,7 @@ impl WhiteboxTool for HorizonAngle {
                     println!("Output file written")
                 }
             }
-            Err(e) => return Err(e),
+            Err(e) => return Err(e.into()),
         };
         if verbose {
             println!(
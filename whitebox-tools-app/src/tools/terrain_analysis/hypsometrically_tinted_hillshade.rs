@@ -1310,7 +1310,7 @@ impl WhiteboxTool for HypsometricallyTintedHillshade {
                     println!("Output file written")
                 }
             }
-            Err(e) => return Err(e),
+            Err(e) => return Err(e.into()),
         };
         if verbose {
             println!(
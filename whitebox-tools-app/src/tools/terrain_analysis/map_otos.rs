@@ -364,7 +364,7 @@ impl WhiteboxTool for MapOffTerrainObjects {
                     println!("Output file written")
                 }
             }
-            Err(e) => return Err(e),
+            Err(e) => return Err(e.into()),
         };
         if verbose {
             println!(
@@ -278,7 +278,7 @@ impl WhiteboxTool for MaxAnisotropyDev {
             sum_sqr = 0f64;
             sum_n = 0;
             for col in 0..columns {
-                val = input[(row, col)];
+                val = input.get_value(row, col);
                 if val == nodata {
                     val = 0f64;
                 } else {
@@ -368,7 +368,7 @@ impl WhiteboxTool for MaxAnisotropyDev {
                             // restricts edge effects
 
                             for col in 0..columns {
-                                z = input_data[(row, col)];
+                                z = input_data.get_value(row, col);
                                 if z != nodata {
                                     // left to right:
                                     // -midpoint  -middle_pane_radius  +middle_pane_radius  +midpoint
@@ -594,15 +594,15 @@ impl WhiteboxTool for MaxAnisotropyDev {
                 for col in 0..columns {
                     z2 = data[col as usize];
                     if z2 != nodata {
-                        z1 = output_mag[(row, col)];
+                        z1 = output_mag.get_value(row, col);
                         if z1 != nodata {
                             if z2 * z2 > z1 * z1 {
-                                output_mag[(row, col)] = z2;
-                                output_scale[(row, col)] = midpoint as f64;
+                                output_mag.set_value(row, col, z2);
+                                output_scale.set_value(row, col, midpoint as f64);
                             }
                         } else {
-                            output_mag[(row, col)] = z2;
-                            output_scale[(row, col)] = midpoint as f64;
+                            output_mag.set_value(row, col, z2);
+                            output_scale.set_value(row, col, midpoint as f64);
                         }
                     }
                 }
@@ -640,7 +640,7 @@ impl WhiteboxTool for MaxAnisotropyDev {
                     println!("Output file written")
                 }
             }
-            Err(e) => return Err(e),
+            Err(e) => return Err(e.into()),
         };
 
         output_scale.configs.palette = "spectrum.plt".to_string();
@@ -663,7 +663,7 @@ impl WhiteboxTool for MaxAnisotropyDev {
                     println!("Output file written")
                 }
             }
-            Err(e) => return Err(e),
+            Err(e) => return Err(e.into()),
         };
 
         if verbose {
@@ -326,7 +326,7 @@ impl WhiteboxTool for MaxAnisotropyDevSignature {
             sum_sqr = 0f64;
             sum_n = 0;
             for col in 0..columns {
-                val = input[(row, col)];
+                val = input.get_value(row, col);
                 if val == nodata {
                     val = 0f64;
                 } else {
@@ -377,7 +377,7 @@ impl WhiteboxTool for MaxAnisotropyDevSignature {
             let mut num_panes_valid: f64;
             for sig_site in 0..signature_sites.len() {
                 let (row, col) = signature_sites[sig_site];
-                z = input[(row, col)];
+                z = input.get_value(row, col);
                 if z != nodata {
                     // top to bottom:
                     // -midpoint  -middle_pane_radius  +middle_pane_radius  +midpoint
@@ -276,7 +276,7 @@ impl WhiteboxTool for MaxDifferenceFromMean {
             sum = 0f64;
             sum_n = 0;
             for col in 0..columns {
-                val = input[(row, col)];
+                val = input.get_value(row, col);
                 if val == nodata {
                     val = 0f64;
                 } else {
@@ -345,7 +345,7 @@ impl WhiteboxTool for MaxDifferenceFromMean {
                         }
                         let mut data = vec![nodata; columns as usize];
                         for col in 0..columns {
-                            z = input_data[(row, col)];
+                            z = input_data.get_value(row, col);
                             if z != nodata {
                                 x1 = col - midpoint - 1;
                                 if x1 < 0 {
@@ -378,15 +378,15 @@ impl WhiteboxTool for MaxDifferenceFromMean {
                 for col in 0..columns {
                     z2 = data[col as usize];
                     if z2 != nodata {
-                        z1 = output_mag[(row, col)];
+                        z1 = output_mag.get_value(row, col);
                         if z1 != nodata {
                             if z2 * z2 > z1 * z1 {
-                                output_mag[(row, col)] = z2;
-                                output_scale[(row, col)] = midpoint as f64;
+                                output_mag.set_value(row, col, z2);
+                                output_scale.set_value(row, col, midpoint as f64);
                             }
                         } else {
-                            output_mag[(row, col)] = z2;
-                            output_scale[(row, col)] = midpoint as f64;
+                            output_mag.set_value(row, col, z2);
+                            output_scale.set_value(row, col, midpoint as f64);
                         }
                     }
                 }
@@ -424,7 +424,7 @@ impl WhiteboxTool for MaxDifferenceFromMean {
                     println!("Output file written")
                 }
             }
-            Err(e) => return Err(e),
+            Err(e) => return Err(e.into()),
         };
 
         output_scale.configs.palette = "spectrum.plt".to_string();
@@ -447,7 +447,7 @@ impl WhiteboxTool for MaxDifferenceFromMean {
                     println!("Output file written")
                 }
             }
-            Err(e) => return Err(e),
+            Err(e) => return Err(e.into()),
         };
 
         if verbose {
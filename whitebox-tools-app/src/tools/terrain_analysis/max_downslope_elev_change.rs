@@ -231,13 +231,13 @@ impl WhiteboxTool for MaxDownslopeElevChange {
                 for row in (0..rows).filter(|r| r % num_procs == tid) {
                     let mut data = vec![nodata; columns as usize];
                     for col in 0..columns {
-                        z = input[(row, col)];
+                        z = input.get_value(row, col);
                         if z != nodata {
                             max_slope = f64::NEG_INFINITY;
                             max_z_change = 0f64;
                             for n in 0..8 {
                                 dist = grid_lengths[n];
-                                zn = input[(row + dy[n], col + dx[n])];
+                                zn = input.get_value(row + dy[n], col + dx[n]);
                                 if zn != nodata && zn < z {
                                     slope = (z - zn) / dist;
 
@@ -288,7 +288,7 @@ impl WhiteboxTool for MaxDownslopeElevChange {
                     println!("Output file written")
                 }
             }
-            Err(e) => return Err(e),
+            Err(e) => return Err(e.into()),
         };
         if verbose {
             println!(
@@ -583,7 +583,7 @@ impl WhiteboxTool for MaximalCurvature {
                     println!("Output file written")
                 }
             }
-            Err(e) => return Err(e),
+            Err(e) => return Err(e.into()),
         };
 
         if verbose {
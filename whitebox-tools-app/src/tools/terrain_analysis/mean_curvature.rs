@@ -575,7 +575,7 @@ impl WhiteboxTool for MeanCurvature {
                     println!("Output file written")
                 }
             }
-            Err(e) => return Err(e),
+            Err(e) => return Err(e.into()),
         };
 
         if verbose {
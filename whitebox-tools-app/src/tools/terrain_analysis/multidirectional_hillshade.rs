@@ -480,7 +480,7 @@ impl WhiteboxTool for MultidirectionalHillshade {
                     println!("Output file written")
                 }
             }
-            Err(e) => return Err(e),
+            Err(e) => return Err(e.into()),
         };
         if verbose {
             println!(
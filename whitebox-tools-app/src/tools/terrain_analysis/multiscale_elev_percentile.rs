@@ -613,7 +613,7 @@ impl WhiteboxTool for MultiscaleElevationPercentile {
                     println!("Output file written")
                 }
             }
-            Err(e) => return Err(e),
+            Err(e) => return Err(e.into()),
         };
 
         drop(output_mag);
@@ -639,7 +639,7 @@ impl WhiteboxTool for MultiscaleElevationPercentile {
                     println!("Output file written")
                 }
             }
-            Err(e) => return Err(e),
+            Err(e) => return Err(e.into()),
         };
 
         if verbose {
@@ -377,7 +377,7 @@ impl WhiteboxTool for MultiscaleRoughness {
             sum = 0f64;
             sum_n = 0;
             for col in 0..columns {
-                val = input[(row, col)];
+                val = input.get_value(row, col);
                 if val == nodata {
                     val = 0f64;
                 } else {
@@ -452,7 +452,7 @@ impl WhiteboxTool for MultiscaleRoughness {
                         }
                         let mut data = vec![nodata; columns as usize];
                         for col in 0..columns {
-                            z = input_data[(row, col)];
+                            z = input_data.get_value(row, col);
                             if z != nodata {
                                 x1 = col - midpoint - 1;
                                 if x1 < 0 {
@@ -563,7 +563,7 @@ impl WhiteboxTool for MultiscaleRoughness {
                         }
                         let mut data = vec![nodata; columns as usize];
                         for col in 0..columns {
-                            z = input_data[(row, col)];
+                            z = input_data.get_value(row, col);
                             if z != nodata {
                                 x1 = col - midpoint - 1;
                                 if x1 < 0 {
@@ -621,7 +621,7 @@ impl WhiteboxTool for MultiscaleRoughness {
                     println!("Output file written")
                 }
             }
-            Err(e) => return Err(e),
+            Err(e) => return Err(e.into()),
         };
 
         output_scale.configs.palette = "spectrum.plt".to_string();
@@ -644,7 +644,7 @@ impl WhiteboxTool for MultiscaleRoughness {
                     println!("Output file written")
                 }
             }
-            Err(e) => return Err(e),
+            Err(e) => return Err(e.into()),
         };
 
         if verbose {
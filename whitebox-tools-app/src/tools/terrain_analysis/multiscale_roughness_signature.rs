@@ -425,7 +425,7 @@ impl WhiteboxTool for MultiscaleRoughnessSignature {
             sum = 0f64;
             sum_n = 0;
             for col in 0..columns {
-                val = input[(row, col)];
+                val = input.get_value(row, col);
                 if val == nodata {
                     val = 0f64;
                 } else {
@@ -495,7 +495,7 @@ impl WhiteboxTool for MultiscaleRoughnessSignature {
                         }
                         let mut data = vec![nodata; columns as usize];
                         for col in 0..columns {
-                            z = input_data[(row, col)];
+                            z = input_data.get_value(row, col);
                             if z != nodata {
                                 x1 = col - midpoint - 1;
                                 if x1 < 0 {
@@ -588,7 +588,7 @@ impl WhiteboxTool for MultiscaleRoughnessSignature {
             let mut z: f64;
             for s in 0..signature_sites.len() {
                 let (row, col) = signature_sites[s];
-                z = input[(row, col)];
+                z = input.get_value(row, col);
                 if z != nodata {
                     y1 = row - midpoint - 1;
                     if y1 < 0 {
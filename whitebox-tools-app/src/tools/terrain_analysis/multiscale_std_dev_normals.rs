@@ -926,7 +926,7 @@ impl WhiteboxTool for MultiscaleStdDevNormals {
                     println!("Output file written")
                 }
             }
-            Err(e) => return Err(e),
+            Err(e) => return Err(e.into()),
         };
 
         drop(output_mag_raster); // Memory requirements: 0.5X
@@ -960,7 +960,7 @@ impl WhiteboxTool for MultiscaleStdDevNormals {
                     println!("Output file written")
                 }
             }
-            Err(e) => return Err(e),
+            Err(e) => return Err(e.into()),
         };
 
         if verbose {
@@ -321,9 +321,9 @@ impl WhiteboxTool for MultiscaleTopographicPositionImage {
                 for row in (0..rows).filter(|r| r % num_procs == tid) {
                     let mut data = vec![nodata_r; columns as usize];
                     for col in 0..columns {
-                        red_val = input_r[(row, col)];
-                        green_val = input_g[(row, col)];
-                        blue_val = input_b[(row, col)];
+                        red_val = input_r.get_value(row, col);
+                        green_val = input_g.get_value(row, col);
+                        blue_val = input_b.get_value(row, col);
                         if red_val != nodata_r && green_val != nodata_g && blue_val != nodata_b {
                             /* Replaced the linear interpolation with this logistic function.*/
                             red_val = (512f64 / (1f64 + (-cutoff * (red_val).abs()).exp())).floor()
@@ -402,7 +402,7 @@ impl WhiteboxTool for MultiscaleTopographicPositionImage {
                     println!("Output file written")
                 }
             }
-            Err(e) => return Err(e),
+            Err(e) => return Err(e.into()),
         };
         if verbose {
             println!(
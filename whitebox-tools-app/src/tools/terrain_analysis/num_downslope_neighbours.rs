@@ -220,11 +220,11 @@ impl WhiteboxTool for NumDownslopeNeighbours {
                 for row in (0..rows).filter(|r| r % num_procs == tid) {
                     let mut data: Vec<f64> = vec![nodata; columns as usize];
                     for col in 0..columns {
-                        z = input[(row, col)];
+                        z = input.get_value(row, col);
                         if z != nodata {
                             count = 0.0;
                             for i in 0..8 {
-                                if input[(row + d_y[i], col + d_x[i])] < z {
+                                if input.get_value(row + d_y[i], col + d_x[i]) < z {
                                     count += 1.0;
                                 }
                             }
@@ -268,7 +268,7 @@ impl WhiteboxTool for NumDownslopeNeighbours {
                     println!("Output file written")
                 }
             }
-            Err(e) => return Err(e),
+            Err(e) => return Err(e.into()),
         };
         if verbose {
             println!(
@@ -381,11 +381,11 @@ impl WhiteboxTool for PennockLandformClass {
                 for row in (0..rows).filter(|r| r % num_procs == tid) {
                     let mut data = vec![-128f64; columns as usize];
                     for col in 0..columns {
-                        z = input[(row, col)];
+                        z = input.get_value(row, col);
                         if z != nodata {
                             z = z * z_factor;
                             for c in 0..8 {
-                                n[c] = input[(row + dy[c], col + dx[c])];
+                                n[c] = input.get_value(row + dy[c], col + dx[c]);
                                 if n[c] != nodata {
                                     n[c] = n[c] * z_factor;
                                 } else {
@@ -508,7 +508,7 @@ impl WhiteboxTool for PennockLandformClass {
                     println!("Output file written")
                 }
             }
-            Err(e) => return Err(e),
+            Err(e) => return Err(e.into()),
         };
 
         if verbose {
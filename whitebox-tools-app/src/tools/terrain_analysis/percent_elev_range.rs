@@ -424,7 +424,7 @@ impl WhiteboxTool for PercentElevRange {
                     println!("Output file written")
                 }
             }
-            Err(e) => return Err(e),
+            Err(e) => return Err(e.into()),
         };
         if verbose {
             println!(
@@ -583,7 +583,7 @@ impl WhiteboxTool for PlanCurvature {
                     println!("Output file written")
                 }
             }
-            Err(e) => return Err(e),
+            Err(e) => return Err(e.into()),
         };
 
         if verbose {
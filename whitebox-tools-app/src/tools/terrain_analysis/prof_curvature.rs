@@ -580,7 +580,7 @@ impl WhiteboxTool for ProfileCurvature {
                     println!("Output file written")
                 }
             }
-            Err(e) => return Err(e),
+            Err(e) => return Err(e.into()),
         };
 
         if verbose {
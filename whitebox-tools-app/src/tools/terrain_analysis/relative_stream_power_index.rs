@@ -274,8 +274,8 @@ impl WhiteboxTool for StreamPowerIndex {
                 for row in (0..rows).filter(|r| r % num_procs == tid) {
                     let mut data: Vec<f64> = vec![sca_nodata; columns as usize];
                     for col in 0..columns {
-                        sca_val = sca[(row, col)];
-                        slope_val = slope[(row, col)];
+                        sca_val = sca.get_value(row, col);
+                        slope_val = slope.get_value(row, col);
                         if sca_val != sca_nodata && slope_val != slope_nodata {
                             data[col as usize] =
                                 sca_val.powf(sca_exponent) * slope_val.to_radians().tan();
@@ -323,7 +323,7 @@ impl WhiteboxTool for StreamPowerIndex {
                     println!("Output file written")
                 }
             }
-            Err(e) => return Err(e),
+            Err(e) => return Err(e.into()),
         };
 
         if sca.configs.maximum < 100.0 {
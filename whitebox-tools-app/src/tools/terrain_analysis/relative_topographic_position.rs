@@ -453,7 +453,7 @@ impl WhiteboxTool for RelativeTopographicPosition {
                     println!("Output file written")
                 }
             }
-            Err(e) => return Err(e),
+            Err(e) => return Err(e.into()),
         };
         if verbose {
             println!(
@@ -627,7 +627,7 @@ impl WhiteboxTool for RemoveOffTerrainObjects {
                     println!("Output file written")
                 }
             }
-            Err(e) => return Err(e),
+            Err(e) => return Err(e.into()),
         };
         if verbose {
             println!(
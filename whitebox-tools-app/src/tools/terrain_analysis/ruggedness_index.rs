@@ -320,7 +320,7 @@ impl WhiteboxTool for RuggednessIndex {
                     println!("Output file written")
                 }
             }
-            Err(e) => return Err(e),
+            Err(e) => return Err(e.into()),
         };
         if verbose {
             println!(
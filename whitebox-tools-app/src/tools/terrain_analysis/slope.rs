@@ -510,7 +510,7 @@ impl WhiteboxTool for Slope {
                     println!("Output file written")
                 }
             }
-            Err(e) => return Err(e),
+            Err(e) => return Err(e.into()),
         };
 
         if verbose {
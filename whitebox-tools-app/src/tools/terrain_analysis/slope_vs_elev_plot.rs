@@ -310,10 +310,10 @@ impl WhiteboxTool for SlopeVsElevationPlot {
                         for row in (0..rows).filter(|r| r % num_procs == tid) {
                             let mut data = vec![nodata; columns as usize];
                             for col in 0..columns {
-                                z = input[(row, col)];
+                                z = input.get_value(row, col);
                                 if z != nodata {
                                     for c in 0..8 {
-                                        n[c] = input[(row + d_y[c], col + d_x[c])];
+                                        n[c] = input.get_value(row + d_y[c], col + d_x[c]);
                                         if n[c] != nodata {
                                             n[c] = n[c] * z_factor;
                                         } else {
@@ -462,10 +462,10 @@ impl WhiteboxTool for SlopeVsElevationPlot {
                         for row in (0..rows).filter(|r| r % num_procs == tid) {
                             let mut data = vec![nodata; columns as usize];
                             for col in 0..columns {
-                                z = input[(row, col)];
+                                z = input.get_value(row, col);
                                 if z != nodata {
                                     for c in 0..8 {
-                                        n[c] = input[(row + d_y[c], col + d_x[c])];
+                                        n[c] = input.get_value(row + d_y[c], col + d_x[c]);
                                         if n[c] != nodata {
                                             n[c] = n[c] * z_factor;
                                         } else {
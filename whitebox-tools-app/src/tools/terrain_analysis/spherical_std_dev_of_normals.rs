@@ -770,7 +770,7 @@ impl WhiteboxTool for SphericalStdDevOfNormals {
                     println!("Output file written")
                 }
             }
-            Err(e) => return Err(e),
+            Err(e) => return Err(e.into()),
         };
 
         if verbose {
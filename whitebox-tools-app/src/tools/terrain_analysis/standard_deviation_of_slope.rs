@@ -334,10 +334,10 @@ impl WhiteboxTool for StandardDeviationOfSlope {
                 for row in (0..rows).filter(|r| r % num_procs == tid) {
                     let mut data = vec![nodata; columns as usize];
                     for col in 0..columns {
-                        z = input[(row, col)];
+                        z = input.get_value(row, col);
                         if z != nodata {
                             for i in 0..8 {
-                                n[i] = input[(row + dy[i], col + dx[i])];
+                                n[i] = input.get_value(row + dy[i], col + dx[i]);
                                 if n[i] != nodata {
                                     n[i] = n[i] * z_factor;
                                 } else {
@@ -384,7 +384,7 @@ impl WhiteboxTool for StandardDeviationOfSlope {
             sum_sqr = 0f64;
             sum_n = 0;
             for col in 0..columns {
-                val = output[(row, col)];
+                val = output.get_value(row, col);
                 if val == nodata {
                     val = 0f64;
                 } else {
@@ -458,7 +458,7 @@ impl WhiteboxTool for StandardDeviationOfSlope {
 
                     let mut data = vec![nodata; columns as usize];
                     for col in 0..columns {
-                        z = input[(row, col)];
+                        z = input.get_value(row, col);
                         if z != nodata {
                             x1 = col - midpoint_x - 1;
                             if x1 < 0 {
@@ -530,7 +530,7 @@ impl WhiteboxTool for StandardDeviationOfSlope {
                     println!("Output file written")
                 }
             }
-            Err(e) => return Err(e),
+            Err(e) => return Err(e.into()),
         };
 
         if verbose {
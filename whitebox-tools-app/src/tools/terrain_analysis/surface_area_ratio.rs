@@ -361,7 +361,7 @@ impl WhiteboxTool for SurfaceAreaRatio {
                     println!("Output file written")
                 }
             }
-            Err(e) => return Err(e),
+            Err(e) => return Err(e.into()),
         };
         if verbose {
             println!(
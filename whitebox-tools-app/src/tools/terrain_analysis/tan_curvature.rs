@@ -590,7 +590,7 @@ impl WhiteboxTool for TangentialCurvature {
                     println!("Output file written")
                 }
             }
-            Err(e) => return Err(e),
+            Err(e) => return Err(e.into()),
         };
 
         if verbose {
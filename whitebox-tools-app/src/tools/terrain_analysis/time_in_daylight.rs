@@ -880,7 +880,7 @@ impl WhiteboxTool for TimeInDaylight {
                         println!("Output file written")
                     }
                 }
-                Err(e) => return Err(e),
+                Err(e) => return Err(e.into()),
             };
 
             if verbose {
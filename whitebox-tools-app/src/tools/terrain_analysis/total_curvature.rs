@@ -551,7 +551,7 @@ impl WhiteboxTool for TotalCurvature {
                     println!("Output file written")
                 }
             }
-            Err(e) => return Err(e),
+            Err(e) => return Err(e.into()),
         };
 
         if verbose {
@@ -708,7 +708,7 @@ impl WhiteboxTool for Viewshed {
                     println!("Output file written")
                 }
             }
-            Err(e) => return Err(e),
+            Err(e) => return Err(e.into()),
         };
         if verbose {
             println!(
@@ -687,7 +687,7 @@ impl WhiteboxTool for VisibilityIndex {
                     println!("Output file written")
                 }
             }
-            Err(e) => return Err(e),
+            Err(e) => return Err(e.into()),
         };
         if verbose {
             println!(
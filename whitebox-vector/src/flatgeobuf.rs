@@ -0,0 +1,1546 @@
+/*
+This code is part of the WhiteboxTools geospatial analysis library.
+License: MIT
+
+Notes: Reading and writing FlatGeobuf (https://flatgeobuf.org/) files. As with GeoJSON and
+GeoPackage, a FlatGeobuf layer is held in memory as a Shapefile. Unlike a Shapefile, a
+FlatGeobuf file has no 2 GB size limit and its text attributes no 254 character limit.
+
+A FlatGeobuf file begins with eight magic bytes, followed by a header and, optionally, a
+packed Hilbert R-tree spatial index, and then the features. The header and each feature
+are FlatBuffers (https://flatbuffers.dev/) tables, preceded by their size. The tables are
+encoded and decoded here directly, following the schemas of the format (header.fbs and
+feature.fbs).
+
+The spatial index is a static R-tree whose leaves are the bounding boxes of the features,
+in the order of the Hilbert curve values of their centres, which is also the order of the
+features in the file. The nodes are stored level by level, beginning with the root, and
+each holds a bounding box and the offset of either its first child node or, for the
+leaves, the feature within the file. The index allows the features that overlap a
+bounding box to be read without reading the rest of the file.
+*/
+
+use crate::geojson::{group_polygon_rings, part_ranges, signed_area, GeometryClass};
+use crate::geopackage::{layer_shape_type, parse_date, FeatureGeometry, Vertex};
+use crate::shapefile::attributes::*;
+use crate::shapefile::geometry::*;
+use crate::shapefile::Shapefile;
+use std::cmp::Reverse;
+use std::f64;
+use std::fs::File;
+use std::io::{BufReader, BufWriter, Error, ErrorKind, Read, Seek, SeekFrom, Write};
+use std::path::Path;
+use whitebox_common::spatial_ref_system::esri_wkt_from_epsg;
+use whitebox_common::structures::{BoundingBox, Point2D};
+
+const MAGIC_BYTES: [u8; 8] = [b'f', b'g', b'b', 3, b'f', b'g', b'b', 0];
+const NODE_ITEM_SIZE: u64 = 40;
+const DEFAULT_NODE_SIZE: u16 = 16;
+
+// geometry types
+const GEOMETRY_UNKNOWN: u8 = 0;
+const GEOMETRY_POINT: u8 = 1;
+const GEOMETRY_LINE_STRING: u8 = 2;
+const GEOMETRY_POLYGON: u8 = 3;
+const GEOMETRY_MULTI_POINT: u8 = 4;
+const GEOMETRY_MULTI_LINE_STRING: u8 = 5;
+const GEOMETRY_MULTI_POLYGON: u8 = 6;
+const GEOMETRY_COLLECTION: u8 = 7;
+
+// column types
+const COLUMN_BYTE: u8 = 0;
+const COLUMN_UBYTE: u8 = 1;
+const COLUMN_BOOL: u8 = 2;
+const COLUMN_SHORT: u8 = 3;
+const COLUMN_USHORT: u8 = 4;
+const COLUMN_INT: u8 = 5;
+const COLUMN_UINT: u8 = 6;
+const COLUMN_LONG: u8 = 7;
+const COLUMN_ULONG: u8 = 8;
+const COLUMN_FLOAT: u8 = 9;
+const COLUMN_DOUBLE: u8 = 10;
+const COLUMN_STRING: u8 = 11;
+const COLUMN_JSON: u8 = 12;
+const COLUMN_DATE_TIME: u8 = 13;
+const COLUMN_BINARY: u8 = 14;
+
+/// Returns true if a file name is that of a FlatGeobuf file (.fgb).
+pub fn is_flatgeobuf_file(file_name: &str) -> bool {
+    file_name.to_ascii_lowercase().ends_with(".fgb")
+}
+
+/// Reads the FlatGeobuf file named by `sf.file_name` into the Shapefile.
+///
+/// All of the features must have geometries of the same class, i.e. points, lines or
+/// polygons. Features without geometries become Null records. The Shapefile is of a Z
+/// ShapeType if the geometries have z values, and of an M ShapeType if they have measures
+/// but not z values. Polygon rings are re-ordered into the clockwise exteriors and
+/// counter-clockwise holes of the Shapefile format.
+///
+/// Each column becomes a field of the attribute table. Integer values that do not fit into 32
+/// bits are read as big integers, or as reals if they are unsigned values that do not fit into
+/// 64 bits, and date-time columns whose values are all dates become date fields. Binary columns cannot
+/// be held in an attribute table and are skipped.
+pub fn read_flatgeobuf(sf: &mut Shapefile) -> Result<(), Error> {
+    let mut reader = open(&sf.file_name)?;
+    let (header, features_start) = read_header(&mut reader)?;
+    reader.seek(SeekFrom::Start(features_start))?;
+    let mut features: Vec<Vec<u8>> = vec![];
+    // A features count of zero means that the number of features is unknown.
+    while header.features_count == 0 || (features.len() as u64) < header.features_count {
+        match read_size_prefixed(&mut reader, true)? {
+            Some(buf) => features.push(buf),
+            None if header.features_count == 0 => break,
+            None => return Err(invalid("The FlatGeobuf file is truncated.")),
+        }
+    }
+    to_shapefile(sf, &header, &features)
+}
+
+/// Reads the features of the FlatGeobuf file named by `sf.file_name` whose bounding boxes
+/// overlap `bbox` into the Shapefile, as `read_flatgeobuf` does. If the file has a spatial
+/// index, only the index nodes that overlap the box and the selected features are read;
+/// otherwise, all of the features are read and those outside of the box are discarded.
+/// The features keep their order within the file.
+pub fn read_flatgeobuf_within(sf: &mut Shapefile, bbox: BoundingBox) -> Result<(), Error> {
+    let mut reader = open(&sf.file_name)?;
+    let (header, features_start) = read_header(&mut reader)?;
+    if header.index_node_size == 0 || header.features_count == 0 {
+        read_flatgeobuf(sf)?;
+        sf.retain_within(bbox);
+        return Ok(());
+    }
+
+    let index_start = features_start - tree_size(header.features_count, header.index_node_size);
+    let offsets = search_index(
+        &mut reader,
+        index_start,
+        header.features_count,
+        header.index_node_size,
+        &bbox,
+    )?;
+    let mut features = Vec::with_capacity(offsets.len());
+    for offset in offsets {
+        reader.seek(SeekFrom::Start(features_start + offset))?;
+        match read_size_prefixed(&mut reader, true)? {
+            Some(buf) => features.push(buf),
+            None => return Err(invalid("The FlatGeobuf file is truncated.")),
+        }
+    }
+    to_shapefile(sf, &header, &features)
+}
+
+/// Writes the Shapefile to the FlatGeobuf file named by `sf.file_name`, with a spatial
+/// index. The features are written in the order of the Hilbert curve values of the
+/// centres of their bounding boxes, as the index requires, and so generally not in the
+/// order of the Shapefile's records.
+///
+/// Lines and polygons are written as MultiLineStrings and MultiPolygons, with polygons'
+/// exterior rings counter-clockwise and their holes clockwise. The z values of Z
+/// ShapeTypes, and the measures of M ShapeTypes and of Z ShapeTypes that have them, are
+/// written. Integer fields are written as 32-bit integer columns, or 64-bit ones if they
+/// hold larger values, real fields as doubles, and date fields as ISO 8601 date-times.
+/// The coordinate reference system is recorded by its WKT and, if it has a known one, its
+/// EPSG code. Null records are written as features without geometries.
+pub fn write_flatgeobuf(sf: &Shapefile) -> Result<(), Error> {
+    let shape_type = sf.header.shape_type;
+    let geometry_type = match shape_type.base_shape_type() {
+        ShapeType::Point => GEOMETRY_POINT,
+        ShapeType::MultiPoint => GEOMETRY_MULTI_POINT,
+        ShapeType::PolyLine => GEOMETRY_MULTI_LINE_STRING,
+        ShapeType::Polygon => GEOMETRY_MULTI_POLYGON,
+        _ => GEOMETRY_UNKNOWN,
+    };
+    let has_z = shape_type.dimension() == ShapeTypeDimension::Z;
+    let has_m = match shape_type.dimension() {
+        ShapeTypeDimension::Measure => true,
+        ShapeTypeDimension::Z => {
+            let mut records = sf.records.iter().filter(|r| r.shape_type != ShapeType::Null);
+            records.clone().next().is_some() && records.all(|r| r.has_m_data())
+        }
+        ShapeTypeDimension::XY => false,
+    };
+
+    // Columns
+    let num_atts = sf.attributes.header.num_records as usize;
+    let fields = sf.attributes.get_fields();
+    let mut has_large_values = vec![false; fields.len()];
+    for i in 0..num_atts {
+        for (j, value) in sf.attributes.get_record(i).iter().enumerate() {
+            if let FieldData::BigInt(_) | FieldData::Real(_) = value {
+                has_large_values[j] = true;
+            }
+        }
+    }
+    let columns = fields
+        .iter()
+        .zip(has_large_values)
+        .map(|(field, has_large_values)| column_for_field(field, has_large_values))
+        .collect::<Vec<Column>>();
+
+    // Encode the features, and find their bounding boxes.
+    let num_features = sf.records.len();
+    let mut features: Vec<Vec<u8>> = Vec::with_capacity(num_features);
+    let mut items: Vec<NodeItem> = Vec::with_capacity(num_features);
+    let mut extent = NodeItem::empty(0);
+    for (i, sfg) in sf.records.iter().enumerate() {
+        let mut fields = vec![];
+        if let Some(geometry) = geometry_table(sfg, has_z, has_m) {
+            fields.push((0, FbField::Object(geometry)));
+        }
+        if i < num_atts {
+            let properties = encode_properties(&sf.attributes.get_record(i), &columns);
+            if !properties.is_empty() {
+                fields.push((1, FbField::Object(FbObject::Bytes(properties))));
+            }
+        }
+        features.push(FbObject::Table(fields).finish());
+        let item = if sfg.shape_type == ShapeType::Null || sfg.points.is_empty() {
+            NodeItem::empty(0)
+        } else {
+            let bb = BoundingBox::from_points(&sfg.points);
+            NodeItem {
+                min_x: bb.min_x,
+                min_y: bb.min_y,
+                max_x: bb.max_x,
+                max_y: bb.max_y,
+                offset: 0,
+            }
+        };
+        extent.expand(&item);
+        items.push(item);
+    }
+
+    // Sort the features by the Hilbert values of their centres and build the index.
+    let mut order = (0..num_features).collect::<Vec<usize>>();
+    let hilbert_values = items.iter().map(|item| hilbert_value(item, &extent)).collect::<Vec<u32>>();
+    order.sort_by_key(|&i| hilbert_values[i]);
+    let mut offset = 0u64;
+    let leaves = order
+        .iter()
+        .map(|&i| {
+            let mut item = items[i];
+            item.offset = offset;
+            offset += 4 + features[i].len() as u64;
+            item
+        })
+        .collect::<Vec<NodeItem>>();
+    let index_node_size = if num_features > 0 { DEFAULT_NODE_SIZE } else { 0 };
+    let tree = if num_features > 0 {
+        build_tree(leaves, index_node_size)
+    } else {
+        vec![]
+    };
+
+    // Header
+    let name = Path::new(&sf.file_name)
+        .file_stem()
+        .map_or(String::new(), |s| s.to_string_lossy().to_string());
+    let mut header_fields = vec![
+        (0, FbField::Object(FbObject::Str(name))),
+        (2, FbField::U8(geometry_type)),
+        (8, FbField::U64(num_features as u64)),
+        (9, FbField::U16(index_node_size)),
+    ];
+    if extent.min_x <= extent.max_x {
+        header_fields.push((
+            1,
+            FbField::Object(FbObject::F64s(vec![
+                extent.min_x,
+                extent.min_y,
+                extent.max_x,
+                extent.max_y,
+            ])),
+        ));
+    }
+    if has_z {
+        header_fields.push((3, FbField::U8(1)));
+    }
+    if has_m {
+        header_fields.push((4, FbField::U8(1)));
+    }
+    if !columns.is_empty() {
+        header_fields.push((
+            7,
+            FbField::Object(FbObject::Tables(columns.iter().map(|c| c.to_table()).collect())),
+        ));
+    }
+    if !sf.projection.trim().is_empty() {
+        let mut crs_fields = vec![(4, FbField::Object(FbObject::Str(sf.projection.trim().to_string())))];
+        if let Some(code) = sf
+            .get_coordinate_reference_system()
+            .and_then(|crs| crs.identify_epsg())
+        {
+            crs_fields.push((0, FbField::Object(FbObject::Str("EPSG".to_string()))));
+            crs_fields.push((1, FbField::I32(code as i32)));
+        }
+        header_fields.push((10, FbField::Object(FbObject::Table(crs_fields))));
+    }
+    let header = FbObject::Table(header_fields).finish();
+
+    let f = File::create(&sf.file_name)?;
+    let mut writer = BufWriter::new(f);
+    writer.write_all(&MAGIC_BYTES)?;
+    writer.write_all(&(header.len() as u32).to_le_bytes())?;
+    writer.write_all(&header)?;
+    for node in &tree {
+        writer.write_all(&node.min_x.to_le_bytes())?;
+        writer.write_all(&node.min_y.to_le_bytes())?;
+        writer.write_all(&node.max_x.to_le_bytes())?;
+        writer.write_all(&node.max_y.to_le_bytes())?;
+        writer.write_all(&node.offset.to_le_bytes())?;
+    }
+    for &i in &order {
+        writer.write_all(&(features[i].len() as u32).to_le_bytes())?;
+        writer.write_all(&features[i])?;
+    }
+    writer.flush()
+}
+
+fn invalid(message: &str) -> Error {
+    Error::new(ErrorKind::InvalidData, message)
+}
+
+fn open(file_name: &str) -> Result<BufReader<File>, Error> {
+    if !Path::new(file_name).is_file() {
+        return Err(Error::new(
+            ErrorKind::NotFound,
+            format!("The file {} does not exist.", file_name),
+        ));
+    }
+    Ok(BufReader::new(File::open(file_name)?))
+}
+
+/// Reads a size-prefixed buffer, i.e. a header or feature. None is returned at the end of
+/// the file.
+fn read_size_prefixed<R: Read>(reader: &mut R, allow_eof: bool) -> Result<Option<Vec<u8>>, Error> {
+    let mut size = [0u8; 4];
+    match reader.read_exact(&mut size) {
+        Ok(()) => {}
+        Err(e) if allow_eof && e.kind() == ErrorKind::UnexpectedEof => return Ok(None),
+        Err(e) => return Err(e),
+    }
+    let mut buf = vec![0u8; u32::from_le_bytes(size) as usize];
+    reader.read_exact(&mut buf)?;
+    Ok(Some(buf))
+}
+
+/// Reads the magic bytes and header of a FlatGeobuf file, returning the header and the
+/// position of the first feature within the file.
+fn read_header<R: Read>(reader: &mut R) -> Result<(Header, u64), Error> {
+    let mut magic = [0u8; 8];
+    reader.read_exact(&mut magic).map_err(|_| invalid("The file is not a FlatGeobuf file."))?;
+    if magic[0..3] != MAGIC_BYTES[0..3] || magic[4..7] != MAGIC_BYTES[4..7] {
+        return Err(invalid("The file is not a FlatGeobuf file."));
+    }
+    if magic[3] != MAGIC_BYTES[3] {
+        return Err(Error::new(
+            ErrorKind::InvalidData,
+            format!(
+                "FlatGeobuf version {} files are not supported; only version {} files can be read.",
+                magic[3], MAGIC_BYTES[3]
+            ),
+        ));
+    }
+    let buf = read_size_prefixed(reader, false)?.unwrap_or_default();
+    let header = Header::parse(&buf)?;
+    let mut features_start = 12 + buf.len() as u64;
+    if header.index_node_size > 0 && header.features_count > 0 {
+        features_start += tree_size(header.features_count, header.index_node_size);
+    }
+    Ok((header, features_start))
+}
+
+/// Converts the features of a FlatGeobuf layer into the records and attributes of the
+/// Shapefile.
+fn to_shapefile(sf: &mut Shapefile, header: &Header, features: &[Vec<u8>]) -> Result<(), Error> {
+    let mut geometries: Vec<FeatureGeometry> = Vec::with_capacity(features.len());
+    let mut values: Vec<Vec<Option<PropertyValue>>> = Vec::with_capacity(features.len());
+    for buf in features {
+        let feature = Table::root(buf)?;
+        let mut g = FeatureGeometry::default();
+        if let Some(geometry) = feature.get_table(0)? {
+            add_geometry(&geometry, header.geometry_type, &mut g)?;
+        }
+        geometries.push(g);
+        values.push(decode_properties(feature.get_bytes(1)?, &header.columns)?);
+    }
+
+    // Geometries
+    let class = match header.geometry_type {
+        GEOMETRY_POINT | GEOMETRY_MULTI_POINT => Some(GeometryClass::Point),
+        GEOMETRY_LINE_STRING | GEOMETRY_MULTI_LINE_STRING => Some(GeometryClass::Line),
+        GEOMETRY_POLYGON | GEOMETRY_MULTI_POLYGON => Some(GeometryClass::Polygon),
+        _ => None,
+    };
+    let (shape_type, has_m) = layer_shape_type(
+        &geometries,
+        class,
+        header.geometry_type == GEOMETRY_MULTI_POINT,
+        header.has_z,
+        header.has_m,
+    )?;
+    sf.header.shape_type = shape_type;
+    sf.records = geometries
+        .iter()
+        .map(|g| g.to_shapefile_geometry(shape_type, has_m))
+        .collect();
+    sf.num_records = sf.records.len();
+
+    // Attributes
+    let mut fields: Vec<(usize, AttributeField)> = vec![];
+    for (j, column) in header.columns.iter().enumerate() {
+        if column.column_type == COLUMN_BINARY {
+            println!(
+                "Warning: The binary column '{}' of the FlatGeobuf file cannot be held in an attribute table and is skipped.",
+                column.name
+            );
+            continue;
+        }
+        let field = column.to_field(values.iter().filter_map(|rec| rec[j].as_ref()));
+        fields.push((j, field));
+    }
+    sf.attributes.add_fields(&fields.iter().map(|f| f.1.clone()).collect());
+    for rec in &values {
+        sf.attributes.add_record(
+            fields
+                .iter()
+                .map(|(j, field)| match &rec[*j] {
+                    Some(value) => value.to_field_data(field),
+                    None => FieldData::Null,
+                })
+                .collect(),
+            false,
+        );
+    }
+
+    // Coordinate reference system
+    if !header.crs_wkt.trim().is_empty() {
+        sf.projection = header.crs_wkt.trim().to_string();
+    } else if (header.crs_org.is_empty() || header.crs_org.eq_ignore_ascii_case("epsg"))
+        && header.crs_code > 0
+        && header.crs_code <= u16::MAX as i32
+    {
+        let wkt = esri_wkt_from_epsg(header.crs_code as u16);
+        if wkt != "Unknown EPSG Code" {
+            sf.projection = wkt;
+        }
+    }
+
+    Ok(())
+}
+
+/// Adds a FlatGeobuf geometry to `g`. The geometry type is that of the layer, unless the
+/// layer is of mixed types, in which case each geometry records its own.
+fn add_geometry(t: &Table, layer_type: u8, g: &mut FeatureGeometry) -> Result<(), Error> {
+    let geometry_type = match layer_type {
+        GEOMETRY_UNKNOWN => t.get_u8(6, GEOMETRY_UNKNOWN)?,
+        _ => layer_type,
+    };
+    if geometry_type == GEOMETRY_MULTI_POLYGON || geometry_type == GEOMETRY_COLLECTION {
+        let parts = t.get_tables(7)?;
+        if !parts.is_empty() {
+            for part in &parts {
+                let part_type = match part.get_u8(6, GEOMETRY_UNKNOWN)? {
+                    GEOMETRY_UNKNOWN if geometry_type == GEOMETRY_MULTI_POLYGON => GEOMETRY_POLYGON,
+                    part_type => part_type,
+                };
+                add_geometry(part, part_type, g)?;
+            }
+            return Ok(());
+        }
+    }
+
+    let xy = t.get_f64s(1)?;
+    let z = t.get_f64s(2)?;
+    let m = t.get_f64s(3)?;
+    let ends = t.get_u32s(0)?;
+    let n = xy.len() / 2;
+    if n == 0 {
+        return Ok(());
+    }
+    g.has_z = g.has_z || !z.is_empty();
+    g.has_m = g.has_m || !m.is_empty();
+    let vertex = |i: usize| Vertex {
+        p: Point2D::new(xy[2 * i], xy[2 * i + 1]),
+        z: z.get(i).copied().unwrap_or(0f64),
+        m: m.get(i).copied().unwrap_or(0f64),
+    };
+    let mut ranges = vec![];
+    let mut start = 0usize;
+    for &end in ends.iter() {
+        let end = end as usize;
+        if end < start || end > n {
+            return Err(invalid("A geometry of the FlatGeobuf file has invalid part ends."));
+        }
+        ranges.push((start, end));
+        start = end;
+    }
+    if ends.is_empty() {
+        ranges.push((0, n));
+    }
+
+    match geometry_type {
+        GEOMETRY_POINT | GEOMETRY_MULTI_POINT => {
+            g.set_class(GeometryClass::Point)?;
+            for i in 0..n {
+                let v = vertex(i);
+                if !v.p.x.is_nan() && !v.p.y.is_nan() {
+                    g.parts.push(vec![v]);
+                }
+            }
+        }
+        GEOMETRY_LINE_STRING | GEOMETRY_MULTI_LINE_STRING => {
+            g.set_class(GeometryClass::Line)?;
+            for &(a, b) in ranges.iter().filter(|(a, b)| b > a) {
+                g.parts.push((a..b).map(vertex).collect());
+            }
+        }
+        GEOMETRY_POLYGON | GEOMETRY_MULTI_POLYGON => {
+            g.set_class(GeometryClass::Polygon)?;
+            for (k, &(a, b)) in ranges.iter().enumerate() {
+                g.push_ring((a..b).map(vertex).collect(), k == 0);
+            }
+        }
+        t => {
+            return Err(Error::new(
+                ErrorKind::InvalidData,
+                format!(
+                    "Unsupported FlatGeobuf geometry type ({}). Only points, lines, polygons, their multi-part forms, and geometry collections of these can be read.",
+                    t
+                ),
+            ))
+        }
+    }
+    Ok(())
+}
+
+/// The coordinates of a geometry as FlatGeobuf stores them: interleaved x and y values,
+/// separate z values and measures, and the ends of the parts, counted in vertices.
+#[derive(Default)]
+struct Coordinates {
+    xy: Vec<f64>,
+    z: Vec<f64>,
+    m: Vec<f64>,
+    ends: Vec<u32>,
+}
+
+impl Coordinates {
+    fn push_range(&mut self, sfg: &ShapefileGeometry, a: usize, b: usize, reverse: bool, has_z: bool, has_m: bool) {
+        let mut push = |i: usize| {
+            self.xy.push(sfg.points[i].x);
+            self.xy.push(sfg.points[i].y);
+            if has_z {
+                self.z.push(sfg.z_array.get(i).copied().unwrap_or(0f64));
+            }
+            if has_m {
+                self.m.push(sfg.m_array.get(i).copied().unwrap_or(0f64));
+            }
+        };
+        if reverse {
+            (a..b).rev().for_each(&mut push);
+        } else {
+            (a..b).for_each(&mut push);
+        }
+        self.ends.push((self.xy.len() / 2) as u32);
+    }
+
+    fn to_table(self, geometry_type: Option<u8>, with_ends: bool) -> FbObject {
+        let mut fields = vec![(1, FbField::Object(FbObject::F64s(self.xy)))];
+        if with_ends && self.ends.len() > 1 {
+            fields.push((0, FbField::Object(FbObject::U32s(self.ends))));
+        }
+        if !self.z.is_empty() {
+            fields.push((2, FbField::Object(FbObject::F64s(self.z))));
+        }
+        if !self.m.is_empty() {
+            fields.push((3, FbField::Object(FbObject::F64s(self.m))));
+        }
+        if let Some(geometry_type) = geometry_type {
+            fields.push((6, FbField::U8(geometry_type)));
+        }
+        FbObject::Table(fields)
+    }
+}
+
+/// Returns the FlatGeobuf geometry table of a record, or None for null and empty records.
+fn geometry_table(sfg: &ShapefileGeometry, has_z: bool, has_m: bool) -> Option<FbObject> {
+    if sfg.shape_type == ShapeType::Null || sfg.points.is_empty() {
+        return None;
+    }
+    let part_ranges = part_ranges(sfg);
+    match sfg.shape_type.base_shape_type() {
+        ShapeType::Point | ShapeType::MultiPoint => {
+            let mut c = Coordinates::default();
+            c.push_range(sfg, 0, sfg.points.len(), false, has_z, has_m);
+            Some(c.to_table(None, false))
+        }
+        ShapeType::PolyLine => {
+            let mut c = Coordinates::default();
+            for &(a, b) in &part_ranges {
+                c.push_range(sfg, a, b, false, has_z, has_m);
+            }
+            Some(c.to_table(None, true))
+        }
+        _ => {
+            let polygons = group_polygon_rings(sfg, &part_ranges)
+                .iter()
+                .map(|rings| {
+                    let mut c = Coordinates::default();
+                    for (i, &(a, b)) in rings.iter().enumerate() {
+                        let is_clockwise = signed_area(&sfg.points[a..b]) <= 0f64;
+                        // exteriors are counter-clockwise and holes clockwise
+                        c.push_range(sfg, a, b, (i == 0) == is_clockwise, has_z, has_m);
+                    }
+                    c.to_table(Some(GEOMETRY_POLYGON), true)
+                })
+                .collect::<Vec<FbObject>>();
+            Some(FbObject::Table(vec![(7, FbField::Object(FbObject::Tables(polygons)))]))
+        }
+    }
+}
+
+/// A column of the attribute table, as described in the header.
+struct Column {
+    name: String,
+    column_type: u8,
+    width: i32,
+    precision: i32,
+    scale: i32,
+}
+
+/// Returns the column that holds an attribute field. Integer fields holding values that
+/// don't fit into 32 bits are written as 64-bit integers.
+fn column_for_field(field: &AttributeField, has_large_values: bool) -> Column {
+    let (column_type, width, precision, scale) = match field.field_type {
+        'N' if field.decimal_count == 0 && has_large_values => (COLUMN_LONG, field.field_length as i32, -1, -1),
+        'N' if field.decimal_count == 0 => (COLUMN_INT, field.field_length as i32, -1, -1),
+        'N' | 'F' => (
+            COLUMN_DOUBLE,
+            -1,
+            field.field_length as i32,
+            field.decimal_count as i32,
+        ),
+        'L' => (COLUMN_BOOL, -1, -1, -1),
+        'D' => (COLUMN_DATE_TIME, -1, -1, -1),
+        _ => (COLUMN_STRING, field.field_length as i32, -1, -1),
+    };
+    Column {
+        name: field.name.clone(),
+        column_type,
+        width,
+        precision,
+        scale,
+    }
+}
+
+impl Column {
+    fn parse(t: &Table) -> Result<Column, Error> {
+        Ok(Column {
+            name: t.get_str(0)?.unwrap_or_default(),
+            column_type: t.get_u8(1, COLUMN_BYTE)?,
+            width: t.get_i32(4, -1)?,
+            precision: t.get_i32(5, -1)?,
+            scale: t.get_i32(6, -1)?,
+        })
+    }
+
+    fn to_table(&self) -> FbObject {
+        let mut fields = vec![
+            (0, FbField::Object(FbObject::Str(self.name.clone()))),
+            (1, FbField::U8(self.column_type)),
+        ];
+        for (id, value) in [(4, self.width), (5, self.precision), (6, self.scale)] {
+            if value != -1 {
+                fields.push((id, FbField::I32(value)));
+            }
+        }
+        FbObject::Table(fields)
+    }
+
+    /// Returns the attribute field that holds the column's values.
+    fn to_field<'a, I: Iterator<Item = &'a PropertyValue>>(&self, values: I) -> AttributeField {
+        let mut length = 1usize;
+        let mut integer_digits = 1usize;
+        let mut decimals = 0usize;
+        let mut fits_int = true;
+        let mut fits_long = true;
+        let mut all_dates = true;
+        for value in values {
+            let s = value.to_string();
+            length = length.max(s.len());
+            match value {
+                PropertyValue::Int(v) => fits_int = fits_int && *v >= i32::MIN as i64 && *v <= i32::MAX as i64,
+                PropertyValue::UInt(v) => {
+                    fits_int = fits_int && *v <= i32::MAX as u64;
+                    fits_long = fits_long && *v <= i64::MAX as u64;
+                }
+                PropertyValue::Text(s) => all_dates = all_dates && s.len() == 10 && parse_date(s).is_some(),
+                _ => {}
+            }
+            if let PropertyValue::Int(_) | PropertyValue::UInt(_) | PropertyValue::Float(_) = value {
+                let mut split = s.splitn(2, '.');
+                integer_digits = integer_digits.max(split.next().unwrap_or("").len());
+                decimals = decimals.max(split.next().unwrap_or("").len());
+            }
+        }
+        let name = self.name.as_str();
+        match self.column_type {
+            COLUMN_BOOL => AttributeField::new(name, FieldDataType::Bool, 1u8, 0u8),
+            COLUMN_BYTE | COLUMN_UBYTE | COLUMN_SHORT | COLUMN_USHORT | COLUMN_INT | COLUMN_UINT
+            | COLUMN_LONG | COLUMN_ULONG => {
+                let length = if self.width > 0 { self.width as usize } else { integer_digits };
+                if fits_int {
+                    AttributeField::new(name, FieldDataType::Int, length.min(18) as u8, 0u8)
+                } else if fits_long {
+                    AttributeField::new(name, FieldDataType::Int, length.max(integer_digits).min(20) as u8, 0u8)
+                } else {
+                    AttributeField::new(name, FieldDataType::Real, length.max(integer_digits).min(254) as u8, 0u8)
+                }
+            }
+            COLUMN_FLOAT | COLUMN_DOUBLE => {
+                let decimals = if self.scale >= 0 { self.scale as usize } else { decimals }.min(15);
+                let length = if self.precision > 0 {
+                    self.precision as usize
+                } else {
+                    integer_digits + decimals + 1
+                };
+                AttributeField::new(name, FieldDataType::Real, length.max(decimals + 2).min(254) as u8, decimals as u8)
+            }
+            COLUMN_DATE_TIME if all_dates => AttributeField::new(name, FieldDataType::Date, 8u8, 0u8),
+            _ => {
+                let length = if self.width > 0 { self.width as usize } else { length };
+                AttributeField::new(name, FieldDataType::Text, length.max(1).min(254) as u8, 0u8)
+            }
+        }
+    }
+}
+
+/// A property value of a feature.
+enum PropertyValue {
+    Int(i64),
+    UInt(u64),
+    Float(f64),
+    Bool(bool),
+    Text(String),
+    Binary,
+}
+
+impl PropertyValue {
+    fn to_string(&self) -> String {
+        match self {
+            PropertyValue::Int(v) => v.to_string(),
+            PropertyValue::UInt(v) => v.to_string(),
+            PropertyValue::Float(v) => v.to_string(),
+            PropertyValue::Bool(v) => v.to_string(),
+            PropertyValue::Text(s) => s.clone(),
+            PropertyValue::Binary => String::new(),
+        }
+    }
+
+    fn to_field_data(&self, field: &AttributeField) -> FieldData {
+        match (field.field_type, self) {
+            ('N', PropertyValue::Int(v)) if field.decimal_count == 0 => FieldData::from_i64(*v),
+            ('N', PropertyValue::UInt(v)) if field.decimal_count == 0 => FieldData::from_i64(*v as i64),
+            ('F', PropertyValue::Int(v)) => FieldData::Real(*v as f64),
+            ('F', PropertyValue::UInt(v)) => FieldData::Real(*v as f64),
+            ('F', PropertyValue::Float(v)) => FieldData::Real(*v),
+            ('L', PropertyValue::Bool(v)) => FieldData::Bool(*v),
+            ('D', PropertyValue::Text(s)) => parse_date(s).unwrap_or(FieldData::Null),
+            ('C', PropertyValue::Binary) => FieldData::Null,
+            ('C', value) => FieldData::Text(value.to_string()),
+            _ => FieldData::Null,
+        }
+    }
+}
+
+/// Encodes the properties of a feature: for each non-null value, the index of its column,
+/// as a u16, followed by the value, in little-endian byte order. Strings and date-times are
+/// preceded by their length in bytes, as a u32.
+fn encode_properties(rec: &[FieldData], columns: &[Column]) -> Vec<u8> {
+    let mut buf = vec![];
+    for (j, (value, column)) in rec.iter().zip(columns.iter()).enumerate() {
+        let push_str = |buf: &mut Vec<u8>, s: &str| {
+            buf.extend_from_slice(&(j as u16).to_le_bytes());
+            buf.extend_from_slice(&(s.len() as u32).to_le_bytes());
+            buf.extend_from_slice(s.as_bytes());
+        };
+        match (column.column_type, value) {
+            (_, FieldData::Null) => {}
+            (COLUMN_STRING, value) => push_str(&mut buf, &value.to_string()),
+            (COLUMN_DATE_TIME, FieldData::Date(d)) => {
+                push_str(&mut buf, &format!("{:04}-{:02}-{:02}", d.year, d.month, d.day))
+            }
+            (COLUMN_INT, FieldData::Int(v)) => {
+                buf.extend_from_slice(&(j as u16).to_le_bytes());
+                buf.extend_from_slice(&v.to_le_bytes());
+            }
+            (COLUMN_LONG, FieldData::Int(v)) => {
+                buf.extend_from_slice(&(j as u16).to_le_bytes());
+                buf.extend_from_slice(&(*v as i64).to_le_bytes());
+            }
+            (COLUMN_LONG, FieldData::BigInt(v)) => {
+                buf.extend_from_slice(&(j as u16).to_le_bytes());
+                buf.extend_from_slice(&v.to_le_bytes());
+            }
+            (COLUMN_LONG, FieldData::Real(v)) => {
+                buf.extend_from_slice(&(j as u16).to_le_bytes());
+                buf.extend_from_slice(&(v.round() as i64).to_le_bytes());
+            }
+            (COLUMN_DOUBLE, FieldData::Real(v)) => {
+                buf.extend_from_slice(&(j as u16).to_le_bytes());
+                buf.extend_from_slice(&v.to_le_bytes());
+            }
+            (COLUMN_DOUBLE, FieldData::Int(v)) => {
+                buf.extend_from_slice(&(j as u16).to_le_bytes());
+                buf.extend_from_slice(&(*v as f64).to_le_bytes());
+            }
+            (COLUMN_DOUBLE, FieldData::BigInt(v)) => {
+                buf.extend_from_slice(&(j as u16).to_le_bytes());
+                buf.extend_from_slice(&(*v as f64).to_le_bytes());
+            }
+            (COLUMN_BOOL, FieldData::Bool(v)) => {
+                buf.extend_from_slice(&(j as u16).to_le_bytes());
+                buf.push(*v as u8);
+            }
+            _ => {} // a value that doesn't match its field's type is written as null
+        }
+    }
+    buf
+}
+
+/// Decodes the properties of a feature into a value, or None, for each column.
+fn decode_properties(buf: &[u8], columns: &[Column]) -> Result<Vec<Option<PropertyValue>>, Error> {
+    let mut values: Vec<Option<PropertyValue>> = columns.iter().map(|_| None).collect();
+    let mut pos = 0usize;
+    while pos < buf.len() {
+        let index = u16::from_le_bytes(read_array(buf, pos)?) as usize;
+        pos += 2;
+        let column = columns.get(index).ok_or_else(|| {
+            invalid("A property of a FlatGeobuf feature refers to a column that does not exist.")
+        })?;
+        let (value, size) = match column.column_type {
+            COLUMN_BYTE => (PropertyValue::Int(read_array::<1>(buf, pos)?[0] as i8 as i64), 1),
+            COLUMN_UBYTE => (PropertyValue::Int(read_array::<1>(buf, pos)?[0] as i64), 1),
+            COLUMN_BOOL => (PropertyValue::Bool(read_array::<1>(buf, pos)?[0] != 0), 1),
+            COLUMN_SHORT => (PropertyValue::Int(i16::from_le_bytes(read_array(buf, pos)?) as i64), 2),
+            COLUMN_USHORT => (PropertyValue::Int(u16::from_le_bytes(read_array(buf, pos)?) as i64), 2),
+            COLUMN_INT => (PropertyValue::Int(i32::from_le_bytes(read_array(buf, pos)?) as i64), 4),
+            COLUMN_UINT => (PropertyValue::Int(u32::from_le_bytes(read_array(buf, pos)?) as i64), 4),
+            COLUMN_LONG => (PropertyValue::Int(i64::from_le_bytes(read_array(buf, pos)?)), 8),
+            COLUMN_ULONG => (PropertyValue::UInt(u64::from_le_bytes(read_array(buf, pos)?)), 8),
+            COLUMN_FLOAT => (PropertyValue::Float(f32::from_le_bytes(read_array(buf, pos)?) as f64), 4),
+            COLUMN_DOUBLE => (PropertyValue::Float(f64::from_le_bytes(read_array(buf, pos)?)), 8),
+            COLUMN_STRING | COLUMN_JSON | COLUMN_DATE_TIME | COLUMN_BINARY => {
+                let len = u32::from_le_bytes(read_array(buf, pos)?) as usize;
+                let bytes = (pos + 4)
+                    .checked_add(len)
+                    .and_then(|end| buf.get(pos + 4..end))
+                    .ok_or_else(|| invalid("The properties of a FlatGeobuf feature are truncated."))?;
+                if column.column_type == COLUMN_BINARY {
+                    (PropertyValue::Binary, 4 + len)
+                } else {
+                    (PropertyValue::Text(String::from_utf8_lossy(bytes).to_string()), 4 + len)
+                }
+            }
+            t => {
+                return Err(Error::new(
+                    ErrorKind::InvalidData,
+                    format!("Unsupported FlatGeobuf column type ({}).", t),
+                ))
+            }
+        };
+        pos += size;
+        values[index] = Some(value);
+    }
+    Ok(values)
+}
+
+/// The parts of the FlatGeobuf header that are used.
+struct Header {
+    geometry_type: u8,
+    has_z: bool,
+    has_m: bool,
+    columns: Vec<Column>,
+    features_count: u64,
+    index_node_size: u16,
+    crs_org: String,
+    crs_code: i32,
+    crs_wkt: String,
+}
+
+impl Header {
+    fn parse(buf: &[u8]) -> Result<Header, Error> {
+        let t = Table::root(buf)?;
+        let mut header = Header {
+            geometry_type: t.get_u8(2, GEOMETRY_UNKNOWN)?,
+            has_z: t.get_u8(3, 0)? != 0,
+            has_m: t.get_u8(4, 0)? != 0,
+            columns: vec![],
+            features_count: t.get_u64(8, 0)?,
+            index_node_size: t.get_u16(9, DEFAULT_NODE_SIZE)?,
+            crs_org: String::new(),
+            crs_code: 0,
+            crs_wkt: String::new(),
+        };
+        for column in t.get_tables(7)? {
+            header.columns.push(Column::parse(&column)?);
+        }
+        if let Some(crs) = t.get_table(10)? {
+            header.crs_org = crs.get_str(0)?.unwrap_or_default();
+            header.crs_code = crs.get_i32(1, 0)?;
+            header.crs_wkt = crs.get_str(4)?.unwrap_or_default();
+        }
+        if header.index_node_size == 1 {
+            return Err(invalid("The spatial index of the FlatGeobuf file has an invalid node size."));
+        }
+        Ok(header)
+    }
+}
+
+////////////////////////////////
+// The packed Hilbert R-tree  //
+////////////////////////////////
+
+/// A node of the spatial index: a bounding box and the offset of either its first child
+/// or, for leaves, the feature within the features section of the file.
+#[derive(Copy, Clone, Debug)]
+struct NodeItem {
+    min_x: f64,
+    min_y: f64,
+    max_x: f64,
+    max_y: f64,
+    offset: u64,
+}
+
+impl NodeItem {
+    fn empty(offset: u64) -> NodeItem {
+        NodeItem {
+            min_x: f64::INFINITY,
+            min_y: f64::INFINITY,
+            max_x: f64::NEG_INFINITY,
+            max_y: f64::NEG_INFINITY,
+            offset,
+        }
+    }
+
+    fn expand(&mut self, other: &NodeItem) {
+        self.min_x = self.min_x.min(other.min_x);
+        self.min_y = self.min_y.min(other.min_y);
+        self.max_x = self.max_x.max(other.max_x);
+        self.max_y = self.max_y.max(other.max_y);
+    }
+
+    fn intersects(&self, bbox: &BoundingBox) -> bool {
+        self.min_x <= bbox.max_x && self.max_x >= bbox.min_x && self.min_y <= bbox.max_y && self.max_y >= bbox.min_y
+    }
+}
+
+/// Returns the range of node positions of each level of the tree, from the leaves up to
+/// the root. The root is stored first and the leaves last.
+fn level_bounds(num_items: u64, node_size: u16) -> Vec<(u64, u64)> {
+    let node_size = node_size.max(2) as u64;
+    let mut level_num_nodes = vec![num_items];
+    let mut n = num_items;
+    let mut num_nodes = n;
+    loop {
+        n = (n + node_size - 1) / node_size;
+        num_nodes += n;
+        level_num_nodes.push(n);
+        if n == 1 {
+            break;
+        }
+    }
+    let mut bounds = Vec::with_capacity(level_num_nodes.len());
+    let mut end = num_nodes;
+    for size in level_num_nodes {
+        bounds.push((end - size, end));
+        end -= size;
+    }
+    bounds
+}
+
+/// Returns the size of the spatial index in bytes.
+fn tree_size(num_items: u64, node_size: u16) -> u64 {
+    level_bounds(num_items, node_size)[0].1 * NODE_ITEM_SIZE
+}
+
+/// Builds the tree from its leaves, which must be in Hilbert order.
+fn build_tree(leaves: Vec<NodeItem>, node_size: u16) -> Vec<NodeItem> {
+    let bounds = level_bounds(leaves.len() as u64, node_size);
+    let mut nodes = vec![NodeItem::empty(0); bounds[0].1 as usize];
+    let leaf_start = bounds[0].0 as usize;
+    nodes[leaf_start..].copy_from_slice(&leaves);
+    for level in 0..bounds.len() - 1 {
+        let (mut pos, end) = (bounds[level].0 as usize, bounds[level].1 as usize);
+        let mut parent = bounds[level + 1].0 as usize;
+        while pos < end {
+            let mut node = NodeItem::empty(pos as u64);
+            for _ in 0..node_size {
+                if pos >= end {
+                    break;
+                }
+                node.expand(&nodes[pos]);
+                pos += 1;
+            }
+            nodes[parent] = node;
+            parent += 1;
+        }
+    }
+    nodes
+}
+
+/// Searches the spatial index, which starts at `index_start` within the file, for the
+/// features that overlap `bbox`, reading only the nodes that are visited. The offsets of
+/// the features within the features section are returned in ascending order.
+fn search_index<R: Read + Seek>(
+    reader: &mut R,
+    index_start: u64,
+    num_items: u64,
+    node_size: u16,
+    bbox: &BoundingBox,
+) -> Result<Vec<u64>, Error> {
+    let bounds = level_bounds(num_items, node_size);
+    let num_nodes = bounds[0].1;
+    let leaf_start = bounds[0].0;
+    let mut offsets = vec![];
+    let mut queue = vec![(0u64, bounds.len() - 1)];
+    let mut buf = vec![0u8; node_size as usize * NODE_ITEM_SIZE as usize];
+    while let Some((node_index, level)) = queue.pop() {
+        let is_leaf = node_index >= leaf_start;
+        let end = (node_index + node_size as u64).min(bounds[level].1);
+        if node_index >= end {
+            return Err(invalid("The spatial index of the FlatGeobuf file is corrupt."));
+        }
+        let bytes = &mut buf[..((end - node_index) * NODE_ITEM_SIZE) as usize];
+        reader.seek(SeekFrom::Start(index_start + node_index * NODE_ITEM_SIZE))?;
+        reader.read_exact(bytes)?;
+        for chunk in bytes.chunks_exact(NODE_ITEM_SIZE as usize) {
+            let value = |k: usize| f64::from_le_bytes(chunk[k * 8..k * 8 + 8].try_into().unwrap());
+            let node = NodeItem {
+                min_x: value(0),
+                min_y: value(1),
+                max_x: value(2),
+                max_y: value(3),
+                offset: u64::from_le_bytes(chunk[32..40].try_into().unwrap()),
+            };
+            if !node.intersects(bbox) {
+                continue;
+            }
+            if is_leaf {
+                offsets.push(node.offset);
+            } else if level > 0 && node.offset < num_nodes {
+                queue.push((node.offset, level - 1));
+            } else {
+                return Err(invalid("The spatial index of the FlatGeobuf file is corrupt."));
+            }
+        }
+    }
+    offsets.sort();
+    Ok(offsets)
+}
+
+/// Returns the Hilbert curve value of the centre of a bounding box within the extent of
+/// the layer. Empty boxes are given the largest value.
+fn hilbert_value(item: &NodeItem, extent: &NodeItem) -> u32 {
+    if item.min_x > item.max_x {
+        return u32::MAX;
+    }
+    let hilbert_max = ((1u32 << 16) - 1) as f64;
+    let scale = |centre: f64, min: f64, max: f64| {
+        if max > min {
+            (hilbert_max * (centre - min) / (max - min)).floor() as u32
+        } else {
+            0u32
+        }
+    };
+    let x = scale((item.min_x + item.max_x) / 2f64, extent.min_x, extent.max_x);
+    let y = scale((item.min_y + item.max_y) / 2f64, extent.min_y, extent.max_y);
+    hilbert(x, y)
+}
+
+/// Returns the position of (x, y), each of 16 bits, along the Hilbert curve. This is the
+/// algorithm of http://threadlocalmutex.com/?p=126, as used by the reference
+/// implementations of FlatGeobuf.
+fn hilbert(x: u32, y: u32) -> u32 {
+    let mut a = x ^ y;
+    let mut b = 0xFFFF ^ a;
+    let mut c = 0xFFFF ^ (x | y);
+    let mut d = x & (y ^ 0xFFFF);
+
+    let mut aa = a | (b >> 1);
+    let mut bb = (a >> 1) ^ a;
+    let mut cc = ((c >> 1) ^ (b & (d >> 1))) ^ c;
+    let mut dd = ((a & (c >> 1)) ^ (d >> 1)) ^ d;
+
+    a = aa;
+    b = bb;
+    c = cc;
+    d = dd;
+    aa = (a & (a >> 2)) ^ (b & (b >> 2));
+    bb = (a & (b >> 2)) ^ (b & ((a ^ b) >> 2));
+    cc ^= (a & (c >> 2)) ^ (b & (d >> 2));
+    dd ^= (b & (c >> 2)) ^ ((a ^ b) & (d >> 2));
+
+    a = aa;
+    b = bb;
+    c = cc;
+    d = dd;
+    aa = (a & (a >> 4)) ^ (b & (b >> 4));
+    bb = (a & (b >> 4)) ^ (b & ((a ^ b) >> 4));
+    cc ^= (a & (c >> 4)) ^ (b & (d >> 4));
+    dd ^= (b & (c >> 4)) ^ ((a ^ b) & (d >> 4));
+
+    a = aa;
+    b = bb;
+    c = cc;
+    d = dd;
+    cc ^= (a & (c >> 8)) ^ (b & (d >> 8));
+    dd ^= (b & (c >> 8)) ^ ((a ^ b) & (d >> 8));
+
+    a = cc ^ (cc >> 1);
+    b = dd ^ (dd >> 1);
+
+    let mut i0 = x ^ y;
+    let mut i1 = b | (0xFFFF ^ (i0 | a));
+
+    i0 = (i0 | (i0 << 8)) & 0x00FF00FF;
+    i0 = (i0 | (i0 << 4)) & 0x0F0F0F0F;
+    i0 = (i0 | (i0 << 2)) & 0x33333333;
+    i0 = (i0 | (i0 << 1)) & 0x55555555;
+
+    i1 = (i1 | (i1 << 8)) & 0x00FF00FF;
+    i1 = (i1 | (i1 << 4)) & 0x0F0F0F0F;
+    i1 = (i1 | (i1 << 2)) & 0x33333333;
+    i1 = (i1 | (i1 << 1)) & 0x55555555;
+
+    (i1 << 1) | i0
+}
+
+////////////////////////////////
+// FlatBuffers decoding       //
+////////////////////////////////
+
+fn read_array<const N: usize>(buf: &[u8], pos: usize) -> Result<[u8; N], Error> {
+    pos.checked_add(N)
+        .and_then(|end| buf.get(pos..end))
+        .map(|b| b.try_into().unwrap())
+        .ok_or_else(|| invalid("The FlatGeobuf file is corrupt."))
+}
+
+/// A table within a FlatBuffers buffer. A table begins with the signed offset of its
+/// vtable, which gives the positions of the fields within the table; fields that are
+/// absent take their default values. Strings, vectors and sub-tables are referred to by
+/// unsigned offsets from the fields that hold them.
+#[derive(Copy, Clone)]
+struct Table<'a> {
+    buf: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Table<'a> {
+    fn root(buf: &'a [u8]) -> Result<Table<'a>, Error> {
+        let pos = u32::from_le_bytes(read_array(buf, 0)?) as usize;
+        Ok(Table { buf, pos })
+    }
+
+    /// Returns the position of a field, if it is present.
+    fn field(&self, id: usize) -> Result<Option<usize>, Error> {
+        let vtable = self.pos as i64 - i32::from_le_bytes(read_array(self.buf, self.pos)?) as i64;
+        if vtable < 0 {
+            return Err(invalid("The FlatGeobuf file is corrupt."));
+        }
+        let vtable = vtable as usize;
+        let vtable_size = u16::from_le_bytes(read_array(self.buf, vtable)?) as usize;
+        let entry = 4 + 2 * id;
+        if entry + 2 > vtable_size {
+            return Ok(None);
+        }
+        let offset = u16::from_le_bytes(read_array(self.buf, vtable + entry)?) as usize;
+        Ok(if offset == 0 { None } else { Some(self.pos + offset) })
+    }
+
+    fn get_u8(&self, id: usize, default: u8) -> Result<u8, Error> {
+        match self.field(id)? {
+            Some(pos) => Ok(read_array::<1>(self.buf, pos)?[0]),
+            None => Ok(default),
+        }
+    }
+
+    fn get_u16(&self, id: usize, default: u16) -> Result<u16, Error> {
+        match self.field(id)? {
+            Some(pos) => Ok(u16::from_le_bytes(read_array(self.buf, pos)?)),
+            None => Ok(default),
+        }
+    }
+
+    fn get_i32(&self, id: usize, default: i32) -> Result<i32, Error> {
+        match self.field(id)? {
+            Some(pos) => Ok(i32::from_le_bytes(read_array(self.buf, pos)?)),
+            None => Ok(default),
+        }
+    }
+
+    fn get_u64(&self, id: usize, default: u64) -> Result<u64, Error> {
+        match self.field(id)? {
+            Some(pos) => Ok(u64::from_le_bytes(read_array(self.buf, pos)?)),
+            None => Ok(default),
+        }
+    }
+
+    /// Returns the position of the object referred to by a field.
+    fn get_offset(&self, id: usize) -> Result<Option<usize>, Error> {
+        match self.field(id)? {
+            Some(pos) => Ok(Some(pos + u32::from_le_bytes(read_array(self.buf, pos)?) as usize)),
+            None => Ok(None),
+        }
+    }
+
+    fn get_table(&self, id: usize) -> Result<Option<Table<'a>>, Error> {
+        Ok(self.get_offset(id)?.map(|pos| Table { buf: self.buf, pos }))
+    }
+
+    /// Returns the position and length of a vector, whose elements are of `size` bytes.
+    fn get_vector(&self, id: usize, size: usize) -> Result<Option<(usize, usize)>, Error> {
+        match self.get_offset(id)? {
+            Some(pos) => {
+                let len = u32::from_le_bytes(read_array(self.buf, pos)?) as usize;
+                match len.checked_mul(size).and_then(|n| n.checked_add(pos + 4)) {
+                    Some(end) if end <= self.buf.len() => Ok(Some((pos + 4, len))),
+                    _ => Err(invalid("The FlatGeobuf file is corrupt.")),
+                }
+            }
+            None => Ok(None),
+        }
+    }
+
+    fn get_bytes(&self, id: usize) -> Result<&'a [u8], Error> {
+        Ok(match self.get_vector(id, 1)? {
+            Some((pos, len)) => &self.buf[pos..pos + len],
+            None => &[],
+        })
+    }
+
+    fn get_str(&self, id: usize) -> Result<Option<String>, Error> {
+        Ok(match self.get_vector(id, 1)? {
+            Some((pos, len)) => Some(String::from_utf8_lossy(&self.buf[pos..pos + len]).to_string()),
+            None => None,
+        })
+    }
+
+    fn get_f64s(&self, id: usize) -> Result<Vec<f64>, Error> {
+        Ok(match self.get_vector(id, 8)? {
+            Some((pos, len)) => self.buf[pos..pos + 8 * len]
+                .chunks_exact(8)
+                .map(|b| f64::from_le_bytes(b.try_into().unwrap()))
+                .collect(),
+            None => vec![],
+        })
+    }
+
+    fn get_u32s(&self, id: usize) -> Result<Vec<u32>, Error> {
+        Ok(match self.get_vector(id, 4)? {
+            Some((pos, len)) => self.buf[pos..pos + 4 * len]
+                .chunks_exact(4)
+                .map(|b| u32::from_le_bytes(b.try_into().unwrap()))
+                .collect(),
+            None => vec![],
+        })
+    }
+
+    fn get_tables(&self, id: usize) -> Result<Vec<Table<'a>>, Error> {
+        let mut tables = vec![];
+        if let Some((pos, len)) = self.get_vector(id, 4)? {
+            for i in 0..len {
+                let p = pos + 4 * i;
+                let target = p + u32::from_le_bytes(read_array(self.buf, p)?) as usize;
+                tables.push(Table { buf: self.buf, pos: target });
+            }
+        }
+        Ok(tables)
+    }
+}
+
+////////////////////////////////
+// FlatBuffers encoding       //
+////////////////////////////////
+
+/// An object to be encoded in a FlatBuffers buffer. Tables hold their fields by id.
+enum FbObject {
+    Table(Vec<(usize, FbField)>),
+    Str(String),
+    Bytes(Vec<u8>),
+    U32s(Vec<u32>),
+    F64s(Vec<f64>),
+    Tables(Vec<FbObject>),
+}
+
+/// A field of a table: a scalar, or a reference to another object.
+enum FbField {
+    U8(u8),
+    U16(u16),
+    I32(i32),
+    U64(u64),
+    Object(FbObject),
+}
+
+impl FbField {
+    fn size(&self) -> usize {
+        match self {
+            FbField::U8(_) => 1,
+            FbField::U16(_) => 2,
+            FbField::I32(_) | FbField::Object(_) => 4,
+            FbField::U64(_) => 8,
+        }
+    }
+}
+
+/// Pads the buffer with zeros until its length plus `extra` is a multiple of `align`.
+fn pad(buf: &mut Vec<u8>, align: usize, extra: usize) {
+    while (buf.len() + extra) % align != 0 {
+        buf.push(0u8);
+    }
+}
+
+impl FbObject {
+    /// Encodes the object as the root of a FlatBuffers buffer. Unlike the FlatBuffers
+    /// builders, which build buffers from back to front, objects are written here from
+    /// front to back, each followed by the objects it refers to, since the unsigned
+    /// offsets of references must point forward. All values are aligned to their size.
+    fn finish(&self) -> Vec<u8> {
+        let mut buf = vec![0u8; 4];
+        let pos = self.write(&mut buf);
+        buf[0..4].copy_from_slice(&(pos as u32).to_le_bytes());
+        buf
+    }
+
+    /// Writes the object to the end of the buffer, returning its position.
+    fn write(&self, buf: &mut Vec<u8>) -> usize {
+        match self {
+            FbObject::Table(fields) => write_table(buf, fields),
+            FbObject::Str(s) => {
+                pad(buf, 4, 0);
+                let pos = buf.len();
+                buf.extend_from_slice(&(s.len() as u32).to_le_bytes());
+                buf.extend_from_slice(s.as_bytes());
+                buf.push(0u8);
+                pos
+            }
+            FbObject::Bytes(b) => {
+                pad(buf, 4, 0);
+                let pos = buf.len();
+                buf.extend_from_slice(&(b.len() as u32).to_le_bytes());
+                buf.extend_from_slice(b);
+                pos
+            }
+            FbObject::U32s(v) => {
+                pad(buf, 4, 0);
+                let pos = buf.len();
+                buf.extend_from_slice(&(v.len() as u32).to_le_bytes());
+                v.iter().for_each(|x| buf.extend_from_slice(&x.to_le_bytes()));
+                pos
+            }
+            FbObject::F64s(v) => {
+                pad(buf, 8, 4);
+                let pos = buf.len();
+                buf.extend_from_slice(&(v.len() as u32).to_le_bytes());
+                v.iter().for_each(|x| buf.extend_from_slice(&x.to_le_bytes()));
+                pos
+            }
+            FbObject::Tables(tables) => {
+                pad(buf, 4, 0);
+                let pos = buf.len();
+                buf.extend_from_slice(&(tables.len() as u32).to_le_bytes());
+                buf.resize(pos + 4 + 4 * tables.len(), 0u8);
+                for (i, table) in tables.iter().enumerate() {
+                    let slot = pos + 4 + 4 * i;
+                    let target = table.write(buf);
+                    buf[slot..slot + 4].copy_from_slice(&((target - slot) as u32).to_le_bytes());
+                }
+                pos
+            }
+        }
+    }
+}
+
+/// Writes a table, preceded by its vtable and followed by the objects that it refers to.
+fn write_table(buf: &mut Vec<u8>, fields: &[(usize, FbField)]) -> usize {
+    // Lay out the fields after the vtable offset, largest first, so that they are aligned.
+    let mut order = (0..fields.len()).collect::<Vec<usize>>();
+    order.sort_by_key(|&i| Reverse(fields[i].1.size()));
+    let mut field_offsets = vec![0usize; fields.len()];
+    let mut table_size = 4usize;
+    let mut align = 4usize;
+    for &i in &order {
+        let size = fields[i].1.size();
+        table_size = (table_size + size - 1) / size * size;
+        field_offsets[i] = table_size;
+        table_size += size;
+        align = align.max(size);
+    }
+
+    let num_slots = fields.iter().map(|f| f.0 + 1).max().unwrap_or(0);
+    let vtable_size = 4 + 2 * num_slots;
+    pad(buf, align, vtable_size);
+    let vtable_pos = buf.len();
+    let mut slots = vec![0u16; num_slots];
+    for (i, (id, _)) in fields.iter().enumerate() {
+        slots[*id] = field_offsets[i] as u16;
+    }
+    buf.extend_from_slice(&(vtable_size as u16).to_le_bytes());
+    buf.extend_from_slice(&(table_size as u16).to_le_bytes());
+    slots.iter().for_each(|s| buf.extend_from_slice(&s.to_le_bytes()));
+
+    let table_pos = buf.len();
+    buf.resize(table_pos + table_size, 0u8);
+    buf[table_pos..table_pos + 4].copy_from_slice(&((table_pos - vtable_pos) as i32).to_le_bytes());
+    for (i, (_, field)) in fields.iter().enumerate() {
+        let pos = table_pos + field_offsets[i];
+        match field {
+            FbField::U8(v) => buf[pos] = *v,
+            FbField::U16(v) => buf[pos..pos + 2].copy_from_slice(&v.to_le_bytes()),
+            FbField::I32(v) => buf[pos..pos + 4].copy_from_slice(&v.to_le_bytes()),
+            FbField::U64(v) => buf[pos..pos + 8].copy_from_slice(&v.to_le_bytes()),
+            FbField::Object(_) => {}
+        }
+    }
+    for (i, (_, field)) in fields.iter().enumerate() {
+        if let FbField::Object(object) = field {
+            let pos = table_pos + field_offsets[i];
+            let target = object.write(buf);
+            buf[pos..pos + 4].copy_from_slice(&((target - pos) as u32).to_le_bytes());
+        }
+    }
+    table_pos
+}
+
+#[cfg(test)]
+mod test {
+    use super::hilbert;
+    use crate::test_utils::*;
+    use crate::*;
+    use std::fs;
+    use whitebox_common::structures::{BoundingBox, Point2D};
+
+    /// Returns the index of the record of `sf` whose geometry is that of `sfg`. The features
+    /// of a FlatGeobuf file are in Hilbert order rather than the order of the records.
+    fn find_record(sf: &Shapefile, sfg: &ShapefileGeometry) -> usize {
+        sf.records
+            .iter()
+            .position(|r| r.parts == sfg.parts && r.points == sfg.points)
+            .expect("The record is missing.")
+    }
+
+    #[test]
+    fn flatgeobuf_round_trip() {
+        for &shape_type in &[
+            ShapeType::Point,
+            ShapeType::MultiPoint,
+            ShapeType::PolyLine,
+            ShapeType::Polygon,
+        ] {
+            let file_name = temp_file(&format!("{}.fgb", shape_type.to_int()));
+            let mut output = sample_file(&file_name, shape_type);
+            output.write().unwrap();
+            let input = Shapefile::read(&file_name).unwrap();
+            assert_eq!(input.header.shape_type, shape_type);
+            assert_eq!(input.num_records, output.num_records);
+            assert_eq!(input.attributes.get_fields().len(), output.attributes.get_fields().len());
+            for i in 0..output.num_records {
+                let j = find_record(&input, &output.records[i]);
+                assert_eq!(input.attributes.get_record(j), output.attributes.get_record(i));
+            }
+            assert!(input.projection.contains("WGS"));
+            fs::remove_file(&file_name).unwrap();
+        }
+    }
+
+    #[test]
+    fn indexed_reads_match_full_scans() {
+        // Enough short lines that the index has several levels.
+        let file_name = temp_file("indexed.fgb");
+        let mut output = Shapefile::new(&file_name, ShapeType::PolyLine).unwrap();
+        output.attributes.add_field(&AttributeField::new("ID", FieldDataType::Int, 6u8, 0u8));
+        let mut seed = 12345u64;
+        let mut random = || {
+            seed = seed.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+            (seed >> 33) as f64 / (1u64 << 31) as f64 * 1000.0
+        };
+        for i in 0..500 {
+            let (x, y) = (random(), random());
+            let mut sfg = ShapefileGeometry::new(ShapeType::PolyLine);
+            sfg.add_part(&points(&[(x, y), (x + random() / 50.0, y + random() / 50.0)]));
+            output.add_record(sfg);
+            output.attributes.add_record(vec![FieldData::Int(i)], false);
+        }
+        output.write().unwrap();
+
+        let ids = |sf: &Shapefile| {
+            let mut ids = (0..sf.num_records)
+                .map(|i| match sf.attributes.get_value(i, "ID") {
+                    FieldData::Int(id) => id,
+                    _ => panic!("The ID is not an integer."),
+                })
+                .collect::<Vec<i32>>();
+            ids.sort();
+            ids
+        };
+        for bbox in [
+            BoundingBox::new(100.0, 300.0, 200.0, 650.0),
+            BoundingBox::new(-10.0, 5.0, -10.0, 1010.0),
+            BoundingBox::new(-10.0, 1050.0, -10.0, 1050.0),
+            BoundingBox::new(2000.0, 3000.0, 0.0, 1000.0),
+        ] {
+            let mut scanned = Shapefile::read(&file_name).unwrap();
+            scanned.retain_within(bbox);
+            let within = Shapefile::read_within(&file_name, bbox).unwrap();
+            assert_eq!(ids(&within), ids(&scanned));
+            for i in 0..within.num_records {
+                let j = find_record(&scanned, &within.records[i]);
+                assert_eq!(within.attributes.get_record(i), scanned.attributes.get_record(j));
+            }
+        }
+        fs::remove_file(&file_name).unwrap();
+    }
+
+    #[test]
+    fn hilbert_curve_is_continuous() {
+        // The centres of the cells of a 16 x 16 grid, in Hilbert order, are each next to the
+        // previous one.
+        let mut cells = vec![];
+        for row in 0..16u32 {
+            for col in 0..16u32 {
+                cells.push((hilbert((col << 12) + 0x800, (row << 12) + 0x800), col, row));
+            }
+        }
+        cells.sort();
+        assert_eq!((cells[0].1, cells[0].2), (0, 0));
+        for w in cells.windows(2) {
+            assert_eq!(w[0].1.abs_diff(w[1].1) + w[0].2.abs_diff(w[1].2), 1);
+        }
+    }
+
+    #[test]
+    fn features_are_written_in_hilbert_order() {
+        // A 4 x 4 grid of points, written row by row from the top, is read in the order of
+        // the Hilbert curve, which steps from each point to one of its neighbours.
+        let file_name = temp_file("hilbert.fgb");
+        let mut output = Shapefile::new(&file_name, ShapeType::Point).unwrap();
+        for row in (0..4).rev() {
+            for col in 0..4 {
+                let mut sfg = ShapefileGeometry::new(ShapeType::Point);
+                sfg.add_point(Point2D::new(col as f64, row as f64));
+                output.add_record(sfg);
+            }
+        }
+        output.write().unwrap();
+        let input = Shapefile::read(&file_name).unwrap();
+        assert_eq!(input.num_records, 16);
+        assert_eq!(input.records[0].points[0], Point2D::new(0.0, 0.0));
+        for i in 1..input.num_records {
+            let (a, b) = (input.records[i - 1].points[0], input.records[i].points[0]);
+            assert_eq!((a.x - b.x).abs() + (a.y - b.y).abs(), 1.0);
+        }
+        fs::remove_file(&file_name).unwrap();
+    }
+}
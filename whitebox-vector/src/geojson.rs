@@ -0,0 +1,804 @@
+/*
+This code is part of the WhiteboxTools geospatial analysis library.
+License: MIT
+
+Notes: Reading and writing GeoJSON (RFC 7946) files. A GeoJSON file is held in memory as a
+Shapefile, i.e. as ShapefileGeometry records and a ShapefileAttributes table, so that it can
+be used wherever a Shapefile can.
+*/
+
+use crate::shapefile::attributes::*;
+use crate::shapefile::geometry::*;
+use crate::shapefile::Shapefile;
+use serde_json::{Map, Number, Value};
+use std::collections::HashMap;
+use std::f64;
+use std::fs::File;
+use std::io::prelude::*;
+use std::io::{BufReader, BufWriter, Error, ErrorKind};
+use std::path::Path;
+use whitebox_common::algorithms::point_in_poly;
+use whitebox_common::spatial_ref_system::esri_wkt_from_epsg;
+use whitebox_common::structures::Point2D;
+
+/// Returns true if a file name has a GeoJSON extension (.geojson or .json).
+pub fn is_geojson_file(file_name: &str) -> bool {
+    match Path::new(file_name).extension() {
+        Some(ext) => {
+            let ext = ext.to_string_lossy().to_lowercase();
+            ext == "geojson" || ext == "json"
+        }
+        None => false,
+    }
+}
+
+/// Reads the GeoJSON file named by `sf.file_name` into the Shapefile. The root of the file
+/// may be a FeatureCollection, a single Feature, or a bare geometry.
+///
+/// All of the features must have geometries of the same class, i.e. points, lines or
+/// polygons, because a Shapefile holds a single ShapeType. Features with null or empty
+/// geometries become Null records. The file is of a Z ShapeType if any position has a third
+/// coordinate, and positions without one are given a z value of zero. Polygon rings are
+/// re-ordered from the counter-clockwise exteriors of RFC 7946 to the clockwise exteriors of
+/// the Shapefile format.
+///
+/// A field is added to the attribute table for each property name, in order of appearance.
+/// Its type is inferred from the property's values, e.g. whole numbers make an integer field,
+/// with values that do not fit into 32 bits read as big integers, and properties with values
+/// of mixed types are held as text. Nested objects and arrays are also held as text, in JSON
+/// notation, and are written back as nested values by `write_geojson`.
+pub fn read_geojson(sf: &mut Shapefile) -> Result<(), Error> {
+    let f = File::open(&sf.file_name)?;
+    let root: Value = serde_json::from_reader(BufReader::new(f)).map_err(|e| {
+        Error::new(
+            ErrorKind::InvalidData,
+            format!("Error parsing GeoJSON file {}: {}", sf.file_name, e),
+        )
+    })?;
+    let empty = Map::new();
+    let features: Vec<(&Value, &Map<String, Value>)> = match member_str(&root, "type") {
+        Some("FeatureCollection") => match root.get("features") {
+            Some(Value::Array(a)) => {
+                let mut features = Vec::with_capacity(a.len());
+                for feature in a {
+                    features.push(feature_parts(feature, &empty)?);
+                }
+                features
+            }
+            _ => return Err(invalid("A FeatureCollection must have a 'features' array.")),
+        },
+        Some("Feature") => vec![feature_parts(&root, &empty)?],
+        Some(_) => vec![(&root, &empty)],
+        None => return Err(invalid("The GeoJSON object does not have a 'type' member.")),
+    };
+
+    // Geometries
+    let mut geometries = Vec::with_capacity(features.len());
+    for (geometry, _) in &features {
+        geometries.push(parse_geometry(geometry)?);
+    }
+    let mut class: Option<GeometryClass> = None;
+    let mut is_multipoint = false;
+    let mut has_z = false;
+    for g in geometries.iter().filter(|g| !g.parts.is_empty()) {
+        match class {
+            None => class = Some(g.class),
+            Some(c) if c != g.class => {
+                return Err(invalid(
+                    "The features of a GeoJSON file must all have geometries of the same class (points, lines or polygons).",
+                ))
+            }
+            _ => {}
+        }
+        is_multipoint = is_multipoint || (g.class == GeometryClass::Point && g.parts.len() != 1);
+        has_z = has_z || g.has_z;
+    }
+    let shape_type = match (class, has_z) {
+        (None, _) => ShapeType::Null,
+        (Some(GeometryClass::Point), false) if is_multipoint => ShapeType::MultiPoint,
+        (Some(GeometryClass::Point), true) if is_multipoint => ShapeType::MultiPointZ,
+        (Some(GeometryClass::Point), false) => ShapeType::Point,
+        (Some(GeometryClass::Point), true) => ShapeType::PointZ,
+        (Some(GeometryClass::Line), false) => ShapeType::PolyLine,
+        (Some(GeometryClass::Line), true) => ShapeType::PolyLineZ,
+        (Some(GeometryClass::Polygon), false) => ShapeType::Polygon,
+        (Some(GeometryClass::Polygon), true) => ShapeType::PolygonZ,
+    };
+    sf.header.shape_type = shape_type;
+    sf.records = geometries
+        .into_iter()
+        .map(|g| g.to_shapefile_geometry(shape_type))
+        .collect();
+    sf.num_records = sf.records.len();
+
+    // Attributes
+    let mut names: Vec<String> = vec![];
+    let mut field_map: HashMap<String, usize> = HashMap::new();
+    let mut kinds: Vec<PropertyKind> = vec![];
+    for (_, properties) in &features {
+        for (name, value) in properties.iter() {
+            let index = *field_map.entry(name.clone()).or_insert_with(|| {
+                names.push(name.clone());
+                kinds.push(PropertyKind::default());
+                names.len() - 1
+            });
+            kinds[index].update(value);
+        }
+    }
+    let fields = names
+        .iter()
+        .zip(kinds.iter())
+        .map(|(name, kind)| kind.to_field(name))
+        .collect::<Vec<AttributeField>>();
+    sf.attributes.add_fields(&fields);
+    for (_, properties) in &features {
+        let mut rec = vec![FieldData::Null; fields.len()];
+        for (name, value) in properties.iter() {
+            let index = field_map[name];
+            rec[index] = kinds[index].to_field_data(value);
+        }
+        sf.attributes.add_record(rec, false);
+    }
+
+    // Coordinate reference system. RFC 7946 coordinates are WGS 84 longitudes and latitudes,
+    // but the 'crs' member of the earlier GeoJSON specification is still recognized.
+    let epsg = match root.get("crs") {
+        Some(crs) => parse_crs(crs).unwrap_or_else(|| {
+            println!("Warning: The 'crs' member of the GeoJSON file could not be interpreted.");
+            0
+        }),
+        None => 4326,
+    };
+    if epsg != 0 {
+        match u16::try_from(epsg) {
+            Ok(code) => sf.projection = esri_wkt_from_epsg(code),
+            Err(_) => println!(
+                "Warning: EPSG code {} of the GeoJSON file is not recognized.",
+                epsg
+            ),
+        }
+    }
+
+    Ok(())
+}
+
+/// Writes the Shapefile to the GeoJSON file named by `sf.file_name`, as a FeatureCollection.
+///
+/// Each record becomes a Feature whose properties are the record's attributes. Polygon rings
+/// are written with counter-clockwise exteriors and clockwise holes, following RFC 7946, and
+/// records with several exterior rings become MultiPolygons. The z values of Z ShapeTypes are
+/// written as the third coordinate of each position, while measures, which have no GeoJSON
+/// representation, are omitted. Text attributes that hold JSON objects or arrays, such as
+/// the nested properties read by `read_geojson`, are written as nested properties.
+///
+/// RFC 7946 requires WGS 84 longitudes and latitudes. Coordinates in any other system are
+/// written as they are, with a 'crs' member naming the EPSG code of the system, as in the
+/// earlier GeoJSON specification.
+pub fn write_geojson(sf: &Shapefile) -> Result<(), Error> {
+    let f = File::create(&sf.file_name)?;
+    let mut writer = BufWriter::new(f);
+
+    writer.write_all(b"{\"type\":\"FeatureCollection\",")?;
+    if !sf.projection.trim().is_empty() {
+        match sf
+            .get_coordinate_reference_system()
+            .and_then(|crs| crs.identify_epsg())
+        {
+            Some(4326) => {}
+            Some(epsg) => {
+                writer.write_all(
+                    format!(
+                        "\"crs\":{{\"type\":\"name\",\"properties\":{{\"name\":\"urn:ogc:def:crs:EPSG::{}\"}}}},",
+                        epsg
+                    )
+                    .as_bytes(),
+                )?;
+            }
+            None => println!(
+                "Warning: The coordinate reference system of {} does not have a known EPSG code and is not recorded in the file.",
+                sf.file_name
+            ),
+        }
+    }
+    writer.write_all(b"\"features\":[")?;
+
+    let fields = sf.attributes.get_fields();
+    let num_atts = sf.attributes.header.num_records as usize;
+    for i in 0..sf.records.len() {
+        if i > 0 {
+            writer.write_all(b",")?;
+        }
+        let mut properties = Map::new();
+        if i < num_atts {
+            let rec = sf.attributes.get_record(i);
+            for (field, value) in fields.iter().zip(rec.iter()) {
+                properties.insert(field.name.clone(), field_data_to_json(value, field));
+            }
+        }
+        let mut feature = Map::new();
+        feature.insert("type".to_string(), Value::String("Feature".to_string()));
+        feature.insert("geometry".to_string(), geometry_to_json(&sf.records[i]));
+        feature.insert("properties".to_string(), Value::Object(properties));
+        serde_json::to_writer(&mut writer, &Value::Object(feature))?;
+    }
+    writer.write_all(b"]}\n")?;
+
+    Ok(())
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub(crate) enum GeometryClass {
+    Point,
+    Line,
+    Polygon,
+}
+
+/// A geometry as read from GeoJSON. For points and lines, each part is a point or line
+/// string; for polygons, each part is a ring, already in the Shapefile's orientation.
+struct ParsedGeometry {
+    class: GeometryClass,
+    parts: Vec<Vec<(Point2D, f64)>>,
+    has_z: bool,
+}
+
+impl ParsedGeometry {
+    fn to_shapefile_geometry(&self, shape_type: ShapeType) -> ShapefileGeometry {
+        if self.parts.is_empty() {
+            return ShapefileGeometry::new(ShapeType::Null);
+        }
+        let mut sfg = ShapefileGeometry::new(shape_type);
+        let has_parts = match shape_type.base_shape_type() {
+            ShapeType::PolyLine | ShapeType::Polygon => true,
+            _ => false,
+        };
+        let has_z = shape_type.dimension() == ShapeTypeDimension::Z;
+        for part in &self.parts {
+            if has_parts {
+                sfg.parts.push(sfg.num_points);
+                sfg.num_parts += 1;
+            }
+            for &(p, z) in part {
+                sfg.add_point(p);
+                if has_z {
+                    sfg.z_array.push(z);
+                    sfg.z_min = sfg.z_min.min(z);
+                    sfg.z_max = sfg.z_max.max(z);
+                }
+            }
+        }
+        sfg
+    }
+}
+
+fn invalid(message: &str) -> Error {
+    Error::new(ErrorKind::InvalidData, message)
+}
+
+fn member_str<'a>(object: &'a Value, name: &str) -> Option<&'a str> {
+    object.get(name).and_then(|v| v.as_str())
+}
+
+/// Returns the geometry and properties of a Feature.
+fn feature_parts<'a>(
+    feature: &'a Value,
+    empty: &'a Map<String, Value>,
+) -> Result<(&'a Value, &'a Map<String, Value>), Error> {
+    if member_str(feature, "type") != Some("Feature") {
+        return Err(invalid(
+            "The members of a FeatureCollection's 'features' array must be Features.",
+        ));
+    }
+    let geometry = feature.get("geometry").unwrap_or(&Value::Null);
+    let properties = match feature.get("properties") {
+        Some(Value::Object(p)) => p,
+        _ => empty,
+    };
+    Ok((geometry, properties))
+}
+
+fn parse_geometry(geometry: &Value) -> Result<ParsedGeometry, Error> {
+    let mut g = ParsedGeometry {
+        class: GeometryClass::Point,
+        parts: vec![],
+        has_z: false,
+    };
+    if !geometry.is_null() {
+        add_geometry(geometry, &mut g, true)?;
+    }
+    Ok(g)
+}
+
+fn add_geometry(geometry: &Value, g: &mut ParsedGeometry, is_first: bool) -> Result<(), Error> {
+    let geometry_type = member_str(geometry, "type")
+        .ok_or_else(|| invalid("A GeoJSON geometry does not have a 'type' member."))?;
+    let coords = geometry.get("coordinates").unwrap_or(&Value::Null);
+    let class = match geometry_type {
+        "Point" | "MultiPoint" => GeometryClass::Point,
+        "LineString" | "MultiLineString" => GeometryClass::Line,
+        "Polygon" | "MultiPolygon" => GeometryClass::Polygon,
+        "GeometryCollection" => {
+            let members = match geometry.get("geometries") {
+                Some(Value::Array(a)) => a,
+                _ => {
+                    return Err(invalid(
+                        "A GeometryCollection must have a 'geometries' array.",
+                    ))
+                }
+            };
+            let mut is_first = is_first;
+            for member in members {
+                add_geometry(member, g, is_first)?;
+                is_first = is_first && g.parts.is_empty();
+            }
+            return Ok(());
+        }
+        _ => {
+            return Err(invalid(&format!(
+                "Unsupported GeoJSON geometry type: {}",
+                geometry_type
+            )))
+        }
+    };
+    if is_first {
+        g.class = class;
+    } else if class != g.class {
+        return Err(invalid(
+            "The members of a GeometryCollection must all be of the same class (points, lines or polygons).",
+        ));
+    }
+    match geometry_type {
+        "Point" => {
+            if !as_array(coords)?.is_empty() {
+                g.parts.push(vec![parse_position(coords, &mut g.has_z)?]);
+            }
+        }
+        "MultiPoint" => {
+            for p in as_array(coords)? {
+                g.parts.push(vec![parse_position(p, &mut g.has_z)?]);
+            }
+        }
+        "LineString" => {
+            let line = parse_positions(coords, &mut g.has_z)?;
+            if !line.is_empty() {
+                g.parts.push(line);
+            }
+        }
+        "MultiLineString" => {
+            for l in as_array(coords)? {
+                let line = parse_positions(l, &mut g.has_z)?;
+                if !line.is_empty() {
+                    g.parts.push(line);
+                }
+            }
+        }
+        "Polygon" => add_polygon(coords, g)?,
+        _ => {
+            for polygon in as_array(coords)? {
+                add_polygon(polygon, g)?;
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Adds the rings of a polygon, closing them if need be, with the exterior ring clockwise and
+/// any holes counter-clockwise.
+fn add_polygon(coords: &Value, g: &mut ParsedGeometry) -> Result<(), Error> {
+    for (i, r) in as_array(coords)?.iter().enumerate() {
+        let mut ring = parse_positions(r, &mut g.has_z)?;
+        if ring.is_empty() {
+            continue;
+        }
+        if ring[0].0 != ring[ring.len() - 1].0 {
+            ring.push(ring[0]);
+        }
+        let area = signed_area(&ring.iter().map(|p| p.0).collect::<Vec<Point2D>>());
+        if (i == 0 && area > 0f64) || (i > 0 && area < 0f64) {
+            ring.reverse();
+        }
+        g.parts.push(ring);
+    }
+    Ok(())
+}
+
+fn as_array(value: &Value) -> Result<&Vec<Value>, Error> {
+    value.as_array().ok_or_else(|| {
+        invalid("The coordinates of a GeoJSON geometry are not properly nested arrays.")
+    })
+}
+
+fn parse_positions(value: &Value, has_z: &mut bool) -> Result<Vec<(Point2D, f64)>, Error> {
+    as_array(value)?
+        .iter()
+        .map(|p| parse_position(p, has_z))
+        .collect()
+}
+
+fn parse_position(value: &Value, has_z: &mut bool) -> Result<(Point2D, f64), Error> {
+    let p = as_array(value)?;
+    let coord = |i: usize| -> Result<f64, Error> {
+        p[i].as_f64()
+            .ok_or_else(|| invalid("A GeoJSON position contains a value that is not a number."))
+    };
+    if p.len() < 2 {
+        return Err(invalid(
+            "A GeoJSON position must have at least two coordinates.",
+        ));
+    }
+    let z = if p.len() > 2 {
+        *has_z = true;
+        coord(2)?
+    } else {
+        0f64
+    };
+    Ok((Point2D::new(coord(0)?, coord(1)?), z))
+}
+
+/// Returns the signed area of a closed ring, which is positive for counter-clockwise rings.
+pub(crate) fn signed_area(ring: &[Point2D]) -> f64 {
+    let mut area = 0f64;
+    for i in 0..ring.len().saturating_sub(1) {
+        area += ring[i].x * ring[i + 1].y - ring[i + 1].x * ring[i].y;
+    }
+    area / 2f64
+}
+
+/// Returns the EPSG code named by the 'crs' member of a GeoJSON object, e.g.
+/// 'urn:ogc:def:crs:EPSG::26917' or 'EPSG:26917'.
+fn parse_crs(crs: &Value) -> Option<u32> {
+    let name = crs.get("properties")?.get("name")?.as_str()?;
+    if name.ends_with("CRS84") {
+        return Some(4326);
+    }
+    name.rsplit(':').next()?.trim().parse::<u32>().ok()
+}
+
+/// The type of the values of a property across all of the features.
+#[derive(Copy, Clone, Debug, PartialEq)]
+enum PropertyType {
+    Unknown,
+    Bool,
+    Int,
+    Real,
+    Text,
+}
+
+#[derive(Debug)]
+struct PropertyKind {
+    property_type: PropertyType,
+    length: usize,
+    integer_digits: usize,
+    decimals: usize,
+}
+
+impl Default for PropertyKind {
+    fn default() -> PropertyKind {
+        PropertyKind {
+            property_type: PropertyType::Unknown,
+            length: 1,
+            integer_digits: 1,
+            decimals: 0,
+        }
+    }
+}
+
+impl PropertyKind {
+    fn update(&mut self, value: &Value) {
+        use PropertyType::*;
+        let value_type = match value {
+            Value::Null => return,
+            Value::Bool(_) => Bool,
+            Value::Number(n) => match n.as_i64() {
+                Some(_) => Int,
+                None => Real,
+            },
+            _ => Text,
+        };
+        self.property_type = match (self.property_type, value_type) {
+            (Unknown, t) => t,
+            (a, b) if a == b => a,
+            (Int, Real) | (Real, Int) => Real,
+            _ => Text,
+        };
+        let s = value_to_string(value);
+        self.length = self.length.max(s.len());
+        if let Value::Number(_) = value {
+            let (integer_digits, decimals) = fixed_point_digits(&s);
+            self.integer_digits = self.integer_digits.max(integer_digits);
+            self.decimals = self.decimals.max(decimals);
+        }
+    }
+
+    fn to_field(&self, name: &str) -> AttributeField {
+        match self.property_type {
+            PropertyType::Bool => AttributeField::new(name, FieldDataType::Bool, 1u8, 0u8),
+            PropertyType::Int => AttributeField::new(
+                name,
+                FieldDataType::Int,
+                self.integer_digits.min(20) as u8,
+                0u8,
+            ),
+            PropertyType::Real => {
+                let decimals = self.decimals.min(15);
+                let length = (self.integer_digits + decimals + 1).min(254);
+                AttributeField::new(name, FieldDataType::Real, length as u8, decimals as u8)
+            }
+            _ => AttributeField::new(name, FieldDataType::Text, self.length.min(254) as u8, 0u8),
+        }
+    }
+
+    fn to_field_data(&self, value: &Value) -> FieldData {
+        if value.is_null() {
+            return FieldData::Null;
+        }
+        match self.property_type {
+            PropertyType::Bool => FieldData::Bool(value.as_bool().unwrap_or(false)),
+            PropertyType::Int => match value.as_i64() {
+                Some(v) => FieldData::from_i64(v),
+                None => FieldData::Null,
+            },
+            PropertyType::Real => FieldData::Real(value.as_f64().unwrap_or(f64::NAN)),
+            _ => FieldData::Text(value_to_string(value)),
+        }
+    }
+}
+
+/// Returns the number of characters before the decimal point, including any sign, and the
+/// number of decimal places of a JSON number written in fixed-point notation, e.g. (3, 0)
+/// for 1.5e2 and (1, 6) for 1.5e-5.
+fn fixed_point_digits(number: &str) -> (usize, usize) {
+    let mut split = number.splitn(2, |c| c == 'e' || c == 'E');
+    let mantissa = split.next().unwrap_or("");
+    let exponent = split.next().and_then(|e| e.parse::<i64>().ok()).unwrap_or(0);
+    let sign = if mantissa.starts_with('-') { 1 } else { 0 };
+    let mut split = mantissa.trim_start_matches('-').splitn(2, '.');
+    let integer = split.next().unwrap_or("").trim_start_matches('0').len() as i64;
+    let fraction = split.next().unwrap_or("").len() as i64;
+    (
+        ((integer + exponent).max(1) + sign) as usize,
+        (fraction - exponent).max(0) as usize,
+    )
+}
+
+/// Returns the text of a value; strings are unquoted and other values are in JSON notation.
+fn value_to_string(value: &Value) -> String {
+    match value {
+        Value::String(s) => s.clone(),
+        _ => value.to_string(),
+    }
+}
+
+fn field_data_to_json(value: &FieldData, field: &AttributeField) -> Value {
+    match value {
+        FieldData::Int(v) => Value::from(*v),
+        FieldData::BigInt(v) => Value::from(*v),
+        FieldData::Real(v) => {
+            if field.decimal_count == 0 && v.fract() == 0f64 && v.abs() < 9.007_199_254_740_992e15 {
+                Value::from(*v as i64)
+            } else {
+                Number::from_f64(*v).map_or(Value::Null, Value::Number)
+            }
+        }
+        FieldData::Text(s) => {
+            let t = s.trim_start();
+            if t.starts_with('{') || t.starts_with('[') {
+                match serde_json::from_str::<Value>(s) {
+                    Ok(v) if v.is_object() || v.is_array() => return v,
+                    _ => {}
+                }
+            }
+            Value::String(s.clone())
+        }
+        FieldData::Date(d) => Value::String(format!("{:04}-{:02}-{:02}", d.year, d.month, d.day)),
+        FieldData::Bool(v) => Value::Bool(*v),
+        FieldData::Null => Value::Null,
+    }
+}
+
+fn position(sfg: &ShapefileGeometry, i: usize) -> Value {
+    let p = sfg.points[i];
+    let mut position = vec![Value::from(p.x), Value::from(p.y)];
+    if sfg.has_z_data() {
+        position.push(Value::from(sfg.z_array[i]));
+    }
+    Value::Array(position)
+}
+
+fn positions(sfg: &ShapefileGeometry, first: usize, last: usize, reverse: bool) -> Value {
+    let mut indices = (first..last).collect::<Vec<usize>>();
+    if reverse {
+        indices.reverse();
+    }
+    Value::Array(indices.into_iter().map(|i| position(sfg, i)).collect())
+}
+
+fn geometry_object(geometry_type: &str, coordinates: Value) -> Value {
+    let mut g = Map::new();
+    g.insert("type".to_string(), Value::String(geometry_type.to_string()));
+    g.insert("coordinates".to_string(), coordinates);
+    Value::Object(g)
+}
+
+/// Returns the ranges of the indices of the points of each non-empty part of a geometry.
+pub(crate) fn part_ranges(sfg: &ShapefileGeometry) -> Vec<(usize, usize)> {
+    let mut part_starts = sfg
+        .parts
+        .iter()
+        .map(|p| *p as usize)
+        .collect::<Vec<usize>>();
+    if part_starts.is_empty() {
+        part_starts.push(0);
+    }
+    part_starts.push(sfg.points.len());
+    part_starts
+        .windows(2)
+        .map(|w| (w[0], w[1]))
+        .filter(|(a, b)| b > a)
+        .collect()
+}
+
+/// Groups the rings of a polygon geometry into polygons, each an exterior ring followed by
+/// its holes. Shapefile exterior rings are clockwise (negative area) and holes are
+/// counter-clockwise. Each hole belongs to the exterior ring that contains it.
+pub(crate) fn group_polygon_rings(
+    sfg: &ShapefileGeometry,
+    part_ranges: &[(usize, usize)],
+) -> Vec<Vec<(usize, usize)>> {
+    let mut polygons: Vec<Vec<(usize, usize)>> = vec![];
+    let mut holes = vec![];
+    for &(a, b) in part_ranges {
+        if signed_area(&sfg.points[a..b]) <= 0f64 {
+            polygons.push(vec![(a, b)]);
+        } else {
+            holes.push((a, b));
+        }
+    }
+    for (a, b) in holes {
+        match polygons.iter().position(|rings| {
+            point_in_poly(&sfg.points[a], &sfg.points[rings[0].0..rings[0].1])
+        }) {
+            Some(i) => polygons[i].push((a, b)),
+            // An unenclosed counter-clockwise ring is taken to be an exterior ring.
+            None => polygons.push(vec![(a, b)]),
+        }
+    }
+    polygons
+}
+
+fn geometry_to_json(sfg: &ShapefileGeometry) -> Value {
+    if sfg.shape_type == ShapeType::Null || sfg.points.is_empty() {
+        return Value::Null;
+    }
+    let num_points = sfg.points.len();
+    let part_ranges = part_ranges(sfg);
+
+    match sfg.shape_type.base_shape_type() {
+        ShapeType::Point => geometry_object("Point", position(sfg, 0)),
+        ShapeType::MultiPoint => {
+            geometry_object("MultiPoint", positions(sfg, 0, num_points, false))
+        }
+        ShapeType::PolyLine => {
+            if part_ranges.len() == 1 {
+                geometry_object("LineString", positions(sfg, 0, num_points, false))
+            } else {
+                geometry_object(
+                    "MultiLineString",
+                    Value::Array(
+                        part_ranges
+                            .iter()
+                            .map(|&(a, b)| positions(sfg, a, b, false))
+                            .collect(),
+                    ),
+                )
+            }
+        }
+        _ => {
+            let polygons = group_polygon_rings(sfg, &part_ranges)
+                .iter()
+                .map(|rings| {
+                    Value::Array(
+                        rings
+                            .iter()
+                            .enumerate()
+                            .map(|(i, &(a, b))| {
+                                let is_clockwise = signed_area(&sfg.points[a..b]) <= 0f64;
+                                // exteriors are counter-clockwise and holes clockwise
+                                positions(sfg, a, b, (i == 0) == is_clockwise)
+                            })
+                            .collect(),
+                    )
+                })
+                .collect::<Vec<Value>>();
+            if polygons.len() == 1 {
+                geometry_object("Polygon", polygons[0].clone())
+            } else {
+                geometry_object("MultiPolygon", Value::Array(polygons))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{fixed_point_digits, parse_crs};
+    use crate::test_utils::*;
+    use crate::*;
+    use std::fs;
+
+    #[test]
+    fn geojson_round_trip() {
+        for &shape_type in &[
+            ShapeType::Point,
+            ShapeType::MultiPoint,
+            ShapeType::PolyLine,
+            ShapeType::Polygon,
+        ] {
+            let file_name = temp_file(&format!("{}.geojson", shape_type.to_int()));
+            let mut output = sample_file(&file_name, shape_type);
+            output.write().unwrap();
+            let input = Shapefile::read(&file_name).unwrap();
+            assert_same_features(&output, &input);
+            assert!(input.projection.contains("WGS"));
+            fs::remove_file(&file_name).unwrap();
+        }
+    }
+
+    #[test]
+    fn nested_properties_round_trip() {
+        let file_name = temp_file("nested.geojson");
+        let copy_name = temp_file("nested_copy.geojson");
+        fs::write(
+            &file_name,
+            r#"{"type":"FeatureCollection","features":[
+                {"type":"Feature","geometry":{"type":"Point","coordinates":[1,2]},
+                 "properties":{"name":"a","tags":["x","y"],"value":1.5e20}},
+                {"type":"Feature","geometry":{"type":"Point","coordinates":[3,4]},
+                 "properties":{"name":"b","tags":{"k":1},"value":-2.5e-5}}]}"#,
+        )
+        .unwrap();
+        let mut input = Shapefile::read(&file_name).unwrap();
+        assert_eq!(
+            input.attributes.get_value(0, "tags"),
+            FieldData::Text(r#"["x","y"]"#.to_string())
+        );
+        assert_eq!(
+            input.attributes.get_value(1, "tags"),
+            FieldData::Text(r#"{"k":1}"#.to_string())
+        );
+        assert_eq!(input.attributes.get_value(1, "name"), FieldData::Text("b".to_string()));
+        let value = &input.attributes.get_fields()[input.attributes.get_field_num("value").unwrap()];
+        assert_eq!(value.decimal_count, 6);
+        assert!(value.field_length as usize >= 21 + 1 + 6);
+        assert_eq!(input.attributes.get_value(0, "value"), FieldData::Real(1.5e20));
+
+        // The text of nested values is written back as nested JSON.
+        input.file_name = copy_name.clone();
+        input.file_mode = "w".to_string();
+        input.write().unwrap();
+        let copy: serde_json::Value =
+            serde_json::from_str(&fs::read_to_string(&copy_name).unwrap()).unwrap();
+        let features = copy["features"].as_array().unwrap();
+        assert_eq!(features[0]["properties"]["tags"], serde_json::json!(["x", "y"]));
+        assert_eq!(features[1]["properties"]["tags"], serde_json::json!({"k": 1}));
+        assert_eq!(features[1]["properties"]["name"], serde_json::json!("b"));
+        fs::remove_file(&file_name).unwrap();
+        fs::remove_file(&copy_name).unwrap();
+    }
+
+    #[test]
+    fn fixed_point_widths() {
+        assert_eq!(fixed_point_digits("12.25"), (2, 2));
+        assert_eq!(fixed_point_digits("-0.5"), (2, 1));
+        assert_eq!(fixed_point_digits("1.5e20"), (21, 0));
+        assert_eq!(fixed_point_digits("1.5E-5"), (1, 6));
+    }
+
+    #[test]
+    fn crs_codes_beyond_16_bits() {
+        let crs = serde_json::from_str(
+            r#"{"type":"name","properties":{"name":"urn:ogc:def:crs:EPSG::102100"}}"#,
+        )
+        .unwrap();
+        assert_eq!(parse_crs(&crs), Some(102100));
+    }
+}
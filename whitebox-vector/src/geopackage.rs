@@ -0,0 +1,1230 @@
+/*
+This code is part of the WhiteboxTools geospatial analysis library.
+License: MIT
+
+Notes: Reading and writing the feature tables of GeoPackage (http://www.geopackage.org/spec/)
+files, which are SQLite databases. As with GeoJSON, a feature table is held in memory as a
+Shapefile. A GeoPackage may contain several feature tables, or layers, one of which is
+selected by appending its name to the file name, e.g. `roads.gpkg:highways`.
+
+Each geometry is stored as a GeoPackage binary blob, i.e. a short header, giving the
+spatial reference system and, optionally, the envelope of the geometry, followed by the
+geometry in the well-known binary (WKB) format.
+*/
+
+use crate::geojson::{group_polygon_rings, part_ranges, signed_area, GeometryClass};
+use crate::shapefile::attributes::*;
+use crate::shapefile::geometry::*;
+use crate::shapefile::Shapefile;
+use byteorder::{LittleEndian, WriteBytesExt};
+use rusqlite::types::Value;
+use rusqlite::{params, params_from_iter, Connection, OpenFlags, OptionalExtension, Transaction};
+use std::f64;
+use std::io::{Error, ErrorKind};
+use std::path::Path;
+use whitebox_common::spatial_ref_system::esri_wkt_from_epsg;
+use whitebox_common::structures::Point2D;
+
+/// Returns true if a file name is that of a GeoPackage (.gpkg), including file names of
+/// the form `file.gpkg:layer`.
+pub fn is_geopackage_file(file_name: &str) -> bool {
+    let (file_name, _) = split_geopackage_file_name(file_name);
+    file_name.to_ascii_lowercase().ends_with(".gpkg")
+}
+
+/// Splits a GeoPackage file name of the form `file.gpkg:layer` into the name of the file
+/// and that of the layer. Other file names are returned unchanged.
+pub fn split_geopackage_file_name(file_name: &str) -> (&str, Option<&str>) {
+    match file_name.to_ascii_lowercase().rfind(".gpkg:") {
+        Some(i) if i + 6 < file_name.len() => (&file_name[..i + 5], Some(&file_name[i + 6..])),
+        Some(i) => (&file_name[..i + 5], None),
+        None => (file_name, None),
+    }
+}
+
+/// Reads a feature table of the GeoPackage named by `sf.file_name` into the Shapefile. The
+/// layer is named in the file name, e.g. `file.gpkg:roads`; otherwise the first feature
+/// table of the file is read.
+///
+/// All of the features must have geometries of the same class, i.e. points, lines or
+/// polygons. Features with null or empty geometries become Null records. The Shapefile is
+/// of a Z ShapeType if the geometries have z values, and of an M ShapeType if they have
+/// measures but not z values. Polygon rings are re-ordered into the clockwise exteriors
+/// and counter-clockwise holes of the Shapefile format.
+///
+/// Each column of the table, other than the primary key and the geometry, becomes a field
+/// of the attribute table. Integer values that do not fit into 32 bits are read as big
+/// integers. BLOB columns cannot be held in an attribute table and are skipped.
+pub fn read_geopackage(sf: &mut Shapefile) -> Result<(), Error> {
+    let (gpkg_file, layer) = split_geopackage_file_name(&sf.file_name);
+    if !Path::new(gpkg_file).is_file() {
+        return Err(Error::new(
+            ErrorKind::NotFound,
+            format!("The file {} does not exist.", gpkg_file),
+        ));
+    }
+    let conn = Connection::open_with_flags(gpkg_file, OpenFlags::SQLITE_OPEN_READ_ONLY)
+        .map_err(sql_error)?;
+
+    let layers = feature_tables(&conn)?;
+    if layers.is_empty() {
+        return Err(Error::new(
+            ErrorKind::InvalidData,
+            format!("The GeoPackage {} does not contain any feature tables.", gpkg_file),
+        ));
+    }
+    let table = match layer {
+        Some(name) => {
+            if !layers.iter().any(|l| l == name) {
+                return Err(Error::new(
+                    ErrorKind::NotFound,
+                    format!(
+                        "The GeoPackage does not contain a feature table named '{}'. Its feature tables are: {}",
+                        name,
+                        layers.join(", ")
+                    ),
+                ));
+            }
+            name.to_string()
+        }
+        None => {
+            if layers.len() > 1 {
+                println!(
+                    "Warning: The GeoPackage contains {} feature tables and only the first, '{}', is read. Use a file name of the form file.gpkg:layer to select another.",
+                    layers.len(),
+                    layers[0]
+                );
+            }
+            layers[0].clone()
+        }
+    };
+
+    let (geom_column, geometry_type_name, srs_id, z, m): (String, String, i64, i64, i64) = conn
+        .query_row(
+            "SELECT column_name, geometry_type_name, srs_id, z, m FROM gpkg_geometry_columns WHERE table_name = ?1",
+            [&table],
+            |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?, row.get(4)?)),
+        )
+        .map_err(sql_error)?;
+
+    // The attribute columns, i.e. those other than the primary key and the geometry.
+    let mut primary_key: Option<String> = None;
+    let mut columns: Vec<(String, String)> = vec![];
+    {
+        let mut stmt = conn
+            .prepare(&format!("PRAGMA table_info({})", quote(&table)))
+            .map_err(sql_error)?;
+        let mut rows = stmt.query([]).map_err(sql_error)?;
+        while let Some(row) = rows.next().map_err(sql_error)? {
+            let name: String = row.get(1).map_err(sql_error)?;
+            let declared_type: String = row.get(2).map_err(sql_error)?;
+            let pk: i64 = row.get(5).map_err(sql_error)?;
+            if pk > 0 && declared_type.to_uppercase().starts_with("INT") {
+                primary_key = Some(name);
+            } else if !name.eq_ignore_ascii_case(&geom_column) {
+                if declared_type.to_uppercase() == "BLOB" {
+                    println!(
+                        "Warning: The BLOB column '{}' of the GeoPackage table cannot be held in an attribute table and is skipped.",
+                        name
+                    );
+                } else {
+                    columns.push((name, declared_type));
+                }
+            }
+        }
+    }
+
+    // Read the features.
+    let mut sql = format!("SELECT {}", quote(&geom_column));
+    for (name, _) in &columns {
+        sql.push_str(&format!(", {}", quote(name)));
+    }
+    sql.push_str(&format!(" FROM {}", quote(&table)));
+    if let Some(pk) = &primary_key {
+        sql.push_str(&format!(" ORDER BY {}", quote(pk)));
+    }
+    let mut geometries: Vec<FeatureGeometry> = vec![];
+    let mut values: Vec<Vec<Value>> = vec![];
+    {
+        let mut stmt = conn.prepare(&sql).map_err(sql_error)?;
+        let mut rows = stmt.query([]).map_err(sql_error)?;
+        while let Some(row) = rows.next().map_err(sql_error)? {
+            let blob: Option<Vec<u8>> = row.get(0).map_err(sql_error)?;
+            geometries.push(match blob {
+                Some(blob) => parse_gpkg_geometry(&blob)?,
+                None => FeatureGeometry::default(),
+            });
+            let mut rec = Vec::with_capacity(columns.len());
+            for i in 0..columns.len() {
+                rec.push(row.get::<_, Value>(i + 1).map_err(sql_error)?);
+            }
+            values.push(rec);
+        }
+    }
+
+    // Geometries
+    let declared_type = geometry_type_name.to_uppercase();
+    let class = match declared_type.as_str() {
+        "POINT" | "MULTIPOINT" => Some(GeometryClass::Point),
+        "LINESTRING" | "MULTILINESTRING" => Some(GeometryClass::Line),
+        "POLYGON" | "MULTIPOLYGON" => Some(GeometryClass::Polygon),
+        _ => None,
+    };
+    let (shape_type, has_m) = layer_shape_type(
+        &geometries,
+        class,
+        declared_type == "MULTIPOINT",
+        z == 1,
+        m == 1,
+    )?;
+    sf.header.shape_type = shape_type;
+    sf.records = geometries
+        .iter()
+        .map(|g| g.to_shapefile_geometry(shape_type, has_m))
+        .collect();
+    sf.num_records = sf.records.len();
+
+    // Attributes
+    let fields = columns
+        .iter()
+        .enumerate()
+        .map(|(i, (name, declared_type))| {
+            ColumnKind::new(declared_type, values.iter().map(|rec| &rec[i])).to_field(name)
+        })
+        .collect::<Vec<(AttributeField, ColumnKind)>>();
+    sf.attributes.add_fields(&fields.iter().map(|f| f.0.clone()).collect());
+    for rec in values {
+        sf.attributes.add_record(
+            rec.iter()
+                .zip(fields.iter())
+                .map(|(value, (_, kind))| kind.to_field_data(value))
+                .collect(),
+            false,
+        );
+    }
+
+    // Coordinate reference system
+    let srs: Option<(String, i64, String)> = conn
+        .query_row(
+            "SELECT organization, organization_coordsys_id, definition FROM gpkg_spatial_ref_sys WHERE srs_id = ?1",
+            [srs_id],
+            |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
+        )
+        .optional()
+        .map_err(sql_error)?;
+    if let Some((organization, code, definition)) = srs {
+        let definition = definition.trim();
+        if !definition.is_empty() && definition != "undefined" {
+            sf.projection = definition.to_string();
+        } else if organization.eq_ignore_ascii_case("epsg") && code > 0 && code <= u16::MAX as i64 {
+            let wkt = esri_wkt_from_epsg(code as u16);
+            if wkt != "Unknown EPSG Code" {
+                sf.projection = wkt;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Writes the Shapefile to a feature table of the GeoPackage named by `sf.file_name`. The
+/// layer is named in the file name, e.g. `file.gpkg:roads`, or otherwise after the file.
+/// The GeoPackage is created if it does not exist; otherwise the layer is added to it,
+/// replacing any existing feature table of the same name, and its other tables are kept.
+///
+/// Lines and polygons are written as MultiLineStrings and MultiPolygons, with polygons'
+/// exterior rings counter-clockwise and their holes clockwise. The z values of Z
+/// ShapeTypes, and the measures of M ShapeTypes and of Z ShapeTypes that have them, are
+/// written. The coordinate reference system is recorded using its EPSG code, if it has a
+/// known one, and otherwise using its WKT definition. Null records have null geometries.
+pub fn write_geopackage(sf: &Shapefile) -> Result<(), Error> {
+    let (gpkg_file, layer) = split_geopackage_file_name(&sf.file_name);
+    let table = match layer {
+        Some(layer) => layer.to_string(),
+        None => Path::new(gpkg_file)
+            .file_stem()
+            .map_or("layer".to_string(), |s| s.to_string_lossy().to_string()),
+    };
+
+    let mut conn = Connection::open(gpkg_file).map_err(sql_error)?;
+    let num_tables: i64 = conn
+        .query_row("SELECT COUNT(*) FROM sqlite_master WHERE type = 'table'", [], |row| row.get(0))
+        .map_err(sql_error)?;
+    let is_new = num_tables == 0;
+    if !is_new && !table_exists(&conn, "gpkg_contents")? {
+        return Err(Error::new(
+            ErrorKind::InvalidInput,
+            format!("The file {} is an SQLite database but not a GeoPackage.", gpkg_file),
+        ));
+    }
+    if is_new {
+        conn.execute_batch("PRAGMA application_id = 1196444487; PRAGMA user_version = 10300;")
+            .map_err(sql_error)?;
+    }
+
+    let tx = conn.transaction().map_err(sql_error)?;
+    if is_new {
+        create_core_tables(&tx)?;
+    } else {
+        drop_feature_table(&tx, &table)?;
+    }
+    let srs_id = get_srs_id(&tx, sf)?;
+
+    // The primary key and geometry columns must not share a name with an attribute.
+    let fields = sf.attributes.get_fields();
+    let unique_name = |base: &str| {
+        let mut name = base.to_string();
+        while fields.iter().any(|f| f.name.eq_ignore_ascii_case(&name)) {
+            name = format!("{}_", name);
+        }
+        name
+    };
+    let fid_column = unique_name("fid");
+    let geom_column = unique_name("geom");
+
+    let shape_type = sf.header.shape_type;
+    let geometry_type_name = match shape_type.base_shape_type() {
+        ShapeType::Point => "POINT",
+        ShapeType::MultiPoint => "MULTIPOINT",
+        ShapeType::PolyLine => "MULTILINESTRING",
+        ShapeType::Polygon => "MULTIPOLYGON",
+        _ => "GEOMETRY",
+    };
+    let has_z = shape_type.dimension() == ShapeTypeDimension::Z;
+    let has_m = match shape_type.dimension() {
+        ShapeTypeDimension::Measure => true,
+        ShapeTypeDimension::Z => {
+            let mut records = sf.records.iter().filter(|r| r.shape_type != ShapeType::Null);
+            records.clone().next().is_some() && records.all(|r| r.has_m_data())
+        }
+        ShapeTypeDimension::XY => false,
+    };
+
+    // Create the feature table.
+    let mut sql = format!(
+        "CREATE TABLE {} ({} INTEGER PRIMARY KEY AUTOINCREMENT NOT NULL, {} {}",
+        quote(&table),
+        quote(&fid_column),
+        quote(&geom_column),
+        geometry_type_name
+    );
+    for field in fields {
+        sql.push_str(&format!(", {} {}", quote(&field.name), column_type(field)));
+    }
+    sql.push(')');
+    tx.execute(&sql, []).map_err(sql_error)?;
+
+    // Insert the features.
+    {
+        let mut sql = format!("INSERT INTO {} ({}", quote(&table), quote(&geom_column));
+        for field in fields {
+            sql.push_str(&format!(", {}", quote(&field.name)));
+        }
+        sql.push_str(") VALUES (?1");
+        for i in 0..fields.len() {
+            sql.push_str(&format!(", ?{}", i + 2));
+        }
+        sql.push(')');
+        let mut stmt = tx.prepare(&sql).map_err(sql_error)?;
+        let num_atts = sf.attributes.header.num_records as usize;
+        for i in 0..sf.records.len() {
+            let mut rec: Vec<Value> = Vec::with_capacity(fields.len() + 1);
+            rec.push(match gpkg_geometry_blob(&sf.records[i], srs_id as i32, has_z, has_m) {
+                Some(blob) => Value::Blob(blob),
+                None => Value::Null,
+            });
+            if i < num_atts {
+                rec.extend(sf.attributes.get_record(i).iter().map(field_data_to_value));
+            } else {
+                rec.extend(fields.iter().map(|_| Value::Null));
+            }
+            stmt.execute(params_from_iter(rec.iter())).map_err(sql_error)?;
+        }
+    }
+
+    // Register the table.
+    let mut bounds = [f64::INFINITY, f64::INFINITY, f64::NEG_INFINITY, f64::NEG_INFINITY];
+    for r in sf.records.iter().filter(|r| !r.points.is_empty()) {
+        bounds[0] = bounds[0].min(r.x_min);
+        bounds[1] = bounds[1].min(r.y_min);
+        bounds[2] = bounds[2].max(r.x_max);
+        bounds[3] = bounds[3].max(r.y_max);
+    }
+    let bounds = bounds
+        .iter()
+        .map(|&b| if b.is_finite() { Value::Real(b) } else { Value::Null })
+        .collect::<Vec<Value>>();
+    tx.execute(
+        "INSERT INTO gpkg_contents (table_name, data_type, identifier, description, min_x, min_y, max_x, max_y, srs_id) \
+         VALUES (?1, 'features', ?1, '', ?2, ?3, ?4, ?5, ?6)",
+        params![&table, &bounds[0], &bounds[1], &bounds[2], &bounds[3], srs_id],
+    )
+    .map_err(sql_error)?;
+    tx.execute(
+        "INSERT INTO gpkg_geometry_columns (table_name, column_name, geometry_type_name, srs_id, z, m) \
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+        params![&table, &geom_column, geometry_type_name, srs_id, has_z as i64, has_m as i64],
+    )
+    .map_err(sql_error)?;
+
+    tx.commit().map_err(sql_error)
+}
+
+fn sql_error(e: rusqlite::Error) -> Error {
+    Error::new(ErrorKind::Other, format!("GeoPackage error: {}", e))
+}
+
+fn invalid(message: &str) -> Error {
+    Error::new(ErrorKind::InvalidData, message)
+}
+
+/// Quotes an SQL identifier, e.g. the name of a table or column.
+fn quote(name: &str) -> String {
+    format!("\"{}\"", name.replace('"', "\"\""))
+}
+
+fn table_exists(conn: &Connection, name: &str) -> Result<bool, Error> {
+    let count: i64 = conn
+        .query_row(
+            "SELECT COUNT(*) FROM sqlite_master WHERE type = 'table' AND name = ?1",
+            [name],
+            |row| row.get(0),
+        )
+        .map_err(sql_error)?;
+    Ok(count > 0)
+}
+
+/// Returns the names of the feature tables of a GeoPackage, in the order they were added.
+fn feature_tables(conn: &Connection) -> Result<Vec<String>, Error> {
+    let mut stmt = conn
+        .prepare("SELECT table_name FROM gpkg_contents WHERE data_type = 'features' ORDER BY rowid")
+        .map_err(sql_error)?;
+    let names = stmt
+        .query_map([], |row| row.get(0))
+        .map_err(sql_error)?
+        .collect::<Result<Vec<String>, rusqlite::Error>>()
+        .map_err(sql_error)?;
+    Ok(names)
+}
+
+/// Creates the tables that every GeoPackage must contain, along with the spatial reference
+/// systems that the specification requires.
+fn create_core_tables(tx: &Transaction) -> Result<(), Error> {
+    tx.execute_batch(
+        "CREATE TABLE gpkg_spatial_ref_sys (
+            srs_name TEXT NOT NULL,
+            srs_id INTEGER NOT NULL PRIMARY KEY,
+            organization TEXT NOT NULL,
+            organization_coordsys_id INTEGER NOT NULL,
+            definition TEXT NOT NULL,
+            description TEXT
+        );
+        CREATE TABLE gpkg_contents (
+            table_name TEXT NOT NULL PRIMARY KEY,
+            data_type TEXT NOT NULL,
+            identifier TEXT UNIQUE,
+            description TEXT DEFAULT '',
+            last_change DATETIME NOT NULL DEFAULT (strftime('%Y-%m-%dT%H:%M:%fZ','now')),
+            min_x DOUBLE,
+            min_y DOUBLE,
+            max_x DOUBLE,
+            max_y DOUBLE,
+            srs_id INTEGER,
+            CONSTRAINT fk_gc_r_srs_id FOREIGN KEY (srs_id) REFERENCES gpkg_spatial_ref_sys(srs_id)
+        );
+        CREATE TABLE gpkg_geometry_columns (
+            table_name TEXT NOT NULL,
+            column_name TEXT NOT NULL,
+            geometry_type_name TEXT NOT NULL,
+            srs_id INTEGER NOT NULL,
+            z TINYINT NOT NULL,
+            m TINYINT NOT NULL,
+            CONSTRAINT pk_geom_cols PRIMARY KEY (table_name, column_name),
+            CONSTRAINT uk_gc_table_name UNIQUE (table_name),
+            CONSTRAINT fk_gc_tn FOREIGN KEY (table_name) REFERENCES gpkg_contents(table_name),
+            CONSTRAINT fk_gc_srs FOREIGN KEY (srs_id) REFERENCES gpkg_spatial_ref_sys (srs_id)
+        );
+        CREATE TABLE gpkg_tile_matrix_set (
+            table_name TEXT NOT NULL PRIMARY KEY,
+            srs_id INTEGER NOT NULL,
+            min_x DOUBLE NOT NULL,
+            min_y DOUBLE NOT NULL,
+            max_x DOUBLE NOT NULL,
+            max_y DOUBLE NOT NULL,
+            CONSTRAINT fk_gtms_table_name FOREIGN KEY (table_name) REFERENCES gpkg_contents(table_name),
+            CONSTRAINT fk_gtms_srs FOREIGN KEY (srs_id) REFERENCES gpkg_spatial_ref_sys (srs_id)
+        );
+        CREATE TABLE gpkg_tile_matrix (
+            table_name TEXT NOT NULL,
+            zoom_level INTEGER NOT NULL,
+            matrix_width INTEGER NOT NULL,
+            matrix_height INTEGER NOT NULL,
+            tile_width INTEGER NOT NULL,
+            tile_height INTEGER NOT NULL,
+            pixel_x_size DOUBLE NOT NULL,
+            pixel_y_size DOUBLE NOT NULL,
+            CONSTRAINT pk_ttm PRIMARY KEY (table_name, zoom_level),
+            CONSTRAINT fk_tmm_table_name FOREIGN KEY (table_name) REFERENCES gpkg_contents(table_name)
+        );",
+    )
+    .map_err(sql_error)?;
+    let srs_rows: [(&str, i64, &str, String, &str); 3] = [
+        (
+            "Undefined cartesian SRS",
+            -1,
+            "NONE",
+            "undefined".to_string(),
+            "undefined cartesian coordinate reference system",
+        ),
+        (
+            "Undefined geographic SRS",
+            0,
+            "NONE",
+            "undefined".to_string(),
+            "undefined geographic coordinate reference system",
+        ),
+        (
+            "WGS 84 geodetic",
+            4326,
+            "EPSG",
+            esri_wkt_from_epsg(4326),
+            "longitude/latitude coordinates in decimal degrees on the WGS 84 spheroid",
+        ),
+    ];
+    for (name, id, organization, definition, description) in srs_rows.iter() {
+        tx.execute(
+            "INSERT INTO gpkg_spatial_ref_sys (srs_name, srs_id, organization, organization_coordsys_id, definition, description) \
+             VALUES (?1, ?2, ?3, ?2, ?4, ?5)",
+            params![name, id, organization, definition, description],
+        )
+        .map_err(sql_error)?;
+    }
+    Ok(())
+}
+
+/// Removes an existing feature table, and its registration, from a GeoPackage.
+fn drop_feature_table(tx: &Transaction, table: &str) -> Result<(), Error> {
+    let data_type: Option<String> = tx
+        .query_row(
+            "SELECT data_type FROM gpkg_contents WHERE table_name = ?1",
+            [table],
+            |row| row.get(0),
+        )
+        .optional()
+        .map_err(sql_error)?;
+    match data_type.as_deref() {
+        Some("features") => {}
+        Some(_) => {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                format!(
+                    "The GeoPackage already contains a table named '{}' that is not a feature table.",
+                    table
+                ),
+            ))
+        }
+        None => {
+            if table_exists(tx, table)? {
+                return Err(Error::new(
+                    ErrorKind::InvalidInput,
+                    format!("The GeoPackage already contains a table named '{}'.", table),
+                ));
+            }
+            return Ok(());
+        }
+    }
+    let geom_column: Option<String> = tx
+        .query_row(
+            "SELECT column_name FROM gpkg_geometry_columns WHERE table_name = ?1",
+            [table],
+            |row| row.get(0),
+        )
+        .optional()
+        .map_err(sql_error)?;
+    if let Some(geom_column) = geom_column {
+        // The spatial index of the table, if it has one.
+        tx.execute(
+            &format!("DROP TABLE IF EXISTS {}", quote(&format!("rtree_{}_{}", table, geom_column))),
+            [],
+        )
+        .map_err(sql_error)?;
+    }
+    if table_exists(tx, "gpkg_extensions")? {
+        tx.execute("DELETE FROM gpkg_extensions WHERE table_name = ?1", [table])
+            .map_err(sql_error)?;
+    }
+    tx.execute("DELETE FROM gpkg_geometry_columns WHERE table_name = ?1", [table])
+        .map_err(sql_error)?;
+    tx.execute("DELETE FROM gpkg_contents WHERE table_name = ?1", [table])
+        .map_err(sql_error)?;
+    tx.execute(&format!("DROP TABLE IF EXISTS {}", quote(table)), [])
+        .map_err(sql_error)?;
+    Ok(())
+}
+
+/// Returns the id of the spatial reference system of the Shapefile within the GeoPackage,
+/// adding the system to the gpkg_spatial_ref_sys table if need be. Systems without a
+/// coordinate reference system are undefined cartesian systems (-1).
+fn get_srs_id(tx: &Transaction, sf: &Shapefile) -> Result<i64, Error> {
+    if sf.projection.trim().is_empty() {
+        return Ok(-1);
+    }
+    let crs = sf.get_coordinate_reference_system();
+    let name = crs.as_ref().map_or("Unknown".to_string(), |crs| crs.name.clone());
+    if let Some(code) = crs.as_ref().and_then(|crs| crs.identify_epsg()) {
+        let existing: Option<i64> = tx
+            .query_row(
+                "SELECT srs_id FROM gpkg_spatial_ref_sys WHERE organization = 'EPSG' COLLATE NOCASE AND organization_coordsys_id = ?1",
+                [code],
+                |row| row.get(0),
+            )
+            .optional()
+            .map_err(sql_error)?;
+        if let Some(srs_id) = existing {
+            return Ok(srs_id);
+        }
+        let srs_id = if srs_id_exists(tx, code as i64)? {
+            new_srs_id(tx)?
+        } else {
+            code as i64
+        };
+        tx.execute(
+            "INSERT INTO gpkg_spatial_ref_sys (srs_name, srs_id, organization, organization_coordsys_id, definition) \
+             VALUES (?1, ?2, 'EPSG', ?3, ?4)",
+            params![name, srs_id, code, sf.projection.trim()],
+        )
+        .map_err(sql_error)?;
+        return Ok(srs_id);
+    }
+
+    // A system without a known EPSG code is identified by its definition.
+    let existing: Option<i64> = tx
+        .query_row(
+            "SELECT srs_id FROM gpkg_spatial_ref_sys WHERE definition = ?1",
+            [sf.projection.trim()],
+            |row| row.get(0),
+        )
+        .optional()
+        .map_err(sql_error)?;
+    if let Some(srs_id) = existing {
+        return Ok(srs_id);
+    }
+    println!(
+        "Warning: The coordinate reference system of {} does not have a known EPSG code and is recorded by its definition only.",
+        sf.file_name
+    );
+    let srs_id = new_srs_id(tx)?;
+    tx.execute(
+        "INSERT INTO gpkg_spatial_ref_sys (srs_name, srs_id, organization, organization_coordsys_id, definition) \
+         VALUES (?1, ?2, 'NONE', ?2, ?3)",
+        params![name, srs_id, sf.projection.trim()],
+    )
+    .map_err(sql_error)?;
+    Ok(srs_id)
+}
+
+fn srs_id_exists(tx: &Transaction, srs_id: i64) -> Result<bool, Error> {
+    let count: i64 = tx
+        .query_row(
+            "SELECT COUNT(*) FROM gpkg_spatial_ref_sys WHERE srs_id = ?1",
+            [srs_id],
+            |row| row.get(0),
+        )
+        .map_err(sql_error)?;
+    Ok(count > 0)
+}
+
+/// Returns an unused id for a user-defined spatial reference system.
+fn new_srs_id(tx: &Transaction) -> Result<i64, Error> {
+    let max_id: Option<i64> = tx
+        .query_row("SELECT MAX(srs_id) FROM gpkg_spatial_ref_sys", [], |row| row.get(0))
+        .map_err(sql_error)?;
+    Ok(max_id.map_or(100000, |id| (id + 1).max(100000)))
+}
+
+/// Returns the GeoPackage data type of the column that holds an attribute field.
+fn column_type(field: &AttributeField) -> &'static str {
+    match field.field_type {
+        'N' if field.decimal_count == 0 => "INTEGER",
+        'N' | 'F' => "REAL",
+        'D' => "DATE",
+        'L' => "BOOLEAN",
+        _ => "TEXT",
+    }
+}
+
+fn field_data_to_value(value: &FieldData) -> Value {
+    match value {
+        FieldData::Int(v) => Value::Integer(*v as i64),
+        FieldData::BigInt(v) => Value::Integer(*v),
+        FieldData::Real(v) if v.is_nan() => Value::Null,
+        FieldData::Real(v) => Value::Real(*v),
+        FieldData::Text(s) => Value::Text(s.clone()),
+        FieldData::Date(d) => Value::Text(format!("{:04}-{:02}-{:02}", d.year, d.month, d.day)),
+        FieldData::Bool(v) => Value::Integer(*v as i64),
+        FieldData::Null => Value::Null,
+    }
+}
+
+/// The kind of attribute field that holds the values of a column.
+#[derive(Copy, Clone, Debug, PartialEq)]
+enum ColumnType {
+    Int,
+    Real,
+    Bool,
+    Date,
+    Text,
+}
+
+#[derive(Debug)]
+struct ColumnKind {
+    column_type: ColumnType,
+    length: usize,
+    integer_digits: usize,
+    decimals: usize,
+}
+
+impl ColumnKind {
+    /// Determines the kind of a column from its declared type and its values. SQLite does
+    /// not enforce declared types, so integer columns are checked for real and text values,
+    /// and columns of other declared types are judged by their values.
+    fn new<'a, I: Iterator<Item = &'a Value>>(declared_type: &str, values: I) -> ColumnKind {
+        let declared_type = declared_type.to_uppercase();
+        let declared_type = declared_type.split('(').next().unwrap_or("").trim();
+        let mut kind = ColumnKind {
+            column_type: match declared_type {
+                "BOOLEAN" => ColumnType::Bool,
+                "TINYINT" | "SMALLINT" | "MEDIUMINT" | "INT" | "INTEGER" => ColumnType::Int,
+                "DOUBLE" | "FLOAT" | "REAL" => ColumnType::Real,
+                "DATE" => ColumnType::Date,
+                _ => ColumnType::Text,
+            },
+            length: 1,
+            integer_digits: 1,
+            decimals: 0,
+        };
+        let is_declared_text = declared_type.starts_with("TEXT") || declared_type == "DATETIME";
+        let mut inferred: Option<ColumnType> = None;
+        for value in values {
+            let value_type = match value {
+                Value::Null => continue,
+                Value::Integer(_) => ColumnType::Int,
+                Value::Real(_) => ColumnType::Real,
+                _ => ColumnType::Text,
+            };
+            inferred = match inferred {
+                None => Some(value_type),
+                Some(t) if t == value_type => Some(t),
+                Some(ColumnType::Int) | Some(ColumnType::Real)
+                    if value_type == ColumnType::Int || value_type == ColumnType::Real =>
+                {
+                    Some(ColumnType::Real)
+                }
+                _ => Some(ColumnType::Text),
+            };
+            let s = value_to_string(value);
+            kind.length = kind.length.max(s.len());
+            if let Value::Integer(_) | Value::Real(_) = value {
+                let mut split = s.splitn(2, '.');
+                kind.integer_digits = kind.integer_digits.max(split.next().unwrap_or("").len());
+                kind.decimals = kind.decimals.max(split.next().unwrap_or("").len());
+            }
+        }
+        match (kind.column_type, inferred) {
+            (ColumnType::Int, Some(ColumnType::Real)) => kind.column_type = ColumnType::Real,
+            (ColumnType::Int, Some(ColumnType::Text)) | (ColumnType::Real, Some(ColumnType::Text)) => {
+                kind.column_type = ColumnType::Text
+            }
+            (ColumnType::Text, Some(t)) if !is_declared_text => kind.column_type = t,
+            _ => {}
+        }
+        kind
+    }
+
+    fn to_field(self, name: &str) -> (AttributeField, ColumnKind) {
+        let field = match self.column_type {
+            ColumnType::Bool => AttributeField::new(name, FieldDataType::Bool, 1u8, 0u8),
+            ColumnType::Date => AttributeField::new(name, FieldDataType::Date, 8u8, 0u8),
+            ColumnType::Int => AttributeField::new(
+                name,
+                FieldDataType::Int,
+                self.integer_digits.min(20) as u8,
+                0u8,
+            ),
+            ColumnType::Real => {
+                let decimals = self.decimals.min(15);
+                let length = (self.integer_digits + decimals + 1).min(254);
+                AttributeField::new(name, FieldDataType::Real, length as u8, decimals as u8)
+            }
+            ColumnType::Text => {
+                AttributeField::new(name, FieldDataType::Text, self.length.min(254) as u8, 0u8)
+            }
+        };
+        (field, self)
+    }
+
+    fn to_field_data(&self, value: &Value) -> FieldData {
+        match (self.column_type, value) {
+            (_, Value::Null) => FieldData::Null,
+            (ColumnType::Int, Value::Integer(v)) => FieldData::from_i64(*v),
+            (ColumnType::Real, Value::Integer(v)) => FieldData::Real(*v as f64),
+            (ColumnType::Real, Value::Real(v)) => FieldData::Real(*v),
+            (ColumnType::Bool, Value::Integer(v)) => FieldData::Bool(*v != 0),
+            (ColumnType::Bool, Value::Text(s)) => {
+                FieldData::Bool(s.eq_ignore_ascii_case("true") || s == "1")
+            }
+            (ColumnType::Date, Value::Text(s)) => parse_date(s).unwrap_or(FieldData::Null),
+            (ColumnType::Text, _) => FieldData::Text(value_to_string(value)),
+            _ => FieldData::Null,
+        }
+    }
+}
+
+fn value_to_string(value: &Value) -> String {
+    match value {
+        Value::Null => String::new(),
+        Value::Integer(v) => v.to_string(),
+        Value::Real(v) => v.to_string(),
+        Value::Text(s) => s.clone(),
+        Value::Blob(b) => format!("<{} bytes>", b.len()),
+    }
+}
+
+/// Parses a date of the form YYYY-MM-DD, which may be followed by a time.
+pub(crate) fn parse_date(s: &str) -> Option<FieldData> {
+    let mut parts = s.get(0..10)?.split('-');
+    let year = parts.next()?.parse::<u16>().ok()?;
+    let month = parts.next()?.parse::<u8>().ok()?;
+    let day = parts.next()?.parse::<u8>().ok()?;
+    Some(FieldData::Date(DateData { year, month, day }))
+}
+
+#[derive(Copy, Clone, Debug)]
+pub(crate) struct Vertex {
+    pub p: Point2D,
+    pub z: f64,
+    pub m: f64,
+}
+
+/// A geometry as read from WKB or FlatGeobuf. For points and lines, each part is a point or
+/// line string; for polygons, each part is a ring, already in the Shapefile's orientation.
+#[derive(Default)]
+pub(crate) struct FeatureGeometry {
+    pub class: Option<GeometryClass>,
+    pub parts: Vec<Vec<Vertex>>,
+    pub has_z: bool,
+    pub has_m: bool,
+}
+
+impl FeatureGeometry {
+    /// Records the class of a member geometry; all members must be of the same class.
+    pub fn set_class(&mut self, class: GeometryClass) -> Result<(), Error> {
+        match self.class {
+            None => self.class = Some(class),
+            Some(c) if c != class => {
+                return Err(invalid(
+                    "The members of a geometry collection must all be of the same class (points, lines or polygons).",
+                ))
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+
+    /// Adds a polygon ring, closing it and orienting it as a Shapefile expects, i.e. with
+    /// exterior rings clockwise and holes counter-clockwise.
+    pub fn push_ring(&mut self, mut ring: Vec<Vertex>, is_exterior: bool) {
+        if ring.is_empty() {
+            return;
+        }
+        if ring[0].p != ring[ring.len() - 1].p {
+            ring.push(ring[0]);
+        }
+        let area = signed_area(&ring.iter().map(|v| v.p).collect::<Vec<Point2D>>());
+        if (is_exterior && area > 0f64) || (!is_exterior && area < 0f64) {
+            ring.reverse();
+        }
+        self.parts.push(ring);
+    }
+
+    pub fn to_shapefile_geometry(&self, shape_type: ShapeType, has_m: bool) -> ShapefileGeometry {
+        if self.parts.is_empty() {
+            return ShapefileGeometry::new(ShapeType::Null);
+        }
+        let mut sfg = ShapefileGeometry::new(shape_type);
+        let has_parts = match shape_type.base_shape_type() {
+            ShapeType::PolyLine | ShapeType::Polygon => true,
+            _ => false,
+        };
+        let dimension = shape_type.dimension();
+        for part in &self.parts {
+            if has_parts {
+                sfg.parts.push(sfg.num_points);
+                sfg.num_parts += 1;
+            }
+            for v in part {
+                match dimension {
+                    ShapeTypeDimension::XY => sfg.add_point(v.p),
+                    ShapeTypeDimension::Measure => sfg.add_pointm(v.p, v.m),
+                    ShapeTypeDimension::Z if has_m => sfg.add_pointz(v.p, v.m, v.z),
+                    ShapeTypeDimension::Z => {
+                        sfg.add_point(v.p);
+                        sfg.z_array.push(v.z);
+                        sfg.z_min = sfg.z_min.min(v.z);
+                        sfg.z_max = sfg.z_max.max(v.z);
+                    }
+                }
+            }
+        }
+        sfg
+    }
+}
+
+/// Determines the ShapeType of a layer from its declared geometry class and dimensions and
+/// those of its features, which must all be of the same class. The returned flag tells
+/// whether measures are to be kept.
+pub(crate) fn layer_shape_type(
+    geometries: &[FeatureGeometry],
+    mut class: Option<GeometryClass>,
+    mut is_multipoint: bool,
+    mut has_z: bool,
+    mut has_m: bool,
+) -> Result<(ShapeType, bool), Error> {
+    for g in geometries.iter().filter(|g| !g.parts.is_empty()) {
+        match class {
+            None => class = g.class,
+            Some(c) if Some(c) != g.class => {
+                return Err(Error::new(
+                    ErrorKind::InvalidData,
+                    "The features of a layer must all have geometries of the same class (points, lines or polygons).",
+                ))
+            }
+            _ => {}
+        }
+        is_multipoint = is_multipoint || (g.class == Some(GeometryClass::Point) && g.parts.len() != 1);
+        has_z = has_z || g.has_z;
+        has_m = has_m || g.has_m;
+    }
+    let shape_type = match (class, has_z, has_m) {
+        (None, _, _) => ShapeType::Null,
+        (Some(GeometryClass::Point), true, _) if is_multipoint => ShapeType::MultiPointZ,
+        (Some(GeometryClass::Point), false, true) if is_multipoint => ShapeType::MultiPointM,
+        (Some(GeometryClass::Point), false, false) if is_multipoint => ShapeType::MultiPoint,
+        (Some(GeometryClass::Point), true, _) => ShapeType::PointZ,
+        (Some(GeometryClass::Point), false, true) => ShapeType::PointM,
+        (Some(GeometryClass::Point), false, false) => ShapeType::Point,
+        (Some(GeometryClass::Line), true, _) => ShapeType::PolyLineZ,
+        (Some(GeometryClass::Line), false, true) => ShapeType::PolyLineM,
+        (Some(GeometryClass::Line), false, false) => ShapeType::PolyLine,
+        (Some(GeometryClass::Polygon), true, _) => ShapeType::PolygonZ,
+        (Some(GeometryClass::Polygon), false, true) => ShapeType::PolygonM,
+        (Some(GeometryClass::Polygon), false, false) => ShapeType::Polygon,
+    };
+    Ok((shape_type, has_m))
+}
+
+/// Parses a GeoPackage binary geometry blob: the 'GP' magic number, a version, a flags
+/// byte, the srs_id, an optional envelope, and the geometry in WKB.
+fn parse_gpkg_geometry(blob: &[u8]) -> Result<FeatureGeometry, Error> {
+    if blob.len() < 8 || &blob[0..2] != b"GP" {
+        return Err(invalid("A geometry of the GeoPackage is not a GeoPackage binary blob."));
+    }
+    let flags = blob[3];
+    let envelope_size = match (flags >> 1) & 7 {
+        0 => 0,
+        1 => 32,
+        2 | 3 => 48,
+        4 => 64,
+        _ => return Err(invalid("A geometry of the GeoPackage has an invalid envelope type.")),
+    };
+    let mut g = FeatureGeometry::default();
+    if flags & 0x10 != 0 || blob.len() <= 8 + envelope_size {
+        // an empty geometry
+        return Ok(g);
+    }
+    let mut reader = WkbReader {
+        buf: &blob[8 + envelope_size..],
+        pos: 0,
+    };
+    reader.read_geometry(&mut g)?;
+    Ok(g)
+}
+
+struct WkbReader<'a> {
+    buf: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> WkbReader<'a> {
+    fn read_bytes<const N: usize>(&mut self) -> Result<[u8; N], Error> {
+        if self.pos + N > self.buf.len() {
+            return Err(invalid("A WKB geometry of the GeoPackage is truncated."));
+        }
+        let mut bytes = [0u8; N];
+        bytes.copy_from_slice(&self.buf[self.pos..self.pos + N]);
+        self.pos += N;
+        Ok(bytes)
+    }
+
+    fn read_u32(&mut self, little_endian: bool) -> Result<u32, Error> {
+        let bytes = self.read_bytes::<4>()?;
+        Ok(if little_endian {
+            u32::from_le_bytes(bytes)
+        } else {
+            u32::from_be_bytes(bytes)
+        })
+    }
+
+    fn read_f64(&mut self, little_endian: bool) -> Result<f64, Error> {
+        let bytes = self.read_bytes::<8>()?;
+        Ok(if little_endian {
+            f64::from_le_bytes(bytes)
+        } else {
+            f64::from_be_bytes(bytes)
+        })
+    }
+
+    fn read_vertex(&mut self, little_endian: bool, has_z: bool, has_m: bool) -> Result<Vertex, Error> {
+        let x = self.read_f64(little_endian)?;
+        let y = self.read_f64(little_endian)?;
+        let z = if has_z { self.read_f64(little_endian)? } else { 0f64 };
+        let m = if has_m { self.read_f64(little_endian)? } else { 0f64 };
+        Ok(Vertex {
+            p: Point2D::new(x, y),
+            z: z,
+            m: m,
+        })
+    }
+
+    fn read_vertices(&mut self, little_endian: bool, has_z: bool, has_m: bool) -> Result<Vec<Vertex>, Error> {
+        let n = self.read_u32(little_endian)? as usize;
+        let mut vertices = Vec::with_capacity(n.min(self.buf.len() / 16));
+        for _ in 0..n {
+            vertices.push(self.read_vertex(little_endian, has_z, has_m)?);
+        }
+        Ok(vertices)
+    }
+
+    /// Reads a geometry, adding its parts to `g`. Both ISO WKB, in which 1000, 2000 and 3000
+    /// are added to the geometry type of Z, M and ZM geometries, and the extended WKB of
+    /// PostGIS, which uses the high bits of the geometry type, are recognized.
+    fn read_geometry(&mut self, g: &mut FeatureGeometry) -> Result<(), Error> {
+        let little_endian = self.read_bytes::<1>()?[0] == 1;
+        let raw_type = self.read_u32(little_endian)?;
+        let mut has_z = raw_type & 0x8000_0000 != 0;
+        let mut has_m = raw_type & 0x4000_0000 != 0;
+        if raw_type & 0x2000_0000 != 0 {
+            // an embedded SRID
+            self.read_u32(little_endian)?;
+        }
+        let code = raw_type & 0x0FFF_FFFF;
+        match code / 1000 {
+            1 => has_z = true,
+            2 => has_m = true,
+            3 => {
+                has_z = true;
+                has_m = true;
+            }
+            _ => {}
+        }
+        g.has_z = g.has_z || has_z;
+        g.has_m = g.has_m || has_m;
+        let class = match code % 1000 {
+            1 => GeometryClass::Point,
+            2 => GeometryClass::Line,
+            3 => GeometryClass::Polygon,
+            4 | 5 | 6 | 7 => {
+                let n = self.read_u32(little_endian)?;
+                for _ in 0..n {
+                    self.read_geometry(g)?;
+                }
+                return Ok(());
+            }
+            t => {
+                return Err(Error::new(
+                    ErrorKind::InvalidData,
+                    format!(
+                        "Unsupported WKB geometry type ({}). Only points, lines, polygons, their multi-part forms, and geometry collections of these can be read.",
+                        t
+                    ),
+                ))
+            }
+        };
+        g.set_class(class)?;
+        match class {
+            GeometryClass::Point => {
+                let v = self.read_vertex(little_endian, has_z, has_m)?;
+                // empty points have NaN coordinates
+                if !v.p.x.is_nan() && !v.p.y.is_nan() {
+                    g.parts.push(vec![v]);
+                }
+            }
+            GeometryClass::Line => {
+                let line = self.read_vertices(little_endian, has_z, has_m)?;
+                if !line.is_empty() {
+                    g.parts.push(line);
+                }
+            }
+            GeometryClass::Polygon => {
+                let num_rings = self.read_u32(little_endian)?;
+                for i in 0..num_rings {
+                    let ring = self.read_vertices(little_endian, has_z, has_m)?;
+                    g.push_ring(ring, i == 0);
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Encodes a geometry as a GeoPackage binary blob, in little-endian byte order, with an
+/// envelope for all but point geometries. Null and empty geometries return None.
+fn gpkg_geometry_blob(sfg: &ShapefileGeometry, srs_id: i32, has_z: bool, has_m: bool) -> Option<Vec<u8>> {
+    if sfg.shape_type == ShapeType::Null || sfg.points.is_empty() {
+        return None;
+    }
+    let is_point = sfg.shape_type.base_shape_type() == ShapeType::Point;
+    let mut buf: Vec<u8> = Vec::with_capacity(48 + sfg.points.len() * 32);
+    buf.extend_from_slice(b"GP");
+    buf.push(0u8); // version 1
+    buf.push(if is_point { 0b0000_0001 } else { 0b0000_0011 });
+    buf.write_i32::<LittleEndian>(srs_id).unwrap();
+    if !is_point {
+        for v in [sfg.x_min, sfg.x_max, sfg.y_min, sfg.y_max] {
+            buf.write_f64::<LittleEndian>(v).unwrap();
+        }
+    }
+
+    let dims = if has_z { 1000 } else { 0 } + if has_m { 2000 } else { 0 };
+    let write_header = |buf: &mut Vec<u8>, geometry_type: u32| {
+        buf.push(1u8);
+        buf.write_u32::<LittleEndian>(geometry_type + dims).unwrap();
+    };
+    let write_vertex = |buf: &mut Vec<u8>, i: usize| {
+        buf.write_f64::<LittleEndian>(sfg.points[i].x).unwrap();
+        buf.write_f64::<LittleEndian>(sfg.points[i].y).unwrap();
+        if has_z {
+            buf.write_f64::<LittleEndian>(sfg.z_array.get(i).copied().unwrap_or(0f64)).unwrap();
+        }
+        if has_m {
+            buf.write_f64::<LittleEndian>(sfg.m_array.get(i).copied().unwrap_or(0f64)).unwrap();
+        }
+    };
+    let write_vertices = |buf: &mut Vec<u8>, a: usize, b: usize, reverse: bool| {
+        buf.write_u32::<LittleEndian>((b - a) as u32).unwrap();
+        if reverse {
+            for i in (a..b).rev() {
+                write_vertex(buf, i);
+            }
+        } else {
+            for i in a..b {
+                write_vertex(buf, i);
+            }
+        }
+    };
+
+    let part_ranges = part_ranges(sfg);
+    match sfg.shape_type.base_shape_type() {
+        ShapeType::Point => {
+            write_header(&mut buf, 1);
+            write_vertex(&mut buf, 0);
+        }
+        ShapeType::MultiPoint => {
+            write_header(&mut buf, 4);
+            buf.write_u32::<LittleEndian>(sfg.points.len() as u32).unwrap();
+            for i in 0..sfg.points.len() {
+                write_header(&mut buf, 1);
+                write_vertex(&mut buf, i);
+            }
+        }
+        ShapeType::PolyLine => {
+            write_header(&mut buf, 5);
+            buf.write_u32::<LittleEndian>(part_ranges.len() as u32).unwrap();
+            for &(a, b) in &part_ranges {
+                write_header(&mut buf, 2);
+                write_vertices(&mut buf, a, b, false);
+            }
+        }
+        _ => {
+            let polygons = group_polygon_rings(sfg, &part_ranges);
+            write_header(&mut buf, 6);
+            buf.write_u32::<LittleEndian>(polygons.len() as u32).unwrap();
+            for rings in &polygons {
+                write_header(&mut buf, 3);
+                buf.write_u32::<LittleEndian>(rings.len() as u32).unwrap();
+                for (i, &(a, b)) in rings.iter().enumerate() {
+                    let is_clockwise = signed_area(&sfg.points[a..b]) <= 0f64;
+                    // exteriors are counter-clockwise and holes clockwise
+                    write_vertices(&mut buf, a, b, (i == 0) == is_clockwise);
+                }
+            }
+        }
+    }
+    Some(buf)
+}
+
+#[cfg(test)]
+mod test {
+    use crate::test_utils::*;
+    use crate::*;
+    use rusqlite::Connection;
+    use std::fs;
+    use whitebox_common::spatial_ref_system::esri_wkt_from_epsg;
+
+    #[test]
+    fn multi_layer_round_trip() {
+        let gpkg_file = temp_file("layers.gpkg");
+        let _ = fs::remove_file(&gpkg_file);
+
+        let mut parcels = sample_file(&format!("{}:parcels", gpkg_file), ShapeType::Polygon);
+        parcels
+            .attributes
+            .add_field(&AttributeField::new("SURVEYED", FieldDataType::Date, 8u8, 0u8));
+        for i in 0..2 {
+            let date = DateData { year: 2020 + i as u16, month: 2, day: 29 - i as u8 };
+            parcels.attributes.set_value(i, "SURVEYED", FieldData::Date(date));
+        }
+        parcels.write().unwrap();
+        let mut roads = sample_file(&format!("{}:roads", gpkg_file), ShapeType::PolyLine);
+        roads.projection = esri_wkt_from_epsg(26917);
+        roads.write().unwrap();
+
+        let input = Shapefile::read(&format!("{}:parcels", gpkg_file)).unwrap();
+        assert_same_features(&parcels, &input);
+        assert_eq!(input.records[0].num_parts, 2);
+        assert_eq!(input.attributes.get_value(0, "BIG"), FieldData::BigInt(10_000_000_000));
+        assert_eq!(
+            input.attributes.get_value(1, "SURVEYED"),
+            FieldData::Date(DateData { year: 2021, month: 2, day: 28 })
+        );
+        assert!(input.projection.contains("WGS"));
+        let input = Shapefile::read(&format!("{}:roads", gpkg_file)).unwrap();
+        assert_same_features(&roads, &input);
+        assert!(input.projection.contains("UTM"));
+
+        // The geometries are GeoPackage binary blobs, which name the SRS of their layer.
+        let conn = Connection::open(&gpkg_file).unwrap();
+        for (table, code) in [("parcels", 4326), ("roads", 26917)] {
+            let (organization, coordsys_id, srs_id): (String, i64, i32) = conn
+                .query_row(
+                    "SELECT s.organization, s.organization_coordsys_id, s.srs_id FROM gpkg_spatial_ref_sys s \
+                     JOIN gpkg_geometry_columns g ON g.srs_id = s.srs_id WHERE g.table_name = ?1",
+                    [table],
+                    |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
+                )
+                .unwrap();
+            assert_eq!((organization.as_str(), coordsys_id), ("EPSG", code));
+            let blob: Vec<u8> = conn
+                .query_row(&format!("SELECT geom FROM {} ORDER BY fid LIMIT 1", table), [], |row| {
+                    row.get(0)
+                })
+                .unwrap();
+            assert_eq!(&blob[0..3], b"GP\0");
+            assert_eq!(blob[3] & 0b0000_0001, 1); // little-endian
+            assert_eq!(blob[3] & 0b0000_1110, 0b0000_0010); // xy envelope
+            assert_eq!(i32::from_le_bytes([blob[4], blob[5], blob[6], blob[7]]), srs_id);
+        }
+        drop(conn);
+        fs::remove_file(&gpkg_file).unwrap();
+    }
+}
@@ -7,7 +7,14 @@ License: MIT
 */
 
 // private sub-module defined in other files
+pub mod flatgeobuf;
+pub mod geojson;
+pub mod geopackage;
 pub mod shapefile;
+pub mod vector_layer;
+
+#[cfg(test)]
+mod test_utils;
 
 // exports identifiers from private sub-modules in the current module namespace
 // pub use self::shapefile::attributes::{
@@ -18,4 +25,7 @@ pub use crate::shapefile::attributes::*;
 pub use crate::shapefile::geometry::*;
 pub use crate::shapefile::geometry::ShapeType;
 pub use crate::shapefile::Shapefile;
+pub use crate::shapefile::reader::{ShapefileFeatures, ShapefileReader};
+pub use crate::shapefile::writer::ShapefileWriter;
+pub use crate::vector_layer::{derived_file_name, is_vector_file, VectorFormat};
 // pub use whitebox_common::structures::Point2D;
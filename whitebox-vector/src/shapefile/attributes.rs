@@ -9,6 +9,7 @@ NOTE: Structures and functions for handling the Shapefile attribute table info
 contained with the associated .dbf file.
 */
 
+use super::dbf::format_real;
 use std::collections::HashMap;
 use std::fmt;
 
@@ -59,6 +60,7 @@ impl fmt::Display for DateData {
 #[derive(Debug, Clone, PartialEq)]
 pub enum FieldData {
     Int(i32),
+    BigInt(i64),
     Real(f64),
     Text(String),
     Date(DateData),
@@ -66,11 +68,23 @@ pub enum FieldData {
     Null,
 }
 
+impl FieldData {
+    /// Returns an integer value, which is an Int if it fits into 32 bits and a BigInt otherwise.
+    pub fn from_i64(value: i64) -> FieldData {
+        if value >= i32::MIN as i64 && value <= i32::MAX as i64 {
+            FieldData::Int(value as i32)
+        } else {
+            FieldData::BigInt(value)
+        }
+    }
+}
+
 impl fmt::Display for FieldData {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         // write!(f, "{:?}", self)
         match self {
             FieldData::Int(value) => return write!(f, "{}", value),
+            FieldData::BigInt(value) => return write!(f, "{}", value),
             FieldData::Real(value) => return write!(f, "{}", value),
             FieldData::Text(value) => return write!(f, "{}", value),
             FieldData::Date(value) => return write!(f, "{}", value),
@@ -173,10 +187,130 @@ impl Intersector for Vec<AttributeField> {
     }
 }
 
+/// The character encodings of the text in .dbf files. The encoding of a file is named by its
+/// .cpg file or, failing that, by the language driver ID in the .dbf header.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum CodePage {
+    Utf8,
+    Latin1,
+    Windows1252,
+}
+
+impl Default for CodePage {
+    fn default() -> CodePage {
+        CodePage::Utf8
+    }
+}
+
+/// The characters of bytes 0x80 to 0x9F in Windows-1252. The five bytes that are undefined
+/// in Windows-1252 map to the C1 control characters, as they do in Latin-1.
+const WINDOWS_1252_HIGH: [char; 32] = [
+    '\u{20AC}', '\u{81}', '\u{201A}', '\u{192}', '\u{201E}', '\u{2026}', '\u{2020}', '\u{2021}',
+    '\u{2C6}', '\u{2030}', '\u{160}', '\u{2039}', '\u{152}', '\u{8D}', '\u{17D}', '\u{8F}',
+    '\u{90}', '\u{2018}', '\u{2019}', '\u{201C}', '\u{201D}', '\u{2022}', '\u{2013}', '\u{2014}',
+    '\u{2DC}', '\u{2122}', '\u{161}', '\u{203A}', '\u{153}', '\u{9D}', '\u{17E}', '\u{178}',
+];
+
+impl CodePage {
+    /// Returns the code page named by the contents of a .cpg file, or None if it is not one
+    /// of the supported code pages.
+    pub fn from_cpg(name: &str) -> Option<CodePage> {
+        let name = name.trim().to_ascii_uppercase().replace(|c| c == '-' || c == '_' || c == ' ', "");
+        match name.as_str() {
+            "UTF8" | "65001" => Some(CodePage::Utf8),
+            "ISO88591" | "88591" | "LATIN1" | "28591" => Some(CodePage::Latin1),
+            "1252" | "CP1252" | "ANSI1252" | "WINDOWS1252" => Some(CodePage::Windows1252),
+            _ => None,
+        }
+    }
+
+    /// Returns the code page of a .dbf language driver ID, or None if the ID is unset or
+    /// names a code page that is not supported.
+    pub fn from_language_driver_id(id: u8) -> Option<CodePage> {
+        match id {
+            0x03 | 0x57 | 0x58 | 0x59 => Some(CodePage::Windows1252),
+            _ => None,
+        }
+    }
+
+    /// Returns the language driver ID that is written to .dbf headers, which is zero for the
+    /// code pages that have no ID and that are only named by the .cpg file.
+    pub fn language_driver_id(&self) -> u8 {
+        match self {
+            CodePage::Windows1252 => 0x57,
+            _ => 0,
+        }
+    }
+
+    /// Returns the name of the code page that is written to .cpg files.
+    pub fn cpg_name(&self) -> &'static str {
+        match self {
+            CodePage::Utf8 => "UTF-8",
+            CodePage::Latin1 => "ISO-8859-1",
+            CodePage::Windows1252 => "1252",
+        }
+    }
+
+    /// Decodes text. A UTF-8 character that is cut off by the end of the bytes, as happens when
+    /// text is truncated to the length of a field, is dropped.
+    pub fn decode(&self, bytes: &[u8]) -> String {
+        match self {
+            CodePage::Utf8 => match std::str::from_utf8(bytes) {
+                Ok(s) => s.to_string(),
+                Err(e) if e.error_len().is_none() => {
+                    String::from_utf8_lossy(&bytes[..e.valid_up_to()]).to_string()
+                }
+                Err(_) => String::from_utf8_lossy(bytes).to_string(),
+            },
+            CodePage::Latin1 => bytes.iter().map(|&b| b as char).collect(),
+            CodePage::Windows1252 => bytes
+                .iter()
+                .map(|&b| match b {
+                    0x80..=0x9F => WINDOWS_1252_HIGH[b as usize - 0x80],
+                    _ => b as char,
+                })
+                .collect(),
+        }
+    }
+
+    /// Encodes text, replacing the characters that the code page cannot represent with '?'.
+    pub fn encode(&self, s: &str) -> Vec<u8> {
+        match self {
+            CodePage::Utf8 => s.as_bytes().to_vec(),
+            CodePage::Latin1 => s
+                .chars()
+                .map(|c| if (c as u32) < 0x100 { c as u8 } else { b'?' })
+                .collect(),
+            CodePage::Windows1252 => s
+                .chars()
+                .map(|c| match WINDOWS_1252_HIGH.iter().position(|&h| h == c) {
+                    Some(i) => 0x80 + i as u8,
+                    None if (c as u32) < 0x80 || ((c as u32) >= 0xA0 && (c as u32) < 0x100) => c as u8,
+                    None => b'?',
+                })
+                .collect(),
+        }
+    }
+
+    /// Returns true if the bytes are text in this code page. Only UTF-8 can be told apart from
+    /// other encodings in this way; the single-byte code pages can decode any bytes.
+    pub fn is_valid(&self, bytes: &[u8]) -> bool {
+        match self {
+            CodePage::Utf8 => match std::str::from_utf8(bytes) {
+                Ok(_) => true,
+                Err(e) => e.error_len().is_none(),
+            },
+            _ => true,
+        }
+    }
+}
+
 #[derive(Default, Clone)]
 pub struct ShapefileAttributes {
     pub header: AttributeHeader,
     pub fields: Vec<AttributeField>,
+    /// The code page of the text in the .dbf file, which is UTF-8 for new files.
+    pub code_page: CodePage,
     data: Vec<Vec<FieldData>>,
     pub is_deleted: Vec<bool>,
     field_map: HashMap<String, usize>,
@@ -243,6 +377,33 @@ impl ShapefileAttributes {
         self.header.num_records = self.data.len() as u32; //+= 1;
     }
 
+    /// Returns all of the attribute records, in order.
+    pub(crate) fn get_records(&self) -> &Vec<Vec<FieldData>> {
+        &self.data
+    }
+
+    /// Widens the numeric fields whose values do not fit in them. Numbers that are wider than
+    /// their fields would otherwise be written as asterisks, i.e. as nulls.
+    pub(crate) fn widen_numeric_fields(&mut self) {
+        for rec in &self.data {
+            for (field, value) in self.fields.iter_mut().zip(rec) {
+                let width = match value {
+                    FieldData::Int(v) => v.to_string().len(),
+                    FieldData::BigInt(v) => v.to_string().len(),
+                    FieldData::Real(v) if v.is_finite() => {
+                        format_real(*v, field.decimal_count as usize).len()
+                    }
+                    _ => 0,
+                };
+                if (field.field_type == 'N' || field.field_type == 'F')
+                    && width > field.field_length as usize
+                {
+                    field.field_length = width.min(255) as u8;
+                }
+            }
+        }
+    }
+
     /// Retrieves an attribute record for a zero-based index. The returned data is a copy of the original.
     pub fn get_record(&self, index: usize) -> Vec<FieldData> {
         if index >= self.header.num_records as usize {
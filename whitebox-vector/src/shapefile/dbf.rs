@@ -0,0 +1,734 @@
+/*
+This code is part of the WhiteboxTools geospatial analysis library.
+License: MIT
+
+Notes: Reading and writing of the dBASE (.dbf) attribute tables of Shapefiles and of their
+memo (.dbt) files. Records are read and written one at a time, so that the same code serves
+both whole tables, held in memory by the Shapefile struct, and the streams of records of
+ShapefileReader and ShapefileWriter.
+*/
+
+use super::attributes::*;
+use byteorder::{LittleEndian, WriteBytesExt};
+use chrono::prelude::*;
+use std::fs;
+use std::fs::File;
+use std::io::prelude::*;
+use std::io::{BufReader, BufWriter, Error, SeekFrom};
+use std::path::Path;
+
+/// The size, in bytes, of the blocks of a dBASE III memo (.dbt) file.
+const MEMO_BLOCK_SIZE: usize = 512;
+
+/// The header of a .dbf file, which describes the fields and the layout of the records.
+pub(crate) struct DbfHeader {
+    pub header: AttributeHeader,
+    pub fields: Vec<AttributeField>,
+    /// The field names, which are decoded once the code page is known.
+    field_names: Vec<Vec<u8>>,
+    /// The offset of each field within a record.
+    offsets: Vec<usize>,
+    pub record_length: usize,
+    pub first_record: u64,
+    /// The code page named by the .cpg file or, failing that, by the language driver ID.
+    pub code_page: Option<CodePage>,
+}
+
+impl DbfHeader {
+    /// Reads the header of a .dbf file of a given size, and the code page named by the .cpg
+    /// file of the same name, if there is one.
+    pub fn read<R: Read>(reader: &mut R, file_size: u64, dbf_file: &Path) -> Result<DbfHeader, Error> {
+        let mut b = [0u8; 32];
+        reader.read_exact(&mut b)?;
+        let mut header = AttributeHeader {
+            version: b[0],
+            year: 1900u32 + b[1] as u32,
+            month: b[2],
+            day: b[3],
+            num_records: u32::from_le_bytes([b[4], b[5], b[6], b[7]]),
+            bytes_in_header: u16::from_le_bytes([b[8], b[9]]),
+            bytes_in_record: u16::from_le_bytes([b[10], b[11]]),
+            // bytes 12 and 13 are reserved
+            incomplete_tansaction: b[14],
+            encryption_flag: b[15],
+            // bytes 16 to 27 are reserved for LAN and multi-user dBASE
+            mdx_flag: b[28],
+            language_driver_id: b[29],
+            ..Default::default()
+        };
+
+        // read the field descriptors, which are followed by a terminator byte (0x0d)
+        let mut fields = vec![];
+        let mut field_names = vec![];
+        let mut header_length = 33u64;
+        loop {
+            reader.read_exact(&mut b[0..1])?;
+            if b[0] == 0x0d {
+                break;
+            }
+            reader.read_exact(&mut b[1..32])?;
+            header_length += 32;
+            let mut name = b[0..11].to_vec();
+            if let Some(end) = name.iter().position(|&c| c == 0) {
+                name.truncate(end);
+            }
+            field_names.push(name);
+            fields.push(AttributeField {
+                name: String::new(),
+                field_type: char::from(b[11]),
+                // bytes 12 to 15 are reserved
+                field_length: b[16],
+                decimal_count: b[17],
+                // the remaining bytes are the work area ID, flags and reserved bytes
+            });
+        }
+        header.num_fields = fields.len() as u32;
+
+        let mut offsets = Vec::with_capacity(fields.len());
+        let mut record_length = 1usize;
+        for field in &fields {
+            offsets.push(record_length);
+            record_length += field.field_length as usize;
+        }
+        record_length = record_length.max(header.bytes_in_record as usize);
+        // The records follow the header, which is longer than the field descriptors in some
+        // versions of the format.
+        let first_record = header_length.max(header.bytes_in_header as u64);
+        let available = (file_size.saturating_sub(first_record) / record_length as u64) as u32;
+        if available < header.num_records {
+            println!(
+                "Warning: The .dbf file is shorter than its header states; only {} of {} records were read.",
+                available, header.num_records
+            );
+            header.num_records = available;
+        }
+
+        // The code page of the text is named by the .cpg file or, failing that, by the
+        // language driver ID.
+        let mut code_page = match fs::read_to_string(dbf_file.with_extension("cpg")) {
+            Ok(name) => {
+                let code_page = CodePage::from_cpg(&name);
+                if code_page.is_none() {
+                    println!(
+                        "Warning: The code page '{}' named by the .cpg file is not supported.",
+                        name.trim()
+                    );
+                }
+                code_page
+            }
+            Err(_) => None,
+        };
+        if code_page.is_none() {
+            code_page = CodePage::from_language_driver_id(header.language_driver_id);
+        }
+
+        Ok(DbfHeader {
+            header,
+            fields,
+            field_names,
+            offsets,
+            record_length,
+            first_record,
+            code_page,
+        })
+    }
+
+    /// Returns the code page of the table. Without a known code page, the text is read as
+    /// UTF-8 if it is valid UTF-8, which is what is written by this library, and otherwise as
+    /// Windows-1252, the usual code page of older files. The field names are decoded in it.
+    pub fn resolve_code_page<I, B>(&mut self, records: I) -> Result<CodePage, Error>
+    where
+        I: Iterator<Item = Result<B, Error>>,
+        B: AsRef<[u8]>,
+    {
+        let code_page = match self.code_page {
+            Some(code_page) => code_page,
+            None => {
+                let mut is_utf8 = self.field_names.iter().all(|name| CodePage::Utf8.is_valid(name));
+                for record in records {
+                    if !is_utf8 {
+                        break;
+                    }
+                    let record = record?;
+                    let record = record.as_ref();
+                    is_utf8 = (0..self.fields.len()).all(|j| {
+                        self.fields[j].field_type != 'C'
+                            || CodePage::Utf8.is_valid(self.field_bytes(record, j))
+                    });
+                }
+                if is_utf8 {
+                    CodePage::Utf8
+                } else {
+                    CodePage::Windows1252
+                }
+            }
+        };
+        self.code_page = Some(code_page);
+        for (field, name) in self.fields.iter_mut().zip(&self.field_names) {
+            field.name = code_page.decode(name);
+        }
+        Ok(code_page)
+    }
+
+    fn field_bytes<'a>(&self, record: &'a [u8], j: usize) -> &'a [u8] {
+        &record[self.offsets[j]..self.offsets[j] + self.fields[j].field_length as usize]
+    }
+
+    /// Returns true if the table has memo fields, whose text is held in a .dbt file.
+    pub fn has_memo_fields(&self) -> bool {
+        self.fields.iter().any(|field| field.field_type == 'M')
+    }
+
+    /// Parses a record, returning its values and whether it is deleted. Values that cannot be
+    /// parsed are read as null and counted by field in `num_unparsed`.
+    pub fn parse_record(
+        &self,
+        record: &[u8],
+        memo_file: Option<&mut MemoFile>,
+        num_unparsed: &mut [usize],
+    ) -> Result<(Vec<FieldData>, bool), Error> {
+        let code_page = self.code_page.unwrap_or_default();
+        let mut memo_file = memo_file;
+        let mut r: Vec<FieldData> = Vec::with_capacity(self.fields.len());
+        for j in 0..self.fields.len() {
+            let bytes = self.field_bytes(record, j);
+            let value = if self.fields[j].field_type == 'M' {
+                match memo_file.as_mut() {
+                    Some(memo_file) => memo_file.get_text(bytes, code_page)?,
+                    None => Some(FieldData::Null),
+                }
+            } else {
+                parse_dbf_value(&self.fields[j], bytes, code_page)
+            };
+            match value {
+                Some(value) => r.push(value),
+                None => {
+                    num_unparsed[j] += 1;
+                    r.push(FieldData::Null);
+                }
+            }
+        }
+        Ok((r, record[0] == 0x2A))
+    }
+
+    /// Prints a warning for each field with values that could not be parsed.
+    pub fn warn_unparsed(&self, num_unparsed: &[usize]) {
+        for (field, &n) in self.fields.iter().zip(num_unparsed) {
+            if n > 0 {
+                println!(
+                    "Warning: {} value(s) of the {} field could not be read and were set to null.",
+                    n, field.name
+                );
+            }
+        }
+    }
+}
+
+/// A memo (.dbt) file, which holds the text of the memo fields of a .dbf file. Memos are read
+/// as they are needed.
+pub(crate) struct MemoFile {
+    reader: BufReader<File>,
+    block_size: u64,
+}
+
+impl MemoFile {
+    /// Opens the memo file of a .dbf file, which must be present if the table has memo
+    /// fields. Failing that, the memo fields are read as null.
+    pub fn open(dbf_file: &Path, dbf_version: u8) -> Option<MemoFile> {
+        let mut reader = match File::open(dbf_file.with_extension("dbt")) {
+            Ok(f) => BufReader::new(f),
+            Err(_) => {
+                println!("Warning: Memo file (.dbt) not located; memo fields were read as null.");
+                return None;
+            }
+        };
+        // dBASE IV memo files state their block size; dBASE III ones always use 512 bytes.
+        let mut block_size = MEMO_BLOCK_SIZE as u64;
+        let mut b = [0u8; 22];
+        if dbf_version == 0x8B && reader.read_exact(&mut b).is_ok() {
+            let size = u16::from_le_bytes([b[20], b[21]]) as u64;
+            if size > 0 {
+                block_size = size;
+            }
+        }
+        Some(MemoFile { reader, block_size })
+    }
+
+    /// Returns the text of a memo field from the block number that is the field's value in
+    /// a record, or None if the block number is not valid.
+    fn get_text(&mut self, field_bytes: &[u8], code_page: CodePage) -> Result<Option<FieldData>, Error> {
+        let block = String::from_utf8_lossy(field_bytes).replace(char::from(0), "");
+        let block = block.trim();
+        if block.is_empty() {
+            return Ok(Some(FieldData::Null));
+        }
+        let start = match block.parse::<u64>().ok().and_then(|b| b.checked_mul(self.block_size)) {
+            Some(start) if start > 0 => start,
+            _ => return Ok(None),
+        };
+        self.reader.seek(SeekFrom::Start(start))?;
+        let mut text = vec![];
+        let mut marker = [0u8; 8];
+        let n = read_up_to(&mut self.reader, &mut marker)?;
+        if n == 0 {
+            return Ok(None);
+        }
+        if n == 8 && marker[0..4] == [0xFF, 0xFF, 0x08, 0x00] {
+            // dBASE IV memos start with a marker and their length, which includes the 8 bytes
+            // of the marker and length.
+            let length = u32::from_le_bytes([marker[4], marker[5], marker[6], marker[7]]) as u64;
+            self.reader
+                .by_ref()
+                .take(length.saturating_sub(8))
+                .read_to_end(&mut text)?;
+        } else {
+            // dBASE III memos end with 0x1A.
+            text.extend_from_slice(&marker[0..n]);
+            if !text.contains(&0x1A) {
+                self.reader.read_until(0x1A, &mut text)?;
+            }
+            let end = text.iter().position(|&b| b == 0x1A).unwrap_or(text.len());
+            text.truncate(end);
+        }
+        if text.is_empty() {
+            Ok(Some(FieldData::Null))
+        } else {
+            Ok(Some(FieldData::Text(code_page.decode(&text))))
+        }
+    }
+}
+
+/// Reads as many bytes as are available, up to the length of the buffer.
+fn read_up_to<R: Read>(reader: &mut R, buf: &mut [u8]) -> Result<usize, Error> {
+    let mut n = 0;
+    while n < buf.len() {
+        match reader.read(&mut buf[n..])? {
+            0 => break,
+            m => n += m,
+        }
+    }
+    Ok(n)
+}
+
+/// A memo (.dbt) file that is being written, in the dBASE III format. The text of each memo
+/// is written to the blocks that follow those of the previous memo.
+pub(crate) struct MemoWriter {
+    writer: BufWriter<File>,
+    next_block: u32,
+}
+
+impl MemoWriter {
+    pub fn create(dbf_file: &Path) -> Result<MemoWriter, Error> {
+        let mut writer = BufWriter::new(File::create(dbf_file.with_extension("dbt"))?);
+        // The header block holds the number of the next free block, which is written when
+        // the file is finished, and the version.
+        let mut header = vec![0u8; MEMO_BLOCK_SIZE];
+        header[16] = 0x03; // dBASE III
+        writer.write_all(&header)?;
+        Ok(MemoWriter {
+            writer,
+            next_block: 1,
+        })
+    }
+
+    /// Writes a memo, returning the number of its first block.
+    fn write_memo(&mut self, text: &[u8]) -> Result<u32, Error> {
+        let block = self.next_block;
+        self.writer.write_all(text)?;
+        self.writer.write_all(&[0x1A, 0x1A])?;
+        let length = text.len() + 2;
+        let num_blocks = (length + MEMO_BLOCK_SIZE - 1) / MEMO_BLOCK_SIZE;
+        self.writer.write_all(&vec![0u8; num_blocks * MEMO_BLOCK_SIZE - length])?;
+        self.next_block += num_blocks as u32;
+        Ok(block)
+    }
+
+    pub fn finish(mut self) -> Result<(), Error> {
+        self.writer.seek(SeekFrom::Start(0))?;
+        self.writer.write_u32::<LittleEndian>(self.next_block)?;
+        self.writer.flush()
+    }
+}
+
+/// Writes the header of a .dbf file, for the number of records in the AttributeHeader. The
+/// version, lengths and language driver ID of the AttributeHeader are updated.
+pub(crate) fn write_dbf_header<W: Write>(
+    writer: &mut W,
+    header: &mut AttributeHeader,
+    fields: &[AttributeField],
+    code_page: CodePage,
+) -> Result<(), Error> {
+    // dBASE III, with a memo file if there are memo fields.
+    header.version = if fields.iter().any(|field| field.field_type == 'M') {
+        0x83u8
+    } else {
+        3u8
+    };
+    writer.write_u8(header.version)?;
+
+    // write the date
+    let now = Local::now();
+    writer.write_u8((now.year() - 1900) as u8)?;
+    writer.write_u8(now.month() as u8)?;
+    writer.write_u8(now.day() as u8)?;
+
+    writer.write_u32::<LittleEndian>(header.num_records)?; // number of records
+    header.num_fields = fields.len() as u32;
+    header.bytes_in_header = 32u16 + fields.len() as u16 * 32u16 + 1u16;
+    writer.write_u16::<LittleEndian>(header.bytes_in_header)?; // header size
+
+    header.bytes_in_record = 1 + fields.iter().map(|field| field.field_length as u16).sum::<u16>();
+    writer.write_u16::<LittleEndian>(header.bytes_in_record)?; // bytes in record
+
+    // reserved or unused bytes
+    for _ in 0..17 {
+        writer.write_u8(0u8)?;
+    }
+    header.language_driver_id = code_page.language_driver_id();
+    writer.write_u8(header.language_driver_id)?;
+    writer.write_u16::<LittleEndian>(0u16)?;
+
+    // Field descriptor array
+    for field in fields {
+        let mut s = code_page.encode(&field.name);
+        truncate_text(&mut s, 10, code_page);
+        s.resize(11, 0u8);
+        writer.write_all(&s)?;
+        writer.write_u8(field.field_type as u8)?;
+
+        for _ in 0..4 {
+            writer.write_u8(0u8)?;
+        }
+
+        writer.write_u8(field.field_length)?;
+        writer.write_u8(field.decimal_count)?;
+
+        for _ in 0..14 {
+            writer.write_u8(0u8)?;
+        }
+    }
+
+    writer.write_u8(0x0D) // terminator byte
+}
+
+/// Writes a record of a .dbf file. The text of memo fields is written to the memo file.
+pub(crate) fn write_dbf_record<W: Write>(
+    writer: &mut W,
+    fields: &[AttributeField],
+    rec: &[FieldData],
+    deleted: bool,
+    code_page: CodePage,
+    memo_writer: Option<&mut MemoWriter>,
+) -> Result<(), Error> {
+    if !deleted {
+        writer.write_u8(0x20)?;
+    } else {
+        writer.write_u8(0x2A)?;
+    }
+    let mut memo_writer = memo_writer;
+    for (field, value) in fields.iter().zip(rec) {
+        match (field.field_type, value, memo_writer.as_mut()) {
+            ('M', FieldData::Null, _) | ('M', _, None) => {
+                writer.write_all(&encode_dbf_value(field, &FieldData::Null, code_page))?
+            }
+            ('M', value, Some(memo_writer)) => {
+                let block = memo_writer.write_memo(&code_page.encode(&value.to_string()))?;
+                writer.write_all(&encode_dbf_value(field, &FieldData::Int(block as i32), code_page))?;
+            }
+            _ => writer.write_all(&encode_dbf_value(field, value, code_page))?,
+        }
+    }
+    Ok(())
+}
+
+/// Writes the .cpg file that names the code page of a .dbf file.
+pub(crate) fn write_cpg_file(dbf_file: &Path, code_page: CodePage) -> Result<(), Error> {
+    fs::write(dbf_file.with_extension("cpg"), code_page.cpg_name())
+}
+
+/// Parses the value of a .dbf field from the field's bytes in a record, returning None if the
+/// bytes are not a value of the field's type.
+fn parse_dbf_value(field: &AttributeField, bytes: &[u8], code_page: CodePage) -> Option<FieldData> {
+    let s = code_page.decode(bytes).replace(char::from(0), "");
+    let s = s.trim();
+    if s.replace(" ", "").replace("?", "").is_empty() {
+        return Some(FieldData::Null);
+    }
+    match field.field_type {
+        'N' | 'F' | 'I' | 'O' => {
+            // Numbers that do not fit in their fields are written as asterisks.
+            if s.chars().all(|c| c == '*') {
+                return Some(FieldData::Null);
+            }
+            if field.decimal_count == 0 {
+                if let Ok(v) = s.parse::<i32>() {
+                    return Some(FieldData::Int(v));
+                }
+                if let Ok(v) = s.parse::<i64>() {
+                    return Some(FieldData::BigInt(v));
+                }
+            }
+            s.parse::<f64>().ok().map(FieldData::Real)
+        }
+        'D' => {
+            if s.len() != 8 || !s.bytes().all(|b| b.is_ascii_digit()) {
+                return None;
+            }
+            let date = DateData {
+                year: s[0..4].parse::<u16>().ok()?,
+                month: s[4..6].parse::<u8>().ok()?,
+                day: s[6..8].parse::<u8>().ok()?,
+            };
+            if date.year == 0 && date.month == 0 && date.day == 0 {
+                // some software writes empty dates as zeros
+                Some(FieldData::Null)
+            } else if date.month >= 1 && date.month <= 12 && date.day >= 1 && date.day <= 31 {
+                Some(FieldData::Date(date))
+            } else {
+                None
+            }
+        }
+        'L' => match s.chars().next() {
+            Some('T') | Some('t') | Some('Y') | Some('y') => Some(FieldData::Bool(true)),
+            Some('F') | Some('f') | Some('N') | Some('n') => Some(FieldData::Bool(false)),
+            _ => None,
+        },
+        _ => {
+            // treat it like a string
+            Some(FieldData::Text(s.to_string()))
+        }
+    }
+}
+
+/// Encodes a value as the bytes of a .dbf field in a record, which are padded with spaces to
+/// the length of the field. Numbers are right-aligned and other values are left-aligned.
+/// Numbers that are wider than their field are written as asterisks, which are read as null,
+/// rather than being truncated to a different value.
+fn encode_dbf_value(field: &AttributeField, value: &FieldData, code_page: CodePage) -> Vec<u8> {
+    let fl = field.field_length as usize;
+    let mut right_aligned = false;
+    let mut bytes = match value {
+        FieldData::Null => vec![],
+        FieldData::Int(v) => {
+            right_aligned = true;
+            v.to_string().into_bytes()
+        }
+        FieldData::BigInt(v) => {
+            right_aligned = true;
+            v.to_string().into_bytes()
+        }
+        FieldData::Real(v) if !v.is_finite() => vec![],
+        FieldData::Real(v) => {
+            right_aligned = true;
+            format_real(*v, field.decimal_count as usize).into_bytes()
+        }
+        FieldData::Bool(v) => {
+            if *v {
+                b"T".to_vec()
+            } else {
+                b"F".to_vec()
+            }
+        }
+        FieldData::Date(v) => format!("{}", v).into_bytes(),
+        FieldData::Text(v) => code_page.encode(v),
+    };
+    if right_aligned {
+        if bytes.len() > fl {
+            return vec![b'*'; fl];
+        }
+        let mut padded = vec![b' '; fl - bytes.len()];
+        padded.extend(bytes);
+        padded
+    } else {
+        truncate_text(&mut bytes, fl, code_page);
+        bytes.resize(fl, b' ');
+        bytes
+    }
+}
+
+/// Formats a real number with a fixed number of decimals, which are truncated rather than
+/// rounded, and without a decimal point if there are none.
+pub(crate) fn format_real(v: f64, decimal_count: usize) -> String {
+    let s = v.to_string();
+    let e: Vec<&str> = s.split(".").collect();
+    if decimal_count == 0 {
+        return e[0].to_string();
+    }
+    let mut decimals = if e.len() == 2 { e[1].to_string() } else { String::new() };
+    decimals.truncate(decimal_count);
+    while decimals.len() < decimal_count {
+        // pad with some trailing zeros
+        decimals.push('0');
+    }
+    format!("{}.{}", e[0], decimals)
+}
+
+/// Truncates encoded text to a number of bytes, without splitting a UTF-8 character.
+fn truncate_text(bytes: &mut Vec<u8>, length: usize, code_page: CodePage) {
+    if bytes.len() > length {
+        let mut end = length;
+        if code_page == CodePage::Utf8 {
+            while end > 0 && bytes[end] & 0xC0 == 0x80 {
+                end -= 1;
+            }
+        }
+        bytes.truncate(end);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::test_utils::*;
+    use crate::*;
+    use whitebox_common::structures::Point2D;
+
+    #[test]
+    fn code_pages() {
+        assert_eq!(CodePage::from_cpg("utf-8\r\n"), Some(CodePage::Utf8));
+        assert_eq!(CodePage::from_cpg("ISO 8859-1"), Some(CodePage::Latin1));
+        assert_eq!(CodePage::from_cpg("ANSI 1252"), Some(CodePage::Windows1252));
+        assert_eq!(CodePage::from_cpg("GB2312"), None);
+        assert_eq!(CodePage::from_language_driver_id(0x57), Some(CodePage::Windows1252));
+        assert_eq!(CodePage::from_language_driver_id(0), None);
+
+        let text = "Zürich € café";
+        let bytes = CodePage::Windows1252.encode(text);
+        assert_eq!(bytes.len(), text.chars().count());
+        assert_eq!(bytes[7], 0x80);
+        assert_eq!(CodePage::Windows1252.decode(&bytes), text);
+        assert_eq!(CodePage::Latin1.decode(&CodePage::Latin1.encode(text)), "Zürich ? café");
+        // A character cut off by the end of a field is dropped.
+        let bytes = CodePage::Utf8.encode(text);
+        assert_eq!(CodePage::Utf8.decode(&bytes[..9]), "Zürich ");
+        assert!(CodePage::Utf8.is_valid(&bytes[..9]));
+        assert!(!CodePage::Utf8.is_valid(&CodePage::Latin1.encode(text)));
+    }
+
+    #[test]
+    fn attributes_round_trip_in_each_code_page() {
+        let file_name = temp_file("code_page.shp");
+        let memo = format!("{} {}", "ü".repeat(300), "memo");
+        for code_page in [CodePage::Utf8, CodePage::Latin1, CodePage::Windows1252] {
+            let mut output = Shapefile::new(&file_name, ShapeType::Point).unwrap();
+            output.attributes.code_page = code_page;
+            let attributes = &mut output.attributes;
+            attributes.add_field(&AttributeField::new("ID", FieldDataType::Int, 12u8, 0u8));
+            attributes.add_field(&AttributeField::new("NAME", FieldDataType::Text, 20u8, 0u8));
+            attributes.add_field(&AttributeField {
+                name: "NOTES".to_string(),
+                field_type: 'M',
+                field_length: 10,
+                decimal_count: 0,
+            });
+            for i in 0..3 {
+                let mut sfg = ShapefileGeometry::new(ShapeType::Point);
+                sfg.add_point(Point2D::new(i as f64, 0.0));
+                output.add_record(sfg);
+            }
+            // Integers too large for 32 bits are read as big integers rather than zeroed.
+            output.attributes.add_record(
+                vec![
+                    FieldData::BigInt(3_000_000_000),
+                    FieldData::Text("Zürich € café".to_string()),
+                    FieldData::Text(memo.clone()),
+                ],
+                false,
+            );
+            output.attributes.add_record(
+                vec![FieldData::Int(-5), FieldData::Null, FieldData::Text("short".to_string())],
+                false,
+            );
+            output.attributes.add_record(vec![FieldData::Null; 3], false);
+            output.write().unwrap();
+            assert_eq!(
+                fs::read_to_string(temp_file("code_page.cpg")).unwrap(),
+                code_page.cpg_name()
+            );
+
+            let expected = |i: usize| match (i, code_page) {
+                (0, CodePage::Latin1) => vec![
+                    FieldData::BigInt(3_000_000_000),
+                    FieldData::Text("Zürich ? café".to_string()),
+                    FieldData::Text(memo.clone()),
+                ],
+                _ => output.attributes.get_record(i),
+            };
+            let input = Shapefile::read(&file_name).unwrap();
+            assert_eq!(input.attributes.code_page, code_page);
+            for i in 0..3 {
+                assert_eq!(input.attributes.get_record(i), expected(i));
+            }
+
+            // Without the .cpg file, the code page is given by the language driver ID or
+            // is inferred from the text.
+            fs::remove_file(temp_file("code_page.cpg")).unwrap();
+            let input = Shapefile::read(&file_name).unwrap();
+            for i in 0..3 {
+                assert_eq!(input.attributes.get_record(i), expected(i));
+            }
+        }
+        for extension in ["shp", "shx", "dbf", "dbt"] {
+            fs::remove_file(temp_file(&format!("code_page.{}", extension))).unwrap();
+        }
+    }
+
+    #[test]
+    fn unparseable_values_are_read_as_null() {
+        let fields = vec![
+            AttributeField::new("COUNT", FieldDataType::Int, 8u8, 0u8),
+            AttributeField::new("SURVEYED", FieldDataType::Date, 8u8, 0u8),
+            AttributeField::new("VALID", FieldDataType::Bool, 1u8, 0u8),
+        ];
+        let records = [
+            vec![
+                FieldData::Int(3),
+                FieldData::Date(DateData {
+                    year: 2023,
+                    month: 12,
+                    day: 31,
+                }),
+                FieldData::Bool(true),
+            ],
+            vec![
+                FieldData::Text("abc".to_string()),
+                FieldData::Text("20231345".to_string()),
+                FieldData::Text("x".to_string()),
+            ],
+            vec![
+                FieldData::Text("********".to_string()),
+                FieldData::Text("00000000".to_string()),
+                FieldData::Text("?".to_string()),
+            ],
+        ];
+        let mut bytes = vec![];
+        let mut header = AttributeHeader {
+            num_records: records.len() as u32,
+            ..Default::default()
+        };
+        write_dbf_header(&mut bytes, &mut header, &fields, CodePage::Utf8).unwrap();
+        for (i, rec) in records.iter().enumerate() {
+            write_dbf_record(&mut bytes, &fields, rec, i == 1, CodePage::Utf8, None).unwrap();
+        }
+
+        let dbf_file = temp_file("unparsed.dbf");
+        let mut dbf = DbfHeader::read(&mut &bytes[..], bytes.len() as u64, Path::new(&dbf_file))
+            .unwrap();
+        let first_record = dbf.first_record as usize;
+        let chunks = bytes[first_record..].chunks(dbf.record_length);
+        assert_eq!(dbf.resolve_code_page(chunks.clone().map(Ok)).unwrap(), CodePage::Utf8);
+        assert_eq!(dbf.fields.len(), 3);
+        assert_eq!(dbf.fields[1].name, "SURVEYED");
+
+        let mut num_unparsed = vec![0; 3];
+        let parsed: Vec<(Vec<FieldData>, bool)> = chunks
+            .map(|record| dbf.parse_record(record, None, &mut num_unparsed).unwrap())
+            .collect();
+        assert_eq!(parsed[0], (records[0].clone(), false));
+        // Values that are not of the field's type are null, and are counted; asterisks
+        // (overflowed numbers), zero dates, and question marks are nulls.
+        assert_eq!(parsed[1], (vec![FieldData::Null; 3], true));
+        assert_eq!(parsed[2], (vec![FieldData::Null; 3], false));
+        assert_eq!(num_unparsed, vec![1, 1, 1]);
+    }
+}
@@ -226,6 +226,24 @@ impl ShapefileGeometry {
         BoundingBox::new(self.x_min, self.x_max, self.y_min, self.y_max)
     }
 
+    /// Returns the points of each part of the geometry, e.g. the rings of a polygon, in the
+    /// form taken by `whitebox_common::algorithms::polygon_boolean`.
+    pub fn get_parts(&self) -> Vec<Vec<Point2D>> {
+        let mut ret = Vec::with_capacity(self.parts.len());
+        for part in 0..self.parts.len() {
+            let start = self.parts[part] as usize;
+            let end = if part < self.parts.len() - 1 {
+                self.parts[part + 1] as usize
+            } else {
+                self.points.len()
+            };
+            if end > start {
+                ret.push(self.points[start..end].to_vec());
+            }
+        }
+        ret
+    }
+
     /// Returns the length of the ShapefileGeometry, including the header, in bytes.
     pub fn get_length(&self) -> i32 {
         let mut ret = 4; //12; // 12 bytes in the record number, content length, and shape type
@@ -254,16 +272,16 @@ impl ShapefileGeometry {
             }
             ShapeType::MultiPointZ => {
                 if self.has_m_data() {
-                    40 + 16 * self.num_points + 16 + 8 * self.num_points + 16 + 8 * self.num_points
+                    36 + 16 * self.num_points + 16 + 8 * self.num_points + 16 + 8 * self.num_points
                 } else {
-                    40 + 16 * self.num_points + 16 + 8 * self.num_points
+                    36 + 16 * self.num_points + 16 + 8 * self.num_points
                 }
                 // 68i32 + self.num_points * 32i32
             }
             ShapeType::PolyLineZ | ShapeType::PolygonZ => {
-                // 44 + 4*NumParts + 16*NumPoints + 16 + 8*NumPoints
+                // 44 + 4*NumParts + 16*NumPoints + 16 + 8*NumPoints, less the shape type
                 if self.has_m_data() {
-                    44i32
+                    40i32
                         + 4 * self.num_parts
                         + 16 * self.num_points
                         + 16
@@ -271,7 +289,7 @@ impl ShapefileGeometry {
                         + 16
                         + 8 * self.num_points
                 } else {
-                    44i32 + 4 * self.num_parts + 16 * self.num_points + 16 + 8 * self.num_points
+                    40i32 + 4 * self.num_parts + 16 * self.num_points + 16 + 8 * self.num_points
                 }
                 // 72i32 + self.num_parts * 4i32 + self.num_points * 32i32
             }
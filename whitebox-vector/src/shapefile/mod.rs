@@ -9,22 +9,31 @@ Notes: The logic behind working with the ESRI Shapefile format.
 */
 
 pub mod attributes;
+mod dbf;
 pub mod geometry;
+pub mod reader;
+mod spatial_index;
+pub mod writer;
 
 use self::attributes::*;
+use self::dbf::*;
 use self::geometry::*;
-use whitebox_common::structures::Point2D;
+use self::spatial_index::SpatialIndex;
+use crate::flatgeobuf::{is_flatgeobuf_file, read_flatgeobuf_within};
+use crate::vector_layer::VectorFormat;
+use whitebox_common::spatial_ref_system::CoordinateReferenceSystem;
+use whitebox_common::structures::{BoundingBox, Point2D};
 use whitebox_common::utils::{ByteOrderReader, Endianness};
 use byteorder::{BigEndian, LittleEndian, WriteBytesExt};
-use chrono::prelude::*;
 use std::f64;
 use std::fmt;
 use std::fs;
 use std::fs::File;
 use std::io::prelude::*;
-use std::io::{BufReader, BufWriter, Cursor, Error, ErrorKind};
+use std::io::{BufReader, BufWriter, Cursor, Error, ErrorKind, SeekFrom};
 use std::path::Path;
 use std::str;
+use std::sync::OnceLock;
 
 #[derive(Debug, Default, Clone)]
 pub struct ShapefileHeader {
@@ -79,23 +88,95 @@ m_max: {}",
     }
 }
 
-/// `Shapefile` is an in-memory ESRI Shapefile.
+impl ShapefileHeader {
+    /// Empties the extent, so that it can be extended by each of the records in turn.
+    pub(crate) fn reset_extent(&mut self) {
+        self.x_min = f64::INFINITY;
+        self.x_max = f64::NEG_INFINITY;
+        self.y_min = f64::INFINITY;
+        self.y_max = f64::NEG_INFINITY;
+        self.m_min = f64::INFINITY;
+        self.m_max = f64::NEG_INFINITY;
+        self.z_min = f64::INFINITY;
+        self.z_max = f64::NEG_INFINITY;
+    }
+
+    /// Extends the extent to include a record. Null records have no extent, and the ranges of
+    /// z values and measures are only extended for ShapeTypes that have them.
+    pub(crate) fn extend_extent(&mut self, sg: &ShapefileGeometry) {
+        if sg.shape_type == ShapeType::Null || self.shape_type == ShapeType::Null {
+            return;
+        }
+        let dimension = self.shape_type.dimension();
+        let (x_min, y_min, x_max, y_max, z_min, z_max, m_min, m_max) =
+            if self.shape_type.base_shape_type() == ShapeType::Point {
+                let (x, y) = (sg.points[0].x, sg.points[0].y);
+                let z = sg.z_array.first().copied().unwrap_or(f64::NAN);
+                let m = sg.m_array.first().copied().unwrap_or(f64::NAN);
+                (x, y, x, y, z, z, m, m)
+            } else {
+                (sg.x_min, sg.y_min, sg.x_max, sg.y_max, sg.z_min, sg.z_max, sg.m_min, sg.m_max)
+            };
+        self.x_min = self.x_min.min(x_min);
+        self.y_min = self.y_min.min(y_min);
+        self.x_max = self.x_max.max(x_max);
+        self.y_max = self.y_max.max(y_max);
+        if dimension == ShapeTypeDimension::Z {
+            self.z_min = self.z_min.min(z_min);
+            self.z_max = self.z_max.max(z_max);
+        }
+        if dimension != ShapeTypeDimension::XY && sg.has_m_data() {
+            self.m_min = self.m_min.min(m_min);
+            self.m_max = self.m_max.max(m_max);
+        }
+    }
+
+    /// Sets the ranges that no record has extended, e.g. the measure range of a file of Z
+    /// records without measures, to zero.
+    pub(crate) fn finish_extent(&mut self) {
+        if self.x_min > self.x_max || self.y_min > self.y_max {
+            self.x_min = 0f64;
+            self.x_max = 0f64;
+            self.y_min = 0f64;
+            self.y_max = 0f64;
+        }
+        if self.z_min > self.z_max {
+            self.z_min = 0f64;
+            self.z_max = 0f64;
+        }
+        if self.m_min > self.m_max {
+            self.m_min = 0f64;
+            self.m_max = 0f64;
+        }
+    }
+}
+
+/// `Shapefile` is an in-memory ESRI Shapefile. It is also the in-memory form of the layers
+/// of the other vector formats, which are read and written according to the extension of
+/// the file name (see `VectorFormat`): GeoJSON (.geojson or .json), GeoPackage (.gpkg,
+/// where the layer may be named after the file name, e.g. `roads.gpkg:highways`) and
+/// FlatGeobuf (.fgb).
 ///
 /// Examples:
 ///
-/// ```
+/// ```no_run
+/// use whitebox_vector::{AttributeField, FieldDataType, ShapeType, Shapefile};
+///
+/// # fn main() -> std::io::Result<()> {
 /// // Read a Shapefile from a file.
-/// let input = Shapefile::read(&input_file)?;
+/// let input = Shapefile::read("input.shp")?;
 ///
 /// // Create a new output Shapefile
-/// let mut output = Shapefile::initialize_using_file(&output_file, &input, ShapeType::PolyLine, true)?;
+/// let mut output =
+///     Shapefile::initialize_using_file("output.shp", &input, ShapeType::PolyLine, true)?;
 ///
 /// // add attributes
 /// let fid = AttributeField::new("FID", FieldDataType::Int, 2u8, 0u8);
 /// let val = AttributeField::new("Value", FieldDataType::Real, 12u8, 4u8);
 /// output.attributes.add_field(&fid);
 /// output.attributes.add_field(&val);
-///
+/// # Ok(())
+/// # }
 /// ```
 #[derive(Default, Clone)]
 pub struct Shapefile {
@@ -106,6 +187,8 @@ pub struct Shapefile {
     pub records: Vec<ShapefileGeometry>,
     pub attributes: ShapefileAttributes,
     pub projection: String,
+    /// The R-tree of the records' bounding boxes, which is built when it is first queried.
+    spatial_index: OnceLock<SpatialIndex>,
 }
 
 impl Shapefile {
@@ -115,10 +198,56 @@ impl Shapefile {
             file_mode: "r".to_string(),
             ..Default::default()
         };
-        sf.read_file()?;
+        VectorFormat::from_file_name(file_name)
+            .unwrap_or(VectorFormat::Shapefile)
+            .read(&mut sf)?;
         Ok(sf)
     }
 
+    /// Reads the records of a file whose bounding boxes overlap `bbox`, along with their
+    /// attributes. The spatial index of a FlatGeobuf file is used to read only those
+    /// records; files of other formats are read in full and the other records discarded.
+    pub fn read_within<'a>(file_name: &'a str, bbox: BoundingBox) -> Result<Shapefile, Error> {
+        if !is_flatgeobuf_file(file_name) {
+            let mut sf = Shapefile::read(file_name)?;
+            sf.retain_within(bbox);
+            return Ok(sf);
+        }
+        let mut sf = Shapefile {
+            file_name: file_name.to_string(),
+            file_mode: "r".to_string(),
+            ..Default::default()
+        };
+        read_flatgeobuf_within(&mut sf, bbox)?;
+        sf.calculate_extent();
+        Ok(sf)
+    }
+
+    /// Keeps only the records whose bounding boxes overlap `bbox`, and their attributes.
+    pub(crate) fn retain_within(&mut self, bbox: BoundingBox) {
+        let keep = self
+            .records
+            .iter()
+            .map(|r| {
+                r.shape_type != ShapeType::Null
+                    && !r.points.is_empty()
+                    && BoundingBox::from_points(&r.points).overlaps(bbox)
+            })
+            .collect::<Vec<bool>>();
+        let mut attributes = ShapefileAttributes::default();
+        attributes.add_fields(self.attributes.get_fields());
+        let num_atts = self.attributes.header.num_records as usize;
+        for i in (0..keep.len().min(num_atts)).filter(|&i| keep[i]) {
+            attributes.add_record(self.attributes.get_record(i), false);
+        }
+        self.attributes = attributes;
+        let mut keep = keep.into_iter();
+        self.records.retain(|_| keep.next().unwrap_or(false));
+        self.num_records = self.records.len();
+        self.clear_spatial_index();
+        self.calculate_extent();
+    }
+
     pub fn new<'a>(file_name: &'a str, file_type: ShapeType) -> Result<Shapefile, Error> {
         let new_file_name = if file_name.contains(".") {
             file_name.to_string()
@@ -179,6 +308,12 @@ impl Shapefile {
     }
 
     /// Returns the filename, in shortened form (e.g. file.shp).
+    /// Returns the coordinate reference system of the shapefile, read from the WKT of its
+    /// .prj file. None is returned if there is no .prj file or it cannot be read.
+    pub fn get_coordinate_reference_system(&self) -> Option<CoordinateReferenceSystem> {
+        CoordinateReferenceSystem::from_wkt_or_epsg(&self.projection, 0)
+    }
+
     pub fn get_short_filename(&self) -> String {
         let path = Path::new(&self.file_name);
         let file_name = path.file_stem().unwrap();
@@ -199,9 +334,11 @@ impl Shapefile {
         if self.file_mode == "r" {
             panic!("The file was opened in read-only mode.");
         }
-        if geometry.shape_type == self.header.shape_type {
+        // Null records may appear in a Shapefile of any ShapeType.
+        if geometry.shape_type == self.header.shape_type || geometry.shape_type == ShapeType::Null {
             self.records.push(geometry);
             self.num_records += 1;
+            self.clear_spatial_index();
         } else {
             panic!("Attempt to add a ShapefileGeometry record of the wrong ShapeType.");
         }
@@ -220,6 +357,7 @@ impl Shapefile {
             sfg.add_point(Point2D { x: x, y: y });
             self.records.push(sfg);
             self.num_records += 1;
+            self.clear_spatial_index();
         } else {
             panic!("Attempt to add a ShapefileGeometry record of the wrong ShapeType.");
         }
@@ -229,7 +367,7 @@ impl Shapefile {
     //     &mut self.attributes
     // }
 
-    fn read_file(&mut self) -> Result<(), Error> {
+    pub(crate) fn read_file(&mut self) -> Result<(), Error> {
         ///////////////////////////////
         // First read the geometries //
         ///////////////////////////////
@@ -248,300 +386,17 @@ impl Shapefile {
         // such, we will need to switch the endianness frequently.
         let mut bor =
             ByteOrderReader::<Cursor<Vec<u8>>>::new(Cursor::new(buffer), Endianness::BigEndian);
-        bor.seek(0);
-        self.header.file_code = bor.read_i32()?;
-        bor.seek(24);
-        self.header.file_length = bor.read_i32()?;
-
-        // the rest of the header is in LittleEndian format
-        bor.set_byte_order(Endianness::LittleEndian);
-        self.header.version = bor.read_i32()?;
-        let st = bor.read_i32()?;
-        self.header.shape_type = ShapeType::from_int(st); //bor.read_i32());
-        
-        // bounding box
-        self.header.x_min = bor.read_f64()?;
-        self.header.y_min = bor.read_f64()?;
-        self.header.x_max = bor.read_f64()?;
-        self.header.y_max = bor.read_f64()?;
-        self.header.z_min = bor.read_f64()?;
-        self.header.z_max = bor.read_f64()?;
-        self.header.m_min = bor.read_f64()?;
-        self.header.m_max = bor.read_f64()?;
+        self.header = read_header(&mut bor)?;
 
         // Read the data
         bor.set_byte_order(Endianness::LittleEndian);
         let mut content_length: i32;
-        let mut length_without_m: i32;
-        let mut contains_m: bool;
-        let mut shape_type: ShapeType;
         while bor.pos() < file_size {
             bor.set_byte_order(Endianness::BigEndian);
             bor.inc_pos(4); // We don't really need the record number
             content_length = bor.read_i32()? * 2; // in bytes
             bor.set_byte_order(Endianness::LittleEndian);
-            shape_type = ShapeType::from_int(bor.read_i32()?);
-
-            match shape_type {
-                ShapeType::Null => {
-                    let sfg = ShapefileGeometry {
-                        shape_type: ShapeType::Null,
-                        ..Default::default()
-                    };
-                    self.records.push(sfg);
-                }
-
-                ShapeType::Point => {
-                    let sfg = ShapefileGeometry {
-                        shape_type: ShapeType::Point,
-                        num_points: 1i32,
-                        points: vec![Point2D {
-                            x: bor.read_f64()?,
-                            y: bor.read_f64()?,
-                        }],
-                        ..Default::default()
-                    };
-                    self.records.push(sfg);
-                }
-
-                ShapeType::PolyLine | ShapeType::Polygon => {
-                    let mut sfg = ShapefileGeometry {
-                        shape_type: shape_type,
-                        x_min: bor.read_f64()?,
-                        y_min: bor.read_f64()?,
-                        x_max: bor.read_f64()?,
-                        y_max: bor.read_f64()?,
-                        num_parts: bor.read_i32()?,
-                        num_points: bor.read_i32()?,
-                        ..Default::default()
-                    };
-
-                    for _ in 0..sfg.num_parts {
-                        sfg.parts.push(bor.read_i32()?);
-                    }
-
-                    for _ in 0..sfg.num_points {
-                        sfg.points.push(Point2D {
-                            x: bor.read_f64()?,
-                            y: bor.read_f64()?,
-                        });
-                    }
-
-                    self.records.push(sfg);
-                }
-
-                ShapeType::MultiPoint => {
-                    let mut sfg = ShapefileGeometry {
-                        shape_type: ShapeType::MultiPoint,
-                        x_min: bor.read_f64()?,
-                        y_min: bor.read_f64()?,
-                        x_max: bor.read_f64()?,
-                        y_max: bor.read_f64()?,
-                        num_points: bor.read_i32()?,
-                        ..Default::default()
-                    };
-
-                    for _ in 0..sfg.num_points {
-                        sfg.points.push(Point2D {
-                            x: bor.read_f64()?,
-                            y: bor.read_f64()?,
-                        });
-                    }
-
-                    self.records.push(sfg);
-                }
-
-                ShapeType::PointZ => {
-                    let sfg = if content_length == 36 {
-                        // both z and m are included
-                        ShapefileGeometry {
-                            shape_type: ShapeType::PointZ,
-                            num_points: 1i32,
-                            points: vec![Point2D {
-                                x: bor.read_f64()?,
-                                y: bor.read_f64()?,
-                            }],
-                            z_array: vec![bor.read_f64()?],
-                            m_array: vec![bor.read_f64()?],
-                            ..Default::default()
-                        }
-                    } else {
-                        // only z is included
-                        ShapefileGeometry {
-                            shape_type: ShapeType::PointZ,
-                            num_points: 1i32,
-                            points: vec![Point2D {
-                                x: bor.read_f64()?,
-                                y: bor.read_f64()?,
-                            }],
-                            z_array: vec![bor.read_f64()?],
-                            ..Default::default()
-                        }
-                    };
-                    self.records.push(sfg);
-                }
-
-                ShapeType::PolyLineZ | ShapeType::PolygonZ => {
-                    let mut sfg = ShapefileGeometry {
-                        shape_type: shape_type,
-                        x_min: bor.read_f64()?,
-                        y_min: bor.read_f64()?,
-                        x_max: bor.read_f64()?,
-                        y_max: bor.read_f64()?,
-                        num_parts: bor.read_i32()?,
-                        num_points: bor.read_i32()?,
-                        ..Default::default()
-                    };
-                    length_without_m =
-                        44 + 4 * sfg.num_parts + 16 * sfg.num_points + 16 + 8 * sfg.num_points;
-                    contains_m = if content_length == length_without_m {
-                        false
-                    } else {
-                        true
-                    };
-
-                    for _ in 0..sfg.num_parts {
-                        sfg.parts.push(bor.read_i32()?);
-                    }
-
-                    for _ in 0..sfg.num_points {
-                        sfg.points.push(Point2D {
-                            x: bor.read_f64()?,
-                            y: bor.read_f64()?,
-                        });
-                    }
-
-                    sfg.z_min = bor.read_f64()?;
-                    sfg.z_max = bor.read_f64()?;
-                    for _ in 0..sfg.num_points {
-                        sfg.z_array.push(bor.read_f64()?);
-                    }
-
-                    if contains_m {
-                        sfg.m_min = bor.read_f64()?;
-                        sfg.m_max = bor.read_f64()?;
-                        for _ in 0..sfg.num_points {
-                            sfg.m_array.push(bor.read_f64()?);
-                        }
-                    }
-
-                    self.records.push(sfg);
-                }
-
-                ShapeType::MultiPointZ => {
-                    let mut sfg = ShapefileGeometry {
-                        shape_type: ShapeType::MultiPointZ,
-                        x_min: bor.read_f64()?,
-                        y_min: bor.read_f64()?,
-                        x_max: bor.read_f64()?,
-                        y_max: bor.read_f64()?,
-                        num_points: bor.read_i32()?,
-                        ..Default::default()
-                    };
-
-                    length_without_m = 40 + 16 * sfg.num_points + 16 + 8 * sfg.num_points;
-                    contains_m = if content_length == length_without_m {
-                        false
-                    } else {
-                        true
-                    };
-
-                    for _ in 0..sfg.num_points {
-                        sfg.points.push(Point2D {
-                            x: bor.read_f64()?,
-                            y: bor.read_f64()?,
-                        });
-                    }
-
-                    sfg.z_min = bor.read_f64()?;
-                    sfg.z_max = bor.read_f64()?;
-                    for _ in 0..sfg.num_points {
-                        sfg.z_array.push(bor.read_f64()?);
-                    }
-                    if contains_m {
-                        sfg.m_min = bor.read_f64()?;
-                        sfg.m_max = bor.read_f64()?;
-                        for _ in 0..sfg.num_points {
-                            sfg.m_array.push(bor.read_f64()?);
-                        }
-                    }
-
-                    self.records.push(sfg);
-                }
-
-                ShapeType::PointM => {
-                    let sfg = ShapefileGeometry {
-                        shape_type: ShapeType::PointM,
-                        num_points: 1i32,
-                        points: vec![Point2D {
-                            x: bor.read_f64()?,
-                            y: bor.read_f64()?,
-                        }],
-                        m_array: vec![bor.read_f64()?],
-                        ..Default::default()
-                    };
-                    self.records.push(sfg);
-                }
-
-                ShapeType::PolyLineM | ShapeType::PolygonM => {
-                    let mut sfg = ShapefileGeometry {
-                        shape_type: shape_type,
-                        x_min: bor.read_f64()?,
-                        y_min: bor.read_f64()?,
-                        x_max: bor.read_f64()?,
-                        y_max: bor.read_f64()?,
-                        num_parts: bor.read_i32()?,
-                        num_points: bor.read_i32()?,
-                        ..Default::default()
-                    };
-
-                    for _ in 0..sfg.num_parts {
-                        sfg.parts.push(bor.read_i32()?);
-                    }
-
-                    for _ in 0..sfg.num_points {
-                        sfg.points.push(Point2D {
-                            x: bor.read_f64()?,
-                            y: bor.read_f64()?,
-                        });
-                    }
-
-                    sfg.m_min = bor.read_f64()?;
-                    sfg.m_max = bor.read_f64()?;
-                    for _ in 0..sfg.num_points {
-                        sfg.m_array.push(bor.read_f64()?);
-                    }
-
-                    self.records.push(sfg);
-                }
-
-                ShapeType::MultiPointM => {
-                    let mut sfg = ShapefileGeometry {
-                        shape_type: ShapeType::MultiPointM,
-                        x_min: bor.read_f64()?,
-                        y_min: bor.read_f64()?,
-                        x_max: bor.read_f64()?,
-                        y_max: bor.read_f64()?,
-                        num_points: bor.read_i32()?,
-                        ..Default::default()
-                    };
-
-                    for _ in 0..sfg.num_points {
-                        sfg.points.push(Point2D {
-                            x: bor.read_f64()?,
-                            y: bor.read_f64()?,
-                        });
-                    }
-
-                    sfg.m_min = bor.read_f64()?;
-                    sfg.m_max = bor.read_f64()?;
-                    for _ in 0..sfg.num_points {
-                        sfg.m_array.push(bor.read_f64()?);
-                    }
-
-                    self.records.push(sfg);
-                }
-            }
+            self.records.push(read_geometry(&mut bor, content_length)?);
         }
 
         self.num_records = self.records.len();
@@ -549,164 +404,38 @@ impl Shapefile {
         //////////////////////////////
         // Read the projection file //
         //////////////////////////////
-        // let prj_file = self.file_name.replace(".shp", ".prj");
-        let prj_file = Path::new(&self.file_name)
-            .with_extension("prj")
-            .into_os_string()
-            .into_string()
-            .unwrap();
-        match File::open(prj_file) {
-            Ok(f) => {
-                let f = BufReader::new(f);
-                for line in f.lines() {
-                    let line_unwrapped = line.unwrap();
-                    self.projection.push_str(&format!("{}\n", line_unwrapped));
-                }
-            }
-            Err(_) => println!("Warning: Projection file not located."),
-        }
+        self.projection = read_projection(&self.file_name);
 
         ///////////////////////////////
         // Read the attributes table //
         ///////////////////////////////
         // read the header
-        // let dbf_file = self.file_name.replace(".shp", ".dbf");
-        let dbf_file = Path::new(&self.file_name)
-            .with_extension("dbf")
-            .into_os_string()
-            .into_string()
-            .unwrap();
-        let mut f = File::open(dbf_file.clone()).unwrap();
-        let metadata = fs::metadata(dbf_file.clone()).unwrap();
-        let file_size: usize = metadata.len() as usize;
-        let mut buffer = vec![0; file_size];
-
-        // read the file's bytes into a buffer
-        f.read(&mut buffer).unwrap();
-        let mut bor =
-            ByteOrderReader::<Cursor<Vec<u8>>>::new(Cursor::new(buffer), Endianness::LittleEndian);
-
-        self.attributes.header.version = bor.read_u8()?;
-        self.attributes.header.year = 1900u32 + bor.read_u8()? as u32;
-        self.attributes.header.month = bor.read_u8()?;
-        self.attributes.header.day = bor.read_u8()?;
-        self.attributes.header.num_records = bor.read_u32()?;
-        self.attributes.header.bytes_in_header = bor.read_u16()?;
-        self.attributes.header.bytes_in_record = bor.read_u16()?;
-        // reserved bytes
-        bor.inc_pos(2);
-        self.attributes.header.incomplete_tansaction = bor.read_u8()?;
-        self.attributes.header.encryption_flag = bor.read_u8()?;
-        // skip free record thread for LAN only
-        bor.inc_pos(4);
-        // reserved for multi-user dBASE in dBASE III+
-        bor.inc_pos(8);
-        self.attributes.header.mdx_flag = bor.read_u8()?;
-        self.attributes.header.language_driver_id = bor.read_u8()?;
-        // reserved bytes
-        bor.inc_pos(2);
-
-        // read the field data
+        let dbf_file = Path::new(&self.file_name).with_extension("dbf");
+        let f = File::open(&dbf_file)?;
+        let file_size = f.metadata()?.len();
+        let mut reader = BufReader::new(f);
+        let mut dbf = DbfHeader::read(&mut reader, file_size, &dbf_file)?;
+
+        // read the records
+        reader.seek(SeekFrom::Start(dbf.first_record))?;
+        let mut records = vec![0u8; dbf.header.num_records as usize * dbf.record_length];
+        reader.read_exact(&mut records)?;
+        self.attributes.code_page = dbf.resolve_code_page(records.chunks(dbf.record_length).map(Ok))?;
         self.attributes.fields = vec![];
-        let mut flag = true;
-        while flag {
-            let name = bor.read_utf8(11).replace(char::from(0), "");
-            let field_type = char::from(bor.read_u8()?);
-            bor.inc_pos(4);
-            let field_length = bor.read_u8()?;
-            let decimal_count = bor.read_u8()?;
-            // Skip reserved bytes multi-user dBASE.
-            bor.inc_pos(2);
-            let _work_area_id = bor.read_u8()?;
-            // Skip reserved bytes multi-user dBASE.
-            bor.inc_pos(2);
-            let _set_field_flag = bor.read_u8()?;
-            // Skip reserved bytes.
-            bor.inc_pos(7);
-            let _index_field_flag = bor.read_u8()?;
-
-            let field_data = AttributeField {
-                name: name.clone(),
-                field_type: field_type,
-                field_length: field_length,
-                decimal_count: decimal_count,
-            };
-            // let field_data = AttributeField::new(
-            //     &name,
-            //     field_type,
-            //     field_length,
-            //     decimal_count,
-            //     work_area_id,
-            //     set_field_flag,
-            //     index_field_flag,
-            // );
-
-            self.attributes.add_field(&field_data); //.fields.push(field_data);
-
-            // Checks for end of field descriptor array (0x0d). Valid .dbf files
-            // will have this flag.
-            if bor.peek_u8()? == 0x0d {
-                flag = false;
-                // break;
-            }
-        }
+        self.attributes.add_fields(&dbf.fields);
 
-        // self.attributes.get_field_hashmap();
-
-        self.attributes.header.num_fields = self.attributes.fields.len() as u32;
-
-        bor.inc_pos(1);
-
-        let mut d: bool;
-        let mut str_rep: String;
-        for _ in 0..self.attributes.header.num_records {
-            d = bor.read_u8()? as u32 == 0x2A;
-            let mut r: Vec<FieldData> = vec![];
-            for j in 0..self.attributes.header.num_fields {
-                str_rep = bor
-                    .read_utf8(self.attributes.fields[j as usize].field_length as usize)
-                    .replace(char::from(0), "")
-                    .replace("*", "")
-                    .trim()
-                    .to_string();
-                if str_rep.replace(" ", "").replace("?", "").is_empty() {
-                    r.push(FieldData::Null);
-                } else {
-                    match self.attributes.fields[j as usize].field_type {
-                        'N' | 'F' | 'I' | 'O' => {
-                            if self.attributes.fields[j as usize].decimal_count == 0 {
-                                r.push(FieldData::Int(str_rep.parse::<i32>().unwrap_or(0)));
-                            } else {
-                                r.push(FieldData::Real(str_rep.parse::<f64>().unwrap_or(0f64)));
-                            }
-                        }
-                        'D' => {
-                            if str_rep.len() == 8 {
-                                r.push(FieldData::Date(DateData {
-                                    year: str_rep[0..4].parse::<u16>().unwrap_or(0),
-                                    month: str_rep[4..6].parse::<u8>().unwrap_or(0),
-                                    day: str_rep[6..8].parse::<u8>().unwrap_or(0),
-                                }));
-                            } else {
-                                r.push(FieldData::Null);
-                            }
-                        }
-                        'L' => {
-                            if str_rep.to_lowercase().contains("t") {
-                                r.push(FieldData::Bool(true));
-                            } else {
-                                r.push(FieldData::Bool(false));
-                            }
-                        }
-                        _ => {
-                            // treat it like a string
-                            r.push(FieldData::Text(str_rep.clone()));
-                        }
-                    }
-                }
-            }
+        let mut memo_file = if dbf.has_memo_fields() {
+            MemoFile::open(&dbf_file, dbf.header.version)
+        } else {
+            None
+        };
+        let mut num_unparsed = vec![0usize; dbf.fields.len()];
+        for record in records.chunks(dbf.record_length) {
+            let (r, d) = dbf.parse_record(record, memo_file.as_mut(), &mut num_unparsed)?;
             self.attributes.add_record(r, d);
         }
+        dbf.warn_unparsed(&num_unparsed);
+        self.attributes.header = dbf.header;
 
         Ok(())
     }
@@ -720,6 +449,13 @@ impl Shapefile {
         }
 
         self.num_records = self.records.len(); // make sure they are the same.
+        VectorFormat::from_file_name(&self.file_name)
+            .unwrap_or(VectorFormat::Shapefile)
+            .write(self)
+    }
+
+    /// Writes the .shp, .shx, .dbf and, if there is a projection, .prj files.
+    pub(crate) fn write_file(&mut self) -> Result<(), Error> {
         if self.num_records == 0 {
             return Err(Error::new(
                 ErrorKind::Other,
@@ -735,314 +471,19 @@ impl Shapefile {
         let f = File::create(&self.file_name)?;
         let mut writer = BufWriter::new(f);
 
-        // magic number
-        writer.write_i32::<BigEndian>(9994i32)?;
-
-        // unused header bytes
-        for _ in 0..5 {
-            writer.write_i32::<BigEndian>(0i32)?;
-        }
-
         // file size
         let mut size = 100i32; // initialized to the size of the file header
         for i in 0..self.num_records {
             size += 8 + self.records[i].get_length();
         }
         let file_length = size / 2i32; // in 16-bit words
-        writer.write_i32::<BigEndian>(file_length)?;
-
-        // version
-        writer.write_i32::<LittleEndian>(1000i32)?;
-
-        // shape type
-        writer.write_i32::<LittleEndian>(self.header.shape_type.to_int())?;
-
-        // extent
         self.calculate_extent();
-        writer.write_f64::<LittleEndian>(self.header.x_min)?;
-        writer.write_f64::<LittleEndian>(self.header.y_min)?;
-        writer.write_f64::<LittleEndian>(self.header.x_max)?;
-        writer.write_f64::<LittleEndian>(self.header.y_max)?;
-        writer.write_f64::<LittleEndian>(self.header.z_min)?;
-        writer.write_f64::<LittleEndian>(self.header.z_max)?;
-        writer.write_f64::<LittleEndian>(self.header.m_min)?;
-        writer.write_f64::<LittleEndian>(self.header.m_max)?;
+        write_header(&mut writer, &self.header, file_length)?;
 
         // Write the geometries
-        match self.header.shape_type {
-            ShapeType::Null => {
-                for i in 0..self.num_records {
-                    writer.write_i32::<BigEndian>(i as i32 + 1i32)?; // Record number
-                    writer.write_i32::<BigEndian>(self.records[i].get_length() / 2)?; // Content length in 16-bit words
-                    writer.write_i32::<LittleEndian>(0i32)?; // Shape type
-                }
-            }
-            ShapeType::Point => {
-                for i in 0..self.num_records {
-                    writer.write_i32::<BigEndian>(i as i32 + 1i32)?; // Record number
-                    writer.write_i32::<BigEndian>(self.records[i].get_length() / 2)?; // Content length in 16-bit words
-                    writer.write_i32::<LittleEndian>(ShapeType::to_int(
-                        &self.records[i].shape_type,
-                    ))?; // Shape type
-
-                    if self.records[i].shape_type != ShapeType::Null {
-                        writer.write_f64::<LittleEndian>(self.records[i].points[0].x)?;
-                        writer.write_f64::<LittleEndian>(self.records[i].points[0].y)?;
-                    }
-                }
-            }
-
-            ShapeType::PolyLine | ShapeType::Polygon => {
-                for i in 0..self.num_records {
-                    writer.write_i32::<BigEndian>(i as i32 + 1i32)?; // Record number
-                    writer.write_i32::<BigEndian>(self.records[i].get_length() / 2)?; // // Content length in 16-bit words
-                    writer.write_i32::<LittleEndian>(ShapeType::to_int(
-                        &self.records[i].shape_type,
-                    ))?; // Shape type
-
-                    if self.records[i].shape_type != ShapeType::Null {
-                        // extent
-                        writer.write_f64::<LittleEndian>(self.records[i].x_min)?;
-                        writer.write_f64::<LittleEndian>(self.records[i].y_min)?;
-                        writer.write_f64::<LittleEndian>(self.records[i].x_max)?;
-                        writer.write_f64::<LittleEndian>(self.records[i].y_max)?;
-
-                        writer.write_i32::<LittleEndian>(self.records[i].num_parts)?; // Num parts
-                        writer.write_i32::<LittleEndian>(self.records[i].num_points)?; // Num points
-
-                        // parts
-                        for part in &self.records[i].parts {
-                            writer.write_i32::<LittleEndian>(*part)?;
-                        }
-
-                        // points
-                        for pt in &self.records[i].points {
-                            writer.write_f64::<LittleEndian>(pt.x)?;
-                            writer.write_f64::<LittleEndian>(pt.y)?;
-                        }
-                    }
-                }
-            }
-
-            ShapeType::MultiPoint => {
-                for i in 0..self.num_records {
-                    writer.write_i32::<BigEndian>(i as i32 + 1i32)?; // Record number
-                    writer.write_i32::<BigEndian>(self.records[i].get_length() / 2)?; // Content length in 16-bit words
-                    writer.write_i32::<LittleEndian>(ShapeType::to_int(
-                        &self.records[i].shape_type,
-                    ))?; // Shape type
-
-                    if self.records[i].shape_type != ShapeType::Null {
-                        // extent
-                        writer.write_f64::<LittleEndian>(self.records[i].x_min)?;
-                        writer.write_f64::<LittleEndian>(self.records[i].y_min)?;
-                        writer.write_f64::<LittleEndian>(self.records[i].x_max)?;
-                        writer.write_f64::<LittleEndian>(self.records[i].y_max)?;
-
-                        writer.write_i32::<LittleEndian>(self.records[i].num_points)?; // Num points
-
-                        // points
-                        for pt in &self.records[i].points {
-                            writer.write_f64::<LittleEndian>(pt.x)?;
-                            writer.write_f64::<LittleEndian>(pt.y)?;
-                        }
-                    }
-                }
-            }
-
-            ShapeType::PointZ => {
-                for i in 0..self.num_records {
-                    writer.write_i32::<BigEndian>(i as i32 + 1i32)?; // Record number
-                    writer.write_i32::<BigEndian>(self.records[i].get_length() / 2)?; // Content length in 16-bit words
-                    writer.write_i32::<LittleEndian>(ShapeType::to_int(
-                        &self.records[i].shape_type,
-                    ))?; // Shape type
-
-                    if self.records[i].shape_type != ShapeType::Null {
-                        writer.write_f64::<LittleEndian>(self.records[i].points[0].x)?;
-                        writer.write_f64::<LittleEndian>(self.records[i].points[0].y)?;
-                        writer.write_f64::<LittleEndian>(self.records[i].z_array[0])?;
-                        writer.write_f64::<LittleEndian>(self.records[i].m_array[0])?;
-                    }
-                }
-            }
-
-            ShapeType::PolyLineZ | ShapeType::PolygonZ => {
-                for i in 0..self.num_records {
-                    writer.write_i32::<BigEndian>(i as i32 + 1i32)?; // Record number
-                    writer.write_i32::<BigEndian>(self.records[i].get_length() / 2)?; // Content length in 16-bit words
-                    writer.write_i32::<LittleEndian>(ShapeType::to_int(
-                        &self.records[i].shape_type,
-                    ))?; // Shape type
-
-                    if self.records[i].shape_type != ShapeType::Null {
-                        // extent
-                        writer.write_f64::<LittleEndian>(self.records[i].x_min)?;
-                        writer.write_f64::<LittleEndian>(self.records[i].y_min)?;
-                        writer.write_f64::<LittleEndian>(self.records[i].x_max)?;
-                        writer.write_f64::<LittleEndian>(self.records[i].y_max)?;
-
-                        writer.write_i32::<LittleEndian>(self.records[i].num_parts)?; // Num parts
-                        writer.write_i32::<LittleEndian>(self.records[i].num_points)?; // Num points
-
-                        // parts
-                        for part in &self.records[i].parts {
-                            writer.write_i32::<LittleEndian>(*part)?;
-                        }
-
-                        // points
-                        for pt in &self.records[i].points {
-                            writer.write_f64::<LittleEndian>(pt.x)?;
-                            writer.write_f64::<LittleEndian>(pt.y)?;
-                        }
-
-                        // z data
-                        writer.write_f64::<LittleEndian>(self.records[i].z_min)?;
-                        writer.write_f64::<LittleEndian>(self.records[i].z_max)?;
-                        for z in &self.records[i].z_array {
-                            writer.write_f64::<LittleEndian>(*z)?;
-                        }
-
-                        // measure data
-                        if self.records[i].has_m_data() {
-                            writer.write_f64::<LittleEndian>(self.records[i].m_min)?;
-                            writer.write_f64::<LittleEndian>(self.records[i].m_max)?;
-                            for m in &self.records[i].m_array {
-                                writer.write_f64::<LittleEndian>(*m)?;
-                            }
-                        }
-                    }
-                }
-            }
-
-            ShapeType::MultiPointZ => {
-                for i in 0..self.num_records {
-                    writer.write_i32::<BigEndian>(i as i32 + 1i32)?; // Record number
-                    writer.write_i32::<BigEndian>(self.records[i].get_length() / 2)?; // Content length in 16-bit words
-                    writer.write_i32::<LittleEndian>(ShapeType::to_int(
-                        &self.records[i].shape_type,
-                    ))?; // Shape type
-
-                    if self.records[i].shape_type != ShapeType::Null {
-                        // extent
-                        writer.write_f64::<LittleEndian>(self.records[i].x_min)?;
-                        writer.write_f64::<LittleEndian>(self.records[i].y_min)?;
-                        writer.write_f64::<LittleEndian>(self.records[i].x_max)?;
-                        writer.write_f64::<LittleEndian>(self.records[i].y_max)?;
-
-                        writer.write_i32::<LittleEndian>(self.records[i].num_points)?; // Num points
-
-                        // points
-                        for pt in &self.records[i].points {
-                            writer.write_f64::<LittleEndian>(pt.x)?;
-                            writer.write_f64::<LittleEndian>(pt.y)?;
-                        }
-
-                        // z data
-                        writer.write_f64::<LittleEndian>(self.records[i].z_min)?;
-                        writer.write_f64::<LittleEndian>(self.records[i].z_max)?;
-                        for z in &self.records[i].z_array {
-                            writer.write_f64::<LittleEndian>(*z)?;
-                        }
-
-                        // measure data
-                        if self.records[i].has_m_data() {
-                            writer.write_f64::<LittleEndian>(self.records[i].m_min)?;
-                            writer.write_f64::<LittleEndian>(self.records[i].m_max)?;
-                            for m in &self.records[i].m_array {
-                                writer.write_f64::<LittleEndian>(*m)?;
-                            }
-                        }
-                    }
-                }
-            }
-
-            ShapeType::PointM => {
-                for i in 0..self.num_records {
-                    writer.write_i32::<BigEndian>(i as i32 + 1i32)?; // Record number
-                    writer.write_i32::<BigEndian>(self.records[i].get_length() / 2)?; // Content length in 16-bit words
-                    writer.write_i32::<LittleEndian>(ShapeType::to_int(
-                        &self.records[i].shape_type,
-                    ))?; // Shape type
-
-                    if self.records[i].shape_type != ShapeType::Null {
-                        writer.write_f64::<LittleEndian>(self.records[i].points[0].x)?;
-                        writer.write_f64::<LittleEndian>(self.records[i].points[0].y)?;
-                        writer.write_f64::<LittleEndian>(self.records[i].m_array[0])?;
-                    }
-                }
-            }
-
-            ShapeType::PolyLineM | ShapeType::PolygonM => {
-                for i in 0..self.num_records {
-                    writer.write_i32::<BigEndian>(i as i32 + 1i32)?; // Record number
-                    writer.write_i32::<BigEndian>(self.records[i].get_length() / 2)?; // Content length in 16-bit words
-                    writer.write_i32::<LittleEndian>(ShapeType::to_int(
-                        &self.records[i].shape_type,
-                    ))?; // Shape type
-
-                    if self.records[i].shape_type != ShapeType::Null {
-                        // extent
-                        writer.write_f64::<LittleEndian>(self.records[i].x_min)?;
-                        writer.write_f64::<LittleEndian>(self.records[i].y_min)?;
-                        writer.write_f64::<LittleEndian>(self.records[i].x_max)?;
-                        writer.write_f64::<LittleEndian>(self.records[i].y_max)?;
-
-                        writer.write_i32::<LittleEndian>(self.records[i].num_parts)?; // Num parts
-                        writer.write_i32::<LittleEndian>(self.records[i].num_points)?; // Num points
-
-                        // parts
-                        for part in &self.records[i].parts {
-                            writer.write_i32::<LittleEndian>(*part)?;
-                        }
-
-                        // points
-                        for pt in &self.records[i].points {
-                            writer.write_f64::<LittleEndian>(pt.x)?;
-                            writer.write_f64::<LittleEndian>(pt.y)?;
-                        }
-
-                        // measure data
-                        writer.write_f64::<LittleEndian>(self.records[i].m_min)?;
-                        writer.write_f64::<LittleEndian>(self.records[i].m_max)?;
-                        for m in &self.records[i].m_array {
-                            writer.write_f64::<LittleEndian>(*m)?;
-                        }
-                    }
-                }
-            }
-
-            ShapeType::MultiPointM => {
-                for i in 0..self.num_records {
-                    writer.write_i32::<BigEndian>(i as i32 + 1i32)?; // Record number
-                    writer.write_i32::<BigEndian>(self.records[i].get_length() / 2)?; // Content length in 16-bit words
-                    writer.write_i32::<LittleEndian>(ShapeType::to_int(
-                        &self.records[i].shape_type,
-                    ))?; // Shape type
-                    if self.records[i].shape_type != ShapeType::Null {
-                        // extent
-                        writer.write_f64::<LittleEndian>(self.records[i].x_min)?;
-                        writer.write_f64::<LittleEndian>(self.records[i].y_min)?;
-                        writer.write_f64::<LittleEndian>(self.records[i].x_max)?;
-                        writer.write_f64::<LittleEndian>(self.records[i].y_max)?;
-
-                        writer.write_i32::<LittleEndian>(self.records[i].num_points)?; // Num points
-
-                        // points
-                        for pt in &self.records[i].points {
-                            writer.write_f64::<LittleEndian>(pt.x)?;
-                            writer.write_f64::<LittleEndian>(pt.y)?;
-                        }
-
-                        // measure data
-                        writer.write_f64::<LittleEndian>(self.records[i].m_min)?;
-                        writer.write_f64::<LittleEndian>(self.records[i].m_max)?;
-                        for m in &self.records[i].m_array {
-                            writer.write_f64::<LittleEndian>(*m)?;
-                        }
-                    }
-                }
-            }
+        let shape_type = self.header.shape_type;
+        for (i, record) in self.records.iter().enumerate() {
+            write_geometry(&mut writer, record, i as i32 + 1i32, shape_type)?;
         }
 
         /////////////////////////////////
@@ -1059,33 +500,8 @@ impl Shapefile {
         let f = File::create(&index_file)?;
         let mut writer = BufWriter::new(f);
 
-        // magic number
-        writer.write_i32::<BigEndian>(9994i32)?;
-
-        // unused header bytes
-        for _ in 0..5 {
-            writer.write_i32::<BigEndian>(0i32)?;
-        }
-
         let file_length = (100 + 8 * self.num_records) as i32 / 2i32; // in 16-bit words
-        writer.write_i32::<BigEndian>(file_length)?;
-
-        // version
-        writer.write_i32::<LittleEndian>(1000i32)?;
-
-        // shape type
-        writer.write_i32::<LittleEndian>(self.header.shape_type.to_int())?;
-
-        // extent
-        // self.calculate_extent();
-        writer.write_f64::<LittleEndian>(self.header.x_min)?;
-        writer.write_f64::<LittleEndian>(self.header.y_min)?;
-        writer.write_f64::<LittleEndian>(self.header.x_max)?;
-        writer.write_f64::<LittleEndian>(self.header.y_max)?;
-        writer.write_f64::<LittleEndian>(self.header.z_min)?;
-        writer.write_f64::<LittleEndian>(self.header.z_max)?;
-        writer.write_f64::<LittleEndian>(self.header.m_min)?;
-        writer.write_f64::<LittleEndian>(self.header.m_max)?;
+        write_header(&mut writer, &self.header, file_length)?;
 
         let mut pos = 100i32;
 
@@ -1115,378 +531,656 @@ impl Shapefile {
         // Write the attributes file //
         ///////////////////////////////
 
-        let dbf_file = Path::new(&self.file_name)
-            .with_extension("dbf")
-            .into_os_string()
-            .into_string()
-            .unwrap();
+        let code_page = self.attributes.code_page;
+        self.attributes.widen_numeric_fields();
+
+        let dbf_file = Path::new(&self.file_name).with_extension("dbf");
         let f = File::create(&dbf_file)?;
         let mut writer = BufWriter::new(f);
+        write_dbf_header(
+            &mut writer,
+            &mut self.attributes.header,
+            &self.attributes.fields,
+            code_page,
+        )?;
 
-        self.attributes.header.version = 3;
-        writer.write_u8(3u8)?;
-
-        // write the date
-        // let now = time::now();
-        // writer.write_u8(now.tm_year as u8)?;
-        // writer.write_u8(now.tm_mon as u8 + 1u8)?;
-        // writer.write_u8(now.tm_mday as u8)?;
-        let now = Local::now();
-        writer.write_u8((now.year() - 1900) as u8)?;
-        writer.write_u8(now.month() as u8)?;
-        writer.write_u8(now.day() as u8)?;
-
-        writer.write_u32::<LittleEndian>(self.attributes.header.num_records)?; // number of records
-        let header_size = 32u16 + self.attributes.header.num_fields as u16 * 32u16 + 1u16;
-        self.attributes.header.bytes_in_header = header_size;
-        writer.write_u16::<LittleEndian>(header_size)?; // header size
-
-        let mut bytes_in_record = 0u16;
-        for field in &self.attributes.fields {
-            bytes_in_record += field.field_length as u16;
+        // write records
+        let mut memo_writer = if self.attributes.fields.iter().any(|field| field.field_type == 'M') {
+            Some(MemoWriter::create(&dbf_file)?)
+        } else {
+            None
+        };
+        for i in 0..self.attributes.header.num_records as usize {
+            write_dbf_record(
+                &mut writer,
+                &self.attributes.fields,
+                &self.attributes.get_records()[i],
+                self.attributes.is_deleted[i],
+                code_page,
+                memo_writer.as_mut(),
+            )?;
         }
-        bytes_in_record += 1;
-        self.attributes.header.bytes_in_record = bytes_in_record;
-        writer.write_u16::<LittleEndian>(bytes_in_record)?; // bytes in record
 
-        // reserved or unused bytes
-        for _ in 0..20 {
-            writer.write_u8(0u8)?;
+        writer.write_u8(0x1A)?; // file terminator byte
+
+        if let Some(memo_writer) = memo_writer {
+            memo_writer.finish()?;
         }
+        write_cpg_file(&dbf_file, code_page)?;
 
-        // Field descriptor array
-        for field in &self.attributes.fields {
-            let mut s = field.name.clone();
-            if s.len() > 10 {
-                s = field.name[0..10].to_string();
-            }
-            for _ in s.len()..11 {
-                s.push(char::from(0));
-            }
-            writer.write_all(s.as_bytes())?;
-            writer.write_u8(field.field_type as u8)?;
+        Ok(())
+    }
 
-            for _ in 0..4 {
-                writer.write_u8(0u8)?;
+    pub(crate) fn calculate_extent(&mut self) {
+        self.header.reset_extent();
+        for sg in &self.records {
+            self.header.extend_extent(sg);
+        }
+        self.header.finish_extent();
+    }
+}
+
+/// Reads the header of a .shp or .shx file.
+fn read_header(bor: &mut ByteOrderReader<Cursor<Vec<u8>>>) -> Result<ShapefileHeader, Error> {
+    let mut header = ShapefileHeader::default();
+    bor.set_byte_order(Endianness::BigEndian);
+    bor.seek(0);
+    header.file_code = bor.read_i32()?;
+    bor.seek(24);
+    header.file_length = bor.read_i32()?;
+
+    // the rest of the header is in LittleEndian format
+    bor.set_byte_order(Endianness::LittleEndian);
+    header.version = bor.read_i32()?;
+    header.shape_type = ShapeType::from_int(bor.read_i32()?);
+
+    // bounding box
+    header.x_min = bor.read_f64()?;
+    header.y_min = bor.read_f64()?;
+    header.x_max = bor.read_f64()?;
+    header.y_max = bor.read_f64()?;
+    header.z_min = bor.read_f64()?;
+    header.z_max = bor.read_f64()?;
+    header.m_min = bor.read_f64()?;
+    header.m_max = bor.read_f64()?;
+    Ok(header)
+}
+
+/// Reads the projection (.prj) file of a Shapefile, returning an empty string if there is none.
+fn read_projection(file_name: &str) -> String {
+    let mut projection = String::new();
+    match File::open(Path::new(file_name).with_extension("prj")) {
+        Ok(f) => {
+            let f = BufReader::new(f);
+            for line in f.lines() {
+                let line_unwrapped = line.unwrap();
+                projection.push_str(&format!("{}\n", line_unwrapped));
             }
+        }
+        Err(_) => println!("Warning: Projection file not located."),
+    }
+    projection
+}
+
+/// Writes the header of a .shp or .shx file, which differ only in their lengths.
+fn write_header<W: Write>(writer: &mut W, header: &ShapefileHeader, file_length: i32) -> Result<(), Error> {
+    // magic number
+    writer.write_i32::<BigEndian>(9994i32)?;
+
+    // unused header bytes
+    for _ in 0..5 {
+        writer.write_i32::<BigEndian>(0i32)?;
+    }
+
+    writer.write_i32::<BigEndian>(file_length)?; // in 16-bit words
+
+    // version
+    writer.write_i32::<LittleEndian>(1000i32)?;
 
-            writer.write_u8(field.field_length)?;
-            writer.write_u8(field.decimal_count)?;
+    // shape type
+    writer.write_i32::<LittleEndian>(header.shape_type.to_int())?;
+
+    // extent
+    writer.write_f64::<LittleEndian>(header.x_min)?;
+    writer.write_f64::<LittleEndian>(header.y_min)?;
+    writer.write_f64::<LittleEndian>(header.x_max)?;
+    writer.write_f64::<LittleEndian>(header.y_max)?;
+    writer.write_f64::<LittleEndian>(header.z_min)?;
+    writer.write_f64::<LittleEndian>(header.z_max)?;
+    writer.write_f64::<LittleEndian>(header.m_min)?;
+    writer.write_f64::<LittleEndian>(header.m_max)?;
+    Ok(())
+}
+
+/// Reads the content of a record of a .shp file, i.e. its shape type and geometry, which is
+/// `content_length` bytes long.
+fn read_geometry(
+    bor: &mut ByteOrderReader<Cursor<Vec<u8>>>,
+    content_length: i32,
+) -> Result<ShapefileGeometry, Error> {
+    let length_without_m: i32;
+    let contains_m: bool;
+    let shape_type = ShapeType::from_int(bor.read_i32()?);
+    let sfg = match shape_type {
+    ShapeType::Null => {
+        ShapefileGeometry {
+            shape_type: ShapeType::Null,
+            ..Default::default()
+        }
+    }
+
+    ShapeType::Point => {
+        ShapefileGeometry {
+            shape_type: ShapeType::Point,
+            num_points: 1i32,
+            points: vec![Point2D {
+                x: bor.read_f64()?,
+                y: bor.read_f64()?,
+            }],
+            ..Default::default()
+        }
+    }
+
+    ShapeType::PolyLine | ShapeType::Polygon => {
+        let mut sfg = ShapefileGeometry {
+            shape_type: shape_type,
+            x_min: bor.read_f64()?,
+            y_min: bor.read_f64()?,
+            x_max: bor.read_f64()?,
+            y_max: bor.read_f64()?,
+            num_parts: bor.read_i32()?,
+            num_points: bor.read_i32()?,
+            ..Default::default()
+        };
+
+        for _ in 0..sfg.num_parts {
+            sfg.parts.push(bor.read_i32()?);
+        }
+
+        for _ in 0..sfg.num_points {
+            sfg.points.push(Point2D {
+                x: bor.read_f64()?,
+                y: bor.read_f64()?,
+            });
+        }
+
+        sfg
+    }
+
+    ShapeType::MultiPoint => {
+        let mut sfg = ShapefileGeometry {
+            shape_type: ShapeType::MultiPoint,
+            x_min: bor.read_f64()?,
+            y_min: bor.read_f64()?,
+            x_max: bor.read_f64()?,
+            y_max: bor.read_f64()?,
+            num_points: bor.read_i32()?,
+            ..Default::default()
+        };
+
+        for _ in 0..sfg.num_points {
+            sfg.points.push(Point2D {
+                x: bor.read_f64()?,
+                y: bor.read_f64()?,
+            });
+        }
+
+        sfg
+    }
 
-            for _ in 0..14 {
-                writer.write_u8(0u8)?;
+    ShapeType::PointZ => {
+        if content_length == 36 {
+            // both z and m are included
+            ShapefileGeometry {
+                shape_type: ShapeType::PointZ,
+                num_points: 1i32,
+                points: vec![Point2D {
+                    x: bor.read_f64()?,
+                    y: bor.read_f64()?,
+                }],
+                z_array: vec![bor.read_f64()?],
+                m_array: vec![bor.read_f64()?],
+                ..Default::default()
+            }
+        } else {
+            // only z is included
+            ShapefileGeometry {
+                shape_type: ShapeType::PointZ,
+                num_points: 1i32,
+                points: vec![Point2D {
+                    x: bor.read_f64()?,
+                    y: bor.read_f64()?,
+                }],
+                z_array: vec![bor.read_f64()?],
+                ..Default::default()
             }
         }
+    }
 
-        writer.write_u8(0x0D)?; // terminator byte
+    ShapeType::PolyLineZ | ShapeType::PolygonZ => {
+        let mut sfg = ShapefileGeometry {
+            shape_type: shape_type,
+            x_min: bor.read_f64()?,
+            y_min: bor.read_f64()?,
+            x_max: bor.read_f64()?,
+            y_max: bor.read_f64()?,
+            num_parts: bor.read_i32()?,
+            num_points: bor.read_i32()?,
+            ..Default::default()
+        };
+        length_without_m =
+            44 + 4 * sfg.num_parts + 16 * sfg.num_points + 16 + 8 * sfg.num_points;
+        // Files written by earlier versions of this library overstate the content length by
+        // four bytes, which is not enough to hold measures.
+        contains_m = content_length >= length_without_m + 16 + 8 * sfg.num_points;
+
+        for _ in 0..sfg.num_parts {
+            sfg.parts.push(bor.read_i32()?);
+        }
 
-        // write records
-        for i in 0..self.attributes.header.num_records as usize {
-            if !self.attributes.is_deleted[i] {
-                writer.write_u8(0x20)?;
-            } else {
-                writer.write_u8(0x2A)?;
+        for _ in 0..sfg.num_points {
+            sfg.points.push(Point2D {
+                x: bor.read_f64()?,
+                y: bor.read_f64()?,
+            });
+        }
+
+        sfg.z_min = bor.read_f64()?;
+        sfg.z_max = bor.read_f64()?;
+        for _ in 0..sfg.num_points {
+            sfg.z_array.push(bor.read_f64()?);
+        }
+
+        if contains_m {
+            sfg.m_min = bor.read_f64()?;
+            sfg.m_max = bor.read_f64()?;
+            for _ in 0..sfg.num_points {
+                sfg.m_array.push(bor.read_f64()?);
             }
-            let rec = self.attributes.get_record(i);
-            for j in 0..self.attributes.header.num_fields {
-                let fl = self.attributes.fields[j as usize].field_length as usize;
-                match &rec[j as usize] {
-                    FieldData::Null => {
-                        let spcs: String = vec![' '; fl].into_iter().collect();
-                        writer.write_all(spcs.as_bytes())?;
-                    }
-                    FieldData::Int(v) => {
-                        let b = v.to_string();
-                        if b.len() < fl {
-                            let mut spcs: String = vec![' '; fl - b.len()].into_iter().collect();
-                            spcs.push_str(&b);
-                            writer.write_all(&spcs.as_bytes())?;
-                        } else if b.len() > fl {
-                            writer.write_all(&b[b.len() - fl..b.len()].as_bytes())?;
-                        } else {
-                            writer.write_all(&b.as_bytes())?;
-                        }
-                    }
-                    FieldData::Real(v) => {
-                        let dc = self.attributes.fields[j as usize].decimal_count as usize;
-                        let s = v.to_string();
-                        let e: Vec<&str> = s.split(".").collect();
-                        let f = if e.len() == 2 { e[1].clone() } else { "" };
-                        let mut s: String;
-                        let decimals = if f.len() > dc {
-                            let (e2, _) = f.split_at(dc);
-                            e2
-                        } else if f.len() < dc {
-                            // pad with some trailing zeros
-                            s = f.clone().to_string();
-                            for _ in 0..(dc - f.len()) {
-                                s.push_str("0");
-                            }
-                            &s
-                        } else {
-                            f
-                        };
-                        s = format!("{}.{}", e[0], decimals);
-                        if s.len() < fl {
-                            for _ in 0..(fl - s.len()) {
-                                s.push_str(" ");
-                            }
-                        } else if s.len() > fl {
-                            s.truncate(fl);
-                        }
-                        writer.write_all(&s.as_bytes())?;
-                        // let s = v.to_string();
-                        // let d = v.trunc().to_string();
-                        // let mut c = if s.len() > d.len() {
-                        //     s[d.len() + 1..s.len()].to_string()
-                        // } else {
-                        //     String::new()
-                        // };
-                        // if c.len() > dc {
-                        //     c = c[0..dc].to_string();
-                        // }
-                        // let b = format!("{}.{}", d, c);
-                        // println!("{}", b);
-                        // if b.len() < fl {
-                        //     let mut spcs: String = vec![' '; fl - b.len()].into_iter().collect();
-                        //     spcs.push_str(&b);
-                        //     writer.write_all(&spcs.as_bytes())?;
-                        // } else if b.len() > fl {
-                        //     writer.write_all(&b[b.len() - fl..b.len()].as_bytes())?;
-                        // } else {
-                        //     writer.write_all(&b.as_bytes())?;
-                        // }
-                    }
-                    FieldData::Bool(v) => {
-                        if *v {
-                            writer.write_all("T".as_bytes())?;
-                        } else {
-                            writer.write_all("F".as_bytes())?;
-                        }
-                    }
-                    FieldData::Date(v) => {
-                        writer.write_all(&format!("{}", v).as_bytes())?;
-                    }
-                    FieldData::Text(v) => {
-                        if v.len() < fl {
-                            // add spaces at start
-                            let spcs: String = vec![' '; fl - v.len()].into_iter().collect();
-                            writer.write_all(&(format!("{}{}", v, spcs)).as_bytes())?;
-                        } else if v.len() > fl {
-                            writer.write_all(&v[0..fl].as_bytes())?;
-                        } else {
-                            writer.write_all(&v.as_bytes())?;
-                        }
-                    }
-                }
+        }
+
+        sfg
+    }
+
+    ShapeType::MultiPointZ => {
+        let mut sfg = ShapefileGeometry {
+            shape_type: ShapeType::MultiPointZ,
+            x_min: bor.read_f64()?,
+            y_min: bor.read_f64()?,
+            x_max: bor.read_f64()?,
+            y_max: bor.read_f64()?,
+            num_points: bor.read_i32()?,
+            ..Default::default()
+        };
+
+        length_without_m = 40 + 16 * sfg.num_points + 16 + 8 * sfg.num_points;
+        // Files written by earlier versions of this library overstate the content length by
+        // four bytes, which is not enough to hold measures.
+        contains_m = content_length >= length_without_m + 16 + 8 * sfg.num_points;
+
+        for _ in 0..sfg.num_points {
+            sfg.points.push(Point2D {
+                x: bor.read_f64()?,
+                y: bor.read_f64()?,
+            });
+        }
+
+        sfg.z_min = bor.read_f64()?;
+        sfg.z_max = bor.read_f64()?;
+        for _ in 0..sfg.num_points {
+            sfg.z_array.push(bor.read_f64()?);
+        }
+        if contains_m {
+            sfg.m_min = bor.read_f64()?;
+            sfg.m_max = bor.read_f64()?;
+            for _ in 0..sfg.num_points {
+                sfg.m_array.push(bor.read_f64()?);
             }
         }
 
-        writer.write_u8(0x1A)?; // file terminator byte
+        sfg
+    }
 
-        Ok(())
+    ShapeType::PointM => {
+        ShapefileGeometry {
+            shape_type: ShapeType::PointM,
+            num_points: 1i32,
+            points: vec![Point2D {
+                x: bor.read_f64()?,
+                y: bor.read_f64()?,
+            }],
+            m_array: vec![bor.read_f64()?],
+            ..Default::default()
+        }
+    }
+
+    ShapeType::PolyLineM | ShapeType::PolygonM => {
+        let mut sfg = ShapefileGeometry {
+            shape_type: shape_type,
+            x_min: bor.read_f64()?,
+            y_min: bor.read_f64()?,
+            x_max: bor.read_f64()?,
+            y_max: bor.read_f64()?,
+            num_parts: bor.read_i32()?,
+            num_points: bor.read_i32()?,
+            ..Default::default()
+        };
+
+        for _ in 0..sfg.num_parts {
+            sfg.parts.push(bor.read_i32()?);
+        }
+
+        for _ in 0..sfg.num_points {
+            sfg.points.push(Point2D {
+                x: bor.read_f64()?,
+                y: bor.read_f64()?,
+            });
+        }
+
+        sfg.m_min = bor.read_f64()?;
+        sfg.m_max = bor.read_f64()?;
+        for _ in 0..sfg.num_points {
+            sfg.m_array.push(bor.read_f64()?);
+        }
+
+        sfg
+    }
+
+    ShapeType::MultiPointM => {
+        let mut sfg = ShapefileGeometry {
+            shape_type: ShapeType::MultiPointM,
+            x_min: bor.read_f64()?,
+            y_min: bor.read_f64()?,
+            x_max: bor.read_f64()?,
+            y_max: bor.read_f64()?,
+            num_points: bor.read_i32()?,
+            ..Default::default()
+        };
+
+        for _ in 0..sfg.num_points {
+            sfg.points.push(Point2D {
+                x: bor.read_f64()?,
+                y: bor.read_f64()?,
+            });
+        }
+
+        sfg.m_min = bor.read_f64()?;
+        sfg.m_max = bor.read_f64()?;
+        for _ in 0..sfg.num_points {
+            sfg.m_array.push(bor.read_f64()?);
+        }
+
+        sfg
     }
+};
+    Ok(sfg)
+}
 
-    fn calculate_extent(&mut self) {
-        match self.header.shape_type {
-            ShapeType::Null => {
-                self.header.x_min = 0f64;
-                self.header.x_max = 0f64;
-                self.header.y_min = 0f64;
-                self.header.y_max = 0f64;
-                self.header.m_min = 0f64;
-                self.header.m_max = 0f64;
-                self.header.z_min = 0f64;
-                self.header.z_max = 0f64;
+/// Writes a record of a .shp file of a given ShapeType, i.e. its record number, content
+/// length, shape type and geometry.
+fn write_geometry<W: Write>(
+    writer: &mut W,
+    record: &ShapefileGeometry,
+    record_number: i32,
+    shape_type: ShapeType,
+) -> Result<(), Error> {
+    match shape_type {
+        ShapeType::Null => {
+            writer.write_i32::<BigEndian>(record_number)?; // Record number
+            writer.write_i32::<BigEndian>(record.get_length() / 2)?; // Content length in 16-bit words
+            writer.write_i32::<LittleEndian>(0i32)?; // Shape type
+        }
+
+        ShapeType::Point => {
+            writer.write_i32::<BigEndian>(record_number)?; // Record number
+            writer.write_i32::<BigEndian>(record.get_length() / 2)?; // Content length in 16-bit words
+            writer.write_i32::<LittleEndian>(ShapeType::to_int(
+                &record.shape_type,
+            ))?; // Shape type
+
+            if record.shape_type != ShapeType::Null {
+                writer.write_f64::<LittleEndian>(record.points[0].x)?;
+                writer.write_f64::<LittleEndian>(record.points[0].y)?;
             }
-            ShapeType::Point => {
-                self.header.x_min = f64::INFINITY;
-                self.header.x_max = f64::NEG_INFINITY;
-                self.header.y_min = f64::INFINITY;
-                self.header.y_max = f64::NEG_INFINITY;
-                self.header.m_min = 0f64;
-                self.header.m_max = 0f64;
-                self.header.z_min = 0f64;
-                self.header.z_max = 0f64;
-                for sg in &self.records {
-                    if sg.points[0].x < self.header.x_min {
-                        self.header.x_min = sg.points[0].x;
-                    }
-                    if sg.points[0].y < self.header.y_min {
-                        self.header.y_min = sg.points[0].y;
-                    }
+        }
 
-                    if sg.points[0].x > self.header.x_max {
-                        self.header.x_max = sg.points[0].x;
-                    }
-                    if sg.points[0].y > self.header.y_max {
-                        self.header.y_max = sg.points[0].y;
-                    }
+        ShapeType::PolyLine | ShapeType::Polygon => {
+            writer.write_i32::<BigEndian>(record_number)?; // Record number
+            writer.write_i32::<BigEndian>(record.get_length() / 2)?; // // Content length in 16-bit words
+            writer.write_i32::<LittleEndian>(ShapeType::to_int(
+                &record.shape_type,
+            ))?; // Shape type
+
+            if record.shape_type != ShapeType::Null {
+                // extent
+                writer.write_f64::<LittleEndian>(record.x_min)?;
+                writer.write_f64::<LittleEndian>(record.y_min)?;
+                writer.write_f64::<LittleEndian>(record.x_max)?;
+                writer.write_f64::<LittleEndian>(record.y_max)?;
+
+                writer.write_i32::<LittleEndian>(record.num_parts)?; // Num parts
+                writer.write_i32::<LittleEndian>(record.num_points)?; // Num points
+
+                // parts
+                for part in &record.parts {
+                    writer.write_i32::<LittleEndian>(*part)?;
+                }
+
+                // points
+                for pt in &record.points {
+                    writer.write_f64::<LittleEndian>(pt.x)?;
+                    writer.write_f64::<LittleEndian>(pt.y)?;
                 }
             }
-            ShapeType::PolyLine | ShapeType::Polygon | ShapeType::MultiPoint => {
-                self.header.x_min = f64::INFINITY;
-                self.header.x_max = f64::NEG_INFINITY;
-                self.header.y_min = f64::INFINITY;
-                self.header.y_max = f64::NEG_INFINITY;
-                self.header.m_min = 0f64;
-                self.header.m_max = 0f64;
-                self.header.z_min = 0f64;
-                self.header.z_max = 0f64;
-                for sg in &self.records {
-                    if sg.x_min < self.header.x_min {
-                        self.header.x_min = sg.x_min;
-                    }
-                    if sg.y_min < self.header.y_min {
-                        self.header.y_min = sg.y_min;
-                    }
+        }
 
-                    if sg.x_max > self.header.x_max {
-                        self.header.x_max = sg.x_max;
-                    }
-                    if sg.y_max > self.header.y_max {
-                        self.header.y_max = sg.y_max;
-                    }
+        ShapeType::MultiPoint => {
+            writer.write_i32::<BigEndian>(record_number)?; // Record number
+            writer.write_i32::<BigEndian>(record.get_length() / 2)?; // Content length in 16-bit words
+            writer.write_i32::<LittleEndian>(ShapeType::to_int(
+                &record.shape_type,
+            ))?; // Shape type
+
+            if record.shape_type != ShapeType::Null {
+                // extent
+                writer.write_f64::<LittleEndian>(record.x_min)?;
+                writer.write_f64::<LittleEndian>(record.y_min)?;
+                writer.write_f64::<LittleEndian>(record.x_max)?;
+                writer.write_f64::<LittleEndian>(record.y_max)?;
+
+                writer.write_i32::<LittleEndian>(record.num_points)?; // Num points
+
+                // points
+                for pt in &record.points {
+                    writer.write_f64::<LittleEndian>(pt.x)?;
+                    writer.write_f64::<LittleEndian>(pt.y)?;
                 }
             }
-            ShapeType::PointM => {
-                self.header.x_min = f64::INFINITY;
-                self.header.x_max = f64::NEG_INFINITY;
-                self.header.y_min = f64::INFINITY;
-                self.header.y_max = f64::NEG_INFINITY;
-                self.header.m_min = f64::INFINITY;
-                self.header.m_max = f64::NEG_INFINITY;
-                self.header.z_min = 0f64;
-                self.header.z_max = 0f64;
-                for sg in &self.records {
-                    if sg.points[0].x < self.header.x_min {
-                        self.header.x_min = sg.points[0].x;
-                    }
-                    if sg.points[0].y < self.header.y_min {
-                        self.header.y_min = sg.points[0].y;
-                    }
-                    if sg.m_array[0] < self.header.m_min {
-                        self.header.m_min = sg.m_array[0];
-                    }
+        }
 
-                    if sg.points[0].x > self.header.x_max {
-                        self.header.x_max = sg.points[0].x;
-                    }
-                    if sg.points[0].y > self.header.y_max {
-                        self.header.y_max = sg.points[0].y;
-                    }
-                    if sg.m_array[0] > self.header.m_max {
-                        self.header.m_max = sg.m_array[0];
-                    }
+        ShapeType::PointZ => {
+            writer.write_i32::<BigEndian>(record_number)?; // Record number
+            writer.write_i32::<BigEndian>(record.get_length() / 2)?; // Content length in 16-bit words
+            writer.write_i32::<LittleEndian>(ShapeType::to_int(
+                &record.shape_type,
+            ))?; // Shape type
+
+            if record.shape_type != ShapeType::Null {
+                writer.write_f64::<LittleEndian>(record.points[0].x)?;
+                writer.write_f64::<LittleEndian>(record.points[0].y)?;
+                writer.write_f64::<LittleEndian>(record.z_array[0])?;
+                if record.has_m_data() {
+                    writer.write_f64::<LittleEndian>(record.m_array[0])?;
                 }
             }
-            ShapeType::PolyLineM | ShapeType::PolygonM | ShapeType::MultiPointM => {
-                self.header.x_min = f64::INFINITY;
-                self.header.x_max = f64::NEG_INFINITY;
-                self.header.y_min = f64::INFINITY;
-                self.header.y_max = f64::NEG_INFINITY;
-                self.header.m_min = f64::INFINITY;
-                self.header.m_max = f64::NEG_INFINITY;
-                self.header.z_min = 0f64;
-                self.header.z_max = 0f64;
-
-                for sg in &self.records {
-                    if sg.x_min < self.header.x_min {
-                        self.header.x_min = sg.x_min;
-                    }
-                    if sg.y_min < self.header.y_min {
-                        self.header.y_min = sg.y_min;
-                    }
-                    if sg.m_min < self.header.m_min {
-                        self.header.m_min = sg.m_min;
-                    }
+        }
 
-                    if sg.x_max > self.header.x_max {
-                        self.header.x_max = sg.x_max;
-                    }
-                    if sg.y_max > self.header.y_max {
-                        self.header.y_max = sg.y_max;
-                    }
-                    if sg.m_max > self.header.m_max {
-                        self.header.m_max = sg.m_max;
+        ShapeType::PolyLineZ | ShapeType::PolygonZ => {
+            writer.write_i32::<BigEndian>(record_number)?; // Record number
+            writer.write_i32::<BigEndian>(record.get_length() / 2)?; // Content length in 16-bit words
+            writer.write_i32::<LittleEndian>(ShapeType::to_int(
+                &record.shape_type,
+            ))?; // Shape type
+
+            if record.shape_type != ShapeType::Null {
+                // extent
+                writer.write_f64::<LittleEndian>(record.x_min)?;
+                writer.write_f64::<LittleEndian>(record.y_min)?;
+                writer.write_f64::<LittleEndian>(record.x_max)?;
+                writer.write_f64::<LittleEndian>(record.y_max)?;
+
+                writer.write_i32::<LittleEndian>(record.num_parts)?; // Num parts
+                writer.write_i32::<LittleEndian>(record.num_points)?; // Num points
+
+                // parts
+                for part in &record.parts {
+                    writer.write_i32::<LittleEndian>(*part)?;
+                }
+
+                // points
+                for pt in &record.points {
+                    writer.write_f64::<LittleEndian>(pt.x)?;
+                    writer.write_f64::<LittleEndian>(pt.y)?;
+                }
+
+                // z data
+                writer.write_f64::<LittleEndian>(record.z_min)?;
+                writer.write_f64::<LittleEndian>(record.z_max)?;
+                for z in &record.z_array {
+                    writer.write_f64::<LittleEndian>(*z)?;
+                }
+
+                // measure data
+                if record.has_m_data() {
+                    writer.write_f64::<LittleEndian>(record.m_min)?;
+                    writer.write_f64::<LittleEndian>(record.m_max)?;
+                    for m in &record.m_array {
+                        writer.write_f64::<LittleEndian>(*m)?;
                     }
                 }
             }
-            ShapeType::PointZ => {
-                self.header.x_min = f64::INFINITY;
-                self.header.x_max = f64::NEG_INFINITY;
-                self.header.y_min = f64::INFINITY;
-                self.header.y_max = f64::NEG_INFINITY;
-                self.header.m_min = f64::INFINITY;
-                self.header.m_max = f64::NEG_INFINITY;
-                self.header.z_min = f64::INFINITY;
-                self.header.z_max = f64::NEG_INFINITY;
-                for sg in &self.records {
-                    if sg.points[0].x < self.header.x_min {
-                        self.header.x_min = sg.points[0].x;
-                    }
-                    if sg.points[0].y < self.header.y_min {
-                        self.header.y_min = sg.points[0].y;
-                    }
-                    if sg.m_array[0] < self.header.m_min {
-                        self.header.m_min = sg.m_array[0];
-                    }
-                    if sg.z_array[0] < self.header.z_min {
-                        self.header.z_min = sg.z_array[0];
-                    }
+        }
 
-                    if sg.points[0].x > self.header.x_max {
-                        self.header.x_max = sg.points[0].x;
-                    }
-                    if sg.points[0].y > self.header.y_max {
-                        self.header.y_max = sg.points[0].y;
-                    }
-                    if sg.m_array[0] > self.header.m_max {
-                        self.header.m_max = sg.m_array[0];
-                    }
-                    if sg.z_array[0] > self.header.z_min {
-                        self.header.z_min = sg.z_array[0];
+        ShapeType::MultiPointZ => {
+            writer.write_i32::<BigEndian>(record_number)?; // Record number
+            writer.write_i32::<BigEndian>(record.get_length() / 2)?; // Content length in 16-bit words
+            writer.write_i32::<LittleEndian>(ShapeType::to_int(
+                &record.shape_type,
+            ))?; // Shape type
+
+            if record.shape_type != ShapeType::Null {
+                // extent
+                writer.write_f64::<LittleEndian>(record.x_min)?;
+                writer.write_f64::<LittleEndian>(record.y_min)?;
+                writer.write_f64::<LittleEndian>(record.x_max)?;
+                writer.write_f64::<LittleEndian>(record.y_max)?;
+
+                writer.write_i32::<LittleEndian>(record.num_points)?; // Num points
+
+                // points
+                for pt in &record.points {
+                    writer.write_f64::<LittleEndian>(pt.x)?;
+                    writer.write_f64::<LittleEndian>(pt.y)?;
+                }
+
+                // z data
+                writer.write_f64::<LittleEndian>(record.z_min)?;
+                writer.write_f64::<LittleEndian>(record.z_max)?;
+                for z in &record.z_array {
+                    writer.write_f64::<LittleEndian>(*z)?;
+                }
+
+                // measure data
+                if record.has_m_data() {
+                    writer.write_f64::<LittleEndian>(record.m_min)?;
+                    writer.write_f64::<LittleEndian>(record.m_max)?;
+                    for m in &record.m_array {
+                        writer.write_f64::<LittleEndian>(*m)?;
                     }
                 }
             }
-            ShapeType::PolyLineZ | ShapeType::PolygonZ | ShapeType::MultiPointZ => {
-                self.header.x_min = f64::INFINITY;
-                self.header.x_max = f64::NEG_INFINITY;
-                self.header.y_min = f64::INFINITY;
-                self.header.y_max = f64::NEG_INFINITY;
-                self.header.m_min = f64::INFINITY;
-                self.header.m_max = f64::NEG_INFINITY;
-                self.header.z_min = f64::INFINITY;
-                self.header.z_max = f64::NEG_INFINITY;
-
-                for sg in &self.records {
-                    if sg.x_min < self.header.x_min {
-                        self.header.x_min = sg.x_min;
-                    }
-                    if sg.y_min < self.header.y_min {
-                        self.header.y_min = sg.y_min;
-                    }
-                    if sg.m_min < self.header.m_min {
-                        self.header.m_min = sg.m_min;
-                    }
-                    if sg.z_min < self.header.z_min {
-                        self.header.z_min = sg.z_min;
-                    }
+        }
 
-                    if sg.x_max > self.header.x_max {
-                        self.header.x_max = sg.x_max;
-                    }
-                    if sg.y_max > self.header.y_max {
-                        self.header.y_max = sg.y_max;
-                    }
-                    if sg.m_max > self.header.m_max {
-                        self.header.m_max = sg.m_max;
-                    }
-                    if sg.z_max > self.header.z_max {
-                        self.header.z_max = sg.z_max;
-                    }
+        ShapeType::PointM => {
+            writer.write_i32::<BigEndian>(record_number)?; // Record number
+            writer.write_i32::<BigEndian>(record.get_length() / 2)?; // Content length in 16-bit words
+            writer.write_i32::<LittleEndian>(ShapeType::to_int(
+                &record.shape_type,
+            ))?; // Shape type
+
+            if record.shape_type != ShapeType::Null {
+                writer.write_f64::<LittleEndian>(record.points[0].x)?;
+                writer.write_f64::<LittleEndian>(record.points[0].y)?;
+                writer.write_f64::<LittleEndian>(record.m_array[0])?;
+            }
+        }
+
+        ShapeType::PolyLineM | ShapeType::PolygonM => {
+            writer.write_i32::<BigEndian>(record_number)?; // Record number
+            writer.write_i32::<BigEndian>(record.get_length() / 2)?; // Content length in 16-bit words
+            writer.write_i32::<LittleEndian>(ShapeType::to_int(
+                &record.shape_type,
+            ))?; // Shape type
+
+            if record.shape_type != ShapeType::Null {
+                // extent
+                writer.write_f64::<LittleEndian>(record.x_min)?;
+                writer.write_f64::<LittleEndian>(record.y_min)?;
+                writer.write_f64::<LittleEndian>(record.x_max)?;
+                writer.write_f64::<LittleEndian>(record.y_max)?;
+
+                writer.write_i32::<LittleEndian>(record.num_parts)?; // Num parts
+                writer.write_i32::<LittleEndian>(record.num_points)?; // Num points
+
+                // parts
+                for part in &record.parts {
+                    writer.write_i32::<LittleEndian>(*part)?;
+                }
+
+                // points
+                for pt in &record.points {
+                    writer.write_f64::<LittleEndian>(pt.x)?;
+                    writer.write_f64::<LittleEndian>(pt.y)?;
+                }
+
+                // measure data
+                writer.write_f64::<LittleEndian>(record.m_min)?;
+                writer.write_f64::<LittleEndian>(record.m_max)?;
+                for m in &record.m_array {
+                    writer.write_f64::<LittleEndian>(*m)?;
+                }
+            }
+        }
+
+        ShapeType::MultiPointM => {
+            writer.write_i32::<BigEndian>(record_number)?; // Record number
+            writer.write_i32::<BigEndian>(record.get_length() / 2)?; // Content length in 16-bit words
+            writer.write_i32::<LittleEndian>(ShapeType::to_int(
+                &record.shape_type,
+            ))?; // Shape type
+            if record.shape_type != ShapeType::Null {
+                // extent
+                writer.write_f64::<LittleEndian>(record.x_min)?;
+                writer.write_f64::<LittleEndian>(record.y_min)?;
+                writer.write_f64::<LittleEndian>(record.x_max)?;
+                writer.write_f64::<LittleEndian>(record.y_max)?;
+
+                writer.write_i32::<LittleEndian>(record.num_points)?; // Num points
+
+                // points
+                for pt in &record.points {
+                    writer.write_f64::<LittleEndian>(pt.x)?;
+                    writer.write_f64::<LittleEndian>(pt.y)?;
+                }
+
+                // measure data
+                writer.write_f64::<LittleEndian>(record.m_min)?;
+                writer.write_f64::<LittleEndian>(record.m_max)?;
+                for m in &record.m_array {
+                    writer.write_f64::<LittleEndian>(*m)?;
                 }
             }
         }
     }
+    Ok(())
 }
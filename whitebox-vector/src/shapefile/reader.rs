@@ -0,0 +1,342 @@
+/*
+This code is part of the WhiteboxTools geospatial analysis library.
+License: MIT
+
+Notes: ShapefileReader reads the features of a Shapefile one at a time, rather than reading
+the whole file into memory as Shapefile::read does, so that large files can be processed in
+constant memory. The .shx index gives the position of each record in the .shp file, so that
+features can be read in any order. Files without a .shx index are scanned once when they are
+opened, to locate their records.
+*/
+
+use super::attributes::*;
+use super::dbf::*;
+use super::geometry::*;
+use super::{read_geometry, read_header, read_projection, ShapefileHeader};
+use whitebox_common::spatial_ref_system::CoordinateReferenceSystem;
+use whitebox_common::utils::{ByteOrderReader, Endianness};
+use std::fs::File;
+use std::io::prelude::*;
+use std::io::{BufReader, Cursor, Error, ErrorKind, SeekFrom};
+use std::path::Path;
+
+/// A reader of the features of a Shapefile, i.e. its geometry records and their attribute
+/// records, which are read from the files as they are needed.
+///
+/// Example:
+///
+/// ```no_run
+/// use whitebox_vector::ShapefileReader;
+///
+/// # fn main() -> std::io::Result<()> {
+/// let mut reader = ShapefileReader::open("input.shp")?;
+/// for feature in reader.features() {
+///     let (geometry, attributes) = feature?;
+///     println!("{} points, {} attributes", geometry.num_points, attributes.len());
+/// }
+/// # Ok(())
+/// # }
+/// ```
+pub struct ShapefileReader {
+    pub file_name: String,
+    pub header: ShapefileHeader,
+    pub num_records: usize,
+    pub projection: String,
+    shp: BufReader<File>,
+    shp_pos: u64,
+    /// The .shx index, or, if there is none, the offset and content length of each record.
+    shx: Option<BufReader<File>>,
+    shx_pos: u64,
+    offsets: Vec<(u64, usize)>,
+    dbf: BufReader<File>,
+    dbf_pos: u64,
+    dbf_header: DbfHeader,
+    memo_file: Option<MemoFile>,
+    num_unparsed: Vec<usize>,
+}
+
+impl ShapefileReader {
+    /// Opens a Shapefile for reading. The headers of the .shp and .dbf files are read, but
+    /// none of the records are. A .dbf file that names no code page is scanned once, to tell
+    /// whether its text is UTF-8.
+    pub fn open(file_name: &str) -> Result<ShapefileReader, Error> {
+        let f = File::open(file_name)?;
+        let shp_size = f.metadata()?.len();
+        let mut shp = BufReader::new(f);
+        let mut buffer = vec![0u8; 100];
+        shp.read_exact(&mut buffer)?;
+        let mut bor =
+            ByteOrderReader::<Cursor<Vec<u8>>>::new(Cursor::new(buffer), Endianness::BigEndian);
+        let header = read_header(&mut bor)?;
+        if header.file_code != 9994 {
+            return Err(Error::new(
+                ErrorKind::InvalidData,
+                format!("{} is not a Shapefile.", file_name),
+            ));
+        }
+        let mut shp_pos = 100u64;
+
+        // Locate the records, from the .shx index if there is one.
+        let (shx, num_records, offsets) =
+            match File::open(Path::new(file_name).with_extension("shx")) {
+                Ok(f) => {
+                    let num_records = (f.metadata()?.len().saturating_sub(100) / 8) as usize;
+                    (Some(BufReader::new(f)), num_records, vec![])
+                }
+                Err(_) => {
+                    let mut offsets = vec![];
+                    let mut record_header = [0u8; 8];
+                    while shp_pos + 8 <= shp_size {
+                        shp.read_exact(&mut record_header)?;
+                        let content_length = 2 * i32::from_be_bytes([
+                            record_header[4],
+                            record_header[5],
+                            record_header[6],
+                            record_header[7],
+                        ]) as usize;
+                        offsets.push((shp_pos, content_length));
+                        shp.seek_relative(content_length as i64)?;
+                        shp_pos += 8 + content_length as u64;
+                    }
+                    (None, offsets.len(), offsets)
+                }
+            };
+
+        let projection = read_projection(file_name);
+
+        // Read the header of the attribute table.
+        let dbf_file = Path::new(file_name).with_extension("dbf");
+        let f = File::open(&dbf_file)?;
+        let dbf_size = f.metadata()?.len();
+        let mut dbf = BufReader::new(f);
+        let mut dbf_header = DbfHeader::read(&mut dbf, dbf_size, &dbf_file)?;
+        dbf.seek(SeekFrom::Start(dbf_header.first_record))?;
+        let record_length = dbf_header.record_length;
+        let num_dbf_records = dbf_header.header.num_records as usize;
+        let mut records_read = 0usize;
+        {
+            let records = (0..num_dbf_records).map(|_| {
+                let mut record = vec![0u8; record_length];
+                dbf.read_exact(&mut record)?;
+                records_read += 1;
+                Ok(record)
+            });
+            dbf_header.resolve_code_page(records)?;
+        }
+        let dbf_pos = dbf_header.first_record + (records_read * record_length) as u64;
+        let memo_file = if dbf_header.has_memo_fields() {
+            MemoFile::open(&dbf_file, dbf_header.header.version)
+        } else {
+            None
+        };
+        let num_unparsed = vec![0usize; dbf_header.fields.len()];
+
+        Ok(ShapefileReader {
+            file_name: file_name.to_string(),
+            header,
+            num_records,
+            projection,
+            shp,
+            shp_pos,
+            shx,
+            shx_pos: 0,
+            offsets,
+            dbf,
+            dbf_pos,
+            dbf_header,
+            memo_file,
+            num_unparsed,
+        })
+    }
+
+    pub fn get_shape_type(&self) -> ShapeType {
+        self.header.shape_type
+    }
+
+    /// Returns the fields of the attribute table.
+    pub fn get_fields(&self) -> &Vec<AttributeField> {
+        &self.dbf_header.fields
+    }
+
+    /// Returns the code page of the attribute table's text.
+    pub fn get_code_page(&self) -> CodePage {
+        self.dbf_header.code_page.unwrap_or_default()
+    }
+
+    pub fn get_coordinate_reference_system(&self) -> Option<CoordinateReferenceSystem> {
+        CoordinateReferenceSystem::from_wkt_or_epsg(&self.projection, 0)
+    }
+
+    /// Reads the geometry record for a zero-based index.
+    pub fn read_record(&mut self, index: usize) -> Result<ShapefileGeometry, Error> {
+        if index >= self.num_records {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                "Specified record index is greater than the number of records.",
+            ));
+        }
+        let (offset, content_length) = match self.shx.as_mut() {
+            Some(shx) => {
+                let mut entry = [0u8; 8];
+                seek_to(shx, &mut self.shx_pos, 100 + 8 * index as u64)?;
+                shx.read_exact(&mut entry)?;
+                self.shx_pos += 8;
+                let offset = 2 * i32::from_be_bytes([entry[0], entry[1], entry[2], entry[3]]) as u64;
+                let content_length =
+                    2 * i32::from_be_bytes([entry[4], entry[5], entry[6], entry[7]]) as usize;
+                (offset, content_length)
+            }
+            None => self.offsets[index],
+        };
+
+        // Skip the record header, which holds the record number and content length.
+        seek_to(&mut self.shp, &mut self.shp_pos, offset + 8)?;
+        // Some older files overstate the content length of their Z records, so the last record
+        // may end before its stated length.
+        let mut buffer = Vec::with_capacity(content_length);
+        let n = (&mut self.shp).take(content_length as u64).read_to_end(&mut buffer)?;
+        self.shp_pos += n as u64;
+        let mut bor =
+            ByteOrderReader::<Cursor<Vec<u8>>>::new(Cursor::new(buffer), Endianness::LittleEndian);
+        read_geometry(&mut bor, content_length as i32)
+    }
+
+    /// Reads the attribute record for a zero-based index. Values that cannot be parsed are
+    /// read as null, and a warning for them is printed when the reader is dropped.
+    pub fn read_attributes(&mut self, index: usize) -> Result<Vec<FieldData>, Error> {
+        if index >= self.dbf_header.header.num_records as usize {
+            return Err(Error::new(
+                ErrorKind::InvalidData,
+                format!("The attribute table has no record {}.", index),
+            ));
+        }
+        let record_length = self.dbf_header.record_length;
+        seek_to(
+            &mut self.dbf,
+            &mut self.dbf_pos,
+            self.dbf_header.first_record + (index * record_length) as u64,
+        )?;
+        let mut record = vec![0u8; record_length];
+        self.dbf.read_exact(&mut record)?;
+        self.dbf_pos += record_length as u64;
+        let (r, _) = self.dbf_header.parse_record(
+            &record,
+            self.memo_file.as_mut(),
+            &mut self.num_unparsed,
+        )?;
+        Ok(r)
+    }
+
+    /// Reads the geometry record and attribute record of the feature for a zero-based index.
+    pub fn read_feature(&mut self, index: usize) -> Result<(ShapefileGeometry, Vec<FieldData>), Error> {
+        Ok((self.read_record(index)?, self.read_attributes(index)?))
+    }
+
+    /// Returns an iterator over the features, in the order of the file.
+    pub fn features(&mut self) -> ShapefileFeatures<'_> {
+        ShapefileFeatures {
+            reader: self,
+            index: 0,
+        }
+    }
+}
+
+impl Drop for ShapefileReader {
+    fn drop(&mut self) {
+        self.dbf_header.warn_unparsed(&self.num_unparsed);
+    }
+}
+
+/// An iterator over the features of a ShapefileReader, which yields the geometry record and
+/// attribute record of each.
+pub struct ShapefileFeatures<'a> {
+    reader: &'a mut ShapefileReader,
+    index: usize,
+}
+
+impl<'a> Iterator for ShapefileFeatures<'a> {
+    type Item = Result<(ShapefileGeometry, Vec<FieldData>), Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.index >= self.reader.num_records {
+            return None;
+        }
+        let feature = self.reader.read_feature(self.index);
+        // Stop after an error, rather than reading the rest of a damaged file.
+        self.index = if feature.is_ok() {
+            self.index + 1
+        } else {
+            self.reader.num_records
+        };
+        Some(feature)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let n = self.reader.num_records - self.index;
+        (n, Some(n))
+    }
+}
+
+/// Moves a reader to a position, keeping its buffer if the position is within it, so that
+/// reading the records in order does not discard the buffer for each record.
+fn seek_to(reader: &mut BufReader<File>, pos: &mut u64, target: u64) -> Result<(), Error> {
+    if *pos != target {
+        reader.seek_relative(target as i64 - *pos as i64)?;
+        *pos = target;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use crate::test_utils::*;
+    use crate::*;
+    use std::fs;
+
+    #[test]
+    fn features_are_read_in_any_order() {
+        let file_name = temp_file("reader.shp");
+        let mut output = Shapefile::new(&file_name, ShapeType::PolyLine).unwrap();
+        output.attributes.add_field(&AttributeField::new("ID", FieldDataType::Int, 6u8, 0u8));
+        output.attributes.add_field(&AttributeField::new("NAME", FieldDataType::Text, 12u8, 0u8));
+        for i in 0..200 {
+            output.add_record(geometry(ShapeType::PolyLine, i));
+            output.attributes.add_record(
+                vec![FieldData::Int(i as i32), FieldData::Text(format!("line {}", i))],
+                false,
+            );
+        }
+        output.write().unwrap();
+
+        // Without its .shx index, the .shp file is scanned to locate the records.
+        for has_index in [true, false] {
+            if !has_index {
+                fs::remove_file(temp_file("reader.shx")).unwrap();
+            }
+            let mut reader = ShapefileReader::open(&file_name).unwrap();
+            assert_eq!(reader.num_records, 200);
+            assert_eq!(reader.get_shape_type(), ShapeType::PolyLine);
+            assert_eq!(reader.get_fields(), output.attributes.get_fields());
+            assert_eq!(reader.get_code_page(), CodePage::Utf8);
+            for i in [199, 0, 57, 58, 3] {
+                let (sfg, rec) = reader.read_feature(i).unwrap();
+                assert_eq!(sfg.points, output.records[i].points);
+                assert_eq!(sfg.parts, output.records[i].parts);
+                assert_eq!(rec, output.attributes.get_record(i));
+            }
+            assert!(reader.read_record(200).is_err());
+            assert!(reader.read_attributes(200).is_err());
+
+            let features = reader.features();
+            assert_eq!(features.size_hint(), (200, Some(200)));
+            for (i, feature) in features.enumerate() {
+                let (sfg, rec) = feature.unwrap();
+                assert_eq!(sfg.points, output.records[i].points);
+                assert_eq!(rec, output.attributes.get_record(i));
+            }
+        }
+        for extension in ["shp", "dbf", "cpg"] {
+            fs::remove_file(temp_file(&format!("reader.{}", extension))).unwrap();
+        }
+    }
+}
@@ -0,0 +1,191 @@
+/*
+This code is part of the WhiteboxTools geospatial analysis library.
+License: MIT
+
+Notes: The spatial index of a Shapefile is an R-tree of the bounding boxes of its records,
+which is bulk loaded the first time that it is queried. Queries are answered in terms of the
+bounding boxes, and so return candidates, which the caller tests against the geometries
+themselves, e.g. with poly_overlaps_poly. The index is dropped by the methods that change the
+records; code that modifies `Shapefile::records` directly must call `clear_spatial_index`.
+*/
+
+use super::geometry::*;
+use super::Shapefile;
+use rstar::{PointDistance, RTree, AABB};
+use whitebox_common::structures::{BoundingBox, RectangleWithData};
+
+/// An R-tree of the bounding boxes of the records of a Shapefile, holding record numbers.
+pub(crate) type SpatialIndex = RTree<RectangleWithData<usize>>;
+
+impl Shapefile {
+    /// Returns the spatial index, building it if it has not been built since the records last
+    /// changed. Null records and records without points are not indexed.
+    fn get_spatial_index(&self) -> &SpatialIndex {
+        self.spatial_index.get_or_init(|| {
+            let rectangles = self
+                .records
+                .iter()
+                .enumerate()
+                .filter(|(_, r)| r.shape_type != ShapeType::Null && !r.points.is_empty())
+                .map(|(i, r)| {
+                    let bb = BoundingBox::from_points(&r.points);
+                    RectangleWithData::new(i, [bb.min_x, bb.min_y], [bb.max_x, bb.max_y])
+                })
+                .collect::<Vec<_>>();
+            RTree::bulk_load(rectangles)
+        })
+    }
+
+    /// Drops the spatial index, so that it is rebuilt by the next query. This must be called
+    /// after the records have been modified other than through the Shapefile's methods.
+    pub fn clear_spatial_index(&mut self) {
+        self.spatial_index.take();
+    }
+
+    /// Returns the numbers of the records whose bounding boxes overlap `bbox`, in ascending
+    /// order.
+    pub fn query_bbox(&self, bbox: &BoundingBox) -> Vec<usize> {
+        let envelope = AABB::from_corners([bbox.min_x, bbox.min_y], [bbox.max_x, bbox.max_y]);
+        let mut ret = self
+            .get_spatial_index()
+            .locate_in_envelope_intersecting(&envelope)
+            .map(|r| r.data)
+            .collect::<Vec<usize>>();
+        ret.sort_unstable();
+        ret
+    }
+
+    /// Returns the numbers of the records whose bounding boxes contain the point (x, y), in
+    /// ascending order.
+    pub fn query_point(&self, x: f64, y: f64) -> Vec<usize> {
+        let mut ret = self
+            .get_spatial_index()
+            .locate_all_at_point(&[x, y])
+            .map(|r| r.data)
+            .collect::<Vec<usize>>();
+        ret.sort_unstable();
+        ret
+    }
+
+    /// Returns the numbers of the k records whose bounding boxes are nearest to the point
+    /// (x, y), with the distances to their bounding boxes, nearest first. The distance is
+    /// zero for records whose bounding boxes contain the point.
+    pub fn nearest_k(&self, x: f64, y: f64, k: usize) -> Vec<(usize, f64)> {
+        let point = [x, y];
+        self.get_spatial_index()
+            .nearest_neighbor_iter(&point)
+            .take(k)
+            .map(|r| (r.data, r.distance_2(&point).sqrt()))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::test_utils::*;
+    use crate::*;
+    use whitebox_common::structures::BoundingBox;
+
+    /// Returns a file of short random lines, with a null record among them.
+    fn random_lines(n: usize) -> Shapefile {
+        let mut sf = Shapefile::new(&temp_file("index.shp"), ShapeType::PolyLine).unwrap();
+        let mut seed = 54321u64;
+        let mut random = || {
+            seed = seed.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+            (seed >> 33) as f64 / (1u64 << 31) as f64 * 1000.0
+        };
+        for i in 0..n {
+            if i == 10 {
+                sf.add_record(ShapefileGeometry::default());
+                continue;
+            }
+            let (x, y) = (random(), random());
+            let mut sfg = ShapefileGeometry::new(ShapeType::PolyLine);
+            sfg.add_part(&points(&[(x, y), (x + random() / 20.0, y - random() / 20.0)]));
+            sf.add_record(sfg);
+        }
+        sf
+    }
+
+    /// Returns the bounding boxes of the records, which are None for null records.
+    fn record_boxes(sf: &Shapefile) -> Vec<Option<BoundingBox>> {
+        sf.records
+            .iter()
+            .map(|r| match r.shape_type {
+                ShapeType::Null => None,
+                _ => Some(BoundingBox::from_points(&r.points)),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn queries_match_full_scans() {
+        let sf = random_lines(1000);
+        let boxes = record_boxes(&sf);
+        for &(x, y, size) in &[(100.0, 100.0, 50.0), (500.0, 250.0, 200.0), (-10.0, 0.0, 5.0)] {
+            let bbox = BoundingBox::new(x, x + size, y, y + size);
+            let expected: Vec<usize> = (0..boxes.len())
+                .filter(|&i| match &boxes[i] {
+                    Some(b) => {
+                        b.min_x <= bbox.max_x
+                            && b.max_x >= bbox.min_x
+                            && b.min_y <= bbox.max_y
+                            && b.max_y >= bbox.min_y
+                    }
+                    None => false,
+                })
+                .collect();
+            assert_eq!(sf.query_bbox(&bbox), expected);
+
+            let expected: Vec<usize> = (0..boxes.len())
+                .filter(|&i| match &boxes[i] {
+                    Some(b) => b.min_x <= x && b.max_x >= x && b.min_y <= y && b.max_y >= y,
+                    None => false,
+                })
+                .collect();
+            assert_eq!(sf.query_point(x, y), expected);
+
+            // Records whose boxes are equally distant may be returned in either order, so
+            // the distances are compared.
+            let mut distances: Vec<f64> = boxes
+                .iter()
+                .flatten()
+                .map(|b| {
+                    let dx = (b.min_x - x).max(x - b.max_x).max(0.0);
+                    let dy = (b.min_y - y).max(y - b.max_y).max(0.0);
+                    (dx * dx + dy * dy).sqrt()
+                })
+                .collect();
+            distances.sort_by(|a, b| a.partial_cmp(b).unwrap());
+            let nearest = sf.nearest_k(x, y, 8);
+            assert_eq!(nearest.len(), 8);
+            for (k, &(_, distance)) in nearest.iter().enumerate() {
+                assert!((distance - distances[k]).abs() < 1e-9);
+            }
+        }
+        assert_eq!(sf.nearest_k(0.0, 0.0, 2000).len(), 999);
+    }
+
+    #[test]
+    fn index_follows_changes_to_the_records() {
+        let mut sf = random_lines(100);
+        let bbox = BoundingBox::new(2000.0, 2010.0, 2000.0, 2010.0);
+        assert!(sf.query_bbox(&bbox).is_empty());
+
+        let mut sfg = ShapefileGeometry::new(ShapeType::PolyLine);
+        sfg.add_part(&points(&[(2001.0, 2001.0), (2002.0, 2002.0)]));
+        sf.add_record(sfg);
+        assert_eq!(sf.query_bbox(&bbox), vec![100]);
+        assert_eq!(sf.nearest_k(2005.0, 2005.0, 1), vec![(100, 3f64.hypot(3.0))]);
+
+        // Records that are modified directly are only found once the index is cleared.
+        for p in &mut sf.records[0].points {
+            p.x += 2000.0;
+            p.y += 2000.0;
+        }
+        assert_eq!(sf.query_bbox(&bbox), vec![100]);
+        sf.clear_spatial_index();
+        let found = sf.query_bbox(&BoundingBox::new(2000.0, 3000.0, 2000.0, 3000.0));
+        assert_eq!(found, vec![0, 100]);
+    }
+}
@@ -0,0 +1,399 @@
+/*
+This code is part of the WhiteboxTools geospatial analysis library.
+License: MIT
+
+Notes: ShapefileWriter writes the features of a Shapefile as they are produced, rather than
+holding them all in memory until Shapefile::write is called. The .shp and .shx files are
+written with placeholder headers, which are patched with the file lengths and extent when
+the writer is closed, as is the number of records in the .dbf header. Because the attribute
+records are written as they come, the fields cannot change once the first feature has been
+written, and their widths are not fitted to the values, as Shapefile::write does. Text that is
+wider than its field is truncated, and numbers that are wider than their fields are written as
+asterisks, which are read as null, so the fields should be wide enough for the values.
+*/
+
+use super::attributes::*;
+use super::dbf::*;
+use super::geometry::*;
+use super::{write_geometry, write_header, ShapefileHeader};
+use byteorder::{BigEndian, LittleEndian, WriteBytesExt};
+use std::fs;
+use std::fs::File;
+use std::io::prelude::*;
+use std::io::{BufWriter, Error, ErrorKind, SeekFrom};
+use std::path::{Path, PathBuf};
+
+/// A writer of the features of a Shapefile, i.e. its geometry records and their attribute
+/// records, which are appended to the files one at a time. The files are complete once the
+/// writer is closed; dropping the writer also closes it, but ignores any error in doing so.
+///
+/// Example:
+///
+/// ```no_run
+/// use whitebox_vector::{
+///     AttributeField, FieldData, FieldDataType, ShapeType, ShapefileReader, ShapefileWriter,
+/// };
+///
+/// # fn main() -> std::io::Result<()> {
+/// let mut reader = ShapefileReader::open("input.shp")?;
+/// let mut writer = ShapefileWriter::create("output.shp", ShapeType::PolyLine)?;
+/// writer.projection = reader.projection.clone();
+/// writer.add_field(&AttributeField::new("FID", FieldDataType::Int, 10u8, 0u8))?;
+/// for i in 0..reader.num_records {
+///     let geometry = reader.read_record(i)?;
+///     writer.write_feature(&geometry, &[FieldData::Int(i as i32 + 1)])?;
+/// }
+/// writer.close()?;
+/// # Ok(())
+/// # }
+/// ```
+pub struct ShapefileWriter {
+    pub file_name: String,
+    /// The WKT of the coordinate reference system, which is written to the .prj file.
+    pub projection: String,
+    /// The code page of the attribute table's text, which cannot change once the first
+    /// feature has been written.
+    pub code_page: CodePage,
+    header: ShapefileHeader,
+    num_records: usize,
+    fields: Vec<AttributeField>,
+    shp: BufWriter<File>,
+    /// The length of the .shp file, in bytes.
+    shp_length: u64,
+    shx: BufWriter<File>,
+    dbf_file: PathBuf,
+    /// The .dbf file, which is created when the first feature is written, once the fields
+    /// are known.
+    dbf: Option<BufWriter<File>>,
+    dbf_header: AttributeHeader,
+    memo_writer: Option<MemoWriter>,
+    is_closed: bool,
+}
+
+impl ShapefileWriter {
+    /// Creates a Shapefile of a ShapeType, with an empty attribute table.
+    pub fn create(file_name: &str, shape_type: ShapeType) -> Result<ShapefileWriter, Error> {
+        let path = Path::new(file_name);
+        if !path
+            .extension()
+            .map_or(false, |e| e.to_string_lossy().to_lowercase() == "shp")
+        {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                format!("{} is not the name of a Shapefile (.shp).", file_name),
+            ));
+        }
+
+        let mut header = ShapefileHeader {
+            file_code: 9994,
+            version: 1000,
+            shape_type,
+            ..Default::default()
+        };
+        header.reset_extent();
+
+        // The headers are rewritten when the writer is closed.
+        let mut shp = BufWriter::new(File::create(path)?);
+        write_header(&mut shp, &ShapefileHeader::default(), 50)?;
+        let mut shx = BufWriter::new(File::create(path.with_extension("shx"))?);
+        write_header(&mut shx, &ShapefileHeader::default(), 50)?;
+
+        Ok(ShapefileWriter {
+            file_name: file_name.to_string(),
+            projection: String::new(),
+            code_page: CodePage::default(),
+            header,
+            num_records: 0,
+            fields: vec![],
+            shp,
+            shp_length: 100,
+            shx,
+            dbf_file: path.with_extension("dbf"),
+            dbf: None,
+            dbf_header: AttributeHeader::default(),
+            memo_writer: None,
+            is_closed: false,
+        })
+    }
+
+    pub fn get_shape_type(&self) -> ShapeType {
+        self.header.shape_type
+    }
+
+    /// Returns the number of features written so far.
+    pub fn get_num_records(&self) -> usize {
+        self.num_records
+    }
+
+    /// Returns the fields of the attribute table.
+    pub fn get_fields(&self) -> &Vec<AttributeField> {
+        &self.fields
+    }
+
+    /// Adds a field to the attribute table, which must be done before the first feature is
+    /// written. Text that is wider than its field is truncated when it is written, and
+    /// numbers that are wider than their field are written as null.
+    pub fn add_field(&mut self, field: &AttributeField) -> Result<(), Error> {
+        if self.dbf.is_some() {
+            return Err(Error::new(
+                ErrorKind::Other,
+                "Fields cannot be added once features have been written.",
+            ));
+        }
+        self.fields.push(field.clone());
+        Ok(())
+    }
+
+    pub fn add_fields(&mut self, fields: &[AttributeField]) -> Result<(), Error> {
+        for field in fields {
+            self.add_field(field)?;
+        }
+        Ok(())
+    }
+
+    /// Appends a feature, i.e. a geometry record of the Shapefile's ShapeType, or a null
+    /// record, and an attribute record with a value for each field.
+    pub fn write_feature(
+        &mut self,
+        geometry: &ShapefileGeometry,
+        attributes: &[FieldData],
+    ) -> Result<(), Error> {
+        if self.is_closed {
+            return Err(Error::new(ErrorKind::Other, "The writer has been closed."));
+        }
+        // Null records may appear in a Shapefile of any ShapeType.
+        if geometry.shape_type != self.header.shape_type && geometry.shape_type != ShapeType::Null
+        {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                "Attempt to add a ShapefileGeometry record of the wrong ShapeType.",
+            ));
+        }
+        if attributes.len() != self.fields.len() {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                format!(
+                    "The attribute record has {} values but there are {} fields.",
+                    attributes.len(),
+                    self.fields.len()
+                ),
+            ));
+        }
+        let length = geometry.get_length();
+        // The file length is held in 16-bit words, as an i32.
+        if (self.shp_length + 8 + length as u64) / 2 > i32::MAX as u64 {
+            return Err(Error::new(
+                ErrorKind::Other,
+                "The Shapefile has reached the maximum size of a .shp file.",
+            ));
+        }
+        self.start_dbf()?;
+
+        self.num_records += 1;
+        write_geometry(
+            &mut self.shp,
+            geometry,
+            self.num_records as i32,
+            self.header.shape_type,
+        )?;
+        self.shx.write_i32::<BigEndian>((self.shp_length / 2) as i32)?; // offset in 16-bit words
+        self.shx.write_i32::<BigEndian>(length / 2)?; // content length in 16-bit words
+        self.shp_length += 8 + length as u64;
+        self.header.extend_extent(geometry);
+
+        if let Some(dbf) = self.dbf.as_mut() {
+            write_dbf_record(
+                dbf,
+                &self.fields,
+                attributes,
+                false,
+                self.code_page,
+                self.memo_writer.as_mut(),
+            )?;
+        }
+        Ok(())
+    }
+
+    /// Finishes the files, i.e. patches the headers and writes the .prj and .cpg files.
+    pub fn close(mut self) -> Result<(), Error> {
+        self.finish()
+    }
+
+    /// Creates the .dbf file and writes its header, unless that has been done.
+    fn start_dbf(&mut self) -> Result<(), Error> {
+        if self.dbf.is_none() {
+            let mut dbf = BufWriter::new(File::create(&self.dbf_file)?);
+            write_dbf_header(&mut dbf, &mut self.dbf_header, &self.fields, self.code_page)?;
+            if self.fields.iter().any(|field| field.field_type == 'M') {
+                self.memo_writer = Some(MemoWriter::create(&self.dbf_file)?);
+            }
+            self.dbf = Some(dbf);
+        }
+        Ok(())
+    }
+
+    fn finish(&mut self) -> Result<(), Error> {
+        if self.is_closed {
+            return Ok(());
+        }
+        self.is_closed = true;
+        self.start_dbf()?;
+        self.header.finish_extent();
+
+        // Patch the headers of the .shp and .shx files with their lengths and the extent.
+        self.shp.seek(SeekFrom::Start(0))?;
+        write_header(&mut self.shp, &self.header, (self.shp_length / 2) as i32)?;
+        self.shp.flush()?;
+        self.shx.seek(SeekFrom::Start(0))?;
+        write_header(&mut self.shx, &self.header, (100 + 8 * self.num_records) as i32 / 2)?;
+        self.shx.flush()?;
+
+        if let Some(mut dbf) = self.dbf.take() {
+            dbf.write_u8(0x1A)?; // file terminator byte
+            dbf.seek(SeekFrom::Start(4))?;
+            dbf.write_u32::<LittleEndian>(self.num_records as u32)?; // number of records
+            dbf.flush()?;
+        }
+        if let Some(memo_writer) = self.memo_writer.take() {
+            memo_writer.finish()?;
+        }
+        write_cpg_file(&self.dbf_file, self.code_page)?;
+
+        if !self.projection.is_empty() {
+            fs::write(Path::new(&self.file_name).with_extension("prj"), &self.projection)?;
+        }
+        Ok(())
+    }
+}
+
+impl Drop for ShapefileWriter {
+    fn drop(&mut self) {
+        let _ = self.finish();
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::test_utils::*;
+    use crate::*;
+    use std::fs;
+    use std::path::Path;
+
+    fn remove_files(file_name: &str) {
+        for extension in ["shp", "shx", "dbf", "prj", "cpg"] {
+            fs::remove_file(Path::new(file_name).with_extension(extension)).unwrap();
+        }
+    }
+
+    #[test]
+    fn streamed_files_match_written_files() {
+        let (written_file, streamed_file) = (temp_file("written.shp"), temp_file("streamed.shp"));
+        for shape_type in [
+            ShapeType::Point,
+            ShapeType::MultiPoint,
+            ShapeType::PolyLine,
+            ShapeType::Polygon,
+        ] {
+            let mut output = sample_file(&written_file, shape_type);
+            output.write().unwrap();
+
+            let mut writer = ShapefileWriter::create(&streamed_file, shape_type).unwrap();
+            writer.projection = output.projection.clone();
+            writer.add_fields(output.attributes.get_fields()).unwrap();
+            for i in 0..output.num_records {
+                let rec = output.attributes.get_record(i);
+                writer.write_feature(output.get_record(i), &rec).unwrap();
+            }
+            assert_eq!(writer.get_num_records(), output.num_records);
+            writer.close().unwrap();
+
+            // The patched headers of the .shp and .shx files are those that Shapefile::write
+            // gives, and so are the records.
+            for extension in ["shp", "shx", "prj", "cpg"] {
+                let written = fs::read(Path::new(&written_file).with_extension(extension));
+                let streamed = fs::read(Path::new(&streamed_file).with_extension(extension));
+                assert_eq!(written.unwrap(), streamed.unwrap());
+            }
+            let input = Shapefile::read(&streamed_file).unwrap();
+            assert_same_features(&Shapefile::read(&written_file).unwrap(), &input);
+            remove_files(&written_file);
+            remove_files(&streamed_file);
+        }
+    }
+
+    #[test]
+    fn writer_rejects_invalid_features() {
+        let file_name = temp_file("invalid.shp");
+        assert!(ShapefileWriter::create(&temp_file("invalid.dbf"), ShapeType::Point).is_err());
+        let mut writer = ShapefileWriter::create(&file_name, ShapeType::PolyLine).unwrap();
+        writer.add_field(&AttributeField::new("ID", FieldDataType::Int, 6u8, 0u8)).unwrap();
+        let line = geometry(ShapeType::PolyLine, 0);
+        let point = geometry(ShapeType::Point, 0);
+        assert!(writer.write_feature(&point, &[FieldData::Int(1)]).is_err());
+        assert!(writer.write_feature(&line, &[]).is_err());
+        // Null records may be written to a file of any ShapeType.
+        writer.write_feature(&ShapefileGeometry::default(), &[FieldData::Int(1)]).unwrap();
+        writer.write_feature(&line, &[FieldData::Int(2)]).unwrap();
+        assert!(writer
+            .add_field(&AttributeField::new("NAME", FieldDataType::Text, 6u8, 0u8))
+            .is_err());
+        // Dropping the writer closes it.
+        drop(writer);
+
+        let input = Shapefile::read(&file_name).unwrap();
+        assert_eq!(input.num_records, 2);
+        assert_eq!(input.records[0].shape_type, ShapeType::Null);
+        assert_eq!(input.records[1].points, line.points);
+        assert_eq!(input.attributes.get_record(1), vec![FieldData::Int(2)]);
+        // The extent is that of the line alone.
+        assert_eq!((input.header.x_min, input.header.x_max), (0.0, 10.0));
+        assert_eq!((input.header.y_min, input.header.y_max), (0.0, 8.0));
+        for extension in ["shp", "shx", "dbf", "cpg"] {
+            fs::remove_file(temp_file(&format!("invalid.{}", extension))).unwrap();
+        }
+    }
+
+    #[test]
+    fn over_wide_numbers_are_written_as_null() {
+        let (streamed_file, written_file) = (temp_file("wide.shp"), temp_file("fitted.shp"));
+        let fields = [
+            AttributeField::new("COUNT", FieldDataType::Int, 4u8, 0u8),
+            AttributeField::new("AREA", FieldDataType::Real, 6u8, 2u8),
+        ];
+        let records = [
+            vec![FieldData::Int(123456), FieldData::Real(1234.5)],
+            vec![FieldData::BigInt(10_000_000_000), FieldData::Real(-99.999)],
+            vec![FieldData::Int(-999), FieldData::Real(12.25)],
+        ];
+        let point = geometry(ShapeType::Point, 0);
+        let mut writer = ShapefileWriter::create(&streamed_file, ShapeType::Point).unwrap();
+        writer.add_fields(&fields).unwrap();
+        let mut output = Shapefile::new(&written_file, ShapeType::Point).unwrap();
+        output.attributes.add_field(&fields[0]);
+        output.attributes.add_field(&fields[1]);
+        for rec in &records {
+            writer.write_feature(&point, rec).unwrap();
+            output.add_record(point.clone());
+            output.attributes.add_record(rec.clone(), false);
+        }
+        writer.close().unwrap();
+        output.write().unwrap();
+
+        // The streamed fields keep their widths, so the numbers that don't fit are null
+        // rather than missing their leading digits.
+        let input = Shapefile::read(&streamed_file).unwrap();
+        assert_eq!(input.attributes.get_record(0), vec![FieldData::Null, FieldData::Null]);
+        assert_eq!(input.attributes.get_record(1), vec![FieldData::Null, FieldData::Real(-99.99)]);
+        assert_eq!(input.attributes.get_record(2), records[2]);
+
+        // Shapefile::write widens the fields to fit the numbers.
+        let input = Shapefile::read(&written_file).unwrap();
+        assert_eq!(input.attributes.get_record(0), records[0]);
+        assert_eq!(input.attributes.get_record(1)[0], records[1][0]);
+        assert_eq!(input.attributes.get_record(2), records[2]);
+        for extension in ["shp", "shx", "dbf", "cpg"] {
+            fs::remove_file(temp_file(&format!("wide.{}", extension))).unwrap();
+            fs::remove_file(temp_file(&format!("fitted.{}", extension))).unwrap();
+        }
+    }
+}
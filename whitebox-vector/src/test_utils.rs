@@ -0,0 +1,106 @@
+// Data shared by the unit tests of the vector formats.
+
+use crate::*;
+use whitebox_common::structures::Point2D;
+
+/// Returns the name of a file in the temporary directory that is unique to this process.
+pub fn temp_file(name: &str) -> String {
+    std::env::temp_dir()
+        .join(format!("wbt_{}_{}", std::process::id(), name))
+        .to_string_lossy()
+        .to_string()
+}
+
+pub fn points(coords: &[(f64, f64)]) -> Vec<Point2D> {
+    coords.iter().map(|&(x, y)| Point2D::new(x, y)).collect()
+}
+
+/// A polygon with a clockwise exterior ring and a counter-clockwise hole.
+pub fn polygon_with_hole() -> ShapefileGeometry {
+    let mut sfg = ShapefileGeometry::new(ShapeType::Polygon);
+    sfg.add_part(&points(&[
+        (0.0, 0.0),
+        (0.0, 10.0),
+        (10.0, 10.0),
+        (10.0, 0.0),
+        (0.0, 0.0),
+    ]));
+    sfg.add_part(&points(&[(2.0, 2.0), (4.0, 2.0), (4.0, 4.0), (2.0, 4.0), (2.0, 2.0)]));
+    sfg
+}
+
+/// Returns one geometry of each class of a ShapeType; `i` shifts the coordinates, so
+/// that the geometries of different records differ.
+pub fn geometry(shape_type: ShapeType, i: usize) -> ShapefileGeometry {
+    let d = i as f64 * 20.0;
+    let mut sfg = ShapefileGeometry::new(shape_type);
+    match shape_type {
+        ShapeType::Point => sfg.add_point(Point2D::new(d + 1.0, 2.0)),
+        ShapeType::MultiPoint => {
+            sfg.add_point(Point2D::new(d + 1.0, 2.0));
+            sfg.add_point(Point2D::new(d + 3.0, 4.0));
+        }
+        ShapeType::PolyLine => {
+            sfg.add_part(&points(&[(d, 0.0), (d + 5.0, 5.0), (d + 10.0, 0.0)]));
+            sfg.add_part(&points(&[(d, 8.0), (d + 10.0, 8.0)]));
+        }
+        ShapeType::Polygon => {
+            for p in polygon_with_hole().get_parts() {
+                let p = p.iter().map(|p| Point2D::new(p.x + d, p.y)).collect::<Vec<Point2D>>();
+                sfg.add_part(&p);
+            }
+        }
+        _ => panic!("Unsupported ShapeType"),
+    }
+    sfg
+}
+
+/// Returns a file of three records of a ShapeType, with integer, big integer, real, text,
+/// and boolean attributes; the third record's attributes are null.
+pub fn sample_file(file_name: &str, shape_type: ShapeType) -> Shapefile {
+    let mut sf = Shapefile::new(file_name, shape_type).unwrap();
+    sf.projection = whitebox_common::spatial_ref_system::esri_wkt_from_epsg(4326);
+    sf.attributes.add_field(&AttributeField::new("ID", FieldDataType::Int, 6u8, 0u8));
+    sf.attributes.add_field(&AttributeField::new("BIG", FieldDataType::Int, 14u8, 0u8));
+    sf.attributes.add_field(&AttributeField::new("VALUE", FieldDataType::Real, 10u8, 3u8));
+    sf.attributes.add_field(&AttributeField::new("NAME", FieldDataType::Text, 12u8, 0u8));
+    sf.attributes.add_field(&AttributeField::new("FLAG", FieldDataType::Bool, 1u8, 0u8));
+    for i in 0..3 {
+        sf.add_record(geometry(shape_type, i));
+        let rec = if i < 2 {
+            vec![
+                FieldData::Int(i as i32 + 1),
+                FieldData::BigInt(10_000_000_000 + i as i64),
+                FieldData::Real(1.25 * i as f64),
+                FieldData::Text(format!("feature {}", i)),
+                FieldData::Bool(i == 0),
+            ]
+        } else {
+            vec![FieldData::Null; 5]
+        };
+        sf.attributes.add_record(rec, false);
+    }
+    sf
+}
+
+/// Asserts that two files have the same geometries and attributes.
+pub fn assert_same_features(a: &Shapefile, b: &Shapefile) {
+    assert_eq!(a.header.shape_type, b.header.shape_type);
+    assert_eq!(a.num_records, b.num_records);
+    for i in 0..a.num_records {
+        assert_eq!(a.records[i].shape_type, b.records[i].shape_type);
+        assert_eq!(a.records[i].parts, b.records[i].parts);
+        assert_eq!(a.records[i].points, b.records[i].points);
+    }
+    let names = |sf: &Shapefile| {
+        sf.attributes
+            .get_fields()
+            .iter()
+            .map(|f| f.name.clone())
+            .collect::<Vec<String>>()
+    };
+    assert_eq!(names(a), names(b));
+    for i in 0..a.num_records {
+        assert_eq!(a.attributes.get_record(i), b.attributes.get_record(i));
+    }
+}
@@ -0,0 +1,204 @@
+/*
+This code is part of the WhiteboxTools geospatial analysis library.
+License: MIT
+
+Notes: The vector formats are backends of a common in-memory layer, which is the Shapefile
+struct: each format reads a file into a Shapefile and writes a Shapefile to a file. The
+format of a file is determined from its extension by `VectorFormat`, which is the one place
+where the formats are listed, so that a new format is supported by all of the tools once
+it has been added there.
+*/
+
+use crate::flatgeobuf::{is_flatgeobuf_file, read_flatgeobuf, write_flatgeobuf};
+use crate::geojson::{is_geojson_file, read_geojson, write_geojson};
+use crate::geopackage::{is_geopackage_file, read_geopackage, split_geopackage_file_name, write_geopackage};
+use crate::shapefile::Shapefile;
+use std::io::Error;
+use std::path::Path;
+
+/// The supported vector file formats.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum VectorFormat {
+    Shapefile,
+    GeoJson,
+    GeoPackage,
+    FlatGeobuf,
+}
+
+impl VectorFormat {
+    /// Returns the format of a file from the extension of its name, or None if it is not
+    /// the extension of a supported vector format. GeoPackage file names may name a layer,
+    /// e.g. `roads.gpkg:highways`.
+    pub fn from_file_name(file_name: &str) -> Option<VectorFormat> {
+        if is_geopackage_file(file_name) {
+            Some(VectorFormat::GeoPackage)
+        } else if is_geojson_file(file_name) {
+            Some(VectorFormat::GeoJson)
+        } else if is_flatgeobuf_file(file_name) {
+            Some(VectorFormat::FlatGeobuf)
+        } else if file_name.to_ascii_lowercase().ends_with(".shp") {
+            Some(VectorFormat::Shapefile)
+        } else {
+            None
+        }
+    }
+
+    /// Returns the name of the format.
+    pub fn get_name(&self) -> &'static str {
+        match self {
+            VectorFormat::Shapefile => "Shapefile",
+            VectorFormat::GeoJson => "GeoJSON",
+            VectorFormat::GeoPackage => "GeoPackage",
+            VectorFormat::FlatGeobuf => "FlatGeobuf",
+        }
+    }
+
+    /// Returns the file extensions of the format, the usual one first.
+    pub fn get_extensions(&self) -> &'static [&'static str] {
+        match self {
+            VectorFormat::Shapefile => &["shp"],
+            VectorFormat::GeoJson => &["geojson", "json"],
+            VectorFormat::GeoPackage => &["gpkg"],
+            VectorFormat::FlatGeobuf => &["fgb"],
+        }
+    }
+
+    /// Reads the file named by `sf.file_name` into the Shapefile.
+    pub(crate) fn read(&self, sf: &mut Shapefile) -> Result<(), Error> {
+        match self {
+            VectorFormat::Shapefile => return sf.read_file(),
+            VectorFormat::GeoJson => read_geojson(sf)?,
+            VectorFormat::GeoPackage => read_geopackage(sf)?,
+            VectorFormat::FlatGeobuf => read_flatgeobuf(sf)?,
+        }
+        sf.calculate_extent();
+        Ok(())
+    }
+
+    /// Writes the Shapefile to the file named by `sf.file_name`.
+    pub(crate) fn write(&self, sf: &mut Shapefile) -> Result<(), Error> {
+        match self {
+            VectorFormat::Shapefile => sf.write_file(),
+            VectorFormat::GeoJson => write_geojson(sf),
+            VectorFormat::GeoPackage => write_geopackage(sf),
+            VectorFormat::FlatGeobuf => write_flatgeobuf(sf),
+        }
+    }
+}
+
+/// Returns true if a file name has the extension of a supported vector format. Tools that
+/// accept either a raster or a vector file use this to tell which they were given.
+pub fn is_vector_file(file_name: &str) -> bool {
+    VectorFormat::from_file_name(file_name).is_some()
+}
+
+/// Returns the name of an additional output file, in the same format as a vector file, by
+/// adding a suffix to the file's stem, e.g. `streams.fgb` becomes `streams_nodes.fgb`. For
+/// GeoPackages, the suffix is added to the layer name instead, giving another layer of the
+/// same file, e.g. `hydro.gpkg:streams_nodes`.
+pub fn derived_file_name(file_name: &str, suffix: &str) -> String {
+    if is_geopackage_file(file_name) {
+        let (gpkg_file, layer) = split_geopackage_file_name(file_name);
+        let layer = match layer {
+            Some(layer) => layer.to_string(),
+            None => Path::new(gpkg_file)
+                .file_stem()
+                .map_or("layer".to_string(), |s| s.to_string_lossy().to_string()),
+        };
+        return format!("{}:{}{}", gpkg_file, layer, suffix);
+    }
+    let path = Path::new(file_name);
+    match (path.file_stem(), path.extension()) {
+        (Some(stem), Some(extension)) => path
+            .with_file_name(format!(
+                "{}{}.{}",
+                stem.to_string_lossy(),
+                suffix,
+                extension.to_string_lossy()
+            ))
+            .to_string_lossy()
+            .to_string(),
+        _ => format!("{}{}", file_name, suffix),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::test_utils::*;
+    use crate::*;
+    use std::fs;
+
+    #[test]
+    fn formats_from_file_names() {
+        for format in [
+            VectorFormat::Shapefile,
+            VectorFormat::GeoJson,
+            VectorFormat::GeoPackage,
+            VectorFormat::FlatGeobuf,
+        ] {
+            for extension in format.get_extensions() {
+                let file_name = format!("/data/roads.{}", extension.to_uppercase());
+                assert_eq!(VectorFormat::from_file_name(&file_name), Some(format));
+            }
+        }
+        assert_eq!(
+            VectorFormat::from_file_name("hydro.gpkg:streams"),
+            Some(VectorFormat::GeoPackage)
+        );
+        assert_eq!(VectorFormat::from_file_name("dem.tif"), None);
+        assert!(is_vector_file("roads.geojson"));
+        assert!(!is_vector_file("roads.dbf"));
+
+        assert_eq!(derived_file_name("/data/streams.fgb", "_nodes"), "/data/streams_nodes.fgb");
+        assert_eq!(derived_file_name("streams", "_nodes"), "streams_nodes");
+        assert_eq!(derived_file_name("hydro.gpkg:streams", "_nodes"), "hydro.gpkg:streams_nodes");
+        assert_eq!(derived_file_name("hydro.gpkg", "_nodes"), "hydro.gpkg:hydro_nodes");
+    }
+
+    #[test]
+    fn files_round_trip_in_every_format() {
+        let input = sample_file(&temp_file("layer.shp"), ShapeType::MultiPoint);
+        for name in ["layer.shp", "layer.geojson", "layer.gpkg:points", "layer.fgb"] {
+            let file_name = temp_file(name);
+            let mut output = Shapefile::initialize_using_file(
+                &file_name,
+                &input,
+                input.header.shape_type,
+                true,
+            )
+            .unwrap();
+            for i in 0..input.num_records {
+                output.add_record(input.get_record(i).clone());
+                output.attributes.add_record(input.attributes.get_record(i), false);
+            }
+            output.write().unwrap();
+
+            let copy = Shapefile::read(&file_name).unwrap();
+            assert_eq!(copy.header.shape_type, ShapeType::MultiPoint);
+            assert_eq!(copy.num_records, input.num_records);
+            let names = |sf: &Shapefile| {
+                sf.attributes.get_fields().iter().map(|f| f.name.clone()).collect::<Vec<String>>()
+            };
+            assert_eq!(names(&copy), names(&input));
+            // FlatGeobuf features are in Hilbert order, so records are matched by geometry.
+            for i in 0..input.num_records {
+                let j = (0..copy.num_records)
+                    .find(|&j| copy.get_record(j).points == input.get_record(i).points)
+                    .expect("The record is missing.");
+                assert_eq!(copy.attributes.get_record(j), input.attributes.get_record(i));
+            }
+            assert!(copy.get_coordinate_reference_system().unwrap().is_geographic());
+
+            let files: Vec<String> = match name {
+                "layer.shp" => ["shp", "shx", "dbf", "prj", "cpg"]
+                    .iter()
+                    .map(|extension| format!("layer.{}", extension))
+                    .collect(),
+                _ => vec![name.split(':').next().unwrap().to_string()],
+            };
+            for file in files {
+                fs::remove_file(temp_file(&file)).unwrap();
+            }
+        }
+    }
+}
@@ -1212,6 +1212,32 @@ Okay, that's it for now.
         if base is not None: args.append("--base='{}'".format(base))
         return self.run_tool('block_minimum_gridding', args, callback) # returns 1 if error
 
+    def buffer_vector(self, i, output, dist=10.0, field=None, cap="round", join="round", dissolve=False, snap=0.0, callback=None):
+        """Creates polygons that enclose the area within a distance of vector points, lines or polygons.
+
+        Keyword arguments:
+
+        i -- Input vector file. 
+        output -- Output vector polygon file. 
+        dist -- Buffer distance; negative distances shrink polygons. 
+        field -- Optional numeric field containing the buffer distance of each feature, used in place of the distance parameter. 
+        cap -- Shape of the ends of buffered lines, 'round' (default), 'flat' or 'square'. 
+        join -- Shape of the outside corners of buffers, 'round' (default) or 'mitre'. 
+        dissolve -- Optional flag to request the output polygons be dissolved. 
+        snap -- Snap tolerance. 
+        callback -- Custom function for handling tool text outputs.
+        """
+        args = []
+        args.append("--input='{}'".format(i))
+        args.append("--output='{}'".format(output))
+        args.append("--dist={}".format(dist))
+        if field is not None: args.append("--field='{}'".format(field))
+        args.append("--cap={}".format(cap))
+        args.append("--join={}".format(join))
+        if dissolve: args.append("--dissolve")
+        args.append("--snap={}".format(snap))
+        return self.run_tool('buffer_vector', args, callback) # returns 1 if error
+
     def centroid(self, i, output, text_output=False, callback=None):
         """Calculates the centroid, or average location, of raster polygon objects.
 
@@ -2650,6 +2676,52 @@ Okay, that's it for now.
         args.append("--input='{}'".format(i))
         return self.run_tool('related_circumscribing_circle', args, callback) # returns 1 if error
 
+    def reproject_raster(self, i, output, epsg=None, crs_file=None, base=None, cell_size=None, extent=None, method="nn", callback=None):
+        """Transforms a raster into another coordinate reference system.
+
+        Keyword arguments:
+
+        i -- Input raster file. 
+        output -- Output raster file. 
+        epsg -- EPSG code of the target coordinate reference system (e.g. 32617). 
+        crs_file -- File containing the WKT of the target coordinate reference system, e.g. a .prj file. Not used when an EPSG code is specified. 
+        base -- Optional base raster, whose grid, and unless otherwise specified, coordinate reference system the output takes. 
+        cell_size -- Optional cell size of the output raster, in the units of the target system. Not used when a base raster is specified. 
+        extent -- Optional extent of the output raster in the target system, as 'xmin,ymin,xmax,ymax'. Not used when a base raster is specified. 
+        method -- Resampling method; options include 'nn' (nearest neighbour), 'bilinear', 'cc' (cubic convolution), and 'mode' (majority, for categorical data). 
+        callback -- Custom function for handling tool text outputs.
+        """
+        args = []
+        args.append("--input='{}'".format(i))
+        args.append("--output='{}'".format(output))
+        if epsg is not None: args.append("--epsg='{}'".format(epsg))
+        if crs_file is not None: args.append("--crs_file='{}'".format(crs_file))
+        if base is not None: args.append("--base='{}'".format(base))
+        if cell_size is not None: args.append("--cell_size='{}'".format(cell_size))
+        if extent is not None: args.append("--extent='{}'".format(extent))
+        args.append("--method={}".format(method))
+        return self.run_tool('reproject_raster', args, callback) # returns 1 if error
+
+    def reproject_vector(self, i, output, epsg=None, crs_file=None, densify=None, callback=None):
+        """Transforms a vector file into another coordinate reference system.
+
+        Keyword arguments:
+
+        i -- Input vector file. 
+        output -- Output vector file. 
+        epsg -- EPSG code of the target coordinate reference system (e.g. 32617). 
+        crs_file -- File containing the WKT of the target coordinate reference system, e.g. a .prj file. Not used when an EPSG code is specified. 
+        densify -- Optional maximum distance between the vertices of line and polygon edges, in the units of the input, to which edges are densified before they are transformed. 
+        callback -- Custom function for handling tool text outputs.
+        """
+        args = []
+        args.append("--input='{}'".format(i))
+        args.append("--output='{}'".format(output))
+        if epsg is not None: args.append("--epsg='{}'".format(epsg))
+        if crs_file is not None: args.append("--crs_file='{}'".format(crs_file))
+        if densify is not None: args.append("--densify='{}'".format(densify))
+        return self.run_tool('reproject_vector', args, callback) # returns 1 if error
+
     def shape_complexity_index(self, i, callback=None):
         """Calculates overall polygon shape complexity or irregularity.
 
@@ -2676,6 +2748,42 @@ Okay, that's it for now.
         args.append("--output='{}'".format(output))
         return self.run_tool('shape_complexity_index_raster', args, callback) # returns 1 if error
 
+    def transform_raster_heights(self, i, geoid, output, to_ellipsoid=False, callback=None):
+        """Transforms raster heights between the ellipsoid and a geoid model.
+
+        Keyword arguments:
+
+        i -- Input raster file. 
+        geoid -- Input geoid grid file, in GTX (.gtx) or NGS (.bin) format. 
+        output -- Output raster file. 
+        to_ellipsoid -- Convert orthometric heights to ellipsoidal heights, rather than ellipsoidal heights to orthometric heights?. 
+        callback -- Custom function for handling tool text outputs.
+        """
+        args = []
+        args.append("--input='{}'".format(i))
+        args.append("--geoid='{}'".format(geoid))
+        args.append("--output='{}'".format(output))
+        if to_ellipsoid: args.append("--to_ellipsoid")
+        return self.run_tool('transform_raster_heights', args, callback) # returns 1 if error
+
+    def transform_vector_heights(self, i, geoid, output, to_ellipsoid=False, callback=None):
+        """Transforms the z values of a vector file between the ellipsoid and a geoid model.
+
+        Keyword arguments:
+
+        i -- Input vector file. 
+        geoid -- Input geoid grid file, in GTX (.gtx) or NGS (.bin) format. 
+        output -- Output vector file. 
+        to_ellipsoid -- Convert orthometric heights to ellipsoidal heights, rather than ellipsoidal heights to orthometric heights?. 
+        callback -- Custom function for handling tool text outputs.
+        """
+        args = []
+        args.append("--input='{}'".format(i))
+        args.append("--geoid='{}'".format(geoid))
+        args.append("--output='{}'".format(output))
+        if to_ellipsoid: args.append("--to_ellipsoid")
+        return self.run_tool('transform_vector_heights', args, callback) # returns 1 if error
+
     ############################
     # Geomorphometric Analysis #
     ############################
@@ -5765,7 +5873,7 @@ Okay, that's it for now.
         output -- Output raster file. 
         cell_size -- Optionally specified cell size of output raster. Not used when base raster is specified. 
         base -- Optionally specified input base raster file. Not used when a cell size is specified. 
-        method -- Resampling method; options include 'nn' (nearest neighbour), 'bilinear', and 'cc' (cubic convolution). 
+        method -- Resampling method; options include 'nn' (nearest neighbour), 'bilinear', 'cc' (cubic convolution), and 'mode' (majority, for categorical data). 
         callback -- Custom function for handling tool text outputs.
         """
         args = []
@@ -7958,6 +8066,24 @@ Okay, that's it for now.
         args.append("--radius={}".format(radius))
         return self.run_tool('normal_vectors', args, callback) # returns 1 if error
 
+    def reproject_lidar(self, i, output, epsg=None, crs_file=None, callback=None):
+        """Transforms a LiDAR point cloud into another coordinate reference system.
+
+        Keyword arguments:
+
+        i -- Input LiDAR file. 
+        output -- Output LiDAR file. 
+        epsg -- EPSG code of the target coordinate reference system (e.g. 32617). 
+        crs_file -- File containing the WKT of the target coordinate reference system, e.g. a .prj file. Not used when an EPSG code is specified. 
+        callback -- Custom function for handling tool text outputs.
+        """
+        args = []
+        args.append("--input='{}'".format(i))
+        args.append("--output='{}'".format(output))
+        if epsg is not None: args.append("--epsg='{}'".format(epsg))
+        if crs_file is not None: args.append("--crs_file='{}'".format(crs_file))
+        return self.run_tool('reproject_lidar', args, callback) # returns 1 if error
+
     def recover_flightline_info(self, i, output, max_time_diff=5.0, pt_src_id=False, user_data=False, rgb=False, callback=None):
         """This sorts the points in a LiDAR file by the GPS time.
 
@@ -7996,6 +8122,24 @@ Okay, that's it for now.
         args.append("--polygons='{}'".format(polygons))
         return self.run_tool('select_tiles_by_polygon', args, callback) # returns 1 if error
 
+    def transform_lidar_heights(self, i, geoid, output, to_ellipsoid=False, callback=None):
+        """Transforms LiDAR point heights between the ellipsoid and a geoid model.
+
+        Keyword arguments:
+
+        i -- Input LiDAR file. 
+        geoid -- Input geoid grid file, in GTX (.gtx) or NGS (.bin) format. 
+        output -- Output LiDAR file. 
+        to_ellipsoid -- Convert orthometric heights to ellipsoidal heights, rather than ellipsoidal heights to orthometric heights?. 
+        callback -- Custom function for handling tool text outputs.
+        """
+        args = []
+        args.append("--input='{}'".format(i))
+        args.append("--geoid='{}'".format(geoid))
+        args.append("--output='{}'".format(output))
+        if to_ellipsoid: args.append("--to_ellipsoid")
+        return self.run_tool('transform_lidar_heights', args, callback) # returns 1 if error
+
     def sort_lidar(self, i=None, output=None, criterion="time", callback=None):
         """Sorts LiDAR points based on their properties.
 